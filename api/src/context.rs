@@ -24,7 +24,11 @@ use aptos_types::{
     contract_event::EventWithVersion,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue,
+        table::TableHandle,
+    },
     transaction::{SignedTransaction, TransactionWithProof, Version},
 };
 use aptos_vm::data_cache::{IntoMoveResolver, StorageAdapter, StorageAdapterOwned};
@@ -80,6 +84,10 @@ impl Context {
         self.node_config.api.max_events_page_size
     }
 
+    pub fn max_table_items_page_size(&self) -> u16 {
+        self.node_config.api.max_table_items_page_size
+    }
+
     pub fn move_resolver(&self) -> Result<StorageAdapterOwned<DbStateView>> {
         self.db
             .latest_state_checkpoint_view()
@@ -119,6 +127,10 @@ impl Context {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
+    pub fn max_table_item_batch_size(&self) -> usize {
+        self.node_config.api.max_table_item_batch_size
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
@@ -229,6 +241,22 @@ impl Context {
             .get_state_values_by_key_prefix(&StateKeyPrefix::from(address), version)
     }
 
+    /// Retrieve a page of a table's items, in the order of their state keys
+    pub fn get_table_items_page(
+        &self,
+        handle: TableHandle,
+        cursor: Option<&StateKey>,
+        limit: usize,
+        version: u64,
+    ) -> Result<(Vec<(StateKey, StateValue)>, Option<StateKey>)> {
+        self.db.get_state_values_by_key_prefix_paged(
+            &StateKeyPrefix::from(handle),
+            cursor,
+            limit,
+            version,
+        )
+    }
+
     pub fn get_account_state<E: InternalError>(
         &self,
         address: AccountAddress,
@@ -533,6 +561,25 @@ impl Context {
         self.db.get_accumulator_root_hash(version)
     }
 
+    /// Root hash of the state after the transaction at `version`, only known at state checkpoints
+    pub fn get_state_checkpoint_hash(
+        &self,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<Option<HashValue>> {
+        let txn = self
+            .db
+            .get_transaction_by_version(version, ledger_version, false)?;
+        Ok(txn.proof.transaction_info.state_checkpoint_hash())
+    }
+
+    /// Proof of the value of `state_key`, or that it has none, against the state at `version`
+    pub fn get_state_proof(&self, state_key: &StateKey, version: u64) -> Result<SparseMerkleProof> {
+        self.db
+            .get_state_proof_by_version_ext(state_key, version)
+            .map(SparseMerkleProof::from)
+    }
+
     fn convert_into_transaction_on_chain_data(
         &self,
         txn: TransactionWithProof,
@@ -4,32 +4,38 @@
 use crate::response::{module_not_found, resource_not_found, table_item_not_found, StdApiError};
 use crate::{
     accept_type::AcceptType,
+    bcs_payload::Bcs,
     failpoint::fail_point_poem,
+    page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        AptosResponseContent, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
     },
     ApiTags, Context,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo, MoveModuleBytecode,
-    MoveResource, MoveStructTag, MoveValue, TableItemRequest, U64,
+    Address, AptosErrorCode, AsConverter, BatchTableItemRequest, BatchTableItemResponse,
+    HexEncodedBytes, IdentifierWrapper, LedgerInfo, MoveConverter, MoveModuleBytecode,
+    MoveResource, MoveStructTag, MoveValue, TableItemRequest, TableItemResult, TableIterItem,
+    TableIterRequest, TableIterResponse, U64,
 };
+use aptos_crypto::HashValue;
 use aptos_state_view::StateView;
 use aptos_types::{
     access_path::AccessPath,
     state_store::{state_key::StateKey, table::TableHandle},
 };
-use aptos_vm::data_cache::AsMoveResolver;
-use move_deps::move_core_types::language_storage::{ModuleId, ResourceKey, StructTag};
+use aptos_vm::data_cache::{AsMoveResolver, StorageAdapter};
+use move_deps::move_core_types::language_storage::{ModuleId, ResourceKey, StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     OpenApi,
 };
+use serde_json::Value;
 use std::{convert::TryInto, sync::Arc};
-use storage_interface::state_view::DbStateView;
+use storage_interface::{state_view::DbStateView, DbReader};
 
 /// API for retrieving individual state
 pub struct StateApi {
@@ -110,6 +116,28 @@ impl StateApi {
     /// Get a table item at a specific ledger version from the table identified by {table_handle}
     /// in the path and the "key" (TableItemRequest) provided in the request body.
     ///
+    /// The key is either given as JSON, and encoded as its "key_type", or given already BCS
+    /// serialized as "key_bytes", but not both.
+    ///
+    /// JSON keys are first rewritten in the canonical encoding of their "key_type", so that
+    /// equivalent keys, such as integers given as numbers or strings, or addresses in upper or
+    /// lower case, find the same item.
+    ///
+    /// With BCS output (an Accept header of application/x-bcs), the value is returned as the
+    /// raw BCS bytes stored in the table, without being converted to JSON by its "value_type",
+    /// so that it can be decoded without losing precision.
+    ///
+    /// If the node indexes tables, and so knows the table's value type, a "value_type" which
+    /// isn't it is rejected with a 400, rather than the value being decoded as the wrong type.
+    ///
+    /// With `with_proof`, the item's sparse merkle proof, in the same format as the proofs of
+    /// table items in a batch, is returned in the X-Aptos-State-Proof header, and the root hash
+    /// of the state at the ledger version it's against in the X-Aptos-State-Root-Hash header.
+    /// The value is returned as it is without them.  The state root hash is only known at state
+    /// checkpoints, so if the ledger version isn't one, the server responds with a 400.  Only
+    /// items in the table are proven, the proof that an item isn't in it is only returned by
+    /// the batch endpoint.
+    ///
     /// This is a POST endpoint because the "key" for requesting a specific
     /// table item (TableItemRequest) could be quite complex, as each of its
     /// fields could themselves be composed of other structs. This makes it
@@ -134,7 +162,11 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
-    ) -> BasicResultWith404<MoveValue> {
+        /// If set to true, include a proof of the item against the state root hash
+        ///
+        /// If not provided, no proof will be retrieved
+        with_proof: Query<Option<bool>>,
+    ) -> poem::Result<TableItemResponse, BasicErrorWith404> {
         fail_point_poem("endpoint_get_table_item")?;
         self.context
             .check_api_output_enabled("Get table item", &accept_type)?;
@@ -143,6 +175,102 @@ impl StateApi {
             table_handle.0,
             table_item_request.0,
             ledger_version.0,
+            with_proof.0.unwrap_or_default(),
+        )
+    }
+
+    /// Get table items
+    ///
+    /// Get several items at a specific ledger version from the table identified by {table_handle}
+    /// in the path, with a request (TableItemRequest) for each of them in the request body.
+    ///
+    /// The items are returned in the order they were requested.  Each is either the item's value,
+    /// or the error which retrieving it on its own would have returned, so that one bad key
+    /// doesn't fail the whole batch.  Items can only be returned as JSON.
+    ///
+    /// If more items are requested than the node's limit, the server responds with a 400.
+    ///
+    /// With `with_proof`, each item also has a sparse merkle proof against the root hash of the
+    /// state at the ledger version, which is returned alongside the items.  Items which aren't
+    /// in the table are proven not to be.  The state root hash is only known at state
+    /// checkpoints, so if the ledger version isn't one, the server responds with a 400.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/items",
+        method = "post",
+        operation_id = "get_table_items",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_items(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Requests for each of the items, detailing the key type, key, and value type
+        batch_table_item_request: Json<BatchTableItemRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// If set to true, include a proof of each item against the state root hash
+        ///
+        /// If not provided, no proofs will be retrieved
+        with_proof: Query<Option<bool>>,
+    ) -> BasicResultWith404<BatchTableItemResponse> {
+        fail_point_poem("endpoint_get_table_items")?;
+        self.context
+            .check_api_output_enabled("Get table items", &accept_type)?;
+        self.table_items(
+            &accept_type,
+            table_handle.0,
+            batch_table_item_request.0,
+            ledger_version.0,
+            with_proof.0.unwrap_or_default(),
+        )
+    }
+
+    /// Get table items page
+    ///
+    /// Get a page of the items of the table identified by {table_handle} in the path, at a
+    /// specific ledger version, from the cursor (TableIterRequest) in the request body.
+    ///
+    /// Items are returned as their BCS serialized keys and values, ordered by their raw state
+    /// keys, which isn't the Move ordering of the keys.  Every page but the last has the cursor
+    /// of the next.  Retrieve each page at the ledger version returned with the first, so that
+    /// together they're a consistent view of the table.  Items can only be returned as JSON.
+    ///
+    /// If the limit is over the node's maximum page size, the server responds with a 400.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/iter",
+        method = "post",
+        operation_id = "get_table_items_page",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_items_page(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Request detailing the cursor and size of the page
+        table_iter_request: Json<TableIterRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<TableIterResponse> {
+        fail_point_poem("endpoint_get_table_items_page")?;
+        self.context
+            .check_api_output_enabled("Get table items page", &accept_type)?;
+        self.table_items_page(
+            &accept_type,
+            table_handle.0,
+            table_iter_request.0,
+            ledger_version.0,
         )
     }
 }
@@ -283,86 +411,495 @@ impl StateApi {
         table_handle: Address,
         table_item_request: TableItemRequest,
         ledger_version: Option<U64>,
-    ) -> BasicResultWith404<MoveValue> {
-        // Parse the key and value types for the table
-        let key_type = table_item_request
-            .key_type
-            .try_into()
-            .context("Failed to parse key_type")
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
-            })?;
-        let key = table_item_request.key;
-        let value_type = table_item_request
-            .value_type
-            .try_into()
-            .context("Failed to parse value_type")
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
-            })?;
+        with_proof: bool,
+    ) -> poem::Result<TableItemResponse, BasicErrorWith404> {
+        // Retrieve local state
+        let (ledger_info, ledger_version, state_view) =
+            self.preprocess_request(ledger_version.map(|inner| inner.0))?;
+        let state_root_hash = if with_proof {
+            Some(self.state_root_hash(ledger_version, &ledger_info)?)
+        } else {
+            None
+        };
 
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let item_key = table_item_key(
+            self.context.db.as_ref(),
+            &converter,
+            table_handle,
+            table_item_request,
+            &ledger_info,
+        )?;
+        let bytes = table_item_bytes(
+            &state_view,
+            table_handle,
+            &item_key,
+            &ledger_info,
+            ledger_version,
+        )?;
+        let proof = match state_root_hash {
+            Some(state_root_hash) => Some((
+                state_root_hash,
+                self.table_item_proof(&item_key, ledger_version, &ledger_info)?,
+            )),
+            None => None,
+        };
+
+        let content = match accept_type {
+            AcceptType::Json => {
+                let move_value =
+                    table_item_value(&converter, &item_key.value_type, &bytes, &ledger_info)?;
+                AptosResponseContent::Json(Json(move_value))
+            }
+            AcceptType::Bcs => AptosResponseContent::Bcs(Bcs(bytes)),
+        };
+        Ok(TableItemResponse::new(content, &ledger_info, proof))
+    }
+
+    /// Retrieve several items of a table for a specific ledger version
+    ///
+    /// Items which can't be retrieved are reported in their place, rather than failing the batch
+    pub fn table_items(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        batch_table_item_request: BatchTableItemRequest,
+        ledger_version: Option<U64>,
+        with_proof: bool,
+    ) -> BasicResultWith404<BatchTableItemResponse> {
         // Retrieve local state
         let (ledger_info, ledger_version, state_view) =
             self.preprocess_request(ledger_version.map(|inner| inner.0))?;
+        if *accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "Table items can only be retrieved as JSON",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        let items = batch_table_item_request.items;
+        if self.context.max_table_item_batch_size() < items.len() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                &format!(
+                    "Requested too many table items: {}, while limit is {}",
+                    items.len(),
+                    self.context.max_table_item_batch_size(),
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_root_hash = if with_proof {
+            Some(self.state_root_hash(ledger_version, &ledger_info)?)
+        } else {
+            None
+        };
 
         let resolver = state_view.as_move_resolver();
         let converter = resolver.as_converter(self.context.db.clone());
+        let items = items
+            .into_iter()
+            .map(|table_item_request| {
+                let item_key = match table_item_key(
+                    self.context.db.as_ref(),
+                    &converter,
+                    table_handle,
+                    table_item_request,
+                    &ledger_info,
+                ) {
+                    Ok(item_key) => item_key,
+                    Err(err) => return failed_table_item(err, None),
+                };
+                // Prove the item's value, or that it's not in the table
+                let proof = if with_proof {
+                    match self.table_item_proof(&item_key, ledger_version, &ledger_info) {
+                        Ok(proof) => Some(proof),
+                        Err(err) => return failed_table_item(err, None),
+                    }
+                } else {
+                    None
+                };
 
-        // Convert key to lookup version for DB
-        let vm_key = converter
-            .try_into_vm_value(&key_type, key.clone())
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code(
-                    err,
-                    AptosErrorCode::InvalidInput,
+                let value = table_item_bytes(
+                    &state_view,
+                    table_handle,
+                    &item_key,
                     &ledger_info,
+                    ledger_version,
                 )
-            })?;
-        let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
-            BasicErrorWith404::bad_request_with_code(
-                "Failed to serialize table key",
+                .and_then(|bytes| {
+                    table_item_value(&converter, &item_key.value_type, &bytes, &ledger_info)
+                });
+                match value {
+                    Ok(value) => TableItemResult {
+                        value: Some(value),
+                        error: None,
+                        proof,
+                    },
+                    Err(err) => failed_table_item(err, proof),
+                }
+            })
+            .collect();
+
+        BasicResponse::try_from_json((
+            BatchTableItemResponse {
+                items,
+                state_root_hash: state_root_hash.map(Into::into),
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
+    /// Retrieve a page of the items of a table for a specific ledger version
+    pub fn table_items_page(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        table_iter_request: TableIterRequest,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<TableIterResponse> {
+        let (ledger_info, ledger_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(
+                ledger_version.map(|inner| inner.0),
+            )?;
+        if *accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "Table items pages can only be retrieved as JSON",
                 AptosErrorCode::InvalidInput,
                 &ledger_info,
-            )
-        })?;
+            ));
+        }
+        let limit = Page::new(
+            None,
+            table_iter_request.limit,
+            self.context.max_table_items_page_size(),
+        )
+        .limit(&ledger_info)?;
 
-        // Retrieve value from the state key
-        let state_key = StateKey::table_item(TableHandle(table_handle.into()), raw_key);
-        let bytes = state_view
-            .get_state_value(&state_key)
-            .context(format!(
-                "Failed when trying to retrieve table item from the DB with key: {}",
-                key
-            ))
+        let handle = TableHandle(table_handle.into());
+        let cursor = table_iter_request
+            .cursor
+            .map(|cursor| StateKey::table_item(handle, cursor.0));
+        let (items, cursor) = self
+            .context
+            .get_table_items_page(handle, cursor.as_ref(), limit as usize, ledger_version)
+            .context("Failed to retrieve table items page")
             .map_err(|err| {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
                     &ledger_info,
                 )
+            })?;
+        let items = items
+            .into_iter()
+            .filter_map(|(state_key, state_value)| match state_key {
+                StateKey::TableItem { key, .. } => Some(TableIterItem {
+                    key: HexEncodedBytes(key),
+                    value: HexEncodedBytes(state_value.into_bytes()),
+                }),
+                _ => None,
+            })
+            .collect();
+        let cursor = cursor.and_then(|state_key| match state_key {
+            StateKey::TableItem { key, .. } => Some(HexEncodedBytes(key)),
+            _ => None,
+        });
+
+        BasicResponse::try_from_json((
+            TableIterResponse {
+                items,
+                cursor,
+                ledger_version: ledger_version.into(),
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
+    /// Retrieve the root hash of the state at a ledger version, which must be a state checkpoint
+    fn state_root_hash(
+        &self,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<HashValue, BasicErrorWith404> {
+        self.context
+            .get_state_checkpoint_hash(ledger_version, ledger_info.version())
+            .context("Failed to retrieve the state root hash")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
             })?
             .ok_or_else(|| {
-                table_item_not_found(table_handle, &key, ledger_version, &ledger_info)
+                BasicErrorWith404::bad_request_with_code(
+                    &format!(
+                        "Proofs can only be retrieved at state checkpoints, \
+                         and ledger version {} is not one",
+                        ledger_version
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })
+    }
+
+    /// Retrieve the BCS serialized proof of a table item against the state at a ledger version
+    fn table_item_proof(
+        &self,
+        item_key: &TableItemKey,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<HexEncodedBytes, BasicErrorWith404> {
+        let proof = self
+            .context
+            .get_state_proof(&item_key.state_key, ledger_version)
+            .context(format!(
+                "Failed when trying to retrieve the proof of table item with key: {}",
+                item_key.key
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
             })?;
+        bcs::to_bytes(&proof)
+            .map(HexEncodedBytes)
+            .context("Failed to serialize the proof of table item")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+    }
+}
 
-        match accept_type {
-            AcceptType::Json => {
-                let move_value = converter
-                    .try_into_move_value(&value_type, &bytes)
-                    .context("Failed to deserialize table item retrieved from DB")
-                    .map_err(|err| {
-                        BasicErrorWith404::internal_with_code(
-                            err,
-                            AptosErrorCode::InternalError,
-                            &ledger_info,
-                        )
-                    })?;
+/// The value of a table item, which is returned as it is whether or not its proof is requested,
+/// so the proof is returned in headers alongside the usual ledger info headers
+#[derive(poem_openapi::ApiResponse)]
+pub enum TableItemResponse {
+    #[oai(status = 200)]
+    Ok(
+        AptosResponseContent<MoveValue>,
+        /// Chain ID of the current chain
+        #[oai(header = "X-Aptos-Chain-Id")]
+        u8,
+        /// Current ledger version of the chain
+        #[oai(header = "X-Aptos-Ledger-Version")]
+        u64,
+        /// Oldest non-pruned ledger version of the chain
+        #[oai(header = "X-Aptos-Ledger-Oldest-Version")]
+        u64,
+        /// Current timestamp of the chain
+        #[oai(header = "X-Aptos-Ledger-TimestampUsec")]
+        u64,
+        /// Current epoch of the chain
+        #[oai(header = "X-Aptos-Epoch")]
+        u64,
+        /// Current block height of the chain
+        #[oai(header = "X-Aptos-Block-Height")]
+        u64,
+        /// Oldest non-pruned block height of the chain
+        #[oai(header = "X-Aptos-Oldest-Block-Height")]
+        u64,
+        /// Root hash of the state at the ledger version, only set when the proof is requested
+        #[oai(header = "X-Aptos-State-Root-Hash")]
+        Option<String>,
+        /// Hex encoded BCS serialized `SparseMerkleProof` of the table item against the state
+        /// root hash, only set when the proof is requested
+        #[oai(header = "X-Aptos-State-Proof")]
+        Option<String>,
+    ),
+}
 
-                BasicResponse::try_from_json((move_value, &ledger_info, BasicResponseStatus::Ok))
-            }
-            AcceptType::Bcs => {
-                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
-            }
+impl TableItemResponse {
+    fn new(
+        content: AptosResponseContent<MoveValue>,
+        ledger_info: &LedgerInfo,
+        proof: Option<(HashValue, HexEncodedBytes)>,
+    ) -> Self {
+        let (state_root_hash, proof) = match proof {
+            Some((state_root_hash, proof)) => (
+                Some(state_root_hash.to_hex_literal()),
+                Some(proof.to_string()),
+            ),
+            None => (None, None),
+        };
+        TableItemResponse::Ok(
+            content,
+            ledger_info.chain_id,
+            ledger_info.ledger_version.into(),
+            ledger_info.oldest_ledger_version.into(),
+            ledger_info.ledger_timestamp.into(),
+            ledger_info.epoch.into(),
+            ledger_info.block_height.into(),
+            ledger_info.oldest_block_height.into(),
+            state_root_hash,
+            proof,
+        )
+    }
+}
+
+/// A table item request, resolved to the state key the item is stored under
+struct TableItemKey {
+    /// The key as requested, for reporting the item
+    key: Value,
+    state_key: StateKey,
+    value_type: TypeTag,
+}
+
+/// Report a table item of a batch which couldn't be retrieved
+fn failed_table_item(
+    mut err: BasicErrorWith404,
+    proof: Option<HexEncodedBytes>,
+) -> TableItemResult {
+    TableItemResult {
+        value: None,
+        error: Some(err.inner_mut().clone()),
+        proof,
+    }
+}
+
+/// Resolve the state key of a table item, along with the type to decode its value as
+fn table_item_key(
+    db: &dyn DbReader,
+    converter: &MoveConverter<StorageAdapter<DbStateView>>,
+    table_handle: Address,
+    mut table_item_request: TableItemRequest,
+    ledger_info: &LedgerInfo,
+) -> Result<TableItemKey, BasicErrorWith404> {
+    // Parse the key and value types for the table
+    let key_type = table_item_request
+        .key_type
+        .try_into()
+        .context("Failed to parse key_type")
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
+        })?;
+    let value_type = table_item_request
+        .value_type
+        .try_into()
+        .context("Failed to parse value_type")
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
+        })?;
+    check_table_value_type(db, table_handle, &table_item_request, ledger_info)?;
+
+    // Equivalent keys are encoded the same, however the client wrote them
+    table_item_request.canonicalize_key();
+    table_item_request.validate_key().map_err(|err| {
+        BasicErrorWith404::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
+    })?;
+
+    // Convert key to lookup version for DB, unless it's already serialized
+    let (key, raw_key) = match (table_item_request.key, table_item_request.key_bytes) {
+        (Some(key), None) => {
+            let vm_key = converter
+                .try_into_vm_value(&key_type, key.clone())
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        ledger_info,
+                    )
+                })?;
+            let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
+                BasicErrorWith404::bad_request_with_code(
+                    "Failed to serialize table key",
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })?;
+            (key, raw_key)
         }
+        (None, Some(key_bytes)) => (Value::String(key_bytes.to_string()), key_bytes.0),
+        _ => {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "Table item request must have exactly one of key and key_bytes",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ))
+        }
+    };
+
+    Ok(TableItemKey {
+        key,
+        state_key: StateKey::table_item(TableHandle(table_handle.into()), raw_key),
+        value_type,
+    })
+}
+
+/// Check the value type of a table item request is the table's, where the indexer knows it
+///
+/// Tables the indexer hasn't recorded the types of aren't checked, and their items are decoded
+/// as the requested type
+fn check_table_value_type(
+    db: &dyn DbReader,
+    table_handle: Address,
+    table_item_request: &TableItemRequest,
+    ledger_info: &LedgerInfo,
+) -> Result<(), BasicErrorWith404> {
+    if !db.indexer_enabled() {
+        return Ok(());
     }
+    let table_info = match db.get_table_info(TableHandle(table_handle.into())) {
+        Ok(table_info) => table_info,
+        Err(_) => return Ok(()),
+    };
+    table_item_request
+        .check_value_type(&table_info.value_type.into())
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::TableValueTypeMismatch,
+                ledger_info,
+            )
+        })
+}
+
+/// Retrieve the BCS encoded value of a table item from its state key
+fn table_item_bytes(
+    state_view: &DbStateView,
+    table_handle: Address,
+    item_key: &TableItemKey,
+    ledger_info: &LedgerInfo,
+    ledger_version: u64,
+) -> Result<Vec<u8>, BasicErrorWith404> {
+    state_view
+        .get_state_value(&item_key.state_key)
+        .context(format!(
+            "Failed when trying to retrieve table item from the DB with key: {}",
+            item_key.key
+        ))
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })?
+        .ok_or_else(|| {
+            table_item_not_found(table_handle, &item_key.key, ledger_version, ledger_info)
+        })
+}
+
+/// Decode the BCS encoded value of a table item for a JSON response
+fn table_item_value(
+    converter: &MoveConverter<StorageAdapter<DbStateView>>,
+    value_type: &TypeTag,
+    bytes: &[u8],
+    ledger_info: &LedgerInfo,
+) -> Result<MoveValue, BasicErrorWith404> {
+    converter
+        .try_into_move_value(value_type, bytes)
+        .context("Failed to deserialize table item retrieved from DB")
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })
 }
@@ -17,3 +17,7 @@ use aptos_api_test_context::{new_test_context as super_new_test_context, TestCon
 fn new_test_context(test_name: String) -> TestContext {
     super_new_test_context(test_name, false)
 }
+
+fn new_test_context_with_indexer(test_name: String) -> TestContext {
+    super_new_test_context(test_name, true)
+}
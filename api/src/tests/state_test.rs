@@ -1,13 +1,21 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_indexer};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{
+    HexEncodedBytes, TableIterResponse, X_APTOS_STATE_PROOF, X_APTOS_STATE_ROOT_HASH,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_sdk::types::LocalAccount;
+use aptos_types::{
+    proof::SparseMerkleProof,
+    state_store::{state_key::StateKey, state_value::StateValue, table::TableHandle},
+};
 use move_deps::{move_core_types::account_address::AccountAddress, move_package::BuildConfig};
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::{convert::TryInto, path::PathBuf};
+use std::{convert::TryInto, path::PathBuf, str::FromStr};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -144,6 +152,19 @@ async fn test_get_table_item() {
 
     assert_table_item(ctx, &tt["u8_table"], "u8", "u8", 1u8, 1u8).await;
     assert_table_item(ctx, &tt["u64_table"], "u64", "u64", "1", "1").await;
+    // Keys may instead be given already BCS serialized
+    let handle = tt["u64_table"]["handle"].as_str().unwrap().parse().unwrap();
+    let response = ctx
+        .post(
+            &get_table_item(handle),
+            json!({
+                "key_type": "u64",
+                "value_type": "u64",
+                "key_bytes": "0x0100000000000000",
+            }),
+        )
+        .await;
+    assert_eq!(response, json!("1"));
     assert_table_item(ctx, &tt["u128_table"], "u128", "u128", "1", "1").await;
     assert_table_item(ctx, &tt["bool_table"], "bool", "bool", true, true).await;
     assert_table_item(
@@ -203,6 +224,507 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_key_and_key_bytes() {
+    let context = new_test_context(current_function_name!());
+    for request in [
+        json!({
+            "key_type": "u64",
+            "value_type": "u64",
+        }),
+        json!({
+            "key_type": "u64",
+            "value_type": "u64",
+            "key": "1",
+            "key_bytes": "0x0100000000000000",
+        }),
+    ] {
+        let resp = context
+            .expect_status_code(400)
+            .post(&get_table_item(AccountAddress::ONE), request)
+            .await;
+        assert_eq!(resp["error_code"], json!("invalid_input"));
+        assert!(resp["message"]
+            .as_str()
+            .unwrap()
+            .contains("exactly one of key and key_bytes"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_mismatched_key() {
+    let context = new_test_context(current_function_name!());
+    for (key_type, key, error) in [
+        (
+            "u64",
+            json!(1.5),
+            "key must be a string encoded u64 integer for u64, but is 1.5",
+        ),
+        (
+            "address",
+            json!("0xzz"),
+            "key must be a 0x prefixed hex string",
+        ),
+        (
+            "vector<u64>",
+            json!(["1", true]),
+            "key[1] must be a string encoded u64",
+        ),
+    ] {
+        let resp = context
+            .expect_status_code(400)
+            .post(
+                &get_table_item(AccountAddress::ONE),
+                json!({
+                    "key_type": key_type,
+                    "value_type": "u64",
+                    "key": key,
+                }),
+            )
+            .await;
+        assert_eq!(resp["error_code"], json!("invalid_input"));
+        let message = resp["message"].as_str().unwrap();
+        assert!(message.contains(error), "{}", message);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_mismatched_value_type() {
+    let mut context = new_test_context_with_indexer(current_function_name!());
+    let ctx = &mut context;
+    let mut account = ctx.gen_account();
+    let acc = &mut account;
+    let txn = ctx.create_user_account(acc);
+    ctx.commit_block(&vec![txn.clone()]).await;
+    make_test_tables(ctx, acc).await;
+    let tt = ctx
+        .api_get_account_resource(
+            acc,
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+
+    // The tables' own value types decode their items
+    assert_table_item(ctx, &tt["u64_table"], "u64", "u64", "1", "1").await;
+    let nested_table = api_get_table_item(
+        ctx,
+        &tt["table_table"],
+        "u8",
+        "0x1::table::Table<u8, u8>",
+        1u8,
+    )
+    .await;
+    assert!(nested_table["handle"].is_string(), "{}", nested_table);
+
+    // Other value types are rejected, rather than decoding the same items as them
+    for (table, key_type, value_type, key, error) in [
+        (
+            "u64_table",
+            "u64",
+            "u128",
+            json!("1"),
+            "value_type u128 doesn't match the table's value type u64",
+        ),
+        (
+            "table_table",
+            "u8",
+            "0x1::table::Table<u8, u64>",
+            json!(1),
+            "type argument 1 of 0x1::table::Table is u64 rather than u8",
+        ),
+    ] {
+        let handle = tt[table]["handle"].as_str().unwrap().parse().unwrap();
+        let request = json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": key,
+        });
+        let resp = ctx
+            .expect_status_code(400)
+            .post(&get_table_item(handle), request.clone())
+            .await;
+        assert_eq!(resp["error_code"], json!("table_value_type_mismatch"));
+        let message = resp["message"].as_str().unwrap();
+        assert!(message.contains(error), "{}", message);
+
+        // and reported in place of the item in a batch
+        let resp = ctx
+            .post(&get_table_items(handle), json!({ "items": [request] }))
+            .await;
+        let item_error = &resp["items"][0]["error"];
+        assert_eq!(item_error["error_code"], json!("table_value_type_mismatch"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items_reports_errors_per_item() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            &get_table_items(AccountAddress::ONE),
+            json!({
+                "items": [
+                    {
+                        "key_type": "u64",
+                        "value_type": "u64",
+                        "key": "1",
+                    },
+                    {
+                        "key_type": "not a type",
+                        "value_type": "u64",
+                        "key": "1",
+                    },
+                ],
+            }),
+        )
+        .await;
+    let items = resp["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        items[0]["error"]["error_code"],
+        json!("table_item_not_found")
+    );
+    assert_eq!(items[1]["error"]["error_code"], json!("invalid_input"));
+    assert!(items.iter().all(|item| item.get("value").is_none()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items_over_batch_limit() {
+    let context = new_test_context(current_function_name!());
+    let item = json!({
+        "key_type": "u64",
+        "value_type": "u64",
+        "key": "1",
+    });
+    let limit = context.context.max_table_item_batch_size();
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            &get_table_items(AccountAddress::ONE),
+            json!({ "items": vec![item; limit + 1] }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], json!("invalid_input"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_bcs() {
+    let context = new_test_context(current_function_name!());
+    // The supply of the native coin is an aggregator, which is an item of a table
+    let coin_info = context
+        .get(&get_account_resource(
+            "0x1",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+        ))
+        .await;
+    let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+    let handle = aggregator["handle"].as_str().unwrap().parse().unwrap();
+    let request = json!({
+        "key_type": "address",
+        "value_type": "u128",
+        "key": aggregator["key"],
+    });
+
+    // The raw bytes of the value are returned, which decode to the value returned as JSON
+    let bytes = context
+        .post_bcs_output(&get_table_item(handle), request.clone())
+        .await;
+    let supply: u128 = bcs::from_bytes(&bytes).unwrap();
+    let json = context.post(&get_table_item(handle), request).await;
+    assert_eq!(json, json!(supply.to_string()));
+    assert!(supply > 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let coin_info = context
+        .get(&get_account_resource(
+            "0x1",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+        ))
+        .await;
+    let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+    let handle = aggregator["handle"].as_str().unwrap().parse().unwrap();
+    let request = json!({
+        "key_type": "address",
+        "value_type": "u128",
+        "key": aggregator["key"],
+    });
+    let at_version =
+        |version: u64| format!("{}?ledger_version={}", get_table_item(handle), version);
+    let genesis_supply = context.post(&at_version(0), request.clone()).await;
+
+    // The fees of each transaction are burned from the supply, which changes its value
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+    let version = context.get_latest_ledger_info().version();
+    let supply = context.post(&at_version(version), request.clone()).await;
+    assert_ne!(supply, genesis_supply);
+    assert_eq!(
+        context.post(&get_table_item(handle), request.clone()).await,
+        supply
+    );
+
+    // The earlier value is still read at the earlier version
+    assert_eq!(
+        context.post(&at_version(0), request.clone()).await,
+        genesis_supply
+    );
+
+    // Keys which weren't in the table at the version aren't found
+    let resp = context
+        .expect_status_code(404)
+        .post(
+            &at_version(0),
+            json!({
+                "key_type": "address",
+                "value_type": "u128",
+                "key": "0xdead",
+            }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], json!("table_item_not_found"));
+
+    // Versions which haven't been committed yet aren't found either
+    let resp = context
+        .expect_status_code(404)
+        .post(&at_version(version + 1), request)
+        .await;
+    assert_eq!(resp["error_code"], json!("version_not_found"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_equivalent_keys() {
+    let context = new_test_context(current_function_name!());
+    let coin_info = context
+        .get(&get_account_resource(
+            "0x1",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+        ))
+        .await;
+    let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+    let handle = aggregator["handle"].as_str().unwrap().parse().unwrap();
+    let address = AccountAddress::from_hex_literal(aggregator["key"].as_str().unwrap()).unwrap();
+    let request = |key: String| {
+        json!({
+            "key_type": "address",
+            "value_type": "u128",
+            "key": key,
+        })
+    };
+    let supply = context
+        .post(&get_table_item(handle), request(address.to_hex_literal()))
+        .await;
+
+    // Addresses written in full, or in upper case, are the same key
+    let keys = [
+        format!("0x{}", address.to_hex()),
+        format!("0x{}", address.short_str_lossless().to_uppercase()),
+    ];
+    for key in keys.clone() {
+        assert_eq!(
+            context.post(&get_table_item(handle), request(key)).await,
+            supply
+        );
+    }
+    let resp = context
+        .post(
+            &get_table_items(handle),
+            json!({ "items": keys.map(request) }),
+        )
+        .await;
+    let items = resp["items"].as_array().unwrap();
+    assert!(items.iter().all(|item| item["value"] == supply), "{}", resp);
+
+    // Integers written as numbers are looked up as the strings they encode as, not rejected
+    let resp = context
+        .expect_status_code(404)
+        .post(
+            &get_table_item(AccountAddress::ONE),
+            json!({
+                "key_type": "u64",
+                "value_type": "u64",
+                "key": 1,
+            }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], json!("table_item_not_found"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            &format!(
+                "{}?ledger_version=0&with_proof=true",
+                get_table_items(AccountAddress::ONE)
+            ),
+            json!({
+                "items": [
+                    {
+                        "key_type": "u64",
+                        "value_type": "u64",
+                        "key": "1",
+                    },
+                ],
+            }),
+        )
+        .await;
+
+    // Proofs are against the state root of the transaction at the ledger version
+    let genesis = context.get("/transactions/by_version/0").await;
+    assert_eq!(resp["state_root_hash"], genesis["state_checkpoint_hash"]);
+    let state_root_hash = HashValue::from_str(resp["state_root_hash"].as_str().unwrap()).unwrap();
+
+    // The item isn't in the table, and its proof shows it isn't
+    let item = &resp["items"][0];
+    assert_eq!(item["error"]["error_code"], json!("table_item_not_found"));
+    let proof: HexEncodedBytes = serde_json::from_value(item["proof"].clone()).unwrap();
+    let proof: SparseMerkleProof = bcs::from_bytes(&proof.0).unwrap();
+    let state_key = StateKey::table_item(
+        TableHandle(AccountAddress::ONE),
+        bcs::to_bytes(&1u64).unwrap(),
+    );
+    proof
+        .verify::<StateValue>(state_root_hash, state_key.hash(), None)
+        .unwrap();
+    let value = StateValue::from(bcs::to_bytes(&1u64).unwrap());
+    assert!(proof
+        .verify(state_root_hash, state_key.hash(), Some(&value))
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let coin_info = context
+        .get(&get_account_resource_with_version(
+            "0x1",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+            0,
+        ))
+        .await;
+    let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+    let handle = aggregator["handle"].as_str().unwrap().parse().unwrap();
+    let request = json!({
+        "key_type": "address",
+        "value_type": "u128",
+        "key": aggregator["key"],
+    });
+
+    // The value is returned as it is, with the proof and state root hash in headers
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path(&context.prepend_path(&format!(
+                    "{}?ledger_version=0&with_proof=true",
+                    get_table_item(handle)
+                )))
+                .json(&request),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let supply: Value = serde_json::from_slice(resp.body()).unwrap();
+    let supply: u128 = supply.as_str().unwrap().parse().unwrap();
+    let genesis = context.get("/transactions/by_version/0").await;
+    let state_root_hash = resp.headers()[X_APTOS_STATE_ROOT_HASH].to_str().unwrap();
+    assert_eq!(json!(state_root_hash), genesis["state_checkpoint_hash"]);
+    let state_root_hash = HashValue::from_str(state_root_hash).unwrap();
+
+    // The proof shows the item has the returned value
+    let proof: HexEncodedBytes = resp.headers()[X_APTOS_STATE_PROOF]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let proof: SparseMerkleProof = bcs::from_bytes(&proof.0).unwrap();
+    let key = AccountAddress::from_hex_literal(aggregator["key"].as_str().unwrap()).unwrap();
+    let state_key = StateKey::table_item(TableHandle(handle), bcs::to_bytes(&key).unwrap());
+    let value = StateValue::from(bcs::to_bytes(&supply).unwrap());
+    proof
+        .verify(state_root_hash, state_key.hash(), Some(&value))
+        .unwrap();
+
+    // Without with_proof, there are no proof headers
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path(&context.prepend_path(&get_table_item(handle)))
+                .json(&request),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key(X_APTOS_STATE_PROOF));
+    assert!(!resp.headers().contains_key(X_APTOS_STATE_ROOT_HASH));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items_page() {
+    let context = new_test_context(current_function_name!());
+    let coin_info = context
+        .get(&get_account_resource(
+            "0x1",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+        ))
+        .await;
+    let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+    let handle = aggregator["handle"].as_str().unwrap().parse().unwrap();
+    let supply_key = AccountAddress::from_hex_literal(aggregator["key"].as_str().unwrap()).unwrap();
+
+    // Page through the table one item at a time, at the version of the first page
+    let mut items = Vec::new();
+    let mut request = json!({ "limit": 1 });
+    let mut ledger_version = None;
+    loop {
+        let path = match ledger_version {
+            Some(ref version) => {
+                format!(
+                    "{}?ledger_version={}",
+                    get_table_items_page(handle),
+                    version
+                )
+            }
+            None => get_table_items_page(handle),
+        };
+        let page: TableIterResponse =
+            serde_json::from_value(context.post(&path, request).await).unwrap();
+        assert!(page.items.len() <= 1);
+        if let Some(ref version) = ledger_version {
+            assert_eq!(&page.ledger_version, version);
+        }
+        ledger_version = Some(page.ledger_version);
+        items.extend(page.items);
+        match page.cursor {
+            Some(cursor) => request = json!({ "cursor": cursor, "limit": 1 }),
+            None => break,
+        }
+    }
+
+    // The supply is one of the items, as its bcs serialized key and value
+    let supply = items
+        .iter()
+        .find(|item| item.key.0 == bcs::to_bytes(&supply_key).unwrap())
+        .unwrap();
+    let supply: u128 = bcs::from_bytes(&supply.value.0).unwrap();
+    assert!(supply > 0);
+
+    for limit in [0, context.context.max_table_items_page_size() + 1] {
+        let resp = context
+            .expect_status_code(400)
+            .post(&get_table_items_page(handle), json!({ "limit": limit }))
+            .await;
+        assert_eq!(resp["error_code"], json!("invalid_input"));
+    }
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
@@ -222,6 +744,14 @@ fn get_table_item(handle: AccountAddress) -> String {
     format!("/tables/{}/item", handle)
 }
 
+fn get_table_items(handle: AccountAddress) -> String {
+    format!("/tables/{}/items", handle)
+}
+
+fn get_table_items_page(handle: AccountAddress) -> String {
+    format!("/tables/{}/iter", handle)
+}
+
 async fn make_test_tables(ctx: &mut TestContext, account: &mut LocalAccount) {
     let module = build_test_module(account.address()).await;
 
@@ -46,7 +46,10 @@ use serde_json::{json, Value};
 use std::{boxed::Box, iter::once, net::SocketAddr, sync::Arc, time::Duration};
 use storage_interface::state_view::DbStateView;
 use vm_validator::vm_validator::VMValidator;
-use warp::{http::header::CONTENT_TYPE, Filter, Rejection, Reply};
+use warp::{
+    http::header::{ACCEPT, CONTENT_TYPE},
+    Filter, Rejection, Reply,
+};
 use warp_reverse_proxy::reverse_proxy_filter;
 
 #[derive(Clone, Debug)]
@@ -519,6 +522,22 @@ impl TestContext {
         .await
     }
 
+    /// Posts a JSON body, returning the BCS output of the response
+    pub async fn post_bcs_output(&self, path: &str, body: Value) -> Bytes {
+        let resp = self
+            .reply(
+                warp::test::request()
+                    .method("POST")
+                    .path(&self.prepend_path(path))
+                    .header(ACCEPT, mime_types::BCS)
+                    .json(&body),
+            )
+            .await;
+        assert_eq!(self.expect_status_code, resp.status());
+        assert_eq!(resp.headers()[CONTENT_TYPE], mime_types::BCS);
+        resp.into_body()
+    }
+
     pub async fn reply(&self, req: warp::test::RequestBuilder) -> Response<Bytes> {
         match self.api_specific_config {
             ApiSpecificConfig::V1(address) => req.reply(&self.get_routes_with_poem(address)).await,
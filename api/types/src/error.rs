@@ -86,6 +86,8 @@ pub enum AptosErrorCode {
 
     /// The API's inputs were invalid
     InvalidInput = 300,
+    /// The value type requested for a table item isn't the type of the table's values
+    TableValueTypeMismatch = 301,
 
     /// The transaction was an invalid update to an already submitted transaction.
     InvalidTransactionUpdate = 401,
@@ -15,3 +15,7 @@ pub const X_APTOS_BLOCK_HEIGHT: &str = "X-Aptos-Block-Height";
 pub const X_APTOS_OLDEST_BLOCK_HEIGHT: &str = "X-Aptos-Oldest-Block-Height";
 /// Current timestamp of the chain
 pub const X_APTOS_LEDGER_TIMESTAMP: &str = "X-Aptos-Ledger-TimestampUsec";
+/// Root hash of the state a table item's proof is against
+pub const X_APTOS_STATE_ROOT_HASH: &str = "X-Aptos-State-Root-Hash";
+/// BCS serialized proof of a table item, hex encoded
+pub const X_APTOS_STATE_PROOF: &str = "X-Aptos-State-Proof";
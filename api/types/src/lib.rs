@@ -15,6 +15,7 @@ mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod pretty;
 mod table;
 mod transaction;
 mod wrappers;
@@ -35,9 +36,14 @@ pub use move_types::{
     MoveFunctionVisibility, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue, U128, U64,
 };
+pub use pretty::format_move_value;
 use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
-pub use table::TableItemRequest;
+pub use table::{
+    decode_key_bytes, BatchTableItemRequest, BatchTableItemResponse, ResourceTable,
+    TableChangeEvent, TableInfo, TableItemRequest, TableItemResult, TableIterItem,
+    TableIterRequest, TableIterResponse, TableSubscriptionRequest,
+};
 pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
     DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
@@ -16,7 +16,7 @@ use move_deps::{
         account_address::AccountAddress,
         identifier::Identifier,
         language_storage::{ModuleId, StructTag, TypeTag},
-        parser::{parse_struct_tag, parse_type_tag},
+        parser::parse_struct_tag,
         transaction_argument::TransactionArgument,
     },
     move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue},
@@ -543,42 +543,143 @@ impl fmt::Display for MoveType {
     }
 }
 
-// This function cannot handle the full range of types that MoveType can
-// represent. Internally, it uses parse_type_tag, which cannot handle references
-// or generic type parameters. This function adds nominal support for references
-// on top of parse_type_tag, but it still does not work for generic type params.
-// For that, we have the Unparsable variant of MoveType, so the deserialization
+// Parses the canonical form of a Move type, as displayed, so that displaying a type and parsing
+// it back gives the same type.  This includes generic type params and references, which
+// `TypeTag` can't represent, and whitespace between tokens is ignored.  Anything else which
+// doesn't parse is kept as is in the Unparsable variant of MoveType, so the deserialization
 // doesn't fail when dealing with these values.
 impl FromStr for MoveType {
     type Err = anyhow::Error;
 
-    fn from_str(mut s: &str) -> Result<Self, Self::Err> {
-        let mut is_ref = false;
-        let mut is_mut = false;
-        if s.starts_with('&') {
-            s = &s[1..];
-            is_ref = true;
-        }
-        if is_ref && s.starts_with("mut ") {
-            s = &s[4..];
-            is_mut = true;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(inner) = s
+            .strip_prefix("unparsable<")
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            return Ok(MoveType::Unparsable(inner.to_string()));
         }
         // Previously this would just crap out, but this meant the API could
         // return a serialized version of an object and not be able to
         // deserialize it using that same object.
-        let inner = match parse_type_tag(s) {
-            Ok(inner) => inner.into(),
-            Err(_e) => MoveType::Unparsable(s.to_string()),
+        Ok(MoveTypeParser { rest: s }
+            .parse()
+            .unwrap_or_else(|_| MoveType::Unparsable(s.to_string())))
+    }
+}
+
+/// A recursive descent parser of the canonical form of a [`MoveType`]
+struct MoveTypeParser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> MoveTypeParser<'a> {
+    /// Parses the whole input as a single type
+    fn parse(mut self) -> anyhow::Result<MoveType> {
+        let typ = self.parse_type()?;
+        self.skip_whitespace();
+        if !self.rest.is_empty() {
+            bail!("unexpected {:?} after type {}", self.rest, typ);
+        }
+        Ok(typ)
+    }
+
+    fn parse_type(&mut self) -> anyhow::Result<MoveType> {
+        if self.consume("&") {
+            self.skip_whitespace();
+            let mutable = match self.rest.strip_prefix("mut") {
+                Some(rest) if rest.starts_with(char::is_whitespace) => {
+                    self.rest = rest;
+                    true
+                }
+                _ => false,
+            };
+            let to = Box::new(self.parse_type()?);
+            return Ok(MoveType::Reference { mutable, to });
+        }
+
+        let token = self.next_token()?;
+        let typ = match token {
+            "bool" => MoveType::Bool,
+            "u8" => MoveType::U8,
+            "u64" => MoveType::U64,
+            "u128" => MoveType::U128,
+            "address" => MoveType::Address,
+            "signer" => MoveType::Signer,
+            "vector" => {
+                self.expect("<")?;
+                let items = Box::new(self.parse_type()?);
+                self.expect(">")?;
+                MoveType::Vector { items }
+            }
+            _ if self.consume("::") => {
+                let address = Address::from_str(token)?;
+                let module = IdentifierWrapper::from_str(self.next_token()?)?;
+                self.expect("::")?;
+                let name = IdentifierWrapper::from_str(self.next_token()?)?;
+                let mut generic_type_params = vec![];
+                if self.consume("<") {
+                    loop {
+                        generic_type_params.push(self.parse_type()?);
+                        if self.consume(">") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                MoveType::Struct(MoveStructTag::new(
+                    address,
+                    module,
+                    name,
+                    generic_type_params,
+                ))
+            }
+            _ => match token.strip_prefix('T').map(u16::from_str) {
+                Some(Ok(index)) => MoveType::GenericTypeParam { index },
+                _ => bail!("invalid type {:?}", token),
+            },
         };
-        if is_ref {
-            Ok(MoveType::Reference {
-                mutable: is_mut,
-                to: Box::new(inner),
-            })
+        Ok(typ)
+    }
+
+    /// Returns the next identifier, address or index
+    fn next_token(&mut self) -> anyhow::Result<&'a str> {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            bail!("expected a type, found {:?}", self.rest);
+        }
+        let (token, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(token)
+    }
+
+    /// Consumes `punctuation` if it's next
+    fn consume(&mut self, punctuation: &str) -> bool {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(punctuation) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, punctuation: &str) -> anyhow::Result<()> {
+        if self.consume(punctuation) {
+            Ok(())
         } else {
-            Ok(inner)
+            bail!("expected {:?}, found {:?}", punctuation, self.rest)
         }
     }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
 }
 
 impl Serialize for MoveType {
@@ -1079,6 +1180,7 @@ mod tests {
         move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue},
     };
 
+    use proptest::{collection::vec, prelude::*};
     use serde::{de::DeserializeOwned, Serialize};
     use serde_json::{json, to_value, Value};
     use std::{boxed::Box, convert::TryFrom, fmt::Debug};
@@ -1320,6 +1422,133 @@ mod tests {
         test_serialize_deserialize(HexEncodedBytes::from(bytes), json!("0xabcd"))
     }
 
+    #[test]
+    fn test_parse_move_type() {
+        fn assert_parse(s: &str, expected: MoveType) {
+            let typ = MoveType::from_str(s).unwrap();
+            assert_eq!(typ, expected);
+            assert_eq!(MoveType::from_str(&typ.to_string()).unwrap(), typ);
+        }
+        let struct_type = |s: &str, generic_type_params| {
+            let tag = MoveStructTag::from_str(s).unwrap();
+            MoveType::Struct(MoveStructTag::new(
+                tag.address,
+                tag.module,
+                tag.name,
+                generic_type_params,
+            ))
+        };
+        let vector = |items| MoveType::Vector {
+            items: Box::new(items),
+        };
+
+        assert_parse("vector<u8>", vector(MoveType::U8));
+        assert_parse(
+            "0x1::string::String",
+            struct_type("0x1::string::String", vec![]),
+        );
+        let coin = struct_type(
+            "0x1::coin::Coin",
+            vec![struct_type("0x1::aptos_coin::AptosCoin", vec![])],
+        );
+        assert_parse("0x1::coin::Coin<0x1::aptos_coin::AptosCoin>", coin.clone());
+        assert_parse(
+            " 0x1::coin::Coin < 0x1::aptos_coin::AptosCoin >  ",
+            coin.clone(),
+        );
+        assert_parse(
+            "0x0000000000000000000000000000000000000000000000000000000000000001::coin::Coin<0x1::aptos_coin::AptosCoin>",
+            coin.clone(),
+        );
+        assert_parse(
+            "0x1::table::Table<address,vector<vector<0x1::coin::Coin<0x1::aptos_coin::AptosCoin>>>>",
+            struct_type(
+                "0x1::table::Table",
+                vec![MoveType::Address, vector(vector(coin))],
+            ),
+        );
+        assert_parse(
+            "&mut vector<T0>",
+            MoveType::Reference {
+                mutable: true,
+                to: Box::new(vector(MoveType::GenericTypeParam { index: 0 })),
+            },
+        );
+        assert_parse(
+            "&0x1::coin::Coin<T1>",
+            MoveType::Reference {
+                mutable: false,
+                to: Box::new(struct_type(
+                    "0x1::coin::Coin",
+                    vec![MoveType::GenericTypeParam { index: 1 }],
+                )),
+            },
+        );
+
+        // Types which don't parse are kept as they are
+        for unparsable in [
+            "vector<u8",
+            "u16",
+            "0x1::coin",
+            "0x1::coin::Coin<>",
+            "T",
+            "u8 u8",
+        ] {
+            assert_parse(unparsable, MoveType::Unparsable(unparsable.to_string()));
+        }
+    }
+
+    fn arb_move_type() -> impl Strategy<Value = MoveType> {
+        let leaf = prop_oneof![
+            Just(MoveType::Bool),
+            Just(MoveType::U8),
+            Just(MoveType::U64),
+            Just(MoveType::U128),
+            Just(MoveType::Address),
+            Just(MoveType::Signer),
+            any::<u16>().prop_map(|index| MoveType::GenericTypeParam { index }),
+        ];
+        let arb_identifier =
+            || "[a-zA-Z][a-zA-Z0-9_]{0,8}".prop_map(|id| IdentifierWrapper::from(identifier(&id)));
+        leaf.prop_recursive(4, 32, 4, move |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|items| MoveType::Vector {
+                    items: Box::new(items)
+                }),
+                (
+                    any::<[u8; AccountAddress::LENGTH]>(),
+                    arb_identifier(),
+                    arb_identifier(),
+                    vec(inner, 0..4),
+                )
+                    .prop_map(|(address, module, name, generic_type_params)| {
+                        MoveType::Struct(MoveStructTag::new(
+                            AccountAddress::new(address).into(),
+                            module,
+                            name,
+                            generic_type_params,
+                        ))
+                    }),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_move_type_round_trip(typ in arb_move_type(), mutable in any::<Option<bool>>()) {
+            let typ = match mutable {
+                Some(mutable) => MoveType::Reference {
+                    mutable,
+                    to: Box::new(typ),
+                },
+                None => typ,
+            };
+            prop_assert_eq!(MoveType::from_str(&typ.to_string()).unwrap(), typ.clone());
+            let json = to_value(&typ).unwrap();
+            prop_assert_eq!(serde_json::from_value::<MoveType>(json).unwrap(), typ);
+        }
+    }
+
     fn test_serialize_deserialize<O>(obj: O, expected: Value)
     where
         O: Serialize + DeserializeOwned + PartialEq + Debug,
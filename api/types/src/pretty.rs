@@ -0,0 +1,330 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, MoveStruct, MoveStructTag, MoveType};
+use aptos_types::account_config::CORE_CODE_ADDRESS;
+use serde_json::Value;
+
+/// Vectors whose items all fit on one line are printed inline, if they fit within this width
+const MAX_INLINE_WIDTH: usize = 80;
+
+const INDENT: &str = "    ";
+
+/// Formats the JSON encoding of a Move value, such as a table item, as an indented string
+/// annotated with its types
+///
+/// Values are written as Move literals e.g. `8u8`, `@0x1` or `x"0102"`, strings are quoted,
+/// options are `some(..)` or `none`, and structs are written with their type, then each field by
+/// its Move name.  The types of a struct's fields are taken from its layout, as returned by
+/// `resolve_struct` e.g. from the ABI of its module, with its generic type params substituted.
+/// The fields of structs which can't be resolved are written as they're encoded in JSON.
+pub fn format_move_value(
+    value_type: &MoveType,
+    value: &Value,
+    resolve_struct: &dyn Fn(&MoveStructTag) -> Option<MoveStruct>,
+) -> String {
+    let raw = || value.to_string();
+    match value_type {
+        MoveType::Bool => value.as_bool().map_or_else(raw, |b| b.to_string()),
+        MoveType::U8 => value.as_u64().map_or_else(raw, |int| format!("{}u8", int)),
+        MoveType::U64 => integer(value, "u64").unwrap_or_else(raw),
+        MoveType::U128 => integer(value, "u128").unwrap_or_else(raw),
+        MoveType::Address => value.as_str().map_or_else(raw, |s| format!("@{}", s)),
+        MoveType::Vector { items } if **items == MoveType::U8 => value
+            .as_str()
+            .and_then(|s| s.strip_prefix("0x"))
+            .map_or_else(raw, |hex| format!("x\"{}\"", hex)),
+        MoveType::Vector { items } => match value.as_array() {
+            Some(values) => format_list(
+                "[",
+                values
+                    .iter()
+                    .map(|value| format_move_value(items, value, resolve_struct)),
+                "]",
+            ),
+            None => raw(),
+        },
+        MoveType::Struct(tag) if is_core_struct(tag, "string", "String") => raw(),
+        MoveType::Struct(tag) if is_core_struct(tag, "option", "Option") => {
+            let item_type = match tag.generic_type_params.first() {
+                Some(item_type) => item_type,
+                None => return raw(),
+            };
+            match value
+                .get("vec")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+            {
+                Some([]) => "none".to_string(),
+                Some([item]) => format!(
+                    "some({})",
+                    format_move_value(item_type, item, resolve_struct)
+                ),
+                _ => raw(),
+            }
+        }
+        MoveType::Struct(tag) => {
+            let fields = match value.as_object() {
+                Some(fields) => fields,
+                None => return raw(),
+            };
+            let formatted: Vec<_> = match resolve_struct(tag) {
+                Some(layout) => layout
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let name = field.name.as_str();
+                        let value = match fields.get(name) {
+                            Some(value) => format_move_value(
+                                &substitute(&field.typ, &tag.generic_type_params),
+                                value,
+                                resolve_struct,
+                            ),
+                            None => "<missing>".to_string(),
+                        };
+                        format!("{}: {}", name, value)
+                    })
+                    .collect(),
+                None => fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect(),
+            };
+            if formatted.is_empty() {
+                format!("{} {{}}", tag)
+            } else {
+                format_block(&format!("{} {{", tag), formatted, "}")
+            }
+        }
+        MoveType::Signer
+        | MoveType::GenericTypeParam { .. }
+        | MoveType::Reference { .. }
+        | MoveType::Unparsable(_) => raw(),
+    }
+}
+
+/// Formats a string encoded integer as a Move literal with its type suffix
+fn integer(value: &Value, suffix: &str) -> Option<String> {
+    let int = value.as_str()?;
+    if !int.is_empty() && int.bytes().all(|byte| byte.is_ascii_digit()) {
+        Some(format!("{}{}", int, suffix))
+    } else {
+        None
+    }
+}
+
+fn is_core_struct(tag: &MoveStructTag, module: &str, name: &str) -> bool {
+    tag.address == Address::from(CORE_CODE_ADDRESS)
+        && tag.module.as_str() == module
+        && tag.name.as_str() == name
+}
+
+/// Replaces the generic type params of a struct's field type with the struct's type arguments
+fn substitute(typ: &MoveType, type_args: &[MoveType]) -> MoveType {
+    match typ {
+        MoveType::GenericTypeParam { index } => type_args
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| typ.clone()),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, type_args)),
+        },
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute(to, type_args)),
+        },
+        MoveType::Struct(tag) => MoveType::Struct(MoveStructTag {
+            generic_type_params: tag
+                .generic_type_params
+                .iter()
+                .map(|param| substitute(param, type_args))
+                .collect(),
+            ..tag.clone()
+        }),
+        typ => typ.clone(),
+    }
+}
+
+/// Formats items between delimiters, on one line if they're short, and otherwise one per line
+fn format_list(open: &str, items: impl Iterator<Item = String>, close: &str) -> String {
+    let items: Vec<_> = items.collect();
+    let inline = format!("{}{}{}", open, items.join(", "), close);
+    if inline.len() <= MAX_INLINE_WIDTH && !inline.contains('\n') {
+        inline
+    } else {
+        format_block(open, items, close)
+    }
+}
+
+/// Formats items one per line between delimiters, indenting every line of the items
+fn format_block(open: &str, items: Vec<String>, close: &str) -> String {
+    let mut block = open.to_string();
+    for item in items {
+        for line in item.lines() {
+            block.push('\n');
+            block.push_str(INDENT);
+            block.push_str(line);
+        }
+        block.push(',');
+    }
+    block.push('\n');
+    block.push_str(close);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format_move_value, MoveStruct, MoveStructTag, MoveType};
+    use serde_json::{json, Value};
+    use std::{collections::BTreeMap, str::FromStr};
+
+    fn layouts() -> BTreeMap<String, MoveStruct> {
+        let layout = |name: &str, generics: usize, fields: Value| {
+            let layout = json!({
+                "name": name,
+                "is_native": false,
+                "abilities": ["store"],
+                "generic_type_params": vec![
+                    json!({"constraints": [], "is_phantom": false});
+                    generics
+                ],
+                "fields": fields,
+            });
+            serde_json::from_value::<MoveStruct>(layout).unwrap()
+        };
+        BTreeMap::from([
+            (
+                "0x1::coin::CoinInfo".to_string(),
+                layout(
+                    "CoinInfo",
+                    1,
+                    json!([
+                        {"name": "name", "type": "0x1::string::String"},
+                        {"name": "decimals", "type": "u8"},
+                        {"name": "supply", "type": "0x1::option::Option<u128>"},
+                    ]),
+                ),
+            ),
+            (
+                "0x1::coin::Coin".to_string(),
+                layout("Coin", 1, json!([{"name": "value", "type": "u64"}])),
+            ),
+            (
+                "0x1::pool::Pool".to_string(),
+                layout(
+                    "Pool",
+                    1,
+                    json!([
+                        {"name": "owner", "type": "address"},
+                        {"name": "items", "type": "vector<T0>"},
+                        {"name": "seed", "type": "vector<u8>"},
+                        {"name": "paused", "type": "bool"},
+                    ]),
+                ),
+            ),
+        ])
+    }
+
+    fn format(value_type: &str, value: Value) -> String {
+        let layouts = layouts();
+        let resolve = |tag: &MoveStructTag| {
+            let name = format!("{}::{}::{}", tag.address, tag.module, tag.name);
+            layouts.get(&name).cloned()
+        };
+        format_move_value(&MoveType::from_str(value_type).unwrap(), &value, &resolve)
+    }
+
+    #[test]
+    fn test_format_primitives() {
+        assert_eq!(format("bool", json!(true)), "true");
+        assert_eq!(format("u8", json!(8)), "8u8");
+        assert_eq!(format("u64", json!("1000")), "1000u64");
+        assert_eq!(format("u128", json!("7")), "7u128");
+        assert_eq!(format("address", json!("0x1")), "@0x1");
+        assert_eq!(format("vector<u8>", json!("0x0102")), "x\"0102\"");
+        assert_eq!(format("vector<u64>", json!(["1", "2"])), "[1u64, 2u64]");
+        assert_eq!(format("vector<u64>", json!([])), "[]");
+        assert_eq!(format("0x1::string::String", json!("APT")), "\"APT\"");
+
+        // Values which don't match their type are printed as they are
+        assert_eq!(format("u64", json!(true)), "true");
+        assert_eq!(format("vector<u64>", json!("0x01")), "\"0x01\"");
+    }
+
+    #[test]
+    fn test_format_options() {
+        assert_eq!(
+            format("0x1::option::Option<u64>", json!({"vec": []})),
+            "none"
+        );
+        assert_eq!(
+            format("0x1::option::Option<u64>", json!({"vec": ["3"]})),
+            "some(3u64)"
+        );
+        assert_eq!(
+            format(
+                "0x1::option::Option<0x1::option::Option<bool>>",
+                json!({"vec": [{"vec": [false]}]})
+            ),
+            "some(some(false))"
+        );
+    }
+
+    #[test]
+    fn test_format_structs() {
+        let coin_info = format(
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+            json!({
+                "name": "Aptos Coin",
+                "decimals": 8,
+                "supply": {"vec": ["18446744073709551616"]},
+            }),
+        );
+        assert_eq!(
+            coin_info,
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin> {\n    \
+                 name: \"Aptos Coin\",\n    \
+                 decimals: 8u8,\n    \
+                 supply: some(18446744073709551616u128),\n\
+             }"
+        );
+
+        // Generics are substituted into the fields, down to vectors of structs
+        let pool = format(
+            "0x1::pool::Pool<0x1::coin::Coin<0x1::aptos_coin::AptosCoin>>",
+            json!({
+                "owner": "0xa",
+                "items": [{"value": "1"}, {"value": "2"}],
+                "seed": "0xff",
+                "paused": false,
+            }),
+        );
+        assert_eq!(
+            pool,
+            "0x1::pool::Pool<0x1::coin::Coin<0x1::aptos_coin::AptosCoin>> {\n    \
+                 owner: @0xa,\n    \
+                 items: [\n        \
+                     0x1::coin::Coin<0x1::aptos_coin::AptosCoin> {\n            \
+                         value: 1u64,\n        \
+                     },\n        \
+                     0x1::coin::Coin<0x1::aptos_coin::AptosCoin> {\n            \
+                         value: 2u64,\n        \
+                     },\n    \
+                 ],\n    \
+                 seed: x\"ff\",\n    \
+                 paused: false,\n\
+             }"
+        );
+
+        // Structs without a layout are labelled with the fields of their JSON encoding
+        assert_eq!(
+            format("0x1::other::Other", json!({"a": "1", "b": [true]})),
+            "0x1::other::Other {\n    a: \"1\",\n    b: [true],\n}"
+        );
+        // Fields missing from the value are marked
+        assert_eq!(
+            format("0x1::coin::Coin<u8>", json!({})),
+            "0x1::coin::Coin<u8> {\n    value: <missing>,\n}"
+        );
+    }
+}
@@ -1,16 +1,1488 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::MoveType;
+use crate::{
+    Address, AptosError, HashValue, HexEncodedBytes, IdentifierWrapper, MoveModule, MoveResource,
+    MoveStruct, MoveStructTag, MoveType, MoveValue, U64,
+};
+use anyhow::{bail, format_err};
+use aptos_types::{account_address::AccountAddress, account_config::CORE_CODE_ADDRESS};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
 
 /// Table Item request for the GetTableItem API
+///
+/// Exactly one of `key` and `key_bytes` must be provided
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct TableItemRequest {
     pub key_type: MoveType,
     pub value_type: MoveType,
+    /// The value of the table item's key, encoded as `key_type`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<Value>,
+    /// The BCS serialized key of the table item, looked up as is
+    ///
+    /// For keys whose JSON encoding is lossy or unsupported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_bytes: Option<HexEncodedBytes>,
+}
+
+impl TableItemRequest {
+    /// Builds a request for the item whose key is BCS serialized as `key_bytes`, a hex string
+    ///
+    /// The bytes are checked to be the BCS encoding of a `key_type`, as by `validate_key_bytes`
+    pub fn from_key_bytes(
+        key_type: MoveType,
+        value_type: MoveType,
+        key_bytes: &str,
+    ) -> anyhow::Result<Self> {
+        let request = TableItemRequest {
+            key_type,
+            value_type,
+            key: None,
+            key_bytes: Some(decode_key_bytes(key_bytes)?),
+        };
+        request.validate_key_bytes()?;
+        Ok(request)
+    }
+
+    /// Rewrites `key` as the canonical JSON encoding of its `key_type`, so that equivalent keys
+    /// look up the same item however they were written
+    ///
+    /// Integers are written as `key_type` encodes them, whether given as numbers or strings, and
+    /// addresses and bytes as lowercase hex.  Struct fields are sorted by name, but their values
+    /// are left as they are without the struct's layout.  Keys which aren't an encoding of
+    /// `key_type` at all are left for `validate_key` to report.
+    pub fn canonicalize_key(&mut self) {
+        if let Some(ref mut key) = self.key {
+            canonicalize_key(&self.key_type, key);
+        }
+    }
+
+    /// Checks the shape of `key` against `key_type`, before it's encoded to look up the item
+    ///
+    /// Primitives, and vectors of them, are fully checked.  Structs are only checked to be JSON
+    /// objects, or strings for `0x1::string::String`, leaving their fields to the encoding.
+    pub fn validate_key(&self) -> anyhow::Result<()> {
+        match self.key {
+            Some(ref key) => validate_key(&self.key_type, key, "key"),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks `key_bytes` is the BCS encoding of a value of `key_type`, with no bytes left over
+    ///
+    /// Primitives, strings, and vectors of them, are fully checked.  Other structs can't be
+    /// checked without their layout, so any bytes are accepted from the start of one on.
+    pub fn validate_key_bytes(&self) -> anyhow::Result<()> {
+        let mut bytes = match self.key_bytes {
+            Some(ref key_bytes) => key_bytes.0.as_slice(),
+            None => return Ok(()),
+        };
+        if take_bcs(&self.key_type, &mut bytes)? && !bytes.is_empty() {
+            bail!(
+                "key_bytes has {} bytes left over after the {} key",
+                bytes.len(),
+                self.key_type
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks `value_type` is `table_value_type`, the type of the values stored in the table
+    ///
+    /// A table's items are stored without their types, so decoding one as any other type would
+    /// give whatever its bytes happen to decode to rather than failing.  Types which only differ
+    /// in their type arguments are reported by the first type argument which differs.
+    pub fn check_value_type(&self, table_value_type: &MoveType) -> anyhow::Result<()> {
+        if self.value_type == *table_value_type {
+            return Ok(());
+        }
+        match type_argument_mismatch(&self.value_type, table_value_type) {
+            Some(mismatch) => bail!(
+                "value_type {} doesn't match the table's value type {}: {}",
+                self.value_type,
+                table_value_type,
+                mismatch
+            ),
+            None => bail!(
+                "value_type {} doesn't match the table's value type {}",
+                self.value_type,
+                table_value_type
+            ),
+        }
+    }
+}
+
+/// Describes the innermost type argument at which `requested` differs from `actual`, if they're
+/// the same struct, or vectors of the same struct, with different type arguments
+fn type_argument_mismatch(requested: &MoveType, actual: &MoveType) -> Option<String> {
+    match (requested, actual) {
+        (MoveType::Vector { items: requested }, MoveType::Vector { items: actual }) => {
+            type_argument_mismatch(requested, actual)
+        }
+        (MoveType::Struct(requested), MoveType::Struct(actual))
+            if requested.address == actual.address
+                && requested.module == actual.module
+                && requested.name == actual.name
+                && requested.generic_type_params.len() == actual.generic_type_params.len() =>
+        {
+            requested
+                .generic_type_params
+                .iter()
+                .zip(&actual.generic_type_params)
+                .enumerate()
+                .find(|(_, (requested_param, actual_param))| requested_param != actual_param)
+                .map(|(index, (requested_param, actual_param))| {
+                    type_argument_mismatch(requested_param, actual_param).unwrap_or_else(|| {
+                        format!(
+                            "type argument {} of {}::{}::{} is {} rather than {}",
+                            index,
+                            actual.address,
+                            actual.module,
+                            actual.name,
+                            requested_param,
+                            actual_param
+                        )
+                    })
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Decodes the hex string of a BCS serialized key, with or without its 0x prefix
+pub fn decode_key_bytes(key_bytes: &str) -> anyhow::Result<HexEncodedBytes> {
+    let digits = key_bytes.strip_prefix("0x").unwrap_or(key_bytes);
+    if digits.is_empty() {
+        bail!("key_bytes must not be empty");
+    }
+    if digits.len() % 2 != 0 {
+        bail!(
+            "key_bytes must have an even number of hex digits, but {} has {}",
+            key_bytes,
+            digits.len()
+        );
+    }
+    HexEncodedBytes::from_str(digits)
+}
+
+/// Takes the BCS encoding of a value of `key_type` from the front of `bytes`
+///
+/// Returns whether the value was fully checked, which it can't be for structs other than
+/// `0x1::string::String`, so the rest of `bytes` is left unchecked
+fn take_bcs(key_type: &MoveType, bytes: &mut &[u8]) -> anyhow::Result<bool> {
+    match key_type {
+        MoveType::Bool => {
+            let byte = take(bytes, 1, key_type)?[0];
+            if byte > 1 {
+                bail!(
+                    "key_bytes must encode a bool as 0x00 or 0x01, but has {:#04x}",
+                    byte
+                );
+            }
+        }
+        MoveType::U8 => {
+            take(bytes, 1, key_type)?;
+        }
+        MoveType::U64 => {
+            take(bytes, 8, key_type)?;
+        }
+        MoveType::U128 => {
+            take(bytes, 16, key_type)?;
+        }
+        MoveType::Address => {
+            take(bytes, AccountAddress::LENGTH, key_type)?;
+        }
+        MoveType::Vector { items } => {
+            for _ in 0..take_length(bytes, key_type)? {
+                if !take_bcs(items, bytes)? {
+                    return Ok(false);
+                }
+            }
+        }
+        MoveType::Struct(tag) if is_string(tag) => {
+            let length = take_length(bytes, key_type)?;
+            if std::str::from_utf8(take(bytes, length, key_type)?).is_err() {
+                bail!("key_bytes must encode a UTF-8 string for {}", key_type);
+            }
+        }
+        MoveType::Struct(_) => {
+            if bytes.is_empty() {
+                bail!("key_bytes ends before the {} key", key_type);
+            }
+            return Ok(false);
+        }
+        MoveType::Signer
+        | MoveType::Reference { .. }
+        | MoveType::GenericTypeParam { .. }
+        | MoveType::Unparsable(_) => bail!("{} can't be a table key", key_type),
+    }
+    Ok(true)
+}
+
+/// Takes the next `length` bytes of a value of `typ` from the front of `bytes`
+fn take<'a>(bytes: &mut &'a [u8], length: usize, typ: impl Display) -> anyhow::Result<&'a [u8]> {
+    if bytes.len() < length {
+        bail!(
+            "key_bytes ends {} bytes before the end of the {} key",
+            length - bytes.len(),
+            typ
+        );
+    }
+    let (taken, rest) = bytes.split_at(length);
+    *bytes = rest;
+    Ok(taken)
+}
+
+/// Takes the ULEB128 encoded length of a vector or string of `typ` from the front of `bytes`
+fn take_length(bytes: &mut &[u8], typ: &MoveType) -> anyhow::Result<usize> {
+    let mut length = 0u64;
+    for shift in (0..32).step_by(7) {
+        let byte = take(bytes, 1, typ)?[0];
+        length |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            // BCS only allows lengths of u32s, in their shortest encoding
+            if (byte == 0 && shift > 0) || length > u32::MAX as u64 {
+                break;
+            }
+            return Ok(length as usize);
+        }
+    }
+    bail!("key_bytes has an invalid length for {}", typ)
+}
+
+fn is_string(tag: &MoveStructTag) -> bool {
+    tag.address == Address::from(CORE_CODE_ADDRESS)
+        && tag.module.as_str() == "string"
+        && tag.name.as_str() == "String"
+}
+
+/// Rewrites `key` as the canonical JSON encoding of a value of `key_type`, where it's equivalent
+/// to one
+fn canonicalize_key(key_type: &MoveType, key: &mut Value) {
+    let canonical = match (key_type, &mut *key) {
+        (MoveType::U8, Value::String(s)) => s.parse::<u8>().ok().map(Value::from),
+        (MoveType::U64 | MoveType::U128, Value::Number(int)) => {
+            int.as_u64().map(|int| Value::from(int.to_string()))
+        }
+        (MoveType::U64, Value::String(s)) => s
+            .parse::<u64>()
+            .ok()
+            .map(|int| Value::from(int.to_string())),
+        (MoveType::U128, Value::String(s)) => s
+            .parse::<u128>()
+            .ok()
+            .map(|int| Value::from(int.to_string())),
+        (MoveType::Address, Value::String(s)) => Address::from_str(s)
+            .ok()
+            .map(|address| Value::from(address.to_string())),
+        (MoveType::Vector { items }, Value::String(s)) if **items == MoveType::U8 => {
+            HexEncodedBytes::from_str(s)
+                .ok()
+                .map(|bytes| Value::from(bytes.to_string()))
+        }
+        (MoveType::Vector { items }, Value::Array(values)) if **items == MoveType::U8 => values
+            .iter()
+            .map(|value| value.as_u64().and_then(|int| u8::try_from(int).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .map(|bytes| Value::from(HexEncodedBytes(bytes).to_string())),
+        (MoveType::Vector { items }, Value::Array(values)) => {
+            for value in values {
+                canonicalize_key(items, value);
+            }
+            None
+        }
+        (MoveType::Struct(tag), Value::Object(fields)) if !is_string(tag) => {
+            let mut sorted: Vec<_> = std::mem::take(fields).into_iter().collect();
+            sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Some(Value::Object(sorted.into_iter().collect()))
+        }
+        _ => None,
+    };
+    if let Some(canonical) = canonical {
+        *key = canonical;
+    }
+}
+
+/// Checks `key` is the JSON encoding of a value of `key_type`, with `path` locating it in the key
+fn validate_key(key_type: &MoveType, key: &Value, path: &str) -> anyhow::Result<()> {
+    let (valid, expected) = match key_type {
+        MoveType::Bool => (key.is_boolean(), "a boolean"),
+        MoveType::U8 => (
+            key.as_u64().map_or(false, |int| int <= u8::MAX as u64),
+            "an integer from 0 to 255",
+        ),
+        MoveType::U64 => (
+            key.as_str().map_or(false, |s| s.parse::<u64>().is_ok()),
+            "a string encoded u64 integer",
+        ),
+        MoveType::U128 => (
+            key.as_str().map_or(false, |s| s.parse::<u128>().is_ok()),
+            "a string encoded u128 integer",
+        ),
+        MoveType::Address => (
+            key.as_str().map_or(false, |s| Address::from_str(s).is_ok()),
+            "a 0x prefixed hex string of at most 64 digits",
+        ),
+        MoveType::Vector { items } if **items == MoveType::U8 => (
+            key.as_str()
+                .map_or(false, |s| HexEncodedBytes::from_str(s).is_ok()),
+            "a 0x prefixed hex string of bytes",
+        ),
+        MoveType::Vector { items } => {
+            let values = match key.as_array() {
+                Some(values) => values,
+                None => bail!("{} must be an array for {}, but is {}", path, key_type, key),
+            };
+            for (index, value) in values.iter().enumerate() {
+                validate_key(items, value, &format!("{}[{}]", path, index))?;
+            }
+            return Ok(());
+        }
+        MoveType::Struct(tag) if is_string(tag) => (key.is_string(), "a string"),
+        MoveType::Struct(_) => (key.is_object(), "an object of the struct's fields"),
+        MoveType::Signer
+        | MoveType::Reference { .. }
+        | MoveType::GenericTypeParam { .. }
+        | MoveType::Unparsable(_) => bail!("{} can't be a table key", key_type),
+    };
+    if !valid {
+        bail!(
+            "{} must be {} for {}, but is {}",
+            path,
+            expected,
+            key_type,
+            key
+        );
+    }
+    Ok(())
+}
+
+/// Table Items request for the GetTableItems API, retrieving several items of a table at once
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BatchTableItemRequest {
+    /// Requests for each of the items, answered in the same order
+    pub items: Vec<TableItemRequest>,
+}
+
+/// The items retrieved by a GetTableItems request, in the order they were requested
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct BatchTableItemResponse {
+    pub items: Vec<TableItemResult>,
+    /// Root hash of the state at the requested ledger version, which the items' proofs are
+    /// against.  Only set when proofs are requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_root_hash: Option<HashValue>,
+}
+
+/// The result of retrieving one item of a batch
+///
+/// Exactly one of `value` and `error` is set, so that one bad key doesn't fail the whole batch
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct TableItemResult {
+    /// The value of the table item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<MoveValue>,
+    /// Why the table item couldn't be retrieved e.g. it's not in the table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AptosError>,
+    /// BCS serialized `SparseMerkleProof` of the table item against the state root hash, only
+    /// set when proofs are requested
+    ///
+    /// The proof is of the leaf keyed by the SHA3-256 `CryptoHash` of the item's `StateKey`,
+    /// and is made of the leaf found on the path to that key, if any, then the hashes of the
+    /// siblings on the path, ordered from the bottom level to the root.  If the item is in the
+    /// table, the leaf is keyed by that hash and holds the hash of the item's `StateValue`.
+    /// Otherwise this proves the item isn't in the table, so items which aren't found still
+    /// have a proof.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<HexEncodedBytes>,
+}
+
+/// Table Items Page request for the GetTableItemsPage API, retrieving a page of a table's items
+///
+/// Items are ordered by their raw state keys, being the table handle followed by the BCS
+/// serialized key, rather than by the Move ordering of their keys
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableIterRequest {
+    /// Cursor of the page to retrieve, as returned with the previous page
+    ///
+    /// If not provided, the first page is retrieved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<HexEncodedBytes>,
+    /// Maximum number of items in the page, up to the node's maximum page size
+    ///
+    /// If not provided, it will be the default page size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u16>,
+}
+
+/// A page of a table's items, retrieved by a GetTableItemsPage request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableIterResponse {
+    /// The items of the page, in the order of their raw state keys
+    pub items: Vec<TableIterItem>,
+    /// Cursor of the next page, which is only set if there are more items
+    ///
+    /// Retrieve the next page at the same ledger version, so that the pages are consistent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<HexEncodedBytes>,
+    /// Ledger version the page was retrieved at
+    pub ledger_version: U64,
+}
+
+/// An item of a table, as it's stored
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableIterItem {
+    /// The BCS serialized key of the item
+    pub key: HexEncodedBytes,
+    /// The BCS serialized value of the item
+    pub value: HexEncodedBytes,
+}
+
+/// A table, along with the key and value types declared for it
+///
+/// These are the types to give in a `TableItemRequest` for the table's items
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableInfo {
+    /// Handle of the table
+    pub handle: Address,
+    pub key_type: MoveType,
+    pub value_type: MoveType,
+}
+
+impl TableInfo {
+    /// Finds the tables held in the fields of a resource, by field name
+    ///
+    /// `struct_def` is the declaration of the resource's struct, from its module, whose field
+    /// types declare the key and value types of each `Table` and `TableWithLength`.  Generic
+    /// type params in them are replaced with the resource's.
+    pub fn from_resource(
+        resource: &MoveResource,
+        struct_def: &MoveStruct,
+    ) -> anyhow::Result<BTreeMap<IdentifierWrapper, TableInfo>> {
+        if resource.typ.name != struct_def.name {
+            return Err(format_err!(
+                "struct {} is not the declaration of resource {}",
+                struct_def.name,
+                resource.typ
+            ));
+        }
+        let mut tables = BTreeMap::new();
+        for field in &struct_def.fields {
+            let typ = substitute(&field.typ, &resource.typ.generic_type_params);
+            let tag = match typ {
+                MoveType::Struct(ref tag) => tag,
+                _ => continue,
+            };
+            let handle_path = match table_handle_path(tag) {
+                Some(handle_path) => handle_path,
+                None => continue,
+            };
+            let mut value = resource.data.0.get(&field.name);
+            for key in handle_path {
+                value = value.and_then(|value| value.get(key));
+            }
+            let handle = value.and_then(Value::as_str).ok_or_else(|| {
+                format_err!("table {} of {} has no handle", field.name, resource.typ)
+            })?;
+            tables.insert(
+                field.name.clone(),
+                TableInfo {
+                    handle: Address::from_str(handle)?,
+                    key_type: tag.generic_type_params[0].clone(),
+                    value_type: tag.generic_type_params[1].clone(),
+                },
+            );
+        }
+        Ok(tables)
+    }
+
+    /// Finds every table held in a resource, including those nested in vectors and structs, in
+    /// the order of its fields
+    ///
+    /// `modules` are the modules declaring the resource's struct, and each struct nested in it
+    /// with fields.  A struct whose value isn't a JSON object, such as a `String`, can't hold a
+    /// table and needn't be declared.  The items of tables aren't searched, as they're in storage
+    /// rather than in the resource.
+    pub fn find_in_resource(
+        resource: &MoveResource,
+        modules: &[MoveModule],
+    ) -> anyhow::Result<Vec<ResourceTable>> {
+        let data = serde_json::to_value(&resource.data)?;
+        let mut tables = Vec::new();
+        find_tables(
+            &MoveType::Struct(resource.typ.clone()),
+            &data,
+            String::new(),
+            modules,
+            &mut tables,
+        )?;
+        Ok(tables)
+    }
+
+    /// Builds a request for the item of the table with `key`, encoded as the key type
+    pub fn item_request(&self, key: Value) -> TableItemRequest {
+        TableItemRequest {
+            key_type: self.key_type.clone(),
+            value_type: self.value_type.clone(),
+            key: Some(key),
+            key_bytes: None,
+        }
+    }
+
+    /// Builds a request for the item of the table with `key_bytes`, the hex string of its BCS
+    /// serialized key
+    pub fn item_request_from_bytes(&self, key_bytes: &str) -> anyhow::Result<TableItemRequest> {
+        TableItemRequest::from_key_bytes(self.key_type.clone(), self.value_type.clone(), key_bytes)
+    }
+}
+
+/// A table found in a resource by `TableInfo::find_in_resource`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceTable {
+    /// Path of fields to the table from the resource, such as `pools[1].stakes`, with the index of
+    /// the item for each vector
+    pub field_path: String,
+    pub table: TableInfo,
+}
+
+/// Path to the handle in a `Table` or `TableWithLength`, if `tag` is either
+fn table_handle_path(tag: &MoveStructTag) -> Option<&'static [&'static str]> {
+    // A `TableWithLength` wraps a `Table`, with its handle
+    if is_table(tag, "table", "Table") {
+        Some(&["handle"])
+    } else if is_table(tag, "table_with_length", "TableWithLength") {
+        Some(&["inner", "handle"])
+    } else {
+        None
+    }
+}
+
+/// Adds the tables in `value`, of type `typ` at `path`, to `tables`
+fn find_tables(
+    typ: &MoveType,
+    value: &Value,
+    path: String,
+    modules: &[MoveModule],
+    tables: &mut Vec<ResourceTable>,
+) -> anyhow::Result<()> {
+    match typ {
+        // A `vector<u8>` is a hex string rather than an array, and holds no tables either way
+        MoveType::Vector { items } => {
+            for (index, item) in value.as_array().into_iter().flatten().enumerate() {
+                find_tables(items, item, format!("{}[{}]", path, index), modules, tables)?;
+            }
+        }
+        MoveType::Struct(tag) => {
+            if let Some(handle_path) = table_handle_path(tag) {
+                let handle = handle_path
+                    .iter()
+                    .try_fold(value, |value, key| value.get(key))
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| format_err!("table {} has no handle", path))?;
+                tables.push(ResourceTable {
+                    field_path: path,
+                    table: TableInfo {
+                        handle: Address::from_str(handle)?,
+                        key_type: tag.generic_type_params[0].clone(),
+                        value_type: tag.generic_type_params[1].clone(),
+                    },
+                });
+                return Ok(());
+            }
+            let fields = match value.as_object() {
+                Some(fields) => fields,
+                None => return Ok(()),
+            };
+            let struct_def = modules
+                .iter()
+                .filter(|module| module.address == tag.address && module.name == tag.module)
+                .flat_map(|module| &module.structs)
+                .find(|struct_def| struct_def.name == tag.name)
+                .ok_or_else(|| format_err!("struct {} isn't declared in the modules", tag))?;
+            for field in &struct_def.fields {
+                let field_path = if path.is_empty() {
+                    field.name.to_string()
+                } else {
+                    format!("{}.{}", path, field.name)
+                };
+                let value = fields
+                    .get(field.name.as_str())
+                    .ok_or_else(|| format_err!("field {} of {} is missing", field_path, tag))?;
+                let typ = substitute(&field.typ, &tag.generic_type_params);
+                find_tables(&typ, value, field_path, modules, tables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn is_table(tag: &MoveStructTag, module: &str, name: &str) -> bool {
+    tag.address == Address::from(CORE_CODE_ADDRESS)
+        && tag.module.as_str() == module
+        && tag.name.as_str() == name
+        && tag.generic_type_params.len() == 2
+}
+
+/// Replaces the generic type params in `typ` with `type_params`
+fn substitute(typ: &MoveType, type_params: &[MoveType]) -> MoveType {
+    match typ {
+        MoveType::GenericTypeParam { index } => type_params
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| typ.clone()),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, type_params)),
+        },
+        MoveType::Struct(tag) => MoveType::Struct(MoveStructTag::new(
+            tag.address,
+            tag.module.clone(),
+            tag.name.clone(),
+            tag.generic_type_params
+                .iter()
+                .map(|param| substitute(param, type_params))
+                .collect(),
+        )),
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute(to, type_params)),
+        },
+        _ => typ.clone(),
+    }
+}
+
+/// Request to subscribe to the changes of every item in a table
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableSubscriptionRequest {
+    /// Handle of the table to subscribe to
+    pub handle: Address,
+    pub key_type: MoveType,
+    pub value_type: MoveType,
+}
+
+/// A change to an item of a subscribed table, streamed for every committed write
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableChangeEvent {
     /// The value of the table item's key
     pub key: Value,
+    /// The new value of the table item, or `None` if the item was deleted
+    pub new_value: Option<Value>,
+    /// Version of the transaction which changed the item
+    pub version: U64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, AptosError, AptosErrorCode, BatchTableItemRequest, BatchTableItemResponse,
+        HashValue, HexEncodedBytes, IdentifierWrapper, MoveModule, MoveResource, MoveStruct,
+        MoveType, MoveValue, TableChangeEvent, TableInfo, TableItemRequest, TableItemResult,
+        TableIterItem, TableIterRequest, TableIterResponse, TableSubscriptionRequest, U64,
+    };
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_item_request_json() {
+        let json = json!({
+            "key_type": "u64",
+            "value_type": "u8",
+            "key": "1",
+        });
+        let request: TableItemRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(request.key, Some(json!("1")));
+        assert_eq!(request.key_bytes, None);
+        assert_eq!(serde_json::to_value(request).unwrap(), json);
+
+        // Keys may instead be given already BCS serialized
+        let json = json!({
+            "key_type": "u64",
+            "value_type": "u8",
+            "key_bytes": "0x0100000000000000",
+        });
+        let request: TableItemRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            request,
+            TableItemRequest {
+                key_type: MoveType::U64,
+                value_type: MoveType::U8,
+                key: None,
+                key_bytes: Some(HexEncodedBytes(vec![1, 0, 0, 0, 0, 0, 0, 0])),
+            }
+        );
+        assert_eq!(serde_json::to_value(request).unwrap(), json);
+    }
+
+    #[test]
+    fn test_validate_key() {
+        let request = |key_type: &str, key: Value| TableItemRequest {
+            key_type: MoveType::from_str(key_type).unwrap(),
+            value_type: MoveType::U8,
+            key: Some(key),
+            key_bytes: None,
+        };
+        let address = format!("0x{}", "a".repeat(64));
+        for (key_type, key) in [
+            ("bool", json!(true)),
+            ("u8", json!(255)),
+            ("u64", json!("18446744073709551615")),
+            ("u128", json!("340282366920938463463374607431768211455")),
+            ("address", json!("0x1")),
+            ("address", json!(address)),
+            ("vector<u8>", json!("0x0102")),
+            ("vector<u64>", json!(["1", "2"])),
+            ("0x1::string::String", json!("name")),
+            ("0x1::guid::ID", json!({"creation_num": "0", "addr": "0x1"})),
+        ] {
+            request(key_type, key.clone())
+                .validate_key()
+                .unwrap_or_else(|err| {
+                    panic!("{} should be a valid {} key: {}", key, key_type, err)
+                });
+        }
+
+        let address = format!("0x{}", "a".repeat(65));
+        for (key_type, key, error) in [
+            ("bool", json!("true"), "key must be a boolean for bool"),
+            (
+                "u8",
+                json!(256),
+                "key must be an integer from 0 to 255 for u8",
+            ),
+            (
+                "u8",
+                json!("1"),
+                "key must be an integer from 0 to 255 for u8",
+            ),
+            (
+                "u64",
+                json!(1),
+                "key must be a string encoded u64 integer for u64",
+            ),
+            (
+                "u64",
+                json!("-1"),
+                "key must be a string encoded u64 integer for u64",
+            ),
+            (
+                "u128",
+                json!("1.5"),
+                "key must be a string encoded u128 integer for u128",
+            ),
+            (
+                "address",
+                json!("0xg"),
+                "key must be a 0x prefixed hex string",
+            ),
+            (
+                "address",
+                json!(address),
+                "key must be a 0x prefixed hex string",
+            ),
+            ("address", json!(1), "key must be a 0x prefixed hex string"),
+            (
+                "vector<u8>",
+                json!([1, 2]),
+                "key must be a 0x prefixed hex string of bytes",
+            ),
+            (
+                "vector<u64>",
+                json!(["1", 2]),
+                "key[1] must be a string encoded u64 integer",
+            ),
+            (
+                "vector<u64>",
+                json!("1"),
+                "key must be an array for vector<u64>",
+            ),
+            (
+                "0x1::string::String",
+                json!(1),
+                "key must be a string for 0x1::string::String",
+            ),
+            (
+                "0x1::guid::ID",
+                json!("0x1"),
+                "key must be an object of the struct's fields",
+            ),
+            ("signer", json!("0x1"), "signer can't be a table key"),
+        ] {
+            let err = request(key_type, key)
+                .validate_key()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(error), "{}", err);
+        }
+
+        // Keys given as bytes aren't checked
+        let request = TableItemRequest {
+            key: None,
+            key_bytes: Some(HexEncodedBytes(vec![1])),
+            ..request("u64", json!("1"))
+        };
+        request.validate_key().unwrap();
+    }
+
+    #[test]
+    fn test_canonicalize_key() {
+        let canonical_key = |key_type: &str, key: Value| {
+            let mut request = TableItemRequest {
+                key_type: MoveType::from_str(key_type).unwrap(),
+                value_type: MoveType::U8,
+                key: Some(key),
+                key_bytes: None,
+            };
+            request.canonicalize_key();
+            request.validate_key().unwrap();
+            request.key.unwrap()
+        };
+        let address = format!("0x{}", "A".repeat(64));
+        for (key_type, canonical, keys) in [
+            ("u8", json!(1), vec![json!(1), json!("1"), json!("01")]),
+            (
+                "u64",
+                json!("1"),
+                vec![json!("1"), json!(1), json!("+1"), json!("001")],
+            ),
+            (
+                "u128",
+                json!("1"),
+                vec![json!("1"), json!(1), json!("0001")],
+            ),
+            (
+                "address",
+                json!("0x1"),
+                vec![
+                    json!("0x1"),
+                    json!("0x0001"),
+                    json!(format!("0x{:0>64}", 1)),
+                ],
+            ),
+            (
+                "address",
+                json!(address.to_lowercase()),
+                vec![json!(address), json!(address.to_lowercase())],
+            ),
+            (
+                "vector<u8>",
+                json!("0x0a0b"),
+                vec![json!("0x0a0b"), json!("0x0A0B"), json!([10, 11])],
+            ),
+            (
+                "vector<u64>",
+                json!(["1", "2"]),
+                vec![json!(["1", "2"]), json!([1, "02"])],
+            ),
+            (
+                "vector<address>",
+                json!(["0x1"]),
+                vec![json!(["0x1"]), json!(["0x01"])],
+            ),
+            (
+                "0x1::guid::ID",
+                json!({"addr": "0x1", "creation_num": "0"}),
+                vec![
+                    json!({"addr": "0x1", "creation_num": "0"}),
+                    json!({"creation_num": "0", "addr": "0x1"}),
+                ],
+            ),
+        ] {
+            for key in keys {
+                let actual = canonical_key(key_type, key.clone());
+                assert_eq!(actual, canonical, "{} as {}", key, key_type);
+                // Objects compare equal whatever the order of their fields, so compare them written
+                assert_eq!(actual.to_string(), canonical.to_string());
+            }
+        }
+
+        // Keys which aren't equivalent to a value of the key type are left for validation to reject
+        for (key_type, key, error) in [
+            (
+                "u8",
+                json!("256"),
+                "key must be an integer from 0 to 255 for u8",
+            ),
+            (
+                "u64",
+                json!(-1),
+                "key must be a string encoded u64 integer for u64",
+            ),
+            (
+                "u64",
+                json!(1.5),
+                "key must be a string encoded u64 integer for u64",
+            ),
+            (
+                "address",
+                json!("0xg"),
+                "key must be a 0x prefixed hex string",
+            ),
+            (
+                "vector<u8>",
+                json!([256]),
+                "key must be a 0x prefixed hex string of bytes",
+            ),
+        ] {
+            let mut request = TableItemRequest {
+                key_type: MoveType::from_str(key_type).unwrap(),
+                value_type: MoveType::U8,
+                key: Some(key.clone()),
+                key_bytes: None,
+            };
+            request.canonicalize_key();
+            assert_eq!(request.key, Some(key));
+            let err = request.validate_key().unwrap_err().to_string();
+            assert!(err.contains(error), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_key_bytes() {
+        let request = |key_type: &str, key_bytes: &str| {
+            let key_type = MoveType::from_str(key_type).unwrap();
+            TableItemRequest::from_key_bytes(key_type, MoveType::U8, key_bytes)
+        };
+        let u128_max = format!("0x{}", "ff".repeat(16));
+        let address = format!("0x{}", "0a".repeat(32));
+        let long_vector = format!("0x8001{}", "00".repeat(128));
+        for (key_type, key_bytes) in [
+            ("bool", "0x00"),
+            ("bool", "0x01"),
+            ("u8", "ff"),
+            ("u64", "0x0100000000000000"),
+            ("u128", u128_max.as_str()),
+            ("address", address.as_str()),
+            ("vector<u8>", "0x020102"),
+            ("vector<u8>", "0x00"),
+            ("vector<u8>", long_vector.as_str()),
+            ("vector<u64>", "0x010100000000000000"),
+            ("0x1::string::String", "0x03616263"),
+            ("vector<0x1::string::String>", "0x020361626300"),
+            ("0x1::guid::ID", "0x00"),
+        ] {
+            request(key_type, key_bytes).unwrap_or_else(|err| {
+                panic!("{} should be a valid {} key: {}", key_bytes, key_type, err)
+            });
+        }
+        assert_eq!(
+            request("u64", "0x0100000000000000").unwrap().key_bytes,
+            Some(HexEncodedBytes(vec![1, 0, 0, 0, 0, 0, 0, 0]))
+        );
+
+        let short_address = format!("0x{}", "0a".repeat(20));
+        let long_address = format!("0x{}", "0a".repeat(33));
+        for (key_type, key_bytes, error) in [
+            ("u64", "", "key_bytes must not be empty"),
+            ("u64", "0x", "key_bytes must not be empty"),
+            (
+                "u8",
+                "0x012",
+                "key_bytes must have an even number of hex digits, but 0x012 has 3",
+            ),
+            ("u8", "0xgg", "decode hex-encoded string"),
+            (
+                "bool",
+                "0x02",
+                "key_bytes must encode a bool as 0x00 or 0x01, but has 0x02",
+            ),
+            (
+                "u8",
+                "0x0100",
+                "key_bytes has 1 bytes left over after the u8 key",
+            ),
+            (
+                "u64",
+                "0x01",
+                "key_bytes ends 7 bytes before the end of the u64 key",
+            ),
+            (
+                "u128",
+                "0x0000000000000000",
+                "ends 8 bytes before the end of the u128 key",
+            ),
+            (
+                "address",
+                short_address.as_str(),
+                "ends 12 bytes before the end of the address key",
+            ),
+            (
+                "address",
+                long_address.as_str(),
+                "has 1 bytes left over after the address key",
+            ),
+            (
+                "vector<u8>",
+                "0x0301",
+                "ends 1 bytes before the end of the u8 key",
+            ),
+            (
+                "vector<u8>",
+                "0x80",
+                "ends 1 bytes before the end of the vector<u8> key",
+            ),
+            (
+                "vector<u8>",
+                "0x8000",
+                "key_bytes has an invalid length for vector<u8>",
+            ),
+            (
+                "0x1::string::String",
+                "0x02ffff",
+                "must encode a UTF-8 string",
+            ),
+            ("signer", address.as_str(), "signer can't be a table key"),
+        ] {
+            let err = request(key_type, key_bytes).unwrap_err().to_string();
+            assert!(err.contains(error), "{}", err);
+        }
+
+        // Tables build requests for their raw keys
+        let table = TableInfo {
+            handle: Address::from_str("0x1").unwrap(),
+            key_type: MoveType::Address,
+            value_type: MoveType::U64,
+        };
+        let request = table.item_request_from_bytes(&address).unwrap();
+        assert_eq!(request.key, None);
+        assert_eq!(request.key_bytes, Some(HexEncodedBytes(vec![10; 32])));
+        assert!(table.item_request_from_bytes("0x0a").is_err());
+    }
+
+    #[test]
+    fn test_check_value_type() {
+        let request = |value_type: &str| TableItemRequest {
+            key_type: MoveType::U64,
+            value_type: MoveType::from_str(value_type).unwrap(),
+            key: Some(json!("1")),
+            key_bytes: None,
+        };
+        let table_type = |value_type: &str| MoveType::from_str(value_type).unwrap();
+        for value_type in [
+            "u64",
+            "vector<0x1::string::String>",
+            "0x1::table::Table<u8, vector<u64>>",
+        ] {
+            request(value_type)
+                .check_value_type(&table_type(value_type))
+                .unwrap();
+        }
+
+        for (value_type, table_value_type, error) in [
+            (
+                "u128",
+                "u64",
+                "value_type u128 doesn't match the table's value type u64",
+            ),
+            (
+                "0x1::guid::ID",
+                "0x1::guid::GUID",
+                "doesn't match the table's value type",
+            ),
+            (
+                "0x1::table::Table<u8, u64>",
+                "0x1::table::Table<u8, u8>",
+                "type argument 1 of 0x1::table::Table is u64 rather than u8",
+            ),
+            (
+                "vector<0x1::table::Table<u8, 0x1::option::Option<u64>>>",
+                "vector<0x1::table::Table<u8, 0x1::option::Option<u128>>>",
+                "type argument 0 of 0x1::option::Option is u64 rather than u128",
+            ),
+        ] {
+            let err = request(value_type)
+                .check_value_type(&table_type(table_value_type))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(error), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_batch_item_json() {
+        let json = json!({
+            "items": [
+                {
+                    "key_type": "u64",
+                    "value_type": "u8",
+                    "key": "1",
+                },
+                {
+                    "key_type": "u64",
+                    "value_type": "u8",
+                    "key": "2",
+                },
+            ],
+        });
+        let request: BatchTableItemRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(request.items.len(), 2);
+        assert_eq!(request.items[1].key, Some(json!("2")));
+        assert_eq!(serde_json::to_value(request).unwrap(), json);
+
+        // Results are positional, each with either a value or an error
+        let response = BatchTableItemResponse {
+            items: vec![
+                TableItemResult {
+                    value: Some(MoveValue::U8(1)),
+                    error: None,
+                    proof: None,
+                },
+                TableItemResult {
+                    value: None,
+                    error: Some(AptosError::new_with_error_code(
+                        "Table Item not found",
+                        AptosErrorCode::TableItemNotFound,
+                    )),
+                    proof: None,
+                },
+            ],
+            state_root_hash: None,
+        };
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "items": [
+                    {
+                        "value": 1,
+                    },
+                    {
+                        "error": {
+                            "message": "Table Item not found",
+                            "error_code": "table_item_not_found",
+                            "vm_error_code": null,
+                        },
+                    },
+                ],
+            })
+        );
+
+        // Proofs, and the root they're against, are only included when requested
+        let response = BatchTableItemResponse {
+            items: vec![TableItemResult {
+                value: Some(MoveValue::U8(1)),
+                error: None,
+                proof: Some(HexEncodedBytes(vec![0, 0])),
+            }],
+            state_root_hash: Some(HashValue::from(aptos_crypto::HashValue::zero())),
+        };
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "items": [
+                    {
+                        "value": 1,
+                        "proof": "0x0000",
+                    },
+                ],
+                "state_root_hash": format!("0x{}", "0".repeat(64)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_iter_json() {
+        // The first page has no cursor, and the limit is optional
+        let request: TableIterRequest = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(request, TableIterRequest::default());
+        let json = json!({
+            "cursor": "0x01",
+            "limit": 10,
+        });
+        let request: TableIterRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(request.cursor, Some(HexEncodedBytes(vec![1])));
+        assert_eq!(request.limit, Some(10));
+        assert_eq!(serde_json::to_value(request).unwrap(), json);
+
+        let response = TableIterResponse {
+            items: vec![TableIterItem {
+                key: HexEncodedBytes(vec![0]),
+                value: HexEncodedBytes(vec![1, 0]),
+            }],
+            cursor: Some(HexEncodedBytes(vec![1])),
+            ledger_version: U64(42),
+        };
+        let json = json!({
+            "items": [
+                {
+                    "key": "0x00",
+                    "value": "0x0100",
+                },
+            ],
+            "cursor": "0x01",
+            "ledger_version": "42",
+        });
+        assert_eq!(serde_json::to_value(&response).unwrap(), json);
+        assert_eq!(
+            serde_json::from_value::<TableIterResponse>(json).unwrap(),
+            response
+        );
+
+        // The last page has no cursor
+        let response = TableIterResponse {
+            cursor: None,
+            ..response
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json.get("cursor").is_none());
+        assert_eq!(
+            serde_json::from_value::<TableIterResponse>(json).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn test_table_info_from_resource() {
+        let struct_def: MoveStruct = serde_json::from_value(json!({
+            "name": "Registry",
+            "is_native": false,
+            "abilities": ["key"],
+            "generic_type_params": [
+                {
+                    "constraints": [],
+                    "is_phantom": false,
+                },
+            ],
+            "fields": [
+                {
+                    "name": "balances",
+                    "type": "0x1::table::Table<address, u64>",
+                },
+                {
+                    "name": "count",
+                    "type": "u64",
+                },
+                {
+                    "name": "names",
+                    "type": "0x1::table_with_length::TableWithLength<T0, 0x1::string::String>",
+                },
+            ],
+        }))
+        .unwrap();
+        let resource: MoveResource = serde_json::from_value(json!({
+            "type": "0x1::registry::Registry<vector<u8>>",
+            "data": {
+                "balances": {
+                    "handle": "0x1b854694ae746cdbd8d44186ca4929b2b337df21d1c74633be19b2710552fdca",
+                },
+                "count": "2",
+                "names": {
+                    "inner": {
+                        "handle": "0x2",
+                    },
+                    "length": "1",
+                },
+            },
+        }))
+        .unwrap();
+
+        // Both kinds of table are found, with the resource's generic type params filled in
+        let tables = TableInfo::from_resource(&resource, &struct_def).unwrap();
+        assert_eq!(
+            tables.keys().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["balances", "names"]
+        );
+        let balances = &tables[&IdentifierWrapper::from_str("balances").unwrap()];
+        assert_eq!(
+            balances,
+            &TableInfo {
+                handle: Address::from_str(
+                    "0x1b854694ae746cdbd8d44186ca4929b2b337df21d1c74633be19b2710552fdca"
+                )
+                .unwrap(),
+                key_type: MoveType::Address,
+                value_type: MoveType::U64,
+            }
+        );
+        let names = &tables[&IdentifierWrapper::from_str("names").unwrap()];
+        assert_eq!(names.handle, Address::from_str("0x2").unwrap());
+        assert_eq!(names.key_type.to_string(), "vector<u8>");
+        assert_eq!(names.value_type.to_string(), "0x1::string::String");
+
+        // The info fills in a request for an item
+        let request = balances.item_request(json!("0x1"));
+        assert_eq!(
+            serde_json::to_value(request).unwrap(),
+            json!({
+                "key_type": "address",
+                "value_type": "u64",
+                "key": "0x1",
+            })
+        );
+
+        // Tables without handles are reported rather than skipped
+        let mut resource = resource;
+        resource
+            .data
+            .0
+            .remove(&IdentifierWrapper::from_str("names").unwrap());
+        assert!(TableInfo::from_resource(&resource, &struct_def).is_err());
+    }
+
+    #[test]
+    fn test_table_info_find_in_resource() {
+        let struct_def = |name: &str, generic: bool, fields: Value| {
+            let generic_type_params = if generic {
+                json!([{ "constraints": [], "is_phantom": false }])
+            } else {
+                json!([])
+            };
+            json!({
+                "name": name,
+                "is_native": false,
+                "abilities": ["store"],
+                "generic_type_params": generic_type_params,
+                "fields": fields,
+            })
+        };
+        let mut module: MoveModule = serde_json::from_value(json!({
+            "address": "0xcafe",
+            "name": "pools",
+            "friends": [],
+            "exposed_functions": [],
+            "structs": [
+                struct_def("Registry", true, json!([
+                    { "name": "pools", "type": "vector<0xcafe::pools::Pool<T0>>" },
+                    { "name": "names", "type": "0x1::table::Table<u64, 0x1::string::String>" },
+                    { "name": "label", "type": "0x1::string::String" },
+                    { "name": "meta", "type": "0xcafe::pools::Meta" },
+                ])),
+                struct_def("Pool", true, json!([
+                    {
+                        "name": "stakes",
+                        "type": "0x1::table_with_length::TableWithLength<address, T0>",
+                    },
+                    { "name": "id", "type": "u64" },
+                ])),
+                struct_def("Meta", false, json!([
+                    { "name": "history", "type": "0x1::table::Table<u64, vector<u8>>" },
+                    { "name": "tag", "type": "vector<u8>" },
+                ])),
+            ],
+        }))
+        .unwrap();
+        let resource: MoveResource = serde_json::from_value(json!({
+            "type": "0xcafe::pools::Registry<u64>",
+            "data": {
+                "pools": [
+                    { "stakes": { "inner": { "handle": "0x2" }, "length": "0" }, "id": "0" },
+                    { "stakes": { "inner": { "handle": "0x3" }, "length": "1" }, "id": "1" },
+                ],
+                "names": { "handle": "0x1" },
+                "label": "registry",
+                "meta": { "history": { "handle": "0x4" }, "tag": "0x01" },
+            },
+        }))
+        .unwrap();
+
+        // Tables in vectors and nested structs are found, in the order of the fields, with the
+        // generic type params filled in
+        let tables = TableInfo::find_in_resource(&resource, &[module.clone()]).unwrap();
+        assert_eq!(
+            tables
+                .iter()
+                .map(|table| (table.field_path.as_str(), table.table.handle.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("pools[0].stakes", "0x2".to_string()),
+                ("pools[1].stakes", "0x3".to_string()),
+                ("names", "0x1".to_string()),
+                ("meta.history", "0x4".to_string()),
+            ]
+        );
+        assert_eq!(tables[1].table.key_type, MoveType::Address);
+        assert_eq!(tables[1].table.value_type, MoveType::U64);
+        assert_eq!(
+            tables[2].table.value_type.to_string(),
+            "0x1::string::String"
+        );
+        assert_eq!(tables[3].table.value_type.to_string(), "vector<u8>");
+
+        // The info of a nested table fills in a request for its items like any other
+        assert_eq!(
+            serde_json::to_value(tables[3].table.item_request(json!("7"))).unwrap(),
+            json!({
+                "key_type": "u64",
+                "value_type": "vector<u8>",
+                "key": "7",
+            })
+        );
+
+        // A table without a handle is reported rather than skipped
+        let mut missing_handle = resource.clone();
+        missing_handle.data.0.insert(
+            IdentifierWrapper::from_str("meta").unwrap(),
+            json!({ "history": {} }),
+        );
+        let err = TableInfo::find_in_resource(&missing_handle, &[module.clone()]).unwrap_err();
+        assert!(err.to_string().contains("meta.history"), "{}", err);
+
+        // A nested struct must be declared in the modules to be searched
+        module
+            .structs
+            .retain(|struct_def| struct_def.name.as_str() != "Meta");
+        let err = TableInfo::find_in_resource(&resource, &[module]).unwrap_err();
+        assert!(err.to_string().contains("0xcafe::pools::Meta"), "{}", err);
+    }
+
+    #[test]
+    fn test_subscription_request_json() {
+        let json = json!({
+            "handle": "0x1b854694ae746cdbd8d44186ca4929b2b337df21d1c74633be19b2710552fdca",
+            "key_type": "address",
+            "value_type": "u128",
+        });
+        let request: TableSubscriptionRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            request,
+            TableSubscriptionRequest {
+                handle: Address::from_str(
+                    "0x1b854694ae746cdbd8d44186ca4929b2b337df21d1c74633be19b2710552fdca"
+                )
+                .unwrap(),
+                key_type: MoveType::Address,
+                value_type: MoveType::U128,
+            }
+        );
+
+        let val: Value = serde_json::to_value(request).unwrap();
+        assert_eq!(val, json);
+    }
+
+    #[test]
+    fn test_change_event_json() {
+        let event = TableChangeEvent {
+            key: json!("0x1"),
+            new_value: Some(json!("18446744073709551616")),
+            version: U64(42),
+        };
+        let val: Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            val,
+            json!({
+                "key": "0x1",
+                "new_value": "18446744073709551616",
+                "version": "42",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<TableChangeEvent>(val).unwrap(),
+            event
+        );
+
+        // Deleted items have no new value
+        let deleted = TableChangeEvent {
+            new_value: None,
+            ..event
+        };
+        let val: Value = serde_json::to_value(&deleted).unwrap();
+        assert_eq!(val["new_value"], Value::Null);
+        assert_eq!(
+            serde_json::from_value::<TableChangeEvent>(val).unwrap(),
+            deleted
+        );
+    }
 }
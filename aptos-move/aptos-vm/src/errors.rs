@@ -32,9 +32,12 @@ pub const EBAD_CHAIN_ID: u64 = 1007;
 pub const ESEQUENCE_NUMBER_TOO_BIG: u64 = 1008;
 // Counts of secondary keys and addresses don't match.
 pub const ESECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH: u64 = 1009;
+// Chain is paused until activation, and the sender is not allowed to submit transactions.
+pub const ECHAIN_PAUSED: u64 = 1010;
 
 const INVALID_ARGUMENT: u8 = 1;
 const LIMIT_EXCEEDED: u8 = 2;
+const INVALID_STATE: u8 = 3;
 
 fn error_split(code: u64) -> (u8, u64) {
     let reason = code & 0xffff;
@@ -88,6 +91,8 @@ pub fn convert_prologue_error(
                 (INVALID_ARGUMENT, ESECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH) => {
                     StatusCode::SECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH
                 }
+                // User transactions are gated until the chain is activated
+                (INVALID_STATE, ECHAIN_PAUSED) => StatusCode::FEATURE_UNDER_GATING,
                 (category, reason) => {
                     log_context.alert();
                     error!(
@@ -5,16 +5,26 @@
 module aptos_framework::chain_status {
     use aptos_framework::system_addresses;
     use std::error;
+    use std::vector;
 
     friend aptos_framework::genesis;
 
     /// Marker to publish at the end of genesis.
     struct GenesisEndMarker has key {}
 
+    /// Marker published by genesis when the chain starts paused. While it exists, only the
+    /// `allowed_senders` can submit transactions, so that they can activate the chain through
+    /// governance.
+    struct PausedMarker has key {
+        allowed_senders: vector<address>,
+    }
+
     /// The blockchain is not in the operating status.
     const ENOT_OPERATING: u64 = 1;
     /// The blockchain is not in the genesis status.
     const ENOT_GENESIS: u64 = 2;
+    /// The blockchain is not paused.
+    const ENOT_PAUSED: u64 = 3;
 
     /// Marks that genesis has finished.
     public(friend) fun set_genesis_end(aptos_framework: &signer) {
@@ -22,6 +32,34 @@ module aptos_framework::chain_status {
         move_to(aptos_framework, GenesisEndMarker {});
     }
 
+    /// Pauses transactions from every account except `allowed_senders`, until `activate` is called.
+    public(friend) fun set_paused(aptos_framework: &signer, allowed_senders: vector<address>) {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        move_to(aptos_framework, PausedMarker { allowed_senders });
+    }
+
+    /// Lifts the pause set at genesis, so that every account can submit transactions. This is
+    /// expected to be called by a governance proposal.
+    public fun activate(aptos_framework: &signer) acquires PausedMarker {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        assert!(is_paused(), error::invalid_state(ENOT_PAUSED));
+        let PausedMarker { allowed_senders: _ } = move_from<PausedMarker>(@aptos_framework);
+    }
+
+    /// Helper function to determine if transactions are paused until the chain is activated.
+    public fun is_paused(): bool {
+        exists<PausedMarker>(@aptos_framework)
+    }
+
+    /// Helper function to determine if `sender` can submit transactions, which is only
+    /// restricted while the chain is paused.
+    public fun is_sender_allowed(sender: address): bool acquires PausedMarker {
+        if (!is_paused()) {
+            return true
+        };
+        vector::contains(&borrow_global<PausedMarker>(@aptos_framework).allowed_senders, &sender)
+    }
+
     /// Helper function to determine if Aptos is in genesis state.
     public fun is_genesis(): bool {
         !exists<GenesisEndMarker>(@aptos_framework)
@@ -43,4 +81,23 @@ module aptos_framework::chain_status {
     public fun assert_genesis() {
         assert!(is_genesis(), error::invalid_state(ENOT_OPERATING));
     }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_paused_until_activated(aptos_framework: signer) acquires PausedMarker {
+        assert!(!is_paused(), 0);
+        set_paused(&aptos_framework, vector[@0xcafe]);
+        assert!(is_paused(), 1);
+        assert!(is_sender_allowed(@0xcafe), 2);
+        assert!(!is_sender_allowed(@0xbeef), 3);
+
+        activate(&aptos_framework);
+        assert!(!is_paused(), 4);
+        assert!(is_sender_allowed(@0xbeef), 5);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x30003)]
+    fun test_activate_when_not_paused(aptos_framework: signer) acquires PausedMarker {
+        activate(&aptos_framework);
+    }
 }
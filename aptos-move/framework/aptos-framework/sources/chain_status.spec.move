@@ -3,6 +3,10 @@ spec aptos_framework::chain_status {
         pragma verify=false;
     }
 
+    spec set_paused {
+        pragma verify=false;
+    }
+
     spec schema RequiresIsOperating {
         requires is_operating();
     }
@@ -1,6 +1,7 @@
 /// Maintains feature flags.
 module aptos_framework::features {
     use aptos_framework::system_addresses;
+    use std::error;
     use std::vector;
 
     // ============================================================================================
@@ -52,6 +53,18 @@ module aptos_framework::features {
         features: vector<u8>,
     }
 
+    /// Feature flags which are scheduled to be enabled at the start of a future epoch. The i-th entry
+    /// of `features` becomes enabled once the chain reaches the i-th entry of `epochs`.
+    struct ScheduledFeatures has key {
+        epochs: vector<u64>,
+        features: vector<u64>,
+    }
+
+    friend aptos_framework::reconfiguration;
+
+    /// The number of epochs does not match the number of scheduled features.
+    const ESCHEDULE_LENGTH_MISMATCH: u64 = 1;
+
     /// Function to enable and disable features. Can only be called by @aptos_framework.
     public fun change_feature_flags(aptos_framework: &signer, enable: vector<u64>, disable: vector<u64>)
     acquires Features {
@@ -74,6 +87,63 @@ module aptos_framework::features {
         };
     }
 
+    /// Schedule features to be enabled at the given epochs. Can only be called by @aptos_framework.
+    public fun schedule_feature_flags(aptos_framework: &signer, features: vector<u64>, epochs: vector<u64>)
+    acquires ScheduledFeatures {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        assert!(
+            vector::length(&features) == vector::length(&epochs),
+            error::invalid_argument(ESCHEDULE_LENGTH_MISMATCH),
+        );
+        if (!exists<ScheduledFeatures>(@aptos_framework)) {
+            move_to<ScheduledFeatures>(aptos_framework, ScheduledFeatures{epochs: vector[], features: vector[]})
+        };
+        let scheduled = borrow_global_mut<ScheduledFeatures>(@aptos_framework);
+        vector::append(&mut scheduled.features, features);
+        vector::append(&mut scheduled.epochs, epochs);
+    }
+
+    /// Enable all scheduled features whose activation epoch has been reached, and drop them from
+    /// the schedule. Called on every reconfiguration, with the epoch it starts.
+    public(friend) fun on_new_epoch(epoch: u64) acquires Features, ScheduledFeatures {
+        if (!exists<ScheduledFeatures>(@aptos_framework)) {
+            return
+        };
+        let scheduled = borrow_global_mut<ScheduledFeatures>(@aptos_framework);
+        let remaining_epochs = vector[];
+        let remaining_features = vector[];
+        let enable = vector[];
+        let i = 0;
+        let n = vector::length(&scheduled.features);
+        while (i < n) {
+            let feature = *vector::borrow(&scheduled.features, i);
+            let activation_epoch = *vector::borrow(&scheduled.epochs, i);
+            if (activation_epoch <= epoch) {
+                vector::push_back(&mut enable, feature)
+            } else {
+                vector::push_back(&mut remaining_features, feature);
+                vector::push_back(&mut remaining_epochs, activation_epoch)
+            };
+            i = i + 1
+        };
+        if (vector::is_empty(&enable)) {
+            return
+        };
+        scheduled.features = remaining_features;
+        scheduled.epochs = remaining_epochs;
+
+        if (!exists<Features>(@aptos_framework)) {
+            return
+        };
+        let features = &mut borrow_global_mut<Features>(@aptos_framework).features;
+        let i = 0;
+        let n = vector::length(&enable);
+        while (i < n) {
+            set(features, *vector::borrow(&enable, i), true);
+            i = i + 1
+        };
+    }
+
     /// Check whether the feature is enabled.
     fun is_enabled(feature: u64): bool acquires Features {
         exists<Features>(@aptos_framework) &&
@@ -132,4 +202,22 @@ module aptos_framework::features {
         assert!(is_enabled(17), 3);
         assert!(is_enabled(23), 4);
     }
+
+    #[test_only]
+    public fun is_enabled_for_test(feature: u64): bool acquires Features {
+        is_enabled(feature)
+    }
+
+    #[test(fx = @aptos_framework)]
+    fun test_scheduled_features(fx: signer) acquires Features, ScheduledFeatures {
+        change_feature_flags(&fx, vector[], vector[]);
+        schedule_feature_flags(&fx, vector[3, 7], vector[5, 8]);
+        on_new_epoch(4);
+        assert!(!is_enabled(3), 1);
+        on_new_epoch(5);
+        assert!(is_enabled(3), 2);
+        assert!(!is_enabled(7), 3);
+        on_new_epoch(9);
+        assert!(is_enabled(7), 4);
+    }
 }
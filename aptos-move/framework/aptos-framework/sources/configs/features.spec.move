@@ -9,6 +9,18 @@ spec aptos_framework::features {
         modifies global<Features>(@aptos_framework);
     }
 
+    spec schedule_feature_flags {
+        pragma opaque = true;
+        modifies global<ScheduledFeatures>(@aptos_framework);
+    }
+
+    spec on_new_epoch {
+        pragma opaque = true;
+        aborts_if false;
+        modifies global<Features>(@aptos_framework);
+        modifies global<ScheduledFeatures>(@aptos_framework);
+    }
+
     spec code_dependency_check_enabled {
         pragma opaque = true;
     }
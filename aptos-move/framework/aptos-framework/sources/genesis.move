@@ -27,6 +27,9 @@ module aptos_framework::genesis {
     use aptos_framework::version;
     use aptos_framework::vesting;
 
+    #[test_only]
+    use aptos_framework::features;
+
     const EDUPLICATE_ACCOUNT: u64 = 1;
     const EACCOUNT_DOES_NOT_EXIST: u64 = 2;
 
@@ -377,6 +380,12 @@ module aptos_framework::genesis {
         stake::join_validator_set_internal(operator, pool_address);
     }
 
+    /// Pauses transactions from every account except `allowed_senders` until the chain is activated
+    /// through governance.
+    fun set_paused(aptos_framework: &signer, allowed_senders: vector<address>) {
+        chain_status::set_paused(aptos_framework, allowed_senders);
+    }
+
     /// The last step of genesis.
     fun set_genesis_end(aptos_framework: &signer) {
         chain_status::set_genesis_end(aptos_framework);
@@ -507,4 +516,20 @@ module aptos_framework::genesis {
         create_account(aptos_framework, addr0, 23456);
         assert!(coin::balance<AptosCoin>(addr0) == 12345, 2);
     }
+
+    #[test(aptos_framework = @0x1)]
+    fun test_scheduled_feature_enabled_by_governance_reconfiguration(aptos_framework: &signer) {
+        setup();
+        initialize_aptos_coin(aptos_framework);
+        features::change_feature_flags(aptos_framework, vector[], vector[]);
+        features::schedule_feature_flags(aptos_framework, vector[3, 7], vector[1, 2]);
+        set_genesis_end(aptos_framework);
+        timestamp::update_global_time_for_test_secs(1);
+
+        // Setting the version reconfigures outside of a block prologue, starting epoch 1
+        version::set_version(aptos_framework, 2);
+        assert!(reconfiguration::current_epoch() == 1, 0);
+        assert!(features::is_enabled_for_test(3), 1);
+        assert!(!features::is_enabled_for_test(7), 2);
+    }
 }
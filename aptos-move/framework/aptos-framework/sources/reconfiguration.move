@@ -6,6 +6,7 @@ module aptos_framework::reconfiguration {
     use std::signer;
 
     use aptos_framework::account;
+    use aptos_framework::features;
     use aptos_framework::stake;
     use aptos_framework::system_addresses;
     use aptos_framework::timestamp;
@@ -15,7 +16,6 @@ module aptos_framework::reconfiguration {
     friend aptos_framework::aptos_governance;
     friend aptos_framework::block;
     friend aptos_framework::consensus_config;
-    friend aptos_framework::features;
     friend aptos_framework::gas_schedule;
     friend aptos_framework::genesis;
     friend aptos_framework::version;
@@ -117,6 +117,8 @@ module aptos_framework::reconfiguration {
 
         // Call stake to compute the new validator set and distribute rewards.
         stake::on_new_epoch();
+        // Enable the features scheduled for the epoch which is starting.
+        features::on_new_epoch(config_ref.epoch + 1);
         storage_gas::on_reconfig();
 
         assert!(current_time > config_ref.last_reconfiguration_time, error::invalid_state(EINVALID_BLOCK_TIME));
@@ -4,6 +4,7 @@ module aptos_framework::transaction_validation {
     use std::vector;
     use aptos_framework::aptos_coin::AptosCoin;
     use aptos_framework::chain_id;
+    use aptos_framework::chain_status;
     use aptos_framework::account;
     use aptos_framework::coin;
     use aptos_framework::system_addresses;
@@ -40,6 +41,7 @@ module aptos_framework::transaction_validation {
     const PROLOGUE_EBAD_CHAIN_ID: u64 = 1007;
     const PROLOGUE_ESEQUENCE_NUMBER_TOO_BIG: u64 = 1008;
     const PROLOGUE_ESECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH: u64 = 1009;
+    const PROLOGUE_ECHAIN_PAUSED: u64 = 1010;
 
     /// Only called during genesis to initialize system resources for this module.
     public(friend) fun initialize(
@@ -78,6 +80,10 @@ module aptos_framework::transaction_validation {
 
         let transaction_sender = signer::address_of(&sender);
         assert!(account::exists_at(transaction_sender), error::invalid_argument(PROLOGUE_EACCOUNT_DOES_NOT_EXIST));
+        assert!(
+            chain_status::is_sender_allowed(transaction_sender),
+            error::invalid_state(PROLOGUE_ECHAIN_PAUSED),
+        );
         assert!(
             txn_authentication_key == account::get_authentication_key(transaction_sender),
             error::invalid_argument(PROLOGUE_EINVALID_ACCOUNT_AUTH_KEY),
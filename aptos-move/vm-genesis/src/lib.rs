@@ -13,7 +13,7 @@ use aptos_crypto::{
 };
 use aptos_gas::{
     AbstractValueSizeGasParameters, AptosGasParameters, InitialGasSchedule, NativeGasParameters,
-    ToOnChainGasSchedule,
+    NumBytes, ToOnChainGasSchedule,
 };
 use aptos_types::account_config::aptos_test_root_address;
 use aptos_types::on_chain_config::{FeatureFlag, Features};
@@ -35,27 +35,44 @@ use move_deps::{
     move_core_types::{
         account_address::AccountAddress,
         identifier::Identifier,
-        language_storage::{ModuleId, TypeTag},
+        language_storage::{ModuleId, StructTag, TypeTag},
         resolver::MoveResolver,
         value::{serialize_values, MoveValue},
     },
+    move_ir_compiler::Compiler,
     move_vm_types::gas::UnmeteredGasMeter,
 };
 use once_cell::sync::Lazy;
 use rand::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
 
 // The seed is arbitrarily picked to produce a consistent key. XXX make this more formal?
 const GENESIS_SEED: [u8; 32] = [42; 32];
 
 const GENESIS_MODULE_NAME: &str = "genesis";
 const GOVERNANCE_MODULE_NAME: &str = "aptos_governance";
+const FEATURES_MODULE_NAME: &str = "features";
 const CODE_MODULE_NAME: &str = "code";
 const VERSION_MODULE_NAME: &str = "version";
+const TOKEN_MODULE_NAME: &str = "token";
+const MANAGED_COIN_MODULE_NAME: &str = "managed_coin";
+/// Name and symbol of the native coin, which no genesis coin may reuse
+pub const NATIVE_COIN_NAME: &str = "Aptos Coin";
+pub const NATIVE_COIN_SYMBOL: &str = "APT";
 
 const NUM_SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
 const MICRO_SECONDS_PER_SECOND: u64 = 1_000_000;
 const APTOS_COINS_BASE_WITH_DECIMALS: u64 = u64::pow(10, 8);
+/// Genesis ends by starting this epoch, so scheduled features must activate after it.
+pub const GENESIS_EPOCH: u64 = 1;
+/// Allowed maximum transaction sizes.  Below the lower bound framework upgrades no longer fit in
+/// a transaction, and above the upper bound transactions risk exceeding network message limits.
+pub const MAX_TRANSACTION_SIZE_RANGE: RangeInclusive<u64> = 16 * 1024..=4 * 1024 * 1024;
 
 pub struct GenesisConfiguration {
     pub allow_new_validators: bool,
@@ -70,6 +87,88 @@ pub struct GenesisConfiguration {
     pub rewards_apy_percentage: u64,
     pub voting_duration_secs: u64,
     pub voting_power_increase_limit: u64,
+    // Feature flags to enable at the start of a future epoch.
+    pub scheduled_features: Vec<ScheduledFeature>,
+    // Token collections, and their tokens, to create at genesis.
+    pub collections: Vec<GenesisCollection>,
+    // Coins, other than the native coin, to create and mint to their holders at genesis.
+    pub coins: Vec<GenesisCoin>,
+    // Accounts to create and fund at genesis, such as a faucet. Mainnet genesis is given its
+    // accounts separately.
+    pub accounts: Vec<AccountMap>,
+    // If true, only validator accounts can submit transactions until the chain is activated.
+    pub start_paused: bool,
+    // Maximum size of a transaction, or the gas schedule default if None.
+    pub max_transaction_size_in_bytes: Option<u64>,
+}
+
+/// A feature flag that is enabled once the chain reaches `epoch`.
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+pub struct ScheduledFeature {
+    /// Name of the feature flag, such as CODE_DEPENDENCY_CHECK
+    #[schemars(with = "String")]
+    pub feature: FeatureFlag,
+    pub epoch: u64,
+}
+
+/// A token collection created by `creator` at genesis, along with its initial tokens.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisCollection {
+    pub creator: AccountAddress,
+    pub name: String,
+    pub description: String,
+    pub uri: String,
+    /// Maximum number of tokens in the collection, 0 for unlimited
+    pub maximum: u64,
+    #[serde(default)]
+    pub tokens: Vec<GenesisToken>,
+}
+
+/// A token minted to its collection's creator at genesis.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisToken {
+    pub name: String,
+    pub description: String,
+    pub uri: String,
+    /// Amount of the token minted to the creator
+    pub supply: u64,
+    /// Maximum supply of the token, 0 for unlimited
+    pub maximum: u64,
+}
+
+/// A coin created at genesis, in addition to the native coin.  Genesis publishes the coin type,
+/// a struct without type parameters, in a module under the issuer's address, and mints the
+/// initial supply to the holders.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisCoin {
+    pub issuer: AccountAddress,
+    /// Module declaring the coin type
+    pub module_name: String,
+    /// Name of the coin type
+    pub struct_name: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(default)]
+    pub holders: Vec<GenesisCoinHolder>,
+}
+
+impl GenesisCoin {
+    pub fn type_tag(&self) -> TypeTag {
+        TypeTag::Struct(StructTag {
+            address: self.issuer,
+            module: Identifier::new(self.module_name.as_str()).unwrap(),
+            name: Identifier::new(self.struct_name.as_str()).unwrap(),
+            type_params: vec![],
+        })
+    }
+}
+
+/// An initial balance of a coin created at genesis.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisCoinHolder {
+    pub address: AccountAddress,
+    pub amount: u64,
 }
 
 pub static GENESIS_KEYPAIR: Lazy<(Ed25519PrivateKey, Ed25519PublicKey)> = Lazy::new(|| {
@@ -116,9 +215,19 @@ pub fn encode_aptos_mainnet_genesis_transaction(
     );
     initialize_aptos_coin(&mut session);
     initialize_on_chain_governance(&mut session, genesis_config);
+    schedule_features(&mut session, genesis_config);
     create_accounts(&mut session, accounts);
     create_employee_validators(&mut session, employees);
     create_and_initialize_validators_with_commission(&mut session, validators);
+    create_collections(&mut session, &genesis_config.collections);
+    create_coins(&mut session, &genesis_config.coins);
+    if genesis_config.start_paused {
+        let validators = validators
+            .iter()
+            .chain(employees.iter().map(|employee| &employee.validator))
+            .map(|validator| &validator.validator);
+        set_paused(&mut session, validator_accounts(validators));
+    }
     set_genesis_end(&mut session);
 
     // Reconfiguration should happen after all on-chain invocations.
@@ -218,10 +327,21 @@ pub fn encode_genesis_change_set(
         initialize_aptos_coin(&mut session);
     }
     initialize_on_chain_governance(&mut session, genesis_config);
+    schedule_features(&mut session, genesis_config);
+    create_accounts(&mut session, &genesis_config.accounts);
     create_and_initialize_validators(&mut session, validators);
+    create_collections(&mut session, &genesis_config.collections);
+    create_coins(&mut session, &genesis_config.coins);
     if genesis_config.is_test {
         allow_core_resources_to_set_version(&mut session);
     }
+    if genesis_config.start_paused {
+        let mut allowed_senders = validator_accounts(validators.iter());
+        if genesis_config.is_test {
+            allowed_senders.push(aptos_test_root_address());
+        }
+        set_paused(&mut session, allowed_senders);
+    }
     set_genesis_end(&mut session);
 
     // Reconfiguration should happen after all on-chain invocations.
@@ -295,6 +415,74 @@ fn validate_genesis_config(genesis_config: &GenesisConfiguration) {
             && genesis_config.voting_power_increase_limit <= 50,
         "voting_power_increase_limit must be > 0 and <= 50"
     );
+    if let Some(size) = genesis_config.max_transaction_size_in_bytes {
+        assert!(
+            MAX_TRANSACTION_SIZE_RANGE.contains(&size),
+            "Maximum transaction size {} must be within {:?}",
+            size,
+            MAX_TRANSACTION_SIZE_RANGE
+        );
+    }
+    for scheduled in &genesis_config.scheduled_features {
+        assert!(
+            scheduled.epoch > GENESIS_EPOCH,
+            "Scheduled feature {:?} must activate after the genesis epoch {}",
+            scheduled.feature,
+            GENESIS_EPOCH
+        );
+    }
+    let mut collection_names = BTreeSet::new();
+    for collection in &genesis_config.collections {
+        assert!(
+            collection_names.insert(collection.name.as_str()),
+            "Collection {} must only be created once",
+            collection.name
+        );
+        let mut token_names = BTreeSet::new();
+        for token in &collection.tokens {
+            assert!(
+                token_names.insert(token.name.as_str()),
+                "Token {} must only be created once in collection {}",
+                token.name,
+                collection.name
+            );
+        }
+    }
+    let mut coin_types = BTreeSet::new();
+    let mut coin_names = BTreeSet::from([NATIVE_COIN_NAME]);
+    let mut coin_symbols = BTreeSet::from([NATIVE_COIN_SYMBOL]);
+    for coin in &genesis_config.coins {
+        assert!(
+            Identifier::is_valid(&coin.module_name) && Identifier::is_valid(&coin.struct_name),
+            "Coin type {}::{} must be made of valid identifiers",
+            coin.module_name,
+            coin.struct_name
+        );
+        assert!(
+            coin_types.insert(coin.type_tag()),
+            "Coin type {} must only be created once",
+            coin.type_tag()
+        );
+        assert!(
+            coin_names.insert(coin.name.as_str()),
+            "Coin name {} must be unique",
+            coin.name
+        );
+        assert!(
+            coin_symbols.insert(coin.symbol.as_str()),
+            "Coin symbol {} must be unique",
+            coin.symbol
+        );
+        let mut holders = BTreeSet::new();
+        for holder in &coin.holders {
+            assert!(
+                holders.insert(holder.address),
+                "Holder {} must only be listed once for coin {}",
+                holder.address,
+                coin.symbol
+            );
+        }
+    }
 }
 
 fn exec_function(
@@ -303,13 +491,28 @@ fn exec_function(
     function_name: &str,
     ty_args: Vec<TypeTag>,
     args: Vec<Vec<u8>>,
+) {
+    exec_function_at(
+        session,
+        account_config::CORE_CODE_ADDRESS,
+        module_name,
+        function_name,
+        ty_args,
+        args,
+    )
+}
+
+fn exec_function_at(
+    session: &mut SessionExt<impl MoveResolver>,
+    module_address: AccountAddress,
+    module_name: &str,
+    function_name: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
 ) {
     session
         .execute_function_bypass_visibility(
-            &ModuleId::new(
-                account_config::CORE_CODE_ADDRESS,
-                Identifier::new(module_name).unwrap(),
-            ),
+            &ModuleId::new(module_address, Identifier::new(module_name).unwrap()),
             &Identifier::new(function_name).unwrap(),
             ty_args,
             args,
@@ -334,7 +537,10 @@ fn initialize(
     genesis_config: &GenesisConfiguration,
     use_gas_schedule_v2: bool,
 ) {
-    let genesis_gas_params = AptosGasParameters::initial();
+    let mut genesis_gas_params = AptosGasParameters::initial();
+    if let Some(size) = genesis_config.max_transaction_size_in_bytes {
+        genesis_gas_params.txn.max_transaction_size_in_bytes = NumBytes::new(size);
+    }
     // TODO(Gas): The `use_gas_schedule_v2` flag is a hack to get tests working for the previous
     //            testnet release.
     //            We should get rid of it after we make another testnet release.
@@ -396,6 +602,41 @@ fn initialize_aptos_coin(session: &mut SessionExt<impl MoveResolver>) {
     );
 }
 
+/// Pauses transactions from every account except `allowed_senders`, until the chain is activated
+/// through governance.
+fn set_paused(session: &mut SessionExt<impl MoveResolver>, allowed_senders: Vec<AccountAddress>) {
+    exec_function(
+        session,
+        GENESIS_MODULE_NAME,
+        "set_paused",
+        vec![],
+        serialize_values(&vec![
+            MoveValue::Signer(CORE_CODE_ADDRESS),
+            MoveValue::Vector(
+                allowed_senders
+                    .into_iter()
+                    .map(MoveValue::Address)
+                    .collect(),
+            ),
+        ]),
+    );
+}
+
+/// The owner, operator and voter accounts of the validators, which take part in governance.
+fn validator_accounts<'a>(validators: impl Iterator<Item = &'a Validator>) -> Vec<AccountAddress> {
+    validators
+        .flat_map(|validator| {
+            [
+                validator.owner_address,
+                validator.operator_address,
+                validator.voter_address,
+            ]
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
 fn set_genesis_end(session: &mut SessionExt<impl MoveResolver>) {
     exec_function(
         session,
@@ -442,6 +683,152 @@ fn initialize_on_chain_governance(
     );
 }
 
+fn schedule_features(
+    session: &mut SessionExt<impl MoveResolver>,
+    genesis_config: &GenesisConfiguration,
+) {
+    if genesis_config.scheduled_features.is_empty() {
+        return;
+    }
+
+    let (features, epochs): (Vec<_>, Vec<_>) = genesis_config
+        .scheduled_features
+        .iter()
+        .map(|scheduled| {
+            (
+                MoveValue::U64(scheduled.feature as u64),
+                MoveValue::U64(scheduled.epoch),
+            )
+        })
+        .unzip();
+    exec_function(
+        session,
+        FEATURES_MODULE_NAME,
+        "schedule_feature_flags",
+        vec![],
+        serialize_values(&vec![
+            MoveValue::Signer(CORE_CODE_ADDRESS),
+            MoveValue::Vector(features),
+            MoveValue::Vector(epochs),
+        ]),
+    );
+}
+
+fn create_collections(
+    session: &mut SessionExt<impl MoveResolver>,
+    collections: &[GenesisCollection],
+) {
+    let token_address = AccountAddress::from_hex_literal("0x3").unwrap();
+    for collection in collections {
+        exec_function_at(
+            session,
+            token_address,
+            TOKEN_MODULE_NAME,
+            "create_collection_script",
+            vec![],
+            serialize_values(&vec![
+                MoveValue::Signer(collection.creator),
+                MoveValue::vector_u8(collection.name.as_bytes().to_vec()),
+                MoveValue::vector_u8(collection.description.as_bytes().to_vec()),
+                MoveValue::vector_u8(collection.uri.as_bytes().to_vec()),
+                MoveValue::U64(collection.maximum),
+                // The description, uri and maximum of the collection are immutable
+                MoveValue::Vector(vec![MoveValue::Bool(false); 3]),
+            ]),
+        );
+
+        for token in &collection.tokens {
+            exec_function_at(
+                session,
+                token_address,
+                TOKEN_MODULE_NAME,
+                "create_token_script",
+                vec![],
+                serialize_values(&vec![
+                    MoveValue::Signer(collection.creator),
+                    MoveValue::vector_u8(collection.name.as_bytes().to_vec()),
+                    MoveValue::vector_u8(token.name.as_bytes().to_vec()),
+                    MoveValue::vector_u8(token.description.as_bytes().to_vec()),
+                    MoveValue::U64(token.supply),
+                    MoveValue::U64(token.maximum),
+                    MoveValue::vector_u8(token.uri.as_bytes().to_vec()),
+                    // No royalty is paid on genesis tokens
+                    MoveValue::Address(collection.creator),
+                    MoveValue::U64(1),
+                    MoveValue::U64(0),
+                    // The maximum, uri, royalty, description and properties are immutable
+                    MoveValue::Vector(vec![MoveValue::Bool(false); 5]),
+                    MoveValue::Vector(vec![]),
+                    MoveValue::Vector(vec![]),
+                    MoveValue::Vector(vec![]),
+                ]),
+            );
+        }
+    }
+}
+
+/// Publishes the types of the genesis coins, then initializes each coin and mints its initial
+/// supply to the holders.  The issuer keeps the mint, burn and freeze capabilities.
+fn create_coins(session: &mut SessionExt<impl MoveResolver>, coins: &[GenesisCoin]) {
+    let mut modules: BTreeMap<(AccountAddress, &str), Vec<&str>> = BTreeMap::new();
+    for coin in coins {
+        modules
+            .entry((coin.issuer, coin.module_name.as_str()))
+            .or_default()
+            .push(coin.struct_name.as_str());
+    }
+    for ((issuer, module_name), struct_names) in modules {
+        // Coin types only need to exist, so they are plain structs without abilities
+        let structs: String = struct_names
+            .iter()
+            .map(|name| format!("struct {} {{ dummy_field: bool }}\n", name))
+            .collect();
+        let code = format!("module 0x{}.{} {{\n{}}}", issuer, module_name, structs);
+        let module = Compiler { deps: vec![] }
+            .into_module_blob(&code)
+            .unwrap_or_else(|e| panic!("Failure compiling coin module {}: {}", module_name, e));
+        session
+            .publish_module(module, issuer, &mut UnmeteredGasMeter)
+            .unwrap_or_else(|e| panic!("Failure publishing coin module {}: {:?}", module_name, e));
+    }
+
+    for coin in coins {
+        exec_function(
+            session,
+            MANAGED_COIN_MODULE_NAME,
+            "initialize",
+            vec![coin.type_tag()],
+            serialize_values(&vec![
+                MoveValue::Signer(coin.issuer),
+                MoveValue::vector_u8(coin.name.as_bytes().to_vec()),
+                MoveValue::vector_u8(coin.symbol.as_bytes().to_vec()),
+                MoveValue::U8(coin.decimals),
+                MoveValue::Bool(true),
+            ]),
+        );
+        for holder in &coin.holders {
+            exec_function(
+                session,
+                MANAGED_COIN_MODULE_NAME,
+                "register",
+                vec![coin.type_tag()],
+                serialize_values(&vec![MoveValue::Signer(holder.address)]),
+            );
+            exec_function(
+                session,
+                MANAGED_COIN_MODULE_NAME,
+                "mint",
+                vec![coin.type_tag()],
+                serialize_values(&vec![
+                    MoveValue::Signer(coin.issuer),
+                    MoveValue::Address(holder.address),
+                    MoveValue::U64(holder.amount),
+                ]),
+            );
+        }
+    }
+}
+
 fn create_accounts(session: &mut SessionExt<impl MoveResolver>, accounts: &[AccountMap]) {
     let accounts_bytes = bcs::to_bytes(accounts).expect("AccountMaps can be serialized");
     let mut serialized_values = serialize_values(&vec![MoveValue::Signer(CORE_CODE_ADDRESS)]);
@@ -643,14 +1030,17 @@ pub fn test_genesis_change_set_and_validators(
     generate_test_genesis(cached_packages::head_release_bundle(), count, true)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Validator {
     /// The Aptos account address of the validator or the admin in the case of a commissioned or
     /// vesting managed validator.
+    #[schemars(with = "String")]
     pub owner_address: AccountAddress,
     /// The Aptos account address of the validator's operator (same as `address` if the validator is
     /// its own operator).
+    #[schemars(with = "String")]
     pub operator_address: AccountAddress,
+    #[schemars(with = "String")]
     pub voter_address: AccountAddress,
     /// Amount to stake for consensus. Also the intial amount minted to the owner account.
     pub stake_amount: u64,
@@ -742,6 +1132,12 @@ pub fn generate_test_genesis(
             rewards_apy_percentage: 10,
             voting_duration_secs: 3600,
             voting_power_increase_limit: 50,
+            scheduled_features: vec![],
+            collections: vec![],
+            coins: vec![],
+            accounts: vec![],
+            start_paused: false,
+            max_transaction_size_in_bytes: None,
         },
         use_gas_schedule_v2,
     );
@@ -785,24 +1181,53 @@ fn mainnet_genesis_config() -> GenesisConfiguration {
         rewards_apy_percentage: 10,
         voting_duration_secs: 7 * 24 * 3600, // 7 days
         voting_power_increase_limit: 30,
+        scheduled_features: vec![],
+        collections: vec![],
+        coins: vec![],
+        accounts: vec![],
+        start_paused: false,
+        max_transaction_size_in_bytes: None,
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AccountMap {
+    #[schemars(with = "String")]
     pub account_address: AccountAddress,
     pub balance: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmployeeAccountMap {
-    accounts: Vec<AccountAddress>,
-    validator: ValidatorWithCommissionRate,
-    vesting_schedule_numerators: Vec<u64>,
-    vesting_schedule_denominator: u64,
+    #[schemars(with = "Vec<String>")]
+    pub accounts: Vec<AccountAddress>,
+    pub validator: ValidatorWithCommissionRate,
+    /// Fraction of each account's balance vested in each period, over the denominator
+    pub vesting_schedule_numerators: Vec<u64>,
+    pub vesting_schedule_denominator: u64,
+}
+
+impl EmployeeAccountMap {
+    /// Total amount vested over the schedule, for an account allocated `allocation` coins.  As in
+    /// the vesting contract, the last fraction repeats until the whole allocation has vested, so
+    /// only a schedule ending in a zero fraction vests less.  The denominator must not be zero.
+    pub fn vested_amount(&self, allocation: u64) -> u128 {
+        let allocation = allocation as u128;
+        match self.vesting_schedule_numerators.last() {
+            Some(last) if *last > 0 => allocation,
+            _ => {
+                let numerator: u128 = self
+                    .vesting_schedule_numerators
+                    .iter()
+                    .map(|numerator| *numerator as u128)
+                    .sum();
+                (allocation * numerator / self.vesting_schedule_denominator as u128).min(allocation)
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidatorWithCommissionRate {
     pub validator: Validator,
     pub validator_commission_percentage: u64,
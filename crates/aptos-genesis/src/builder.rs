@@ -36,6 +36,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use vm_genesis::{AccountMap, GenesisCoin, GenesisCollection, ScheduledFeature};
 
 const VALIDATOR_IDENTITY: &str = "validator-identity.yaml";
 const VFN_IDENTITY: &str = "vfn-identity.yaml";
@@ -203,12 +204,14 @@ impl TryFrom<&ValidatorNodeConfig> for ValidatorConfiguration {
                 .validator_network_private_key
                 .public_key(),
             validator_host,
+            additional_validator_hosts: vec![],
             full_node_network_public_key: Some(
                 private_identity.full_node_network_private_key.public_key(),
             ),
             full_node_host,
             stake_amount: config.genesis_stake_amount,
             commission_percentage: config.commission_percentage,
+            chain_id: None,
         })
     }
 }
@@ -397,6 +400,12 @@ pub struct GenesisConfiguration {
     pub rewards_apy_percentage: u64,
     pub voting_duration_secs: u64,
     pub voting_power_increase_limit: u64,
+    pub scheduled_features: Vec<ScheduledFeature>,
+    pub collections: Vec<GenesisCollection>,
+    pub coins: Vec<GenesisCoin>,
+    pub accounts: Vec<AccountMap>,
+    pub start_paused: bool,
+    pub max_transaction_size_in_bytes: Option<u64>,
 }
 
 pub type InitConfigFn = Arc<dyn Fn(usize, &mut NodeConfig, &mut u64) + Send + Sync>;
@@ -592,6 +601,12 @@ impl Builder {
             rewards_apy_percentage: 10,
             voting_duration_secs: ONE_DAY / 24,
             voting_power_increase_limit: 50,
+            scheduled_features: vec![],
+            collections: vec![],
+            coins: vec![],
+            accounts: vec![],
+            start_paused: false,
+            max_transaction_size_in_bytes: None,
         };
         if let Some(init_genesis_config) = &self.init_genesis_config {
             (init_genesis_config)(&mut genesis_config);
@@ -1,16 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::bail;
 use aptos_config::config::HANDSHAKE_VERSION;
 use aptos_crypto::{bls12381, ed25519::Ed25519PublicKey, x25519};
 use aptos_types::{
     account_address::AccountAddress,
-    chain_id::ChainId,
+    chain_id::{ChainId, NamedChain},
     network_address::{DnsName, NetworkAddress, Protocol},
     transaction::authenticator::AuthenticationKey,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     fs::File,
     io::Read,
@@ -18,41 +21,79 @@ use std::{
     path::Path,
     str::FromStr,
 };
-use vm_genesis::{Validator, ValidatorWithCommissionRate};
+use vm_genesis::{ScheduledFeature, Validator, ValidatorWithCommissionRate};
+
+/// JSON schema describing the structure of a [`Layout`] file, generated from [`Layout`] by
+/// `aptos genesis print-schema --file layout`
+pub const LAYOUT_SCHEMA: &str = include_str!("layout.schema.json");
 
 /// Template for setting up Github for Genesis
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
+#[schemars(deny_unknown_fields)]
 pub struct Layout {
     /// Root key for the blockchain
     /// TODO: In the future, we won't need a root key
+    #[schemars(with = "Option<String>", regex(pattern = r"^(0x)?[0-9a-fA-F]{64}$"))]
     pub root_key: Option<Ed25519PublicKey>,
     /// List of usernames or identifiers
+    ///
+    /// Validators are included in genesis in the lexicographic order of their users, whatever
+    /// order they're listed in, so genesis doesn't depend on how the list was built
+    #[schemars(with = "BTreeSet<String>", length(min = 1))]
     pub users: Vec<String>,
+    /// Owner account keys of the users, registered from their public identity files
+    ///
+    /// Signed validator configurations are checked against these, which the coordinator registers
+    /// apart from the configurations the users write themselves
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(with = "BTreeMap<String, String>")]
+    pub owner_public_keys: BTreeMap<String, Ed25519PublicKey>,
     /// ChainId for the target network
+    #[schemars(with = "u8", range(min = 1))]
     pub chain_id: ChainId,
     /// Whether to allow new validators to join the set after genesis
     #[serde(default)]
     pub allow_new_validators: bool,
     /// Duration of an epoch
+    #[schemars(range(min = 1))]
     pub epoch_duration_secs: u64,
     pub is_test: bool,
     /// Minimum stake to be in the validator set
     pub min_stake: u64,
     /// Minimum number of votes to consider a proposal valid.
+    #[schemars(range(min = 1))]
     pub min_voting_threshold: u128,
     /// Maximum stake to be in the validator set
+    #[schemars(range(min = 1))]
     pub max_stake: u64,
     /// Minimum number of seconds to lockup staked coins
+    #[schemars(range(min = 1))]
     pub recurring_lockup_duration_secs: u64,
     /// Required amount of stake to create proposals.
     pub required_proposer_stake: u64,
     /// Percentage of stake given out as rewards a year (0-100%).
+    #[schemars(range(max = 100))]
     pub rewards_apy_percentage: u64,
     /// Voting duration for a proposal in seconds.
+    #[schemars(range(min = 1))]
     pub voting_duration_secs: u64,
     /// % of current epoch's total voting power that can be added in this epoch.
+    #[schemars(range(min = 1, max = 50))]
     pub voting_power_increase_limit: u64,
+    /// Feature flags to enable at the start of a future epoch
+    #[serde(default)]
+    pub scheduled_features: Vec<ScheduledFeature>,
+    /// Maximum size of a transaction in bytes, defaults to the gas schedule's limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 16384, max = 4194304))]
+    pub max_transaction_size_in_bytes: Option<u64>,
+    /// Total coins allocated to employee vesting accounts, which their balances must add up to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub employee_vesting_pool_size: Option<u64>,
+    /// Set on layouts redacted for review, which must not be used for genesis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redacted: Option<String>,
 }
 
 impl Layout {
@@ -68,6 +109,101 @@ impl Layout {
 
         Ok(serde_yaml::from_str(&contents)?)
     }
+
+    /// Checks the layout is complete, and that its parameters are within the bounds genesis
+    /// accepts, naming the first field which isn't
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self.problems().into_iter().next() {
+            Some(problem) => bail!(problem),
+            None => Ok(()),
+        }
+    }
+
+    /// Every field which makes the layout incomplete, or is out of the bounds genesis accepts, in
+    /// the order `validate` checks them
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.root_key.is_none() {
+            problems.push("root_key is missing".to_string());
+        }
+        if self.users.is_empty() {
+            problems.push("users is empty, genesis needs at least one validator".to_string());
+        }
+        let mut users = BTreeSet::new();
+        let mut duplicates = BTreeSet::new();
+        for user in &self.users {
+            if !users.insert(user) && duplicates.insert(user) {
+                problems.push(format!("users lists {} more than once", user));
+            }
+        }
+        if self.chain_id.id() == 0 {
+            problems.push("chain_id is not set, it must be between 1 and 255".to_string());
+        }
+        if self.epoch_duration_secs == 0 {
+            problems
+                .push("epoch_duration_secs is 0, epochs must last at least a second".to_string());
+        }
+        if self.recurring_lockup_duration_secs == 0 {
+            problems
+                .push("recurring_lockup_duration_secs is 0, stake must be locked up".to_string());
+        }
+        if self.voting_duration_secs == 0 {
+            problems
+                .push("voting_duration_secs is 0, proposals must be open for voting".to_string());
+        }
+        if self.max_stake == 0 {
+            problems.push("max_stake is 0, validators must be able to stake".to_string());
+        }
+        if self.min_stake > self.max_stake {
+            problems.push(format!(
+                "min_stake {} is greater than max_stake {}",
+                self.min_stake, self.max_stake
+            ));
+        }
+        if self.rewards_apy_percentage > 100 {
+            problems.push(format!(
+                "rewards_apy_percentage {} is greater than 100",
+                self.rewards_apy_percentage
+            ));
+        }
+        if !(1..=50).contains(&self.voting_power_increase_limit) {
+            problems.push(format!(
+                "voting_power_increase_limit {} must be between 1 and 50 percent",
+                self.voting_power_increase_limit
+            ));
+        }
+        if self.min_voting_threshold == 0 {
+            problems
+                .push("min_voting_threshold is 0, proposals would pass without votes".to_string());
+        }
+        problems
+    }
+
+    /// The users in the canonical order their validators are included in genesis, sorted
+    /// lexicographically
+    pub fn sorted_users(&self) -> Vec<&String> {
+        let mut users: Vec<_> = self.users.iter().collect();
+        users.sort();
+        users
+    }
+
+    /// Checks which don't make the layout invalid, but are likely mistakes, returning a warning
+    /// for each
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        if !self.is_test {
+            if let Ok(chain) = NamedChain::from_chain_id(&self.chain_id) {
+                warnings.push(format!(
+                    "chain_id {} is reserved for {}, but is_test isn't set, make sure the layout \
+                     is for {}",
+                    self.chain_id.id(),
+                    chain,
+                    chain
+                ));
+            }
+        }
+        warnings
+    }
 }
 
 impl Default for Layout {
@@ -75,6 +211,7 @@ impl Default for Layout {
         Layout {
             root_key: None,
             users: vec![],
+            owner_public_keys: BTreeMap::new(),
             chain_id: ChainId::test(),
             allow_new_validators: false,
             epoch_duration_secs: 7_200,
@@ -87,6 +224,10 @@ impl Default for Layout {
             rewards_apy_percentage: 10,
             voting_duration_secs: 43_200,
             voting_power_increase_limit: 20,
+            scheduled_features: vec![],
+            max_transaction_size_in_bytes: None,
+            employee_vesting_pool_size: None,
+            redacted: None,
         }
     }
 }
@@ -111,6 +252,9 @@ pub struct ValidatorConfiguration {
     pub validator_network_public_key: x25519::PublicKey,
     /// Host for validator which can be an IP or a DNS name
     pub validator_host: HostAndPort,
+    /// Other hosts the validator can also be reached at, after `validator_host`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_validator_hosts: Vec<HostAndPort>,
     /// Public key used for full node network identity (same as account address)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub full_node_network_public_key: Option<x25519::PublicKey>,
@@ -121,6 +265,16 @@ pub struct ValidatorConfiguration {
     pub stake_amount: u64,
     /// Commission percentage for validator
     pub commission_percentage: u64,
+    /// Chain id of the layout the configuration was built against, if it was known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<ChainId>,
+}
+
+impl ValidatorConfiguration {
+    /// Every host the validator can be reached at, starting with `validator_host`
+    pub fn validator_hosts(&self) -> impl Iterator<Item = &HostAndPort> {
+        std::iter::once(&self.validator_host).chain(&self.additional_validator_hosts)
+    }
 }
 
 impl TryFrom<ValidatorConfiguration> for ValidatorWithCommissionRate {
@@ -139,15 +293,13 @@ impl TryFrom<ValidatorConfiguration> for Validator {
     type Error = anyhow::Error;
 
     fn try_from(config: ValidatorConfiguration) -> Result<Self, Self::Error> {
-        let validator_addresses = vec![config
-            .validator_host
-            .as_network_address(config.validator_network_public_key)
-            .unwrap()];
+        let validator_addresses = config
+            .validator_hosts()
+            .map(|host| host.as_network_address(config.validator_network_public_key))
+            .collect::<anyhow::Result<Vec<_>>>()?;
         let full_node_addresses = if let Some(full_node_host) = config.full_node_host {
             if let Some(full_node_network_key) = config.full_node_network_public_key {
-                vec![full_node_host
-                    .as_network_address(full_node_network_key)
-                    .unwrap()]
+                vec![full_node_host.as_network_address(full_node_network_key)?]
             } else {
                 return Err(anyhow::Error::msg(
                     "Full node host specified, but not full node network key",
@@ -274,6 +426,9 @@ pub struct OwnerConfiguration {
     pub operator_account_public_key: Ed25519PublicKey,
     pub stake_amount: u64,
     pub commission_percentage: u64,
+    /// Chain id of the layout, if it had been uploaded when the configuration was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<ChainId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -284,6 +439,9 @@ pub struct OperatorConfiguration {
     pub consensus_proof_of_possession: bls12381::ProofOfPossession,
     pub validator_network_public_key: x25519::PublicKey,
     pub validator_host: HostAndPort,
+    /// Other hosts the validator can also be reached at, after `validator_host`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_validator_hosts: Vec<HostAndPort>,
     pub full_node_network_public_key: Option<x25519::PublicKey>,
     pub full_node_host: Option<HostAndPort>,
 }
@@ -298,6 +456,8 @@ pub struct StringOwnerConfiguration {
     pub operator_account_public_key: Option<String>,
     pub stake_amount: Option<String>,
     pub commission_percentage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -308,6 +468,9 @@ pub struct StringOperatorConfiguration {
     pub consensus_proof_of_possession: Option<String>,
     pub validator_network_public_key: Option<String>,
     pub validator_host: HostAndPort,
+    /// Other hosts the validator can also be reached at, after `validator_host`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_validator_hosts: Vec<HostAndPort>,
     pub full_node_network_public_key: Option<String>,
     pub full_node_host: Option<HostAndPort>,
 }
@@ -0,0 +1,168 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    builder::GenesisConfiguration,
+    config::{Layout, ValidatorConfiguration},
+    mainnet::MainnetGenesisInfo,
+    GenesisInfo,
+};
+use anyhow::bail;
+use aptos_types::{transaction::Transaction, waypoint::Waypoint};
+use framework::ReleaseBundle;
+use vm_genesis::{AccountMap, EmployeeAccountMap, GenesisCoin, GenesisCollection};
+
+/// Builds genesis from a layout and validator configurations held in memory
+///
+/// These are the same inputs the genesis commands read from a git repository, so genesis can be
+/// built without writing them out first.  Genesis is for mainnet if accounts are given, and
+/// otherwise for a network minting with the layout's root key.
+#[derive(Clone)]
+pub struct GenesisBuilder {
+    layout: Layout,
+    validators: Vec<ValidatorConfiguration>,
+    framework: ReleaseBundle,
+    accounts: Option<Vec<AccountMap>>,
+    employee_vesting_accounts: Vec<EmployeeAccountMap>,
+    collections: Vec<GenesisCollection>,
+    coins: Vec<GenesisCoin>,
+    faucet: Option<AccountMap>,
+    start_paused: bool,
+}
+
+impl GenesisBuilder {
+    pub fn new(
+        layout: Layout,
+        validators: Vec<ValidatorConfiguration>,
+        framework: ReleaseBundle,
+    ) -> Self {
+        Self {
+            layout,
+            validators,
+            framework,
+            accounts: None,
+            employee_vesting_accounts: vec![],
+            collections: vec![],
+            coins: vec![],
+            faucet: None,
+            start_paused: false,
+        }
+    }
+
+    /// Initial accounts and balances, which make this mainnet genesis
+    pub fn with_accounts(mut self, accounts: Vec<AccountMap>) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    /// Employee vesting configurations, only used by mainnet genesis
+    pub fn with_employee_vesting_accounts(
+        mut self,
+        employee_vesting_accounts: Vec<EmployeeAccountMap>,
+    ) -> Self {
+        self.employee_vesting_accounts = employee_vesting_accounts;
+        self
+    }
+
+    pub fn with_collections(mut self, collections: Vec<GenesisCollection>) -> Self {
+        self.collections = collections;
+        self
+    }
+
+    pub fn with_coins(mut self, coins: Vec<GenesisCoin>) -> Self {
+        self.coins = coins;
+        self
+    }
+
+    /// Account funded at genesis for test transactions to be funded from, alongside any others
+    pub fn with_faucet(mut self, faucet: Option<AccountMap>) -> Self {
+        self.faucet = faucet;
+        self
+    }
+
+    pub fn with_start_paused(mut self, start_paused: bool) -> Self {
+        self.start_paused = start_paused;
+        self
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.accounts.is_some()
+    }
+
+    /// Builds mainnet genesis, failing if no accounts were given
+    pub fn into_mainnet_genesis_info(self) -> anyhow::Result<MainnetGenesisInfo> {
+        let genesis_config = self.genesis_config();
+        let mut accounts = match self.accounts {
+            Some(accounts) => accounts,
+            None => bail!("Mainnet genesis needs the initial accounts and balances"),
+        };
+        accounts.extend(self.faucet);
+        MainnetGenesisInfo::new(
+            self.layout.chain_id,
+            accounts,
+            self.employee_vesting_accounts,
+            self.validators,
+            self.framework,
+            &genesis_config,
+        )
+    }
+
+    /// Builds genesis minting with the layout's root key, failing if it isn't set
+    pub fn into_genesis_info(self) -> anyhow::Result<GenesisInfo> {
+        let genesis_config = self.genesis_config();
+        let root_key = match self.layout.root_key {
+            Some(root_key) => root_key,
+            None => bail!("Layout field root_key was not set"),
+        };
+        GenesisInfo::new(
+            self.layout.chain_id,
+            root_key,
+            self.validators,
+            self.framework,
+            &genesis_config,
+        )
+    }
+
+    /// Generates the genesis transaction, and the waypoint committing to it
+    pub fn build(self) -> anyhow::Result<(Transaction, Waypoint)> {
+        if self.is_mainnet() {
+            let mut genesis_info = self.into_mainnet_genesis_info()?;
+            let waypoint = genesis_info.generate_waypoint()?;
+            Ok((genesis_info.get_genesis().clone(), waypoint))
+        } else {
+            let mut genesis_info = self.into_genesis_info()?;
+            let waypoint = genesis_info.generate_waypoint()?;
+            Ok((genesis_info.get_genesis().clone(), waypoint))
+        }
+    }
+
+    fn genesis_config(&self) -> GenesisConfiguration {
+        let layout = &self.layout;
+        let mainnet = self.is_mainnet();
+        GenesisConfiguration {
+            // Mainnet always accepts new validators, and is never a test network
+            allow_new_validators: mainnet || layout.allow_new_validators,
+            epoch_duration_secs: layout.epoch_duration_secs,
+            is_test: !mainnet && layout.is_test,
+            min_stake: layout.min_stake,
+            min_voting_threshold: layout.min_voting_threshold,
+            max_stake: layout.max_stake,
+            recurring_lockup_duration_secs: layout.recurring_lockup_duration_secs,
+            required_proposer_stake: layout.required_proposer_stake,
+            rewards_apy_percentage: layout.rewards_apy_percentage,
+            voting_duration_secs: layout.voting_duration_secs,
+            voting_power_increase_limit: layout.voting_power_increase_limit,
+            scheduled_features: layout.scheduled_features.clone(),
+            collections: self.collections.clone(),
+            coins: self.coins.clone(),
+            // Mainnet's faucet is created with the rest of its accounts
+            accounts: if mainnet {
+                vec![]
+            } else {
+                self.faucet.iter().cloned().collect()
+            },
+            start_paused: self.start_paused,
+            max_transaction_size_in_bytes: layout.max_transaction_size_in_bytes,
+        }
+    }
+}
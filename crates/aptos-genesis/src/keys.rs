@@ -1,11 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::bail;
 use aptos_config::{config::IdentityBlob, keys::ConfigKey};
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_crypto::{bls12381, ed25519::Ed25519PrivateKey, x25519, PrivateKey};
 use aptos_keygen::KeyGen;
 use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Type for serializing private keys file
@@ -18,17 +20,70 @@ pub struct PrivateIdentity {
     pub validator_network_private_key: x25519::PrivateKey,
 }
 
+/// Version of the public keys file format written by [`generate_key_objects`]
+pub const PUBLIC_IDENTITY_VERSION: u64 = 2;
+
+/// Public keys files written before the format was versioned are version 1
+fn default_public_identity_version() -> u64 {
+    1
+}
+
+/// The version of a public keys file, read before its contents are trusted to match a format
+#[derive(Deserialize)]
+pub struct PublicIdentityVersion {
+    #[serde(default = "default_public_identity_version")]
+    pub version: u64,
+}
+
 /// Type for serializing public keys file
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
 pub struct PublicIdentity {
+    /// Version of the file format, up to [`PUBLIC_IDENTITY_VERSION`]
+    #[serde(default = "default_public_identity_version")]
+    pub version: u64,
+    #[schemars(with = "String")]
     pub account_address: AccountAddress,
+    #[schemars(with = "String")]
     pub account_public_key: Ed25519PublicKey,
+    #[schemars(with = "Option<String>")]
     pub consensus_public_key: Option<bls12381::PublicKey>,
+    #[schemars(with = "Option<String>")]
     pub consensus_proof_of_possession: Option<bls12381::ProofOfPossession>,
+    #[schemars(with = "Option<String>")]
     pub full_node_network_public_key: Option<x25519::PublicKey>,
+    #[schemars(with = "Option<String>")]
     pub validator_network_public_key: Option<x25519::PublicKey>,
 }
 
+impl PublicIdentity {
+    /// Parses a public keys file of any known version, upgrading it to the current version
+    ///
+    /// Files of a version newer than [`PUBLIC_IDENTITY_VERSION`] are rejected, as they may hold
+    /// fields which would otherwise be dropped.
+    pub fn from_yaml(contents: &str) -> anyhow::Result<Self> {
+        let PublicIdentityVersion { version } = serde_yaml::from_str(contents)?;
+        if version == 0 || version > PUBLIC_IDENTITY_VERSION {
+            bail!(
+                "Public keys file has version {}, but only versions 1 to {} are supported, \
+                 upgrade the aptos CLI to read it",
+                version,
+                PUBLIC_IDENTITY_VERSION
+            );
+        }
+        let identity: PublicIdentity = serde_yaml::from_str(contents)?;
+        Ok(identity.upgrade())
+    }
+
+    /// Upgrades an identity read from an older version of the file to the current version
+    pub fn upgrade(mut self) -> Self {
+        if self.version == 1 {
+            // Version 2 only adds the version itself
+            self.version = 2;
+        }
+        self
+    }
+}
+
 /// Generates objects used for a user in genesis
 pub fn generate_key_objects(
     keygen: &mut KeyGen,
@@ -63,6 +118,7 @@ pub fn generate_key_objects(
     };
 
     let public_identity = PublicIdentity {
+        version: PUBLIC_IDENTITY_VERSION,
         account_address,
         account_public_key: account_key.public_key(),
         consensus_public_key: Some(private_identity.consensus_private_key.public_key()),
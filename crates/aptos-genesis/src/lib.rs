@@ -5,6 +5,7 @@
 
 pub mod builder;
 pub mod config;
+pub mod genesis_builder;
 pub mod keys;
 pub mod mainnet;
 
@@ -24,7 +25,7 @@ use aptosdb::AptosDB;
 use framework::ReleaseBundle;
 use std::convert::TryInto;
 use storage_interface::DbReaderWriter;
-use vm_genesis::Validator;
+use vm_genesis::{AccountMap, GenesisCoin, GenesisCollection, ScheduledFeature, Validator};
 
 /// Holder object for all pieces needed to generate a genesis transaction
 #[derive(Clone)]
@@ -61,6 +62,18 @@ pub struct GenesisInfo {
     pub voting_duration_secs: u64,
     /// Percent of current epoch's total voting power that can be added in this epoch.
     pub voting_power_increase_limit: u64,
+    /// Feature flags to enable at the start of a future epoch.
+    pub scheduled_features: Vec<ScheduledFeature>,
+    /// Token collections, and their tokens, to create at genesis.
+    pub collections: Vec<GenesisCollection>,
+    /// Coins, other than the native coin, to create and mint to their holders at genesis.
+    pub coins: Vec<GenesisCoin>,
+    /// Accounts, other than the validators', to create and fund at genesis, such as a faucet.
+    pub accounts: Vec<AccountMap>,
+    /// Whether only validator accounts can submit transactions until the chain is activated.
+    pub start_paused: bool,
+    /// Maximum size of a transaction, or the gas schedule default if `None`.
+    pub max_transaction_size_in_bytes: Option<u64>,
 }
 
 impl GenesisInfo {
@@ -94,9 +107,27 @@ impl GenesisInfo {
             rewards_apy_percentage: genesis_config.rewards_apy_percentage,
             voting_duration_secs: genesis_config.voting_duration_secs,
             voting_power_increase_limit: genesis_config.voting_power_increase_limit,
+            scheduled_features: genesis_config.scheduled_features.clone(),
+            collections: genesis_config.collections.clone(),
+            coins: genesis_config.coins.clone(),
+            accounts: genesis_config.accounts.clone(),
+            start_paused: genesis_config.start_paused,
+            max_transaction_size_in_bytes: genesis_config.max_transaction_size_in_bytes,
         })
     }
 
+    pub fn num_validators(&self) -> usize {
+        self.validators.len()
+    }
+
+    /// Stake of every validator in genesis, in octas
+    pub fn total_stake(&self) -> u128 {
+        self.validators
+            .iter()
+            .map(|validator| validator.stake_amount as u128)
+            .sum()
+    }
+
     pub fn get_genesis(&mut self) -> &Transaction {
         if let Some(ref genesis) = self.genesis {
             genesis
@@ -124,23 +155,33 @@ impl GenesisInfo {
                 rewards_apy_percentage: self.rewards_apy_percentage,
                 voting_duration_secs: self.voting_duration_secs,
                 voting_power_increase_limit: self.voting_power_increase_limit,
+                scheduled_features: self.scheduled_features.clone(),
+                collections: self.collections.clone(),
+                coins: self.coins.clone(),
+                accounts: self.accounts.clone(),
+                start_paused: self.start_paused,
+                max_transaction_size_in_bytes: self.max_transaction_size_in_bytes,
             },
         )
     }
 
     pub fn generate_waypoint(&mut self) -> anyhow::Result<Waypoint> {
-        let genesis = self.get_genesis();
-        let path = TempPath::new();
-        let aptosdb = AptosDB::open(
-            &path,
-            false,
-            NO_OP_STORAGE_PRUNER_CONFIG,
-            RocksdbConfigs::default(),
-            false,
-            TARGET_SNAPSHOT_SIZE,
-            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-        )?;
-        let db_rw = DbReaderWriter::new(aptosdb);
-        executor::db_bootstrapper::generate_waypoint::<AptosVM>(&db_rw, genesis)
+        generate_waypoint(self.get_genesis())
     }
 }
+
+/// Computes the waypoint of a genesis transaction, by applying it to an empty database
+pub fn generate_waypoint(genesis: &Transaction) -> anyhow::Result<Waypoint> {
+    let path = TempPath::new();
+    let aptosdb = AptosDB::open(
+        &path,
+        false,
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        RocksdbConfigs::default(),
+        false,
+        TARGET_SNAPSHOT_SIZE,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    )?;
+    let db_rw = DbReaderWriter::new(aptosdb);
+    executor::db_bootstrapper::generate_waypoint::<AptosVM>(&db_rw, genesis)
+}
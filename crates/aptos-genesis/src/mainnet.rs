@@ -12,7 +12,10 @@ use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
 use framework::ReleaseBundle;
 use storage_interface::DbReaderWriter;
-use vm_genesis::{AccountMap, EmployeeAccountMap, ValidatorWithCommissionRate};
+use vm_genesis::{
+    AccountMap, EmployeeAccountMap, GenesisCoin, GenesisCollection, ScheduledFeature,
+    ValidatorWithCommissionRate,
+};
 
 /// Holder object for all pieces needed to generate a genesis transaction
 #[derive(Clone)]
@@ -42,6 +45,16 @@ pub struct MainnetGenesisInfo {
     pub voting_duration_secs: u64,
     /// Percent of current epoch's total voting power that can be added in this epoch.
     pub voting_power_increase_limit: u64,
+    /// Feature flags to enable at the start of a future epoch.
+    pub scheduled_features: Vec<ScheduledFeature>,
+    /// Token collections, and their tokens, to create at genesis.
+    pub collections: Vec<GenesisCollection>,
+    /// Coins, other than the native coin, to create and mint to their holders at genesis.
+    pub coins: Vec<GenesisCoin>,
+    /// Whether only validator accounts can submit transactions until the chain is activated.
+    pub start_paused: bool,
+    /// Maximum size of a transaction, or the gas schedule default if `None`.
+    pub max_transaction_size_in_bytes: Option<u64>,
 
     // MAINNET SPECIFIC FIELDS.
     /// Initial accounts and balances.
@@ -80,9 +93,26 @@ impl MainnetGenesisInfo {
             rewards_apy_percentage: genesis_config.rewards_apy_percentage,
             voting_duration_secs: genesis_config.voting_duration_secs,
             voting_power_increase_limit: genesis_config.voting_power_increase_limit,
+            scheduled_features: genesis_config.scheduled_features.clone(),
+            collections: genesis_config.collections.clone(),
+            coins: genesis_config.coins.clone(),
+            start_paused: genesis_config.start_paused,
+            max_transaction_size_in_bytes: genesis_config.max_transaction_size_in_bytes,
         })
     }
 
+    pub fn num_validators(&self) -> usize {
+        self.validators.len()
+    }
+
+    /// Stake of every validator in genesis, in octas
+    pub fn total_stake(&self) -> u128 {
+        self.validators
+            .iter()
+            .map(|validator| validator.validator.stake_amount as u128)
+            .sum()
+    }
+
     pub fn get_genesis(&mut self) -> &Transaction {
         if let Some(ref genesis) = self.genesis {
             genesis
@@ -111,6 +141,13 @@ impl MainnetGenesisInfo {
                 rewards_apy_percentage: self.rewards_apy_percentage,
                 voting_duration_secs: self.voting_duration_secs,
                 voting_power_increase_limit: self.voting_power_increase_limit,
+                scheduled_features: self.scheduled_features.clone(),
+                collections: self.collections.clone(),
+                coins: self.coins.clone(),
+                // Mainnet's accounts are created from its own list
+                accounts: vec![],
+                start_paused: self.start_paused,
+                max_transaction_size_in_bytes: self.max_transaction_size_in_bytes,
             },
         )
     }
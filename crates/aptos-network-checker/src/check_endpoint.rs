@@ -10,10 +10,10 @@ use aptos_crypto::x25519::{self, PRIVATE_KEY_SIZE};
 use aptos_types::{account_address, chain_id::ChainId, network_address::NetworkAddress, PeerId};
 use futures::{AsyncReadExt, AsyncWriteExt};
 use network::{
-    noise::{HandshakeAuthMode, NoiseUpgrader},
+    noise::{stream::NoiseStream, HandshakeAuthMode, NoiseUpgrader},
     protocols::wire::handshake::v1::ProtocolIdSet,
     transport::{resolve_and_connect, TcpSocket},
-    transport::{upgrade_outbound, UpgradeContext, SUPPORTED_MESSAGING_PROTOCOL},
+    transport::{upgrade_outbound, Connection, UpgradeContext, SUPPORTED_MESSAGING_PROTOCOL},
 };
 use std::{collections::BTreeMap, sync::Arc};
 use tokio::time::Duration;
@@ -53,6 +53,34 @@ pub async fn check_endpoint(
     .await
 }
 
+/// Connects via Noise and returns the connection, which stays open until it's dropped.
+///
+/// Unlike `check_endpoint`, this lets callers hold many connections to a node at once, e.g. to
+/// exercise its connection limits.
+pub async fn open_connection(
+    chain_id: ChainId,
+    network_id: NetworkId,
+    private_key: x25519::PrivateKey,
+    address: &NetworkAddress,
+    timeout: Duration,
+) -> Result<Connection<NoiseStream<TcpSocket>>> {
+    let (peer_id, _) = private_key_to_public_info(&private_key);
+    let remote_pubkey = address.find_noise_proto().with_context(|| {
+        format!(
+            "Failed to find noise protocol in {}, /noise-ik/<pubkey> missing",
+            address
+        )
+    })?;
+    let upgrade_context = build_upgrade_context(chain_id, network_id, peer_id, private_key);
+
+    tokio::time::timeout(
+        timeout,
+        connect_with_handshake(upgrade_context, address.clone(), remote_pubkey),
+    )
+    .await
+    .with_context(|| format!("Timed out while connecting to {}", address))?
+}
+
 async fn check_endpoint_wrapper(
     upgrade_context: Arc<UpgradeContext>,
     address: &NetworkAddress,
@@ -84,6 +112,19 @@ async fn check_endpoint_with_handshake(
     address: NetworkAddress,
     remote_pubkey: x25519::PublicKey,
 ) -> Result<String> {
+    let conn = connect_with_handshake(upgrade_context, address, remote_pubkey).await?;
+    let msg = format!("Successfully connected to {}", conn.metadata.addr);
+
+    // Disconnect.
+    drop(conn);
+    Ok(msg)
+}
+
+async fn connect_with_handshake(
+    upgrade_context: Arc<UpgradeContext>,
+    address: NetworkAddress,
+    remote_pubkey: x25519::PublicKey,
+) -> Result<Connection<NoiseStream<TcpSocket>>> {
     // Connect to the address, this should handle DNS resolution if necessary.
     let fut_socket = async {
         resolve_and_connect(address.clone())
@@ -93,7 +134,7 @@ async fn check_endpoint_with_handshake(
 
     // The peer id doesn't matter because we don't validate it.
     let remote_peer_id = account_address::from_identity_public_key(remote_pubkey);
-    let conn = upgrade_outbound(
+    upgrade_outbound(
         upgrade_context,
         fut_socket,
         address.clone(),
@@ -101,12 +142,7 @@ async fn check_endpoint_with_handshake(
         remote_pubkey,
     )
     .await
-    .with_context(|| format!("Failed to connect to {}", address))?;
-    let msg = format!("Successfully connected to {}", conn.metadata.addr);
-
-    // Disconnect.
-    drop(conn);
-    Ok(msg)
+    .with_context(|| format!("Failed to connect to {}", address))
 }
 
 const INVALID_NOISE_HEADER: &[u8; 152] = &[7; 152];
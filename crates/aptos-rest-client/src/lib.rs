@@ -23,8 +23,9 @@ use anyhow::{anyhow, Result};
 use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE},
-    AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, MoveModuleId, TransactionData,
-    TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent,
+    AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, MoveModuleId, TableIterItem,
+    TableIterRequest, TableIterResponse, TransactionData, TransactionOnChainData,
+    TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent,
 };
 use aptos_crypto::HashValue;
 use aptos_types::{
@@ -33,6 +34,7 @@ use aptos_types::{
     contract_event::EventWithVersion,
     transaction::{ExecutionStatus, SignedTransaction},
 };
+use futures::{stream, Stream, TryStreamExt};
 use move_deps::move_core_types::language_storage::StructTag;
 use reqwest::header::ACCEPT;
 use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, StatusCode};
@@ -969,6 +971,55 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Retrieves a page of a table's items, continuing from the cursor of the previous page
+    pub async fn get_table_items_page(
+        &self,
+        table_handle: AccountAddress,
+        cursor: Option<HexEncodedBytes>,
+        limit: Option<u16>,
+        version: Option<u64>,
+    ) -> AptosResult<Response<TableIterResponse>> {
+        let mut url = self.build_path(&format!("tables/{}/iter", table_handle))?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+        let request = TableIterRequest { cursor, limit };
+
+        let response = self.inner.post(url).json(&request).send().await?;
+        self.json(response).await
+    }
+
+    /// Streams every item of a table, retrieving it page by page
+    ///
+    /// Every page is retrieved at the same ledger version, being the given version or otherwise
+    /// the version of the first page, so that the items are a consistent view of the table.  Items
+    /// are ordered by their raw state keys, rather than by the Move ordering of their keys.
+    pub fn stream_table_items(
+        &self,
+        table_handle: AccountAddress,
+        page_size: Option<u16>,
+        version: Option<u64>,
+    ) -> impl Stream<Item = AptosResult<TableIterItem>> + '_ {
+        // The state is the cursor and version of the next page, or `None` after the last page
+        stream::try_unfold(Some((None, version)), move |state| async move {
+            let (cursor, version) = match state {
+                Some(state) => state,
+                None => return Ok(None),
+            };
+            let page = self
+                .get_table_items_page(table_handle, cursor, page_size, version)
+                .await?
+                .into_inner();
+            let items = stream::iter(page.items.into_iter().map(Ok::<_, RestError>));
+            let next = page
+                .cursor
+                .map(|cursor| (Some(cursor), Some(page.ledger_version.0)));
+            Ok(Some((items, next)))
+        })
+        .try_flatten()
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> AptosResult<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address))?;
         let response = self.inner.get(url).send().await?;
@@ -182,7 +182,7 @@ impl CliCommand<RotateSummary> for RotateKey {
                     self.txn_options.prompt_options,
                 ) {
                     match cli_err {
-                        CliError::AbortedError => {
+                        CliError::AbortedError | CliError::AbortedByAssumeNo => {
                             return Ok(RotateSummary {
                                 transaction: txn_summary,
                                 message: None,
@@ -7,13 +7,12 @@ use crate::{
         init::{DEFAULT_FAUCET_URL, DEFAULT_REST_URL},
         utils::{
             chain_id, check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
-            get_auth_key, get_sequence_number, read_from_file, start_logger, to_common_result,
-            to_common_success_result, write_to_file, write_to_file_with_opts,
-            write_to_user_only_file,
+            get_auth_key, get_sequence_number, read_from_file, start_logger_for,
+            to_formatted_result, write_to_file, write_to_file_with_opts, write_to_user_only_file,
         },
     },
     config::GlobalConfig,
-    genesis::git::from_yaml,
+    genesis::{error::GenesisError, git::from_yaml},
 };
 use aptos_crypto::ed25519::Ed25519Signature;
 use aptos_crypto::{
@@ -59,6 +58,8 @@ pub type CliTypedResult<T> = Result<T, CliError>;
 pub enum CliError {
     #[error("Aborted command")]
     AbortedError,
+    #[error("Aborted command by --assume-no")]
+    AbortedByAssumeNo,
     #[error("API error: {0}")]
     ApiError(String),
     #[error("Error (de)serializing '{0}': {1}")]
@@ -69,6 +70,8 @@ pub enum CliError {
     ConfigLoadError(String, String),
     #[error("Unable to find config {0}, have you run `aptos init`?")]
     ConfigNotFoundError(String),
+    #[error(transparent)]
+    GenesisError(#[from] GenesisError),
     #[error("Error accessing '{0}': {1}")]
     IO(String, #[source] std::io::Error),
     #[error("Move compilation failed: {0}")]
@@ -79,6 +82,8 @@ pub enum CliError {
     MoveProverError(String),
     #[error("Unable to parse '{0}': error: {1}")]
     UnableToParse(&'static str, String),
+    #[error("Unable to decrypt '{0}', is the passphrase correct?")]
+    UnableToDecrypt(String),
     #[error("Unable to read file '{0}', error: {1}")]
     UnableToReadFile(String, String),
     #[error("Unexpected error: {0}")]
@@ -89,16 +94,19 @@ impl CliError {
     pub fn to_str(&self) -> &'static str {
         match self {
             CliError::AbortedError => "AbortedError",
+            CliError::AbortedByAssumeNo => "AbortedByAssumeNo",
             CliError::ApiError(_) => "ApiError",
             CliError::BCS(_, _) => "BCS",
             CliError::CommandArgumentError(_) => "CommandArgumentError",
             CliError::ConfigLoadError(_, _) => "ConfigLoadError",
             CliError::ConfigNotFoundError(_) => "ConfigNotFoundError",
+            CliError::GenesisError(_) => "GenesisError",
             CliError::IO(_, _) => "IO",
             CliError::MoveCompilationError(_) => "MoveCompilationError",
             CliError::MoveTestError => "MoveTestError",
             CliError::MoveProverError(_) => "MoveProverError",
             CliError::UnableToParse(_, _) => "UnableToParse",
+            CliError::UnableToDecrypt(_) => "UnableToDecrypt",
             CliError::UnableToReadFile(_, _) => "UnableToReadFile",
             CliError::UnexpectedError(_) => "UnexpectedError",
         }
@@ -457,21 +465,32 @@ impl EncodingType {
     }
 }
 
+/// Length in bytes of the seed used for key generation
+pub const RNG_SEED_LENGTH: usize = 32;
+
 #[derive(Clone, Debug, Parser)]
 pub struct RngArgs {
     /// The seed used for key generation, should be a 64 character hex string and only used for testing
     ///
-    /// If a predictable random seed is used, the key that is produced will be insecure and easy
-    /// to reproduce.  Please do not use this unless sufficient randomness is put into the random
-    /// seed.
-    #[clap(long)]
+    /// Key generation with a seed is fully deterministic: the same seed always generates the same
+    /// keys.  If a predictable random seed is used, the key that is produced will be insecure and
+    /// easy to reproduce.  Please do not use this unless sufficient randomness is put into the
+    /// random seed.
+    #[clap(long, alias = "seed-hex", group = "rng_seed")]
     random_seed: Option<String>,
+    /// File holding the seed used for key generation, as exactly 32 raw bytes
+    ///
+    /// As with `--random-seed`, the same seed always generates the same keys, and should only be
+    /// used for testing.
+    #[clap(long, group = "rng_seed", parse(from_os_str))]
+    seed_file: Option<PathBuf>,
 }
 
 impl RngArgs {
     pub fn from_seed(seed: [u8; 32]) -> RngArgs {
         RngArgs {
             random_seed: Some(hex::encode(seed)),
+            seed_file: None,
         }
     }
 
@@ -480,9 +499,29 @@ impl RngArgs {
         if let Some(ref seed) = self.random_seed {
             // Strip 0x
             let seed = seed.strip_prefix("0x").unwrap_or(seed);
-            let mut seed_slice = [0u8; 32];
+            if seed.len() != 2 * RNG_SEED_LENGTH {
+                return Err(CliError::UnableToParse(
+                    "--random-seed",
+                    format!(
+                        "expected {} hex characters, found {}",
+                        2 * RNG_SEED_LENGTH,
+                        seed.len()
+                    ),
+                ));
+            }
+            let mut seed_slice = [0u8; RNG_SEED_LENGTH];
 
-            hex::decode_to_slice(seed, &mut seed_slice)?;
+            hex::decode_to_slice(seed, &mut seed_slice)
+                .map_err(|err| CliError::UnableToParse("--random-seed", err.to_string()))?;
+            Ok(KeyGen::from_seed(seed_slice))
+        } else if let Some(ref seed_file) = self.seed_file {
+            let seed = read_from_file(seed_file)?;
+            let seed_slice: [u8; RNG_SEED_LENGTH] = seed.as_slice().try_into().map_err(|_| {
+                CliError::UnableToParse(
+                    "--seed-file",
+                    format!("expected {} bytes, found {}", RNG_SEED_LENGTH, seed.len()),
+                )
+            })?;
             Ok(KeyGen::from_seed(seed_slice))
         } else {
             Ok(KeyGen::from_os_rng())
@@ -526,7 +565,7 @@ pub struct PromptOptions {
     /// Assume yes for all yes/no prompts
     #[clap(long, group = "prompt_options")]
     pub assume_yes: bool,
-    /// Assume no for all yes/no prompts
+    /// Assume no for all yes/no prompts, aborting instead of overwriting or changing anything
     #[clap(long, group = "prompt_options")]
     pub assume_no: bool,
 }
@@ -539,6 +578,7 @@ impl PromptOptions {
         }
     }
 
+    /// Declines every prompt, e.g. for automated runs which mustn't hang waiting for input
     pub fn no() -> Self {
         Self {
             assume_yes: false,
@@ -547,6 +587,84 @@ impl PromptOptions {
     }
 }
 
+/// Formats the result of a command is printed in
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// The result, or the error, in a pretty printed JSON object
+    Default,
+    /// A [`CommandOutput`] on a single line, with logs written to stderr instead of stdout
+    Json,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        OutputFormat::Default
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            OutputFormat::Default => "default",
+            OutputFormat::Json => "json",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(OutputFormat::Default),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err("Invalid output format. Valid values are default, json"),
+        }
+    }
+}
+
+/// An insertable option for how the result of a command is printed
+#[derive(Clone, Copy, Debug, Default, Parser)]
+pub struct OutputOptions {
+    /// Format to print the result in: [default, json]
+    ///
+    /// With json, stdout holds only the result as a JSON object with stable fields, so that
+    /// scripts can read it, and logs are written to stderr
+    #[clap(long = "output", default_value_t = OutputFormat::Default)]
+    pub output_format: OutputFormat,
+}
+
+/// The result of a command, as printed with `--output json`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommandOutput {
+    /// Name of the command e.g. GenerateGenesis
+    pub command: String,
+    pub success: bool,
+    /// The command specific result, if it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// Why the command failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CommandOutput {
+    pub fn new<T: Serialize>(command: &str, result: &CliTypedResult<T>) -> CommandOutput {
+        let result = match result {
+            Ok(result) => serde_json::to_value(result)
+                .map_err(|err| format!("Failed to serialize the result: {}", err)),
+            Err(err) => Err(err.to_string()),
+        };
+        CommandOutput {
+            command: command.to_string(),
+            success: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+            result: result.ok(),
+        }
+    }
+}
+
 /// An insertable option for use with encodings.
 #[derive(Debug, Default, Parser)]
 pub struct EncodingOptions {
@@ -951,27 +1069,48 @@ pub trait CliCommand<T: Serialize + Send>: Sized + Send {
     /// Executes the command, returning a command specific type
     async fn execute(self) -> CliTypedResult<T>;
 
+    /// Returns the format the result is printed in, which commands taking `--output` override
+    fn output_format(&self) -> OutputFormat {
+        OutputFormat::Default
+    }
+
     /// Executes the command, and serializes it to the common JSON output type
     async fn execute_serialized(self) -> CliResult {
         let command_name = self.command_name();
-        start_logger();
+        let output_format = self.output_format();
+        start_logger_for(output_format);
         let start_time = Instant::now();
-        to_common_result(command_name, start_time, self.execute().await).await
+        to_formatted_result(
+            command_name,
+            start_time,
+            self.execute().await,
+            output_format,
+        )
+        .await
     }
 
     /// Same as execute serialized without setting up logging
     async fn execute_serialized_without_logger(self) -> CliResult {
         let command_name = self.command_name();
+        let output_format = self.output_format();
         let start_time = Instant::now();
-        to_common_result(command_name, start_time, self.execute().await).await
+        to_formatted_result(
+            command_name,
+            start_time,
+            self.execute().await,
+            output_format,
+        )
+        .await
     }
 
     /// Executes the command, and throws away Ok(result) for the string Success
     async fn execute_serialized_success(self) -> CliResult {
-        start_logger();
+        let output_format = self.output_format();
+        start_logger_for(output_format);
         let command_name = self.command_name();
         let start_time = Instant::now();
-        to_common_success_result(command_name, start_time, self.execute().await).await
+        let result = self.execute().await.map(|_| "Success");
+        to_formatted_result(command_name, start_time, result, output_format).await
     }
 }
 
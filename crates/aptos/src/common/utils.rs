@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    common::types::{CliError, CliTypedResult, PromptOptions},
+    common::types::{CliError, CliTypedResult, CommandOutput, OutputFormat, PromptOptions},
     CliResult,
 };
 use aptos_build_info::build_information;
@@ -20,9 +20,10 @@ use std::{
     collections::BTreeMap,
     env,
     fs::OpenOptions,
-    io::Write,
+    io::{BufWriter, ErrorKind, Read, Write},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
     time::{Duration, Instant},
 };
 
@@ -78,6 +79,36 @@ pub async fn to_common_result<T: Serialize>(
     }
 }
 
+/// For printing outputs as a single line `CommandOutput`, with `--output json`
+pub async fn to_command_output<T: Serialize>(
+    command: &str,
+    start_time: Instant,
+    result: CliTypedResult<T>,
+) -> CliResult {
+    let latency = start_time.elapsed();
+    let output = CommandOutput::new(command, &result);
+    send_telemetry_event(command, latency, output.success, output.error.clone()).await;
+    let string = serde_json::to_string(&output).unwrap();
+    if output.success {
+        Ok(string)
+    } else {
+        Err(string)
+    }
+}
+
+/// Prints outputs in the given format
+pub async fn to_formatted_result<T: Serialize>(
+    command: &str,
+    start_time: Instant,
+    result: CliTypedResult<T>,
+    output_format: OutputFormat,
+) -> CliResult {
+    match output_format {
+        OutputFormat::Default => to_common_result(command, start_time, result).await,
+        OutputFormat::Json => to_command_output(command, start_time, result).await,
+    }
+}
+
 pub fn cli_build_information() -> BTreeMap<String, String> {
     build_information!()
 }
@@ -134,7 +165,7 @@ impl<T> From<CliTypedResult<T>> for ResultWrapper<T> {
 
 /// Checks if a file exists, being overridden by `PromptOptions`
 pub fn check_if_file_exists(file: &Path, prompt_options: PromptOptions) -> CliTypedResult<()> {
-    if file.exists() {
+    if !is_stdio(file) && file.exists() {
         prompt_yes_with_override(
             &format!(
                 "{:?} already exists, are you sure you want to overwrite it?",
@@ -148,19 +179,58 @@ pub fn check_if_file_exists(file: &Path, prompt_options: PromptOptions) -> CliTy
 }
 
 pub fn prompt_yes_with_override(prompt: &str, prompt_options: PromptOptions) -> CliTypedResult<()> {
-    if prompt_options.assume_no || (!prompt_options.assume_yes && !prompt_yes(prompt)) {
+    if prompt_options.assume_no {
+        Err(CliError::AbortedByAssumeNo)
+    } else if !prompt_options.assume_yes && !prompt_yes(prompt) {
         Err(CliError::AbortedError)
     } else {
         Ok(())
     }
 }
 
+/// Read the contents of a file, or everything on stdin if the path is `-`
 pub fn read_from_file(path: &Path) -> CliTypedResult<Vec<u8>> {
+    if is_stdio(path) {
+        return read_from_reader(std::io::stdin().lock(), "stdin");
+    }
     std::fs::read(path)
         .map_err(|e| CliError::UnableToReadFile(format!("{}", path.display()), e.to_string()))
 }
 
-/// Write a `&[u8]` to a file
+/// Path which stands for stdin when it's read from, and stdout when it's written to
+pub const STDIO_PATH: &str = "-";
+
+/// Whether a path is `-`, to pipe through stdin or stdout instead of a file
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO_PATH)
+}
+
+/// Set once a file has been written to stdout, see `wrote_to_stdout`
+static WROTE_TO_STDOUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether a file has been written to stdout for the path `-`
+///
+/// The result of the command is then printed to stderr, so that stdout holds only the file.
+pub fn wrote_to_stdout() -> bool {
+    WROTE_TO_STDOUT.load(Ordering::SeqCst)
+}
+
+/// Stdout, locked to write a file to it for the path `-`
+fn stdout_for_file() -> std::io::StdoutLock<'static> {
+    WROTE_TO_STDOUT.store(true, Ordering::SeqCst);
+    std::io::stdout().lock()
+}
+
+/// Read everything from a reader e.g. stdin
+pub fn read_from_reader(mut reader: impl Read, name: &str) -> CliTypedResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| CliError::UnableToReadFile(name.to_string(), e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Write a `&[u8]` to a file, or as is to stdout if the path is `-`
 pub fn write_to_file(path: &Path, name: &str, bytes: &[u8]) -> CliTypedResult<()> {
     write_to_file_with_opts(path, name, bytes, &mut OpenOptions::new())
 }
@@ -173,20 +243,87 @@ pub fn write_to_user_only_file(path: &Path, name: &str, bytes: &[u8]) -> CliType
     write_to_file_with_opts(path, name, bytes, &mut opts)
 }
 
-/// Write a `&[u8]` to a file with the given options
+/// Write a `&[u8]` to a file with the given options, or as is to stdout if the path is `-`
+///
+/// The file is written atomically: the bytes go to a temporary file next to it, which is then
+/// renamed over it.  A reader never sees a partially written file, and if writing is interrupted,
+/// whatever was at the path before is left as it was.
 pub fn write_to_file_with_opts(
     path: &Path,
     name: &str,
     bytes: &[u8],
     opts: &mut OpenOptions,
 ) -> CliTypedResult<()> {
-    let mut file = opts
+    if is_stdio(path) {
+        return write_to_writer(stdout_for_file(), name, bytes);
+    }
+    write_atomically(path, name, opts, |file| file.write_all(bytes))
+}
+
+/// Serialize a value as BCS straight into a file, or to stdout if the path is `-`
+///
+/// Writes the same bytes as `write_to_file` with `bcs::to_bytes`, but without holding the whole
+/// encoding in memory, for values too large to buffer.  The file is written atomically, in the
+/// same way.
+pub fn write_bcs_to_file<T: Serialize>(path: &Path, name: &str, value: &T) -> CliTypedResult<()> {
+    let serialize = |writer: &mut dyn Write| -> std::io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        bcs::serialize_into(&mut writer, value)
+            .map_err(|e| std::io::Error::new(ErrorKind::Other, e))?;
+        writer.flush()
+    };
+    if is_stdio(path) {
+        return serialize(&mut stdout_for_file()).map_err(|e| CliError::IO(name.to_string(), e));
+    }
+    write_atomically(path, name, &mut OpenOptions::new(), serialize)
+}
+
+/// Writes a file with `write` through a temporary file renamed over it, see
+/// `write_to_file_with_opts`
+fn write_atomically(
+    path: &Path,
+    name: &str,
+    opts: &mut OpenOptions,
+    write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
+) -> CliTypedResult<()> {
+    let temp_path = temp_file_path(path);
+    let result = opts
         .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
-        .map_err(|e| CliError::IO(name.to_string(), e))?;
-    file.write_all(bytes)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.map_err(|e| CliError::IO(name.to_string(), e))
+}
+
+/// Prefix of the temporary files written to, before being renamed into place
+pub const TEMP_FILE_PREFIX: &str = ".tmp-";
+
+/// A temporary path in the same directory as `path`, so that it can be renamed to `path`
+fn temp_file_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        "{}{}-{:08x}",
+        TEMP_FILE_PREFIX,
+        file_name,
+        rand::random::<u32>()
+    ))
+}
+
+/// Write a `&[u8]` to a writer e.g. stdout, flushing it so nothing is left buffered
+pub fn write_to_writer(mut writer: impl Write, name: &str, bytes: &[u8]) -> CliTypedResult<()> {
+    writer
+        .write_all(bytes)
+        .and_then(|_| writer.flush())
         .map_err(|e| CliError::IO(name.to_string(), e))
 }
 
@@ -341,11 +478,33 @@ pub async fn fund_account(
 }
 
 pub fn start_logger() {
+    start_logger_for(OutputFormat::Default)
+}
+
+/// Starts the logger, writing logs to stderr with `--output json` so that stdout holds only the
+/// output
+pub fn start_logger_for(output_format: OutputFormat) {
     let mut logger = aptos_logger::Logger::new();
     logger
         .channel_size(1000)
         .is_async(false)
         .level(Level::Warn)
         .read_env();
+    if output_format == OutputFormat::Json {
+        logger.printer(Box::new(StderrWriter));
+    }
     logger.build();
 }
+
+/// Writes logs to stderr
+struct StderrWriter;
+
+impl aptos_logger::Writer for StderrWriter {
+    fn write(&self, log: String) {
+        eprintln!("{}", log);
+    }
+
+    fn write_buferred(&mut self, log: String) {
+        eprintln!("{}", log);
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::read_from_file,
+    },
+    genesis::{error::GenesisError, git::from_yaml},
+};
+use aptos_types::account_address::AccountAddress;
+use clap::ArgEnum;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use vm_genesis::AccountMap;
+
+/// Header of a CSV of account balances, as exported from a spreadsheet
+pub const BALANCES_CSV_HEADER: [&str; 2] = ["account_address", "balance"];
+
+/// Number of octas in one APT
+pub const OCTAS_PER_APT: u64 = 100_000_000;
+/// Number of decimal places of APT, below which there's less than one octa
+const APT_DECIMALS: usize = 8;
+
+/// Units balances are given in, which are always converted to octas for genesis
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BalanceUnit {
+    /// Whole APT, which may have up to 8 decimal places
+    Apt,
+    /// Octas, as genesis stores balances in
+    Octas,
+}
+
+impl BalanceUnit {
+    /// Converts a balance written in this unit to octas
+    ///
+    /// On failure, the reason is given as the end of a sentence about the balance
+    pub fn to_octas(self, balance: &str) -> Result<u64, String> {
+        let not_a_number = |err: std::num::ParseIntError| format!("is not a number: {}", err);
+        match self {
+            BalanceUnit::Octas => u64::from_str(balance).map_err(not_a_number),
+            BalanceUnit::Apt => {
+                let (whole, fraction) = balance.split_once('.').unwrap_or((balance, ""));
+                let fraction = fraction.trim_end_matches('0');
+                if !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err("is not a number, its decimal places aren't digits".to_string());
+                }
+                if fraction.len() > APT_DECIMALS {
+                    return Err(format!(
+                        "is finer than one octa, APT has only {} decimal places",
+                        APT_DECIMALS
+                    ));
+                }
+                let whole = u64::from_str(whole).map_err(not_a_number)?;
+                let fraction = if fraction.is_empty() {
+                    0
+                } else {
+                    let octas = format!("{:0<width$}", fraction, width = APT_DECIMALS);
+                    u64::from_str(&octas).map_err(not_a_number)?
+                };
+                whole
+                    .checked_mul(OCTAS_PER_APT)
+                    .and_then(|octas| octas.checked_add(fraction))
+                    .ok_or_else(|| format!("APT is more than the maximum of {} octas", u64::MAX))
+            }
+        }
+    }
+}
+
+impl Display for BalanceUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            BalanceUnit::Apt => "apt",
+            BalanceUnit::Octas => "octas",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for BalanceUnit {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apt" => Ok(BalanceUnit::Apt),
+            "octas" => Ok(BalanceUnit::Octas),
+            _ => Err("Invalid balance unit. Valid values are apt, octas"),
+        }
+    }
+}
+
+/// Reads the initial account balances of mainnet from a CSV, in place of the balances file
+pub fn read_balances_csv(path: &Path, unit: BalanceUnit) -> CliTypedResult<Vec<AccountMap>> {
+    let contents = String::from_utf8(read_from_file(path)?).map_err(CliError::from)?;
+    parse_balances_csv(&contents, unit)
+}
+
+/// Reads and merges the initial account balances of mainnet from several files, in place of the
+/// balances file
+///
+/// Files ending in `.csv` are read as CSVs, and others as YAML like the balances file.  An account
+/// may only be in one of the files, so that no allocation is counted twice.  The merged balances
+/// are sorted by account, so they're the same whatever order the files are given in.
+pub fn read_balances_files(
+    paths: &[PathBuf],
+    unit: BalanceUnit,
+) -> CliTypedResult<Vec<AccountMap>> {
+    let mut paths = paths.to_vec();
+    paths.sort();
+
+    let mut balances = BTreeMap::new();
+    for path in &paths {
+        for account in read_balances_file(path, unit)? {
+            let address = account.account_address;
+            if let Some((_, first_path)) = balances.insert(address, (account.balance, path)) {
+                let reason = if first_path == path {
+                    format!("it's listed twice in {}", path.display())
+                } else {
+                    format!(
+                        "it's in both {} and {}",
+                        first_path.display(),
+                        path.display()
+                    )
+                };
+                return Err(GenesisError::BalancesInvalid(format!(
+                    "Balances files can't share account {}, but {}",
+                    address.to_hex_literal(),
+                    reason
+                ))
+                .into());
+            }
+        }
+    }
+    Ok(balances
+        .into_iter()
+        .map(|(account_address, (balance, _))| AccountMap {
+            account_address,
+            balance,
+        })
+        .collect())
+}
+
+/// Reads a CSV or YAML file of balances, whose balances in YAML are whole numbers of the unit
+fn read_balances_file(path: &Path, unit: BalanceUnit) -> CliTypedResult<Vec<AccountMap>> {
+    if path
+        .extension()
+        .map_or(false, |extension| extension == "csv")
+    {
+        return read_balances_csv(path, unit);
+    }
+    let contents = String::from_utf8(read_from_file(path)?).map_err(CliError::from)?;
+    let mut accounts: Vec<AccountMap> = from_yaml(&contents)?;
+    for account in accounts.iter_mut() {
+        account.balance = unit
+            .to_octas(&account.balance.to_string())
+            .map_err(|reason| {
+                CliError::UnableToParse(
+                    "--balances-file",
+                    format!(
+                        "{}: balance {} of account {} {}",
+                        path.display(),
+                        account.balance,
+                        account.account_address.to_hex_literal(),
+                        reason
+                    ),
+                )
+            })?;
+    }
+    Ok(accounts)
+}
+
+/// Parses a CSV of account balances in the given unit, with an `account_address,balance` header
+///
+/// Blank lines are skipped, and fields may be quoted.  Malformed addresses, non-numeric
+/// balances and addresses listed twice are reported with the line they're on.
+pub fn parse_balances_csv(contents: &str, unit: BalanceUnit) -> CliTypedResult<Vec<AccountMap>> {
+    let mut lines = contents
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let (line_number, header) = lines
+        .next()
+        .ok_or_else(|| csv_error("File is empty".to_string()))?;
+    if parse_csv_line(header) != BALANCES_CSV_HEADER {
+        return Err(csv_error(format!(
+            "Line {}: expected header {}, found {}",
+            line_number,
+            BALANCES_CSV_HEADER.join(","),
+            header
+        )));
+    }
+
+    let mut accounts = Vec::new();
+    let mut lines_by_address = BTreeMap::new();
+    for (line_number, line) in lines {
+        let (address, balance) = match parse_csv_line(line).as_slice() {
+            [address, balance] => (*address, *balance),
+            fields => {
+                return Err(csv_error(format!(
+                    "Line {}: expected 2 fields, found {}",
+                    line_number,
+                    fields.len()
+                )))
+            }
+        };
+        let account_address = AccountAddress::from_str(address).map_err(|err| {
+            csv_error(format!(
+                "Line {}: malformed account address {}: {}",
+                line_number, address, err
+            ))
+        })?;
+        let balance = unit.to_octas(balance).map_err(|reason| {
+            csv_error(format!(
+                "Line {}: balance {} {}",
+                line_number, balance, reason
+            ))
+        })?;
+        if let Some(first_line) = lines_by_address.insert(account_address, line_number) {
+            return Err(csv_error(format!(
+                "Line {}: account {} is already on line {}",
+                line_number,
+                account_address.to_hex_literal(),
+                first_line
+            )));
+        }
+        accounts.push(AccountMap {
+            account_address,
+            balance,
+        });
+    }
+
+    if accounts.is_empty() {
+        return Err(csv_error("File has no balances".to_string()));
+    }
+    Ok(accounts)
+}
+
+fn parse_csv_line(line: &str) -> Vec<&str> {
+    line.split(',')
+        .map(|field| field.trim().trim_matches('"').trim())
+        .collect()
+}
+
+fn csv_error(reason: String) -> CliError {
+    CliError::UnableToParse("--balances-csv", reason)
+}
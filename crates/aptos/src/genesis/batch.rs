@@ -0,0 +1,212 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult, OutputFormat, OutputOptions},
+    genesis::{
+        encrypt::PassphraseOptions,
+        git::GitOptions,
+        keys::{
+            check_reachability, SetValidatorConfiguration, ValidatorConfigurationSummary,
+            PUBLIC_KEYS_FILE,
+        },
+    },
+    CliCommand,
+};
+use aptos_genesis::config::HostAndPort;
+use async_trait::async_trait;
+use clap::Parser;
+use futures::{stream, StreamExt};
+use std::{collections::BTreeSet, path::PathBuf, str::FromStr};
+
+/// Number of users whose configurations are set at once, by default
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Set the validator configurations of many users at once
+///
+/// For a coordinator holding every user's keys.  Each `--user-dir` holds a user's keys generated
+/// by `GenerateKeys`, and is paired with the `--validator-host` at the same position.  The
+/// configurations are built and written concurrently, holding the repository's lock throughout.
+/// A user whose configuration fails doesn't stop the others being written, and every failure is
+/// reported once the rest are done.
+#[derive(Parser)]
+pub struct SetValidatorConfigurations {
+    /// A user's keys directory, as `<username>=<dir>`, or just `<dir>` named after the user
+    #[clap(long = "user-dir", required = true)]
+    pub(crate) user_dirs: Vec<UserDirectory>,
+
+    /// Host and port pair of each user's validator, in the order of `--user-dir`
+    #[clap(long = "validator-host", required = true)]
+    pub(crate) validator_hosts: Vec<HostAndPort>,
+
+    /// Stake amount of every user
+    #[clap(long, default_value_t = 1)]
+    pub(crate) stake_amount: u64,
+
+    /// Commission rate every user pays their operator
+    #[clap(long, default_value_t = 0)]
+    pub(crate) commission_percentage: u64,
+
+    /// Check each validator host can be connected to before writing its configuration
+    #[clap(long)]
+    pub(crate) check_reachability: bool,
+
+    /// Commit every configuration written, in one commit, to the local git repository
+    #[clap(long)]
+    pub(crate) commit: bool,
+
+    /// Number of configurations to build and write at once
+    #[clap(long, default_value_t = DEFAULT_CONCURRENCY)]
+    pub(crate) concurrency: usize,
+
+    #[clap(flatten)]
+    pub(crate) output_options: OutputOptions,
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// A user's keys directory given to `SetValidatorConfigurations`
+#[derive(Clone, Debug)]
+pub struct UserDirectory {
+    pub username: String,
+    pub dir: PathBuf,
+}
+
+impl FromStr for UserDirectory {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (username, dir) = match s.split_once('=') {
+            Some((username, dir)) => (username.to_string(), PathBuf::from(dir)),
+            None => {
+                let dir = PathBuf::from(s);
+                let username = dir
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or_else(|| {
+                        CliError::CommandArgumentError(format!(
+                            "--user-dir {} isn't named after a user, give it as <username>=<dir>",
+                            s
+                        ))
+                    })?
+                    .to_string();
+                (username, dir)
+            }
+        };
+        if username.is_empty() {
+            return Err(CliError::CommandArgumentError(format!(
+                "--user-dir {} has an empty username",
+                s
+            )));
+        }
+        Ok(UserDirectory { username, dir })
+    }
+}
+
+#[async_trait]
+impl CliCommand<Vec<ValidatorConfigurationSummary>> for SetValidatorConfigurations {
+    fn command_name(&self) -> &'static str {
+        "SetValidatorConfigurations"
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        self.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<ValidatorConfigurationSummary>> {
+        if self.user_dirs.len() != self.validator_hosts.len() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Expected a --validator-host for each of the {} --user-dir, but got {}",
+                self.user_dirs.len(),
+                self.validator_hosts.len()
+            )));
+        }
+        if self.concurrency == 0 {
+            return Err(CliError::CommandArgumentError(
+                "--concurrency must be at least 1".to_string(),
+            ));
+        }
+        let mut usernames = BTreeSet::new();
+        for user_dir in &self.user_dirs {
+            if !usernames.insert(&user_dir.username) {
+                return Err(CliError::CommandArgumentError(format!(
+                    "User {} is given more than once",
+                    user_dir.username
+                )));
+            }
+        }
+
+        // The lock is held for the whole batch, so each write skips taking it
+        let git_client = self.git_options.clone().get_client()?;
+        let _lock = git_client.lock()?;
+        let commands: Vec<_> = self
+            .user_dirs
+            .iter()
+            .zip(&self.validator_hosts)
+            .map(|(user_dir, validator_host)| SetValidatorConfiguration {
+                username: user_dir.username.clone(),
+                validator_host: vec![validator_host.clone()],
+                full_node_host: None,
+                stake_amount: self.stake_amount,
+                commission_percentage: self.commission_percentage,
+                check_reachability: self.check_reachability,
+                commit: false,
+                owner_public_identity_file: Some(user_dir.dir.join(PUBLIC_KEYS_FILE)),
+                owner_private_keys_file: None,
+                operator_public_identity_file: None,
+                voter_public_identity_file: None,
+                passphrase_options: PassphraseOptions::default(),
+                output_options: OutputOptions::default(),
+                git_options: self.git_options.clone(),
+            })
+            .collect();
+        let results: Vec<_> = stream::iter(commands)
+            .map(|command| tokio::task::spawn_blocking(move || configure(command)))
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+
+        let mut summaries = Vec::new();
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        for (user_dir, result) in self.user_dirs.iter().zip(results) {
+            match result {
+                Ok(Ok((summary, written))) => {
+                    summaries.push(summary);
+                    files.extend(written);
+                }
+                Ok(Err(err)) => errors.push(format!("{}: {}", user_dir.username, err)),
+                Err(err) => errors.push(format!("{}: {}", user_dir.username, err)),
+            }
+        }
+        if self.commit && !files.is_empty() {
+            git_client.commit(
+                &files,
+                &format!("add configs for {} users", summaries.len()),
+            )?;
+        }
+        if errors.is_empty() {
+            Ok(summaries)
+        } else {
+            Err(CliError::CommandArgumentError(format!(
+                "Failed to set the configurations of {} users, the other {} were written:\n{}",
+                errors.len(),
+                summaries.len(),
+                errors.join("\n")
+            )))
+        }
+    }
+}
+
+/// Builds and writes a user's configurations, with the repository's lock already held
+fn configure(
+    command: SetValidatorConfiguration,
+) -> CliTypedResult<(ValidatorConfigurationSummary, Vec<PathBuf>)> {
+    let (operator_config, owner_config) = command.build_configurations()?;
+    if command.check_reachability {
+        check_reachability(&command.validator_host)?;
+    }
+    let git_client = command.git_options.clone().get_client()?;
+    let files = command.put_configurations(&git_client, &operator_config, &owner_config)?;
+    Ok((command.summary(&owner_config), files))
+}
@@ -0,0 +1,121 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult},
+    genesis::{
+        git::{GitOptions, BALANCES_FILE},
+        mainnet_genesis_info,
+        progress::ProgressReporter,
+    },
+    CliCommand,
+};
+use aptos_crypto::HashValue;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::{path::Path, time::Instant};
+use sysinfo::{ProcessExt, System, SystemExt};
+use vm_genesis::AccountMap;
+
+/// Balance of every synthetic account
+const SYNTHETIC_BALANCE: u64 = 1_000_000;
+
+/// Benchmark mainnet genesis generation across several balances file sizes
+///
+/// For each size, that many synthetic accounts are added to the balances file of the Git
+/// repository, and the time to generate genesis and the memory used by the process afterwards
+/// are reported.  Comparing the time per account across sizes shows any superlinear behavior.
+#[derive(Parser)]
+pub struct BenchGenesis {
+    /// Numbers of synthetic accounts to generate genesis with
+    #[clap(long, multiple_values = true, default_values = &["1000", "10000", "100000"])]
+    pub(crate) sizes: Vec<usize>,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// Cost of generating genesis with a given number of synthetic accounts
+#[derive(Debug, Serialize)]
+pub struct GenesisBenchResult {
+    pub num_accounts: usize,
+    /// Time taken to generate the genesis transaction
+    pub duration_ms: u128,
+    /// Time taken per synthetic account
+    pub micros_per_account: u128,
+    /// Resident memory of the process after generating genesis, if it could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<GenesisBenchResult>> for BenchGenesis {
+    fn command_name(&self) -> &'static str {
+        "BenchGenesis"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<GenesisBenchResult>> {
+        if self.sizes.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "At least one size must be provided".to_string(),
+            ));
+        }
+        let mut sizes = self.sizes;
+        sizes.sort_unstable();
+
+        let client = self.git_options.get_client()?;
+        let accounts: Vec<AccountMap> = client.get(Path::new(BALANCES_FILE))?;
+
+        let mut results = Vec::new();
+        for num_accounts in sizes {
+            let mut all_accounts = accounts.clone();
+            all_accounts.extend(synthetic_accounts(num_accounts));
+            let (mut genesis_info, _) = mainnet_genesis_info(
+                &client,
+                all_accounts,
+                vec![],
+                vec![],
+                false,
+                None,
+                true,
+                None,
+                &ProgressReporter::default(),
+            )?;
+
+            let start = Instant::now();
+            genesis_info.get_genesis();
+            let duration = start.elapsed();
+
+            results.push(GenesisBenchResult {
+                num_accounts,
+                duration_ms: duration.as_millis(),
+                micros_per_account: duration.as_micros() / std::cmp::max(num_accounts, 1) as u128,
+                memory_bytes: process_memory(),
+            });
+        }
+        Ok(results)
+    }
+}
+
+/// Deterministic accounts which can't collide with configured or framework accounts
+fn synthetic_accounts(num_accounts: usize) -> impl Iterator<Item = AccountMap> {
+    (0..num_accounts).map(|index| AccountMap {
+        account_address: AccountAddress::new(
+            *HashValue::sha3_256_of(format!("bench-genesis-{}", index).as_bytes()).as_ref(),
+        ),
+        balance: SYNTHETIC_BALANCE,
+    })
+}
+
+/// Resident memory of the current process in bytes
+fn process_memory() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = System::new();
+    system.refresh_process(pid);
+    // sysinfo reports memory in KB, as read from /proc
+    system
+        .process(pid)
+        .map(|process| process.memory().saturating_mul(1024))
+}
@@ -0,0 +1,101 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, OutputFormat, OutputOptions},
+        utils::read_from_file,
+    },
+    genesis::dump::{summarize_genesis, GenesisSummary},
+    CliCommand,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::transaction::Transaction;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Decode a BCS encoded transaction, such as a genesis.blob, and print it as JSON
+///
+/// A genesis transaction is summarized by the validator set, balances and framework modules it
+/// writes, rather than printed with its whole write set.  Any other transaction is printed as it
+/// was encoded.
+#[derive(Parser)]
+pub struct DecodeTransaction {
+    /// Path to the BCS encoded transaction
+    #[clap(long, parse(from_os_str))]
+    pub(crate) transaction_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) output_options: OutputOptions,
+}
+
+/// A transaction decoded by `DecodeTransaction`
+#[derive(Debug, Serialize)]
+pub struct DecodedTransaction {
+    pub hash: HashValue,
+    /// Kind of the transaction: [user, genesis, block_metadata, state_checkpoint]
+    pub kind: &'static str,
+    /// What a genesis transaction writes, in place of its write set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genesis: Option<GenesisSummary>,
+    /// The transaction, for every kind but genesis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<Transaction>,
+}
+
+#[async_trait]
+impl CliCommand<DecodedTransaction> for DecodeTransaction {
+    fn command_name(&self) -> &'static str {
+        "DecodeTransaction"
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        self.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<DecodedTransaction> {
+        decode_transaction(&read_transaction(&self.transaction_file)?)
+    }
+}
+
+/// Reads and decodes a file holding a BCS encoded `Transaction`
+///
+/// Bytes which aren't a whole `Transaction`, such as a waypoint file or a truncated blob, are
+/// reported as such rather than as a bare BCS error.
+pub fn read_transaction(path: &Path) -> CliTypedResult<Transaction> {
+    let bytes = read_from_file(path)?;
+    bcs::from_bytes(&bytes).map_err(|err| {
+        CliError::UnableToParse(
+            "transaction",
+            format!(
+                "{} is not a BCS encoded transaction ({} bytes): {}",
+                path.display(),
+                bytes.len(),
+                err
+            ),
+        )
+    })
+}
+
+/// Decodes a transaction for display, summarizing a genesis transaction
+pub fn decode_transaction(transaction: &Transaction) -> CliTypedResult<DecodedTransaction> {
+    let hash = transaction.hash();
+    let kind = match transaction {
+        Transaction::UserTransaction(_) => "user",
+        Transaction::GenesisTransaction(_) => "genesis",
+        Transaction::BlockMetadata(_) => "block_metadata",
+        Transaction::StateCheckpoint(_) => "state_checkpoint",
+    };
+    let (genesis, transaction) = match transaction {
+        Transaction::GenesisTransaction(_) => (Some(summarize_genesis(transaction, None)?), None),
+        _ => (None, Some(transaction.clone())),
+    };
+    Ok(DecodedTransaction {
+        hash,
+        kind,
+        genesis,
+        transaction,
+    })
+}
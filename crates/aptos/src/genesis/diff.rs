@@ -0,0 +1,442 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::read_from_file,
+    },
+    genesis::{
+        dump::{summarize_genesis, GenesisValidator},
+        read_waypoint, GENESIS_FILE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    access_path::Path as AccessPathType,
+    account_address::AccountAddress,
+    state_store::state_key::StateKey,
+    transaction::{Transaction, WriteSetPayload},
+    write_set::TransactionWrite,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+/// Compare the genesis outputs of two directories
+///
+/// Both genesis blobs are decoded and compared field by field, reporting the validators,
+/// balances, framework modules and chain id which differ, along with the waypoints.  With
+/// `--state`, every state value is compared as well, reporting exactly which accounts and
+/// resources differ.  Fails if the outputs differ in any way, so that it can be used in scripts.
+#[derive(Parser)]
+pub struct DiffGenesis {
+    /// Directory containing the first genesis output
+    #[clap(long, parse(from_os_str))]
+    pub(crate) first_dir: PathBuf,
+
+    /// Directory containing the second genesis output
+    #[clap(long, parse(from_os_str))]
+    pub(crate) second_dir: PathBuf,
+
+    /// Name of the genesis file in both directories
+    #[clap(long, default_value = GENESIS_FILE)]
+    pub(crate) genesis_file_name: String,
+
+    /// Name of the waypoint file in both directories
+    #[clap(long, default_value = WAYPOINT_FILE)]
+    pub(crate) waypoint_file_name: String,
+
+    /// Materialize both genesis states and diff every state value
+    ///
+    /// This is the authoritative semantic comparison, but is heavier than the default
+    #[clap(long)]
+    pub(crate) state: bool,
+}
+
+/// Differences found between two genesis outputs
+#[derive(Debug, Serialize)]
+pub struct GenesisDiff {
+    /// Whether the genesis blobs are byte for byte identical
+    pub identical: bool,
+    /// Fields of the decoded genesis, and the waypoints, which differ
+    pub differences: Vec<String>,
+    /// State values which differ, only computed with `--state`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<Vec<StateValueDiff>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum StateValueDiffKind {
+    OnlyInFirst,
+    OnlyInSecond,
+    Modified,
+}
+
+/// A single state value which differs between two genesis states
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct StateValueDiff {
+    /// Human readable state key e.g. the account and resource type
+    pub key: String,
+    pub kind: StateValueDiffKind,
+    /// Hex encoded value in the first genesis, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_value: Option<String>,
+    /// Hex encoded value in the second genesis, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_value: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<GenesisDiff> for DiffGenesis {
+    fn command_name(&self) -> &'static str {
+        "DiffGenesis"
+    }
+
+    async fn execute(self) -> CliTypedResult<GenesisDiff> {
+        let diff = diff_genesis(
+            &self.first_dir,
+            &self.second_dir,
+            &self.genesis_file_name,
+            &self.waypoint_file_name,
+            self.state,
+        )?;
+        let state_diffs = diff.state_diff.as_deref().unwrap_or_default();
+        if diff.differences.is_empty() && state_diffs.is_empty() {
+            Ok(diff)
+        } else {
+            let mut differences = diff.differences.clone();
+            differences.extend(describe_state_diffs(state_diffs));
+            Err(CliError::UnexpectedError(format!(
+                "Genesis outputs differ:\n{}",
+                differences.join("\n")
+            )))
+        }
+    }
+}
+
+/// Number of differing state values named when genesis isn't reproducible
+const MAX_REPORTED_STATE_DIFFS: usize = 10;
+
+/// Checks genesis generated again from the same inputs serializes to the same bytes, naming the
+/// sections which diverged if it doesn't
+pub fn check_reproducible(genesis_bytes: &[u8], regenerated: &Transaction) -> CliTypedResult<()> {
+    let regenerated_bytes =
+        bcs::to_bytes(regenerated).map_err(|err| CliError::BCS(GENESIS_FILE, err))?;
+    if genesis_bytes == regenerated_bytes {
+        return Ok(());
+    }
+
+    let genesis = decode_genesis(genesis_bytes)?;
+    let mut differences = diff_genesis_contents(&genesis, regenerated)?;
+    if differences.is_empty() {
+        differences.extend(describe_state_diffs(&diff_genesis_state(
+            &genesis,
+            regenerated,
+        )?));
+    }
+    if differences.is_empty() {
+        differences.push("the genesis state is the same, but the transactions differ".to_string());
+    }
+    Err(CliError::UnexpectedError(format!(
+        "{} isn't reproducible, generating it twice from the same inputs diverged in:\n{}",
+        GENESIS_FILE,
+        differences.join("\n")
+    )))
+}
+
+/// Names the first few differing state values, and how many more there are
+fn describe_state_diffs(state_diffs: &[StateValueDiff]) -> Vec<String> {
+    let mut descriptions: Vec<_> = state_diffs
+        .iter()
+        .take(MAX_REPORTED_STATE_DIFFS)
+        .map(|diff| format!("state {}: {:?}", diff.key, diff.kind))
+        .collect();
+    if state_diffs.len() > MAX_REPORTED_STATE_DIFFS {
+        descriptions.push(format!(
+            "and {} more state values",
+            state_diffs.len() - MAX_REPORTED_STATE_DIFFS
+        ));
+    }
+    descriptions
+}
+
+/// Compares the genesis outputs of these names in two directories, materializing both genesis
+/// states to compare every state value if `state` is set
+pub fn diff_genesis(
+    first_dir: &Path,
+    second_dir: &Path,
+    genesis_file_name: &str,
+    waypoint_file_name: &str,
+    state: bool,
+) -> CliTypedResult<GenesisDiff> {
+    let first_bytes = read_from_file(first_dir.join(genesis_file_name).as_path())?;
+    let second_bytes = read_from_file(second_dir.join(genesis_file_name).as_path())?;
+    let identical = first_bytes == second_bytes;
+    let first = decode_genesis(&first_bytes)?;
+    let second = decode_genesis(&second_bytes)?;
+
+    let mut differences = diff_genesis_contents(&first, &second)?;
+    let first_waypoint = read_waypoint(&first_dir.join(waypoint_file_name))?;
+    let second_waypoint = read_waypoint(&second_dir.join(waypoint_file_name))?;
+    if first_waypoint != second_waypoint {
+        differences.push(format!(
+            "{}: {} != {}",
+            waypoint_file_name, first_waypoint, second_waypoint
+        ));
+    }
+    if !identical && differences.is_empty() {
+        differences.push(format!(
+            "{}: blobs differ outside of the validators, balances, modules and chain id, \
+             compare with --state to find where",
+            genesis_file_name
+        ));
+    }
+
+    let state_diff = if state {
+        Some(diff_genesis_state(&first, &second)?)
+    } else {
+        None
+    };
+
+    Ok(GenesisDiff {
+        identical,
+        differences,
+        state_diff,
+    })
+}
+
+/// Describes every difference between the validator sets, balances, framework modules and chain
+/// ids of two genesis transactions
+pub fn diff_genesis_contents(
+    first: &Transaction,
+    second: &Transaction,
+) -> CliTypedResult<Vec<String>> {
+    let first_summary = summarize_genesis(first, None)?;
+    let second_summary = summarize_genesis(second, None)?;
+    let mut differences = Vec::new();
+    if first_summary.chain_id != second_summary.chain_id {
+        differences.push(format!(
+            "chain id: {} != {}",
+            first_summary.chain_id, second_summary.chain_id
+        ));
+    }
+
+    let first_validators = validators_by_address(&first_summary.validators);
+    let second_validators = validators_by_address(&second_summary.validators);
+    let addresses: BTreeSet<_> = first_validators
+        .keys()
+        .chain(second_validators.keys())
+        .collect();
+    for address in addresses {
+        let (first, second) = match (
+            first_validators.get(address),
+            second_validators.get(address),
+        ) {
+            (Some(first), Some(second)) => (first, second),
+            (Some(_), None) => {
+                differences.push(format!("validator {}: not in the second genesis", address));
+                continue;
+            }
+            (None, Some(_)) => {
+                differences.push(format!("validator {}: not in the first genesis", address));
+                continue;
+            }
+            (None, None) => continue,
+        };
+        let field = |name: &str, first: &dyn Display, second: &dyn Display| {
+            format!("validator {}: {} {} != {}", address, name, first, second)
+        };
+        if first.0 != second.0 {
+            differences.push(field("index", &first.0, &second.0));
+        }
+        let (first, second) = (first.1, second.1);
+        if first.voting_power != second.voting_power {
+            differences.push(field(
+                "voting power",
+                &first.voting_power,
+                &second.voting_power,
+            ));
+        }
+        if first.consensus_public_key != second.consensus_public_key {
+            differences.push(field(
+                "consensus public key",
+                &first.consensus_public_key,
+                &second.consensus_public_key,
+            ));
+        }
+        if first.validator_network_addresses != second.validator_network_addresses {
+            differences.push(field(
+                "validator network addresses",
+                &first.validator_network_addresses.join(", "),
+                &second.validator_network_addresses.join(", "),
+            ));
+        }
+        if first.fullnode_network_addresses != second.fullnode_network_addresses {
+            differences.push(field(
+                "full node network addresses",
+                &first.fullnode_network_addresses.join(", "),
+                &second.fullnode_network_addresses.join(", "),
+            ));
+        }
+    }
+    if first_summary.total_voting_power != second_summary.total_voting_power {
+        differences.push(format!(
+            "total voting power: {} != {}",
+            first_summary.total_voting_power, second_summary.total_voting_power
+        ));
+    }
+
+    diff_maps(
+        "balance of",
+        &first_summary.balances,
+        &second_summary.balances,
+        &mut differences,
+    );
+    diff_maps(
+        "hash of module",
+        &module_hashes(first)?,
+        &module_hashes(second)?,
+        &mut differences,
+    );
+    Ok(differences)
+}
+
+/// Indexes validators by their account address, along with their index in the validator set
+fn validators_by_address(
+    validators: &[GenesisValidator],
+) -> BTreeMap<AccountAddress, (usize, &GenesisValidator)> {
+    validators
+        .iter()
+        .enumerate()
+        .map(|(index, validator)| (validator.account_address, (index, validator)))
+        .collect()
+}
+
+/// Hashes of the code of every module published by a genesis transaction, by module
+fn module_hashes(genesis: &Transaction) -> CliTypedResult<BTreeMap<String, HashValue>> {
+    Ok(genesis_state(genesis)?
+        .into_iter()
+        .filter_map(|(key, bytes)| match key {
+            StateKey::AccessPath(access_path) => match access_path.get_path() {
+                AccessPathType::Code(module_id) => Some((
+                    format!("{}::{}", access_path.address, module_id.name()),
+                    HashValue::sha3_256_of(&bytes),
+                )),
+                AccessPathType::Resource(_) => None,
+            },
+            _ => None,
+        })
+        .collect())
+}
+
+/// Describes every key whose value differs between two maps, or which is only in one of them
+fn diff_maps<K: Display + Ord, V: Display + PartialEq>(
+    name: &str,
+    first: &BTreeMap<K, V>,
+    second: &BTreeMap<K, V>,
+    differences: &mut Vec<String>,
+) {
+    let keys: BTreeSet<_> = first.keys().chain(second.keys()).collect();
+    for key in keys {
+        match (first.get(key), second.get(key)) {
+            (Some(first), Some(second)) if first == second => {}
+            (Some(first), Some(second)) => {
+                differences.push(format!("{} {}: {} != {}", name, key, first, second))
+            }
+            (Some(_), None) => {
+                differences.push(format!("{} {}: not in the second genesis", name, key))
+            }
+            (None, Some(_)) => {
+                differences.push(format!("{} {}: not in the first genesis", name, key))
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Decodes the bytes of a `genesis.blob`
+pub fn decode_genesis(bytes: &[u8]) -> CliTypedResult<Transaction> {
+    bcs::from_bytes(bytes).map_err(|err| CliError::BCS(GENESIS_FILE, err))
+}
+
+/// Reads and decodes a `genesis.blob` file
+pub fn read_genesis(path: &Path) -> CliTypedResult<Transaction> {
+    decode_genesis(&read_from_file(path)?)
+}
+
+/// Materializes the state written by a genesis transaction
+pub fn genesis_state(genesis: &Transaction) -> CliTypedResult<BTreeMap<StateKey, Vec<u8>>> {
+    let change_set = match genesis {
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)) => change_set,
+        _ => {
+            return Err(CliError::UnexpectedError(
+                "Transaction is not a direct genesis write set".to_string(),
+            ))
+        }
+    };
+
+    Ok(change_set
+        .write_set()
+        .iter()
+        .filter_map(|(key, op)| op.extract_raw_bytes().map(|bytes| (key.clone(), bytes)))
+        .collect())
+}
+
+/// Computes every state value which differs between two genesis transactions, ordered by key
+pub fn diff_genesis_state(
+    first: &Transaction,
+    second: &Transaction,
+) -> CliTypedResult<Vec<StateValueDiff>> {
+    let first = genesis_state(first)?;
+    let second = genesis_state(second)?;
+    let keys: BTreeSet<_> = first.keys().chain(second.keys()).collect();
+
+    let mut diffs = Vec::new();
+    for key in keys {
+        let (kind, first_value, second_value) = match (first.get(key), second.get(key)) {
+            (Some(first_value), Some(second_value)) if first_value == second_value => continue,
+            (Some(first_value), Some(second_value)) => (
+                StateValueDiffKind::Modified,
+                Some(first_value),
+                Some(second_value),
+            ),
+            (Some(first_value), None) => (StateValueDiffKind::OnlyInFirst, Some(first_value), None),
+            (None, Some(second_value)) => {
+                (StateValueDiffKind::OnlyInSecond, None, Some(second_value))
+            }
+            (None, None) => continue,
+        };
+        diffs.push(StateValueDiff {
+            key: describe_state_key(key),
+            kind,
+            first_value: first_value.map(hex::encode),
+            second_value: second_value.map(hex::encode),
+        });
+    }
+    Ok(diffs)
+}
+
+/// Describes a state key by the account and resource or module it refers to
+pub fn describe_state_key(key: &StateKey) -> String {
+    match key {
+        StateKey::AccessPath(access_path) => match access_path.get_path() {
+            AccessPathType::Resource(struct_tag) => {
+                format!("{}/resource/{}", access_path.address, struct_tag)
+            }
+            AccessPathType::Code(module_id) => {
+                format!("{}/module/{}", access_path.address, module_id.name())
+            }
+        },
+        StateKey::TableItem { handle, key } => {
+            format!("table/{}/{}", handle.0, hex::encode(key))
+        }
+        StateKey::Raw(bytes) => format!("raw/{}", hex::encode(bytes)),
+    }
+}
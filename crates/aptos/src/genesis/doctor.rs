@@ -0,0 +1,189 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::CliTypedResult,
+    genesis::{
+        employee_vesting_problems,
+        error::{is_not_found, GenesisError},
+        git::{
+            Client, GitOptions, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE, FRAMEWORK_NAME,
+            LAYOUT_FILE, OPERATOR_FILE, OWNER_FILE,
+        },
+        redact::ensure_layout_not_redacted,
+        validate_config, validate_max_transaction_size, validate_scheduled_features,
+        validate_total_supply,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::{Layout, StringOperatorConfiguration, StringOwnerConfiguration};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display, Formatter},
+    path::{Path, PathBuf},
+};
+use vm_genesis::{AccountMap, EmployeeAccountMap};
+
+/// Check every genesis input in the repository, reporting all of their problems at once
+///
+/// `GenerateGenesis` stops at the first problem it finds.  This checks the layout, the
+/// configuration of every validator, the framework and, for mainnet, the balances and employee
+/// vesting accounts, without generating genesis, so that every problem can be fixed before the
+/// ceremony.  Problems are grouped by the file they're in, and the command fails if there are any.
+#[derive(Parser)]
+pub struct GenesisDoctor {
+    /// Whether the inputs are for mainnet genesis, which also checks the balances and employee
+    /// vesting accounts
+    #[clap(long)]
+    pub(crate) mainnet: bool,
+    /// Total supply the mainnet balances must add up to
+    #[clap(long)]
+    pub(crate) expected_total_supply: Option<u64>,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// Every problem found with the genesis inputs
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct GenesisReport {
+    /// Number of validators whose configurations were checked
+    pub validators: usize,
+    /// Problems found, keyed by the file they're in.  Problems which span both of a validator's
+    /// files are keyed by the validator's directory.
+    pub problems: BTreeMap<String, Vec<String>>,
+}
+
+impl GenesisReport {
+    /// Number of problems found, in all files
+    pub fn count(&self) -> usize {
+        self.problems.values().map(Vec::len).sum()
+    }
+
+    fn add(&mut self, file: &str, problem: impl Display) {
+        self.problems
+            .entry(file.to_string())
+            .or_default()
+            .push(problem.to_string());
+    }
+
+    /// Records the failure of a check, if it failed
+    fn check<T, E: Display>(&mut self, file: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.add(file, err);
+                None
+            }
+        }
+    }
+
+    /// Reads a file from the repository, recording why it couldn't be read
+    fn read<T: DeserializeOwned + Debug>(&mut self, client: &Client, file: &Path) -> Option<T> {
+        let name = file.display().to_string();
+        match client.get(file) {
+            Ok(value) => Some(value),
+            Err(err) if is_not_found(&err) => {
+                self.add(&name, "is missing from the repository");
+                None
+            }
+            Err(err) => {
+                self.add(&name, err);
+                None
+            }
+        }
+    }
+}
+
+impl Display for GenesisReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Found {} problems in {} files",
+            self.count(),
+            self.problems.len()
+        )?;
+        for (file, problems) in &self.problems {
+            write!(f, "\n{} ({}):", file, problems.len())?;
+            for problem in problems {
+                write!(f, "\n  {}", problem)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CliCommand<GenesisReport> for GenesisDoctor {
+    fn command_name(&self) -> &'static str {
+        "GenesisDoctor"
+    }
+
+    async fn execute(self) -> CliTypedResult<GenesisReport> {
+        let client = self.git_options.get_client()?;
+        let report = diagnose(&client, self.mainnet, self.expected_total_supply);
+        if report.problems.is_empty() {
+            Ok(report)
+        } else {
+            Err(GenesisError::ProblemsFound(report).into())
+        }
+    }
+}
+
+/// Checks every genesis input in the repository, continuing past any problems found
+pub fn diagnose(
+    client: &Client,
+    mainnet: bool,
+    expected_total_supply: Option<u64>,
+) -> GenesisReport {
+    let mut report = GenesisReport::default();
+
+    let layout: Option<Layout> = report.read(client, Path::new(LAYOUT_FILE));
+    if let Some(ref layout) = layout {
+        report.check(LAYOUT_FILE, ensure_layout_not_redacted(layout));
+        for problem in layout.problems() {
+            report.add(LAYOUT_FILE, problem);
+        }
+        report.check(LAYOUT_FILE, validate_scheduled_features(layout));
+        report.check(LAYOUT_FILE, validate_max_transaction_size(layout));
+
+        for user in layout.sorted_users() {
+            let dir = PathBuf::from(user);
+            let owner: Option<StringOwnerConfiguration> =
+                report.read(client, &dir.join(OWNER_FILE));
+            let operator: Option<StringOperatorConfiguration> =
+                report.read(client, &dir.join(OPERATOR_FILE));
+            if let (Some(owner), Some(operator)) = (owner, operator) {
+                let user_dir = format!("{}/", user);
+                report.check(&user_dir, validate_config(user, &owner, &operator));
+            }
+            report.validators += 1;
+        }
+    }
+
+    report.check(FRAMEWORK_NAME, client.get_framework());
+
+    if mainnet {
+        let accounts: Option<Vec<AccountMap>> = report.read(client, Path::new(BALANCES_FILE));
+        let employees: Option<Vec<EmployeeAccountMap>> =
+            report.read(client, Path::new(EMPLOYEE_VESTING_ACCOUNTS_FILE));
+        if let Some(ref accounts) = accounts {
+            if let Some(expected_total_supply) = expected_total_supply {
+                report.check(
+                    BALANCES_FILE,
+                    validate_total_supply(accounts, expected_total_supply),
+                );
+            }
+        }
+        if let (Some(accounts), Some(employees)) = (accounts, employees) {
+            let pool_size = layout.and_then(|layout| layout.employee_vesting_pool_size);
+            for problem in employee_vesting_problems(&employees, &accounts, pool_size) {
+                report.add(EMPLOYEE_VESTING_ACCOUNTS_FILE, problem);
+            }
+        }
+    }
+    report
+}
@@ -0,0 +1,210 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::write_to_file,
+    },
+    genesis::{diff::genesis_state, GENESIS_FILE},
+};
+use aptos_crypto::bls12381;
+use aptos_types::{
+    access_path::Path as AccessPathType,
+    account_address::AccountAddress,
+    account_config::{ChainIdResource, CoinStoreResource, CORE_CODE_ADDRESS},
+    network_address::NetworkAddress,
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    state_store::state_key::StateKey,
+    transaction::Transaction,
+};
+use clap::ArgEnum;
+use move_deps::move_core_types::move_resource::MoveStructType;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use vm_genesis::AccountMap;
+
+/// File in the genesis output directory holding the decoded genesis, with `--output-format json`
+pub const GENESIS_JSON_FILE: &str = "genesis.json";
+
+/// Formats genesis is written in
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GenesisOutputFormat {
+    /// Only the BCS encoded genesis.blob
+    Blob,
+    /// The genesis.blob, along with a human readable genesis.json
+    Json,
+}
+
+impl Display for GenesisOutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            GenesisOutputFormat::Blob => "blob",
+            GenesisOutputFormat::Json => "json",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for GenesisOutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blob" => Ok(GenesisOutputFormat::Blob),
+            "json" => Ok(GenesisOutputFormat::Json),
+            _ => Err("Invalid output format. Valid values are blob, json"),
+        }
+    }
+}
+
+/// The contents of a genesis transaction which matter when auditing a ceremony
+///
+/// Everything is ordered, so that genesis generated twice from the same inputs is identical.
+#[derive(Debug, Serialize)]
+pub struct GenesisSummary {
+    pub chain_id: u8,
+    /// Validators active after genesis, in validator index order
+    pub validators: Vec<GenesisValidator>,
+    pub total_voting_power: u128,
+    /// Coin balance of every account with a coin store
+    pub balances: BTreeMap<AccountAddress, u64>,
+    /// Names of the modules published at each address
+    pub modules: BTreeMap<AccountAddress, BTreeSet<String>>,
+    /// The faucet account funded at genesis, which is one of the balances, if it was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<AccountMap>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GenesisValidator {
+    pub account_address: AccountAddress,
+    pub voting_power: u64,
+    pub consensus_public_key: bls12381::PublicKey,
+    pub validator_network_addresses: Vec<String>,
+    pub fullnode_network_addresses: Vec<String>,
+}
+
+/// Decodes the validator set, balances, chain id and modules written by a genesis transaction
+///
+/// The faucet account, if given, is labelled among the balances, as genesis funds it like any
+/// other account.
+pub fn summarize_genesis(
+    genesis: &Transaction,
+    faucet: Option<AccountAddress>,
+) -> CliTypedResult<GenesisSummary> {
+    let mut chain_id = None;
+    let mut validator_set = None;
+    let mut balances = BTreeMap::new();
+    let mut modules: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for (key, bytes) in genesis_state(genesis)? {
+        let access_path = match key {
+            StateKey::AccessPath(access_path) => access_path,
+            _ => continue,
+        };
+        let address = access_path.address;
+        match access_path.get_path() {
+            AccessPathType::Code(module_id) => {
+                modules
+                    .entry(address)
+                    .or_default()
+                    .insert(module_id.name().to_string());
+            }
+            AccessPathType::Resource(struct_tag) => {
+                if struct_tag == CoinStoreResource::struct_tag() {
+                    let coin_store: CoinStoreResource = decode_resource(&bytes, address)?;
+                    balances.insert(address, coin_store.coin());
+                } else if address == CORE_CODE_ADDRESS {
+                    if struct_tag == ChainIdResource::struct_tag() {
+                        let resource: ChainIdResource = decode_resource(&bytes, address)?;
+                        chain_id = Some(resource.chain_id().id());
+                    } else if struct_tag == ValidatorSet::struct_tag() {
+                        validator_set = Some(decode_resource::<ValidatorSet>(&bytes, address)?);
+                    }
+                }
+            }
+        }
+    }
+
+    let chain_id = chain_id.ok_or_else(|| missing_resource("chain id"))?;
+    let validator_set = validator_set.ok_or_else(|| missing_resource("validator set"))?;
+    let mut validators = Vec::new();
+    for validator in validator_set.active_validators {
+        let config = validator.config();
+        let network_addresses = |addresses: Result<Vec<NetworkAddress>, bcs::Error>| {
+            addresses
+                .map(|addresses| addresses.iter().map(ToString::to_string).collect())
+                .map_err(|err| CliError::BCS("network addresses", err))
+        };
+        validators.push(GenesisValidator {
+            account_address: *validator.account_address(),
+            voting_power: validator.consensus_voting_power(),
+            consensus_public_key: validator.consensus_public_key().clone(),
+            validator_network_addresses: network_addresses(config.validator_network_addresses())?,
+            fullnode_network_addresses: network_addresses(config.fullnode_network_addresses())?,
+        });
+    }
+
+    let faucet = faucet
+        .map(|faucet| funded_faucet(&balances, faucet))
+        .transpose()?;
+    Ok(GenesisSummary {
+        chain_id,
+        validators,
+        total_voting_power: validator_set.total_voting_power,
+        balances,
+        modules,
+        faucet,
+    })
+}
+
+/// The faucet account among the balances of a genesis, failing if genesis didn't fund it
+pub(crate) fn funded_faucet(
+    balances: &BTreeMap<AccountAddress, u64>,
+    account_address: AccountAddress,
+) -> CliTypedResult<AccountMap> {
+    let balance = balances.get(&account_address).ok_or_else(|| {
+        CliError::UnexpectedError(format!(
+            "Faucet account {} isn't funded in {}",
+            account_address, GENESIS_FILE
+        ))
+    })?;
+    Ok(AccountMap {
+        account_address,
+        balance: *balance,
+    })
+}
+
+/// Writes the summary of `genesis` as JSON to `file`, labelling the faucet account if any
+pub fn write_genesis_json(
+    file: &Path,
+    genesis: &Transaction,
+    faucet: Option<AccountAddress>,
+) -> CliTypedResult<PathBuf> {
+    let summary = summarize_genesis(genesis, faucet)?;
+    let json = serde_json::to_string_pretty(&summary)
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to encode genesis: {}", err)))?;
+    write_to_file(file, &file.display().to_string(), json.as_bytes())?;
+    Ok(file.to_path_buf())
+}
+
+pub(crate) fn decode_resource<T: DeserializeOwned>(
+    bytes: &[u8],
+    address: AccountAddress,
+) -> CliTypedResult<T> {
+    bcs::from_bytes(bytes).map_err(|err| {
+        CliError::UnexpectedError(format!(
+            "Malformed resource at {} in {}: {}",
+            address, GENESIS_FILE, err
+        ))
+    })
+}
+
+pub(crate) fn missing_resource(name: &str) -> CliError {
+    CliError::UnexpectedError(format!("{} has no {}", GENESIS_FILE, name))
+}
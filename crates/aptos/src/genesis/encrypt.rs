@@ -0,0 +1,216 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::{
+            append_file_extension, check_if_file_exists, dir_default_to_current, read_from_file,
+            write_to_user_only_file,
+        },
+    },
+    genesis::{
+        git::{from_yaml, to_yaml},
+        keys::PRIVATE_FILES,
+    },
+    CliCommand,
+};
+use aes_gcm::{
+    aead::{Aead, NewAead},
+    Aes256Gcm, Key, Nonce,
+};
+use argon2::Argon2;
+use async_trait::async_trait;
+use clap::Parser;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Extension appended to the name of a file when it's encrypted e.g. private-keys.yaml.encrypted
+pub const ENCRYPTED_EXTENSION: &str = "encrypted";
+
+/// Version of the encryption scheme: an Argon2id key derived from the passphrase, with AES-256-GCM
+const ENCRYPTION_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// Options for the passphrase private keys are encrypted with
+#[derive(Clone, Debug, Default, Parser)]
+pub struct PassphraseOptions {
+    /// File holding the passphrase, in place of being prompted for it
+    ///
+    /// Only its first line is used.  Without it, the passphrase is read from the terminal without
+    /// being echoed, which isn't possible when prompts are assumed answered.
+    #[clap(long, parse(from_os_str))]
+    pub passphrase_file: Option<PathBuf>,
+}
+
+impl PassphraseOptions {
+    /// Reads the passphrase, asking for it twice if it's prompted for and is being set
+    pub fn passphrase(
+        &self,
+        prompt_options: PromptOptions,
+        confirm: bool,
+    ) -> CliTypedResult<String> {
+        let passphrase = if let Some(ref passphrase_file) = self.passphrase_file {
+            let contents = String::from_utf8(read_from_file(passphrase_file)?)?;
+            contents.lines().next().unwrap_or_default().to_string()
+        } else if prompt_options.assume_yes || prompt_options.assume_no {
+            return Err(CliError::CommandArgumentError(
+                "Must provide --passphrase-file when prompts are assumed answered".to_string(),
+            ));
+        } else {
+            let passphrase = read_passphrase("Passphrase: ")?;
+            if confirm && read_passphrase("Confirm passphrase: ")? != passphrase {
+                return Err(CliError::CommandArgumentError(
+                    "Passphrases don't match".to_string(),
+                ));
+            }
+            passphrase
+        };
+        if passphrase.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "Passphrase must not be empty".to_string(),
+            ));
+        }
+        Ok(passphrase)
+    }
+}
+
+fn read_passphrase(prompt: &str) -> CliTypedResult<String> {
+    rpassword::prompt_password(prompt).map_err(|err| CliError::IO("passphrase".to_string(), err))
+}
+
+/// A file encrypted with a key derived from a passphrase
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EncryptedFile {
+    pub version: u8,
+    /// Hex encoded salt the key is derived with
+    pub salt: String,
+    /// Hex encoded nonce the contents are encrypted with
+    pub nonce: String,
+    /// Hex encoded encrypted contents, followed by their authentication tag
+    pub ciphertext: String,
+}
+
+/// Encrypts `plaintext` with a key derived from `passphrase`, returning an [`EncryptedFile`]
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> CliTypedResult<EncryptedFile> {
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut nonce = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| CliError::UnexpectedError("Failed to encrypt".to_string()))?;
+    Ok(EncryptedFile {
+        version: ENCRYPTION_VERSION,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+/// Decrypts an [`EncryptedFile`] named `name`, failing if the passphrase isn't the one it was
+/// encrypted with
+pub fn decrypt(passphrase: &str, file: &EncryptedFile, name: &str) -> CliTypedResult<Vec<u8>> {
+    if file.version != ENCRYPTION_VERSION {
+        return Err(CliError::UnableToParse(
+            "encrypted file",
+            format!("unknown encryption version {}", file.version),
+        ));
+    }
+    let decode = |field: &str, value: &str| {
+        hex::decode(value).map_err(|err| {
+            CliError::UnableToParse("encrypted file", format!("invalid {}: {}", field, err))
+        })
+    };
+    let salt = decode("salt", &file.salt)?;
+    let nonce = decode("nonce", &file.nonce)?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(CliError::UnableToParse(
+            "encrypted file",
+            format!("nonce is {} bytes instead of {}", nonce.len(), NONCE_LENGTH),
+        ));
+    }
+    let ciphertext = decode("ciphertext", &file.ciphertext)?;
+    cipher(passphrase, &salt)?
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| CliError::UnableToDecrypt(name.to_string()))
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> CliTypedResult<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to derive key: {}", err)))?;
+    Ok(Aes256Gcm::new(Key::from_slice(&key)))
+}
+
+/// Path the encrypted version of `file` is written to
+pub fn encrypted_file_path(file: &Path) -> CliTypedResult<PathBuf> {
+    append_file_extension(file, ENCRYPTED_EXTENSION)
+}
+
+/// Encrypts `bytes` with `passphrase`, writing them to the encrypted version of `file`
+pub fn write_encrypted_file(
+    file: &Path,
+    name: &str,
+    passphrase: &str,
+    bytes: &[u8],
+) -> CliTypedResult<PathBuf> {
+    let encrypted_file = encrypted_file_path(file)?;
+    write_to_user_only_file(
+        &encrypted_file,
+        name,
+        to_yaml(&encrypt(passphrase, bytes)?)?.as_bytes(),
+    )?;
+    Ok(encrypted_file)
+}
+
+/// Decrypt the private keys written by `GenerateKeys --encrypt`
+///
+/// Each encrypted file of private keys in the directory is decrypted next to it, under its
+/// original name.  Nothing is written unless every file decrypts with the passphrase.
+#[derive(Parser)]
+pub struct DecryptKeys {
+    /// Directory containing the encrypted key files
+    #[clap(long, parse(from_os_str))]
+    pub(crate) key_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) passphrase_options: PassphraseOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<PathBuf>> for DecryptKeys {
+    fn command_name(&self) -> &'static str {
+        "DecryptKeys"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
+        let key_dir = dir_default_to_current(self.key_dir)?;
+        let mut files = Vec::new();
+        for name in PRIVATE_FILES {
+            let file = key_dir.join(name);
+            check_if_file_exists(&file, self.prompt_options)?;
+            let encrypted: EncryptedFile = from_yaml(&String::from_utf8(read_from_file(
+                &encrypted_file_path(&file)?,
+            )?)?)?;
+            files.push((file, name, encrypted));
+        }
+
+        let passphrase = self
+            .passphrase_options
+            .passphrase(self.prompt_options, false)?;
+        let mut decrypted = Vec::new();
+        for (file, name, encrypted) in &files {
+            decrypted.push((file, name, decrypt(&passphrase, encrypted, name)?));
+        }
+        for (file, name, bytes) in &decrypted {
+            write_to_user_only_file(file, name, bytes)?;
+        }
+        Ok(files.into_iter().map(|(file, _, _)| file).collect())
+    }
+}
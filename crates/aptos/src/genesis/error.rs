@@ -0,0 +1,61 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{common::types::CliError, genesis::doctor::GenesisReport};
+use aptos_crypto::HashValue;
+use aptos_types::{chain_id::ChainId, waypoint::Waypoint};
+use std::io::ErrorKind;
+use thiserror::Error;
+
+/// Why the inputs of a genesis command were rejected
+///
+/// Returned by the genesis commands inside `CliError::GenesisError`, so callers can tell apart
+/// what has to be fixed without matching on messages
+#[derive(Debug, Error)]
+pub enum GenesisError {
+    #[error("Invalid layout: {0}")]
+    LayoutInvalid(String),
+    #[error("Layout users lists {0} more than once")]
+    DuplicateUser(String),
+    #[error(
+        "Missing validator configurations for users {}, who must run SetValidatorConfiguration",
+        .0.join(", ")
+    )]
+    MissingValidatorConfig(Vec<String>),
+    #[error("Failed to parse genesis inputs:\n{}", .0.join("\n"))]
+    ValidatorConfigInvalid(Vec<String>),
+    #[error(
+        "Validator configuration of {user} was set for chain id {config}, but the layout has \
+         chain id {layout}"
+    )]
+    ChainIdMismatch {
+        user: String,
+        config: ChainId,
+        layout: ChainId,
+    },
+    #[error("Validator configurations aren't signed by their owners:\n{}", .0.join("\n"))]
+    ConfigSignatureInvalid(Vec<String>),
+    #[error("Invalid balances: {0}")]
+    BalancesInvalid(String),
+    #[error("Waypoint is {expected}, but genesis has waypoint {computed}")]
+    WaypointMismatch {
+        expected: Waypoint,
+        computed: Waypoint,
+    },
+    #[error("{0} is missing from the repository, run SetupGit to write one")]
+    FrameworkMissing(String),
+    #[error("Framework has hash {hash}, but --expected-framework-hash is {expected}")]
+    FrameworkHashMismatch {
+        hash: HashValue,
+        expected: HashValue,
+    },
+    #[error("{0}")]
+    ProblemsFound(GenesisReport),
+    #[error("Timed out after {secs}s while {phase}, and removed the outputs written so far")]
+    Timeout { secs: u64, phase: &'static str },
+}
+
+/// Whether a file couldn't be read because it doesn't exist, locally or on Github
+pub fn is_not_found(err: &CliError) -> bool {
+    matches!(err, CliError::IO(_, err) if err.kind() == ErrorKind::NotFound)
+}
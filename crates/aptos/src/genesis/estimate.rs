@@ -0,0 +1,118 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult, OutputFormat},
+    genesis::{
+        check_configs_submitted,
+        git::{BALANCES_FILE, LAYOUT_FILE},
+        redact::ensure_layout_not_redacted,
+        validate_stakes, validator_configs, GenerateGenesis, GENESIS_FILE,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::{Layout, ValidatorConfiguration};
+use async_trait::async_trait;
+use framework::ReleaseBundle;
+use serde::Serialize;
+use std::path::Path;
+use vm_genesis::AccountMap;
+
+/// Bytes of genesis besides its framework, validators and accounts, for the on chain configs such
+/// as the gas schedule, and the framework's own accounts
+pub const BASE_GENESIS_SIZE: usize = 32 * 1024;
+/// Bytes written for a validator besides its configuration, for its account, stake pool and place
+/// in the validator set
+pub const VALIDATOR_RESOURCES_SIZE: usize = 2 * 1024;
+/// Bytes written for an account with a balance, for its account resource and coin store
+pub const ACCOUNT_RESOURCES_SIZE: usize = 512;
+
+/// Estimates the size of the genesis blob generated from the given inputs, without generating it
+///
+/// Genesis is mostly its framework, which is written whole, so the estimate is the encoded size of
+/// the framework, the layout and each validator and account, along with what genesis writes for
+/// each of them.  It's meant to be within a few percent of the blob generated, which depends on
+/// details such as the length of the validators' hosts.
+pub fn estimate_genesis_size(
+    layout: &Layout,
+    framework: &ReleaseBundle,
+    configs: &[ValidatorConfiguration],
+    balances: &[AccountMap],
+) -> CliTypedResult<usize> {
+    let mut size = BASE_GENESIS_SIZE + encoded_size(framework)? + encoded_size(layout)?;
+    for config in configs {
+        size += encoded_size(config)? + VALIDATOR_RESOURCES_SIZE;
+    }
+    for balance in balances {
+        size += encoded_size(balance)? + ACCOUNT_RESOURCES_SIZE;
+    }
+    Ok(size)
+}
+
+fn encoded_size<T: Serialize>(value: &T) -> CliTypedResult<usize> {
+    bcs::serialized_size(value).map_err(|e| CliError::BCS(GENESIS_FILE, e))
+}
+
+/// Estimated size of genesis, printed by `GenerateGenesis --estimate-only`
+#[derive(Clone, Debug, Serialize)]
+pub struct GenesisSizeEstimate {
+    /// Approximate size of the genesis blob, in bytes
+    pub bytes: usize,
+    /// Number of validators genesis would include
+    pub validators: usize,
+    /// Number of accounts funded at genesis, besides the validators'
+    pub accounts: usize,
+}
+
+/// `GenerateGenesis` run with `--estimate-only`, which estimates genesis in place of generating it
+pub struct EstimateGenesisSize(pub GenerateGenesis);
+
+#[async_trait]
+impl CliCommand<GenesisSizeEstimate> for EstimateGenesisSize {
+    fn command_name(&self) -> &'static str {
+        "GenerateGenesis"
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        self.0.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<GenesisSizeEstimate> {
+        self.0.estimate()
+    }
+}
+
+impl GenerateGenesis {
+    /// Estimates the size of the genesis these inputs would generate, reading them as genesis
+    /// does, but neither generating nor writing anything
+    ///
+    /// Mainnet's employee vesting pools aren't counted, as their employees are already among the
+    /// balances.
+    pub fn estimate(self) -> CliTypedResult<GenesisSizeEstimate> {
+        let mainnet = self.mainnet.unwrap_or_default();
+        let balances = self.read_balances(mainnet)?;
+        let faucet = self.faucet_options.faucet()?;
+        let client = self.git_options.get_client()?;
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        ensure_layout_not_redacted(&layout)?;
+        check_configs_submitted(&client, &layout)?;
+        let (validators, _) = validate_stakes(
+            &layout,
+            validator_configs(&client, &layout, None)?,
+            self.strict_min_stake,
+        )?;
+
+        let mut accounts = match balances {
+            Some(balances) => balances,
+            None if mainnet => client.get(Path::new(BALANCES_FILE))?,
+            None => vec![],
+        };
+        accounts.extend(faucet);
+        let framework = client.get_framework()?;
+        Ok(GenesisSizeEstimate {
+            bytes: estimate_genesis_size(&layout, &framework, &validators, &accounts)?,
+            validators: validators.len(),
+            accounts: accounts.len(),
+        })
+    }
+}
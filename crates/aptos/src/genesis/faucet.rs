@@ -0,0 +1,85 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliError, CliTypedResult};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{aptos_test_root_address, reserved_vm_address, CORE_CODE_ADDRESS},
+};
+use clap::Parser;
+use std::collections::BTreeSet;
+use vm_genesis::AccountMap;
+
+/// Balance of the faucet account when `--faucet-amount` isn't given, 1 billion APT in octas
+pub const DEFAULT_FAUCET_AMOUNT: u64 = 100_000_000_000_000_000;
+
+/// An account funded at genesis, for local developers to fund test transactions from
+#[derive(Clone, Debug, Default, Parser)]
+pub struct FaucetOptions {
+    /// Address of an account to create at genesis, holding `--faucet-amount`
+    ///
+    /// The faucet isn't a validator's account, nor for mainnet one of the balances, and is
+    /// created alongside them
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    pub(crate) faucet_account: Option<AccountAddress>,
+    /// Balance of the faucet account in octas, defaults to 1 billion APT
+    #[clap(long)]
+    pub(crate) faucet_amount: Option<u64>,
+}
+
+impl FaucetOptions {
+    /// The account entry of the faucet, if one is given
+    pub fn faucet(&self) -> CliTypedResult<Option<AccountMap>> {
+        match (self.faucet_account, self.faucet_amount) {
+            (None, None) => Ok(None),
+            (None, Some(_)) => Err(CliError::CommandArgumentError(
+                "--faucet-amount requires --faucet-account".to_string(),
+            )),
+            (Some(_), Some(0)) => Err(CliError::CommandArgumentError(
+                "--faucet-amount must be more than 0".to_string(),
+            )),
+            (Some(account_address), amount) => Ok(Some(AccountMap {
+                account_address,
+                balance: amount.unwrap_or(DEFAULT_FAUCET_AMOUNT),
+            })),
+        }
+    }
+}
+
+/// Accounts genesis creates for itself: the VM's, the framework's packages', and the root account
+fn system_accounts() -> [AccountAddress; 5] {
+    [
+        reserved_vm_address(),
+        CORE_CODE_ADDRESS,
+        AccountAddress::from_hex_literal("0x3").unwrap(),
+        AccountAddress::from_hex_literal("0x4").unwrap(),
+        aptos_test_root_address(),
+    ]
+}
+
+/// Ensures the faucet isn't an account already configured for genesis, nor one genesis creates
+/// for itself, since genesis only funds an account when it creates it
+pub fn validate_faucet(
+    faucet: Option<&AccountMap>,
+    configured_accounts: &BTreeSet<AccountAddress>,
+) -> CliTypedResult<()> {
+    let faucet = match faucet {
+        Some(faucet) => faucet.account_address,
+        None => return Ok(()),
+    };
+    if configured_accounts.contains(&faucet) {
+        Err(CliError::CommandArgumentError(format!(
+            "Faucet account {} is already configured for genesis, as a validator's account or a \
+             balance",
+            faucet
+        )))
+    } else if system_accounts().contains(&faucet) {
+        Err(CliError::CommandArgumentError(format!(
+            "Faucet account {} is already created by genesis, for the framework or as the root \
+             account",
+            faucet
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::{
+            check_if_file_exists, create_dir_if_not_exist, dir_default_to_current, write_to_file,
+            write_to_user_only_file,
+        },
+    },
+    genesis::{
+        git::to_yaml,
+        keys::{PRIVATE_KEYS_FILE, PUBLIC_KEYS_FILE},
+        GENESIS_FILE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_crypto::{HashValue, PrivateKey};
+use aptos_genesis::{
+    builder::GenesisConfiguration,
+    config::{HostAndPort, Layout, ValidatorConfiguration},
+    keys::generate_key_objects,
+    GenesisInfo,
+};
+use aptos_keygen::KeyGen;
+use aptos_types::chain_id::ChainId;
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Version of the CI fixture produced by this build.
+///
+/// The same version must always reproduce the exact same bytes.  Bump this whenever a change
+/// intentionally alters the fixture (e.g. a framework or genesis change), and update the golden
+/// hash in the tests.
+pub const LATEST_CI_FIXTURE_VERSION: u64 = 1;
+const CI_FIXTURE_NUM_VALIDATORS: u8 = 4;
+const CI_FIXTURE_VALIDATOR_PORT: u16 = 6180;
+const CI_FIXTURE_VERSION_FILE: &str = "fixture-version.txt";
+pub const ROOT_KEY_FILE: &str = "root-private-key.yaml";
+
+/// Generate a deterministic genesis fixture for CI
+///
+/// The fixture (genesis blob, waypoint and all validator keys) is derived entirely from a fixed
+/// seed and the fixture version, so that a given version always reproduces the same bytes.
+#[derive(Parser)]
+pub struct GenerateCiFixture {
+    /// Output directory for the fixture
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    /// Version of the fixture to generate.  Only the latest version can be generated by a build.
+    #[clap(long, default_value_t = LATEST_CI_FIXTURE_VERSION)]
+    pub(crate) fixture_version: u64,
+
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<PathBuf>> for GenerateCiFixture {
+    fn command_name(&self) -> &'static str {
+        "GenerateCiFixture"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
+        if self.fixture_version != LATEST_CI_FIXTURE_VERSION {
+            return Err(CliError::CommandArgumentError(format!(
+                "Fixture version {} cannot be reproduced by this build, only version {} can",
+                self.fixture_version, LATEST_CI_FIXTURE_VERSION
+            )));
+        }
+
+        let output_dir = dir_default_to_current(self.output_dir.clone())?;
+        let genesis_file = output_dir.join(GENESIS_FILE);
+        let waypoint_file = output_dir.join(WAYPOINT_FILE);
+        let version_file = output_dir.join(CI_FIXTURE_VERSION_FILE);
+        let root_key_file = output_dir.join(ROOT_KEY_FILE);
+        check_if_file_exists(genesis_file.as_path(), self.prompt_options)?;
+        check_if_file_exists(waypoint_file.as_path(), self.prompt_options)?;
+        check_if_file_exists(version_file.as_path(), self.prompt_options)?;
+        check_if_file_exists(root_key_file.as_path(), self.prompt_options)?;
+        create_dir_if_not_exist(output_dir.as_path())?;
+
+        let mut outputs = vec![];
+        let mut configs = vec![];
+        let layout = Layout::default();
+        for index in 0..CI_FIXTURE_NUM_VALIDATORS {
+            let seed = fixture_seed(self.fixture_version, &format!("validator-{}", index));
+            let mut key_generator = KeyGen::from_seed(seed);
+            let (_, _, private_identity, public_identity) =
+                generate_key_objects(&mut key_generator)?;
+
+            let validator_dir = output_dir.join(format!("validator-{}", index));
+            create_dir_if_not_exist(validator_dir.as_path())?;
+            let private_keys_file = validator_dir.join(PRIVATE_KEYS_FILE);
+            let public_keys_file = validator_dir.join(PUBLIC_KEYS_FILE);
+            check_if_file_exists(private_keys_file.as_path(), self.prompt_options)?;
+            check_if_file_exists(public_keys_file.as_path(), self.prompt_options)?;
+            write_to_user_only_file(
+                private_keys_file.as_path(),
+                PRIVATE_KEYS_FILE,
+                to_yaml(&private_identity)?.as_bytes(),
+            )?;
+            write_to_user_only_file(
+                public_keys_file.as_path(),
+                PUBLIC_KEYS_FILE,
+                to_yaml(&public_identity)?.as_bytes(),
+            )?;
+            outputs.push(private_keys_file);
+            outputs.push(public_keys_file);
+
+            let account_address = public_identity.account_address;
+            let account_public_key = public_identity.account_public_key.clone();
+            configs.push(ValidatorConfiguration {
+                owner_account_address: account_address,
+                owner_account_public_key: account_public_key.clone(),
+                operator_account_address: account_address,
+                operator_account_public_key: account_public_key.clone(),
+                voter_account_address: account_address,
+                voter_account_public_key: account_public_key,
+                consensus_public_key: private_identity.consensus_private_key.public_key(),
+                proof_of_possession: public_identity.consensus_proof_of_possession.unwrap(),
+                validator_network_public_key: private_identity
+                    .validator_network_private_key
+                    .public_key(),
+                validator_host: HostAndPort::local(CI_FIXTURE_VALIDATOR_PORT + index as u16)?,
+                additional_validator_hosts: vec![],
+                full_node_network_public_key: None,
+                full_node_host: None,
+                stake_amount: layout.min_stake,
+                commission_percentage: 0,
+                chain_id: None,
+            });
+        }
+
+        let root_key = KeyGen::from_seed(fixture_seed(self.fixture_version, "root"))
+            .generate_ed25519_private_key();
+        write_to_user_only_file(
+            root_key_file.as_path(),
+            ROOT_KEY_FILE,
+            to_yaml(&root_key)?.as_bytes(),
+        )?;
+
+        let mut genesis_info = GenesisInfo::new(
+            ChainId::test(),
+            root_key.public_key(),
+            configs,
+            cached_packages::head_release_bundle().clone(),
+            &GenesisConfiguration {
+                allow_new_validators: layout.allow_new_validators,
+                epoch_duration_secs: layout.epoch_duration_secs,
+                is_test: true,
+                min_stake: layout.min_stake,
+                min_voting_threshold: layout.min_voting_threshold,
+                max_stake: layout.max_stake,
+                recurring_lockup_duration_secs: layout.recurring_lockup_duration_secs,
+                required_proposer_stake: layout.required_proposer_stake,
+                rewards_apy_percentage: layout.rewards_apy_percentage,
+                voting_duration_secs: layout.voting_duration_secs,
+                voting_power_increase_limit: layout.voting_power_increase_limit,
+                scheduled_features: vec![],
+                collections: vec![],
+                coins: vec![],
+                accounts: vec![],
+                start_paused: false,
+                max_transaction_size_in_bytes: None,
+            },
+        )?;
+        let genesis_bytes = bcs::to_bytes(genesis_info.get_genesis())
+            .map_err(|e| CliError::BCS(GENESIS_FILE, e))?;
+        let waypoint = genesis_info.generate_waypoint()?;
+
+        write_to_file(genesis_file.as_path(), GENESIS_FILE, &genesis_bytes)?;
+        write_to_file(
+            waypoint_file.as_path(),
+            WAYPOINT_FILE,
+            waypoint.to_string().as_bytes(),
+        )?;
+        write_to_file(
+            version_file.as_path(),
+            CI_FIXTURE_VERSION_FILE,
+            self.fixture_version.to_string().as_bytes(),
+        )?;
+        outputs.push(root_key_file);
+        outputs.push(genesis_file);
+        outputs.push(waypoint_file);
+        outputs.push(version_file);
+        Ok(outputs)
+    }
+}
+
+/// Derives the seed for one of the fixture's keys from the fixture version
+fn fixture_seed(version: u64, purpose: &str) -> [u8; 32] {
+    *HashValue::sha3_256_of(format!("aptos-ci-fixture-v{}-{}", version, purpose).as_bytes())
+}
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::utils::create_dir_if_not_exist;
+use crate::common::utils::{create_dir_if_not_exist, read_from_file};
+use crate::genesis::error::{is_not_found, GenesisError};
+use crate::genesis::progress::{GenesisProgress, ProgressReporter};
+use crate::genesis::redact::ensure_layout_not_redacted;
 use crate::{
     common::{
         types::{CliError, CliTypedResult},
@@ -10,14 +13,26 @@ use crate::{
     CliCommand,
 };
 use aptos_config::config::Token;
-use aptos_genesis::config::Layout;
+use aptos_crypto::HashValue;
+use aptos_genesis::config::{Layout, LAYOUT_SCHEMA};
 use aptos_github_client::Client as GithubClient;
 use async_trait::async_trait;
 use clap::Parser;
 use framework::ReleaseBundle;
+use fs2::FileExt;
+use move_deps::move_binary_format::CompiledModule;
 use serde::{de::DeserializeOwned, Serialize};
 use std::path::Path;
-use std::{fmt::Debug, io::Read, path::PathBuf, str::FromStr};
+use std::{
+    collections::BTreeSet,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read},
+    path::PathBuf,
+    process::Command,
+    str::FromStr,
+};
+use tempfile::TempDir;
 
 pub const LAYOUT_FILE: &str = "layout.yaml";
 pub const OPERATOR_FILE: &str = "operator.yaml";
@@ -25,20 +40,49 @@ pub const OWNER_FILE: &str = "owner.yaml";
 pub const FRAMEWORK_NAME: &str = "framework.mrb";
 pub const BALANCES_FILE: &str = "balances.yaml";
 pub const EMPLOYEE_VESTING_ACCOUNTS_FILE: &str = "employee_vesting_accounts.yaml";
+pub const LOCK_FILE: &str = ".genesis.lock";
+const GITIGNORE_FILE: &str = ".gitignore";
+
+/// Identity of commits made in a repository without a configured git user
+const GIT_USER_NAME: &str = "Aptos Genesis";
+const GIT_USER_EMAIL: &str = "genesis@aptoslabs.com";
 
 /// Setup a shared Git repository for Genesis
 ///
 /// This will setup a folder or an online Github repository to be used
 /// for Genesis.  If it's the local, it will create the folders but not
-/// set up a Git repository.
+/// set up a Git repository, unless `--init-git` is given.
 #[derive(Parser)]
 pub struct SetupGit {
     #[clap(flatten)]
     pub(crate) git_options: GitOptions,
 
-    /// Path to the `Layout` file which defines where all the files are
+    /// Path to the `Layout` file which defines where all the files are, or `-` for stdin
     #[clap(long, parse(from_os_str))]
     pub(crate) layout_file: PathBuf,
+
+    /// Validate the `Layout` file against its JSON schema before reading it
+    ///
+    /// Errors point at the offending fields with JSON pointers
+    #[clap(long)]
+    pub(crate) schema_validate: bool,
+
+    /// Path to a compiled framework release bundle (.mrb) to generate genesis with
+    ///
+    /// Written to the repository as framework.mrb, in place of the cached head release
+    #[clap(long, parse(from_os_str))]
+    pub(crate) framework_bundle: Option<PathBuf>,
+
+    /// Initialize the local repository as a git repository, committing the layout and framework
+    ///
+    /// Validators can then commit their configurations with `SetValidatorConfiguration --commit`,
+    /// keeping a history of every change to the genesis inputs
+    #[clap(long)]
+    pub(crate) init_git: bool,
+
+    /// Receives the progress of the setup, which is otherwise printed to stderr
+    #[clap(skip)]
+    pub(crate) progress: ProgressReporter,
 }
 
 #[async_trait]
@@ -48,16 +92,116 @@ impl CliCommand<()> for SetupGit {
     }
 
     async fn execute(self) -> CliTypedResult<()> {
+        if self.schema_validate {
+            let contents =
+                String::from_utf8(read_from_file(&self.layout_file)?).map_err(CliError::from)?;
+            validate_layout_schema(&contents)?;
+        }
         let layout = Layout::from_disk(&self.layout_file)?;
+        ensure_layout_not_redacted(&layout)?;
+        let mut users = BTreeSet::new();
+        if let Some(user) = layout.users.iter().find(|user| !users.insert(*user)) {
+            return Err(GenesisError::DuplicateUser(user.clone()).into());
+        }
+        layout.validate().map_err(|err| {
+            GenesisError::LayoutInvalid(format!("{} in {}", err, self.layout_file.display()))
+        })?;
+        for warning in layout.warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+        let framework = self
+            .framework_bundle
+            .as_deref()
+            .map(read_framework_bundle)
+            .transpose()?;
 
         // Upload layout file to ensure we can read later
         let client = self.git_options.get_client()?;
+        if self.init_git && matches!(client, Client::Github(..)) {
+            return Err(CliError::CommandArgumentError(
+                "--init-git is only supported with --local-repository-dir".to_string(),
+            ));
+        }
+        let _lock = client.lock()?;
         client.put(Path::new(LAYOUT_FILE), &layout)?;
+        self.progress.report(GenesisProgress::WroteLayout {
+            users: layout.users.len(),
+        });
+        let mut files = vec![PathBuf::from(LAYOUT_FILE)];
+        if let Some(framework) = framework {
+            client.put_framework(&framework)?;
+            self.progress.report(GenesisProgress::WroteFramework);
+            files.push(PathBuf::from(FRAMEWORK_NAME));
+        }
 
+        if self.init_git {
+            client.init_repository()?;
+            files.push(PathBuf::from(GITIGNORE_FILE));
+            client.commit(&files, "setup genesis layout and framework")?;
+        }
         Ok(())
     }
 }
 
+/// Reads a framework release bundle, checking it has packages whose modules all deserialize
+pub fn read_framework_bundle(path: &Path) -> CliTypedResult<ReleaseBundle> {
+    let invalid = |reason: String| {
+        CliError::CommandArgumentError(format!(
+            "Invalid framework bundle {}: {}",
+            path.display(),
+            reason
+        ))
+    };
+    let bundle = ReleaseBundle::read(path.to_path_buf()).map_err(|err| invalid(err.to_string()))?;
+    if bundle.packages.is_empty() {
+        return Err(invalid("it has no packages".to_string()));
+    }
+    for package in &bundle.packages {
+        for code in package.code() {
+            CompiledModule::deserialize(code).map_err(|err| {
+                invalid(format!(
+                    "package {} has a malformed module: {}",
+                    package.name(),
+                    err
+                ))
+            })?;
+        }
+    }
+    Ok(bundle)
+}
+
+/// Validates the contents of a `Layout` file against [`LAYOUT_SCHEMA`]
+///
+/// Every violation is reported on its own line, prefixed by the JSON pointer of the field
+pub fn validate_layout_schema(contents: &str) -> CliTypedResult<()> {
+    let instance: serde_json::Value = serde_yaml::from_str(contents)
+        .map_err(|err| CliError::UnableToParse(LAYOUT_FILE, err.to_string()))?;
+    let schema: serde_json::Value = serde_json::from_str(LAYOUT_SCHEMA)
+        .map_err(|err| CliError::UnexpectedError(format!("Invalid layout schema: {}", err)))?;
+    let schema = jsonschema::JSONSchema::compile(&schema)
+        .map_err(|err| CliError::UnexpectedError(format!("Invalid layout schema: {}", err)))?;
+
+    let result = schema.validate(&instance);
+    if let Err(errors) = result {
+        let errors: Vec<_> = errors
+            .map(|error| {
+                let pointer = error.instance_path.to_string();
+                let pointer = if pointer.is_empty() {
+                    "/"
+                } else {
+                    pointer.as_str()
+                };
+                format!("{}: {}", pointer, error)
+            })
+            .collect();
+        return Err(CliError::CommandArgumentError(format!(
+            "Layout does not match schema:\n{}",
+            errors.join("\n")
+        )));
+    }
+    Ok(())
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct GithubRepo {
     owner: String,
@@ -91,9 +235,20 @@ pub struct GitOptions {
     pub(crate) github_branch: String,
 
     /// Path to Github API token.  Token must have repo:* permissions
+    ///
+    /// This, or the `APTOS_GITHUB_TOKEN` environment variable, is the recommended way to give the
+    /// token, as it's kept out of the shell's history and the process list
     #[clap(long, parse(from_os_str))]
     pub(crate) github_token_file: Option<PathBuf>,
 
+    /// Github API token, for private repositories.  Use either this or --github-token-file
+    ///
+    /// Public repositories can be read without a token, but writing to them still needs one.  A
+    /// token given on the command line is visible to other users of the machine, so prefer
+    /// --github-token-file, or setting it in the `APTOS_GITHUB_TOKEN` environment variable
+    #[clap(long, env = "APTOS_GITHUB_TOKEN", hide_env_values = true)]
+    pub(crate) github_token: Option<String>,
+
     /// Path to local git repository
     #[clap(long, parse(from_os_str))]
     pub(crate) local_repository_dir: Option<PathBuf>,
@@ -103,30 +258,37 @@ impl GitOptions {
     pub fn get_client(self) -> CliTypedResult<Client> {
         if self.github_repository.is_none()
             && self.github_token_file.is_none()
+            && self.github_token.is_none()
             && self.local_repository_dir.is_some()
         {
             Ok(Client::local(self.local_repository_dir.unwrap()))
-        } else if self.github_repository.is_some()
-            && self.github_token_file.is_some()
-            && self.local_repository_dir.is_none()
-        {
-            Client::github(
-                self.github_repository.unwrap(),
-                self.github_branch,
-                self.github_token_file.unwrap(),
-            )
+        } else if self.github_repository.is_some() && self.local_repository_dir.is_none() {
+            let token = match (self.github_token_file, self.github_token) {
+                (Some(token_file), None) => Some(Token::FromDisk(token_file).read_token()?),
+                (None, token) => token,
+                (Some(_), Some(_)) => {
+                    return Err(CliError::CommandArgumentError(
+                        "Must provide only one of --github-token-file and --github-token"
+                            .to_string(),
+                    ))
+                }
+            };
+            Client::github(self.github_repository.unwrap(), self.github_branch, token)
         } else {
-            Err(CliError::CommandArgumentError("Must provide either only --local-repository-dir or both --github-repository and --github-token-path".to_string()))
+            Err(CliError::CommandArgumentError("Must provide either only --local-repository-dir or --github-repository, with an optional --github-token-file or --github-token".to_string()))
         }
     }
 }
 
 /// A client for abstracting away local vs Github storage
 ///
-/// Note: Writes do not commit locally
+/// Files read from Github are downloaded into a temporary directory, which is removed along with
+/// the client.
+///
+/// Note: Writes do not commit locally, unless committed with [`Client::commit`]
 pub enum Client {
     Local(PathBuf),
-    Github(GithubClient),
+    Github(GithubClient, TempDir),
 }
 
 impl Client {
@@ -134,37 +296,36 @@ impl Client {
         Client::Local(path)
     }
 
+    /// Creates a client for a Github repository, which can only read it without a token
     pub fn github(
         repository: GithubRepo,
         branch: String,
-        token_path: PathBuf,
+        token: Option<String>,
     ) -> CliTypedResult<Client> {
-        let token = Token::FromDisk(token_path).read_token()?;
-        Ok(Client::Github(GithubClient::new(
-            repository.owner,
-            repository.repository,
-            branch,
-            token,
-        )))
+        let client = if let Some(token) = token {
+            GithubClient::new(repository.owner, repository.repository, branch, token)
+        } else {
+            GithubClient::unauthenticated(repository.owner, repository.repository, branch)
+        };
+        let download_dir = TempDir::new().map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to create temporary directory {}", err))
+        })?;
+        Ok(Client::Github(client, download_dir))
     }
 
     /// Retrieves an object as a YAML encoded file from the appropriate storage
     pub fn get<T: DeserializeOwned + Debug>(&self, path: &Path) -> CliTypedResult<T> {
-        match self {
-            Client::Local(local_repository_path) => {
-                let path = local_repository_path.join(path);
-                let mut file = std::fs::File::open(path.as_path())
-                    .map_err(|e| CliError::IO(path.display().to_string(), e))?;
-
-                let mut contents = String::new();
-                file.read_to_string(&mut contents)
-                    .map_err(|e| CliError::IO(path.display().to_string(), e))?;
-                from_yaml(&contents)
-            }
-            Client::Github(client) => {
-                from_base64_encoded_yaml(&client.get_file(&path.display().to_string())?)
-            }
-        }
+        let path = match self {
+            Client::Local(local_repository_path) => local_repository_path.join(path),
+            Client::Github(client, download_dir) => download(client, download_dir, path)?,
+        };
+        let mut file = std::fs::File::open(path.as_path())
+            .map_err(|e| CliError::IO(path.display().to_string(), e))?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(|e| CliError::IO(path.display().to_string(), e))?;
+        from_yaml(&contents)
     }
 
     /// Puts an object as a YAML encoded file to the appropriate storage
@@ -188,7 +349,7 @@ impl Client {
                     to_yaml(input)?.as_bytes(),
                 )?;
             }
-            Client::Github(client) => {
+            Client::Github(client, _) => {
                 client.put(&name.display().to_string(), &to_base64_encoded_yaml(input)?)?;
             }
         }
@@ -196,13 +357,101 @@ impl Client {
         Ok(())
     }
 
+    /// Locks a local repository against writes by other processes, failing if it's already locked
+    ///
+    /// Github repositories aren't locked, and reads never take the lock
+    pub fn lock(&self) -> CliTypedResult<Option<RepositoryLock>> {
+        let local_repository_path = match self {
+            Client::Local(local_repository_path) => local_repository_path,
+            Client::Github(_, _) => return Ok(None),
+        };
+        create_dir_if_not_exist(local_repository_path)?;
+        let path = local_repository_path.join(LOCK_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&path)
+            .map_err(|e| CliError::IO(path.display().to_string(), e))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(RepositoryLock { file })),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => {
+                Err(CliError::CommandArgumentError(format!(
+                    "Repository {} is locked by another process, try again once it's done",
+                    local_repository_path.display()
+                )))
+            }
+            Err(err) => Err(CliError::IO(path.display().to_string(), err)),
+        }
+    }
+
+    /// Initializes a local repository as a git repository, ignoring its lock file
+    ///
+    /// Reinitializing an existing git repository leaves its history as it is
+    pub fn init_repository(&self) -> CliTypedResult<()> {
+        let local_repository_path = match self {
+            Client::Local(local_repository_path) => local_repository_path,
+            Client::Github(_, _) => {
+                return Err(CliError::UnexpectedError(
+                    "Github repositories can't be initialized".to_string(),
+                ))
+            }
+        };
+        create_dir_if_not_exist(local_repository_path)?;
+        run_git(local_repository_path, &["init", "--quiet"])?;
+        let gitignore = local_repository_path.join(GITIGNORE_FILE);
+        if !gitignore.exists() {
+            write_to_file(
+                &gitignore,
+                GITIGNORE_FILE,
+                format!("{}\n", LOCK_FILE).as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Commits files of a local git repository, relative to the repository
+    ///
+    /// Only the given files are committed, leaving anything else the committer has staged as it
+    /// was.  The repository must be the top level of its git repository, so that its files aren't
+    /// committed to an enclosing one.  Github writes are already commits, so there's nothing to
+    /// commit for Github repositories.
+    pub fn commit(&self, files: &[PathBuf], message: &str) -> CliTypedResult<()> {
+        let local_repository_path = match self {
+            Client::Local(local_repository_path) => local_repository_path,
+            Client::Github(_, _) => return Ok(()),
+        };
+        check_top_level(local_repository_path)?;
+        let paths: Vec<_> = files.iter().filter_map(|file| file.to_str()).collect();
+        let mut add = vec!["add", "--"];
+        add.extend(&paths);
+        run_git(local_repository_path, &add)?;
+
+        // Rewriting files unchanged leaves nothing to commit
+        let mut unchanged = vec!["diff", "--cached", "--quiet", "--"];
+        unchanged.extend(&paths);
+        if run_git(local_repository_path, &unchanged).is_ok() {
+            return Ok(());
+        }
+
+        // Commits are made as a genesis user, if the committer hasn't configured git
+        let user_name = format!("user.name={}", GIT_USER_NAME);
+        let user_email = format!("user.email={}", GIT_USER_EMAIL);
+        let mut commit = vec![];
+        if run_git(local_repository_path, &["config", "user.email"]).is_err() {
+            commit.extend(["-c", user_name.as_str(), "-c", user_email.as_str()]);
+        }
+        commit.extend(["commit", "--quiet", "--only", "--message", message, "--"]);
+        commit.extend(&paths);
+        run_git(local_repository_path, &commit)
+    }
+
     pub fn create_dir(&self, dir: &Path) -> CliTypedResult<()> {
         match self {
             Client::Local(local_repository_path) => {
                 let path = local_repository_path.join(dir);
                 create_dir_if_not_exist(path.as_path())?;
             }
-            Client::Github(_) => {
+            Client::Github(..) => {
                 // There's no such thing as an empty directory in Git, so do nothing
             }
         }
@@ -210,20 +459,138 @@ impl Client {
         Ok(())
     }
 
+    /// Puts a framework release bundle as BCS to the appropriate storage
+    pub fn put_framework(&self, framework: &ReleaseBundle) -> CliTypedResult<()> {
+        let bytes = bcs::to_bytes(framework).map_err(|err| CliError::BCS(FRAMEWORK_NAME, err))?;
+        match self {
+            Client::Local(local_repository_path) => {
+                create_dir_if_not_exist(local_repository_path)?;
+                write_to_file(
+                    &local_repository_path.join(FRAMEWORK_NAME),
+                    FRAMEWORK_NAME,
+                    &bytes,
+                )?;
+            }
+            Client::Github(client, _) => {
+                client.put(FRAMEWORK_NAME, &base64::encode(bytes))?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Retrieve framework release bundle.
     pub fn get_framework(&self) -> CliTypedResult<ReleaseBundle> {
+        Ok(ReleaseBundle::read(self.framework_file()?)?)
+    }
+
+    /// SHA3-256 hash of the framework release bundle, as stored
+    pub fn framework_hash(&self) -> CliTypedResult<HashValue> {
+        let bytes = read_from_file(&self.framework_file()?)?;
+        Ok(HashValue::sha3_256_of(&bytes))
+    }
+
+    /// Local path of the framework release bundle, downloading it first from Github
+    fn framework_file(&self) -> CliTypedResult<PathBuf> {
+        let missing = || CliError::from(GenesisError::FrameworkMissing(FRAMEWORK_NAME.to_string()));
         match self {
-            Client::Local(local_repository_path) => Ok(ReleaseBundle::read(
-                local_repository_path.join(FRAMEWORK_NAME),
-            )?),
-            Client::Github(client) => {
-                let bytes = base64::decode(client.get_file(FRAMEWORK_NAME)?)?;
-                Ok(bcs::from_bytes::<ReleaseBundle>(&bytes)?)
+            Client::Local(local_repository_path) => {
+                let path = local_repository_path.join(FRAMEWORK_NAME);
+                if path.exists() {
+                    Ok(path)
+                } else {
+                    Err(missing())
+                }
+            }
+            Client::Github(client, download_dir) => {
+                download(client, download_dir, Path::new(FRAMEWORK_NAME)).map_err(|err| {
+                    if is_not_found(&err) {
+                        missing()
+                    } else {
+                        err
+                    }
+                })
             }
         }
     }
 }
 
+/// Downloads a file from Github over the raw content API, into the same path in `download_dir`
+fn download(client: &GithubClient, download_dir: &TempDir, path: &Path) -> CliTypedResult<PathBuf> {
+    let contents = client
+        .get_raw_file(&path.display().to_string())
+        .map_err(|err| match err {
+            // Missing files are reported the same as missing local files
+            aptos_github_client::Error::NotFound(file) => {
+                CliError::IO(file, ErrorKind::NotFound.into())
+            }
+            err => err.into(),
+        })?;
+    let download_path = download_dir.path().join(path);
+    if let Some(dir) = download_path.parent() {
+        create_dir_if_not_exist(dir)?;
+    }
+    write_to_file(&download_path, &path.display().to_string(), &contents)?;
+    Ok(download_path)
+}
+
+/// Checks a local repository is the top level of a git repository, rather than a directory in one
+fn check_top_level(local_repository_path: &Path) -> CliTypedResult<()> {
+    let top_level = git_output(local_repository_path, &["rev-parse", "--show-toplevel"])?;
+    let canonical = |dir: &Path| {
+        std::fs::canonicalize(dir).map_err(|err| CliError::IO(dir.display().to_string(), err))
+    };
+    let top_level = canonical(Path::new(top_level.trim()))?;
+    if top_level == canonical(local_repository_path)? {
+        Ok(())
+    } else {
+        Err(CliError::CommandArgumentError(format!(
+            "{} isn't the top level of a git repository, but a directory in {}, initialize it \
+             with SetupGit --init-git to commit to it",
+            local_repository_path.display(),
+            top_level.display()
+        )))
+    }
+}
+
+/// Runs git in a local repository, failing with what it printed to stderr if it fails
+fn run_git(local_repository_path: &Path, args: &[&str]) -> CliTypedResult<()> {
+    git_output(local_repository_path, args).map(|_| ())
+}
+
+/// Runs git in a local repository as `run_git`, returning what it printed to stdout
+fn git_output(local_repository_path: &Path, args: &[&str]) -> CliTypedResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(local_repository_path)
+        .args(args)
+        .output()
+        .map_err(|err| CliError::IO("git".to_string(), err))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(CliError::UnexpectedError(format!(
+            "git {} failed in {}: {}",
+            args.join(" "),
+            local_repository_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// An advisory lock on a local repository, held by a command while it writes to the repository
+///
+/// The lock is released when dropped, or when the process exits
+pub struct RepositoryLock {
+    file: File,
+}
+
+impl Drop for RepositoryLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
 pub fn to_yaml<T: Serialize + ?Sized>(input: &T) -> CliTypedResult<String> {
     Ok(serde_yaml::to_string(input)?)
 }
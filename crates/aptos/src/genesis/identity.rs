@@ -0,0 +1,181 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::{current_dir, read_from_file},
+    },
+    genesis::{
+        get_config,
+        git::{from_yaml, GitOptions},
+        keys::{VALIDATOR_FILE, VFN_FILE},
+    },
+    CliCommand,
+};
+use aptos_config::config::IdentityBlob;
+use aptos_crypto::PrivateKey;
+use aptos_genesis::config::ValidatorConfiguration;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    fmt::{Debug, Display, Formatter},
+    path::PathBuf,
+};
+
+/// Compare a node's identity against the validator configuration it was registered with
+///
+/// Reads the identity file a node is started with, and checks that its account, consensus and
+/// network keys are those of the user's configuration in the repository, reporting every field
+/// that differs.  No node needs to be running, so keys can be checked before genesis, or before
+/// a node is restarted with a new identity.
+#[derive(Parser)]
+pub struct DiffNodeIdentity {
+    /// Name of the validator
+    #[clap(long)]
+    pub(crate) username: String,
+
+    /// Path to the node's identity file, defaults to validator-identity.yaml in the current
+    /// directory, or validator-full-node-identity.yaml for a fullnode
+    #[clap(long, parse(from_os_str))]
+    pub(crate) identity_file: Option<PathBuf>,
+
+    /// Whether the identity is of the validator's fullnode
+    ///
+    /// Fullnodes only have an account address and a network key, which is compared against the
+    /// configuration's fullnode network key
+    #[clap(long)]
+    pub(crate) full_node: bool,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// A field of a node's identity which doesn't match the validator's configuration
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct IdentityMismatch {
+    /// Name of the field in the validator's configuration
+    pub field: &'static str,
+    /// The value in the identity, if it has one
+    pub identity: Option<String>,
+    /// The value in the configuration, if it has one
+    pub genesis: Option<String>,
+}
+
+impl Display for IdentityMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+        write!(
+            f,
+            "{}: identity has {}, but genesis has {}",
+            self.field,
+            value(&self.identity),
+            value(&self.genesis)
+        )
+    }
+}
+
+#[async_trait]
+impl CliCommand<()> for DiffNodeIdentity {
+    fn command_name(&self) -> &'static str {
+        "DiffNodeIdentity"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let identity_file = match self.identity_file {
+            Some(identity_file) => identity_file,
+            None if self.full_node => current_dir()?.join(VFN_FILE),
+            None => current_dir()?.join(VALIDATOR_FILE),
+        };
+        let identity: IdentityBlob = from_yaml(
+            &String::from_utf8(read_from_file(&identity_file)?).map_err(CliError::from)?,
+        )?;
+        let client = self.git_options.get_client()?;
+        let config = get_config(&client, &self.username)?;
+
+        let mismatches = diff_identity(&identity, &config, self.full_node);
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(CliError::CommandArgumentError(format!(
+                "Identity {} doesn't match the configuration of {} in {} fields:\n{}",
+                identity_file.display(),
+                self.username,
+                mismatches.len(),
+                mismatches
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )))
+        }
+    }
+}
+
+/// Compares the public keys of a node's identity with a validator's configuration
+///
+/// Validators run as their operator, so their account must be the operator's.  A fullnode's
+/// identity is only compared on its account address and network key.
+pub fn diff_identity(
+    identity: &IdentityBlob,
+    config: &ValidatorConfiguration,
+    full_node: bool,
+) -> Vec<IdentityMismatch> {
+    let mut mismatches = Vec::new();
+    check(
+        &mut mismatches,
+        "operator_account_address",
+        identity.account_address,
+        Some(config.operator_account_address),
+    );
+    if full_node {
+        check(
+            &mut mismatches,
+            "full_node_network_public_key",
+            Some(identity.network_private_key.public_key()),
+            config.full_node_network_public_key,
+        );
+    } else {
+        check(
+            &mut mismatches,
+            "operator_account_public_key",
+            identity
+                .account_private_key
+                .as_ref()
+                .map(PrivateKey::public_key),
+            Some(config.operator_account_public_key.clone()),
+        );
+        check(
+            &mut mismatches,
+            "consensus_public_key",
+            identity
+                .consensus_private_key
+                .as_ref()
+                .map(PrivateKey::public_key),
+            Some(config.consensus_public_key.clone()),
+        );
+        check(
+            &mut mismatches,
+            "validator_network_public_key",
+            Some(identity.network_private_key.public_key()),
+            Some(config.validator_network_public_key),
+        );
+    }
+    mismatches
+}
+
+fn check<T: Debug + PartialEq>(
+    mismatches: &mut Vec<IdentityMismatch>,
+    field: &'static str,
+    identity: Option<T>,
+    genesis: Option<T>,
+) {
+    if identity != genesis {
+        mismatches.push(IdentityMismatch {
+            field,
+            identity: identity.map(|value| format!("{:?}", value)),
+            genesis: genesis.map(|value| format!("{:?}", value)),
+        });
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult, OutputFormat, OutputOptions},
+    genesis::{
+        encrypt::PassphraseOptions,
+        git::{GitOptions, LAYOUT_FILE},
+        keys::{SetValidatorConfiguration, ValidatorConfigurationSummary},
+        redact::ensure_layout_not_redacted,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::{HostAndPort, Layout};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Import the public identities of users who generated their keys on their own machines
+///
+/// For ceremonies where each operator runs `GenerateKeys` themselves, and sends only their public
+/// keys file.  The `--identity-dir` holds one `<username>.yaml` public keys file per user, and
+/// each user needs a `--validator-host`.  Every file is checked before anything is written, and
+/// any which are malformed, of an unsupported key type, or duplicate another user are all
+/// reported.  The users are then added to the layout, and their validator configurations set,
+/// holding the repository's lock throughout.
+#[derive(Parser)]
+pub struct ImportPublicIdentity {
+    /// Directory of public keys files, each named after its user as `<username>.yaml`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) identity_dir: PathBuf,
+
+    /// Host and port pair of a user's validator, as `<username>=<host>:<port>`
+    #[clap(long = "validator-host", required = true)]
+    pub(crate) validator_hosts: Vec<UserHost>,
+
+    /// Stake amount of every user
+    #[clap(long, default_value_t = 1)]
+    pub(crate) stake_amount: u64,
+
+    /// Commission rate every user pays their operator
+    #[clap(long, default_value_t = 0)]
+    pub(crate) commission_percentage: u64,
+
+    /// Commit the layout and every configuration written, in one commit, to the local git
+    /// repository
+    #[clap(long)]
+    pub(crate) commit: bool,
+
+    #[clap(flatten)]
+    pub(crate) output_options: OutputOptions,
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// A user's validator host given to `ImportPublicIdentity`
+#[derive(Clone, Debug)]
+pub struct UserHost {
+    pub username: String,
+    pub host: HostAndPort,
+}
+
+impl FromStr for UserHost {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (username, host) = s.split_once('=').ok_or_else(|| {
+            CliError::CommandArgumentError(format!(
+                "--validator-host {} must be given as <username>=<host>:<port>",
+                s
+            ))
+        })?;
+        if username.is_empty() {
+            return Err(CliError::CommandArgumentError(format!(
+                "--validator-host {} has an empty username",
+                s
+            )));
+        }
+        let host = HostAndPort::from_str(host).map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid --validator-host {}: {}", s, err))
+        })?;
+        Ok(UserHost {
+            username: username.to_string(),
+            host,
+        })
+    }
+}
+
+#[async_trait]
+impl CliCommand<Vec<ValidatorConfigurationSummary>> for ImportPublicIdentity {
+    fn command_name(&self) -> &'static str {
+        "ImportPublicIdentity"
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        self.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<ValidatorConfigurationSummary>> {
+        let mut hosts = BTreeMap::new();
+        for user_host in &self.validator_hosts {
+            if hosts
+                .insert(user_host.username.clone(), user_host.host.clone())
+                .is_some()
+            {
+                return Err(CliError::CommandArgumentError(format!(
+                    "--validator-host is given more than once for user {}",
+                    user_host.username
+                )));
+            }
+        }
+        let identity_files = read_identity_dir(&self.identity_dir)?;
+
+        let git_client = self.git_options.clone().get_client()?;
+        let _lock = git_client.lock()?;
+        let mut layout: Layout = git_client.get(Path::new(LAYOUT_FILE))?;
+        ensure_layout_not_redacted(&layout)?;
+
+        // Check every file before writing anything
+        let mut imports = Vec::new();
+        let mut errors = Vec::new();
+        let mut accounts: BTreeMap<AccountAddress, &PathBuf> = BTreeMap::new();
+        for (username, identity_file) in &identity_files {
+            let command = match hosts.remove(username) {
+                Some(host) => self.configuration(username, identity_file, host),
+                None => {
+                    errors.push(format!(
+                        "{}: no --validator-host is given for user {}",
+                        identity_file.display(),
+                        username
+                    ));
+                    continue;
+                }
+            };
+            if layout.users.contains(username) {
+                errors.push(format!(
+                    "{}: user {} is already in the layout",
+                    identity_file.display(),
+                    username
+                ));
+                continue;
+            }
+            match command.build_configurations() {
+                Ok((operator_config, owner_config)) => {
+                    let account_address = owner_config.owner_account_address;
+                    if let Some(other_file) = accounts.insert(account_address, identity_file) {
+                        errors.push(format!(
+                            "{}: account {} is the same as in {}",
+                            identity_file.display(),
+                            account_address,
+                            other_file.display()
+                        ));
+                    } else {
+                        imports.push((command, operator_config, owner_config));
+                    }
+                }
+                Err(err) => errors.push(format!("{}: {}", identity_file.display(), err)),
+            }
+        }
+        for username in hosts.keys() {
+            errors.push(format!(
+                "--validator-host is given for user {}, who has no public keys file",
+                username
+            ));
+        }
+        if !errors.is_empty() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Failed to import {} public identities, none were imported:\n{}",
+                errors.len(),
+                errors.join("\n")
+            )));
+        }
+
+        // The layout is written last, so that it never lists a user without configurations
+        let mut files = Vec::new();
+        let mut summaries = Vec::new();
+        for (command, operator_config, owner_config) in &imports {
+            files.extend(command.put_configurations(&git_client, operator_config, owner_config)?);
+            summaries.push(command.summary(owner_config));
+            layout.users.push(command.username.clone());
+            layout.owner_public_keys.insert(
+                command.username.clone(),
+                owner_config.owner_account_public_key.clone(),
+            );
+        }
+        git_client.put(Path::new(LAYOUT_FILE), &layout)?;
+        files.push(PathBuf::from(LAYOUT_FILE));
+        if self.commit {
+            git_client.commit(
+                &files,
+                &format!("import public identities of {} users", summaries.len()),
+            )?;
+        }
+        Ok(summaries)
+    }
+}
+
+impl ImportPublicIdentity {
+    /// The configuration of a user's validator, owned and operated with their identity
+    fn configuration(
+        &self,
+        username: &str,
+        identity_file: &Path,
+        host: HostAndPort,
+    ) -> SetValidatorConfiguration {
+        SetValidatorConfiguration {
+            username: username.to_string(),
+            validator_host: vec![host],
+            full_node_host: None,
+            stake_amount: self.stake_amount,
+            commission_percentage: self.commission_percentage,
+            check_reachability: false,
+            commit: false,
+            owner_public_identity_file: Some(identity_file.to_path_buf()),
+            owner_private_keys_file: None,
+            operator_public_identity_file: None,
+            voter_public_identity_file: None,
+            passphrase_options: PassphraseOptions::default(),
+            output_options: OutputOptions::default(),
+            git_options: self.git_options.clone(),
+        }
+    }
+}
+
+/// The public keys files in a directory, by the username each is named after
+///
+/// Files which aren't `.yaml` are left out, so that a directory of files sent by the users can be
+/// imported as it is.
+fn read_identity_dir(identity_dir: &Path) -> CliTypedResult<BTreeMap<String, PathBuf>> {
+    let entries = std::fs::read_dir(identity_dir)
+        .map_err(|e| CliError::IO(identity_dir.display().to_string(), e))?;
+    let mut identity_files = BTreeMap::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| CliError::IO(identity_dir.display().to_string(), e))?
+            .path();
+        if !path.is_file() || path.extension() != Some(OsStr::new("yaml")) {
+            continue;
+        }
+        if let Some(username) = path.file_stem().and_then(|stem| stem.to_str()) {
+            identity_files.insert(username.to_string(), path.clone());
+        }
+    }
+    if identity_files.is_empty() {
+        return Err(CliError::CommandArgumentError(format!(
+            "No public keys files named <username>.yaml in {}",
+            identity_dir.display()
+        )));
+    }
+    Ok(identity_files)
+}
@@ -0,0 +1,112 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::CliTypedResult,
+        utils::{read_from_file, write_to_file},
+    },
+    genesis::{
+        get_string_configs,
+        git::{from_yaml, to_yaml, Client},
+        validate_config, ConfigFailures,
+    },
+};
+use aptos_genesis::config::{Layout, ValidatorConfiguration};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// File in the output directory recording the inputs validated by incremental genesis
+pub const VALIDATED_INPUTS_FILE: &str = "validated_inputs.yaml";
+
+/// Validator configurations validated by a previous genesis, keyed by user
+///
+/// During a staged ceremony, genesis is regenerated every time validators add their
+/// configuration.  A user's configuration is only validated again if their owner or operator
+/// file changed since it was last validated.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ValidatedInputs {
+    pub validators: BTreeMap<String, ValidatedValidator>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ValidatedValidator {
+    /// The owner file, as YAML, the configuration was validated from
+    pub owner: String,
+    /// The operator file, as YAML, the configuration was validated from
+    pub operator: String,
+    pub config: ValidatorConfiguration,
+}
+
+impl ValidatedInputs {
+    /// Loads the inputs validated by a previous genesis, or none if there is no previous genesis
+    pub fn load(file: &Path) -> CliTypedResult<ValidatedInputs> {
+        if file.exists() {
+            from_yaml(&String::from_utf8(read_from_file(file)?)?)
+        } else {
+            Ok(ValidatedInputs::default())
+        }
+    }
+
+    pub fn save(&self, file: &Path) -> CliTypedResult<()> {
+        write_to_file(file, &file.display().to_string(), to_yaml(self)?.as_bytes())
+    }
+
+    /// Returns the configurations of every validator in the layout, in the order of its sorted
+    /// users, reusing those whose files are unchanged and validating the others, along with the
+    /// users which were validated.
+    ///
+    /// Users no longer in the layout are forgotten.
+    pub fn update(
+        &mut self,
+        client: &Client,
+        layout: &Layout,
+    ) -> CliTypedResult<(Vec<ValidatorConfiguration>, Vec<String>)> {
+        let mut validators = Vec::new();
+        let mut validated_users = Vec::new();
+        let mut failures = ConfigFailures::default();
+        let mut previous = std::mem::take(&mut self.validators);
+        for user in layout.sorted_users() {
+            match self.update_user(client, user, previous.remove(user)) {
+                Ok((config, validated)) => {
+                    validators.push(config);
+                    if validated {
+                        validated_users.push(user.clone());
+                    }
+                }
+                Err(failure) => failures.push(user, failure),
+            }
+        }
+        failures.check()?;
+        Ok((validators, validated_users))
+    }
+
+    fn update_user(
+        &mut self,
+        client: &Client,
+        user: &str,
+        previous: Option<ValidatedValidator>,
+    ) -> CliTypedResult<(ValidatorConfiguration, bool)> {
+        let (owner_config, operator_config) = get_string_configs(client, user)?;
+        let owner = to_yaml(&owner_config)?;
+        let operator = to_yaml(&operator_config)?;
+
+        let (validator, validated) = match previous {
+            Some(previous) if previous.owner == owner && previous.operator == operator => {
+                (previous, false)
+            }
+            _ => {
+                let config = validate_config(user, &owner_config, &operator_config)?;
+                let validator = ValidatedValidator {
+                    owner,
+                    operator,
+                    config,
+                };
+                (validator, true)
+            }
+        };
+        let config = validator.config.clone();
+        self.validators.insert(user.to_string(), validator);
+        Ok((config, validated))
+    }
+}
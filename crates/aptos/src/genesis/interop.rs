@@ -0,0 +1,205 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::{read_from_file, write_to_file},
+    },
+    genesis::{
+        diff::{decode_genesis, genesis_state},
+        dump::{decode_resource, missing_resource, summarize_genesis, GenesisValidator},
+        error::GenesisError,
+        GENESIS_FILE,
+    },
+    CliCommand,
+};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    access_path::Path as AccessPathType,
+    account_config::{CoinInfoResource, OptionalAggregator, CORE_CODE_ADDRESS},
+    state_store::state_key::StateKey,
+    transaction::Transaction,
+    waypoint::Waypoint,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_deps::move_core_types::move_resource::MoveStructType;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// File in the genesis output directory holding the portable manifest, with `--manifest`
+pub const INTEROP_MANIFEST_FILE: &str = "genesis_interop_manifest.json";
+/// Version of the portable manifest's schema, raised whenever a field changes meaning or is
+/// removed
+pub const INTEROP_MANIFEST_VERSION: u32 = 1;
+
+/// A genesis described in a single JSON file, for handing it off to tooling outside of the CLI
+///
+/// Unlike the signed genesis manifest, it isn't signed, and only identifies the genesis blob by
+/// its SHA-256, which most tooling can compute.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InteropManifest {
+    /// Version of the manifest's schema
+    pub version: u32,
+    pub chain_id: u8,
+    pub waypoint: Waypoint,
+    /// SHA3-256 hash of the framework release bundle genesis was generated with
+    pub framework_hash: HashValue,
+    /// SHA-256 of the genesis blob, hex encoded
+    pub genesis_sha256: String,
+    /// Validators active after genesis, in validator index order
+    pub validators: Vec<GenesisValidator>,
+    pub total_voting_power: u128,
+    /// Supply of the native coin after genesis, if the framework tracks it
+    pub total_supply: Option<u128>,
+}
+
+impl InteropManifest {
+    /// Describes the genesis encoded in `genesis_bytes`
+    pub fn new(
+        genesis_bytes: &[u8],
+        waypoint: Waypoint,
+        framework_hash: HashValue,
+    ) -> CliTypedResult<InteropManifest> {
+        let genesis = decode_genesis(genesis_bytes)?;
+        let summary = summarize_genesis(&genesis, None)?;
+        Ok(InteropManifest {
+            version: INTEROP_MANIFEST_VERSION,
+            chain_id: summary.chain_id,
+            waypoint,
+            framework_hash,
+            genesis_sha256: sha256(genesis_bytes),
+            validators: summary.validators,
+            total_voting_power: summary.total_voting_power,
+            total_supply: genesis_total_supply(&genesis)?,
+        })
+    }
+
+    /// Reads a manifest, rejecting versions of the schema newer than this CLI's
+    pub fn read(path: &Path) -> CliTypedResult<InteropManifest> {
+        let unreadable =
+            |reason: String| CliError::UnableToReadFile(path.display().to_string(), reason);
+        let json: serde_json::Value = serde_json::from_slice(&read_from_file(path)?)
+            .map_err(|err| unreadable(err.to_string()))?;
+        match json.get("version").and_then(serde_json::Value::as_u64) {
+            Some(version) if version > INTEROP_MANIFEST_VERSION as u64 => {
+                return Err(unreadable(format!(
+                    "Manifest has version {}, but this CLI only reads up to version {}",
+                    version, INTEROP_MANIFEST_VERSION
+                )))
+            }
+            Some(_) => {}
+            None => return Err(unreadable("Manifest has no version".to_string())),
+        }
+        serde_json::from_value(json).map_err(|err| unreadable(err.to_string()))
+    }
+
+    /// Writes the manifest to `file`
+    pub fn write(&self, file: &Path) -> CliTypedResult<PathBuf> {
+        let json = serde_json::to_string_pretty(self).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to encode manifest: {}", err))
+        })?;
+        write_to_file(file, &file.display().to_string(), json.as_bytes())?;
+        Ok(file.to_path_buf())
+    }
+}
+
+/// Verify a genesis against its portable manifest
+///
+/// Checks the SHA-256 of the genesis blob is the one the manifest lists, and that the waypoint
+/// computed from the blob is the manifest's waypoint.
+#[derive(Parser)]
+pub struct VerifyInteropManifest {
+    /// Path to the manifest written by `GenerateGenesis --manifest`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) manifest_file: PathBuf,
+
+    /// Path to the genesis blob, defaults to the genesis.blob next to the manifest
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_file: Option<PathBuf>,
+}
+
+#[async_trait]
+impl CliCommand<InteropManifest> for VerifyInteropManifest {
+    fn command_name(&self) -> &'static str {
+        "VerifyInteropManifest"
+    }
+
+    async fn execute(self) -> CliTypedResult<InteropManifest> {
+        let manifest = InteropManifest::read(&self.manifest_file)?;
+        let genesis_file = self.genesis_file.unwrap_or_else(|| {
+            self.manifest_file
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(GENESIS_FILE)
+        });
+        let genesis_bytes = read_from_file(&genesis_file)?;
+        let genesis_sha256 = sha256(&genesis_bytes);
+        if genesis_sha256 != manifest.genesis_sha256 {
+            return Err(CliError::UnexpectedError(format!(
+                "{} has SHA-256 {}, but the manifest lists {}",
+                genesis_file.display(),
+                genesis_sha256,
+                manifest.genesis_sha256
+            )));
+        }
+        let computed = aptos_genesis::generate_waypoint(&decode_genesis(&genesis_bytes)?)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        if computed != manifest.waypoint {
+            return Err(GenesisError::WaypointMismatch {
+                expected: manifest.waypoint,
+                computed,
+            }
+            .into());
+        }
+        Ok(manifest)
+    }
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// The supply of the native coin written by a genesis transaction, if the framework tracks it
+pub fn genesis_total_supply(genesis: &Transaction) -> CliTypedResult<Option<u128>> {
+    let state = genesis_state(genesis)?;
+    let coin_info = state.iter().find_map(|(key, bytes)| match key {
+        StateKey::AccessPath(access_path) if access_path.address == CORE_CODE_ADDRESS => {
+            match access_path.get_path() {
+                AccessPathType::Resource(struct_tag)
+                    if struct_tag == CoinInfoResource::struct_tag() =>
+                {
+                    Some(bytes)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    });
+    let coin_info: CoinInfoResource = decode_resource(
+        coin_info.ok_or_else(|| missing_resource("coin info"))?,
+        CORE_CODE_ADDRESS,
+    )?;
+
+    // The supply is kept either in the coin info, or in an aggregator's table item
+    match coin_info.supply() {
+        Some(OptionalAggregator {
+            integer: Some(integer),
+            ..
+        }) => Ok(Some(integer.value)),
+        Some(OptionalAggregator {
+            aggregator: Some(aggregator),
+            ..
+        }) => {
+            let bytes = state
+                .get(&aggregator.state_key())
+                .ok_or_else(|| missing_resource("coin supply"))?;
+            bcs::from_bytes(bytes)
+                .map(Some)
+                .map_err(|err| CliError::BCS("coin supply", err))
+        }
+        _ => Ok(None),
+    }
+}
@@ -6,23 +6,39 @@ use crate::common::utils::{create_dir_if_not_exist, current_dir, dir_default_to_
 use crate::genesis::git::{LAYOUT_FILE, OPERATOR_FILE, OWNER_FILE};
 use crate::{
     common::{
-        types::{CliError, CliTypedResult, PromptOptions, RngArgs},
+        types::{CliError, CliTypedResult, OutputFormat, OutputOptions, PromptOptions, RngArgs},
         utils::{check_if_file_exists, read_from_file, write_to_user_only_file},
     },
-    genesis::git::{from_yaml, to_yaml, GitOptions},
+    genesis::{
+        encrypt::{encrypted_file_path, write_encrypted_file, PassphraseOptions},
+        git::{to_yaml, Client, GitOptions},
+        signature::{read_owner_private_key, sign_config},
+    },
     CliCommand,
 };
+use aptos_config::config::IdentityBlob;
+use aptos_crypto::{ed25519::Ed25519PublicKey, ValidCryptoMaterialStringExt};
 use aptos_genesis::config::{Layout, OperatorConfiguration, OwnerConfiguration};
-use aptos_genesis::keys::PublicIdentity;
+use aptos_genesis::keys::{PrivateIdentity, PublicIdentity};
 use aptos_genesis::{config::HostAndPort, keys::generate_key_objects};
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use async_trait::async_trait;
 use clap::Parser;
-use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-const PRIVATE_KEYS_FILE: &str = "private-keys.yaml";
+pub const PRIVATE_KEYS_FILE: &str = "private-keys.yaml";
 pub const PUBLIC_KEYS_FILE: &str = "public-keys.yaml";
-const VALIDATOR_FILE: &str = "validator-identity.yaml";
-const VFN_FILE: &str = "validator-full-node-identity.yaml";
+pub const VALIDATOR_FILE: &str = "validator-identity.yaml";
+pub const VFN_FILE: &str = "validator-full-node-identity.yaml";
+/// Files holding private key material, which are encrypted with `--encrypt`
+pub const PRIVATE_FILES: [&str; 3] = [PRIVATE_KEYS_FILE, VALIDATOR_FILE, VFN_FILE];
+/// How long each host has to accept a connection, with `--check-reachability`
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Generate account key, consensus key, and network key for a validator
 #[derive(Parser)]
@@ -31,6 +47,15 @@ pub struct GenerateKeys {
     #[clap(long, parse(from_os_str))]
     pub(crate) output_dir: Option<PathBuf>,
 
+    /// Encrypt the private keys with a passphrase
+    ///
+    /// The private keys and identity files are written with an `.encrypted` extension, and can be
+    /// decrypted with `DecryptKeys`.  The public keys are always written in plaintext.
+    #[clap(long)]
+    pub(crate) encrypt: bool,
+
+    #[clap(flatten)]
+    pub(crate) passphrase_options: PassphraseOptions,
     #[clap(flatten)]
     pub(crate) pool_address_args: OptionalPoolAddressArgs,
     #[clap(flatten)]
@@ -48,14 +73,15 @@ impl CliCommand<Vec<PathBuf>> for GenerateKeys {
     async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
         let output_dir = dir_default_to_current(self.output_dir.clone())?;
 
-        let private_keys_file = output_dir.join(PRIVATE_KEYS_FILE);
-        let public_keys_file = output_dir.join(PUBLIC_KEYS_FILE);
-        let validator_file = output_dir.join(VALIDATOR_FILE);
-        let vfn_file = output_dir.join(VFN_FILE);
-        check_if_file_exists(private_keys_file.as_path(), self.prompt_options)?;
-        check_if_file_exists(public_keys_file.as_path(), self.prompt_options)?;
-        check_if_file_exists(validator_file.as_path(), self.prompt_options)?;
-        check_if_file_exists(vfn_file.as_path(), self.prompt_options)?;
+        check_key_files(&output_dir, self.encrypt, self.prompt_options)?;
+        let passphrase = if self.encrypt {
+            Some(
+                self.passphrase_options
+                    .passphrase(self.prompt_options, true)?,
+            )
+        } else {
+            None
+        };
 
         let mut key_generator = self.rng_args.key_generator()?;
         let (mut validator_blob, mut vfn_blob, private_identity, public_identity) =
@@ -67,32 +93,75 @@ impl CliCommand<Vec<PathBuf>> for GenerateKeys {
             vfn_blob.account_address = Some(pool_address);
         }
 
-        // Create the directory if it doesn't exist
-        create_dir_if_not_exist(output_dir.as_path())?;
+        write_key_files(
+            &output_dir,
+            passphrase.as_deref(),
+            &public_identity,
+            &private_identity,
+            &validator_blob,
+            &vfn_blob,
+        )
+    }
+}
+
+/// Checks that none of the key files in `output_dir` are overwritten without confirmation
+pub(crate) fn check_key_files(
+    output_dir: &Path,
+    encrypt: bool,
+    prompt_options: PromptOptions,
+) -> CliTypedResult<()> {
+    check_if_file_exists(output_dir.join(PUBLIC_KEYS_FILE).as_path(), prompt_options)?;
+    for name in PRIVATE_FILES {
+        let file = output_dir.join(name);
+        let file = if encrypt {
+            encrypted_file_path(&file)?
+        } else {
+            file
+        };
+        check_if_file_exists(file.as_path(), prompt_options)?;
+    }
+    Ok(())
+}
 
-        write_to_user_only_file(
-            private_keys_file.as_path(),
-            PRIVATE_KEYS_FILE,
-            to_yaml(&private_identity)?.as_bytes(),
-        )?;
-        write_to_user_only_file(
-            public_keys_file.as_path(),
-            PUBLIC_KEYS_FILE,
-            to_yaml(&public_identity)?.as_bytes(),
-        )?;
-        write_to_user_only_file(
-            validator_file.as_path(),
-            VALIDATOR_FILE,
-            to_yaml(&validator_blob)?.as_bytes(),
-        )?;
-        write_to_user_only_file(vfn_file.as_path(), VFN_FILE, to_yaml(&vfn_blob)?.as_bytes())?;
-        Ok(vec![
-            public_keys_file,
-            private_keys_file,
-            validator_file,
-            vfn_file,
-        ])
+/// Writes the key files into `output_dir`, encrypting the private ones if given a passphrase
+pub(crate) fn write_key_files(
+    output_dir: &Path,
+    passphrase: Option<&str>,
+    public_identity: &PublicIdentity,
+    private_identity: &PrivateIdentity,
+    validator_blob: &IdentityBlob,
+    vfn_blob: &IdentityBlob,
+) -> CliTypedResult<Vec<PathBuf>> {
+    // Create the directory if it doesn't exist
+    create_dir_if_not_exist(output_dir)?;
+
+    let public_keys_file = output_dir.join(PUBLIC_KEYS_FILE);
+    write_to_user_only_file(
+        public_keys_file.as_path(),
+        PUBLIC_KEYS_FILE,
+        to_yaml(public_identity)?.as_bytes(),
+    )?;
+    let mut files = vec![public_keys_file];
+    let contents = [
+        to_yaml(private_identity)?,
+        to_yaml(validator_blob)?,
+        to_yaml(vfn_blob)?,
+    ];
+    for (name, contents) in PRIVATE_FILES.into_iter().zip(contents) {
+        let file = output_dir.join(name);
+        if let Some(passphrase) = passphrase {
+            files.push(write_encrypted_file(
+                &file,
+                name,
+                passphrase,
+                contents.as_bytes(),
+            )?);
+        } else {
+            write_to_user_only_file(file.as_path(), name, contents.as_bytes())?;
+            files.push(file);
+        }
     }
+    Ok(files)
 }
 
 /// Set validator configuration for a single validator in the git repository
@@ -103,10 +172,15 @@ pub struct SetValidatorConfiguration {
     pub(crate) username: String,
 
     /// Host and port pair for the validator e.g. 127.0.0.1:6180 or aptoslabs.com:6180
-    #[clap(long)]
-    pub(crate) validator_host: HostAndPort,
+    ///
+    /// May be repeated for a validator reachable at several hosts, the first being its main host
+    #[clap(long, required = true)]
+    pub(crate) validator_host: Vec<HostAndPort>,
 
     /// Host and port pair for the fullnode e.g. 127.0.0.1:6180 or aptoslabs.com:6180
+    ///
+    /// Advertised on chain with the operator's fullnode network key, which the operator's public
+    /// identity must then have
     #[clap(long)]
     pub(crate) full_node_host: Option<HostAndPort>,
 
@@ -118,10 +192,31 @@ pub struct SetValidatorConfiguration {
     #[clap(long, default_value_t = 0)]
     pub(crate) commission_percentage: u64,
 
+    /// Check each validator and fullnode host can be connected to before writing the configuration
+    ///
+    /// Fails if a host doesn't resolve, and warns if a host resolves but doesn't accept a
+    /// connection, as its node may not be running yet
+    #[clap(long)]
+    pub(crate) check_reachability: bool,
+
+    /// Commit the configuration to the local git repository set up by `SetupGit --init-git`
+    ///
+    /// Committed as "add config for <username>".  Writes to Github are always commits.
+    #[clap(long)]
+    pub(crate) commit: bool,
+
     /// Path to private identity generated from GenerateKeys
     #[clap(long, parse(from_os_str))]
     pub(crate) owner_public_identity_file: Option<PathBuf>,
 
+    /// Path to the owner's private keys generated from GenerateKeys, to sign the configuration
+    ///
+    /// The signature is written to the validator's directory, for `GenerateGenesis
+    /// --require-signed-configs` to check against the owner's account key.  Keys encrypted by
+    /// `GenerateKeys --encrypt` are decrypted with the passphrase.
+    #[clap(long, parse(from_os_str))]
+    pub(crate) owner_private_keys_file: Option<PathBuf>,
+
     /// Path to operator public identity, defaults to owner identity
     #[clap(long, parse(from_os_str))]
     pub(crate) operator_public_identity_file: Option<PathBuf>,
@@ -130,27 +225,73 @@ pub struct SetValidatorConfiguration {
     #[clap(long, parse(from_os_str))]
     pub(crate) voter_public_identity_file: Option<PathBuf>,
 
+    #[clap(flatten)]
+    pub(crate) passphrase_options: PassphraseOptions,
+    #[clap(flatten)]
+    pub(crate) output_options: OutputOptions,
     #[clap(flatten)]
     pub(crate) git_options: GitOptions,
 }
 
+/// Summary of the configuration set for a validator
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidatorConfigurationSummary {
+    pub username: String,
+    pub owner_account_address: AccountAddress,
+    pub operator_account_address: AccountAddress,
+    pub voter_account_address: AccountAddress,
+    pub stake_amount: u64,
+    pub commission_percentage: u64,
+    /// Hosts the validator is reachable at, its main host first
+    pub validator_hosts: Vec<HostAndPort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_node_host: Option<HostAndPort>,
+}
+
 #[async_trait]
-impl CliCommand<()> for SetValidatorConfiguration {
+impl CliCommand<ValidatorConfigurationSummary> for SetValidatorConfiguration {
     fn command_name(&self) -> &'static str {
         "SetValidatorConfiguration"
     }
 
-    async fn execute(self) -> CliTypedResult<()> {
+    fn output_format(&self) -> OutputFormat {
+        self.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<ValidatorConfigurationSummary> {
+        let (operator_config, owner_config) = self.build_configurations()?;
+        if self.check_reachability {
+            check_reachability(self.validator_host.iter().chain(&self.full_node_host))?;
+        }
+        self.write_configurations(&operator_config, &owner_config)?;
+        Ok(self.summary(&owner_config))
+    }
+}
+
+impl SetValidatorConfiguration {
+    /// Loads and checks all inputs, building the configurations without writing anything
+    pub(crate) fn build_configurations(
+        &self,
+    ) -> CliTypedResult<(OperatorConfiguration, OwnerConfiguration)> {
+        // The layout is normally uploaded first by SetupGit, but may not have been yet
+        let layout: Option<Layout> = self
+            .git_options
+            .clone()
+            .get_client()?
+            .get(Path::new(LAYOUT_FILE))
+            .ok();
+        self.check_stake(layout.as_ref())?;
+
         // Load owner
-        let owner_keys_file = if let Some(owner_keys_file) = self.owner_public_identity_file {
-            owner_keys_file
+        let owner_keys_file = if let Some(ref owner_keys_file) = self.owner_public_identity_file {
+            owner_keys_file.clone()
         } else {
             current_dir()?.join(PUBLIC_KEYS_FILE)
         };
         let owner_identity = read_public_identity_file(owner_keys_file.as_path())?;
 
         // Load voter
-        let voter_identity = if let Some(voter_keys_file) = self.voter_public_identity_file {
+        let voter_identity = if let Some(ref voter_keys_file) = self.voter_public_identity_file {
             read_public_identity_file(voter_keys_file.as_path())?
         } else {
             owner_identity.clone()
@@ -158,10 +299,10 @@ impl CliCommand<()> for SetValidatorConfiguration {
 
         // Load operator
         let (operator_identity, operator_keys_file) =
-            if let Some(operator_keys_file) = self.operator_public_identity_file {
+            if let Some(ref operator_keys_file) = self.operator_public_identity_file {
                 (
                     read_public_identity_file(operator_keys_file.as_path())?,
-                    operator_keys_file,
+                    operator_keys_file.clone(),
                 )
             } else {
                 (owner_identity.clone(), owner_keys_file)
@@ -202,10 +343,19 @@ impl CliCommand<()> for SetValidatorConfiguration {
 
         // Only add the public key if there is a full node
         let full_node_network_public_key = if self.full_node_host.is_some() {
+            if operator_identity.full_node_network_public_key.is_none() {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Failed to read full node network public key from public identity file {}",
+                    operator_keys_file.display()
+                )));
+            }
             operator_identity.full_node_network_public_key
         } else {
             None
         };
+        if let Some(warning) = self.full_node_host_warning() {
+            eprintln!("Warning: {}", warning);
+        }
 
         // Build operator configuration file
         let operator_config = OperatorConfiguration {
@@ -214,9 +364,10 @@ impl CliCommand<()> for SetValidatorConfiguration {
             consensus_public_key,
             consensus_proof_of_possession,
             validator_network_public_key,
-            validator_host: self.validator_host,
+            validator_host: self.validator_host[0].clone(),
+            additional_validator_hosts: self.validator_host[1..].to_vec(),
             full_node_network_public_key,
-            full_node_host: self.full_node_host,
+            full_node_host: self.full_node_host.clone(),
         };
 
         let owner_config = OwnerConfiguration {
@@ -228,32 +379,193 @@ impl CliCommand<()> for SetValidatorConfiguration {
             operator_account_public_key: operator_identity.account_public_key,
             stake_amount: self.stake_amount,
             commission_percentage: self.commission_percentage,
+            chain_id: layout.map(|layout| layout.chain_id),
         };
 
+        Ok((operator_config, owner_config))
+    }
+
+    /// Warns of a fullnode host which is also a validator host, as the validator and its fullnode
+    /// can't both listen on it
+    pub(crate) fn full_node_host_warning(&self) -> Option<String> {
+        let full_node_host = self.full_node_host.as_ref()?;
+        self.validator_host
+            .iter()
+            .find(|host| host.host == full_node_host.host && host.port == full_node_host.port)
+            .map(|host| {
+                format!(
+                    "--full-node-host {}:{} is the same as a --validator-host",
+                    host.host, host.port
+                )
+            })
+    }
+
+    /// Checks the commission is a percentage, and the stake is within the layout's maximum if the
+    /// layout has been uploaded
+    ///
+    /// A stake below the layout's minimum is only warned of, as genesis leaves the validator out
+    /// unless it's generated with `--strict-min-stake`, and the stake may yet be raised.
+    fn check_stake(&self, layout: Option<&Layout>) -> CliTypedResult<()> {
+        if self.commission_percentage > 100 {
+            return Err(CliError::CommandArgumentError(format!(
+                "--commission-percentage {} is greater than 100",
+                self.commission_percentage
+            )));
+        }
+
+        if let Some(layout) = layout {
+            if self.stake_amount < layout.min_stake {
+                eprintln!(
+                    "Warning: --stake-amount {} is less than the layout's min_stake {}, so genesis \
+                     will leave the validator out, or reject it with --strict-min-stake",
+                    self.stake_amount, layout.min_stake
+                );
+            }
+            if self.stake_amount > layout.max_stake {
+                return Err(CliError::CommandArgumentError(format!(
+                    "--stake-amount {} is greater than the layout's max_stake {}",
+                    self.stake_amount, layout.max_stake
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the configurations to the user's directory in the repository
+    pub(crate) fn write_configurations(
+        &self,
+        operator_config: &OperatorConfiguration,
+        owner_config: &OwnerConfiguration,
+    ) -> CliTypedResult<()> {
+        let git_client = self.git_options.clone().get_client()?;
+        let _lock = git_client.lock()?;
+        let files = self.put_configurations(&git_client, operator_config, owner_config)?;
+        if self.commit {
+            git_client.commit(&files, &format!("add config for {}", self.username))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the configurations to the user's directory in the repository, without taking the
+    /// repository's lock, which the caller must hold.  Returns the files written.
+    pub(crate) fn put_configurations(
+        &self,
+        git_client: &Client,
+        operator_config: &OperatorConfiguration,
+        owner_config: &OwnerConfiguration,
+    ) -> CliTypedResult<Vec<PathBuf>> {
         let directory = PathBuf::from(&self.username);
         let operator_file = directory.join(OPERATOR_FILE);
         let owner_file = directory.join(OWNER_FILE);
+        git_client.put(operator_file.as_path(), operator_config)?;
+        git_client.put(owner_file.as_path(), owner_config)?;
+        let mut files = vec![operator_file, owner_file];
+        if let Some(ref owner_private_keys_file) = self.owner_private_keys_file {
+            let owner_private_key =
+                read_owner_private_key(owner_private_keys_file, &self.passphrase_options)?;
+            files.push(sign_config(git_client, &self.username, &owner_private_key)?);
+        }
+        Ok(files)
+    }
+
+    /// Summarizes the configuration set by the command, from the owner configuration it built
+    pub(crate) fn summary(
+        &self,
+        owner_config: &OwnerConfiguration,
+    ) -> ValidatorConfigurationSummary {
+        ValidatorConfigurationSummary {
+            username: self.username.clone(),
+            owner_account_address: owner_config.owner_account_address,
+            operator_account_address: owner_config.operator_account_address,
+            voter_account_address: owner_config.voter_account_address,
+            stake_amount: owner_config.stake_amount,
+            commission_percentage: owner_config.commission_percentage,
+            validator_hosts: self.validator_host.clone(),
+            full_node_host: self.full_node_host.clone(),
+        }
+    }
+}
+
+/// Checks every host resolves, and warns of any which doesn't accept a connection in time
+pub(crate) fn check_reachability<'a>(
+    hosts: impl IntoIterator<Item = &'a HostAndPort>,
+) -> CliTypedResult<()> {
+    let mut unresolved = Vec::new();
+    for host in hosts {
+        let name = format!("{}:{}", host.host, host.port);
+        let addresses = match (host.host.to_string(), host.port).to_socket_addrs() {
+            Ok(addresses) => addresses.collect::<Vec<_>>(),
+            Err(err) => {
+                unresolved.push(format!("{} ({})", name, err));
+                continue;
+            }
+        };
+        if addresses.is_empty() {
+            unresolved.push(format!("{} (no addresses)", name));
+            continue;
+        }
 
-        let git_client = self.git_options.get_client()?;
-        git_client.put(operator_file.as_path(), &operator_config)?;
-        git_client.put(owner_file.as_path(), &owner_config)
+        let mut errors = Vec::new();
+        for address in &addresses {
+            match TcpStream::connect_timeout(address, REACHABILITY_TIMEOUT) {
+                Ok(_) => break,
+                Err(err) => errors.push(format!("{}: {}", address, err)),
+            }
+        }
+        if errors.len() == addresses.len() {
+            eprintln!(
+                "Warning: {} resolves, but can't be connected to: {}",
+                name,
+                errors.join(", ")
+            );
+        }
+    }
+
+    if unresolved.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::CommandArgumentError(format!(
+            "Hosts failed to resolve: {}",
+            unresolved.join(", ")
+        )))
     }
 }
 
+/// Reads a public identity file of any supported version, upgraded to the current version
 pub fn read_public_identity_file(public_identity_file: &Path) -> CliTypedResult<PublicIdentity> {
     let bytes = read_from_file(public_identity_file)?;
-    from_yaml(&String::from_utf8(bytes).map_err(CliError::from)?)
+    let contents = String::from_utf8(bytes).map_err(CliError::from)?;
+    PublicIdentity::from_yaml(&contents).map_err(|err| {
+        CliError::UnableToReadFile(public_identity_file.display().to_string(), err.to_string())
+    })
 }
 
 /// Generate a Layout template file with empty values
 ///
 /// This will generate a layout template file for genesis with some default values.  To start a
-/// new chain, these defaults should be carefully thought through and chosen.
+/// new chain, these defaults should be carefully thought through and chosen.  The chain id, root
+/// key, users and whether it's a test network can be given to fill them in, and the rest are left
+/// as defaults.
 #[derive(Parser)]
 pub struct GenerateLayoutTemplate {
-    /// Path of the output layout template
+    /// Path of the output layout template, or `-` for stdout
     #[clap(long, parse(from_os_str), default_value = LAYOUT_FILE)]
     pub(crate) output_file: PathBuf,
+    /// Chain id of the network, as a number or a name such as testnet
+    #[clap(long)]
+    pub(crate) chain_id: Option<ChainId>,
+    /// Root public key of the network, hex encoded
+    #[clap(long, parse(try_from_str = Ed25519PublicKey::from_encoded_string))]
+    pub(crate) root_key: Option<Ed25519PublicKey>,
+    /// Users who will configure validators, may be given more than once
+    #[clap(long = "user")]
+    pub(crate) users: Vec<String>,
+    /// Path to a file of users, one per line, added after those given by `--user`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) users_file: Option<PathBuf>,
+    /// Whether the network is a test network: [true, false]
+    #[clap(long)]
+    pub(crate) is_test: Option<bool>,
 
     #[clap(flatten)]
     pub(crate) prompt_options: PromptOptions,
@@ -267,7 +579,25 @@ impl CliCommand<()> for GenerateLayoutTemplate {
 
     async fn execute(self) -> CliTypedResult<()> {
         check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
-        let layout = Layout::default();
+        let mut layout = Layout::default();
+        if let Some(chain_id) = self.chain_id {
+            layout.chain_id = chain_id;
+        }
+        layout.root_key = self.root_key;
+        layout.users = self.users;
+        if let Some(ref users_file) = self.users_file {
+            let users = String::from_utf8(read_from_file(users_file)?).map_err(CliError::from)?;
+            layout.users.extend(
+                users
+                    .lines()
+                    .map(str::trim)
+                    .filter(|user| !user.is_empty())
+                    .map(ToString::to_string),
+            );
+        }
+        if let Some(is_test) = self.is_test {
+            layout.is_test = is_test;
+        }
 
         write_to_user_only_file(
             self.output_file.as_path(),
@@ -0,0 +1,90 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult},
+    genesis::{
+        error::GenesisError,
+        git::{GitOptions, LAYOUT_FILE},
+        keys::read_public_identity_file,
+        redact::ensure_layout_not_redacted,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::Layout;
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Add a validator's user to the layout of an existing genesis repository
+///
+/// For networks which add validators over time, in place of rebuilding the repository with
+/// `SetupGit`.  The user is appended to the layout's users, keeping the others in their order
+/// and every other field of the layout as it is.  The new user can then run
+/// `SetValidatorConfiguration`.
+///
+/// Given the public identity file the user shared, their owner key is registered in the layout,
+/// which their configuration's signature is checked against by `GenerateGenesis
+/// --require-signed-configs`.
+#[derive(Parser)]
+pub struct AddValidatorToLayout {
+    /// Name of the user to add, which mustn't already be in the layout
+    #[clap(long)]
+    pub(crate) username: String,
+
+    /// Public identity file of the user, whose account key is registered as their owner key
+    #[clap(long, parse(from_os_str))]
+    pub(crate) owner_public_identity_file: Option<PathBuf>,
+
+    /// Commit the updated layout to the local git repository
+    ///
+    /// Committed as "add <username> to layout".  Writes to Github are always commits.
+    #[clap(long)]
+    pub(crate) commit: bool,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for AddValidatorToLayout {
+    fn command_name(&self) -> &'static str {
+        "AddValidatorToLayout"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        if self.username.is_empty() || self.username.contains('/') {
+            return Err(CliError::CommandArgumentError(format!(
+                "--username {:?} must be a non-empty name without a '/'",
+                self.username
+            )));
+        }
+
+        let owner_public_key = match self.owner_public_identity_file {
+            Some(ref file) => Some(read_public_identity_file(file)?.account_public_key),
+            None => None,
+        };
+
+        let client = self.git_options.get_client()?;
+        let _lock = client.lock()?;
+        let mut layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        ensure_layout_not_redacted(&layout)?;
+        if layout.users.contains(&self.username) {
+            return Err(GenesisError::DuplicateUser(self.username).into());
+        }
+        layout.users.push(self.username.clone());
+        if let Some(owner_public_key) = owner_public_key {
+            layout
+                .owner_public_keys
+                .insert(self.username.clone(), owner_public_key);
+        }
+        client.put(Path::new(LAYOUT_FILE), &layout)?;
+        if self.commit {
+            client.commit(
+                &[PathBuf::from(LAYOUT_FILE)],
+                &format!("add {} to layout", self.username),
+            )?;
+        }
+        Ok(layout.users)
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, OutputOptions, PromptOptions, RngArgs},
+        utils::{
+            check_if_file_exists, create_dir_if_not_exist, dir_default_to_current, write_to_file,
+            write_to_user_only_file,
+        },
+    },
+    genesis::{
+        balances::BalanceUnit,
+        dump::GenesisOutputFormat,
+        encrypt::PassphraseOptions,
+        faucet::FaucetOptions,
+        fixture::ROOT_KEY_FILE,
+        git::{to_yaml, GitOptions, SetupGit, LAYOUT_FILE},
+        keys::{check_key_files, write_key_files, SetValidatorConfiguration, PUBLIC_KEYS_FILE},
+        progress::ProgressReporter,
+        GenerateGenesis, GENESIS_FILE, MAX_BUFFERED_GENESIS_SIZE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_crypto::PrivateKey;
+use aptos_genesis::{
+    config::{HostAndPort, Layout},
+    keys::generate_key_objects,
+};
+use aptos_types::waypoint::Waypoint;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+use vm_genesis::AccountMap;
+
+/// Name of the directory in the output directory holding the genesis repository
+pub const LOCAL_REPOSITORY_DIR: &str = "repository";
+/// Port of the first validator, each of the others listening on the next port
+const LOCAL_VALIDATOR_PORT: u16 = 6180;
+
+/// Generate genesis for a local test network of validators, in one go
+///
+/// Generates the keys of every validator, sets up a repository with a test layout and the head
+/// framework, configures every validator on localhost with equal stake, and generates genesis
+/// from it, as validators of a real network would one by one.  The output directory holds the
+/// genesis and waypoint, the root key, a `validator-<index>` directory with each validator's keys
+/// and identities, and the repository genesis was generated from.  Test transactions can be
+/// funded from a faucet account given with `--faucet-account`.
+#[derive(Parser)]
+pub struct GenerateLocalTestnetGenesis {
+    /// Number of validators in the network
+    #[clap(long, default_value_t = 4)]
+    pub(crate) num_validators: u8,
+
+    /// Output directory for the network, defaults to the current directory
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) faucet_options: FaucetOptions,
+    #[clap(flatten)]
+    pub(crate) rng_args: RngArgs,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+/// A local test network generated by `GenerateLocalTestnetGenesis`
+#[derive(Clone, Debug, Serialize)]
+pub struct LocalTestnetGenesis {
+    pub waypoint: Waypoint,
+    pub genesis_file: PathBuf,
+    pub root_key_file: PathBuf,
+    /// Directories of the validators' keys, in validator index order
+    pub validator_dirs: Vec<PathBuf>,
+    /// The faucet account funded at genesis, which isn't a validator's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<AccountMap>,
+}
+
+#[async_trait]
+impl CliCommand<LocalTestnetGenesis> for GenerateLocalTestnetGenesis {
+    fn command_name(&self) -> &'static str {
+        "GenerateLocalTestnetGenesis"
+    }
+
+    async fn execute(self) -> CliTypedResult<LocalTestnetGenesis> {
+        if self.num_validators == 0 {
+            return Err(CliError::CommandArgumentError(
+                "--num-validators must be at least 1".to_string(),
+            ));
+        }
+        // Checked before anything is written, as genesis is generated last
+        self.faucet_options.faucet()?;
+        let output_dir = dir_default_to_current(self.output_dir.clone())?;
+        let root_key_file = output_dir.join(ROOT_KEY_FILE);
+        let layout_file = output_dir.join(LAYOUT_FILE);
+        check_if_file_exists(root_key_file.as_path(), self.prompt_options)?;
+        check_if_file_exists(layout_file.as_path(), self.prompt_options)?;
+        create_dir_if_not_exist(output_dir.as_path())?;
+
+        // Every key comes from the one generator, so a seed reproduces the whole network
+        let mut key_generator = self.rng_args.key_generator()?;
+        let root_key = key_generator.generate_ed25519_private_key();
+        write_to_user_only_file(
+            root_key_file.as_path(),
+            ROOT_KEY_FILE,
+            to_yaml(&root_key)?.as_bytes(),
+        )?;
+
+        let users: Vec<_> = (0..self.num_validators)
+            .map(|index| format!("validator-{}", index))
+            .collect();
+        let mut validator_dirs = Vec::new();
+        for user in &users {
+            let (validator_blob, vfn_blob, private_identity, public_identity) =
+                generate_key_objects(&mut key_generator)?;
+            let validator_dir = output_dir.join(user);
+            check_key_files(&validator_dir, false, self.prompt_options)?;
+            write_key_files(
+                &validator_dir,
+                None,
+                &public_identity,
+                &private_identity,
+                &validator_blob,
+                &vfn_blob,
+            )?;
+            validator_dirs.push(validator_dir);
+        }
+
+        let layout = Layout {
+            root_key: Some(root_key.public_key()),
+            users: users.clone(),
+            ..Layout::default()
+        };
+        write_to_file(&layout_file, LAYOUT_FILE, to_yaml(&layout)?.as_bytes())?;
+        let git_options = GitOptions {
+            local_repository_dir: Some(output_dir.join(LOCAL_REPOSITORY_DIR)),
+            ..GitOptions::default()
+        };
+        SetupGit {
+            git_options: git_options.clone(),
+            layout_file,
+            schema_validate: false,
+            framework_bundle: None,
+            init_git: false,
+            progress: ProgressReporter::default(),
+        }
+        .execute()
+        .await?;
+        git_options
+            .clone()
+            .get_client()?
+            .put_framework(cached_packages::head_release_bundle())?;
+
+        for (index, (user, validator_dir)) in users.iter().zip(&validator_dirs).enumerate() {
+            SetValidatorConfiguration {
+                username: user.clone(),
+                validator_host: vec![HostAndPort::local(LOCAL_VALIDATOR_PORT + index as u16)?],
+                full_node_host: None,
+                stake_amount: layout.min_stake,
+                commission_percentage: 0,
+                check_reachability: false,
+                commit: false,
+                owner_private_keys_file: None,
+                owner_public_identity_file: Some(validator_dir.join(PUBLIC_KEYS_FILE)),
+                operator_public_identity_file: None,
+                voter_public_identity_file: None,
+                passphrase_options: PassphraseOptions::default(),
+                output_options: OutputOptions::default(),
+                git_options: git_options.clone(),
+            }
+            .execute()
+            .await?;
+        }
+
+        let output = GenerateGenesis {
+            output_dir: Some(output_dir.clone()),
+            genesis_file_name: GENESIS_FILE.to_string(),
+            waypoint_file_name: WAYPOINT_FILE.to_string(),
+            mainnet: Some(false),
+            collections_file: None,
+            coins_file: None,
+            start_paused: false,
+            incremental: false,
+            root_private_key_file: None,
+            output_format: GenesisOutputFormat::Blob,
+            expected_total_supply: None,
+            expected_framework_hash: None,
+            balances_csv: None,
+            balances_files: vec![],
+            balance_unit: BalanceUnit::Octas,
+            resume: false,
+            force: false,
+            reproducibility_check: false,
+            require_signed_configs: false,
+            strict_min_stake: false,
+            manifest: false,
+            faucet_options: self.faucet_options.clone(),
+            timeout: None,
+            estimate_only: false,
+            progress: ProgressReporter::default(),
+            max_buffered_genesis_size: MAX_BUFFERED_GENESIS_SIZE,
+            output_options: OutputOptions::default(),
+            prompt_options: self.prompt_options,
+            git_options,
+        }
+        .execute()
+        .await?;
+
+        Ok(LocalTestnetGenesis {
+            waypoint: output.waypoint,
+            genesis_file: output_dir.join(GENESIS_FILE),
+            root_key_file,
+            validator_dirs,
+            faucet: output.faucet,
+        })
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::{dir_default_to_current, read_from_file, write_to_file},
+    },
+    genesis::{
+        git::{from_yaml, to_yaml, GitOptions, LAYOUT_FILE},
+        read_waypoint,
+        resume::output_file_name,
+        GENESIS_FILE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    HashValue, PrivateKey, Signature, SigningKey,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_genesis::config::Layout;
+use aptos_types::{chain_id::ChainId, waypoint::Waypoint};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File in the genesis output directory holding the signed manifest
+pub const MANIFEST_FILE: &str = "genesis_manifest.yaml";
+
+/// Identifies the genesis produced by a ceremony
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, CryptoHasher, BCSCryptoHash)]
+pub struct GenesisManifest {
+    pub chain_id: ChainId,
+    /// SHA3-256 hash of the genesis blob
+    pub genesis_hash: HashValue,
+    pub waypoint: Waypoint,
+}
+
+/// A genesis manifest signed by the layout's root key, so that node operators can check their
+/// genesis came from the ceremony
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedGenesisManifest {
+    pub manifest: GenesisManifest,
+    pub signature: Ed25519Signature,
+}
+
+impl SignedGenesisManifest {
+    pub fn sign(manifest: GenesisManifest, root_key: &Ed25519PrivateKey) -> SignedGenesisManifest {
+        let signature = root_key.sign(&manifest);
+        SignedGenesisManifest {
+            manifest,
+            signature,
+        }
+    }
+
+    /// Checks that the manifest was signed by `root_public_key`
+    pub fn verify(&self, root_public_key: &Ed25519PublicKey) -> CliTypedResult<()> {
+        self.signature
+            .verify(&self.manifest, root_public_key)
+            .map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Manifest is not signed by root key {}: {}",
+                    root_public_key, err
+                ))
+            })
+    }
+}
+
+/// Signs a manifest of the genesis blob and waypoint in `genesis_file` and `waypoint_file` with the
+/// root key, and writes it to `manifest_file`
+pub(crate) fn write_signed_manifest(
+    genesis_file: &Path,
+    waypoint_file: &Path,
+    manifest_file: &Path,
+    chain_id: ChainId,
+    root_key: &Ed25519PrivateKey,
+    root_public_key: Option<&Ed25519PublicKey>,
+) -> CliTypedResult<PathBuf> {
+    if root_public_key != Some(&root_key.public_key()) {
+        return Err(CliError::CommandArgumentError(
+            "Root private key does not match the layout's root_key".to_string(),
+        ));
+    }
+    let manifest = GenesisManifest {
+        chain_id,
+        genesis_hash: HashValue::sha3_256_of(&read_from_file(genesis_file)?),
+        waypoint: read_waypoint(waypoint_file)?,
+    };
+    write_to_file(
+        manifest_file,
+        &manifest_file.display().to_string(),
+        to_yaml(&SignedGenesisManifest::sign(manifest, root_key))?.as_bytes(),
+    )?;
+    Ok(manifest_file.to_path_buf())
+}
+
+/// Verify a genesis against the manifest signed by the ceremony's root key
+///
+/// Checks that the manifest in the genesis directory is signed by the `root_key` of the ceremony's
+/// layout, and that the genesis blob and waypoint next to it are the ones it lists.
+#[derive(Parser)]
+pub struct VerifyManifest {
+    /// Directory containing the genesis.blob, waypoint.txt and the signed manifest
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_dir: Option<PathBuf>,
+
+    /// Name of the genesis file in the directory, which also prefixes the
+    /// manifest's name when it isn't the default
+    #[clap(long, default_value = GENESIS_FILE)]
+    pub(crate) genesis_file_name: String,
+
+    /// Name of the waypoint file in the directory
+    #[clap(long, default_value = WAYPOINT_FILE)]
+    pub(crate) waypoint_file_name: String,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<GenesisManifest> for VerifyManifest {
+    fn command_name(&self) -> &'static str {
+        "VerifyManifest"
+    }
+
+    async fn execute(self) -> CliTypedResult<GenesisManifest> {
+        let genesis_dir = dir_default_to_current(self.genesis_dir)?;
+        let layout: Layout = self.git_options.get_client()?.get(Path::new(LAYOUT_FILE))?;
+        let root_public_key = layout.root_key.ok_or_else(|| {
+            CliError::CommandArgumentError(
+                "Layout has no root_key to check the manifest against".to_string(),
+            )
+        })?;
+        let signed: SignedGenesisManifest = from_yaml(&String::from_utf8(read_from_file(
+            &genesis_dir.join(output_file_name(&self.genesis_file_name, MANIFEST_FILE)),
+        )?)?)?;
+        signed.verify(&root_public_key)?;
+
+        let manifest = signed.manifest;
+        let genesis_hash =
+            HashValue::sha3_256_of(&read_from_file(&genesis_dir.join(&self.genesis_file_name))?);
+        if genesis_hash != manifest.genesis_hash {
+            return Err(CliError::UnexpectedError(format!(
+                "{} has hash {}, but the manifest lists {}",
+                self.genesis_file_name, genesis_hash, manifest.genesis_hash
+            )));
+        }
+        let waypoint = read_waypoint(&genesis_dir.join(&self.waypoint_file_name))?;
+        if waypoint != manifest.waypoint {
+            return Err(CliError::UnexpectedError(format!(
+                "{} is {}, but the manifest lists {}",
+                self.waypoint_file_name, waypoint, manifest.waypoint
+            )));
+        }
+        Ok(manifest)
+    }
+}
@@ -1,40 +1,113 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod balances;
+pub mod batch;
+pub mod bench;
+pub mod decode;
+pub mod diff;
+pub mod doctor;
+pub mod dump;
+pub mod encrypt;
+pub mod error;
+pub mod estimate;
+pub mod faucet;
+pub mod fixture;
 pub mod git;
+pub mod identity;
+pub mod import;
+pub mod incremental;
+pub mod interop;
 pub mod keys;
+pub mod layout;
+pub mod local;
+pub mod manifest;
+pub mod onboarding;
+pub mod progress;
+pub mod redact;
+pub mod resume;
+pub mod roster;
+pub mod rotate;
+pub mod schema;
+pub mod signature;
+pub mod simulate;
 #[cfg(test)]
 mod tests;
+pub mod timeout;
+pub mod upload;
+pub mod validate;
+pub mod validator_set;
+pub mod verify;
+pub mod vesting;
 
 use crate::common::utils::dir_default_to_current;
+use crate::genesis::balances::{read_balances_csv, read_balances_files, BalanceUnit};
+use crate::genesis::diff::check_reproducible;
+use crate::genesis::dump::{write_genesis_json, GenesisOutputFormat, GENESIS_JSON_FILE};
+use crate::genesis::error::{is_not_found, GenesisError};
+use crate::genesis::estimate::EstimateGenesisSize;
+use crate::genesis::faucet::{validate_faucet, FaucetOptions};
 use crate::genesis::git::{OPERATOR_FILE, OWNER_FILE};
+use crate::genesis::incremental::{ValidatedInputs, VALIDATED_INPUTS_FILE};
+use crate::genesis::interop::{InteropManifest, INTEROP_MANIFEST_FILE};
+use crate::genesis::manifest::{write_signed_manifest, MANIFEST_FILE};
+use crate::genesis::progress::{GenesisProgress, ProgressReporter};
+use crate::genesis::redact::ensure_layout_not_redacted;
+use crate::genesis::resume::{
+    inputs_file_name, output_file_name, read_inputs_hash, write_inputs_hash, GenesisInputs,
+};
+use crate::genesis::signature::verify_config_signatures;
+use crate::genesis::timeout::{run_with_timeout, OutputTracker};
 use crate::{
     common::{
-        types::{CliError, CliTypedResult, PromptOptions},
-        utils::{check_if_file_exists, write_to_file},
+        types::{
+            CliError, CliTypedResult, EncodingType, OutputFormat, OutputOptions, PromptOptions,
+        },
+        utils::{check_if_file_exists, read_from_file, write_bcs_to_file, write_to_file},
     },
     genesis::git::{
-        Client, GitOptions, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE, LAYOUT_FILE,
+        from_yaml, Client, GitOptions, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE, LAYOUT_FILE,
     },
     CliCommand, CliResult,
 };
-use aptos_crypto::{bls12381, ed25519::Ed25519PublicKey, x25519, ValidCryptoMaterialStringExt};
-use aptos_genesis::builder::GenesisConfiguration;
+use aptos_crypto::{
+    bls12381,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    x25519, HashValue, ValidCryptoMaterialStringExt,
+};
 use aptos_genesis::config::{StringOperatorConfiguration, StringOwnerConfiguration};
 use aptos_genesis::{
     config::{Layout, ValidatorConfiguration},
+    genesis_builder::GenesisBuilder,
     mainnet::MainnetGenesisInfo,
     GenesisInfo,
 };
-use aptos_types::account_address::AccountAddress;
+use aptos_types::{
+    account_address::AccountAddress, chain_id::ChainId, utility_coin::APTOS_COIN_TYPE,
+    waypoint::Waypoint,
+};
 use async_trait::async_trait;
 use clap::Parser;
+use move_deps::move_core_types::identifier::Identifier;
+use serde::Serialize;
 use std::path::Path;
-use std::{path::PathBuf, str::FromStr};
-use vm_genesis::{AccountMap, EmployeeAccountMap};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+use vm_genesis::{
+    AccountMap, EmployeeAccountMap, GenesisCoin, GenesisCollection, GENESIS_EPOCH,
+    MAX_TRANSACTION_SIZE_RANGE, NATIVE_COIN_NAME, NATIVE_COIN_SYMBOL,
+};
 
 const WAYPOINT_FILE: &str = "waypoint.txt";
 const GENESIS_FILE: &str = "genesis.blob";
+/// Default of `GenerateGenesis::max_buffered_genesis_size`
+const MAX_BUFFERED_GENESIS_SIZE: usize = 16 * 1024 * 1024;
 
 /// Tool for setting up an Aptos chain Genesis transaction
 ///
@@ -42,21 +115,74 @@ const GENESIS_FILE: &str = "genesis.blob";
 /// accounts to build a genesis transaction for a new chain.
 #[derive(Parser)]
 pub enum GenesisTool {
+    AddValidatorToLayout(layout::AddValidatorToLayout),
+    BenchGenesis(bench::BenchGenesis),
+    DecodeTransaction(decode::DecodeTransaction),
+    DecryptKeys(encrypt::DecryptKeys),
+    DiffGenesis(diff::DiffGenesis),
+    DiffNodeIdentity(identity::DiffNodeIdentity),
+    GenerateCiFixture(fixture::GenerateCiFixture),
     GenerateGenesis(GenerateGenesis),
     GenerateKeys(keys::GenerateKeys),
     GenerateLayoutTemplate(keys::GenerateLayoutTemplate),
+    GenerateLocalTestnetGenesis(local::GenerateLocalTestnetGenesis),
+    GenerateOnboardingSheets(onboarding::GenerateOnboardingSheets),
+    GenesisDoctor(doctor::GenesisDoctor),
+    ImportPublicIdentity(import::ImportPublicIdentity),
+    PrintSchema(schema::PrintSchema),
+    RedactLayout(redact::RedactLayout),
+    RotateValidatorKeys(rotate::RotateValidatorKeys),
     SetupGit(git::SetupGit),
     SetValidatorConfiguration(keys::SetValidatorConfiguration),
+    SetValidatorConfigurations(batch::SetValidatorConfigurations),
+    SetValidatorConfigurationsFromRoster(roster::SetValidatorConfigurationsFromRoster),
+    ShowGenesisValidators(validator_set::ShowGenesisValidators),
+    SimulateValidatorSet(simulate::SimulateValidatorSet),
+    UploadValidatorConfiguration(upload::UploadValidatorConfiguration),
+    ValidateValidatorConfiguration(validate::ValidateValidatorConfiguration),
+    VerifyGenesis(verify::VerifyGenesis),
+    VerifyManifest(manifest::VerifyManifest),
+    VerifyInteropManifest(interop::VerifyInteropManifest),
+    VerifyWaypoint(verify::VerifyWaypoint),
 }
 
 impl GenesisTool {
     pub async fn execute(self) -> CliResult {
         match self {
+            GenesisTool::AddValidatorToLayout(tool) => tool.execute_serialized().await,
+            GenesisTool::BenchGenesis(tool) => tool.execute_serialized().await,
+            GenesisTool::DecodeTransaction(tool) => tool.execute_serialized().await,
+            GenesisTool::DecryptKeys(tool) => tool.execute_serialized().await,
+            GenesisTool::DiffGenesis(tool) => tool.execute_serialized().await,
+            GenesisTool::DiffNodeIdentity(tool) => tool.execute_serialized_success().await,
+            GenesisTool::GenerateCiFixture(tool) => tool.execute_serialized().await,
+            GenesisTool::GenerateGenesis(tool) if tool.estimate_only => {
+                EstimateGenesisSize(tool).execute_serialized().await
+            }
             GenesisTool::GenerateGenesis(tool) => tool.execute_serialized().await,
             GenesisTool::GenerateKeys(tool) => tool.execute_serialized().await,
             GenesisTool::GenerateLayoutTemplate(tool) => tool.execute_serialized_success().await,
+            GenesisTool::GenerateLocalTestnetGenesis(tool) => tool.execute_serialized().await,
+            GenesisTool::GenerateOnboardingSheets(tool) => tool.execute_serialized().await,
+            GenesisTool::GenesisDoctor(tool) => tool.execute_serialized().await,
+            GenesisTool::ImportPublicIdentity(tool) => tool.execute_serialized().await,
+            GenesisTool::PrintSchema(tool) => tool.execute_serialized_success().await,
+            GenesisTool::RedactLayout(tool) => tool.execute_serialized_success().await,
+            GenesisTool::RotateValidatorKeys(tool) => tool.execute_serialized().await,
             GenesisTool::SetupGit(tool) => tool.execute_serialized_success().await,
-            GenesisTool::SetValidatorConfiguration(tool) => tool.execute_serialized_success().await,
+            GenesisTool::SetValidatorConfiguration(tool) => tool.execute_serialized().await,
+            GenesisTool::SetValidatorConfigurations(tool) => tool.execute_serialized().await,
+            GenesisTool::SetValidatorConfigurationsFromRoster(tool) => {
+                tool.execute_serialized().await
+            }
+            GenesisTool::ShowGenesisValidators(tool) => tool.execute_formatted().await,
+            GenesisTool::SimulateValidatorSet(tool) => tool.execute_serialized().await,
+            GenesisTool::UploadValidatorConfiguration(tool) => tool.execute_serialized().await,
+            GenesisTool::ValidateValidatorConfiguration(tool) => tool.execute_serialized().await,
+            GenesisTool::VerifyGenesis(tool) => tool.execute_serialized().await,
+            GenesisTool::VerifyManifest(tool) => tool.execute_serialized().await,
+            GenesisTool::VerifyInteropManifest(tool) => tool.execute_serialized().await,
+            GenesisTool::VerifyWaypoint(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -67,165 +193,1133 @@ pub struct GenerateGenesis {
     /// Output directory for Genesis file and waypoint
     #[clap(long, parse(from_os_str))]
     output_dir: Option<PathBuf>,
+    /// Name of the genesis file written to the output directory
+    ///
+    /// Genesis for several networks can be kept side by side in one directory by giving each its
+    /// own names.  Its inputs are recorded next to it for `--resume`, under a name derived from it,
+    /// and its other outputs are prefixed with its stem, e.g. `devnet.genesis.json` for
+    /// `devnet.blob`.
+    #[clap(long, default_value = GENESIS_FILE)]
+    genesis_file_name: String,
+    /// Name of the waypoint file written to the output directory
+    #[clap(long, default_value = WAYPOINT_FILE)]
+    waypoint_file_name: String,
     /// Whether this is mainnet genesis.
     #[clap(long)]
     mainnet: Option<bool>,
+    /// Path to a YAML descriptor of token collections, and their tokens, to create at genesis
+    ///
+    /// Every collection must be created by an account configured for genesis
+    #[clap(long, parse(from_os_str))]
+    collections_file: Option<PathBuf>,
+    /// Path to a YAML descriptor of coins, other than the native coin, to create at genesis
+    ///
+    /// Every coin is published under its issuer's address and minted to its holders, which must
+    /// all be accounts configured for genesis
+    #[clap(long, parse(from_os_str))]
+    coins_file: Option<PathBuf>,
+    /// Start the chain paused, so that only validator accounts can submit transactions
+    ///
+    /// The chain is opened to every account once a governance proposal calls
+    /// `0x1::chain_status::activate`.  Validators still form consensus while paused.
+    #[clap(long)]
+    start_paused: bool,
+    /// Reuse the validator configurations validated by the previous genesis in the output directory
+    ///
+    /// Only the configurations which changed since then are validated again, so that genesis can
+    /// be regenerated quickly as validators join a staged ceremony
+    #[clap(long)]
+    incremental: bool,
+    /// File with the hex encoded private key of the layout's `root_key`
+    ///
+    /// If given, a manifest listing the genesis hash, waypoint and chain id is signed with it and
+    /// written alongside genesis, for node operators to check with `VerifyManifest`
+    #[clap(long, parse(from_os_str))]
+    root_private_key_file: Option<PathBuf>,
+    /// Format to write genesis in: [blob, json]
+    ///
+    /// With json, a genesis.json decoding the validator set, balances, chain id and modules of
+    /// the genesis.blob is written alongside it
+    #[clap(long, default_value_t = GenesisOutputFormat::Blob)]
+    output_format: GenesisOutputFormat,
+    /// Total supply the mainnet balances must add up to
+    ///
+    /// The balances include the employee vesting allocations, which are staked from them.  If
+    /// they don't add up to it, genesis fails before it's written, reporting the difference.
+    #[clap(long)]
+    expected_total_supply: Option<u64>,
+    /// SHA3-256 hash the repository's framework bundle must have, in hex
+    ///
+    /// Genesis fails before it's generated if the framework differs.  The framework's hash is
+    /// printed whether or not this is given, so it can be shared with the other participants.
+    #[clap(long)]
+    expected_framework_hash: Option<HashValue>,
+    /// Path to a CSV of the mainnet balances, to use in place of the repository's balances file
+    ///
+    /// The CSV has an `account_address,balance` header, and a row for each account.  It's read
+    /// from stdin if the path is `-`.
+    #[clap(long, parse(from_os_str))]
+    balances_csv: Option<PathBuf>,
+    /// Path to a file of mainnet balances, to merge with the others in place of the repository's
+    /// balances file
+    ///
+    /// May be given more than once, such as for allocations maintained by different teams.  Files
+    /// ending in `.csv` are read like `--balances-csv`, and others as YAML like the balances file.
+    /// An account may only be in one of the files.
+    #[clap(long = "balances-file", parse(from_os_str))]
+    balances_files: Vec<PathBuf>,
+    /// Unit of the balances in `--balances-csv` and `--balances-file`: [apt, octas]
+    ///
+    /// Balances in APT may have up to 8 decimal places in a CSV, and are whole numbers in YAML.
+    /// They're converted to octas for genesis, and the repository's balances file is always in
+    /// octas.
+    #[clap(long, default_value_t = BalanceUnit::Octas)]
+    balance_unit: BalanceUnit,
+    /// Keep the genesis already in the output directory if its inputs are unchanged
+    ///
+    /// The inputs are compared by the hash recorded alongside genesis when it was generated.  If
+    /// they changed, genesis is generated again.
+    #[clap(long)]
+    resume: bool,
+    /// Overwrite the genesis already in the output directory
+    #[clap(long)]
+    force: bool,
+    /// Generate genesis a second time from the same inputs, and fail if the blobs differ
+    ///
+    /// This catches nondeterminism in genesis, naming the sections of it which diverged
+    #[clap(long)]
+    reproducibility_check: bool,
+    /// Reject validator configurations which aren't signed by their owner's account key
+    ///
+    /// Configurations are signed with `SetValidatorConfiguration --owner-private-keys-file`
+    #[clap(long)]
+    require_signed_configs: bool,
+    /// Reject validators whose stake is below the layout's min_stake
+    ///
+    /// Otherwise they're left out of genesis with a warning, as a pool below the minimum stake
+    /// can't join the validator set on chain
+    #[clap(long)]
+    strict_min_stake: bool,
+    /// Write a portable JSON manifest of genesis alongside it, for external tooling
+    ///
+    /// The manifest bundles the chain id, waypoint, framework hash, validator set, total supply
+    /// and SHA-256 of the genesis blob, and is checked against a genesis with
+    /// `VerifyInteropManifest`
+    #[clap(long)]
+    manifest: bool,
+    /// Fail if genesis takes longer than this many seconds, removing the outputs written so far
+    ///
+    /// The error names the phase genesis was in when it timed out, such as generating genesis
+    #[clap(long)]
+    timeout: Option<u64>,
+    /// Print an estimate of the size of genesis, and exit without generating or writing it
+    ///
+    /// Estimated from the encoded framework, validator configurations and balances, for planning
+    /// the storage and bandwidth to distribute genesis with
+    #[clap(long)]
+    estimate_only: bool,
+    /// Receives the progress of genesis, which is otherwise printed to stderr
+    #[clap(skip)]
+    progress: ProgressReporter,
+    /// Largest genesis serialized in memory before being written, larger ones are streamed
+    #[clap(skip = MAX_BUFFERED_GENESIS_SIZE)]
+    max_buffered_genesis_size: usize,
 
+    #[clap(flatten)]
+    faucet_options: FaucetOptions,
+    #[clap(flatten)]
+    output_options: OutputOptions,
     #[clap(flatten)]
     prompt_options: PromptOptions,
     #[clap(flatten)]
     git_options: GitOptions,
 }
 
+/// Genesis generated by `GenerateGenesis`, or kept by it when resuming
+#[derive(Clone, Debug, Serialize)]
+pub struct GenerateGenesisOutput {
+    pub waypoint: Waypoint,
+    /// Files written to the output directory, starting with genesis and its waypoint
+    pub files: Vec<PathBuf>,
+    /// Number of validators in genesis
+    pub validators: usize,
+    /// Stake of every validator in genesis, in octas
+    pub total_stake: u128,
+    /// The faucet account funded at genesis, which isn't a validator's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<AccountMap>,
+    /// Validators left out of genesis for a stake below the layout's min_stake
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_validators: Vec<ExcludedValidator>,
+}
+
+/// A validator left out of genesis, as its stake is below the layout's min_stake
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ExcludedValidator {
+    pub username: String,
+    pub owner_account_address: AccountAddress,
+    pub stake_amount: u64,
+}
+
+impl Display for ExcludedValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Validator {} of {} has stake_amount {}, less than the layout's min_stake, leaving it \
+             out of genesis",
+            self.owner_account_address, self.username, self.stake_amount
+        )
+    }
+}
+
 #[async_trait]
-impl CliCommand<Vec<PathBuf>> for GenerateGenesis {
+impl CliCommand<GenerateGenesisOutput> for GenerateGenesis {
     fn command_name(&self) -> &'static str {
         "GenerateGenesis"
     }
 
-    async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
+    fn output_format(&self) -> OutputFormat {
+        self.output_options.output_format
+    }
+
+    async fn execute(self) -> CliTypedResult<GenerateGenesisOutput> {
+        let timeout = match self.timeout {
+            None => return self.generate(&OutputTracker::default()),
+            Some(0) => {
+                return Err(CliError::CommandArgumentError(
+                    "--timeout must be at least 1 second".to_string(),
+                ))
+            }
+            Some(secs) => Duration::from_secs(secs),
+        };
+        let progress = self.progress.clone();
+        let outputs = OutputTracker::default();
+        let tracked = outputs.clone();
+        run_with_timeout(timeout, progress, outputs, move || self.generate(&tracked)).await
+    }
+}
+
+impl GenerateGenesis {
+    /// Generates genesis, writing every output through `outputs`
+    fn generate(self, outputs: &OutputTracker) -> CliTypedResult<GenerateGenesisOutput> {
         let output_dir = dir_default_to_current(self.output_dir.clone())?;
-        let genesis_file = output_dir.join(GENESIS_FILE);
-        let waypoint_file = output_dir.join(WAYPOINT_FILE);
-        check_if_file_exists(genesis_file.as_path(), self.prompt_options)?;
-        check_if_file_exists(waypoint_file.as_path(), self.prompt_options)?;
-
-        // Generate genesis and waypoint files
-        let (genesis_bytes, waypoint) = if self.mainnet.unwrap_or_default() {
-            let mut mainnet_genesis = fetch_mainnet_genesis_info(self.git_options)?;
-            let genesis_bytes = bcs::to_bytes(mainnet_genesis.clone().get_genesis())
+        for (option, name) in [
+            ("--genesis-file-name", &self.genesis_file_name),
+            ("--waypoint-file-name", &self.waypoint_file_name),
+        ] {
+            if Path::new(name).file_name() != Some(OsStr::new(name)) {
+                return Err(CliError::CommandArgumentError(format!(
+                    "{} must be the name of a file in the output directory, but is {}",
+                    option, name
+                )));
+            }
+        }
+        if self.genesis_file_name == self.waypoint_file_name {
+            return Err(CliError::CommandArgumentError(
+                "--genesis-file-name and --waypoint-file-name must differ".to_string(),
+            ));
+        }
+        let genesis_file = output_dir.join(&self.genesis_file_name);
+        let waypoint_file = output_dir.join(&self.waypoint_file_name);
+        let inputs_file = output_dir.join(inputs_file_name(&self.genesis_file_name));
+        // The other outputs are named after the genesis file, so genesis files side by side each
+        // keep their own
+        let output_file =
+            |file_name| output_dir.join(output_file_name(&self.genesis_file_name, file_name));
+        let genesis_json_file = output_file(GENESIS_JSON_FILE);
+        let interop_manifest_file = output_file(INTEROP_MANIFEST_FILE);
+        let manifest_file = output_file(MANIFEST_FILE);
+        let validated_inputs_file = output_file(VALIDATED_INPUTS_FILE);
+        if self.resume && self.force {
+            return Err(CliError::CommandArgumentError(
+                "Must provide only one of --resume and --force".to_string(),
+            ));
+        }
+        if (genesis_file.exists() || waypoint_file.exists()) && !self.resume && !self.force {
+            return Err(CliError::CommandArgumentError(format!(
+                "Genesis already exists in {}, use --resume to keep it if its inputs are \
+                 unchanged, or --force to overwrite it",
+                output_dir.display()
+            )));
+        }
+        if self.output_format == GenesisOutputFormat::Json {
+            check_if_file_exists(&genesis_json_file, self.prompt_options)?;
+        }
+        if self.manifest {
+            check_if_file_exists(&interop_manifest_file, self.prompt_options)?;
+        }
+        let mainnet = self.mainnet.unwrap_or_default();
+        if self.expected_total_supply.is_some() && !mainnet {
+            return Err(CliError::CommandArgumentError(
+                "--expected-total-supply only applies to mainnet genesis".to_string(),
+            ));
+        }
+        let balances = self.read_balances(mainnet)?;
+        let faucet = self.faucet_options.faucet()?;
+        let root_key = if let Some(ref root_private_key_file) = self.root_private_key_file {
+            let root_key: Ed25519PrivateKey =
+                EncodingType::Hex.load_key("--root-private-key-file", root_private_key_file)?;
+            let layout: Layout = self
+                .git_options
+                .clone()
+                .get_client()?
+                .get(Path::new(LAYOUT_FILE))?;
+            check_if_file_exists(&manifest_file, self.prompt_options)?;
+            Some((root_key, layout))
+        } else {
+            None
+        };
+
+        let collections = if let Some(ref collections_file) = self.collections_file {
+            from_yaml(&String::from_utf8(read_from_file(collections_file)?)?)?
+        } else {
+            vec![]
+        };
+        let coins = if let Some(ref coins_file) = self.coins_file {
+            from_yaml(&String::from_utf8(read_from_file(coins_file)?)?)?
+        } else {
+            vec![]
+        };
+        let inputs = GenesisInputs {
+            mainnet,
+            collections: collections.clone(),
+            coins: coins.clone(),
+            start_paused: self.start_paused,
+            output_format: self.output_format.to_string(),
+            signed: root_key.is_some(),
+            balances: balances.clone(),
+            faucet: faucet.clone(),
+        };
+        let client = self.git_options.clone().get_client()?;
+        let progress = self
+            .progress
+            .clone()
+            .for_output(self.output_options.output_format);
+        let framework_hash = client.framework_hash()?;
+        if let Some(expected_framework_hash) = self.expected_framework_hash {
+            if framework_hash != expected_framework_hash {
+                return Err(GenesisError::FrameworkHashMismatch {
+                    hash: framework_hash,
+                    expected: expected_framework_hash,
+                }
+                .into());
+            }
+        }
+        progress.report(GenesisProgress::LoadedFramework {
+            hash: framework_hash,
+        });
+        if self.require_signed_configs {
+            verify_config_signatures(&client, &client.get(Path::new(LAYOUT_FILE))?)?;
+        }
+        if self.resume && genesis_file.exists() && waypoint_file.exists() {
+            let inputs_hash = inputs.hash(&client)?;
+            if read_inputs_hash(&inputs_file)? == Some(inputs_hash) {
+                eprintln!(
+                    "Inputs are unchanged since genesis was generated in {}, keeping it",
+                    output_dir.display()
+                );
+                // The inputs are unchanged, so are the validators in the kept genesis
+                let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+                let (validators, excluded_validators) = validate_stakes(
+                    &layout,
+                    validator_configs(&client, &layout, None)?,
+                    self.strict_min_stake,
+                )?;
+                return Ok(GenerateGenesisOutput {
+                    waypoint: read_waypoint(&waypoint_file)?,
+                    files: vec![genesis_file, waypoint_file],
+                    validators: validators.len(),
+                    total_stake: validators
+                        .iter()
+                        .map(|validator| validator.stake_amount as u128)
+                        .sum(),
+                    faucet,
+                    excluded_validators,
+                });
+            }
+        }
+
+        let mut validated = if self.incremental {
+            Some(ValidatedInputs::load(&validated_inputs_file)?)
+        } else {
+            None
+        };
+
+        // Generate genesis and waypoint files.  Genesis is borrowed from whichever info generated
+        // it, rather than copied, as it can be large.
+        let mut mainnet_genesis;
+        let mut test_genesis;
+        let excluded_validators;
+        let (waypoint, genesis, genesis_size, validators, total_stake) = if mainnet {
+            (mainnet_genesis, excluded_validators) = fetch_mainnet_genesis_info(
+                self.git_options,
+                collections,
+                coins,
+                self.start_paused,
+                validated.as_mut(),
+                balances,
+                self.expected_total_supply,
+                self.strict_min_stake,
+                faucet.clone(),
+                &progress,
+            )?;
+            progress.report(GenesisProgress::LoadedValidatorConfigs {
+                validators: mainnet_genesis.num_validators(),
+            });
+            // Copied before genesis is generated, so that the check generates it again
+            let mut regenerated = self.reproducibility_check.then(|| mainnet_genesis.clone());
+            let genesis_size = bcs::serialized_size(mainnet_genesis.get_genesis())
                 .map_err(|e| CliError::BCS(GENESIS_FILE, e))?;
-            (genesis_bytes, mainnet_genesis.generate_waypoint()?)
+            if let Some(regenerated) = regenerated.as_mut() {
+                let genesis_bytes = bcs::to_bytes(mainnet_genesis.get_genesis())
+                    .map_err(|e| CliError::BCS(GENESIS_FILE, e))?;
+                check_reproducible(&genesis_bytes, regenerated.get_genesis())?;
+            }
+            progress.report(GenesisProgress::GeneratedGenesis {
+                bytes: genesis_size,
+            });
+            let waypoint = mainnet_genesis.generate_waypoint()?;
+            let validators = mainnet_genesis.num_validators();
+            let total_stake = mainnet_genesis.total_stake();
+            (
+                waypoint,
+                mainnet_genesis.get_genesis(),
+                genesis_size,
+                validators,
+                total_stake,
+            )
         } else {
-            let mut test_genesis = fetch_genesis_info(self.git_options)?;
-            let genesis_bytes = bcs::to_bytes(test_genesis.clone().get_genesis())
+            (test_genesis, excluded_validators) = fetch_genesis_info(
+                self.git_options,
+                collections,
+                coins,
+                self.start_paused,
+                validated.as_mut(),
+                self.strict_min_stake,
+                faucet.clone(),
+                &progress,
+            )?;
+            progress.report(GenesisProgress::LoadedValidatorConfigs {
+                validators: test_genesis.num_validators(),
+            });
+            // Copied before genesis is generated, so that the check generates it again
+            let mut regenerated = self.reproducibility_check.then(|| test_genesis.clone());
+            let genesis_size = bcs::serialized_size(test_genesis.get_genesis())
                 .map_err(|e| CliError::BCS(GENESIS_FILE, e))?;
-            (genesis_bytes, test_genesis.generate_waypoint()?)
+            if let Some(regenerated) = regenerated.as_mut() {
+                let genesis_bytes = bcs::to_bytes(test_genesis.get_genesis())
+                    .map_err(|e| CliError::BCS(GENESIS_FILE, e))?;
+                check_reproducible(&genesis_bytes, regenerated.get_genesis())?;
+            }
+            progress.report(GenesisProgress::GeneratedGenesis {
+                bytes: genesis_size,
+            });
+            let waypoint = test_genesis.generate_waypoint()?;
+            let validators = test_genesis.num_validators();
+            let total_stake = test_genesis.total_stake();
+            (
+                waypoint,
+                test_genesis.get_genesis(),
+                genesis_size,
+                validators,
+                total_stake,
+            )
         };
-        write_to_file(genesis_file.as_path(), GENESIS_FILE, &genesis_bytes)?;
-        write_to_file(
-            waypoint_file.as_path(),
-            WAYPOINT_FILE,
-            waypoint.to_string().as_bytes(),
-        )?;
-        Ok(vec![genesis_file, waypoint_file])
+        progress.report(GenesisProgress::ComputedWaypoint { waypoint });
+        // Large genesis is streamed into its file, rather than held in memory a second time
+        let genesis_bytes = if genesis_size <= self.max_buffered_genesis_size {
+            Some(bcs::to_bytes(genesis).map_err(|e| CliError::BCS(GENESIS_FILE, e))?)
+        } else {
+            None
+        };
+        outputs.write(&genesis_file, || match &genesis_bytes {
+            Some(genesis_bytes) => {
+                write_to_file(&genesis_file, &self.genesis_file_name, genesis_bytes)
+            }
+            None => write_bcs_to_file(&genesis_file, &self.genesis_file_name, genesis),
+        })?;
+        outputs.write(&waypoint_file, || {
+            write_to_file(
+                &waypoint_file,
+                &self.waypoint_file_name,
+                waypoint.to_string().as_bytes(),
+            )
+        })?;
+        let inputs_hash = inputs.hash(&client)?;
+        outputs.write(&inputs_file, || {
+            write_inputs_hash(&inputs_file, inputs_hash)
+        })?;
+        let mut files = vec![genesis_file.clone(), waypoint_file.clone(), inputs_file];
+        if self.output_format == GenesisOutputFormat::Json {
+            files.push(outputs.write(&genesis_json_file, || {
+                write_genesis_json(
+                    &genesis_json_file,
+                    genesis,
+                    faucet.as_ref().map(|faucet| faucet.account_address),
+                )
+            })?);
+        }
+        if self.manifest {
+            let genesis_bytes = match genesis_bytes {
+                Some(genesis_bytes) => genesis_bytes,
+                None => bcs::to_bytes(genesis).map_err(|e| CliError::BCS(GENESIS_FILE, e))?,
+            };
+            let manifest = InteropManifest::new(&genesis_bytes, waypoint, framework_hash)?;
+            files.push(outputs.write(&interop_manifest_file, || {
+                manifest.write(&interop_manifest_file)
+            })?);
+        }
+        if let Some(validated) = validated {
+            outputs.write(&validated_inputs_file, || {
+                validated.save(&validated_inputs_file)
+            })?;
+            files.push(validated_inputs_file);
+        }
+        if let Some((root_key, layout)) = root_key {
+            files.push(outputs.write(&manifest_file, || {
+                write_signed_manifest(
+                    &genesis_file,
+                    &waypoint_file,
+                    &manifest_file,
+                    layout.chain_id,
+                    &root_key,
+                    layout.root_key.as_ref(),
+                )
+            })?);
+        }
+        progress.report(GenesisProgress::WroteOutputs { files: files.len() });
+        Ok(GenerateGenesisOutput {
+            waypoint,
+            files,
+            validators,
+            total_stake,
+            faucet,
+            excluded_validators,
+        })
+    }
+
+    /// Reads the mainnet balances given by `--balances-csv` or `--balances-file`, if any, in place
+    /// of the repository's balances file
+    fn read_balances(&self, mainnet: bool) -> CliTypedResult<Option<Vec<AccountMap>>> {
+        if self.balances_csv.is_some() && !mainnet {
+            return Err(CliError::CommandArgumentError(
+                "--balances-csv only applies to mainnet genesis".to_string(),
+            ));
+        }
+        if !self.balances_files.is_empty() && !mainnet {
+            return Err(CliError::CommandArgumentError(
+                "--balances-file only applies to mainnet genesis".to_string(),
+            ));
+        }
+        if self.balances_csv.is_some() && !self.balances_files.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "Must provide only one of --balances-csv and --balances-file".to_string(),
+            ));
+        }
+        if self.balances_files.is_empty() {
+            self.balances_csv
+                .as_deref()
+                .map(|path| read_balances_csv(path, self.balance_unit))
+                .transpose()
+        } else {
+            Ok(Some(read_balances_files(
+                &self.balances_files,
+                self.balance_unit,
+            )?))
+        }
     }
 }
 
 /// Retrieves all information for mainnet genesis from the Git repository
-pub fn fetch_mainnet_genesis_info(git_options: GitOptions) -> CliTypedResult<MainnetGenesisInfo> {
+///
+/// The balances are read from the repository's balances file, unless they're given.  The
+/// validators left out of genesis are returned along with it, and warned of through `progress`.
+pub fn fetch_mainnet_genesis_info(
+    git_options: GitOptions,
+    collections: Vec<GenesisCollection>,
+    coins: Vec<GenesisCoin>,
+    start_paused: bool,
+    validated: Option<&mut ValidatedInputs>,
+    balances: Option<Vec<AccountMap>>,
+    expected_total_supply: Option<u64>,
+    strict_min_stake: bool,
+    faucet: Option<AccountMap>,
+    progress: &ProgressReporter,
+) -> CliTypedResult<(MainnetGenesisInfo, Vec<ExcludedValidator>)> {
     let client = git_options.get_client()?;
+    let accounts: Vec<AccountMap> = match balances {
+        Some(balances) => balances,
+        None => client.get(Path::new(BALANCES_FILE))?,
+    };
+    if let Some(expected_total_supply) = expected_total_supply {
+        // The faucet's balance is part of the supply, like any other
+        let balances: Vec<_> = accounts.iter().chain(&faucet).cloned().collect();
+        validate_total_supply(&balances, expected_total_supply)?;
+    }
+    mainnet_genesis_info(
+        &client,
+        accounts,
+        collections,
+        coins,
+        start_paused,
+        validated,
+        strict_min_stake,
+        faucet,
+        progress,
+    )
+}
+
+/// Builds mainnet genesis from the Git repository, with the given initial account balances
+///
+/// The faucet, if any, is created along with the accounts, and mustn't be one of them.  The
+/// validators left out of genesis are returned along with it, as by `fetch_genesis_info`.
+pub fn mainnet_genesis_info(
+    client: &Client,
+    accounts: Vec<AccountMap>,
+    collections: Vec<GenesisCollection>,
+    coins: Vec<GenesisCoin>,
+    start_paused: bool,
+    validated: Option<&mut ValidatedInputs>,
+    strict_min_stake: bool,
+    faucet: Option<AccountMap>,
+    progress: &ProgressReporter,
+) -> CliTypedResult<(MainnetGenesisInfo, Vec<ExcludedValidator>)> {
     let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+    ensure_layout_not_redacted(&layout)?;
+
+    validate_scheduled_features(&layout)?;
+    validate_max_transaction_size(&layout)?;
 
-    let accounts: Vec<AccountMap> = client.get(Path::new(BALANCES_FILE))?;
     let employee_vesting_accounts: Vec<EmployeeAccountMap> =
         client.get(Path::new(EMPLOYEE_VESTING_ACCOUNTS_FILE))?;
-    let validators = get_validator_configs(&client, &layout).map_err(parse_error)?;
+    validate_employee_vesting(
+        &employee_vesting_accounts,
+        &accounts,
+        layout.employee_vesting_pool_size,
+    )?;
+    check_configs_submitted(client, &layout)?;
+    let (validators, excluded_validators) = validate_stakes(
+        &layout,
+        validator_configs(client, &layout, validated)?,
+        strict_min_stake,
+    )?;
+    for excluded in &excluded_validators {
+        progress.report(GenesisProgress::Warning {
+            message: excluded.to_string(),
+        });
+    }
+    for message in stake_concentration_warnings(&validators) {
+        progress.report(GenesisProgress::Warning { message });
+    }
+    let mut configured_accounts = validator_accounts(&validators)
+        .chain(accounts.iter().map(|account| account.account_address))
+        .collect();
+    validate_faucet(faucet.as_ref(), &configured_accounts)?;
+    configured_accounts.extend(faucet.iter().map(|faucet| faucet.account_address));
+    validate_collections(&collections, &configured_accounts)?;
+    validate_coins(&coins, &configured_accounts)?;
     let framework = client.get_framework()?;
-    Ok(MainnetGenesisInfo::new(
-        layout.chain_id,
-        accounts,
-        employee_vesting_accounts,
-        validators,
-        framework,
-        &GenesisConfiguration {
-            allow_new_validators: true,
-            epoch_duration_secs: layout.epoch_duration_secs,
-            is_test: false,
-            min_stake: layout.min_stake,
-            min_voting_threshold: layout.min_voting_threshold,
-            max_stake: layout.max_stake,
-            recurring_lockup_duration_secs: layout.recurring_lockup_duration_secs,
-            required_proposer_stake: layout.required_proposer_stake,
-            rewards_apy_percentage: layout.rewards_apy_percentage,
-            voting_duration_secs: layout.voting_duration_secs,
-            voting_power_increase_limit: layout.voting_power_increase_limit,
-        },
-    )?)
+    let genesis_info = GenesisBuilder::new(layout, validators, framework)
+        .with_accounts(accounts)
+        .with_employee_vesting_accounts(employee_vesting_accounts)
+        .with_collections(collections)
+        .with_coins(coins)
+        .with_faucet(faucet)
+        .with_start_paused(start_paused)
+        .into_mainnet_genesis_info()?;
+    Ok((genesis_info, excluded_validators))
 }
 
 /// Retrieves all information for genesis from the Git repository
-pub fn fetch_genesis_info(git_options: GitOptions) -> CliTypedResult<GenesisInfo> {
+///
+/// The validators left out of genesis for a stake below the minimum are returned along with it,
+/// and they and any validator holding too much of the stake are warned of through `progress`.
+pub fn fetch_genesis_info(
+    git_options: GitOptions,
+    collections: Vec<GenesisCollection>,
+    coins: Vec<GenesisCoin>,
+    start_paused: bool,
+    validated: Option<&mut ValidatedInputs>,
+    strict_min_stake: bool,
+    faucet: Option<AccountMap>,
+    progress: &ProgressReporter,
+) -> CliTypedResult<(GenesisInfo, Vec<ExcludedValidator>)> {
     let client = git_options.get_client()?;
     let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+    ensure_layout_not_redacted(&layout)?;
 
     if layout.root_key.is_none() {
-        return Err(CliError::UnexpectedError(
-            "Layout field root_key was not set.  Please provide a hex encoded Ed25519PublicKey."
-                .to_string(),
-        ));
+        return Err(GenesisError::LayoutInvalid(
+            "root_key was not set.  Please provide a hex encoded Ed25519PublicKey.".to_string(),
+        )
+        .into());
     }
 
-    let validators = get_validator_configs(&client, &layout).map_err(parse_error)?;
+    validate_scheduled_features(&layout)?;
+    validate_max_transaction_size(&layout)?;
+
+    check_configs_submitted(&client, &layout)?;
+    let (validators, excluded_validators) = validate_stakes(
+        &layout,
+        validator_configs(&client, &layout, validated)?,
+        strict_min_stake,
+    )?;
+    for excluded in &excluded_validators {
+        progress.report(GenesisProgress::Warning {
+            message: excluded.to_string(),
+        });
+    }
+    for message in stake_concentration_warnings(&validators) {
+        progress.report(GenesisProgress::Warning { message });
+    }
+    let mut configured_accounts = validator_accounts(&validators).collect();
+    validate_faucet(faucet.as_ref(), &configured_accounts)?;
+    configured_accounts.extend(faucet.iter().map(|faucet| faucet.account_address));
+    validate_collections(&collections, &configured_accounts)?;
+    validate_coins(&coins, &configured_accounts)?;
     let framework = client.get_framework()?;
-    Ok(GenesisInfo::new(
-        layout.chain_id,
-        layout.root_key.unwrap(),
-        validators,
-        framework,
-        &GenesisConfiguration {
-            allow_new_validators: layout.allow_new_validators,
-            epoch_duration_secs: layout.epoch_duration_secs,
-            is_test: layout.is_test,
-            min_stake: layout.min_stake,
-            min_voting_threshold: layout.min_voting_threshold,
-            max_stake: layout.max_stake,
-            recurring_lockup_duration_secs: layout.recurring_lockup_duration_secs,
-            required_proposer_stake: layout.required_proposer_stake,
-            rewards_apy_percentage: layout.rewards_apy_percentage,
-            voting_duration_secs: layout.voting_duration_secs,
-            voting_power_increase_limit: layout.voting_power_increase_limit,
-        },
-    )?)
+    let genesis_info = GenesisBuilder::new(layout, validators, framework)
+        .with_collections(collections)
+        .with_coins(coins)
+        .with_faucet(faucet)
+        .with_start_paused(start_paused)
+        .into_genesis_info()?;
+    Ok((genesis_info, excluded_validators))
+}
+
+/// Ensures every scheduled feature activates after genesis.  Unrecognized feature flags are
+/// already rejected when the layout is parsed.
+fn validate_scheduled_features(layout: &Layout) -> CliTypedResult<()> {
+    for scheduled in &layout.scheduled_features {
+        if scheduled.epoch <= GENESIS_EPOCH {
+            return Err(GenesisError::LayoutInvalid(format!(
+                "Scheduled feature {:?} must activate after the genesis epoch {}, but was \
+                 scheduled for epoch {}",
+                scheduled.feature, GENESIS_EPOCH, scheduled.epoch
+            ))
+            .into());
+        }
+    }
+    Ok(())
 }
 
-fn parse_error(errors: Vec<String>) -> CliError {
-    eprintln!(
-        "Failed to parse genesis inputs:\n{}",
-        serde_yaml::to_string(&errors).unwrap()
-    );
-    CliError::UnexpectedError("Failed to parse genesis inputs".to_string())
+/// Ensures the maximum transaction size, if set, is within the range allowed by the protocol
+fn validate_max_transaction_size(layout: &Layout) -> CliTypedResult<()> {
+    if let Some(size) = layout.max_transaction_size_in_bytes {
+        if !MAX_TRANSACTION_SIZE_RANGE.contains(&size) {
+            return Err(GenesisError::LayoutInvalid(format!(
+                "Maximum transaction size {} must be within {} and {} bytes",
+                size,
+                MAX_TRANSACTION_SIZE_RANGE.start(),
+                MAX_TRANSACTION_SIZE_RANGE.end()
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Addresses of every account created for the validators at genesis
+fn validator_accounts(
+    validators: &[ValidatorConfiguration],
+) -> impl Iterator<Item = AccountAddress> + '_ {
+    validators.iter().flat_map(|validator| {
+        [
+            validator.owner_account_address,
+            validator.operator_account_address,
+            validator.voter_account_address,
+        ]
+    })
+}
+
+/// Ensures every validator's stake is within the layout's minimum and maximum stake, reporting
+/// every validator outside of them at once, rather than genesis aborting on the first
+///
+/// A pool below the minimum stake can't join the validator set on chain, so unless
+/// `strict_min_stake` rejects them, validators below it are left out of genesis.  The validators
+/// kept are returned, along with those left out.  The validators are those of the layout's
+/// sorted users, in order, as read by `validator_configs`.
+fn validate_stakes(
+    layout: &Layout,
+    validators: Vec<ValidatorConfiguration>,
+    strict_min_stake: bool,
+) -> CliTypedResult<(Vec<ValidatorConfiguration>, Vec<ExcludedValidator>)> {
+    let mut kept = Vec::new();
+    let mut excluded = Vec::new();
+    let mut errors = Vec::new();
+    for (user, validator) in layout.sorted_users().into_iter().zip(validators) {
+        if validator.stake_amount < layout.min_stake {
+            if strict_min_stake {
+                errors.push(format!(
+                    "Validator {} has stake_amount {}, less than the layout's min_stake {}",
+                    validator.owner_account_address, validator.stake_amount, layout.min_stake
+                ));
+            } else {
+                excluded.push(ExcludedValidator {
+                    username: user.clone(),
+                    owner_account_address: validator.owner_account_address,
+                    stake_amount: validator.stake_amount,
+                });
+            }
+        } else if validator.stake_amount > layout.max_stake {
+            errors.push(format!(
+                "Validator {} has stake_amount {}, greater than the layout's max_stake {}",
+                validator.owner_account_address, validator.stake_amount, layout.max_stake
+            ));
+        } else {
+            kept.push(validator);
+        }
+    }
+    if errors.is_empty() && kept.is_empty() {
+        errors.push(format!(
+            "No validator has the layout's min_stake {}, leaving none for genesis",
+            layout.min_stake
+        ));
+    }
+    if errors.is_empty() {
+        Ok((kept, excluded))
+    } else {
+        Err(GenesisError::ValidatorConfigInvalid(errors).into())
+    }
+}
+
+/// Fewest validators for which stake concentration is warned of.  With fewer, consensus can't
+/// tolerate any validator failing, so every validator can halt it whatever the stakes
+const MIN_VALIDATORS_FOR_CONCENTRATION_WARNINGS: usize = 4;
+
+/// Warns of validators holding more than a third of the total stake, which can halt consensus on
+/// their own, once there are enough validators for the stake to be spread
+fn stake_concentration_warnings(validators: &[ValidatorConfiguration]) -> Vec<String> {
+    if validators.len() < MIN_VALIDATORS_FOR_CONCENTRATION_WARNINGS {
+        return Vec::new();
+    }
+    let total_stake: u128 = validators
+        .iter()
+        .map(|validator| validator.stake_amount as u128)
+        .sum();
+    validators
+        .iter()
+        .filter(|validator| validator.stake_amount as u128 * 3 > total_stake)
+        .map(|validator| {
+            format!(
+                "Validator {} holds {} of the total stake {}, more than a third, so it can halt \
+                 consensus on its own",
+                validator.owner_account_address, validator.stake_amount, total_stake
+            )
+        })
+        .collect()
+}
+
+/// Ensures collection names are unique for their creator, token names are unique within their
+/// collection, and every collection is created by an account which exists at genesis
+fn validate_collections(
+    collections: &[GenesisCollection],
+    configured_accounts: &BTreeSet<AccountAddress>,
+) -> CliTypedResult<()> {
+    let mut collection_names = BTreeSet::new();
+    for collection in collections {
+        if !collection_names.insert((collection.creator, collection.name.as_str())) {
+            return Err(CliError::UnexpectedError(format!(
+                "Collection {} of creator {} is defined more than once",
+                collection.name, collection.creator
+            )));
+        }
+        if !configured_accounts.contains(&collection.creator) {
+            return Err(CliError::UnexpectedError(format!(
+                "Creator {} of collection {} is not an account configured for genesis",
+                collection.creator, collection.name
+            )));
+        }
+
+        let mut token_names = BTreeSet::new();
+        for token in &collection.tokens {
+            if !token_names.insert(token.name.as_str()) {
+                return Err(CliError::UnexpectedError(format!(
+                    "Token {} is defined more than once in collection {}",
+                    token.name, collection.name
+                )));
+            }
+            if token.supply == 0 {
+                return Err(CliError::UnexpectedError(format!(
+                    "Token {} in collection {} must have a non-zero supply",
+                    token.name, collection.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ensures every employee's balance fully vests over their vesting schedule, and that the balances
+/// of all employees add up to the employee vesting pool, if one is declared.  Every discrepancy is
+/// reported, so that the allocation spreadsheet can be fixed in one go.
+fn validate_employee_vesting(
+    employees: &[EmployeeAccountMap],
+    accounts: &[AccountMap],
+    pool_size: Option<u64>,
+) -> CliTypedResult<()> {
+    let errors = employee_vesting_problems(employees, accounts, pool_size);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(GenesisError::BalancesInvalid(format!(
+            "Employee vesting allocations are inconsistent:\n{}",
+            errors.join("\n")
+        ))
+        .into())
+    }
+}
+
+/// Every discrepancy between the employees' balances and their vesting schedules, or the employee
+/// vesting pool
+fn employee_vesting_problems(
+    employees: &[EmployeeAccountMap],
+    accounts: &[AccountMap],
+    pool_size: Option<u64>,
+) -> Vec<String> {
+    let balances: BTreeMap<_, _> = accounts
+        .iter()
+        .map(|account| (account.account_address, account.balance))
+        .collect();
+    let mut errors = Vec::new();
+    let mut total_allocation: u128 = 0;
+    for group in employees {
+        if group.vesting_schedule_denominator == 0 {
+            errors.push(format!(
+                "Vesting schedule of employees {:?} has a zero denominator",
+                group.accounts
+            ));
+            continue;
+        }
+        for account in &group.accounts {
+            let allocation = match balances.get(account) {
+                Some(allocation) => *allocation,
+                None => {
+                    errors.push(format!("Employee {} has no balance", account));
+                    continue;
+                }
+            };
+            total_allocation += allocation as u128;
+            let vested = group.vested_amount(allocation);
+            if vested != allocation as u128 {
+                errors.push(format!(
+                    "Employee {} is allocated {}, but its vesting schedule {:?}/{} vests {}",
+                    account,
+                    allocation,
+                    group.vesting_schedule_numerators,
+                    group.vesting_schedule_denominator,
+                    vested
+                ));
+            }
+        }
+    }
+    if let Some(pool_size) = pool_size {
+        if total_allocation != pool_size as u128 {
+            errors.push(format!(
+                "Employees are allocated {} in total, but the employee vesting pool is {}",
+                total_allocation, pool_size
+            ));
+        }
+    }
+    errors
+}
+
+/// Ensures the balances add up to the expected total supply, so that a mistyped balance is caught
+/// before genesis is written
+fn validate_total_supply(
+    accounts: &[AccountMap],
+    expected_total_supply: u64,
+) -> CliTypedResult<()> {
+    let mut total_supply: u64 = 0;
+    for account in accounts {
+        total_supply = total_supply.checked_add(account.balance).ok_or_else(|| {
+            GenesisError::BalancesInvalid(format!(
+                "Balances overflow the total supply at account {}",
+                account.account_address
+            ))
+        })?;
+    }
+    if total_supply != expected_total_supply {
+        let (delta, direction) = if total_supply > expected_total_supply {
+            (total_supply - expected_total_supply, "more")
+        } else {
+            (expected_total_supply - total_supply, "less")
+        };
+        return Err(GenesisError::BalancesInvalid(format!(
+            "Balances add up to {}, {} {} than the expected total supply {}",
+            total_supply, delta, direction, expected_total_supply
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn read_waypoint(waypoint_file: &Path) -> CliTypedResult<Waypoint> {
+    let contents = String::from_utf8(read_from_file(waypoint_file)?).map_err(CliError::from)?;
+    Waypoint::from_str(contents.trim())
+        .map_err(|err| CliError::UnableToParse(WAYPOINT_FILE, format!("Invalid waypoint: {}", err)))
+}
+
+/// Failures reading the configurations of validators, collected so they're all reported at once
+#[derive(Default)]
+pub(crate) struct ConfigFailures {
+    missing_users: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl ConfigFailures {
+    pub(crate) fn push(&mut self, user: &str, failure: CliError) {
+        if is_not_found(&failure) {
+            self.missing_users.push(user.to_string());
+        }
+        self.errors.push(config_error(user, failure));
+    }
+
+    /// Prints every failure, and fails with the missing configurations if there are any, since
+    /// those users have yet to configure their validators at all
+    pub(crate) fn check(self) -> Result<(), GenesisError> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        eprintln!(
+            "Failed to parse genesis inputs:\n{}",
+            serde_yaml::to_string(&self.errors).unwrap()
+        );
+        if self.missing_users.is_empty() {
+            Err(GenesisError::ValidatorConfigInvalid(self.errors))
+        } else {
+            Err(GenesisError::MissingValidatorConfig(self.missing_users))
+        }
+    }
+}
+
+/// Ensures every user in the layout has submitted their configuration, before any is parsed
+/// for genesis
+///
+/// A user who never ran `SetValidatorConfiguration` is the most common reason a ceremony can't
+/// generate genesis, so every such user is reported at once, ahead of any invalid configuration.
+fn check_configs_submitted(client: &Client, layout: &Layout) -> CliTypedResult<()> {
+    let mut missing_users = Vec::new();
+    for user in layout.sorted_users() {
+        let dir = PathBuf::from(user);
+        for file in [OWNER_FILE, OPERATOR_FILE] {
+            // Configurations which can't be read for any other reason are reported once parsed
+            match client.get::<serde_yaml::Value>(&dir.join(file)) {
+                Err(err) if is_not_found(&err) => {
+                    missing_users.push(user.to_string());
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    if missing_users.is_empty() {
+        Ok(())
+    } else {
+        Err(GenesisError::MissingValidatorConfig(missing_users).into())
+    }
+}
+
+/// Reads the configurations of every validator, only validating those which changed if the
+/// previously validated inputs are given
+fn validator_configs(
+    client: &Client,
+    layout: &Layout,
+    validated: Option<&mut ValidatedInputs>,
+) -> CliTypedResult<Vec<ValidatorConfiguration>> {
+    let validators = if let Some(validated) = validated {
+        let (validators, validated_users) = validated.update(client, layout)?;
+        eprintln!(
+            "Validated the configurations of {} of {} validators: {:?}",
+            validated_users.len(),
+            validators.len(),
+            validated_users
+        );
+        validators
+    } else {
+        get_validator_configs(client, layout)?
+    };
+    // Checked even for configurations validated before, as the layout may have changed since
+    for (user, validator) in layout.sorted_users().into_iter().zip(&validators) {
+        check_chain_id(user, validator, layout)?;
+    }
+    Ok(validators)
 }
 
 fn get_validator_configs(
     client: &Client,
     layout: &Layout,
-) -> Result<Vec<ValidatorConfiguration>, Vec<String>> {
+) -> CliTypedResult<Vec<ValidatorConfiguration>> {
     let mut validators = Vec::new();
-    let mut errors = Vec::new();
-    for user in &layout.users {
+    let mut failures = ConfigFailures::default();
+    for user in layout.sorted_users() {
         match get_config(client, user) {
             Ok(validator) => {
                 validators.push(validator);
             }
-            Err(failure) => {
-                if let CliError::UnexpectedError(failure) = failure {
-                    errors.push(format!("{}: {}", user, failure));
-                } else {
-                    errors.push(format!("{}: {:?}", user, failure));
-                }
-            }
+            Err(failure) => failures.push(user, failure),
         }
     }
+    failures.check()?;
+    Ok(validators)
+}
 
-    if errors.is_empty() {
-        Ok(validators)
+/// Ensures a configuration set against a layout was set against this layout's chain, so that a
+/// validator configured for another network isn't brought into this one's genesis
+///
+/// Configurations set before the layout was uploaded don't record a chain id, and are left as
+/// they are.
+fn check_chain_id(
+    user: &str,
+    validator: &ValidatorConfiguration,
+    layout: &Layout,
+) -> Result<(), GenesisError> {
+    match validator.chain_id {
+        Some(chain_id) if chain_id != layout.chain_id => Err(GenesisError::ChainIdMismatch {
+            user: user.to_string(),
+            config: chain_id,
+            layout: layout.chain_id,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn config_error(user: &str, failure: CliError) -> String {
+    if let CliError::UnexpectedError(failure) = failure {
+        format!("{}: {}", user, failure)
     } else {
-        Err(errors)
+        format!("{}: {:?}", user, failure)
     }
 }
 
 /// Do proper parsing so more information is known about failures
 fn get_config(client: &Client, user: &str) -> CliTypedResult<ValidatorConfiguration> {
-    // Load a user's configuration files
+    let (owner_config, operator_config) = get_string_configs(client, user)?;
+    validate_config(user, &owner_config, &operator_config)
+}
+
+/// Load a user's configuration files, without checking their fields
+pub(crate) fn get_string_configs(
+    client: &Client,
+    user: &str,
+) -> CliTypedResult<(StringOwnerConfiguration, StringOperatorConfiguration)> {
+    let dir = PathBuf::from(user);
+    let owner_config = client.get::<StringOwnerConfiguration>(&dir.join(OWNER_FILE))?;
+    let operator_config = client.get::<StringOperatorConfiguration>(&dir.join(OPERATOR_FILE))?;
+    Ok((owner_config, operator_config))
+}
+
+/// Check and convert a user's configuration files into their validator's configuration
+fn validate_config(
+    user: &str,
+    owner_config: &StringOwnerConfiguration,
+    operator_config: &StringOperatorConfiguration,
+) -> CliTypedResult<ValidatorConfiguration> {
     let dir = PathBuf::from(user);
     let owner_file = dir.join(OWNER_FILE);
     let owner_file = owner_file.as_path();
-    let owner_config = client.get::<StringOwnerConfiguration>(owner_file)?;
-
     let operator_file = dir.join(OPERATOR_FILE);
     let operator_file = operator_file.as_path();
-    let operator_config = client.get::<StringOperatorConfiguration>(operator_file)?;
 
     // Check and convert fields in owner file
     let owner_account_address = parse_required_option(
@@ -282,6 +1376,12 @@ fn get_config(client: &Client, user: &str) -> CliTypedResult<ValidatorConfigurat
         u64::from_str,
     )?
     .unwrap_or_default();
+    let chain_id = parse_optional_option(
+        &owner_config.chain_id,
+        owner_file,
+        "chain_id",
+        ChainId::from_str,
+    )?;
 
     // Check and convert fields in operator file
     let operator_account_address_from_file = parse_required_option(
@@ -354,11 +1454,13 @@ fn get_config(client: &Client, user: &str) -> CliTypedResult<ValidatorConfigurat
         consensus_public_key,
         proof_of_possession: consensus_proof_of_possession,
         validator_network_public_key,
-        validator_host: operator_config.validator_host,
+        validator_host: operator_config.validator_host.clone(),
+        additional_validator_hosts: operator_config.additional_validator_hosts.clone(),
         full_node_network_public_key,
-        full_node_host: operator_config.full_node_host,
+        full_node_host: operator_config.full_node_host.clone(),
         stake_amount,
         commission_percentage,
+        chain_id,
     })
 }
 
@@ -407,3 +1509,71 @@ fn parse_optional_option<F: Fn(&str) -> Result<T, E>, T, E: std::fmt::Display>(
         Ok(None)
     }
 }
+
+/// Ensures every coin type is a valid and new type, coin names and symbols are unique and differ
+/// from the native coin's, and the issuer and holders of every coin are accounts which exist at
+/// genesis
+fn validate_coins(
+    coins: &[GenesisCoin],
+    configured_accounts: &BTreeSet<AccountAddress>,
+) -> CliTypedResult<()> {
+    let mut coin_types = BTreeSet::from([APTOS_COIN_TYPE.clone()]);
+    let mut names = BTreeSet::from([NATIVE_COIN_NAME]);
+    let mut symbols = BTreeSet::from([NATIVE_COIN_SYMBOL]);
+    for coin in coins {
+        if !Identifier::is_valid(&coin.module_name) || !Identifier::is_valid(&coin.struct_name) {
+            return Err(CliError::UnexpectedError(format!(
+                "Coin type {}::{} of coin {} is not made of valid identifiers",
+                coin.module_name, coin.struct_name, coin.symbol
+            )));
+        }
+        if !coin_types.insert(coin.type_tag()) {
+            return Err(CliError::UnexpectedError(format!(
+                "Coin type {} is defined more than once",
+                coin.type_tag()
+            )));
+        }
+        if !names.insert(coin.name.as_str()) {
+            return Err(CliError::UnexpectedError(format!(
+                "Coin name {} is used more than once",
+                coin.name
+            )));
+        }
+        if !symbols.insert(coin.symbol.as_str()) {
+            return Err(CliError::UnexpectedError(format!(
+                "Coin symbol {} is used more than once",
+                coin.symbol
+            )));
+        }
+        if !configured_accounts.contains(&coin.issuer) {
+            return Err(CliError::UnexpectedError(format!(
+                "Issuer {} of coin {} is not an account configured for genesis",
+                coin.issuer, coin.symbol
+            )));
+        }
+
+        let mut holders = BTreeSet::new();
+        let mut supply: u64 = 0;
+        for holder in &coin.holders {
+            if !configured_accounts.contains(&holder.address) {
+                return Err(CliError::UnexpectedError(format!(
+                    "Holder {} of coin {} is not an account configured for genesis",
+                    holder.address, coin.symbol
+                )));
+            }
+            if !holders.insert(holder.address) {
+                return Err(CliError::UnexpectedError(format!(
+                    "Holder {} is listed more than once for coin {}",
+                    holder.address, coin.symbol
+                )));
+            }
+            supply = supply.checked_add(holder.amount).ok_or_else(|| {
+                CliError::UnexpectedError(format!(
+                    "Initial supply of coin {} overflows a u64",
+                    coin.symbol
+                ))
+            })?;
+        }
+    }
+    Ok(())
+}
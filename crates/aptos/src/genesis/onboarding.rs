@@ -0,0 +1,146 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::{
+            check_if_file_exists, create_dir_if_not_exist, dir_default_to_current, write_to_file,
+        },
+    },
+    genesis::{
+        get_validator_configs,
+        git::{GitOptions, LAYOUT_FILE},
+        read_waypoint, GENESIS_FILE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::{HostAndPort, Layout, ValidatorConfiguration};
+use aptos_types::waypoint::Waypoint;
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Generate an onboarding sheet for every validator in the genesis repository
+///
+/// Run after `GenerateGenesis`.  One Markdown sheet per user is written to the output directory,
+/// with the user's accounts, hosts and stake from the Git repository, the waypoint of the
+/// generated genesis, and the command to start their node with it.
+#[derive(Parser)]
+pub struct GenerateOnboardingSheets {
+    /// Directory containing the genesis.blob and waypoint.txt generated by `GenerateGenesis`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_dir: Option<PathBuf>,
+    /// Output directory for the onboarding sheets
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_dir: Option<PathBuf>,
+    /// Directory validators keep their node configuration and the genesis files in
+    #[clap(long, default_value = "/opt/aptos/etc")]
+    pub(crate) node_config_dir: String,
+
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<PathBuf>> for GenerateOnboardingSheets {
+    fn command_name(&self) -> &'static str {
+        "GenerateOnboardingSheets"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
+        let genesis_dir = dir_default_to_current(self.genesis_dir)?;
+        let output_dir = dir_default_to_current(self.output_dir)?;
+        if !genesis_dir.join(GENESIS_FILE).exists() {
+            return Err(CliError::UnexpectedError(format!(
+                "{} not found in {}, generate genesis first",
+                GENESIS_FILE,
+                genesis_dir.display()
+            )));
+        }
+        let waypoint = read_waypoint(&genesis_dir.join(WAYPOINT_FILE))?;
+
+        let client = self.git_options.get_client()?;
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        let validators = get_validator_configs(&client, &layout)?;
+
+        create_dir_if_not_exist(&output_dir)?;
+        let mut sheets = Vec::new();
+        for (user, validator) in layout.sorted_users().into_iter().zip(&validators) {
+            let sheet_file = output_dir.join(format!("{}.md", user));
+            check_if_file_exists(&sheet_file, self.prompt_options)?;
+            let sheet =
+                onboarding_sheet(user, &layout, validator, &waypoint, &self.node_config_dir);
+            write_to_file(
+                &sheet_file,
+                &sheet_file.display().to_string(),
+                sheet.as_bytes(),
+            )?;
+            sheets.push(sheet_file);
+        }
+        Ok(sheets)
+    }
+}
+
+/// Renders the onboarding instructions of a single validator
+pub fn onboarding_sheet(
+    user: &str,
+    layout: &Layout,
+    validator: &ValidatorConfiguration,
+    waypoint: &Waypoint,
+    node_config_dir: &str,
+) -> String {
+    let host = |host: &HostAndPort| format!("{}:{}", host.host, host.port);
+    let full_node_host = validator
+        .full_node_host
+        .as_ref()
+        .map_or_else(|| "none".to_string(), host);
+    format!(
+        "# Validator onboarding for {user}
+
+Chain ID: {chain_id}
+
+## Accounts
+
+- Owner: {owner}
+- Operator: {operator}
+- Voter: {voter}
+
+## Validator
+
+- Validator host: {validator_host}
+- Full node host: {full_node_host}
+- Stake: {stake}
+- Commission: {commission}%
+
+## Starting the node
+
+Check that the waypoint of your genesis is:
+
+    {waypoint}
+
+Copy `{genesis_file}` and `{waypoint_file}` into `{dir}`, then start the node with:
+
+    aptos-node -f {dir}/validator.yaml
+",
+        user = user,
+        chain_id = layout.chain_id,
+        owner = validator.owner_account_address.to_hex_literal(),
+        operator = validator.operator_account_address.to_hex_literal(),
+        voter = validator.voter_account_address.to_hex_literal(),
+        validator_host = validator
+            .validator_hosts()
+            .map(host)
+            .collect::<Vec<_>>()
+            .join(", "),
+        full_node_host = full_node_host,
+        stake = validator.stake_amount,
+        commission = validator.commission_percentage,
+        waypoint = waypoint,
+        genesis_file = GENESIS_FILE,
+        waypoint_file = WAYPOINT_FILE,
+        dir = node_config_dir,
+    )
+}
@@ -0,0 +1,117 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::OutputFormat;
+use aptos_crypto::HashValue;
+use aptos_logger::info;
+use aptos_types::waypoint::Waypoint;
+use std::{
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex},
+};
+
+/// A step of a long genesis operation, reported once it's done
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GenesisProgress {
+    /// The layout was uploaded to the repository, for its users to configure their validators
+    WroteLayout { users: usize },
+    /// A framework bundle was uploaded to the repository, in place of the cached head release
+    WroteFramework,
+    /// The repository's framework was read, with its hash
+    LoadedFramework { hash: HashValue },
+    /// Every validator configuration in the repository was read and validated
+    LoadedValidatorConfigs { validators: usize },
+    /// The genesis transaction was generated, and serialized to this many bytes
+    GeneratedGenesis { bytes: usize },
+    /// The waypoint of genesis was computed, by executing it
+    ComputedWaypoint { waypoint: Waypoint },
+    /// The files of the genesis output were written
+    WroteOutputs { files: usize },
+    /// Something which doesn't stop genesis, but is likely a mistake, such as a validator left
+    /// out of it
+    Warning { message: String },
+}
+
+impl Display for GenesisProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenesisProgress::WroteLayout { users } => {
+                write!(f, "Wrote layout with {} users", users)
+            }
+            GenesisProgress::WroteFramework => write!(f, "Wrote framework bundle"),
+            GenesisProgress::LoadedFramework { hash } => {
+                write!(f, "Loaded framework with hash {}", hash)
+            }
+            GenesisProgress::LoadedValidatorConfigs { validators } => {
+                write!(f, "Loaded {} validator configs", validators)
+            }
+            GenesisProgress::GeneratedGenesis { bytes } => {
+                write!(f, "Generated genesis of {} bytes", bytes)
+            }
+            GenesisProgress::ComputedWaypoint { waypoint } => {
+                write!(f, "Computed waypoint {}", waypoint)
+            }
+            GenesisProgress::WroteOutputs { files } => write!(f, "Wrote {} files", files),
+            GenesisProgress::Warning { message } => write!(f, "Warning: {}", message),
+        }
+    }
+}
+
+/// Receives the progress of genesis operations, such as to drive a progress bar
+pub type ProgressCallback = Arc<dyn Fn(&GenesisProgress) + Send + Sync>;
+
+/// Reports the progress of genesis operations to the logger, and to a callback if one is given
+///
+/// Without a callback, progress is printed to stderr, so that stdout holds only the output, unless
+/// the output is JSON, for which nothing but logs is written to stderr
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    callback: Option<ProgressCallback>,
+    quiet: bool,
+    /// The last step reported, shared by every clone of the reporter
+    last: Arc<Mutex<Option<GenesisProgress>>>,
+}
+
+impl ProgressReporter {
+    pub fn with_callback(callback: ProgressCallback) -> Self {
+        ProgressReporter {
+            callback: Some(callback),
+            quiet: false,
+            last: Arc::default(),
+        }
+    }
+
+    /// Stops printing progress to stderr if the output is JSON
+    pub fn for_output(mut self, output_format: OutputFormat) -> Self {
+        self.quiet |= output_format == OutputFormat::Json;
+        self
+    }
+
+    pub fn report(&self, progress: GenesisProgress) {
+        info!("{}", progress);
+        // A warning isn't a step, so leaves the phase as it was
+        if !matches!(progress, GenesisProgress::Warning { .. }) {
+            *self.last.lock().unwrap() = Some(progress.clone());
+        }
+        if let Some(ref callback) = self.callback {
+            callback(&progress);
+        } else if !self.quiet {
+            eprintln!("{}", progress);
+        }
+    }
+
+    /// What the operation is doing, going by the last step it reported
+    pub fn phase(&self) -> &'static str {
+        match *self.last.lock().unwrap() {
+            None => "reading the repository",
+            Some(GenesisProgress::WroteLayout { .. }) => "writing the framework",
+            Some(GenesisProgress::WroteFramework) => "finishing the repository setup",
+            Some(GenesisProgress::LoadedFramework { .. }) => "loading validator configurations",
+            Some(GenesisProgress::LoadedValidatorConfigs { .. }) => "generating genesis",
+            Some(GenesisProgress::GeneratedGenesis { .. }) => "computing the waypoint",
+            Some(GenesisProgress::ComputedWaypoint { .. }) => "writing outputs",
+            Some(GenesisProgress::WroteOutputs { .. }) => "finishing",
+            Some(GenesisProgress::Warning { .. }) => unreachable!("warnings aren't recorded"),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::{check_if_file_exists, read_from_file, write_to_file},
+    },
+    genesis::git::LAYOUT_FILE,
+    CliCommand,
+};
+use aptos_genesis::config::Layout;
+use async_trait::async_trait;
+use clap::Parser;
+use serde_yaml::Value;
+use std::path::PathBuf;
+
+/// Replacement for every redacted value
+pub const REDACTED_VALUE: &str = "<redacted>";
+/// Notice added to redacted layouts, which marks them as unusable for genesis
+pub const REDACTED_NOTICE: &str =
+    "This layout has been redacted for review and must not be used for genesis";
+/// Field names containing any of these are considered sensitive
+const SENSITIVE_FIELDS: [&str; 5] = ["private", "secret", "seed", "mnemonic", "password"];
+
+/// Redact sensitive fields of a layout before sharing it for review
+///
+/// Any field whose name refers to private material e.g. `private_key`, `secret` or `seed`, at any
+/// depth, has its values replaced with `<redacted>`.  Every other field, including public keys
+/// and the genesis parameters, is kept as is.  The output is marked as redacted, and is rejected
+/// by `SetupGit` and `GenerateGenesis`.
+#[derive(Parser)]
+pub struct RedactLayout {
+    /// Path to the layout to redact
+    #[clap(long, parse(from_os_str), default_value = LAYOUT_FILE)]
+    pub(crate) layout_file: PathBuf,
+
+    /// Path of the redacted layout
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for RedactLayout {
+    fn command_name(&self) -> &'static str {
+        "RedactLayout"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let contents =
+            String::from_utf8(read_from_file(&self.layout_file)?).map_err(CliError::from)?;
+        let redacted = redact_layout(&contents)?;
+
+        check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
+        write_to_file(
+            self.output_file.as_path(),
+            &self.output_file.display().to_string(),
+            redacted.as_bytes(),
+        )
+    }
+}
+
+/// Redacts the sensitive fields of a YAML layout, keeping its structure
+pub fn redact_layout(contents: &str) -> CliTypedResult<String> {
+    let mut layout: Value = serde_yaml::from_str(contents)
+        .map_err(|err| CliError::UnableToParse(LAYOUT_FILE, err.to_string()))?;
+    let mapping = layout.as_mapping_mut().ok_or_else(|| {
+        CliError::UnableToParse(LAYOUT_FILE, "Layout is not a YAML mapping".to_string())
+    })?;
+
+    for (field, value) in mapping.iter_mut() {
+        redact_field(field, value);
+    }
+    mapping.insert(
+        Value::String("redacted".to_string()),
+        Value::String(REDACTED_NOTICE.to_string()),
+    );
+
+    Ok(format!(
+        "# {}\n{}",
+        REDACTED_NOTICE,
+        serde_yaml::to_string(&layout)?
+    ))
+}
+
+/// Fails if the layout was produced by `RedactLayout`
+pub fn ensure_layout_not_redacted(layout: &Layout) -> CliTypedResult<()> {
+    if layout.redacted.is_some() {
+        Err(CliError::CommandArgumentError(format!(
+            "Layout is redacted and cannot be used: {}",
+            REDACTED_NOTICE
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_sensitive_field(field: &Value) -> bool {
+    field.as_str().map_or(false, |field| {
+        let field = field.to_lowercase();
+        SENSITIVE_FIELDS
+            .iter()
+            .any(|sensitive| field.contains(sensitive))
+    })
+}
+
+fn redact_field(field: &Value, value: &mut Value) {
+    if is_sensitive_field(field) {
+        mask(value);
+        return;
+    }
+
+    match value {
+        Value::Mapping(mapping) => {
+            for (field, value) in mapping.iter_mut() {
+                redact_field(field, value);
+            }
+        }
+        Value::Sequence(values) => {
+            for value in values.iter_mut() {
+                redact_field(&Value::Null, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces every value under a sensitive field, keeping mappings and sequences in place
+fn mask(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (_, value) in mapping.iter_mut() {
+                mask(value);
+            }
+        }
+        Value::Sequence(values) => values.iter_mut().for_each(mask),
+        Value::Null => {}
+        _ => *value = Value::String(REDACTED_VALUE.to_string()),
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::{read_from_file, write_to_file},
+    },
+    genesis::{
+        get_string_configs,
+        git::{
+            to_yaml, Client, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE, FRAMEWORK_NAME,
+            LAYOUT_FILE,
+        },
+        GENESIS_FILE,
+    },
+};
+use aptos_crypto::HashValue;
+use aptos_genesis::config::Layout;
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+use vm_genesis::{AccountMap, GenesisCoin, GenesisCollection};
+
+/// Sidecar in the output directory recording the hash of the inputs genesis was generated from
+pub const GENESIS_INPUTS_FILE: &str = ".genesis-manifest";
+
+/// Name of the sidecar recording the inputs of the genesis file of this name, so that several
+/// genesis files in one output directory are each resumed from their own inputs
+pub fn inputs_file_name(genesis_file_name: &str) -> String {
+    if genesis_file_name == GENESIS_FILE {
+        GENESIS_INPUTS_FILE.to_string()
+    } else {
+        format!("{}.{}", GENESIS_INPUTS_FILE, genesis_file_name)
+    }
+}
+
+/// Name of an output written alongside the genesis file of this name, prefixed with its stem when
+/// it isn't the default genesis file, so that several genesis files in one output directory each
+/// keep their own outputs
+pub fn output_file_name(genesis_file_name: &str, file_name: &str) -> String {
+    if genesis_file_name == GENESIS_FILE {
+        file_name.to_string()
+    } else {
+        let stem = Path::new(genesis_file_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        format!("{}.{}", stem, file_name)
+    }
+}
+
+/// The options genesis is generated with, which along with the repository determine its outputs
+#[derive(Serialize)]
+pub struct GenesisInputs {
+    pub mainnet: bool,
+    pub collections: Vec<GenesisCollection>,
+    pub coins: Vec<GenesisCoin>,
+    pub start_paused: bool,
+    /// Format genesis is written in
+    pub output_format: String,
+    /// Whether a manifest of genesis is signed
+    pub signed: bool,
+    /// Mainnet balances given in place of the repository's balances file, hashed with the others
+    #[serde(skip)]
+    pub balances: Option<Vec<AccountMap>>,
+    /// The faucet account funded at genesis, left out without one so earlier hashes still match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<AccountMap>,
+}
+
+impl GenesisInputs {
+    /// Hashes these options along with the layout, the configuration of every validator in it,
+    /// the balances and employee vesting accounts of mainnet, and the framework
+    pub fn hash(&self, client: &Client) -> CliTypedResult<HashValue> {
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        let mut validators = BTreeMap::new();
+        for user in &layout.users {
+            let (owner_config, operator_config) = get_string_configs(client, user)?;
+            validators.insert(
+                user.clone(),
+                (to_yaml(&owner_config)?, to_yaml(&operator_config)?),
+            );
+        }
+        let accounts = if self.mainnet {
+            let balances: serde_yaml::Value = match self.balances {
+                Some(ref balances) => serde_yaml::to_value(balances)?,
+                None => client.get(Path::new(BALANCES_FILE))?,
+            };
+            let employees: serde_yaml::Value =
+                client.get(Path::new(EMPLOYEE_VESTING_ACCOUNTS_FILE))?;
+            Some((balances, employees))
+        } else {
+            None
+        };
+        let framework = bcs::to_bytes(&client.get_framework()?)
+            .map_err(|err| CliError::BCS(FRAMEWORK_NAME, err))?;
+
+        let inputs = to_yaml(&(
+            self,
+            layout,
+            validators,
+            accounts,
+            HashValue::sha3_256_of(&framework),
+        ))?;
+        Ok(HashValue::sha3_256_of(inputs.as_bytes()))
+    }
+}
+
+/// Reads the hash of the inputs of the genesis in the output directory, if it was recorded
+pub fn read_inputs_hash(inputs_file: &Path) -> CliTypedResult<Option<HashValue>> {
+    if !inputs_file.exists() {
+        return Ok(None);
+    }
+    let contents = String::from_utf8(read_from_file(inputs_file)?).map_err(CliError::from)?;
+    HashValue::from_str(contents.trim())
+        .map(Some)
+        .map_err(|err| CliError::UnableToParse(GENESIS_INPUTS_FILE, err.to_string()))
+}
+
+pub fn write_inputs_hash(inputs_file: &Path, inputs_hash: HashValue) -> CliTypedResult<()> {
+    write_to_file(
+        inputs_file,
+        GENESIS_INPUTS_FILE,
+        inputs_hash.to_hex().as_bytes(),
+    )
+}
@@ -0,0 +1,169 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, OutputOptions},
+        utils::read_from_file,
+    },
+    genesis::{encrypt::PassphraseOptions, git::GitOptions, keys::SetValidatorConfiguration},
+    CliCommand,
+};
+use aptos_genesis::config::HostAndPort;
+use async_trait::async_trait;
+use clap::Parser;
+use std::{path::PathBuf, str::FromStr};
+
+/// Columns expected in the header of a roster file, in order
+pub const ROSTER_COLUMNS: [&str; 7] = [
+    "username",
+    "owner_key",
+    "operator_key",
+    "voter_key",
+    "host",
+    "stake",
+    "commission",
+];
+
+/// Set validator configurations for every validator in a CSV roster
+///
+/// The roster must have the header
+/// `username,owner_key,operator_key,voter_key,host,stake,commission`, where the key columns are
+/// paths to public identity files generated by `GenerateKeys`.  The operator and voter keys may be
+/// left empty to default to the owner's.  Every row is validated before any configuration is
+/// written, and all failures are reported together.
+#[derive(Parser)]
+pub struct SetValidatorConfigurationsFromRoster {
+    /// Path to the CSV roster
+    #[clap(long, parse(from_os_str))]
+    pub(crate) roster_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for SetValidatorConfigurationsFromRoster {
+    fn command_name(&self) -> &'static str {
+        "SetValidatorConfigurationsFromRoster"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let bytes = read_from_file(self.roster_file.as_path())?;
+        let contents = String::from_utf8(bytes).map_err(CliError::from)?;
+        let commands = parse_roster(&contents, &self.git_options).map_err(roster_error)?;
+
+        // Validate every row before writing anything
+        let mut configs = Vec::new();
+        let mut errors = Vec::new();
+        for command in &commands {
+            match command.build_configurations() {
+                Ok(config) => configs.push(config),
+                Err(error) => errors.push(format!("{}: {}", command.username, error)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(roster_error(errors));
+        }
+
+        for (command, (operator_config, owner_config)) in commands.iter().zip(configs) {
+            command.write_configurations(&operator_config, &owner_config)?;
+        }
+        Ok(commands
+            .into_iter()
+            .map(|command| command.username)
+            .collect())
+    }
+}
+
+fn roster_error(errors: Vec<String>) -> CliError {
+    CliError::CommandArgumentError(format!("Invalid roster:\n{}", errors.join("\n")))
+}
+
+/// Parses a roster into one `SetValidatorConfiguration` per row, collecting all row errors
+fn parse_roster(
+    contents: &str,
+    git_options: &GitOptions,
+) -> Result<Vec<SetValidatorConfiguration>, Vec<String>> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let header: Vec<_> = match lines.next() {
+        Some((_, header)) => header.split(',').map(str::trim).collect(),
+        None => return Err(vec!["Roster is empty".to_string()]),
+    };
+    if header != ROSTER_COLUMNS {
+        return Err(vec![format!(
+            "Roster header must be '{}', but was '{}'",
+            ROSTER_COLUMNS.join(","),
+            header.join(",")
+        )]);
+    }
+
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+    for (line_number, line) in lines {
+        match parse_row(line, git_options) {
+            Ok(command) => commands.push(command),
+            Err(error) => errors.push(format!("line {}: {}", line_number, error)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(commands)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_row(line: &str, git_options: &GitOptions) -> Result<SetValidatorConfiguration, String> {
+    let fields: Vec<_> = line.split(',').map(str::trim).collect();
+    if fields.len() != ROSTER_COLUMNS.len() {
+        return Err(format!(
+            "Expected {} columns, but found {}",
+            ROSTER_COLUMNS.len(),
+            fields.len()
+        ));
+    }
+
+    let username = fields[0];
+    if username.is_empty() {
+        return Err("username must not be empty".to_string());
+    }
+    if fields[1].is_empty() {
+        return Err("owner_key must not be empty".to_string());
+    }
+    let optional_path = |field: &str| {
+        if field.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(field))
+        }
+    };
+    let validator_host = HostAndPort::from_str(fields[4])
+        .map_err(|error| format!("Invalid host '{}': {}", fields[4], error))?;
+    let stake_amount = u64::from_str(fields[5])
+        .map_err(|error| format!("Invalid stake '{}': {}", fields[5], error))?;
+    let commission_percentage = u64::from_str(fields[6])
+        .map_err(|error| format!("Invalid commission '{}': {}", fields[6], error))?;
+
+    Ok(SetValidatorConfiguration {
+        username: username.to_string(),
+        validator_host: vec![validator_host],
+        full_node_host: None,
+        stake_amount,
+        commission_percentage,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        owner_public_identity_file: optional_path(fields[1]),
+        operator_public_identity_file: optional_path(fields[2]),
+        voter_public_identity_file: optional_path(fields[3]),
+        passphrase_options: PassphraseOptions::default(),
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+    })
+}
@@ -0,0 +1,280 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, OptionalPoolAddressArgs, PromptOptions, RngArgs},
+        utils::{check_if_file_exists, current_dir, dir_default_to_current, write_to_file},
+    },
+    genesis::{
+        encrypt::PassphraseOptions,
+        keys::{check_key_files, read_public_identity_file, write_key_files, PUBLIC_KEYS_FILE},
+    },
+    CliCommand,
+};
+use aptos_genesis::{
+    config::HostAndPort,
+    keys::{generate_key_objects, PublicIdentity},
+};
+use aptos_rest_client::aptos_api_types::{
+    EntryFunctionId, EntryFunctionPayload, HexEncodedBytes, TransactionPayload,
+};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use std::{
+    fs::canonicalize,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// File the transaction payload rotating the consensus key is written to
+pub const ROTATION_PAYLOAD_FILE: &str = "rotate-consensus-key.json";
+/// File the transaction payload updating the network addresses to the new keys is written to
+pub const NETWORK_ROTATION_PAYLOAD_FILE: &str = "update-network-addresses.json";
+const ROTATE_CONSENSUS_KEY_FUNCTION: &str = "0x1::stake::rotate_consensus_key";
+const UPDATE_NETWORK_ADDRESSES_FUNCTION: &str = "0x1::stake::update_network_and_fullnode_addresses";
+
+/// Generate new keys for a validator configured from a public identity file
+///
+/// The new keys are written next to JSON transaction payloads rotating the validator's consensus
+/// key on chain, and updating its network addresses to the new network keys, which the operator
+/// can submit.  Both are needed for the validator to keep its place, as peers only connect to it
+/// through the keys in its addresses.  Nothing is changed on chain by this command.
+/// The new identity keeps the account address of the original, as rotating an account's key
+/// doesn't change its address, so the owner and operator of the validator stay the same.  The
+/// account key itself is rotated with `aptos account rotate-key`, signed with the current key.
+#[derive(Parser)]
+pub struct RotateValidatorKeys {
+    /// Path to the public identity file the validator was configured with
+    #[clap(long, parse(from_os_str))]
+    pub(crate) public_identity_file: Option<PathBuf>,
+
+    /// Output directory for the new key files and rotation payload
+    ///
+    /// Must differ from the directory of the original identity, whose keys are still needed to
+    /// sign the rotation
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    /// Host and port of the validator, given the new validator network key in its address
+    #[clap(long)]
+    pub(crate) validator_host: HostAndPort,
+
+    /// Host and port of the validator fullnode, given the new fullnode network key in its address
+    ///
+    /// Without it, the validator is left with no fullnode address on chain
+    #[clap(long)]
+    pub(crate) full_node_host: Option<HostAndPort>,
+
+    /// Encrypt the new private keys with a passphrase, as with `GenerateKeys --encrypt`
+    #[clap(long)]
+    pub(crate) encrypt: bool,
+
+    #[clap(flatten)]
+    pub(crate) passphrase_options: PassphraseOptions,
+    #[clap(flatten)]
+    pub(crate) pool_address_args: OptionalPoolAddressArgs,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+    #[clap(flatten)]
+    pub rng_args: RngArgs,
+}
+
+#[async_trait]
+impl CliCommand<Vec<PathBuf>> for RotateValidatorKeys {
+    fn command_name(&self) -> &'static str {
+        "RotateValidatorKeys"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<PathBuf>> {
+        let public_identity_file = if let Some(ref file) = self.public_identity_file {
+            file.clone()
+        } else {
+            current_dir()?.join(PUBLIC_KEYS_FILE)
+        };
+        let original = read_public_identity_file(&public_identity_file)?;
+
+        let output_dir = dir_default_to_current(self.output_dir.clone())?;
+        check_output_dir(&public_identity_file, &output_dir)?;
+        check_key_files(&output_dir, self.encrypt, self.prompt_options)?;
+        let payload_file = output_dir.join(ROTATION_PAYLOAD_FILE);
+        check_if_file_exists(&payload_file, self.prompt_options)?;
+        let network_payload_file = output_dir.join(NETWORK_ROTATION_PAYLOAD_FILE);
+        check_if_file_exists(&network_payload_file, self.prompt_options)?;
+        let passphrase = if self.encrypt {
+            Some(
+                self.passphrase_options
+                    .passphrase(self.prompt_options, true)?,
+            )
+        } else {
+            None
+        };
+
+        let mut key_generator = self.rng_args.key_generator()?;
+        let (mut validator_blob, mut vfn_blob, mut private_identity, mut public_identity) =
+            generate_key_objects(&mut key_generator)?;
+        let pool_address = self
+            .pool_address_args
+            .pool_address
+            .unwrap_or(original.account_address);
+        private_identity.account_address = original.account_address;
+        public_identity.account_address = original.account_address;
+        validator_blob.account_address = Some(pool_address);
+        vfn_blob.account_address = Some(pool_address);
+        check_rotated(&original, &public_identity)?;
+
+        let payload = rotate_consensus_key_payload(pool_address, &public_identity)?;
+        let network_payload = update_network_addresses_payload(
+            pool_address,
+            &public_identity,
+            &self.validator_host,
+            self.full_node_host.as_ref(),
+        )?;
+        let mut files = write_key_files(
+            &output_dir,
+            passphrase.as_deref(),
+            &public_identity,
+            &private_identity,
+            &validator_blob,
+            &vfn_blob,
+        )?;
+        for (file, name, payload) in [
+            (payload_file, ROTATION_PAYLOAD_FILE, payload),
+            (
+                network_payload_file,
+                NETWORK_ROTATION_PAYLOAD_FILE,
+                network_payload,
+            ),
+        ] {
+            let json = serde_json::to_string_pretty(&payload).map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to encode payload: {}", err))
+            })?;
+            write_to_file(&file, name, json.as_bytes())?;
+            files.push(file);
+        }
+        Ok(files)
+    }
+}
+
+/// Writing over the original keys would lose the account key the rotation must be signed with
+fn check_output_dir(public_identity_file: &Path, output_dir: &Path) -> CliTypedResult<()> {
+    let identity_dir = match public_identity_file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => current_dir()?,
+    };
+    let canonical =
+        |dir: &Path| canonicalize(dir).map_err(|err| CliError::IO(dir.display().to_string(), err));
+    if output_dir.exists() && canonical(output_dir)? == canonical(&identity_dir)? {
+        return Err(CliError::CommandArgumentError(format!(
+            "Output directory {} holds the keys being rotated, choose another with --output-dir",
+            output_dir.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the new identity has new keys, for the same account
+fn check_rotated(original: &PublicIdentity, rotated: &PublicIdentity) -> CliTypedResult<()> {
+    if original.account_address != rotated.account_address {
+        return Err(CliError::UnexpectedError(format!(
+            "Rotated identity has account {}, instead of {}",
+            rotated.account_address, original.account_address
+        )));
+    }
+    if original.account_public_key == rotated.account_public_key
+        || original.consensus_public_key == rotated.consensus_public_key
+        || original.validator_network_public_key == rotated.validator_network_public_key
+        || original.full_node_network_public_key == rotated.full_node_network_public_key
+    {
+        return Err(CliError::CommandArgumentError(
+            "Rotated keys are the same as the original keys, use a different --random-seed"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Hex encodes bytes as a payload argument, as the REST API accepts a `vector<u8>`
+fn bytes_argument(bytes: Vec<u8>) -> serde_json::Value {
+    serde_json::Value::String(HexEncodedBytes(bytes).to_string())
+}
+
+/// Builds the payload of the transaction rotating the consensus key of `pool_address`, in the
+/// JSON the REST API accepts
+pub fn rotate_consensus_key_payload(
+    pool_address: AccountAddress,
+    identity: &PublicIdentity,
+) -> CliTypedResult<TransactionPayload> {
+    let missing =
+        |name: &str| CliError::UnexpectedError(format!("Rotated identity has no {}", name));
+    let consensus_public_key = identity
+        .consensus_public_key
+        .as_ref()
+        .ok_or_else(|| missing("consensus public key"))?;
+    let proof_of_possession = identity
+        .consensus_proof_of_possession
+        .as_ref()
+        .ok_or_else(|| missing("consensus proof of possession"))?;
+    let payload = EntryFunctionPayload {
+        function: EntryFunctionId::from_str(ROTATE_CONSENSUS_KEY_FUNCTION)?,
+        type_arguments: vec![],
+        arguments: vec![
+            serde_json::Value::String(pool_address.to_hex_literal()),
+            bytes_argument(consensus_public_key.to_bytes().to_vec()),
+            bytes_argument(proof_of_possession.to_bytes().to_vec()),
+        ],
+    };
+    Ok(TransactionPayload::EntryFunctionPayload(payload))
+}
+
+/// Builds the payload of the transaction updating the network addresses of `pool_address` to the
+/// network keys of `identity`, in the JSON the REST API accepts
+///
+/// The addresses are BCS encoded, as `UpdateValidatorNetworkAddresses` submits them.
+pub fn update_network_addresses_payload(
+    pool_address: AccountAddress,
+    identity: &PublicIdentity,
+    validator_host: &HostAndPort,
+    full_node_host: Option<&HostAndPort>,
+) -> CliTypedResult<TransactionPayload> {
+    let missing =
+        |name: &str| CliError::UnexpectedError(format!("Rotated identity has no {}", name));
+    let network_address = |host: &HostAndPort, key| {
+        host.as_network_address(key).map_err(|err| {
+            CliError::CommandArgumentError(format!(
+                "Invalid host {}:{}: {}",
+                host.host, host.port, err
+            ))
+        })
+    };
+    let validator_network_public_key = identity
+        .validator_network_public_key
+        .ok_or_else(|| missing("validator network public key"))?;
+    let validator_addresses = vec![network_address(
+        validator_host,
+        validator_network_public_key,
+    )?];
+    let full_node_addresses = match full_node_host {
+        Some(host) => {
+            let full_node_network_public_key = identity
+                .full_node_network_public_key
+                .ok_or_else(|| missing("fullnode network public key"))?;
+            vec![network_address(host, full_node_network_public_key)?]
+        }
+        None => vec![],
+    };
+    let encode = |addresses| {
+        bcs::to_bytes(&addresses).map_err(|err| CliError::BCS("network addresses", err))
+    };
+    let payload = EntryFunctionPayload {
+        function: EntryFunctionId::from_str(UPDATE_NETWORK_ADDRESSES_FUNCTION)?,
+        type_arguments: vec![],
+        arguments: vec![
+            serde_json::Value::String(pool_address.to_hex_literal()),
+            bytes_argument(encode(validator_addresses)?),
+            bytes_argument(encode(full_node_addresses)?),
+        ],
+    };
+    Ok(TransactionPayload::EntryFunctionPayload(payload))
+}
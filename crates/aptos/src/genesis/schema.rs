@@ -0,0 +1,108 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::{check_if_file_exists, write_to_file},
+    },
+    CliCommand,
+};
+use aptos_genesis::{config::Layout, keys::PublicIdentity};
+use async_trait::async_trait;
+use clap::{ArgEnum, Parser};
+use schemars::{schema::RootSchema, schema_for};
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    str::FromStr,
+};
+use vm_genesis::{AccountMap, EmployeeAccountMap};
+
+/// Files read by the genesis commands, which have a JSON schema
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SchemaFile {
+    /// The layout uploaded by `SetupGit`
+    Layout,
+    /// The mainnet balances, in the repository or given by `--balances-file`
+    Balances,
+    /// The mainnet employee vesting accounts
+    EmployeeVestingAccounts,
+    /// The public keys file written by `GenerateKeys`
+    PublicIdentity,
+}
+
+impl SchemaFile {
+    /// The schema of the file, generated from the type it's read into
+    pub fn schema(self) -> RootSchema {
+        match self {
+            SchemaFile::Layout => schema_for!(Layout),
+            SchemaFile::Balances => schema_for!(Vec<AccountMap>),
+            SchemaFile::EmployeeVestingAccounts => schema_for!(Vec<EmployeeAccountMap>),
+            SchemaFile::PublicIdentity => schema_for!(PublicIdentity),
+        }
+    }
+}
+
+impl Display for SchemaFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            SchemaFile::Layout => "layout",
+            SchemaFile::Balances => "balances",
+            SchemaFile::EmployeeVestingAccounts => "employee-vesting-accounts",
+            SchemaFile::PublicIdentity => "public-identity",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for SchemaFile {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "layout" => Ok(SchemaFile::Layout),
+            "balances" => Ok(SchemaFile::Balances),
+            "employee-vesting-accounts" => Ok(SchemaFile::EmployeeVestingAccounts),
+            "public-identity" => Ok(SchemaFile::PublicIdentity),
+            _ => Err("Invalid schema file. Valid values are layout, balances, \
+                 employee-vesting-accounts, public-identity"),
+        }
+    }
+}
+
+/// Print the JSON schema of a file read by the genesis commands
+///
+/// The schemas are generated from the types the files are read into, so they always match what
+/// the commands accept.  Editors and tooling can use them to check files before `SetupGit` or
+/// `GenerateGenesis` reads them.
+#[derive(Parser)]
+pub struct PrintSchema {
+    /// File to print the schema of: [layout, balances, employee-vesting-accounts, public-identity]
+    #[clap(long)]
+    pub(crate) file: SchemaFile,
+    /// Path to write the schema to, or `-` for stdout
+    #[clap(long, parse(from_os_str), default_value = "-")]
+    pub(crate) output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for PrintSchema {
+    fn command_name(&self) -> &'static str {
+        "PrintSchema"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        check_if_file_exists(&self.output_file, self.prompt_options)?;
+        let schema = serde_json::to_string_pretty(&self.file.schema())
+            .map_err(|err| CliError::UnexpectedError(format!("Invalid schema: {}", err)))?;
+        write_to_file(
+            &self.output_file,
+            &self.output_file.display().to_string(),
+            schema.as_bytes(),
+        )
+    }
+}
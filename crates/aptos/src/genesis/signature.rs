@@ -0,0 +1,150 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult, PromptOptions},
+        utils::read_from_file,
+    },
+    genesis::{
+        encrypt::{decrypt, EncryptedFile, PassphraseOptions, ENCRYPTED_EXTENSION},
+        error::{is_not_found, GenesisError},
+        get_config,
+        git::{from_yaml, Client},
+    },
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519Signature},
+    PrivateKey, Signature, SigningKey,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_genesis::{
+    config::{Layout, ValidatorConfiguration},
+    keys::PrivateIdentity,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// File in a validator's directory holding its owner's signature of its configuration
+pub const CONFIG_SIGNATURE_FILE: &str = "config.sig";
+
+/// What the owner of a validator signs, being its configuration as genesis reads it from the
+/// owner and operator files
+///
+/// The username is signed along with it, so that a signed configuration can't be copied into
+/// another user's directory.
+#[derive(Clone, Debug, Deserialize, Serialize, CryptoHasher, BCSCryptoHash)]
+pub struct ValidatorConfigurationMessage {
+    pub username: String,
+    pub config: ValidatorConfiguration,
+}
+
+/// Reads the owner's account key from the private keys file generated by `GenerateKeys`
+///
+/// Files written by `GenerateKeys --encrypt`, ending in `.encrypted`, are decrypted with the
+/// passphrase, which is prompted for unless it's given in a file.
+pub(crate) fn read_owner_private_key(
+    private_keys_file: &Path,
+    passphrase_options: &PassphraseOptions,
+) -> CliTypedResult<Ed25519PrivateKey> {
+    let mut contents = read_from_file(private_keys_file)?;
+    if private_keys_file.extension() == Some(OsStr::new(ENCRYPTED_EXTENSION)) {
+        let encrypted: EncryptedFile = from_yaml(&String::from_utf8(contents)?)?;
+        let passphrase = passphrase_options.passphrase(PromptOptions::default(), false)?;
+        contents = decrypt(
+            &passphrase,
+            &encrypted,
+            &private_keys_file.display().to_string(),
+        )?;
+    }
+    let private_identity: PrivateIdentity = from_yaml(&String::from_utf8(contents)?)?;
+    Ok(private_identity.account_private_key)
+}
+
+/// Signs the configuration of `username` in the repository with its owner's account key, and
+/// writes the signature alongside it
+pub(crate) fn sign_config(
+    client: &Client,
+    username: &str,
+    owner_private_key: &Ed25519PrivateKey,
+) -> CliTypedResult<PathBuf> {
+    let config = get_config(client, username)?;
+    if config.owner_account_public_key != owner_private_key.public_key() {
+        return Err(CliError::CommandArgumentError(format!(
+            "Owner private key does not match the owner_account_public_key of {}",
+            username
+        )));
+    }
+    let signature = owner_private_key.sign(&ValidatorConfigurationMessage {
+        username: username.to_string(),
+        config,
+    });
+    let signature_file = PathBuf::from(username).join(CONFIG_SIGNATURE_FILE);
+    client.put(&signature_file, &signature)?;
+    Ok(signature_file)
+}
+
+/// Checks every validator's configuration is signed by the owner key registered for its user in
+/// the layout
+///
+/// The owner keys are registered by the coordinator from the users' public identity files, apart
+/// from the configurations, so that whoever can write a configuration can't also swap in their
+/// own key to sign it with.  This proves the configuration wasn't changed since its owner signed
+/// it.  Every unsigned or mis-signed configuration is reported at once.
+pub fn verify_config_signatures(client: &Client, layout: &Layout) -> CliTypedResult<()> {
+    let failures: Vec<_> = layout
+        .sorted_users()
+        .into_iter()
+        .filter_map(|user| {
+            verify_config_signature(client, layout, user)
+                .err()
+                .map(|failure| format!("{}: {}", user, failure))
+        })
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(GenesisError::ConfigSignatureInvalid(failures).into())
+    }
+}
+
+fn verify_config_signature(client: &Client, layout: &Layout, username: &str) -> Result<(), String> {
+    let owner_public_key = layout.owner_public_keys.get(username).ok_or_else(|| {
+        "no owner key is registered for the user in the layout's owner_public_keys, register the \
+         account key of their public identity file"
+            .to_string()
+    })?;
+    let config = get_config(client, username).map_err(|err| err.to_string())?;
+    if config.owner_account_public_key != *owner_public_key {
+        return Err(format!(
+            "owner_account_public_key {} is not the owner key {} registered in the layout",
+            config.owner_account_public_key, owner_public_key
+        ));
+    }
+    let signature_file = PathBuf::from(username).join(CONFIG_SIGNATURE_FILE);
+    let signature: Ed25519Signature = match client.get(&signature_file) {
+        Ok(signature) => signature,
+        Err(err) if is_not_found(&err) => {
+            return Err(format!(
+                "{} is missing, sign the configuration with SetValidatorConfiguration \
+                 --owner-private-keys-file",
+                signature_file.display()
+            ))
+        }
+        Err(err) => return Err(err.to_string()),
+    };
+    let message = ValidatorConfigurationMessage {
+        username: username.to_string(),
+        config,
+    };
+    signature.verify(&message, owner_public_key).map_err(|_| {
+        format!(
+            "{} is not a signature of the configuration by owner key {}",
+            signature_file.display(),
+            owner_public_key
+        )
+    })
+}
@@ -0,0 +1,164 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::read_from_file,
+    },
+    genesis::{
+        get_validator_configs,
+        git::{from_yaml, GitOptions, LAYOUT_FILE},
+    },
+    CliCommand,
+};
+use aptos_genesis::config::{Layout, ValidatorConfiguration};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Simulate the validator set genesis would start with
+///
+/// Every validator in the Git repository joins the validator set at genesis if its stake is
+/// within the layout's minimum and maximum stake, otherwise genesis fails.  With a what-if file,
+/// the stakes of some validators are overridden, without changing their configuration, and the
+/// validators whose membership or stake would change are listed.
+#[derive(Parser)]
+pub struct SimulateValidatorSet {
+    /// Path to a YAML map of user names to the stake to simulate them with
+    #[clap(long, parse(from_os_str))]
+    pub(crate) what_if_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// A validator as it would be at genesis
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SimulatedValidator {
+    pub user: String,
+    pub owner_account_address: AccountAddress,
+    pub stake_amount: u64,
+    pub active: bool,
+    /// Why the validator is left out of the validator set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A validator whose stake or membership differs between the configured and what-if stakes
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ValidatorSetChange {
+    pub user: String,
+    pub stake_before: u64,
+    pub stake_after: u64,
+    pub active_before: bool,
+    pub active_after: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ValidatorSetSimulation {
+    pub min_stake: u64,
+    pub max_stake: u64,
+    /// Validators with the what-if stakes applied
+    pub validators: Vec<SimulatedValidator>,
+    pub active_voting_power: u128,
+    /// Differences caused by the what-if stakes, empty without a what-if file
+    pub changes: Vec<ValidatorSetChange>,
+}
+
+#[async_trait]
+impl CliCommand<ValidatorSetSimulation> for SimulateValidatorSet {
+    fn command_name(&self) -> &'static str {
+        "SimulateValidatorSet"
+    }
+
+    async fn execute(self) -> CliTypedResult<ValidatorSetSimulation> {
+        let overrides: BTreeMap<String, u64> = if let Some(ref what_if_file) = self.what_if_file {
+            from_yaml(&String::from_utf8(read_from_file(what_if_file)?)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        let client = self.git_options.get_client()?;
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        let validators = get_validator_configs(&client, &layout)?;
+        if let Some(user) = overrides.keys().find(|user| !layout.users.contains(user)) {
+            return Err(CliError::CommandArgumentError(format!(
+                "What-if stake given for {}, which is not a user in the layout",
+                user
+            )));
+        }
+
+        let configured = simulate_validator_set(&layout, &validators, &BTreeMap::new());
+        let what_if = simulate_validator_set(&layout, &validators, &overrides);
+        let changes = configured
+            .iter()
+            .zip(&what_if)
+            .filter(|(before, after)| before != after)
+            .map(|(before, after)| ValidatorSetChange {
+                user: before.user.clone(),
+                stake_before: before.stake_amount,
+                stake_after: after.stake_amount,
+                active_before: before.active,
+                active_after: after.active,
+            })
+            .collect();
+
+        Ok(ValidatorSetSimulation {
+            min_stake: layout.min_stake,
+            max_stake: layout.max_stake,
+            active_voting_power: what_if
+                .iter()
+                .filter(|validator| validator.active)
+                .map(|validator| validator.stake_amount as u128)
+                .sum(),
+            validators: what_if,
+            changes,
+        })
+    }
+}
+
+/// Determines which validators join the validator set at genesis, with the stakes of the users in
+/// `overrides` replaced
+pub fn simulate_validator_set(
+    layout: &Layout,
+    validators: &[ValidatorConfiguration],
+    overrides: &BTreeMap<String, u64>,
+) -> Vec<SimulatedValidator> {
+    layout
+        .users
+        .iter()
+        .zip(validators)
+        .map(|(user, validator)| {
+            let stake_amount = overrides
+                .get(user)
+                .copied()
+                .unwrap_or(validator.stake_amount);
+            let reason = if stake_amount < layout.min_stake {
+                Some(format!(
+                    "Stake is below the minimum stake {}",
+                    layout.min_stake
+                ))
+            } else if stake_amount > layout.max_stake {
+                Some(format!(
+                    "Stake is above the maximum stake {}",
+                    layout.max_stake
+                ))
+            } else {
+                None
+            };
+            SimulatedValidator {
+                user: user.clone(),
+                owner_account_address: validator.owner_account_address,
+                stake_amount,
+                active: reason.is_none(),
+                reason,
+            }
+        })
+        .collect()
+}
@@ -1,129 +1,5035 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::OptionalPoolAddressArgs;
-use crate::common::utils::read_from_file;
+use crate::common::types::{
+    CliError, CliTypedResult, CommandOutput, EncodingType, OptionalPoolAddressArgs, OutputFormat,
+};
+use crate::common::utils::{
+    is_stdio, read_from_file, read_from_reader, write_bcs_to_file, write_to_user_only_file,
+    write_to_writer, wrote_to_stdout, STDIO_PATH, TEMP_FILE_PREFIX,
+};
+use crate::genesis::balances::{
+    parse_balances_csv, read_balances_files, BalanceUnit, OCTAS_PER_APT,
+};
+use crate::genesis::batch::{SetValidatorConfigurations, UserDirectory};
+use crate::genesis::bench::BenchGenesis;
+use crate::genesis::decode::{decode_transaction, read_transaction, DecodeTransaction};
+use crate::genesis::diff::{
+    check_reproducible, decode_genesis, describe_state_key, diff_genesis, diff_genesis_state,
+    genesis_state, read_genesis, DiffGenesis, StateValueDiffKind,
+};
+use crate::genesis::doctor::GenesisDoctor;
+use crate::genesis::dump::{summarize_genesis, GenesisOutputFormat, GENESIS_JSON_FILE};
+use crate::genesis::encrypt::{
+    encrypted_file_path, write_encrypted_file, DecryptKeys, PassphraseOptions,
+};
+use crate::genesis::error::GenesisError;
+use crate::genesis::estimate::{estimate_genesis_size, BASE_GENESIS_SIZE};
+use crate::genesis::faucet::{validate_faucet, FaucetOptions, DEFAULT_FAUCET_AMOUNT};
+use crate::genesis::fixture::{GenerateCiFixture, LATEST_CI_FIXTURE_VERSION};
 use crate::genesis::git::FRAMEWORK_NAME;
-use crate::genesis::git::{from_yaml, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE};
-use crate::genesis::keys::{GenerateLayoutTemplate, PUBLIC_KEYS_FILE};
+use crate::genesis::git::{
+    from_yaml, to_yaml, Client, GithubRepo, BALANCES_FILE, EMPLOYEE_VESTING_ACCOUNTS_FILE,
+    LAYOUT_FILE, OPERATOR_FILE, OWNER_FILE,
+};
+use crate::genesis::identity::{diff_identity, DiffNodeIdentity};
+use crate::genesis::import::{ImportPublicIdentity, UserHost};
+use crate::genesis::incremental::{ValidatedInputs, VALIDATED_INPUTS_FILE};
+use crate::genesis::interop::{
+    InteropManifest, VerifyInteropManifest, INTEROP_MANIFEST_FILE, INTEROP_MANIFEST_VERSION,
+};
+use crate::genesis::keys::{
+    check_reachability, read_public_identity_file, GenerateLayoutTemplate,
+    ValidatorConfigurationSummary, PRIVATE_FILES, PRIVATE_KEYS_FILE, PUBLIC_KEYS_FILE,
+    VALIDATOR_FILE, VFN_FILE,
+};
+use crate::genesis::layout::AddValidatorToLayout;
+use crate::genesis::local::{GenerateLocalTestnetGenesis, LOCAL_REPOSITORY_DIR};
+use crate::genesis::manifest::{SignedGenesisManifest, VerifyManifest, MANIFEST_FILE};
+use crate::genesis::onboarding::GenerateOnboardingSheets;
+use crate::genesis::progress::{GenesisProgress, ProgressReporter};
+use crate::genesis::redact::{ensure_layout_not_redacted, RedactLayout, REDACTED_NOTICE};
+use crate::genesis::resume::GENESIS_INPUTS_FILE;
+use crate::genesis::roster::SetValidatorConfigurationsFromRoster;
+use crate::genesis::rotate::{
+    RotateValidatorKeys, NETWORK_ROTATION_PAYLOAD_FILE, ROTATION_PAYLOAD_FILE,
+};
+use crate::genesis::schema::{PrintSchema, SchemaFile};
+use crate::genesis::signature::CONFIG_SIGNATURE_FILE;
+use crate::genesis::simulate::{SimulateValidatorSet, ValidatorSetChange};
+use crate::genesis::timeout::OutputTracker;
+use crate::genesis::upload::upload_configuration;
+use crate::genesis::validate::ValidateValidatorConfiguration;
+use crate::genesis::validator_set::{
+    genesis_validator_set, ShowGenesisValidators, ValidatorSetFormat, ValidatorStatus,
+};
+use crate::genesis::verify::{
+    verify_genesis, verify_waypoint, GenesisVerificationError, VerifyGenesis, VerifyWaypoint,
+    WaypointVerificationError,
+};
+use crate::genesis::vesting::{
+    generate_employee_vesting, EmployeeVestingBuilder, EmployeeVestingGroup, VestingScheduleShape,
+};
+use crate::genesis::{
+    fetch_genesis_info, get_config, read_waypoint, stake_concentration_warnings, validate_stakes,
+    validate_total_supply, ExcludedValidator, GENESIS_FILE, MAX_BUFFERED_GENESIS_SIZE,
+    WAYPOINT_FILE,
+};
 use crate::{
     common::{
-        types::{PromptOptions, RngArgs},
+        types::{OutputOptions, PromptOptions, RngArgs},
         utils::write_to_file,
     },
     genesis::{
         git::{GitOptions, SetupGit},
         keys::{GenerateKeys, SetValidatorConfiguration},
-        GenerateGenesis,
+        GenerateGenesis, GenerateGenesisOutput,
     },
     CliCommand,
 };
+use aptos_config::config::IdentityBlob;
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-    PrivateKey,
+    hash::CryptoHash,
+    HashValue, PrivateKey, ValidCryptoMaterialStringExt,
+};
+use aptos_genesis::config::{
+    HostAndPort, Layout, StringOperatorConfiguration, StringOwnerConfiguration,
+    ValidatorConfiguration, LAYOUT_SCHEMA,
 };
-use aptos_genesis::config::{HostAndPort, Layout};
+use aptos_genesis::genesis_builder::GenesisBuilder;
+use aptos_genesis::keys::{generate_key_objects, PublicIdentity, PUBLIC_IDENTITY_VERSION};
+use aptos_github_client::Client as GithubClient;
 use aptos_keygen::KeyGen;
 use aptos_temppath::TempPath;
+use aptos_types::access_path::AccessPath;
 use aptos_types::account_address::AccountAddress;
+use aptos_types::account_config::{aptos_test_root_address, CoinInfoResource, CoinStoreResource};
 use aptos_types::chain_id::ChainId;
+use aptos_types::network_address::NetworkAddress;
+use aptos_types::on_chain_config::{FeatureFlag, GasScheduleV2, OnChainConfig, ScheduledFeatures};
+use aptos_types::stake_pool::StakePool;
+use aptos_types::state_store::{state_key::StateKey, table::TableHandle};
+use aptos_types::transaction::{Transaction, WriteSetPayload};
+use aptos_types::waypoint::Waypoint;
+use aptos_types::write_set::{TransactionWrite, WriteSet};
+use clap::{CommandFactory, Parser};
+use framework::ReleaseBundle;
+use futures::{stream, StreamExt};
+use goldenfile::Mint;
+use move_deps::move_binary_format::{
+    access::ModuleAccess, file_format::Visibility, CompiledModule,
+};
+use move_deps::move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    move_resource::MoveResource,
+};
+use serde::de::DeserializeOwned;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ffi::OsStr,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
+    process::Command,
     str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use vm_genesis::{
+    AccountMap, EmployeeAccountMap, GenesisCoin, GenesisCoinHolder, GenesisCollection,
+    GenesisToken, ScheduledFeature, Validator, ValidatorWithCommissionRate,
+    MAX_TRANSACTION_SIZE_RANGE, NATIVE_COIN_NAME,
 };
-use vm_genesis::{AccountMap, EmployeeAccountMap};
 
 /// Test the E2E genesis flow since it doesn't require a node to run
 #[tokio::test]
-async fn test_genesis_e2e_flow() {
+async fn test_genesis_e2e_flow() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let git_options = workspace.git_options();
+
+    // Now generate genesis
+    let output = workspace.generate_genesis(false).await;
+
+    // The waypoint is returned, along with the files written and the validators in genesis
+    let genesis_file = workspace.genesis_path();
+    let waypoint_file = workspace.waypoint_path();
+    assert_eq!(
+        output.files[..2],
+        [genesis_file.clone(), waypoint_file.clone()]
+    );
+    assert_eq!(read_waypoint(&waypoint_file).unwrap(), output.waypoint);
+    assert_eq!(output.validators, 2);
+    assert_eq!(output.total_stake, 2 * 100_000_000_000_000);
+
+    // Every validator's stake is active, and operated and voted by its owner
+    let genesis = read_genesis(&genesis_file).unwrap();
+    for user in ["user-0", "user-1"] {
+        let owner = owner_address(&git_options, user);
+        let stake_pool: StakePool = assert_genesis_resource(&genesis, owner);
+        assert_eq!(stake_pool.active, 100_000_000_000_000);
+        assert_eq!(stake_pool.pending_active, 0);
+        assert_eq!(stake_pool.operator_address, owner);
+        assert_eq!(stake_pool.delegated_voter, owner);
+    }
+}
+
+#[tokio::test]
+async fn test_generate_local_testnet_genesis() {
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let faucet = AccountAddress::from_hex_literal("0xfa").unwrap();
+    let generate = |num_validators| GenerateLocalTestnetGenesis {
+        num_validators,
+        output_dir: Some(output_dir.path().to_path_buf()),
+        faucet_options: FaucetOptions {
+            faucet_account: Some(faucet),
+            faucet_amount: None,
+        },
+        rng_args: RngArgs::from_seed([7; 32]),
+        prompt_options: PromptOptions::yes(),
+    };
+    assert!(matches!(
+        generate(0).execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+    let mut amount_only = generate(4);
+    amount_only.faucet_options = FaucetOptions {
+        faucet_account: None,
+        faucet_amount: Some(1),
+    };
+    assert!(matches!(
+        amount_only.execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+    let testnet = generate(4).execute().await.unwrap();
+
+    // Genesis has every validator, each listening on its own local port
+    let waypoint_file = output_dir.path().join(WAYPOINT_FILE);
+    assert_eq!(read_waypoint(&waypoint_file).unwrap(), testnet.waypoint);
+    assert!(testnet.root_key_file.exists());
+    let genesis = read_genesis(&testnet.genesis_file).unwrap();
+    let summary = summarize_genesis(&genesis, Some(faucet)).unwrap();
+    assert_eq!(summary.validators.len(), 4);
+    // along with the faucet, which isn't one of them, and is labelled among the balances
+    let faucet_account = testnet.faucet.as_ref().unwrap();
+    assert_eq!(faucet_account.account_address, faucet);
+    assert_eq!(summary.faucet.as_ref(), Some(faucet_account));
+    assert_eq!(summary.balances.get(&faucet), Some(&DEFAULT_FAUCET_AMOUNT));
+    assert!(summary
+        .validators
+        .iter()
+        .all(|validator| validator.account_address != faucet));
+    let validator_set = ShowGenesisValidators {
+        genesis_file: Some(testnet.genesis_file.clone()),
+        format: ValidatorSetFormat::Table,
+        git_options: GitOptions::default(),
+        faucet_account: Some(faucet),
+    }
+    .execute()
+    .await
+    .unwrap();
+    assert_eq!(validator_set.faucet.as_ref(), Some(faucet_account));
+    assert!(validator_set
+        .validators
+        .iter()
+        .all(|validator| validator.pool_address != faucet));
+    assert!(validator_set.to_string().contains(&format!(
+        "faucet: {}, balance: {}",
+        faucet.to_hex_literal(),
+        DEFAULT_FAUCET_AMOUNT
+    )));
+    // An account genesis didn't fund isn't its faucet
+    let unfunded = AccountAddress::from_hex_literal("0xfb").unwrap();
+    assert!(summarize_genesis(&genesis, Some(unfunded)).is_err());
+    for port in 6180..6184 {
+        let prefix = format!("/dns/localhost/tcp/{}/", port);
+        assert!(summary
+            .validators
+            .iter()
+            .any(|validator| validator.validator_network_addresses[0].starts_with(&prefix)));
+    }
+
+    // Each validator's identity is the one it was configured with in genesis
+    let git_options = GitOptions {
+        local_repository_dir: Some(output_dir.path().join(LOCAL_REPOSITORY_DIR)),
+        ..GitOptions::default()
+    };
+    assert_eq!(testnet.validator_dirs.len(), 4);
+    for (index, validator_dir) in testnet.validator_dirs.iter().enumerate() {
+        DiffNodeIdentity {
+            username: format!("validator-{}", index),
+            identity_file: Some(validator_dir.join(VALIDATOR_FILE)),
+            full_node: false,
+            git_options: git_options.clone(),
+        }
+        .execute()
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_genesis_workspace_cleanup() {
+    let workspace = GenesisWorkspace::with_users(1).await;
+    workspace.generate_genesis(false).await;
+    assert!(workspace.layout_path().exists());
+    assert!(workspace.public_keys_path(0).exists());
+    assert!(workspace.genesis_path().exists());
+    let dirs = [
+        workspace.keys_dir().to_path_buf(),
+        workspace.git_dir().to_path_buf(),
+        workspace.output_dir().to_path_buf(),
+    ];
+    drop(workspace);
+    for dir in &dirs {
+        assert!(!dir.exists(), "{} should be removed", dir.display());
+    }
+
+    // A flow which panics part way through leaves nothing behind either
+    let mut output_dir = None;
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let workspace = GenesisWorkspace::new();
+        output_dir = Some(workspace.output_dir().to_path_buf());
+        write_to_file(&workspace.genesis_path(), GENESIS_FILE, b"partial").unwrap();
+        panic!("genesis failed part way through");
+    }));
+    assert!(result.is_err());
+    assert!(!output_dir.unwrap().exists());
+}
+
+#[tokio::test]
+async fn test_generate_keys_deterministic() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let other_dir = TempPath::new();
+    other_dir.create_as_dir().unwrap();
+
+    // The same seed generates the same identity
+    let keys_file = generate_keys(dir.path(), 7).await.join(PUBLIC_KEYS_FILE);
+    let other_keys_file = generate_keys(other_dir.path(), 7)
+        .await
+        .join(PUBLIC_KEYS_FILE);
+    let keys = read_from_file(&keys_file).unwrap();
+    assert_eq!(keys, read_from_file(&other_keys_file).unwrap());
+
+    // Identities which recorded the Ed25519 key type of their account key are still read
+    let keys = String::from_utf8(keys).unwrap();
+    let identity = read_public_identity_file(&keys_file).unwrap();
+    let typed: PublicIdentity = from_yaml(&format!("{}key_type: ed25519\n", keys)).unwrap();
+    assert_eq!(typed.account_address, identity.account_address);
+}
+
+#[tokio::test]
+async fn test_set_validator_configuration_bounds() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let layout: Layout = git_options
+        .clone()
+        .get_client()
+        .unwrap()
+        .get(Path::new(LAYOUT_FILE))
+        .unwrap();
+    let configure = |stake_amount, commission_percentage| SetValidatorConfiguration {
+        username: "user-0".to_string(),
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+        owner_public_identity_file: Some(dir.path().join("0").join(PUBLIC_KEYS_FILE)),
+        validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+        stake_amount,
+        full_node_host: None,
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        commission_percentage,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
+    };
+
+    for commission_percentage in [0, 100] {
+        let summary = configure(layout.min_stake, commission_percentage)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(summary.commission_percentage, commission_percentage);
+    }
+    let rejected =
+        |result: CliTypedResult<ValidatorConfigurationSummary>, expected: &str| match result {
+            Err(CliError::CommandArgumentError(err)) => assert!(err.contains(expected), "{}", err),
+            result => panic!("Expected {} to be rejected, got {:?}", expected, result),
+        };
+    rejected(
+        configure(layout.min_stake, 101).execute().await,
+        "--commission-percentage 101",
+    );
+
+    // The stake must be within the layout's maximum, and is only warned of below its minimum
+    configure(layout.max_stake, 0).execute().await.unwrap();
+    let summary = configure(layout.min_stake - 1, 0).execute().await.unwrap();
+    assert_eq!(summary.stake_amount, layout.min_stake - 1);
+    rejected(
+        configure(layout.max_stake + 1, 0).execute().await,
+        "max_stake",
+    );
+}
+
+#[tokio::test]
+async fn test_set_validator_configurations() {
+    const NUM_USERS: u8 = 10;
+    let keys_dir = TempPath::new();
+    keys_dir.create_as_dir().unwrap();
+    let users = generate_users_keys(NUM_USERS, keys_dir.path(), KEY_GENERATION_CONCURRENCY).await;
+    let names: Vec<_> = (0..NUM_USERS)
+        .map(|index| format!("user-{}", index))
+        .collect();
+    let git_options = setup_git_dir(
+        git_options(),
+        &root_private_key(NUM_USERS),
+        names.clone(),
+        ChainId::test(),
+    )
+    .await;
+    let configure = |user_dirs: Vec<UserDirectory>| SetValidatorConfigurations {
+        validator_hosts: (0..user_dirs.len())
+            .map(|index| HostAndPort::from_str(&format!("localhost:{}", 6180 + index)).unwrap())
+            .collect(),
+        user_dirs,
+        stake_amount: DEFAULT_STAKE_AMOUNT,
+        commission_percentage: 0,
+        check_reachability: false,
+        commit: false,
+        concurrency: 4,
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+    };
+
+    // Every user's configuration lands in the repository, from one call
+    let user_dirs = names
+        .iter()
+        .map(|name| UserDirectory {
+            username: name.clone(),
+            dir: users[name].clone(),
+        })
+        .collect();
+    let summaries = configure(user_dirs).execute().await.unwrap();
+    assert_eq!(summaries.len(), NUM_USERS as usize);
+    let client = git_options.clone().get_client().unwrap();
+    for (name, summary) in names.iter().zip(&summaries) {
+        assert_eq!(&summary.username, name);
+        let config = get_config(&client, name).unwrap();
+        assert_eq!(config.owner_account_address, summary.owner_account_address);
+        assert_eq!(config.stake_amount, DEFAULT_STAKE_AMOUNT);
+    }
+    assert!(client.lock().unwrap().is_some());
+
+    // A user whose configuration fails is reported, without stopping the others
+    let valid = UserDirectory::from_str(users["user-0"].to_str().unwrap()).unwrap();
+    assert_eq!(valid.username, "0");
+    let missing_dir = keys_dir.path().join("missing");
+    let missing = UserDirectory::from_str(&format!("user-10={}", missing_dir.display())).unwrap();
+    match configure(vec![valid, missing]).execute().await {
+        Err(CliError::CommandArgumentError(err)) => {
+            assert!(err.contains("the other 1 were written"), "{}", err);
+            assert!(err.contains("\nuser-10: "), "{}", err);
+        }
+        result => panic!("Expected user-10 to fail, got {:?}", result),
+    }
+    assert!(get_config(&client, "0").is_ok());
+    assert!(get_config(&client, "user-10").is_err());
+}
+
+#[tokio::test]
+async fn test_repository_lock() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let git_dir = git_options.local_repository_dir.clone().unwrap();
+    let configure = |stake_amount: u64| SetValidatorConfiguration {
+        username: "user-0".to_string(),
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+        owner_public_identity_file: Some(dir.path().join("0").join(PUBLIC_KEYS_FILE)),
+        validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+        stake_amount,
+        full_node_host: None,
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        commission_percentage: 0,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
+    };
+    let is_locked = |result: &CliTypedResult<ValidatorConfigurationSummary>| {
+        matches!(result, Err(CliError::CommandArgumentError(err))
+            if err.contains("is locked by another process"))
+    };
+
+    // A held lock keeps other writers out until it's released, but not readers
+    let client = git_options.clone().get_client().unwrap();
+    let lock = client.lock().unwrap().unwrap();
+    let result = configure(100_000_000_000_001).execute().await;
+    assert!(is_locked(&result), "{:?}", result);
+    assert!(client.get::<Layout>(Path::new(LAYOUT_FILE)).is_ok());
+    drop(lock);
+    configure(100_000_000_000_001).execute().await.unwrap();
+
+    // Concurrent writers each write their whole configuration, or fail on the lock
+    let writers: Vec<_> = (2..=3)
+        .map(|stake_amount| {
+            let command = configure(100_000_000_000_000 + stake_amount);
+            std::thread::spawn(move || {
+                let result = tokio::runtime::Runtime::new()
+                    .unwrap()
+                    .block_on(command.execute());
+                (stake_amount, result)
+            })
+        })
+        .collect();
+    let mut written = Vec::new();
+    for writer in writers {
+        let (stake_amount, result) = writer.join().unwrap();
+        match result {
+            Ok(_) => written.push(100_000_000_000_000 + stake_amount),
+            result => assert!(is_locked(&result), "{:?}", result),
+        }
+    }
+    assert!(!written.is_empty());
+
+    let owner_config: StringOwnerConfiguration = from_yaml(
+        &String::from_utf8(read_from_file(&git_dir.join("user-0").join(OWNER_FILE)).unwrap())
+            .unwrap(),
+    )
+    .unwrap();
+    let stake_amount: u64 = owner_config.stake_amount.unwrap().parse().unwrap();
+    assert!(written.contains(&stake_amount), "{}", stake_amount);
+    let operator_config: CliTypedResult<StringOperatorConfiguration> =
+        client.get(&Path::new("user-0").join(OPERATOR_FILE));
+    assert!(operator_config.is_ok());
+    assert!(client.lock().unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_validate_validator_configuration() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let git_dir = git_options.local_repository_dir.clone().unwrap();
+    let validate = |identity: &str| ValidateValidatorConfiguration {
+        username: "user-0".to_string(),
+        owner_public_identity_file: Some(dir.path().join(identity).join(PUBLIC_KEYS_FILE)),
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        git_options: git_options.clone(),
+    };
+    let problems = |result: CliTypedResult<ValidatorConfigurationSummary>| match result {
+        Err(CliError::CommandArgumentError(err)) => err,
+        result => panic!("Expected configuration problems, got {:?}", result),
+    };
+
+    let identity = read_public_identity_file(&dir.path().join("0").join(PUBLIC_KEYS_FILE)).unwrap();
+    let summary = validate("0").execute().await.unwrap();
+    assert_eq!(summary.owner_account_address, identity.account_address);
+    assert_eq!(summary.stake_amount, 100_000_000_000_000);
+
+    // Against another validator's identity, none of the keys match
+    let err = problems(validate("1").execute().await);
+    for field in [
+        "owner_account_address",
+        "owner_account_public_key",
+        "operator_account_address",
+        "voter_account_public_key",
+        "consensus_public_key",
+        "validator_network_public_key",
+    ] {
+        assert!(err.contains(&format!("{} ", field)), "{}", err);
+    }
+    assert!(err.contains("doesn't match"), "{}", err);
+
+    // Break the owner file
+    let owner_file = git_dir.join("user-0").join(OWNER_FILE);
+    let mut owner_config: StringOwnerConfiguration =
+        from_yaml(&String::from_utf8(read_from_file(&owner_file).unwrap()).unwrap()).unwrap();
+    owner_config.voter_account_address = None;
+    owner_config.stake_amount = Some("lots".to_string());
+    owner_config.commission_percentage = Some("150".to_string());
+    write_to_file(
+        &owner_file,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    // Break the operator file, with another validator's proof of possession
+    let operator_file = git_dir.join("user-0").join(OPERATOR_FILE);
+    let read_operator_config = |file: &Path| -> StringOperatorConfiguration {
+        from_yaml(&String::from_utf8(read_from_file(file).unwrap()).unwrap()).unwrap()
+    };
+    let mut operator_config = read_operator_config(&operator_file);
+    operator_config.consensus_proof_of_possession =
+        read_operator_config(&git_dir.join("user-1").join(OPERATOR_FILE))
+            .consensus_proof_of_possession;
+    operator_config.validator_host = HostAndPort::from_str("localhost:0").unwrap();
+    operator_config.full_node_host = Some(HostAndPort::from_str("localhost:6182").unwrap());
+    write_to_file(
+        &operator_file,
+        OPERATOR_FILE,
+        serde_yaml::to_string(&operator_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    // Every problem is reported at once
+    let err = problems(validate("0").execute().await);
+    assert!(err.contains("has 6 problems"), "{}", err);
+    for problem in [
+        "voter_account_address is missing",
+        "stake_amount is invalid",
+        "commission_percentage 150 is greater than 100",
+        "consensus_proof_of_possession isn't valid",
+        "host localhost has port 0",
+        "full_node_host and full_node_network_public_key must be set together",
+    ] {
+        assert!(err.contains(problem), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn test_diff_node_identity() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let diff = |identity: &str, file: &str, full_node| DiffNodeIdentity {
+        username: "user-0".to_string(),
+        identity_file: Some(dir.path().join(identity).join(file)),
+        full_node,
+        git_options: git_options.clone(),
+    };
+
+    // The identity the validator's keys were generated with matches
+    diff("0", VALIDATOR_FILE, false).execute().await.unwrap();
+
+    // Another validator's identity differs in every key
+    let identity = IdentityBlob::from_file(&dir.path().join("1").join(VALIDATOR_FILE)).unwrap();
+    let config = get_config(&git_options.clone().get_client().unwrap(), "user-0").unwrap();
+    let fields: Vec<_> = diff_identity(&identity, &config, false)
+        .into_iter()
+        .map(|mismatch| mismatch.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "operator_account_address",
+            "operator_account_public_key",
+            "consensus_public_key",
+            "validator_network_public_key",
+        ]
+    );
+    let error = diff("1", VALIDATOR_FILE, false)
+        .execute()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CliError::CommandArgumentError(_)));
+    assert!(error.to_string().contains("in 4 fields"), "{}", error);
+
+    // A fullnode identity in place of the validator's has none of its keys
+    let error = diff("0", VFN_FILE, false).execute().await.unwrap_err();
+    for problem in [
+        "operator_account_public_key: identity has none",
+        "consensus_public_key: identity has none",
+        "validator_network_public_key: identity has",
+    ] {
+        assert!(error.to_string().contains(problem), "{}", error);
+    }
+    assert!(!error.to_string().contains("operator_account_address"));
+
+    // The fullnode wasn't configured, so its network key isn't in genesis
+    let error = diff("0", VFN_FILE, true).execute().await.unwrap_err();
+    assert!(
+        error.to_string().contains("but genesis has none"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_public_identity_versions() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let keys_file = dir.path().join("0").join(PUBLIC_KEYS_FILE);
+    let keys = String::from_utf8(read_from_file(&keys_file).unwrap()).unwrap();
+    let current_version = format!("version: {}\n", PUBLIC_IDENTITY_VERSION);
+    assert!(keys.contains(&current_version));
+    let identity = read_public_identity_file(&keys_file).unwrap();
+    assert_eq!(identity.version, PUBLIC_IDENTITY_VERSION);
+
+    let configure = |keys: String| {
+        let keys_file = dir.path().join("versioned-keys.yaml");
+        write_to_file(&keys_file, PUBLIC_KEYS_FILE, keys.as_bytes()).unwrap();
+        SetValidatorConfiguration {
+            username: "user-0".to_string(),
+            output_options: OutputOptions::default(),
+            git_options: git_options.clone(),
+            owner_public_identity_file: Some(keys_file),
+            validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+            stake_amount: 100_000_000_000_000,
+            full_node_host: None,
+            operator_public_identity_file: None,
+            voter_public_identity_file: None,
+            commission_percentage: 0,
+            check_reachability: false,
+            commit: false,
+            owner_private_keys_file: None,
+            passphrase_options: PassphraseOptions::default(),
+        }
+    };
+
+    // Version 1 files have no version, and are upgraded when read
+    let v1_keys = keys.replace(&current_version, "");
+    let v1_identity = PublicIdentity::from_yaml(&v1_keys).unwrap();
+    assert_eq!(v1_identity.version, PUBLIC_IDENTITY_VERSION);
+    assert_eq!(v1_identity.account_address, identity.account_address);
+    assert_eq!(v1_identity.account_public_key, identity.account_public_key);
+    assert_eq!(
+        v1_identity.consensus_public_key,
+        identity.consensus_public_key
+    );
+    let summary = configure(v1_keys).execute().await.unwrap();
+    assert_eq!(summary.owner_account_address, identity.account_address);
+
+    // Versions newer than the CLI's are rejected, rather than have their new fields dropped
+    for version in [0, PUBLIC_IDENTITY_VERSION + 1] {
+        let newer_keys = keys.replace(&current_version, &format!("version: {}\n", version));
+        let result = configure(newer_keys).execute().await;
+        match result {
+            Err(CliError::UnableToReadFile(_, reason)) => {
+                assert!(reason.contains(&format!("has version {}", version)))
+            }
+            _ => panic!("Expected version {} to be rejected", version),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_genesis_json_output() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+
+    // Genesis generated twice from the same inputs decodes to the same JSON
+    let mut outputs = Vec::new();
+    for _ in 0..2 {
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let files = GenerateGenesis {
+            output_format: GenesisOutputFormat::Json,
+            ..generate_genesis_command(git_options.clone(), output_dir.path().to_path_buf())
+        }
+        .execute()
+        .await
+        .unwrap();
+        let json_file = output_dir.path().join(GENESIS_JSON_FILE);
+        assert!(files.contains(&json_file));
+        assert!(output_dir.path().join("genesis.blob").exists());
+        outputs.push(String::from_utf8(read_from_file(&json_file).unwrap()).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    let json: serde_json::Value = serde_json::from_str(&outputs[0]).unwrap();
+    let address = |address: AccountAddress| serde_json::to_value(address).unwrap();
+    assert_eq!(json["chain_id"], ChainId::test().id());
+    assert!(
+        json["modules"][address(AccountAddress::ONE).as_str().unwrap()]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::Value::from("coin"))
+    );
+    let validators = json["validators"].as_array().unwrap();
+    assert_eq!(validators.len(), 2);
+    for user in ["user-0", "user-1"] {
+        let owner = address(owner_address(&git_options, user));
+        assert!(validators
+            .iter()
+            .any(|validator| validator["account_address"] == owner));
+        assert!(json["balances"].get(owner.as_str().unwrap()).is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_decode_transaction() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    workspace.generate_genesis(false).await;
+
+    // Genesis is summarized by what it writes, rather than by its write set
+    let decoded = DecodeTransaction {
+        transaction_file: workspace.genesis_path(),
+        output_options: OutputOptions::default(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    let genesis = read_genesis(&workspace.genesis_path()).unwrap();
+    assert_eq!(decoded.hash, genesis.hash());
+    assert_eq!(decoded.kind, "genesis");
+    assert!(decoded.transaction.is_none());
+    let summary = decoded.genesis.as_ref().unwrap();
+    assert_eq!(summary.validators.len(), 2);
+    assert!(summary.modules[&AccountAddress::ONE].contains("stake"));
+    let json = serde_json::to_value(&decoded).unwrap();
+    assert_eq!(json["kind"], "genesis");
+    assert_eq!(json["genesis"]["validators"].as_array().unwrap().len(), 2);
+
+    // Other transactions are shown as they were encoded
+    let checkpoint = Transaction::StateCheckpoint(HashValue::random());
+    let checkpoint_file = workspace.output_dir().join("checkpoint.blob");
+    write_to_file(
+        &checkpoint_file,
+        "checkpoint.blob",
+        &bcs::to_bytes(&checkpoint).unwrap(),
+    )
+    .unwrap();
+    let decoded = decode_transaction(&read_transaction(&checkpoint_file).unwrap()).unwrap();
+    assert_eq!(decoded.kind, "state_checkpoint");
+    assert!(decoded.genesis.is_none());
+    assert_eq!(decoded.transaction, Some(checkpoint));
+
+    // Files which aren't a whole transaction are reported as such
+    let mut truncated = read_from_file(&workspace.genesis_path()).unwrap();
+    truncated.truncate(truncated.len() / 2);
+    let truncated_file = workspace.output_dir().join("truncated.blob");
+    write_to_file(&truncated_file, "truncated.blob", &truncated).unwrap();
+    for file in [workspace.waypoint_path(), truncated_file] {
+        match read_transaction(&file) {
+            Err(CliError::UnableToParse("transaction", reason)) => {
+                assert!(
+                    reason.contains("is not a BCS encoded transaction"),
+                    "{}",
+                    reason
+                )
+            }
+            result => panic!("Expected {:?} to be rejected, got {:?}", file, result),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_interop_manifest_round_trip() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let output = GenerateGenesis {
+        manifest: true,
+        ..generate_genesis_command(
+            workspace.git_options(),
+            workspace.output_dir().to_path_buf(),
+        )
+    }
+    .execute()
+    .await
+    .unwrap();
+    let manifest_file = workspace.output_dir().join(INTEROP_MANIFEST_FILE);
+    assert!(output.files.contains(&manifest_file));
+
+    // The manifest describes the genesis it was written with, and verifies against it
+    let verify = |genesis_file| VerifyInteropManifest {
+        manifest_file: manifest_file.clone(),
+        genesis_file,
+    };
+    let manifest = verify(None).execute().await.unwrap();
+    let client = workspace.git_options().get_client().unwrap();
+    assert_eq!(manifest, InteropManifest::read(&manifest_file).unwrap());
+    assert_eq!(manifest.version, INTEROP_MANIFEST_VERSION);
+    assert_eq!(manifest.chain_id, ChainId::test().id());
+    assert_eq!(manifest.waypoint, output.waypoint);
+    assert_eq!(manifest.framework_hash, client.framework_hash().unwrap());
+    assert_eq!(manifest.validators.len(), 2);
+    assert_eq!(manifest.total_voting_power, output.total_stake);
+    assert!(manifest.total_supply.unwrap() >= output.total_stake);
+
+    // A different genesis blob is rejected by its hash
+    let mut tampered = read_from_file(&workspace.genesis_path()).unwrap();
+    tampered.push(0);
+    let tampered_file = workspace.output_dir().join("tampered.blob");
+    write_to_file(&tampered_file, "tampered.blob", &tampered).unwrap();
+    match verify(Some(tampered_file)).execute().await {
+        Err(CliError::UnexpectedError(err)) => assert!(err.contains("SHA-256"), "{}", err),
+        result => panic!(
+            "Expected the tampered genesis to be rejected, got {:?}",
+            result
+        ),
+    }
+
+    // As is a manifest listing another waypoint, or a newer version of the schema
+    let other_waypoint = Waypoint::from_str(&format!("0:{}", HashValue::zero())).unwrap();
+    InteropManifest {
+        waypoint: other_waypoint,
+        ..manifest.clone()
+    }
+    .write(workspace.output_dir())
+    .unwrap();
+    assert!(matches!(
+        verify(None).execute().await,
+        Err(CliError::GenesisError(
+            GenesisError::WaypointMismatch { .. }
+        ))
+    ));
+    InteropManifest {
+        version: INTEROP_MANIFEST_VERSION + 1,
+        ..manifest
+    }
+    .write(workspace.output_dir())
+    .unwrap();
+    match verify(None).execute().await {
+        Err(CliError::UnableToReadFile(_, reason)) => {
+            assert!(reason.contains("only reads up to version"), "{}", reason)
+        }
+        result => panic!(
+            "Expected the newer manifest to be rejected, got {:?}",
+            result
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_generate_genesis_streams_large_genesis() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let generate = |max_buffered_genesis_size| GenerateGenesis {
+        force: true,
+        manifest: true,
+        output_format: GenesisOutputFormat::Json,
+        max_buffered_genesis_size,
+        ..generate_genesis_command(
+            workspace.git_options(),
+            workspace.output_dir().to_path_buf(),
+        )
+    };
+    let buffered = generate(MAX_BUFFERED_GENESIS_SIZE).execute().await.unwrap();
+    let buffered_genesis = read_from_file(&workspace.genesis_path()).unwrap();
+    let buffered_manifest =
+        InteropManifest::read(&workspace.output_dir().join(INTEROP_MANIFEST_FILE)).unwrap();
+
+    // Genesis larger than is buffered is streamed into its file, and encoded again for the
+    // manifest, writing the same outputs
+    let streamed = generate(0).execute().await.unwrap();
+    assert_eq!(streamed.waypoint, buffered.waypoint);
+    assert_eq!(streamed.files, buffered.files);
+    assert_eq!(
+        read_from_file(&workspace.genesis_path()).unwrap(),
+        buffered_genesis
+    );
+    assert_eq!(
+        InteropManifest::read(&workspace.output_dir().join(INTEROP_MANIFEST_FILE)).unwrap(),
+        buffered_manifest
+    );
+    assert_eq!(
+        verify_waypoint(&workspace.genesis_path(), streamed.waypoint).unwrap(),
+        streamed.waypoint
+    );
+}
+
+#[tokio::test]
+async fn test_genesis_chain_ids() {
+    // A simulated mainnet, and a custom chain
+    let mut blobs = Vec::new();
+    for chain_id in [ChainId::from_str("mainnet").unwrap(), ChainId::new(42)] {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let git_options = create_users_on_chain(1, &dir, chain_id).await;
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let output_dir = PathBuf::from(output_dir.path());
+        generate_genesis(git_options, output_dir.clone(), false).await;
+
+        let blob_file = output_dir.join("genesis.blob");
+        let genesis = read_genesis(&blob_file).unwrap();
+        assert_eq!(
+            summarize_genesis(&genesis, None).unwrap().chain_id,
+            chain_id.id()
+        );
+        blobs.push(read_from_file(&blob_file).unwrap());
+    }
+    assert_ne!(blobs[0], blobs[1]);
+}
+
+#[test]
+fn test_genesis_builder_in_memory() {
+    let layout = Layout {
+        root_key: Some(root_private_key(2).public_key()),
+        users: vec!["user-0".to_string(), "user-1".to_string()],
+        ..Layout::default()
+    };
+    let validators = in_memory_validators(2, layout.min_stake);
+    let owners: Vec<_> = validators
+        .iter()
+        .map(|validator| validator.owner_account_address)
+        .collect();
+    let builder = GenesisBuilder::new(
+        layout.clone(),
+        validators,
+        cached_packages::head_release_bundle().clone(),
+    );
+
+    // Genesis is built from the inputs alone, the same each time
+    let (genesis, waypoint) = builder.clone().build().unwrap();
+    assert_eq!(builder.clone().build().unwrap().1, waypoint);
+    assert_eq!(waypoint.version(), 0);
+    let summary = summarize_genesis(&genesis, None).unwrap();
+    assert_eq!(summary.chain_id, layout.chain_id.id());
+    assert_eq!(summary.validators.len(), 2);
+    for owner in owners {
+        assert!(summary
+            .validators
+            .iter()
+            .any(|validator| validator.owner_account_address == owner));
+    }
+
+    // Without accounts, genesis mints with the root key, so needs one
+    let layout = Layout {
+        root_key: None,
+        ..layout
+    };
+    let builder = GenesisBuilder::new(
+        layout,
+        vec![],
+        cached_packages::head_release_bundle().clone(),
+    );
+    assert!(!builder.is_mainnet());
+    assert!(builder.build().is_err());
+}
+
+#[tokio::test]
+async fn test_genesis_independent_of_user_order() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(3, &dir).await;
+    let client = git_options.clone().get_client().unwrap();
+    let mut layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+
+    // The same users, listed in two different orders
+    let mut blobs = Vec::new();
+    for reverse in [false, true] {
+        layout.users.sort();
+        if reverse {
+            layout.users.reverse();
+        }
+        client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let output_dir = PathBuf::from(output_dir.path());
+        generate_genesis(git_options.clone(), output_dir.clone(), false).await;
+        blobs.push(read_from_file(&output_dir.join(GENESIS_FILE)).unwrap());
+    }
+    assert_eq!(blobs[0], blobs[1]);
+    assert_eq!(layout.sorted_users(), vec!["user-0", "user-1", "user-2"]);
+}
+
+#[tokio::test]
+async fn test_genesis_progress() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let progress = {
+        let events = events.clone();
+        ProgressReporter::with_callback(Arc::new(move |progress: &GenesisProgress| {
+            events.lock().unwrap().push(progress.clone())
+        }))
+    };
+
+    let layout_file = TempPath::new();
+    layout_file.create_as_file().unwrap();
+    create_layout_file(
+        layout_file.path(),
+        root_private_key(1).public_key(),
+        vec!["user-0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+    SetupGit {
+        git_options: git_options(),
+        layout_file: PathBuf::from(layout_file.path()),
+        schema_validate: false,
+        framework_bundle: None,
+        init_git: false,
+        progress: progress.clone(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    assert_eq!(
+        events.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![GenesisProgress::WroteLayout { users: 1 }]
+    );
+
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let client = git_options.clone().get_client().unwrap();
+    let framework_hash = client.framework_hash().unwrap();
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let files = GenerateGenesis {
+        progress,
+        ..generate_genesis_command(git_options, output_dir.path().to_path_buf())
+    }
+    .execute()
+    .await
+    .unwrap();
+
+    // Each step is reported once it's done, in order, without warning of the two equal stakes
+    let genesis = read_from_file(&output_dir.path().join(GENESIS_FILE)).unwrap();
+    let waypoint = read_waypoint(&output_dir.path().join(WAYPOINT_FILE)).unwrap();
+    assert_eq!(
+        events.lock().unwrap().clone(),
+        vec![
+            GenesisProgress::LoadedFramework {
+                hash: framework_hash,
+            },
+            GenesisProgress::LoadedValidatorConfigs { validators: 2 },
+            GenesisProgress::GeneratedGenesis {
+                bytes: genesis.len(),
+            },
+            GenesisProgress::ComputedWaypoint { waypoint },
+            GenesisProgress::WroteOutputs { files: files.len() },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_genesis_timeout() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+
+    // Loading the validator configurations is held up well past the timeout
+    let progress = ProgressReporter::with_callback(Arc::new(|progress: &GenesisProgress| {
+        if matches!(progress, GenesisProgress::LoadedFramework { .. }) {
+            std::thread::sleep(Duration::from_secs(5));
+        }
+    }));
+    let result = GenerateGenesis {
+        timeout: Some(1),
+        progress,
+        ..generate_genesis_command(git_options, output_dir.path().to_path_buf())
+    }
+    .execute()
+    .await;
+    match result {
+        Err(CliError::GenesisError(GenesisError::Timeout { secs, phase })) => {
+            assert_eq!(secs, 1);
+            assert_eq!(phase, "loading validator configurations");
+        }
+        Err(err) => panic!("Expected genesis to time out: {}", err),
+        Ok(_) => panic!("Expected genesis to time out"),
+    }
+    assert!(!output_dir.path().join(GENESIS_FILE).exists());
+    assert!(!output_dir.path().join(WAYPOINT_FILE).exists());
+}
+
+#[tokio::test]
+async fn test_estimate_genesis_size() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let estimate = GenerateGenesis {
+        estimate_only: true,
+        ..generate_genesis_command(git_options.clone(), output_dir.path().to_path_buf())
+    }
+    .estimate()
+    .unwrap();
+    assert_eq!(estimate.validators, 2);
+    assert_eq!(estimate.accounts, 0);
+
+    // Nothing is written for the estimate, which is within a small margin of the genesis written
+    assert_eq!(std::fs::read_dir(output_dir.path()).unwrap().count(), 0);
+    generate_genesis(git_options, output_dir.path().to_path_buf(), false).await;
+    let size = read_from_file(&output_dir.path().join(GENESIS_FILE))
+        .unwrap()
+        .len();
+    let margin = size / 10;
+    assert!(
+        estimate.bytes.abs_diff(size) <= margin,
+        "Estimated {} bytes, but genesis is {} bytes",
+        estimate.bytes,
+        size
+    );
+
+    // Each validator and account adds to the estimate
+    let layout = Layout::default();
+    let framework = cached_packages::head_release_bundle().clone();
+    let validators = in_memory_validators(2, layout.min_stake);
+    let balances = vec![AccountMap {
+        account_address: AccountAddress::ONE,
+        balance: 1,
+    }];
+    let empty = estimate_genesis_size(&layout, &framework, &[], &[]).unwrap();
+    assert!(empty > BASE_GENESIS_SIZE);
+    let with_validator = estimate_genesis_size(&layout, &framework, &validators[..1], &[]).unwrap();
+    let with_validators = estimate_genesis_size(&layout, &framework, &validators, &[]).unwrap();
+    let with_balance = estimate_genesis_size(&layout, &framework, &[], &balances).unwrap();
+    assert!(empty < with_validator && with_validator < with_validators);
+    assert!(empty < with_balance);
+}
+
+#[test]
+fn test_output_tracker_cancel() {
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let written = output_dir.path().join(GENESIS_FILE);
+    let outputs = OutputTracker::default();
+    outputs
+        .write(&written, || {
+            write_to_file(&written, GENESIS_FILE, b"genesis")
+        })
+        .unwrap();
+    assert!(written.exists());
+
+    // Cancelling removes what was written, and refuses anything more
+    outputs.cancel();
+    assert!(!written.exists());
+    let refused = output_dir.path().join(WAYPOINT_FILE);
+    assert!(matches!(
+        outputs.write(&refused, || write_to_file(
+            &refused,
+            WAYPOINT_FILE,
+            b"waypoint"
+        )),
+        Err(CliError::AbortedError)
+    ));
+    assert!(!refused.exists());
+}
+
+#[tokio::test]
+async fn test_genesis_reproducibility_check() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    GenerateGenesis {
+        reproducibility_check: true,
+        ..generate_genesis_command(git_options, output_dir.path().to_path_buf())
+    }
+    .execute()
+    .await
+    .unwrap();
+
+    // Diverging genesis is reported by the section which diverged
+    let genesis_bytes = read_from_file(&output_dir.path().join(GENESIS_FILE)).unwrap();
+    let genesis = decode_genesis(&genesis_bytes).unwrap();
+    check_reproducible(&genesis_bytes, &genesis).unwrap();
+    let layout = Layout {
+        root_key: Some(root_private_key(2).public_key()),
+        users: vec!["user-0".to_string(), "user-1".to_string()],
+        chain_id: ChainId::new(42),
+        ..Layout::default()
+    };
+    let validators = in_memory_validators(2, layout.min_stake);
+    let framework = cached_packages::head_release_bundle().clone();
+    let (diverged, _) = GenesisBuilder::new(layout, validators, framework)
+        .build()
+        .unwrap();
+    match check_reproducible(&genesis_bytes, &diverged) {
+        Err(CliError::UnexpectedError(err)) => assert!(err.contains("chain id"), "{}", err),
+        result => panic!(
+            "Expected diverging genesis to be reported, got {:?}",
+            result
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_expected_framework_hash() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let client = git_options.clone().get_client().unwrap();
+    let framework_hash = client.framework_hash().unwrap();
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis = |expected_framework_hash| GenerateGenesis {
+        expected_framework_hash: Some(expected_framework_hash),
+        force: true,
+        ..generate_genesis_command(git_options.clone(), output_dir.path().to_path_buf())
+    };
+    generate_genesis(framework_hash).execute().await.unwrap();
+
+    // A framework changed after its hash was published is rejected
+    let framework_file = git_options
+        .local_repository_dir
+        .clone()
+        .unwrap()
+        .join(FRAMEWORK_NAME);
+    let mut framework = read_from_file(&framework_file).unwrap();
+    let last = framework.len() - 1;
+    framework[last] ^= 1;
+    write_to_file(&framework_file, FRAMEWORK_NAME, &framework).unwrap();
+    match generate_genesis(framework_hash).execute().await {
+        Err(CliError::GenesisError(GenesisError::FrameworkHashMismatch { hash, expected })) => {
+            assert_eq!(expected, framework_hash);
+            assert_ne!(hash, expected);
+        }
+        Err(err) => panic!("Expected the framework hash check to fail: {}", err),
+        Ok(_) => panic!("Expected a changed framework to be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_genesis_errors() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let git_dir = git_options.local_repository_dir.clone().unwrap();
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis =
+        || generate_genesis_command(git_options.clone(), PathBuf::from(output_dir.path()));
+
+    // Genesis can't be generated without a framework
+    std::fs::remove_file(git_dir.join(FRAMEWORK_NAME)).unwrap();
+    match generate_genesis().execute().await {
+        Err(CliError::GenesisError(GenesisError::FrameworkMissing(file))) => {
+            assert_eq!(file, FRAMEWORK_NAME)
+        }
+        result => panic!(
+            "Expected the missing framework to be reported, got {:?}",
+            result
+        ),
+    }
+    add_framework_to_dir(&git_dir);
+
+    // Invalid configurations are reported with their users
+    let owner_file = git_dir.join("user-0").join(OWNER_FILE);
+    let owner = std::fs::read_to_string(&owner_file).unwrap();
+    let mut invalid_owner: StringOwnerConfiguration = from_yaml(&owner).unwrap();
+    invalid_owner.stake_amount = Some("not a number".to_string());
+    write_to_file(
+        &owner_file,
+        OWNER_FILE,
+        serde_yaml::to_string(&invalid_owner).unwrap().as_bytes(),
+    )
+    .unwrap();
+    match generate_genesis().execute().await {
+        Err(CliError::GenesisError(GenesisError::ValidatorConfigInvalid(errors))) => {
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(errors[0].starts_with("user-0: "), "{:?}", errors);
+        }
+        result => panic!(
+            "Expected the invalid stake to be reported, got {:?}",
+            result
+        ),
+    }
+
+    // Users yet to configure their validators take precedence, as they have the most to do
+    std::fs::remove_file(git_dir.join("user-1").join(OWNER_FILE)).unwrap();
+    match generate_genesis().execute().await {
+        Err(CliError::GenesisError(GenesisError::MissingValidatorConfig(users))) => {
+            assert_eq!(users, vec!["user-1".to_string()])
+        }
+        result => panic!(
+            "Expected the missing configuration to be reported, got {:?}",
+            result
+        ),
+    }
+
+    // A layout without a root key can't mint for a test network
+    let layout_file = git_dir.join(LAYOUT_FILE);
+    let mut layout = Layout::from_disk(&layout_file).unwrap();
+    layout.root_key = None;
+    write_to_file(
+        &layout_file,
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+    match generate_genesis().execute().await {
+        Err(CliError::GenesisError(GenesisError::LayoutInvalid(reason))) => {
+            assert!(reason.contains("root_key"), "{}", reason)
+        }
+        result => panic!(
+            "Expected the missing root key to be reported, got {:?}",
+            result
+        ),
+    }
+    assert!(!output_dir.path().join(GENESIS_FILE).exists());
+}
+
+#[tokio::test]
+async fn test_genesis_missing_validator_config() {
+    let workspace = GenesisWorkspace::with_users(3).await;
+    let fetch = || {
+        fetch_genesis_info(
+            workspace.git_options(),
+            vec![],
+            vec![],
+            false,
+            None,
+            false,
+            None,
+            &ProgressReporter::default(),
+        )
+    };
+
+    // A user missing either configuration file is reported, even ahead of invalid configurations
+    std::fs::remove_file(workspace.operator_path("user-1")).unwrap();
+    let owner_path = workspace.owner_path("user-2");
+    let mut invalid_owner: StringOwnerConfiguration =
+        from_yaml(&std::fs::read_to_string(&owner_path).unwrap()).unwrap();
+    invalid_owner.stake_amount = Some("not a number".to_string());
+    write_to_file(
+        &owner_path,
+        OWNER_FILE,
+        serde_yaml::to_string(&invalid_owner).unwrap().as_bytes(),
+    )
+    .unwrap();
+    match fetch().err() {
+        Some(CliError::GenesisError(err @ GenesisError::MissingValidatorConfig(_))) => {
+            assert!(err.to_string().contains("user-1"), "{}", err);
+            if let GenesisError::MissingValidatorConfig(users) = err {
+                assert_eq!(users, vec!["user-1".to_string()]);
+            }
+        }
+        err => panic!(
+            "Expected user-1's configuration to be missing, got {:?}",
+            err
+        ),
+    }
+
+    // Once it's submitted, the invalid configuration is reported
+    set_validator_config(
+        "user-1".to_string(),
+        workspace.git_options(),
+        &workspace.keys_dir().join("1"),
+    )
+    .await;
+    match fetch().err() {
+        Some(CliError::GenesisError(GenesisError::ValidatorConfigInvalid(errors))) => {
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(errors[0].starts_with("user-2: "), "{:?}", errors);
+        }
+        err => panic!(
+            "Expected user-2's configuration to be invalid, got {:?}",
+            err
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_genesis_chain_id_mismatch() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let fetch = || {
+        fetch_genesis_info(
+            workspace.git_options(),
+            vec![],
+            vec![],
+            false,
+            None,
+            false,
+            None,
+            &ProgressReporter::default(),
+        )
+    };
+
+    // Configurations record the chain id of the layout they were set against
+    let owner_path = workspace.owner_path("user-1");
+    let mut owner_config: StringOwnerConfiguration =
+        from_yaml(&std::fs::read_to_string(&owner_path).unwrap()).unwrap();
+    assert_eq!(
+        owner_config.chain_id,
+        Some(ChainId::test().id().to_string())
+    );
+    fetch().unwrap();
+
+    // A configuration set against another chain is reported with both chain ids
+    owner_config.chain_id = Some("42".to_string());
+    write_to_file(
+        &owner_path,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+    match fetch().err() {
+        Some(CliError::GenesisError(err @ GenesisError::ChainIdMismatch { .. })) => {
+            let message = err.to_string();
+            assert!(message.contains("user-1"), "{}", message);
+            assert!(message.contains("chain id 42"), "{}", message);
+            assert!(
+                message.contains(&format!("chain id {}", ChainId::test())),
+                "{}",
+                message
+            );
+        }
+        err => panic!("Expected user-1's chain id to mismatch, got {:?}", err),
+    }
+
+    // Configurations which don't record a chain id are left as they are
+    owner_config.chain_id = None;
+    write_to_file(
+        &owner_path,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+    fetch().unwrap();
+}
+
+#[tokio::test]
+async fn test_add_validator_to_layout() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let git_options = workspace.git_options();
+    let client = git_options.clone().get_client().unwrap();
+    let original: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    let add_with_owner = |username: &str, owner_public_identity_file: Option<PathBuf>| {
+        AddValidatorToLayout {
+            username: username.to_string(),
+            owner_public_identity_file,
+            commit: false,
+            git_options: git_options.clone(),
+        }
+        .execute()
+    };
+    let add = |username: &str| add_with_owner(username, None);
+
+    // Each user is appended in the order they're added, rather than sorted, and genesis waits
+    // for their configuration before including them.  Only owner keys given are registered.
+    let mut users = original.users.clone();
+    for (index, username, registered) in [(3, "user-3", true), (2, "user-2", false)] {
+        let keys_dir = generate_keys(workspace.keys_dir(), index).await;
+        let identity_file = keys_dir.join(PUBLIC_KEYS_FILE);
+        users.push(username.to_string());
+        assert_eq!(
+            add_with_owner(username, registered.then(|| identity_file.clone()))
+                .await
+                .unwrap(),
+            users
+        );
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+        assert_eq!(layout.users, users);
+        assert_eq!(
+            layout.owner_public_keys.get(username),
+            registered
+                .then(|| read_public_identity_file(&identity_file)
+                    .unwrap()
+                    .account_public_key)
+                .as_ref()
+        );
+        match fetch_genesis_info(
+            git_options.clone(),
+            vec![],
+            vec![],
+            false,
+            None,
+            false,
+            None,
+            &ProgressReporter::default(),
+        ) {
+            Err(CliError::GenesisError(GenesisError::MissingValidatorConfig(missing))) => {
+                assert_eq!(missing, vec![username.to_string()])
+            }
+            Err(err) => panic!(
+                "Expected {} to be missing a configuration: {}",
+                username, err
+            ),
+            Ok(_) => panic!("Expected {} to be missing a configuration", username),
+        }
+
+        set_validator_config(username.to_string(), git_options.clone(), &keys_dir).await;
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let output =
+            generate_genesis(git_options.clone(), output_dir.path().to_path_buf(), false).await;
+        assert_eq!(output.validators, users.len());
+    }
+
+    // The rest of the layout is kept as it was
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    assert_eq!(
+        to_yaml(&Layout {
+            users: original.users.clone(),
+            owner_public_keys: original.owner_public_keys.clone(),
+            ..layout
+        })
+        .unwrap(),
+        to_yaml(&original).unwrap()
+    );
+
+    // A user can only be added once
+    match add("user-0").await {
+        Err(CliError::GenesisError(GenesisError::DuplicateUser(user))) => {
+            assert_eq!(user, "user-0")
+        }
+        Err(err) => panic!("Expected user-0 to be a duplicate: {}", err),
+        Ok(_) => panic!("Expected user-0 to be rejected as a duplicate"),
+    }
+    assert!(add("").await.is_err());
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    assert_eq!(layout.users, users);
+}
+
+#[tokio::test]
+async fn test_import_public_identity() {
+    let workspace = GenesisWorkspace::with_users(1).await;
+    let git_options = workspace.git_options();
+    let client = git_options.clone().get_client().unwrap();
+    let original: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    let mut public_keys_files = Vec::new();
+    for index in 1..4 {
+        let keys_dir = generate_keys(workspace.keys_dir(), index).await;
+        public_keys_files.push(keys_dir.join(PUBLIC_KEYS_FILE));
+    }
+    let import = |identity_dir: &Path, usernames: &[&str]| {
+        ImportPublicIdentity {
+            identity_dir: identity_dir.to_path_buf(),
+            validator_hosts: usernames
+                .iter()
+                .enumerate()
+                .map(|(index, username)| {
+                    UserHost::from_str(&format!("{}=localhost:{}", username, 6180 + index)).unwrap()
+                })
+                .collect(),
+            stake_amount: DEFAULT_STAKE_AMOUNT,
+            commission_percentage: 0,
+            commit: false,
+            output_options: OutputOptions::default(),
+            git_options: git_options.clone(),
+        }
+        .execute()
+    };
+
+    // Each malformed or duplicate identity is reported, and nothing is imported
+    let invalid_dir = TempPath::new();
+    invalid_dir.create_as_dir().unwrap();
+    let invalid = invalid_dir.path();
+    std::fs::copy(&public_keys_files[0], invalid.join("user-0.yaml")).unwrap();
+    std::fs::copy(&public_keys_files[0], invalid.join("user-1.yaml")).unwrap();
+    std::fs::copy(&public_keys_files[0], invalid.join("user-2.yaml")).unwrap();
+    write_to_file(&invalid.join("user-3.yaml"), "user-3", b"not: [an identity").unwrap();
+    write_to_file(&invalid.join("notes.txt"), "notes", b"ignored").unwrap();
+    match import(invalid, &["user-0", "user-1", "user-2", "user-3"]).await {
+        Err(CliError::CommandArgumentError(err)) => {
+            assert!(err.contains("user-0 is already in the layout"), "{}", err);
+            assert!(err.contains("user-2.yaml: account"), "{}", err);
+            assert!(err.contains("user-3.yaml:"), "{}", err);
+            assert!(!err.contains("notes.txt"), "{}", err);
+            assert_eq!(err.lines().count(), 4, "{}", err);
+        }
+        result => panic!(
+            "Expected the invalid identities to be reported, got {:?}",
+            result
+        ),
+    }
+    match import(invalid, &["user-1", "user-4"]).await {
+        Err(CliError::CommandArgumentError(err)) => {
+            assert!(
+                err.contains("no --validator-host is given for user user-0"),
+                "{}",
+                err
+            );
+            assert!(
+                err.contains("user user-4, who has no public keys file"),
+                "{}",
+                err
+            );
+        }
+        result => panic!(
+            "Expected the missing hosts to be reported, got {:?}",
+            result
+        ),
+    }
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    assert_eq!(layout.users, original.users);
+
+    // The users are added after those already in the layout, and genesis includes them all
+    let identity_dir = TempPath::new();
+    identity_dir.create_as_dir().unwrap();
+    let usernames = ["user-1", "user-2", "user-3"];
+    for (username, public_keys_file) in usernames.iter().zip(&public_keys_files) {
+        let identity_file = identity_dir.path().join(format!("{}.yaml", username));
+        std::fs::copy(public_keys_file, identity_file).unwrap();
+    }
+    let summaries = import(identity_dir.path(), &usernames).await.unwrap();
+    assert_eq!(
+        summaries
+            .iter()
+            .map(|summary| summary.username.as_str())
+            .collect::<Vec<_>>(),
+        usernames
+    );
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    assert_eq!(layout.users, vec!["user-0", "user-1", "user-2", "user-3"]);
+    for (summary, public_keys_file) in summaries.iter().zip(&public_keys_files) {
+        let identity = read_public_identity_file(public_keys_file).unwrap();
+        assert_eq!(summary.owner_account_address, identity.account_address);
+        assert_eq!(summary.operator_account_address, identity.account_address);
+        // Their owner keys are registered, for their signed configurations to be checked against
+        assert_eq!(
+            layout.owner_public_keys.get(&summary.username),
+            Some(&identity.account_public_key)
+        );
+    }
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let output = generate_genesis(git_options, output_dir.path().to_path_buf(), false).await;
+    assert_eq!(output.validators, 4);
+}
+
+#[tokio::test]
+async fn test_upload_validator_configuration() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let source = workspace.git_dir();
+    let operator_file = PathBuf::from("user-0").join(OPERATOR_FILE);
+    let owner_file = PathBuf::from("user-0").join(OWNER_FILE);
+    let remote = MockGithub::start();
+    let github = remote.client();
+    let upload = |force| upload_configuration(&github, source, "user-0", force);
+
+    // Transient failures are retried, and the configuration lands as it is locally
+    remote.state().failures = 3;
+    let summary = upload(false).unwrap();
+    assert_eq!(
+        summary.uploaded,
+        vec![operator_file.clone(), owner_file.clone()]
+    );
+    assert!(summary.unchanged.is_empty());
+    for file in &summary.uploaded {
+        assert_eq!(
+            remote.file(file).unwrap(),
+            read_from_file(&source.join(file)).unwrap()
+        );
+    }
+    // The owner is checked after the three failures, then each file is read, and written after
+    // reading its sha
+    assert_eq!(remote.state().requests, 10);
+
+    // Uploading again finishes an interrupted upload, skipping the files already uploaded
+    remote
+        .state()
+        .files
+        .remove(&owner_file.display().to_string());
+    let summary = upload(false).unwrap();
+    assert_eq!(summary.uploaded, vec![owner_file.clone()]);
+    assert_eq!(summary.unchanged, vec![operator_file.clone()]);
+
+    // Another owner's configuration for the user is only overwritten with --force
+    let other_owner = read_from_file(&workspace.owner_path("user-1")).unwrap();
+    remote
+        .state()
+        .files
+        .insert(owner_file.display().to_string(), other_owner.clone());
+    match upload(false) {
+        Err(CliError::CommandArgumentError(err)) => assert!(err.contains("--force"), "{}", err),
+        result => panic!(
+            "Expected another owner's configuration to be kept, got {:?}",
+            result
+        ),
+    }
+    assert_eq!(remote.file(&owner_file).unwrap(), other_owner);
+    assert_eq!(upload(true).unwrap().uploaded, vec![owner_file.clone()]);
+    assert_eq!(
+        remote.file(&owner_file).unwrap(),
+        read_from_file(&workspace.owner_path("user-0")).unwrap()
+    );
+
+    // Failures which outlast the retries are reported
+    remote.state().failures = 4;
+    assert!(upload(false).is_err());
+}
+
+#[tokio::test]
+async fn test_genesis_doctor() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(3, &dir).await;
+    let git_dir = git_options.local_repository_dir.clone().unwrap();
+    let doctor = |mainnet| GenesisDoctor {
+        mainnet,
+        expected_total_supply: None,
+        git_options: git_options.clone(),
+    };
+
+    // A complete repository has no problems
+    let report = doctor(false).execute().await.unwrap();
+    assert_eq!(report.validators, 3);
+    assert!(report.problems.is_empty(), "{}", report);
+
+    // Every problem is reported, rather than only the first
+    let layout_file = git_dir.join(LAYOUT_FILE);
+    let mut layout = Layout::from_disk(&layout_file).unwrap();
+    layout.epoch_duration_secs = 0;
+    layout.scheduled_features = vec![ScheduledFeature {
+        feature: FeatureFlag::CODE_DEPENDENCY_CHECK,
+        epoch: 1,
+    }];
+    write_to_file(
+        &layout_file,
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let owner_file = git_dir.join("user-0").join(OWNER_FILE);
+    let mut owner: StringOwnerConfiguration =
+        from_yaml(&std::fs::read_to_string(&owner_file).unwrap()).unwrap();
+    owner.stake_amount = Some("not a number".to_string());
+    write_to_file(
+        &owner_file,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner).unwrap().as_bytes(),
+    )
+    .unwrap();
+    std::fs::remove_file(git_dir.join("user-1").join(OWNER_FILE)).unwrap();
+    std::fs::remove_file(git_dir.join("user-2").join(OPERATOR_FILE)).unwrap();
+    std::fs::remove_file(git_dir.join(FRAMEWORK_NAME)).unwrap();
+
+    let report = match doctor(true).execute().await {
+        Err(CliError::GenesisError(GenesisError::ProblemsFound(report))) => report,
+        result => panic!("Expected the problems to be reported, got {:?}", result),
+    };
+    assert_eq!(report.validators, 3);
+    let count = |file: &str| report.problems.get(file).map_or(0, Vec::len);
+    assert_eq!(count(LAYOUT_FILE), 2, "{}", report);
+    assert_eq!(count("user-0/"), 1, "{}", report);
+    assert_eq!(count("user-1/owner.yaml"), 1, "{}", report);
+    assert_eq!(count("user-2/operator.yaml"), 1, "{}", report);
+    assert_eq!(count(FRAMEWORK_NAME), 1, "{}", report);
+    assert_eq!(count(BALANCES_FILE), 1, "{}", report);
+    assert_eq!(count(EMPLOYEE_VESTING_ACCOUNTS_FILE), 1, "{}", report);
+    assert_eq!(report.count(), 8, "{}", report);
+    assert!(
+        report.problems["user-0/"][0].contains("stake_amount"),
+        "{}",
+        report
+    );
+
+    // The report groups the problems by file, with their counts
+    let printed = report.to_string();
+    assert!(
+        printed.starts_with("Found 8 problems in 7 files"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("\nlayout.yaml (2):\n"), "{}", printed);
+}
+
+#[test]
+fn test_layout_chain_id_warnings() {
+    let mut layout = Layout::default();
+    assert!(layout.warnings().is_empty());
+
+    // Reserved chain ids are only expected of test layouts, or of the named network itself
+    layout.is_test = false;
+    assert!(layout.warnings()[0].contains("reserved for testing"));
+    layout.chain_id = ChainId::from_str("mainnet").unwrap();
+    assert!(layout.warnings()[0].contains("reserved for mainnet"));
+    layout.chain_id = ChainId::new(42);
+    assert!(layout.warnings().is_empty());
+
+    layout.root_key = Some(root_private_key(1).public_key());
+    layout.users = vec!["user-0".to_string()];
+    layout.chain_id = ChainId::new(0);
+    assert!(layout
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("chain_id is not set"));
+}
+
+#[tokio::test]
+async fn test_genesis_with_multiple_validator_hosts() {
+    let workspace = GenesisWorkspace::with_users(1).await;
+    let git_options = workspace.git_options();
+
+    // A single host is written as before
+    let operator_file = workspace.operator_path("user-0");
+    let operator = String::from_utf8(read_from_file(&operator_file).unwrap()).unwrap();
+    assert!(!operator.contains("additional_validator_hosts"));
+
+    SetValidatorConfiguration {
+        username: "user-0".to_string(),
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+        owner_public_identity_file: Some(workspace.public_keys_path(0)),
+        validator_host: vec![
+            HostAndPort::from_str("localhost:6180").unwrap(),
+            HostAndPort::from_str("validator.example.com:6182").unwrap(),
+        ],
+        stake_amount: 100_000_000_000_000,
+        full_node_host: None,
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        commission_percentage: 0,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    let client = git_options.clone().get_client().unwrap();
+    let config = get_config(&client, "user-0").unwrap();
+    assert_eq!(config.additional_validator_hosts.len(), 1);
+
+    // Every host is registered on chain, in order
+    workspace.generate_genesis(false).await;
+    let genesis = read_genesis(&workspace.genesis_path()).unwrap();
+    let summary = summarize_genesis(&genesis, None).unwrap();
+    let addresses = &summary.validators[0].validator_network_addresses;
+    assert_eq!(addresses.len(), 2);
+    assert!(addresses[0].starts_with("/dns/localhost/tcp/6180/"));
+    assert!(addresses[1].starts_with("/dns/validator.example.com/tcp/6182/"));
+}
+
+#[tokio::test]
+async fn test_genesis_with_full_node_hosts() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let identity_file = |user: &str| dir.path().join(user).join(PUBLIC_KEYS_FILE);
+    let set_validator_config =
+        |user: &str, identity_file, full_node_host: &str| SetValidatorConfiguration {
+            username: format!("user-{}", user),
+            output_options: OutputOptions::default(),
+            git_options: git_options.clone(),
+            owner_public_identity_file: Some(identity_file),
+            validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+            stake_amount: 100_000_000_000_000,
+            full_node_host: Some(HostAndPort::from_str(full_node_host).unwrap()),
+            operator_public_identity_file: None,
+            voter_public_identity_file: None,
+            commission_percentage: 0,
+            check_reachability: false,
+            commit: false,
+            owner_private_keys_file: None,
+            passphrase_options: PassphraseOptions::default(),
+        };
+
+    // A fullnode host on a validator host is warned of
+    let command = set_validator_config("0", identity_file("0"), "localhost:6180");
+    assert!(command.full_node_host_warning().is_some());
+    let command = set_validator_config("0", identity_file("0"), "fullnode-0.example.com:6182");
+    assert_eq!(command.full_node_host_warning(), None);
+    command.execute().await.unwrap();
+    set_validator_config("1", identity_file("1"), "fullnode-1.example.com:6182")
+        .execute()
+        .await
+        .unwrap();
+
+    // The fullnode key is required, as the host can't be dialed without it
+    let mut identity = read_public_identity_file(&identity_file("1")).unwrap();
+    identity.full_node_network_public_key = None;
+    let no_full_node_file = dir.path().join("no-full-node.yaml");
+    write_to_file(
+        &no_full_node_file,
+        "no-full-node.yaml",
+        serde_yaml::to_string(&identity).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let error = set_validator_config("1", no_full_node_file, "fullnode-1.example.com:6182")
+        .execute()
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("full node network public key"),
+        "{}",
+        error
+    );
+
+    // Every fullnode is registered on chain, with its key
+    let client = git_options.clone().get_client().unwrap();
+    let mut expected = BTreeMap::new();
+    for user in ["0", "1"] {
+        let config = get_config(&client, &format!("user-{}", user)).unwrap();
+        let key = config.full_node_network_public_key.unwrap();
+        assert_eq!(
+            Some(key),
+            read_public_identity_file(&identity_file(user))
+                .unwrap()
+                .full_node_network_public_key
+        );
+        expected.insert(config.operator_account_address, (user, key));
+    }
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let output_dir = PathBuf::from(output_dir.path());
+    generate_genesis(git_options, output_dir.clone(), false).await;
+    let genesis = read_genesis(&output_dir.join("genesis.blob")).unwrap();
+    let summary = summarize_genesis(&genesis, None).unwrap();
+    assert_eq!(summary.validators.len(), 2);
+    for validator in &summary.validators {
+        let (user, key) = expected[&validator.account_address];
+        let host = HostAndPort::from_str(&format!("fullnode-{}.example.com:6182", user)).unwrap();
+        assert_eq!(
+            validator.fullnode_network_addresses,
+            vec![host.as_network_address(key).unwrap().to_string()]
+        );
+    }
+}
+
+#[test]
+fn test_check_reachability() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let listening = HostAndPort::from_str(&format!("127.0.0.1:{}", port)).unwrap();
+    check_reachability([&listening]).unwrap();
+
+    // A host with nothing listening may not have started its node yet, so is only warned of
+    drop(listener);
+    check_reachability([&listening]).unwrap();
+
+    // A host which doesn't resolve is likely a typo
+    let unresolved = HostAndPort::from_str("validator.invalid:6180").unwrap();
+    match check_reachability([&listening, &unresolved]) {
+        Err(CliError::CommandArgumentError(err)) => {
+            assert!(err.contains("validator.invalid:6180"), "{}", err);
+            assert!(!err.contains("127.0.0.1"), "{}", err);
+        }
+        result => panic!("Expected the unresolved host to be rejected: {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_genesis() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let git_options = workspace.git_options();
+    let output_dir = workspace.output_dir().to_path_buf();
+    let output = workspace.generate_genesis(false).await;
+
+    let verified = VerifyGenesis {
+        genesis_dir: Some(output_dir.clone()),
+        genesis_file_name: GENESIS_FILE.to_string(),
+        waypoint_file_name: WAYPOINT_FILE.to_string(),
+        git_options: git_options.clone(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    assert_eq!(verified.waypoint, output.waypoint);
+    assert_eq!(verified.validators.len(), 2);
+
+    // A validator without stake is named
+    let client = git_options.clone().get_client().unwrap();
+    let owner_file = workspace.owner_path("user-1");
+    let mut owner_config: StringOwnerConfiguration =
+        from_yaml(&String::from_utf8(read_from_file(&owner_file).unwrap()).unwrap()).unwrap();
+    owner_config.stake_amount = Some("0".to_string());
+    write_to_file(
+        &owner_file,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+    match verify_genesis(
+        &workspace.genesis_path(),
+        &workspace.waypoint_path(),
+        &client,
+    ) {
+        Err(GenesisVerificationError::ZeroStake { user }) => assert_eq!(user, "user-1"),
+        result => panic!("Expected zero stake for user-1, got {:?}", result),
+    }
+
+    // A waypoint that doesn't match the genesis blob is caught first
+    write_to_file(
+        &workspace.waypoint_path(),
+        WAYPOINT_FILE,
+        Waypoint::default().to_string().as_bytes(),
+    )
+    .unwrap();
+    assert!(matches!(
+        verify_genesis(
+            &workspace.genesis_path(),
+            &workspace.waypoint_path(),
+            &client
+        ),
+        Err(GenesisVerificationError::WaypointMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_verify_waypoint() {
+    let workspace = GenesisWorkspace::with_users(1).await;
+    workspace.generate_genesis(false).await;
+    let genesis_file = workspace.genesis_path();
+    let waypoint_file = workspace.waypoint_path();
+    let verify = |waypoint, waypoint_file| VerifyWaypoint {
+        genesis_file: genesis_file.clone(),
+        waypoint,
+        waypoint_file,
+    };
+
+    // The waypoint written by genesis verifies, whether it's given inline or as a file
+    let waypoint = verify(None, Some(waypoint_file.clone()))
+        .execute()
+        .await
+        .unwrap();
+    let written = String::from_utf8(read_from_file(&waypoint_file).unwrap()).unwrap();
+    assert_eq!(waypoint.to_string(), written);
+    assert_eq!(
+        verify(Some(waypoint), None).execute().await.unwrap(),
+        waypoint
+    );
+    assert!(matches!(
+        verify(Some(waypoint), Some(waypoint_file)).execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+
+    // Any other waypoint is reported along with the recomputed one
+    match verify_waypoint(&genesis_file, Waypoint::default()) {
+        Err(WaypointVerificationError::WaypointMismatch { expected, computed }) => {
+            assert_eq!(expected, Waypoint::default());
+            assert_eq!(computed, waypoint);
+        }
+        result => panic!("Expected a waypoint mismatch, got {:?}", result),
+    }
+    match verify(Some(Waypoint::default()), None).execute().await {
+        Err(CliError::GenesisError(GenesisError::WaypointMismatch { expected, computed })) => {
+            assert_eq!(expected, Waypoint::default());
+            assert_eq!(computed, waypoint);
+        }
+        result => panic!("Expected a waypoint mismatch, got {:?}", result),
+    }
+
+    // A blob which isn't a genesis is distinguished from a mismatch
+    let garbage_file = workspace.output_dir().join("garbage.blob");
+    write_to_file(&garbage_file, "garbage.blob", b"not a genesis").unwrap();
+    assert!(matches!(
+        verify_waypoint(&garbage_file, waypoint),
+        Err(WaypointVerificationError::UnreadableGenesis { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_mainnet_genesis_e2e_flow() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let git_options = workspace.git_options();
+
+    // Create initial balances and employee vesting account files.  The employees are generated
+    // from a seed, each pair pooling the minimum stake for a validator run by an account funded
+    // with nothing.
+    let git_dir = workspace.git_dir();
+    let vesting = generate_employee_vesting(
+        [7; 32],
+        4,
+        2,
+        50_000_000_000_000,
+        VestingScheduleShape {
+            cliff_periods: 12,
+            vesting_periods: 36,
+        },
+    )
+    .unwrap();
+    let mut accounts = vec![
+        AccountMap {
+            account_address: AccountAddress::from_hex_literal("0x123").unwrap(),
+            balance: 1,
+        },
+        AccountMap {
+            account_address: AccountAddress::from_hex_literal("0x234").unwrap(),
+            balance: 2,
+        },
+    ];
+    accounts.extend(vesting.accounts.clone());
+    create_account_balances_file(
+        PathBuf::from(git_dir),
+        accounts
+            .iter()
+            .map(|account| account.account_address)
+            .collect(),
+        accounts.iter().map(|account| account.balance).collect(),
+    )
+    .await;
+    vesting.builder().unwrap().write(git_dir).unwrap();
+
+    // Now generate genesis
+    let generate_mainnet_genesis = |expected_total_supply, balances_csv| GenerateGenesis {
+        mainnet: Some(true),
+        expected_total_supply: Some(expected_total_supply),
+        balances_csv,
+        force: true,
+        ..generate_genesis_command(git_options.clone(), workspace.output_dir().to_path_buf())
+    };
+
+    // The balances, including the employees' allocations, must add up to the total supply
+    let error = generate_mainnet_genesis(200_000_000_000_000, None)
+        .execute()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("3 more than the expected total supply 200000000000000"),
+        "{}",
+        error
+    );
+    assert!(!workspace.genesis_path().exists());
+    let output = generate_mainnet_genesis(200_000_000_000_003, None)
+        .execute()
+        .await
+        .unwrap();
+
+    let genesis_file = workspace.genesis_path();
+    let waypoint_file = workspace.waypoint_path();
+    assert_eq!(
+        output.files[..2],
+        [genesis_file.clone(), waypoint_file.clone()]
+    );
+    assert_eq!(read_waypoint(&waypoint_file).unwrap(), output.waypoint);
+
+    // Every account is funded with its configured balance
+    let genesis = read_genesis(&genesis_file).unwrap();
+    for (account, balance) in [("0x123", 1), ("0x234", 2)] {
+        let account = AccountAddress::from_hex_literal(account).unwrap();
+        let coin_store: CoinStoreResource = assert_genesis_resource(&genesis, account);
+        assert_eq!(coin_store.coin(), balance);
+    }
+    // Except for the employees, whose balances are staked by their vesting contract, for the
+    // validator of their group
+    let summary = summarize_genesis(&genesis, None).unwrap();
+    for group in &vesting.groups {
+        for account in &group.beneficiaries {
+            let coin_store: CoinStoreResource = assert_genesis_resource(&genesis, *account);
+            assert_eq!(coin_store.coin(), 0);
+        }
+        let consensus_key = &group.validator.consensus_public_key;
+        assert!(summary
+            .validators
+            .iter()
+            .any(|validator| &validator.consensus_public_key == consensus_key));
+    }
+
+    // The same balances in a CSV, in place of the balances file, give the same genesis
+    let balances_csv = workspace.keys_dir().join("balances.csv");
+    write_to_file(
+        &balances_csv,
+        "balances.csv",
+        balances_csv_contents(&accounts).as_bytes(),
+    )
+    .unwrap();
+    std::fs::remove_file(workspace.balances_path()).unwrap();
+    generate_mainnet_genesis(200_000_000_000_003, Some(balances_csv.clone()))
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(read_genesis(&genesis_file).unwrap(), genesis);
+
+    // A faucet is funded alongside the balances, counting towards the total supply
+    let with_faucet = |expected_total_supply, faucet_account: &str| {
+        let mut command =
+            generate_mainnet_genesis(expected_total_supply, Some(balances_csv.clone()));
+        command.faucet_options = FaucetOptions {
+            faucet_account: Some(AccountAddress::from_hex_literal(faucet_account).unwrap()),
+            faucet_amount: Some(5),
+        };
+        command
+    };
+    let error = with_faucet(200_000_000_000_003, "0xfa")
+        .execute()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("5 more than the expected total supply"),
+        "{}",
+        error
+    );
+    let output = with_faucet(200_000_000_000_008, "0xfa")
+        .execute()
+        .await
+        .unwrap();
+    let faucet = AccountAddress::from_hex_literal("0xfa").unwrap();
+    assert_eq!(
+        output.faucet.map(|faucet| faucet.account_address),
+        Some(faucet)
+    );
+    let summary = summarize_genesis(&read_genesis(&genesis_file).unwrap(), None).unwrap();
+    assert_eq!(summary.balances.get(&faucet), Some(&5));
+
+    // It can't be one of the balances, which genesis would fund instead
+    let error = with_faucet(200_000_000_000_008, "0x123")
+        .execute()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("already configured for genesis"),
+        "{}",
+        error
+    );
+
+    // Nor an account genesis creates for the framework, or as the root account
+    for system_account in ["0x1", "0x3", "0xa550c18"] {
+        let faucet = AccountMap {
+            account_address: AccountAddress::from_hex_literal(system_account).unwrap(),
+            balance: 5,
+        };
+        match validate_faucet(Some(&faucet), &BTreeSet::new()) {
+            Err(CliError::CommandArgumentError(err)) => {
+                assert!(err.contains("already created by genesis"), "{}", err)
+            }
+            result => panic!("Expected the faucet to be rejected, got {:?}", result),
+        }
+    }
+}
+
+#[test]
+fn test_parse_balances_csv() {
+    let accounts = vec![
+        AccountMap {
+            account_address: AccountAddress::from_hex_literal("0x123").unwrap(),
+            balance: 1,
+        },
+        AccountMap {
+            account_address: AccountAddress::random(),
+            balance: u64::MAX,
+        },
+    ];
+    let parsed = parse_balances_csv(&balances_csv_contents(&accounts), BalanceUnit::Octas).unwrap();
+    assert_eq!(parsed.len(), accounts.len());
+    for (parsed, account) in parsed.iter().zip(&accounts) {
+        assert_eq!(parsed.account_address, account.account_address);
+        assert_eq!(parsed.balance, account.balance);
+    }
+
+    // Spreadsheet exports may quote fields and leave blank lines
+    let parsed = parse_balances_csv(
+        "\"account_address\",\"balance\"\n\n\"0x123\",\"5\"\n",
+        BalanceUnit::Octas,
+    )
+    .unwrap();
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].account_address, accounts[0].account_address);
+    assert_eq!(parsed[0].balance, 5);
+
+    // Files without balances, or with an unexpected header, are rejected
+    let error = |contents: &str| {
+        let error = parse_balances_csv(contents, BalanceUnit::Octas).unwrap_err();
+        assert!(matches!(error, CliError::UnableToParse(..)));
+        error.to_string()
+    };
+    for (contents, expected_error) in [
+        ("", "File is empty"),
+        ("account_address,balance\n", "File has no balances"),
+        ("address,amount\n0x1,1\n", "Line 1: expected header"),
+    ] {
+        let error = error(contents);
+        assert!(error.contains(expected_error), "{}", error);
+    }
+
+    // As are malformed rows, reporting the line they're on
+    for (row, expected_error) in [
+        ("0x1,1,1", "Line 2: expected 2 fields"),
+        ("0xZZ,1", "Line 2: malformed account address"),
+        ("0x1,ten", "Line 2: balance ten is not a number"),
+        ("0x1,-1", "Line 2: balance -1 is not a number"),
+    ] {
+        let error = error(&format!("account_address,balance\n{}\n", row));
+        assert!(error.contains(expected_error), "{}", error);
+    }
+    let error = error("account_address,balance\n0x1,1\n\n0x01,2\n");
+    assert!(
+        error.contains("Line 4: account 0x1 is already on line 2"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_read_balances_files() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let account = |address: &str, balance| AccountMap {
+        account_address: AccountAddress::from_hex_literal(address).unwrap(),
+        balance,
+    };
+    let write_yaml = |name: &str, accounts: &[AccountMap]| {
+        let path = dir.path().join(name);
+        write_to_file(
+            &path,
+            name,
+            serde_yaml::to_string(accounts).unwrap().as_bytes(),
+        )
+        .unwrap();
+        path
+    };
+    let team_a = write_yaml("team-a.yaml", &[account("0x3", 3), account("0x1", 1)]);
+    let team_b = dir.path().join("team-b.csv");
+    write_to_file(
+        &team_b,
+        "team-b.csv",
+        balances_csv_contents(&[account("0x2", 2)]).as_bytes(),
+    )
+    .unwrap();
+
+    // The files are merged, in the same order whichever order they're given in
+    let merged = |paths: &[PathBuf]| -> Vec<(AccountAddress, u64)> {
+        read_balances_files(paths, BalanceUnit::Octas)
+            .unwrap()
+            .into_iter()
+            .map(|account| (account.account_address, account.balance))
+            .collect()
+    };
+    let expected: Vec<_> = [account("0x1", 1), account("0x2", 2), account("0x3", 3)]
+        .into_iter()
+        .map(|account| (account.account_address, account.balance))
+        .collect();
+    assert_eq!(merged(&[team_a.clone(), team_b.clone()]), expected);
+    assert_eq!(merged(&[team_b.clone(), team_a.clone()]), expected);
+
+    // An account in two files is rejected, naming both
+    let team_c = write_yaml("team-c.yaml", &[account("0x2", 20)]);
+    for paths in [
+        vec![team_a.clone(), team_b.clone(), team_c.clone()],
+        vec![team_c.clone(), team_b.clone()],
+    ] {
+        let error = read_balances_files(&paths, BalanceUnit::Octas)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("account 0x2"), "{}", error);
+        assert!(
+            error.contains(&format!("{} and {}", team_b.display(), team_c.display())),
+            "{}",
+            error
+        );
+    }
+    let error = read_balances_files(&[team_a.clone(), team_a], BalanceUnit::Octas).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            CliError::GenesisError(GenesisError::BalancesInvalid(_))
+        ),
+        "{}",
+        error
+    );
+    assert!(error.to_string().contains("listed twice"), "{}", error);
+}
+
+#[test]
+fn test_balance_unit() {
+    for (balance, octas) in [
+        ("0", 0),
+        ("1", OCTAS_PER_APT),
+        ("1.5", 150_000_000),
+        ("0.00000001", 1),
+        ("2.100000000", 210_000_000),
+        ("12.", 12 * OCTAS_PER_APT),
+        // The largest balance genesis can hold
+        ("184467440737.09551615", u64::MAX),
+    ] {
+        assert_eq!(
+            BalanceUnit::Apt.to_octas(balance).unwrap(),
+            octas,
+            "{}",
+            balance
+        );
+    }
+    assert_eq!(
+        BalanceUnit::Octas.to_octas("18446744073709551615").unwrap(),
+        u64::MAX
+    );
+
+    for (unit, balance, expected_error) in [
+        (BalanceUnit::Apt, "0.000000001", "finer than one octa"),
+        (
+            BalanceUnit::Apt,
+            "184467440737.09551616",
+            "more than the maximum",
+        ),
+        (BalanceUnit::Apt, "184467440738", "more than the maximum"),
+        (BalanceUnit::Apt, "1.+5", "not a number"),
+        (BalanceUnit::Apt, "ten", "not a number"),
+        (BalanceUnit::Octas, "1.5", "not a number"),
+        (BalanceUnit::Octas, "18446744073709551616", "not a number"),
+    ] {
+        let error = unit.to_octas(balance).unwrap_err();
+        assert!(error.contains(expected_error), "{}: {}", balance, error);
+    }
+
+    // Balances are stored in octas whichever unit they're read in
+    let parse_apt = |contents: &str| parse_balances_csv(contents, BalanceUnit::Apt);
+    let parsed = parse_apt("account_address,balance\n0x1,2.5\n").unwrap();
+    assert_eq!(parsed[0].balance, 250_000_000);
+    let error = parse_apt("account_address,balance\n0x1,1.000000001\n")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("Line 2: balance 1.000000001 is finer"),
+        "{}",
+        error
+    );
+
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let balances_file = dir.path().join("balances.yaml");
+    let accounts = vec![AccountMap {
+        account_address: AccountAddress::from_hex_literal("0x1").unwrap(),
+        balance: 3,
+    }];
+    write_to_file(
+        &balances_file,
+        "balances.yaml",
+        serde_yaml::to_string(&accounts).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let read = |unit| read_balances_files(&[balances_file.clone()], unit);
+    assert_eq!(read(BalanceUnit::Octas).unwrap()[0].balance, 3);
+    assert_eq!(
+        read(BalanceUnit::Apt).unwrap()[0].balance,
+        3 * OCTAS_PER_APT
+    );
+}
+
+#[test]
+fn test_validate_total_supply() {
+    let accounts = |balances: &[u64]| -> Vec<AccountMap> {
+        balances
+            .iter()
+            .map(|balance| AccountMap {
+                account_address: AccountAddress::random(),
+                balance: *balance,
+            })
+            .collect()
+    };
+    validate_total_supply(&accounts(&[1, 2]), 3).unwrap();
+    let error = validate_total_supply(&accounts(&[1, 2]), 5).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            CliError::GenesisError(GenesisError::BalancesInvalid(_))
+        ),
+        "{}",
+        error
+    );
+    let error = error.to_string();
+    assert!(
+        error.contains("2 less than the expected total supply 5"),
+        "{}",
+        error
+    );
+
+    // Balances summing past u64::MAX are reported rather than wrapping around
+    let error = validate_total_supply(&accounts(&[u64::MAX, 1]), 0)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("overflow"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_employee_vesting_builder_round_trips_yaml() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let validator = employee_validator_config(dir.path(), &git_options).await;
+    let employee = AccountAddress::from_hex_literal("0x345").unwrap();
+    let unfunded = AccountAddress::from_hex_literal("0x456").unwrap();
+    let mut accounts = vec![AccountMap {
+        account_address: employee,
+        balance: 4_800,
+    }];
+    for account in BTreeSet::from([
+        validator.owner_account_address,
+        validator.operator_account_address,
+        validator.voter_account_address,
+    ]) {
+        accounts.push(AccountMap {
+            account_address: account,
+            balance: 0,
+        });
+    }
+    let group = |beneficiaries: Vec<AccountAddress>, vesting_schedule_numerators: Vec<u64>| {
+        EmployeeVestingGroup {
+            beneficiaries,
+            cliff_periods: 2,
+            vesting_schedule_numerators,
+            vesting_schedule_denominator: 48,
+            validator: validator.clone(),
+        }
+    };
+
+    // Schedules must vest the whole balance, and every employee must be funded
+    let builder = EmployeeVestingBuilder::new(&accounts);
+    assert!(builder
+        .add_group(group(vec![employee], vec![12, 12, 12]))
+        .is_err());
+    let builder = EmployeeVestingBuilder::new(&accounts);
+    assert!(builder
+        .add_group(group(vec![employee, unfunded], vec![12, 12, 12, 12]))
+        .is_err());
+
+    let file = EmployeeVestingBuilder::new(&accounts)
+        .add_group(group(vec![employee], vec![12, 12, 12, 12]))
+        .unwrap()
+        .write(dir.path())
+        .unwrap();
+    assert_eq!(file, dir.path().join(EMPLOYEE_VESTING_ACCOUNTS_FILE));
+    let contents = String::from_utf8(read_from_file(&file).unwrap()).unwrap();
+    let employees: Vec<EmployeeAccountMap> = from_yaml(&contents).unwrap();
+    assert_eq!(employees.len(), 1);
+    assert_eq!(employees[0].accounts, vec![employee]);
+    assert_eq!(
+        employees[0].vesting_schedule_numerators,
+        vec![0, 0, 12, 12, 12, 12]
+    );
+    assert_eq!(employees[0].vesting_schedule_denominator, 48);
+    assert_eq!(
+        employees[0].validator.validator.owner_address,
+        validator.owner_account_address
+    );
+    assert_eq!(employees[0].vested_amount(4_800), 4_800);
+    assert_eq!(serde_yaml::to_string(&employees).unwrap(), contents);
+}
+
+#[test]
+fn test_generate_employee_vesting() {
+    let shape = VestingScheduleShape {
+        cliff_periods: 2,
+        vesting_periods: 4,
+    };
+    let generate = |seed| {
+        let vesting = generate_employee_vesting(seed, 5, 2, 100, shape).unwrap();
+        let employees = vesting.builder().unwrap().build();
+        (vesting, to_yaml(&employees).unwrap())
+    };
+
+    // The employees are split in order into groups, each vesting with the given shape
+    let (vesting, employees) = generate([1; 32]);
+    assert_eq!(
+        vesting
+            .groups
+            .iter()
+            .map(|group| group.beneficiaries.len())
+            .collect::<Vec<_>>(),
+        vec![2, 2, 1]
+    );
+    let parsed: Vec<EmployeeAccountMap> = from_yaml(&employees).unwrap();
+    for (group, stake) in parsed.iter().zip([200, 200, 100]) {
+        assert_eq!(group.vesting_schedule_numerators, vec![0, 0, 1, 1, 1, 1]);
+        assert_eq!(group.vesting_schedule_denominator, 4);
+        assert_eq!(group.validator.validator.stake_amount, stake);
+    }
+    // Funding each employee, and each validator account with nothing
+    assert_eq!(vesting.accounts.len(), 5 + 3);
+    assert_eq!(
+        vesting
+            .accounts
+            .iter()
+            .map(|account| account.balance)
+            .sum::<u64>(),
+        500
+    );
+
+    // The same seed always generates the same groups, and another seed different ones
+    assert_eq!(generate([1; 32]).1, employees);
+    assert_ne!(generate([2; 32]).1, employees);
+
+    // Groups must have employees, and vest over at least one period
+    assert!(generate_employee_vesting([1; 32], 5, 0, 100, shape).is_err());
+    let shape = VestingScheduleShape {
+        cliff_periods: 2,
+        vesting_periods: 0,
+    };
+    assert!(generate_employee_vesting([1; 32], 5, 2, 100, shape).is_err());
+}
+
+#[tokio::test]
+async fn test_mainnet_genesis_rejects_mismatched_employee_vesting() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let git_dir = PathBuf::from(git_options.local_repository_dir.as_ref().unwrap());
+
+    // The first schedule vests the whole allocation as its last fraction repeats, the second one
+    // stops vesting after its second period
+    let fully_vested = AccountAddress::from_hex_literal("0x345").unwrap();
+    let partially_vested = AccountAddress::from_hex_literal("0x456").unwrap();
+    create_account_balances_file(
+        git_dir.clone(),
+        vec![fully_vested, partially_vested],
+        vec![4_800, 9_600],
+    )
+    .await;
+    create_employee_vesting_accounts_file(
+        git_dir.clone(),
+        vec![
+            employee_group(vec![fully_vested], vec![24, 12], 48),
+            employee_group(vec![partially_vested], vec![12, 12, 0], 48),
+        ],
+    )
+    .await;
+    let layout_file = git_dir.join(LAYOUT_FILE);
+    let mut layout = Layout::from_disk(&layout_file).unwrap();
+    layout.employee_vesting_pool_size = Some(15_000);
+    write_to_file(
+        &layout_file,
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let command = GenerateGenesis {
+        mainnet: Some(true),
+        ..generate_genesis_command(git_options, PathBuf::from(output_dir.path()))
+    };
+    match command.execute().await.unwrap_err() {
+        CliError::GenesisError(GenesisError::BalancesInvalid(error)) => assert_eq!(
+            error,
+            format!(
+                "Employee vesting allocations are inconsistent:\n\
+                 Employee {} is allocated 9600, but its vesting schedule [12, 12, 0]/48 vests \
+                 4800\n\
+                 Employees are allocated 14400 in total, but the employee vesting pool is 15000",
+                partially_vested
+            )
+        ),
+        error => panic!("Expected an inconsistent vesting error, got {:?}", error),
+    }
+}
+
+#[tokio::test]
+async fn test_bench_genesis() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let git_dir = PathBuf::from(git_options.local_repository_dir.as_ref().unwrap());
+    create_account_balances_file(
+        git_dir.clone(),
+        vec![AccountAddress::from_hex_literal("0x123").unwrap()],
+        vec![1],
+    )
+    .await;
+    create_employee_vesting_accounts_file(git_dir, vec![]).await;
+
+    let results = BenchGenesis {
+        sizes: vec![10, 1],
+        git_options,
+    }
+    .execute()
+    .await
+    .unwrap();
+
+    // Sizes are reported in increasing order, each with its cost per account and memory
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.num_accounts)
+            .collect::<Vec<_>>(),
+        vec![1, 10]
+    );
+    for result in &results {
+        let json = serde_json::to_value(result).unwrap();
+        assert!(json.get("micros_per_account").is_some(), "{}", json);
+        assert!(json.get("memory_bytes").is_some(), "{}", json);
+    }
+}
+
+#[tokio::test]
+async fn test_diff_genesis_state() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let git_dir = PathBuf::from(git_options.local_repository_dir.as_ref().unwrap());
+    let first = AccountAddress::from_hex_literal("0x123").unwrap();
+    let second = AccountAddress::from_hex_literal("0x234").unwrap();
+    create_employee_vesting_accounts_file(git_dir.clone(), vec![]).await;
+
+    // Generate two genesis blobs differing only in the balance of one account
+    let mut output_dirs = vec![];
+    for balance in [2, 3] {
+        create_account_balances_file(git_dir.clone(), vec![first, second], vec![1, balance]).await;
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let output_dir = PathBuf::from(output_dir.path());
+        generate_genesis(git_options.clone(), output_dir.clone(), true).await;
+        output_dirs.push(output_dir);
+    }
+
+    // Without the flag, the decoded genesis is compared field by field, pointing at the account
+    let diff = diff_genesis(
+        &output_dirs[0],
+        &output_dirs[1],
+        GENESIS_FILE,
+        WAYPOINT_FILE,
+        false,
+    )
+    .unwrap();
+    assert!(!diff.identical);
+    assert!(diff.state_diff.is_none());
+    let balance_diff = format!("balance of {}: 2 != 3", second);
+    assert!(
+        diff.differences.contains(&balance_diff),
+        "{:?}",
+        diff.differences
+    );
+    assert!(
+        diff.differences
+            .iter()
+            .any(|diff| diff.starts_with("waypoint.txt: ")),
+        "{:?}",
+        diff.differences
+    );
+    assert!(
+        diff.differences
+            .iter()
+            .all(|diff| *diff == balance_diff || diff.starts_with("waypoint.txt: ")),
+        "{:?}",
+        diff.differences
+    );
+
+    // The command fails on any difference, so that scripts can rely on its exit code
+    let diff_command = |second_dir: &PathBuf| DiffGenesis {
+        first_dir: output_dirs[0].clone(),
+        second_dir: second_dir.clone(),
+        genesis_file_name: GENESIS_FILE.to_string(),
+        waypoint_file_name: WAYPOINT_FILE.to_string(),
+        state: false,
+    };
+    let error = diff_command(&output_dirs[1])
+        .execute()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&balance_diff), "{}", error);
+    let diff = diff_command(&output_dirs[0]).execute().await.unwrap();
+    assert!(diff.identical);
+    assert!(diff.differences.is_empty());
+
+    let diff = diff_genesis(
+        &output_dirs[0],
+        &output_dirs[1],
+        GENESIS_FILE,
+        WAYPOINT_FILE,
+        true,
+    )
+    .unwrap();
+    let state_diff = diff.state_diff.unwrap();
+
+    // The only resource which differs is the changed account's coin store, the rest is the
+    // total supply, which is tracked in an aggregator table item
+    let coin_store = describe_state_key(&StateKey::AccessPath(AccessPath::new(
+        second,
+        CoinStoreResource::resource_path(),
+    )));
+    let (resource_diffs, table_diffs): (Vec<_>, Vec<_>) = state_diff
+        .iter()
+        .partition(|diff| !diff.key.starts_with("table/"));
+    assert_eq!(resource_diffs.len(), 1);
+    assert_eq!(resource_diffs[0].key, coin_store);
+    assert_eq!(resource_diffs[0].kind, StateValueDiffKind::Modified);
+    assert_eq!(table_diffs.len(), 1);
+    assert_eq!(table_diffs[0].kind, StateValueDiffKind::Modified);
+
+    // With --state, the command's error names the differing state values too
+    let error = DiffGenesis {
+        state: true,
+        ..diff_command(&output_dirs[1])
+    }
+    .execute()
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(&balance_diff), "{}", error);
+    assert!(
+        error.contains(&format!("state {}: Modified", coin_store)),
+        "{}",
+        error
+    );
+
+    // The same genesis has no state differences
+    let genesis = read_genesis(&output_dirs[0].join("genesis.blob")).unwrap();
+    assert!(diff_genesis_state(&genesis, &genesis).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_generate_layout_template_populated() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    let users_file = dir.path().join("users.txt");
+    write_to_file(&users_file, "users.txt", b"bob\n\n  carol\n").unwrap();
+    let root_key = root_private_key(1).public_key();
+    let encoded_root_key = root_key.to_encoded_string().unwrap();
+
+    // Every field is filled in by a single call
+    GenerateLayoutTemplate::try_parse_from([
+        "GenerateLayoutTemplate",
+        "--output-file",
+        layout_file.to_str().unwrap(),
+        "--chain-id",
+        "testnet",
+        "--root-key",
+        encoded_root_key.as_str(),
+        "--user",
+        "alice",
+        "--users-file",
+        users_file.to_str().unwrap(),
+        "--is-test",
+        "false",
+        "--assume-yes",
+    ])
+    .unwrap()
+    .execute()
+    .await
+    .unwrap();
+
+    // And the layout is ready for the repository as is
+    let git_options = git_options();
+    SetupGit {
+        git_options: git_options.clone(),
+        layout_file: layout_file.clone(),
+        schema_validate: true,
+        framework_bundle: None,
+        init_git: false,
+        progress: ProgressReporter::default(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    let layout: Layout = git_options
+        .get_client()
+        .unwrap()
+        .get(Path::new(LAYOUT_FILE))
+        .unwrap();
+    assert_eq!(layout.chain_id, ChainId::from_str("testnet").unwrap());
+    assert_eq!(layout.root_key, Some(root_key));
+    assert_eq!(layout.users, vec!["alice", "bob", "carol"]);
+    assert!(!layout.is_test);
+
+    // Fields which weren't given are left as the template's defaults
+    let default = Layout::default();
+    assert_eq!(layout.epoch_duration_secs, default.epoch_duration_secs);
+    assert_eq!(layout.min_stake, default.min_stake);
+    assert_eq!(layout.max_stake, default.max_stake);
+}
+
+#[tokio::test]
+async fn test_print_schema() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let mut schemas = BTreeMap::new();
+    for file in [
+        SchemaFile::Layout,
+        SchemaFile::Balances,
+        SchemaFile::EmployeeVestingAccounts,
+        SchemaFile::PublicIdentity,
+    ] {
+        let output_file = dir.path().join(format!("{}.schema.json", file));
+        PrintSchema {
+            file,
+            output_file: output_file.clone(),
+            prompt_options: PromptOptions::yes(),
+        }
+        .execute()
+        .await
+        .unwrap();
+        let schema: serde_json::Value =
+            serde_json::from_slice(&read_from_file(&output_file).unwrap()).unwrap();
+        schemas.insert(file.to_string(), schema);
+    }
+
+    // The layout schema SetupGit validates against is the generated one
+    let layout_schema: serde_json::Value = serde_json::from_str(LAYOUT_SCHEMA).unwrap();
+    assert_eq!(
+        layout_schema,
+        schemas[&SchemaFile::Layout.to_string()],
+        "layout.schema.json is out of date, regenerate it with PrintSchema"
+    );
+    let validate = |file: SchemaFile, path: &Path| -> Result<(), Vec<String>> {
+        let schema = jsonschema::JSONSchema::compile(&schemas[&file.to_string()]).unwrap();
+        let contents = String::from_utf8(read_from_file(path).unwrap()).unwrap();
+        let instance: serde_json::Value = serde_yaml::from_str(&contents).unwrap();
+        if let Err(errors) = schema.validate(&instance) {
+            return Err(errors.map(|error| error.to_string()).collect());
+        }
+        Ok(())
+    };
+
+    // Files written by the genesis commands match their schemas
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    create_layout_file(
+        &layout_file,
+        root_private_key(1).public_key(),
+        vec!["user-0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+    validate(SchemaFile::Layout, &layout_file).unwrap();
+    let keys_dir = generate_keys(dir.path(), 0).await;
+    validate(SchemaFile::PublicIdentity, &keys_dir.join(PUBLIC_KEYS_FILE)).unwrap();
+    let balances_file = dir.path().join(BALANCES_FILE);
+    create_account_balances_file(
+        PathBuf::from(dir.path()),
+        vec![AccountAddress::ONE],
+        vec![1],
+    )
+    .await;
+    validate(SchemaFile::Balances, &balances_file).unwrap();
+
+    // And files which the commands would reject don't
+    let contents = String::from_utf8(read_from_file(&layout_file).unwrap()).unwrap();
+    let mut layout: serde_yaml::Value = serde_yaml::from_str(&contents).unwrap();
+    layout["epoch_duration_secs"] = serde_yaml::Value::from("a day");
+    let contents = serde_yaml::to_string(&layout).unwrap();
+    write_to_file(&layout_file, LAYOUT_FILE, contents.as_bytes()).unwrap();
+    let errors = validate(SchemaFile::Layout, &layout_file).unwrap_err();
+    assert!(
+        errors.iter().any(|error| error.contains("a day")),
+        "{:?}",
+        errors
+    );
+    assert!(from_yaml::<Layout>(&contents).is_err());
+}
+
+#[tokio::test]
+async fn test_setup_git_schema_validate() {
+    let layout_file = TempPath::new();
+    layout_file.create_as_file().unwrap();
+    let root_key = Ed25519PrivateKey::generate_for_testing();
+    create_layout_file(
+        layout_file.path(),
+        root_key.public_key(),
+        vec!["0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+    let setup_git = |git_options| SetupGit {
+        git_options,
+        layout_file: PathBuf::from(layout_file.path()),
+        schema_validate: true,
+        framework_bundle: None,
+        init_git: false,
+        progress: ProgressReporter::default(),
+    };
+
+    // A well formed layout passes validation
+    setup_git(git_options()).execute().await.unwrap();
+
+    // Out of range values are reported with a pointer to the field
+    let mut layout = Layout::from_disk(layout_file.path()).unwrap();
+    layout.rewards_apy_percentage = 150;
+    write_to_file(
+        layout_file.path(),
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let git_options = git_options();
+    let error = setup_git(git_options.clone())
+        .execute()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("/rewards_apy_percentage: 150 is greater than the maximum of 100"),
+        "{}",
+        error
+    );
+    assert!(!git_options
+        .local_repository_dir
+        .unwrap()
+        .join(LAYOUT_FILE)
+        .exists());
+}
+
+#[test]
+fn test_git_options_get_client() {
+    // A local repository is used as is
+    assert!(matches!(
+        git_options().get_client().unwrap(),
+        Client::Local(_)
+    ));
+
+    // Github repositories can be read without a token, or with one
+    let github = GitOptions {
+        github_repository: Some(GithubRepo::from_str("aptos-labs/aptos-genesis").unwrap()),
+        github_branch: "main".to_string(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        github.clone().get_client().unwrap(),
+        Client::Github(..)
+    ));
+    let with_token = GitOptions {
+        github_token: Some("token".to_string()),
+        ..github.clone()
+    };
+    let client = with_token.clone().get_client().unwrap();
+    assert!(matches!(client, Client::Github(..)));
+
+    // Only one source of the token, and one kind of repository, may be given
+    let token_file = TempPath::new();
+    token_file.create_as_file().unwrap();
+    let both_tokens = GitOptions {
+        github_token_file: Some(token_file.path().to_path_buf()),
+        ..with_token
+    };
+    assert!(matches!(
+        both_tokens.get_client(),
+        Err(CliError::CommandArgumentError(_))
+    ));
+    let both_repositories = GitOptions {
+        local_repository_dir: git_options().local_repository_dir,
+        ..github
+    };
+    assert!(matches!(
+        both_repositories.get_client(),
+        Err(CliError::CommandArgumentError(_))
+    ));
+
+    // The token can be kept off the command line in an environment variable, whose value is
+    // never shown in the help
+    let command = GitOptions::command();
+    let token_arg = command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some("github-token"))
+        .unwrap();
+    assert_eq!(token_arg.get_env(), Some(OsStr::new("APTOS_GITHUB_TOKEN")));
+    assert!(token_arg.is_hide_env_values_set());
+}
+
+#[test]
+fn test_layout_validate() {
+    let valid = || Layout {
+        root_key: Some(Ed25519PrivateKey::generate_for_testing().public_key()),
+        users: vec!["0".to_string(), "1".to_string()],
+        ..Layout::default()
+    };
+    valid().validate().unwrap();
+
+    let broken_layouts: &[(fn(&mut Layout), &str)] = &[
+        (|layout| layout.root_key = None, "root_key is missing"),
+        (|layout| layout.users.clear(), "users is empty"),
+        (
+            |layout| layout.users.push("0".to_string()),
+            "users lists 0 more than once",
+        ),
+        (
+            |layout| layout.epoch_duration_secs = 0,
+            "epoch_duration_secs is 0",
+        ),
+        (
+            |layout| layout.recurring_lockup_duration_secs = 0,
+            "recurring_lockup_duration_secs is 0",
+        ),
+        (
+            |layout| layout.voting_duration_secs = 0,
+            "voting_duration_secs is 0",
+        ),
+        (
+            |layout| layout.min_stake = layout.max_stake + 1,
+            "is greater than max_stake",
+        ),
+        (
+            |layout| layout.rewards_apy_percentage = 101,
+            "rewards_apy_percentage 101 is greater than 100",
+        ),
+        (
+            |layout| layout.voting_power_increase_limit = 0,
+            "voting_power_increase_limit 0 must be between 1 and 50",
+        ),
+        (
+            |layout| layout.voting_power_increase_limit = 51,
+            "voting_power_increase_limit 51 must be between 1 and 50",
+        ),
+        (
+            |layout| layout.min_voting_threshold = 0,
+            "min_voting_threshold is 0",
+        ),
+    ];
+    for &(break_layout, expected) in broken_layouts {
+        let mut layout = valid();
+        break_layout(&mut layout);
+        let error = layout.validate().unwrap_err().to_string();
+        assert!(error.contains(expected), "{}", error);
+    }
+}
+
+#[tokio::test]
+async fn test_setup_git_rejects_invalid_layout() {
+    let layout_file = TempPath::new();
+    layout_file.create_as_file().unwrap();
+    create_layout_file(
+        layout_file.path(),
+        Ed25519PrivateKey::generate_for_testing().public_key(),
+        vec!["0".to_string(), "0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+
+    // Invalid layouts are rejected before anything is written to the repository
+    let git_options = git_options();
+    let error = SetupGit {
+        git_options: git_options.clone(),
+        layout_file: PathBuf::from(layout_file.path()),
+        schema_validate: false,
+        framework_bundle: None,
+        init_git: false,
+        progress: ProgressReporter::default(),
+    }
+    .execute()
+    .await
+    .unwrap_err();
+    match error {
+        CliError::GenesisError(GenesisError::DuplicateUser(ref user)) => assert_eq!(user, "0"),
+        ref error => panic!(
+            "Expected the duplicate user to be rejected, got {:?}",
+            error
+        ),
+    }
+    assert!(
+        error.to_string().contains("users lists 0 more than once"),
+        "{}",
+        error
+    );
+    assert!(!git_options
+        .local_repository_dir
+        .unwrap()
+        .join(LAYOUT_FILE)
+        .exists());
+}
+
+#[tokio::test]
+async fn test_setup_git_framework_bundle() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    create_layout_file(
+        &layout_file,
+        Ed25519PrivateKey::generate_for_testing().public_key(),
+        vec!["0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+    let setup_git = |git_options, framework_bundle| SetupGit {
+        git_options,
+        layout_file: layout_file.clone(),
+        schema_validate: false,
+        framework_bundle: Some(framework_bundle),
+        init_git: false,
+        progress: ProgressReporter::default(),
+    };
+
+    // A given bundle is written to the repository as the framework
+    let framework = cached_packages::head_release_bundle();
+    let bundle_file = dir.path().join("custom.mrb");
+    framework.write(bundle_file.clone()).unwrap();
+    let git_options = git_options();
+    setup_git(git_options.clone(), bundle_file)
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(
+        &git_options.get_client().unwrap().get_framework().unwrap(),
+        framework
+    );
+
+    // Files which aren't bundles, and bundles without packages, are rejected
+    let garbage_file = dir.path().join("garbage.mrb");
+    write_to_file(&garbage_file, "garbage.mrb", b"not a bundle").unwrap();
+    let empty_file = dir.path().join("empty.mrb");
+    ReleaseBundle::new(vec![], vec![])
+        .write(empty_file.clone())
+        .unwrap();
+    for bundle_file in [garbage_file, empty_file] {
+        let git_options = git_options();
+        let error = setup_git(git_options.clone(), bundle_file)
+            .execute()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CliError::CommandArgumentError(_)));
+        assert!(
+            error.to_string().contains("Invalid framework bundle"),
+            "{}",
+            error
+        );
+        assert!(!git_options
+            .local_repository_dir
+            .unwrap()
+            .join(FRAMEWORK_NAME)
+            .exists());
+    }
+}
+
+#[tokio::test]
+async fn test_setup_git_init() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let users = generate_users_keys(1, dir.path(), KEY_GENERATION_CONCURRENCY).await;
+    let (username, keys_dir) = users.into_iter().next().unwrap();
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    create_layout_file(
+        &layout_file,
+        root_private_key(1).public_key(),
+        vec![username.clone()],
+        ChainId::test(),
+    )
+    .await;
+    let bundle_file = dir.path().join("custom.mrb");
+    cached_packages::head_release_bundle()
+        .write(bundle_file.clone())
+        .unwrap();
+    let setup_git = |git_options| SetupGit {
+        git_options,
+        layout_file: layout_file.clone(),
+        schema_validate: false,
+        framework_bundle: Some(bundle_file.clone()),
+        init_git: true,
+        progress: ProgressReporter::default(),
+    };
+
+    // The layout and framework are the first commit
+    let git_options = git_options();
+    let git_dir = git_options.local_repository_dir.clone().unwrap();
+    setup_git(git_options.clone()).execute().await.unwrap();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&git_dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {}", args.join(" "));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(
+        git(&["log", "--format=%s"]),
+        "setup genesis layout and framework\n"
+    );
+    assert_eq!(
+        git(&["ls-files"]),
+        format!(".gitignore\n{}\n{}\n", FRAMEWORK_NAME, LAYOUT_FILE)
+    );
+
+    // Each configuration is committed, unless it's unchanged, and the lock file is ignored
+    let set_validator_config = || SetValidatorConfiguration {
+        username: username.clone(),
+        output_options: OutputOptions::default(),
+        git_options: git_options.clone(),
+        owner_public_identity_file: Some(keys_dir.join(PUBLIC_KEYS_FILE)),
+        validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+        stake_amount: 100_000_000_000_000,
+        full_node_host: None,
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        commission_percentage: 0,
+        check_reachability: false,
+        commit: true,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
+    };
+    set_validator_config().execute().await.unwrap();
+    set_validator_config().execute().await.unwrap();
+    assert_eq!(
+        git(&["log", "--format=%s"]),
+        format!(
+            "add config for {}\nsetup genesis layout and framework\n",
+            username
+        )
+    );
+    assert_eq!(git(&["status", "--porcelain"]), "");
+
+    // Only the files written are committed, leaving whatever else was staged as it was
+    let notes_file = git_dir.join("notes.txt");
+    write_to_file(&notes_file, "notes.txt", b"staged by hand").unwrap();
+    git(&["add", "notes.txt"]);
+    let mut changed = set_validator_config();
+    changed.stake_amount += 1;
+    changed.execute().await.unwrap();
+    assert_eq!(
+        git(&["log", "-1", "--format=%s", "--name-only"]),
+        format!(
+            "add config for {}\n\n{}/{}\n",
+            username, username, OWNER_FILE
+        )
+    );
+    assert_eq!(git(&["status", "--porcelain"]), "A  notes.txt\n");
+
+    // A directory inside a git repository isn't committed to, as it would commit into the
+    // enclosing repository
+    let nested_dir = git_dir.join("nested");
+    std::fs::create_dir(&nested_dir).unwrap();
+    let nested_client = GitOptions {
+        local_repository_dir: Some(nested_dir.clone()),
+        ..Default::default()
+    }
+    .get_client()
+    .unwrap();
+    write_to_file(&nested_dir.join(LAYOUT_FILE), LAYOUT_FILE, b"users: []\n").unwrap();
+    match nested_client.commit(&[PathBuf::from(LAYOUT_FILE)], "nested layout") {
+        Err(CliError::CommandArgumentError(err)) => {
+            assert!(err.contains("isn't the top level"), "{}", err)
+        }
+        result => panic!(
+            "Expected the nested commit to be rejected, got {:?}",
+            result
+        ),
+    }
+    assert_eq!(
+        git(&["status", "--porcelain"]),
+        "A  notes.txt\n?? nested/\n"
+    );
+
+    // Github repositories can't be initialized
+    let error = setup_git(GitOptions {
+        github_repository: Some(GithubRepo::from_str("aptos-labs/aptos-core").unwrap()),
+        ..Default::default()
+    })
+    .execute()
+    .await
+    .unwrap_err();
+    assert!(matches!(error, CliError::CommandArgumentError(_)));
+}
+
+#[tokio::test]
+async fn test_redact_layout() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    let root_key = Ed25519PrivateKey::generate_for_testing();
+    create_layout_file(
+        &layout_file,
+        root_key.public_key(),
+        vec!["0".to_string()],
+        ChainId::test(),
+    )
+    .await;
+
+    // Embed private material the way it could accidentally end up in a layout
+    let private_key = root_key.to_encoded_string().unwrap();
+    let seed = "ab".repeat(32);
+    let mut contents = String::from_utf8(read_from_file(&layout_file).unwrap()).unwrap();
+    contents.push_str(&format!(
+        "root_private_key: \"{}\"\noperator:\n  seed: \"{}\"\n  host: \"127.0.0.1:6180\"\n",
+        private_key, seed
+    ));
+    write_to_file(&layout_file, LAYOUT_FILE, contents.as_bytes()).unwrap();
+
+    let output_file = dir.path().join("redacted.yaml");
+    RedactLayout {
+        layout_file: layout_file.clone(),
+        output_file: output_file.clone(),
+        prompt_options: PromptOptions::yes(),
+    }
+    .execute()
+    .await
+    .unwrap();
+
+    let redacted = String::from_utf8(read_from_file(&output_file).unwrap()).unwrap();
+    assert!(redacted.starts_with(&format!("# {}", REDACTED_NOTICE)));
+    assert!(!redacted.contains(&private_key));
+    assert!(!redacted.contains(&seed));
+    assert!(redacted.contains("127.0.0.1:6180"));
+    assert!(redacted.contains(&root_key.public_key().to_encoded_string().unwrap()));
+
+    // Public parameters are retained, but the layout can't be used for genesis
+    let original = Layout::from_disk(&layout_file).unwrap();
+    let layout = Layout::from_disk(&output_file).unwrap();
+    assert_eq!(layout.root_key, original.root_key);
+    assert_eq!(layout.users, original.users);
+    assert_eq!(layout.chain_id, original.chain_id);
+    assert_eq!(layout.min_stake, original.min_stake);
+    assert!(ensure_layout_not_redacted(&original).is_ok());
+    assert!(ensure_layout_not_redacted(&layout).is_err());
+    assert!(SetupGit {
+        git_options: git_options(),
+        layout_file: output_file,
+        schema_validate: false,
+        framework_bundle: None,
+        init_git: false,
+        progress: ProgressReporter::default(),
+    }
+    .execute()
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_genesis_with_collections() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let creator = owner_address(&git_options, "user-0");
+    let other_creator = owner_address(&git_options, "user-1");
+
+    let collection = GenesisCollection {
+        creator,
+        name: "Genesis Collection".to_string(),
+        description: "Created at genesis".to_string(),
+        uri: "https://aptoslabs.com".to_string(),
+        maximum: 0,
+        tokens: (0..2)
+            .map(|i| GenesisToken {
+                name: format!("Genesis Token {}", i),
+                description: "Minted at genesis".to_string(),
+                uri: format!("https://aptoslabs.com/{}", i),
+                supply: 1,
+                maximum: 1,
+            })
+            .collect(),
+    };
+    let write_collections = |name: &str, collections: Vec<GenesisCollection>| {
+        let file = dir.path().join(name);
+        write_to_file(
+            &file,
+            name,
+            serde_yaml::to_string(&collections).unwrap().as_bytes(),
+        )
+        .unwrap();
+        file
+    };
+    let other_collection = GenesisCollection {
+        creator: other_creator,
+        ..collection.clone()
+    };
+    let collections_file = write_collections(
+        "collections.yaml",
+        vec![collection.clone(), other_collection],
+    );
+    let unknown_creator_file = write_collections(
+        "unknown_creator.yaml",
+        vec![GenesisCollection {
+            creator: AccountAddress::from_hex_literal("0x1234").unwrap(),
+            ..collection.clone()
+        }],
+    );
+    let duplicate_file = write_collections(
+        "duplicate.yaml",
+        vec![collection.clone(), collection.clone()],
+    );
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis = |collections_file| GenerateGenesis {
+        collections_file: Some(collections_file),
+        ..generate_genesis_command(git_options.clone(), PathBuf::from(output_dir.path()))
+    };
+
+    // Creators must be configured accounts, and names must be unique for each creator, though
+    // different creators may share one
+    assert!(generate_genesis(unknown_creator_file)
+        .execute()
+        .await
+        .is_err());
+    assert!(generate_genesis(duplicate_file).execute().await.is_err());
+    generate_genesis(collections_file).execute().await.unwrap();
+
+    let genesis = read_genesis(&output_dir.path().join("genesis.blob")).unwrap();
+    let state = genesis_state(&genesis).unwrap();
+    let collections_tag = StructTag {
+        address: AccountAddress::from_hex_literal("0x3").unwrap(),
+        module: Identifier::new("token").unwrap(),
+        name: Identifier::new("Collections").unwrap(),
+        type_params: vec![],
+    };
+    let collections_of = |creator| {
+        state
+            .get(&StateKey::AccessPath(AccessPath::new(
+                creator,
+                AccessPath::resource_access_vec(collections_tag.clone()),
+            )))
+            .expect("Collections should be created for the creator")
+    };
+    let collections = collections_of(creator);
+    assert_ne!(collections, collections_of(other_creator));
+
+    // `Collections` starts with the handles of its collection and token data tables
+    let handle = |index: usize| {
+        let start = index * AccountAddress::LENGTH;
+        TableHandle(
+            AccountAddress::from_bytes(&collections[start..start + AccountAddress::LENGTH])
+                .unwrap(),
+        )
+    };
+    let table_keys = |table: TableHandle| {
+        state
+            .keys()
+            .filter_map(|key| match key {
+                StateKey::TableItem { handle, key } if *handle == table => Some(key.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        table_keys(handle(0)),
+        vec![bcs::to_bytes(&collection.name).unwrap()]
+    );
+    assert_eq!(table_keys(handle(1)).len(), 2);
+}
+
+#[tokio::test]
+async fn test_genesis_with_coins() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let issuer = owner_address(&git_options, "user-0");
+    let holder = owner_address(&git_options, "user-1");
+
+    let coin = GenesisCoin {
+        issuer,
+        module_name: "moon_coin".to_string(),
+        struct_name: "MoonCoin".to_string(),
+        name: "Moon Coin".to_string(),
+        symbol: "MOON".to_string(),
+        decimals: 6,
+        holders: vec![
+            GenesisCoinHolder {
+                address: issuer,
+                amount: 1_000,
+            },
+            GenesisCoinHolder {
+                address: holder,
+                amount: 2_000,
+            },
+        ],
+    };
+    let write_coins = |name: &str, coins: Vec<GenesisCoin>| {
+        let file = dir.path().join(name);
+        write_to_file(
+            &file,
+            name,
+            serde_yaml::to_string(&coins).unwrap().as_bytes(),
+        )
+        .unwrap();
+        file
+    };
+    let coins_file = write_coins("coins.yaml", vec![coin.clone()]);
+    let unknown_holder_file = write_coins(
+        "unknown_holder.yaml",
+        vec![GenesisCoin {
+            holders: vec![GenesisCoinHolder {
+                address: AccountAddress::from_hex_literal("0x1234").unwrap(),
+                amount: 1,
+            }],
+            ..coin.clone()
+        }],
+    );
+    let duplicate_symbol_file = write_coins(
+        "duplicate_symbol.yaml",
+        vec![
+            coin.clone(),
+            GenesisCoin {
+                struct_name: "OtherCoin".to_string(),
+                name: "Other Coin".to_string(),
+                ..coin.clone()
+            },
+        ],
+    );
+    let native_name_file = write_coins(
+        "native_name.yaml",
+        vec![GenesisCoin {
+            name: NATIVE_COIN_NAME.to_string(),
+            ..coin.clone()
+        }],
+    );
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis = |coins_file| GenerateGenesis {
+        coins_file: Some(coins_file),
+        ..generate_genesis_command(git_options.clone(), PathBuf::from(output_dir.path()))
+    };
+
+    // Holders must be configured accounts, and names and symbols must be unique
+    for invalid_file in [unknown_holder_file, duplicate_symbol_file, native_name_file] {
+        assert!(generate_genesis(invalid_file).execute().await.is_err());
+    }
+    generate_genesis(coins_file).execute().await.unwrap();
+
+    let genesis = read_genesis(&output_dir.path().join("genesis.blob")).unwrap();
+    let state = genesis_state(&genesis).unwrap();
+    let coin_resource = |address: AccountAddress, name: &str| {
+        let tag = StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("coin").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![coin.type_tag()],
+        };
+        state
+            .get(&StateKey::AccessPath(AccessPath::new(
+                address,
+                AccessPath::resource_access_vec(tag),
+            )))
+            .unwrap_or_else(|| panic!("{} should exist at {}", name, address))
+    };
+
+    let coin_info: CoinInfoResource = bcs::from_bytes(coin_resource(issuer, "CoinInfo")).unwrap();
+    assert_eq!(coin_info.name().unwrap(), coin.name);
+    assert_eq!(coin_info.symbol().unwrap(), coin.symbol);
+    assert_eq!(coin_info.decimals(), coin.decimals);
+    let supply = coin_info
+        .supply()
+        .as_ref()
+        .unwrap()
+        .integer
+        .as_ref()
+        .unwrap();
+    assert_eq!(supply.value, 3_000);
+
+    for holder in &coin.holders {
+        let coin_store: CoinStoreResource =
+            bcs::from_bytes(coin_resource(holder.address, "CoinStore")).unwrap();
+        assert_eq!(coin_store.coin(), holder.amount);
+    }
+}
+
+#[tokio::test]
+async fn test_generate_onboarding_sheets() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let genesis_dir = TempPath::new();
+    genesis_dir.create_as_dir().unwrap();
+    let waypoint = generate_genesis(git_options.clone(), genesis_dir.path().to_path_buf(), false)
+        .await
+        .waypoint
+        .to_string();
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let sheets = GenerateOnboardingSheets {
+        genesis_dir: Some(genesis_dir.path().to_path_buf()),
+        output_dir: Some(output_dir.path().to_path_buf()),
+        node_config_dir: "/opt/aptos/etc".to_string(),
+        prompt_options: PromptOptions::yes(),
+        git_options: git_options.clone(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    assert_eq!(sheets.len(), 2);
+
+    // Every sheet has the user's own accounts, and the waypoint shared by all validators
+    for user in ["user-0", "user-1"] {
+        let sheet = String::from_utf8(
+            read_from_file(&output_dir.path().join(format!("{}.md", user))).unwrap(),
+        )
+        .unwrap();
+        let owner = owner_address(&git_options, user).to_hex_literal();
+        assert!(sheet.contains(&format!("Owner: {}", owner)));
+        assert!(sheet.contains(&waypoint));
+        assert!(sheet.contains("aptos-node -f /opt/aptos/etc/validator.yaml"));
+    }
+    let other_owner = owner_address(&git_options, "user-1").to_hex_literal();
+    let sheet =
+        String::from_utf8(read_from_file(&output_dir.path().join("user-0.md")).unwrap()).unwrap();
+    assert!(!sheet.contains(&other_owner));
+}
+
+#[tokio::test]
+async fn test_simulate_validator_set_what_if() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let min_stake = Layout::default().min_stake;
+    let simulate = |what_if_file| SimulateValidatorSet {
+        what_if_file,
+        git_options: git_options.clone(),
+    };
+
+    // Both validators are staked within the limits
+    let configured = simulate(None).execute().await.unwrap();
+    assert!(configured
+        .validators
+        .iter()
+        .all(|validator| validator.active));
+    assert!(configured.changes.is_empty());
+    let configured_stake = configured
+        .validators
+        .iter()
+        .find(|validator| validator.user == "user-1")
+        .unwrap()
+        .stake_amount;
+
+    // Dropping a validator below the minimum stake takes it out of the set
+    let what_if_file = dir.path().join("what_if.yaml");
+    let overrides = BTreeMap::from([("user-1".to_string(), min_stake - 1)]);
+    write_to_file(
+        &what_if_file,
+        "what_if.yaml",
+        serde_yaml::to_string(&overrides).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let what_if = simulate(Some(what_if_file)).execute().await.unwrap();
+    assert_eq!(
+        what_if.changes,
+        vec![ValidatorSetChange {
+            user: "user-1".to_string(),
+            stake_before: configured_stake,
+            stake_after: min_stake - 1,
+            active_before: true,
+            active_after: false,
+        }]
+    );
+    assert_eq!(
+        what_if.active_voting_power,
+        configured.active_voting_power - configured_stake as u128
+    );
+
+    // The configured stakes are untouched
+    let after = simulate(None).execute().await.unwrap();
+    assert_eq!(after, configured);
+
+    let unknown_user_file = dir.path().join("unknown_user.yaml");
+    write_to_file(
+        &unknown_user_file,
+        "unknown_user.yaml",
+        b"user-9: 100000000000000\n",
+    )
+    .unwrap();
+    assert!(simulate(Some(unknown_user_file)).execute().await.is_err());
+}
+
+#[tokio::test]
+async fn test_show_genesis_validators() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    workspace.generate_genesis(false).await;
+    let show = |git_options| ShowGenesisValidators {
+        genesis_file: Some(workspace.genesis_path()),
+        format: ValidatorSetFormat::Json,
+        git_options,
+        faucet_account: None,
+    };
+
+    // Every configured validator is active with its configured stake, in validator index order
+    let validator_set = show(workspace.git_options()).execute().await.unwrap();
+    let client = workspace.git_options().get_client().unwrap();
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    assert_eq!(validator_set.min_stake, layout.min_stake);
+    assert_eq!(validator_set.max_stake, layout.max_stake);
+    let mut users: Vec<_> = validator_set
+        .validators
+        .iter()
+        .map(|validator| validator.user.clone().unwrap())
+        .collect();
+    users.sort();
+    assert_eq!(users, layout.users);
+    for validator in &validator_set.validators {
+        let config = get_config(&client, validator.user.as_ref().unwrap()).unwrap();
+        assert_eq!(validator.pool_address, config.owner_account_address);
+        assert_eq!(validator.operator_address, config.operator_account_address);
+        assert_eq!(validator.stake, config.stake_amount);
+        assert_eq!(validator.voting_power, config.stake_amount);
+        assert_eq!(validator.status, ValidatorStatus::Active);
+        assert!(!validator.below_minimum_stake);
+    }
+    assert_eq!(
+        validator_set.total_voting_power,
+        validator_set
+            .validators
+            .iter()
+            .map(|validator| validator.voting_power as u128)
+            .sum::<u128>()
+    );
+
+    // Without the repository, validators are only listed by address
+    let unlabelled = show(GitOptions::default()).execute().await.unwrap();
+    assert!(unlabelled
+        .validators
+        .iter()
+        .all(|validator| validator.user.is_none()));
+    assert_eq!(
+        unlabelled
+            .validators
+            .iter()
+            .map(|validator| validator.pool_address)
+            .collect::<Vec<_>>(),
+        validator_set
+            .validators
+            .iter()
+            .map(|validator| validator.pool_address)
+            .collect::<Vec<_>>()
+    );
+
+    // The table has a row per validator, after the header
+    let table = validator_set.to_string();
+    assert_eq!(table.lines().count(), 2 + validator_set.validators.len());
+    for user in &layout.users {
+        assert!(table.contains(user.as_str()), "{}", table);
+    }
+}
+
+#[tokio::test]
+async fn test_show_genesis_validators_below_minimum_stake() {
+    let min_stake = Layout::default().min_stake;
+    let stakes = [min_stake, min_stake - 1, min_stake + 1];
+    let workspace = GenesisWorkspace::with_stakes(&stakes).await;
+    workspace.generate_genesis(false).await;
+    let show = |git_options| ShowGenesisValidators {
+        genesis_file: Some(workspace.genesis_path()),
+        format: ValidatorSetFormat::Table,
+        git_options,
+        faucet_account: None,
+    };
+
+    // The validator left out of genesis for its stake is listed last, flagged, without a pool
+    let validator_set = show(workspace.git_options()).execute().await.unwrap();
+    assert_eq!(validator_set.validators.len(), 3);
+    let (excluded, validators) = validator_set.validators.split_last().unwrap();
+    assert_eq!(excluded.user.as_deref(), Some("user-1"));
+    assert_eq!(
+        excluded.pool_address,
+        owner_address(&workspace.git_options(), "user-1")
+    );
+    assert_eq!(excluded.stake, min_stake - 1);
+    assert_eq!(excluded.voting_power, 0);
+    assert_eq!(excluded.status, ValidatorStatus::Excluded);
+    assert!(excluded.below_minimum_stake);
+
+    // The validators at and above the minimum are active, and not flagged
+    for validator in validators {
+        assert_eq!(validator.status, ValidatorStatus::Active);
+        assert!(!validator.below_minimum_stake);
+        assert!(validator.stake >= min_stake);
+    }
+    assert_eq!(
+        validator_set.total_voting_power,
+        (stakes[0] + stakes[2]) as u128
+    );
+    let table = validator_set.to_string();
+    let row = table
+        .lines()
+        .find(|line| line.starts_with("user-1"))
+        .unwrap();
+    assert!(row.ends_with("excluded (below minimum stake)"), "{}", table);
+
+    // Without the repository, only the pools in genesis are known
+    let unlabelled = show(GitOptions::default()).execute().await.unwrap();
+    assert_eq!(unlabelled.validators.len(), 2);
+    assert!(unlabelled
+        .validators
+        .iter()
+        .all(|validator| !validator.below_minimum_stake));
+}
+
+#[tokio::test]
+async fn test_genesis_with_unequal_stakes() {
+    let stakes = [
+        DEFAULT_STAKE_AMOUNT,
+        2 * DEFAULT_STAKE_AMOUNT,
+        4 * DEFAULT_STAKE_AMOUNT,
+    ];
+    let workspace = GenesisWorkspace::with_stakes(&stakes).await;
+    workspace.generate_genesis(false).await;
+
+    // Each validator's voting power is its own stake
+    let client = workspace.git_options().get_client().unwrap();
+    let genesis = read_genesis(&workspace.genesis_path()).unwrap();
+    let validator_set = genesis_validator_set(&genesis, Some(&client), None).unwrap();
+    let voting_powers: BTreeMap<_, _> = validator_set
+        .validators
+        .iter()
+        .map(|validator| (validator.user.clone().unwrap(), validator.voting_power))
+        .collect();
+    assert_eq!(
+        voting_powers,
+        BTreeMap::from([
+            ("user-0".to_string(), stakes[0]),
+            ("user-1".to_string(), stakes[1]),
+            ("user-2".to_string(), stakes[2]),
+        ])
+    );
+    assert_eq!(
+        validator_set.total_voting_power,
+        stakes.iter().map(|stake| *stake as u128).sum::<u128>()
+    );
+
+    // Too few validators to spread the stake aren't warned about
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    let mut validators: Vec<_> = layout
+        .sorted_users()
+        .into_iter()
+        .map(|user| get_config(&client, user).unwrap())
+        .collect();
+    assert!(stake_concentration_warnings(&validators).is_empty());
+
+    // Otherwise the validator with more than a third of the stake is warned about
+    let mut concentrated = in_memory_validators(4, DEFAULT_STAKE_AMOUNT);
+    concentrated[3].stake_amount = 2 * DEFAULT_STAKE_AMOUNT;
+    let warnings = stake_concentration_warnings(&concentrated);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains(&concentrated[3].owner_account_address.to_string()));
+    let balanced = in_memory_validators(4, DEFAULT_STAKE_AMOUNT);
+    assert!(stake_concentration_warnings(&balanced).is_empty());
+
+    // Every stake outside of the layout's limits is reported at once
+    assert!(validate_stakes(&layout, validators.clone(), true).is_ok());
+    validators[0].stake_amount = layout.min_stake - 1;
+    validators[2].stake_amount = layout.max_stake + 1;
+    match validate_stakes(&layout, validators, true) {
+        Err(CliError::GenesisError(GenesisError::ValidatorConfigInvalid(errors))) => {
+            assert_eq!(errors.len(), 2, "{:?}", errors);
+            assert!(errors[0].contains("min_stake"), "{:?}", errors);
+            assert!(errors[1].contains("max_stake"), "{:?}", errors);
+        }
+        result => panic!("Expected invalid stakes, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_genesis_min_stake() {
+    let min_stake = Layout::default().min_stake;
+    let stakes = [min_stake, min_stake - 1, min_stake + 1];
+    let workspace = GenesisWorkspace::with_stakes(&stakes).await;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let progress = {
+        let warnings = warnings.clone();
+        ProgressReporter::with_callback(Arc::new(move |progress: &GenesisProgress| {
+            if let GenesisProgress::Warning { message } = progress {
+                warnings.lock().unwrap().push(message.clone());
+            }
+        }))
+    };
+    let generate = |strict_min_stake| GenerateGenesis {
+        force: true,
+        strict_min_stake,
+        progress: progress.clone(),
+        ..generate_genesis_command(
+            workspace.git_options(),
+            workspace.output_dir().to_path_buf(),
+        )
+    };
+
+    // The validator just below the minimum is rejected with --strict-min-stake
+    let below_minimum = owner_address(&workspace.git_options(), "user-1");
+    match generate(true).execute().await {
+        Err(CliError::GenesisError(GenesisError::ValidatorConfigInvalid(errors))) => {
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(
+                errors[0].contains(&below_minimum.to_string()),
+                "{:?}",
+                errors
+            );
+        }
+        result => panic!(
+            "Expected the stake below min_stake to be rejected: {:?}",
+            result
+        ),
+    }
+    assert!(!workspace.genesis_path().exists());
+
+    // Otherwise it's left out, and the validators at and above the minimum are kept
+    let output = generate(false).execute().await.unwrap();
+    assert_eq!(output.validators, 2);
+    assert_eq!(output.total_stake, (stakes[0] + stakes[2]) as u128);
+    let excluded = ExcludedValidator {
+        username: "user-1".to_string(),
+        owner_account_address: below_minimum,
+        stake_amount: stakes[1],
+    };
+    assert_eq!(output.excluded_validators, vec![excluded.clone()]);
+    assert!(warnings.lock().unwrap().contains(&excluded.to_string()));
+    let client = workspace.git_options().get_client().unwrap();
+    let genesis = read_genesis(&workspace.genesis_path()).unwrap();
+    let validator_set = genesis_validator_set(&genesis, Some(&client), None).unwrap();
+    let statuses: BTreeMap<_, _> = validator_set
+        .validators
+        .iter()
+        .map(|validator| (validator.user.clone().unwrap(), validator.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        BTreeMap::from([
+            ("user-0".to_string(), ValidatorStatus::Active),
+            ("user-1".to_string(), ValidatorStatus::Excluded),
+            ("user-2".to_string(), ValidatorStatus::Active),
+        ])
+    );
+
+    // Verifying genesis reports the validators in it, apart from the one left out
+    let verified = verify_genesis(
+        &workspace.genesis_path(),
+        &workspace.waypoint_path(),
+        &client,
+    )
+    .unwrap();
+    assert_eq!(verified.validators, vec!["user-0", "user-2"]);
+    assert_eq!(verified.excluded_validators, vec!["user-1"]);
+}
+
+#[tokio::test]
+async fn test_incremental_genesis_validates_only_new_validators() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let generate_genesis = |output_dir: &Path, incremental| GenerateGenesis {
+        incremental,
+        force: true,
+        ..generate_genesis_command(git_options.clone(), PathBuf::from(output_dir))
+    };
+    let incremental_dir = TempPath::new();
+    incremental_dir.create_as_dir().unwrap();
+    generate_genesis(incremental_dir.path(), true)
+        .execute()
+        .await
+        .unwrap();
+
+    // A third validator joins the ceremony
+    let client = git_options.clone().get_client().unwrap();
+    let mut layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    layout.users.push("user-2".to_string());
+    client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+    let keys_dir = generate_keys(dir.path(), 2).await;
+    set_validator_config("user-2".to_string(), git_options.clone(), &keys_dir).await;
+
+    // Only the new validator's configuration is validated again
+    let validated_inputs_file = incremental_dir.path().join(VALIDATED_INPUTS_FILE);
+    let mut validated = ValidatedInputs::load(&validated_inputs_file).unwrap();
+    assert_eq!(validated.validators.len(), 2);
+    let (validators, validated_users) = validated.update(&client, &layout).unwrap();
+    assert_eq!(validators.len(), 3);
+    assert_eq!(validated_users, vec!["user-2".to_string()]);
+
+    // The incremental genesis matches one generated from scratch
+    generate_genesis(incremental_dir.path(), true)
+        .execute()
+        .await
+        .unwrap();
+    let full_dir = TempPath::new();
+    full_dir.create_as_dir().unwrap();
+    generate_genesis(full_dir.path(), false)
+        .execute()
+        .await
+        .unwrap();
+    for file in ["genesis.blob", "waypoint.txt"] {
+        assert_eq!(
+            read_from_file(&incremental_dir.path().join(file)).unwrap(),
+            read_from_file(&full_dir.path().join(file)).unwrap()
+        );
+    }
+    assert_eq!(
+        ValidatedInputs::load(&validated_inputs_file)
+            .unwrap()
+            .validators
+            .len(),
+        3
+    );
+
+    let genesis = read_genesis(&incremental_dir.path().join("genesis.blob")).unwrap();
+    let owner = owner_address(&git_options, "user-2");
+    let stake_pool: StakePool = assert_genesis_resource(&genesis, owner);
+    assert_eq!(stake_pool.active, 100_000_000_000_000);
+}
+
+#[tokio::test]
+async fn test_generate_genesis_resume() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis = |resume, force| GenerateGenesis {
+        resume,
+        force,
+        ..generate_genesis_command(git_options.clone(), output_dir.path().to_path_buf())
+    };
+    let output = generate_genesis(false, false).execute().await.unwrap();
+    assert!(output
+        .files
+        .contains(&output_dir.path().join(GENESIS_INPUTS_FILE)));
+
+    // An existing genesis isn't overwritten by accident
+    assert!(matches!(
+        generate_genesis(false, false).execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+
+    // Resuming with unchanged inputs keeps the existing genesis
+    let genesis_file = output_dir.path().join("genesis.blob");
+    write_to_file(&genesis_file, "genesis.blob", b"kept").unwrap();
+    let waypoint_file = output_dir.path().join("waypoint.txt");
+    let kept = generate_genesis(true, false).execute().await.unwrap();
+    assert_eq!(kept.files, vec![genesis_file.clone(), waypoint_file]);
+    assert_eq!(kept.waypoint, output.waypoint);
+    assert_eq!(kept.validators, 2);
+    assert_eq!(kept.total_stake, output.total_stake);
+    assert_eq!(read_from_file(&genesis_file).unwrap(), b"kept");
+
+    // Once the inputs change, genesis is generated again
+    let client = git_options.clone().get_client().unwrap();
+    let mut layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    layout.epoch_duration_secs += 1;
+    client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+    generate_genesis(true, false).execute().await.unwrap();
+    read_genesis(&genesis_file).unwrap();
+
+    // Or whenever it's forced
+    write_to_file(&genesis_file, "genesis.blob", b"kept").unwrap();
+    generate_genesis(false, true).execute().await.unwrap();
+    read_genesis(&genesis_file).unwrap();
+}
+
+#[tokio::test]
+async fn test_generate_genesis_file_names() {
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let generate_genesis =
+        |git_options, genesis_file_name: &str, waypoint_file_name: &str| GenerateGenesis {
+            genesis_file_name: genesis_file_name.to_string(),
+            waypoint_file_name: waypoint_file_name.to_string(),
+            output_format: GenesisOutputFormat::Json,
+            manifest: true,
+            incremental: true,
+            ..generate_genesis_command(git_options, output_dir.path().to_path_buf())
+        };
+
+    // Genesis of two networks is written side by side, each under its own names, along with
+    // outputs named after each genesis file
+    let mut waypoints = vec![];
+    for (chain_id, genesis_file_name, waypoint_file_name) in [
+        (40, "devnet.blob", "devnet-waypoint.txt"),
+        (41, "testnet.blob", "testnet-waypoint.txt"),
+    ] {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let git_options = create_users_on_chain(1, &dir, ChainId::new(chain_id)).await;
+        let output = generate_genesis(git_options, genesis_file_name, waypoint_file_name)
+            .execute()
+            .await
+            .unwrap();
+        let genesis_file = output_dir.path().join(genesis_file_name);
+        let waypoint_file = output_dir.path().join(waypoint_file_name);
+        assert_eq!(
+            output.files[..2],
+            [genesis_file.clone(), waypoint_file.clone()]
+        );
+        assert_eq!(read_waypoint(&waypoint_file).unwrap(), output.waypoint);
+        assert_eq!(
+            verify_waypoint(&genesis_file, output.waypoint).unwrap(),
+            output.waypoint
+        );
+        let network = genesis_file_name.trim_end_matches(".blob");
+        for file_name in [
+            GENESIS_JSON_FILE,
+            INTEROP_MANIFEST_FILE,
+            VALIDATED_INPUTS_FILE,
+        ] {
+            let file = output_dir.path().join(format!("{}.{}", network, file_name));
+            assert!(output.files.contains(&file), "{:?}", output.files);
+        }
+        let manifest = InteropManifest::read(
+            &output_dir
+                .path()
+                .join(format!("{}.{}", network, INTEROP_MANIFEST_FILE)),
+        )
+        .unwrap();
+        assert_eq!(manifest.waypoint, output.waypoint);
+        waypoints.push(output.waypoint);
+    }
+    assert_ne!(waypoints[0], waypoints[1]);
+    for file_name in [
+        GENESIS_FILE,
+        WAYPOINT_FILE,
+        GENESIS_JSON_FILE,
+        INTEROP_MANIFEST_FILE,
+        VALIDATED_INPUTS_FILE,
+    ] {
+        assert!(!output_dir.path().join(file_name).exists());
+    }
+
+    // Names can't point outside of the output directory, nor be shared by both files
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    for (genesis_file_name, waypoint_file_name) in [
+        ("../genesis.blob", WAYPOINT_FILE),
+        (GENESIS_FILE, "nested/waypoint.txt"),
+        ("genesis", "genesis"),
+    ] {
+        assert!(matches!(
+            generate_genesis(git_options.clone(), genesis_file_name, waypoint_file_name)
+                .execute()
+                .await,
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_signed_validator_configs() {
+    let workspace = GenesisWorkspace::with_users(2).await;
+    let private_keys_file = |index: u8| {
+        workspace
+            .keys_dir()
+            .join(index.to_string())
+            .join(PRIVATE_KEYS_FILE)
+    };
+    let sign_with =
+        |index: u8, public_identity_file, private_keys_file| SetValidatorConfiguration {
+            username: format!("user-{}", index),
+            output_options: OutputOptions::default(),
+            git_options: workspace.git_options(),
+            owner_public_identity_file: Some(public_identity_file),
+            validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+            stake_amount: 100_000_000_000_000,
+            full_node_host: None,
+            operator_public_identity_file: None,
+            voter_public_identity_file: None,
+            commission_percentage: 0,
+            check_reachability: false,
+            commit: false,
+            owner_private_keys_file: Some(private_keys_file),
+            passphrase_options: PassphraseOptions::default(),
+        };
+    let sign = |index: u8, private_keys_file| {
+        sign_with(index, workspace.public_keys_path(index), private_keys_file)
+    };
+    let register_owner_keys = |indices: &[u8]| {
+        let client = workspace.git_options().get_client().unwrap();
+        let mut layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+        layout.owner_public_keys = indices
+            .iter()
+            .map(|index| {
+                let identity = read_public_identity_file(&workspace.public_keys_path(*index));
+                (
+                    format!("user-{}", index),
+                    identity.unwrap().account_public_key,
+                )
+            })
+            .collect();
+        client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+    };
+    let generate = |require_signed_configs| GenerateGenesis {
+        force: true,
+        require_signed_configs,
+        ..generate_genesis_command(
+            workspace.git_options(),
+            workspace.output_dir().to_path_buf(),
+        )
+    };
+    let signature_failures = |result: CliTypedResult<GenerateGenesisOutput>| match result {
+        Err(CliError::GenesisError(GenesisError::ConfigSignatureInvalid(failures))) => failures,
+        result => panic!("Expected configurations with bad signatures: {:?}", result),
+    };
+
+    // Configurations are only checked against owner keys registered in the layout
+    let failures = signature_failures(generate(true).execute().await);
+    assert_eq!(failures.len(), 2);
+    assert!(
+        failures[0].starts_with("user-0: no owner key is registered"),
+        "{}",
+        failures[0]
+    );
+    register_owner_keys(&[0, 1]);
+
+    // Unsigned configurations are only rejected when signatures are required
+    generate(false).execute().await.unwrap();
+    let failures = signature_failures(generate(true).execute().await);
+    assert_eq!(failures.len(), 2);
+    assert!(failures[0].starts_with("user-0: user-0/config.sig is missing"));
+
+    // Only the owner's key can sign its configuration
+    assert!(matches!(
+        sign(0, private_keys_file(1)).execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+    assert!(!workspace
+        .git_dir()
+        .join("user-0")
+        .join(CONFIG_SIGNATURE_FILE)
+        .exists());
+
+    // Configurations signed by their owners are accepted
+    for index in 0..2 {
+        sign(index, private_keys_file(index))
+            .execute()
+            .await
+            .unwrap();
+    }
+    generate(true).execute().await.unwrap();
+
+    // A configuration changed after it was signed is rejected
+    let owner_file = workspace.owner_path("user-1");
+    let mut owner_config: StringOwnerConfiguration =
+        from_yaml(&String::from_utf8(read_from_file(&owner_file).unwrap()).unwrap()).unwrap();
+    owner_config.commission_percentage = Some("10".to_string());
+    write_to_file(
+        &owner_file,
+        OWNER_FILE,
+        serde_yaml::to_string(&owner_config).unwrap().as_bytes(),
+    )
+    .unwrap();
+    let failures = signature_failures(generate(true).execute().await);
+    assert_eq!(failures.len(), 1);
+    assert!(
+        failures[0].starts_with("user-1: user-1/config.sig is not a signature"),
+        "{}",
+        failures[0]
+    );
+
+    // As is a signature moved to another user's directory
+    sign(1, private_keys_file(1)).execute().await.unwrap();
+    generate(true).execute().await.unwrap();
+    std::fs::copy(
+        workspace
+            .git_dir()
+            .join("user-1")
+            .join(CONFIG_SIGNATURE_FILE),
+        workspace
+            .git_dir()
+            .join("user-0")
+            .join(CONFIG_SIGNATURE_FILE),
+    )
+    .unwrap();
+    let failures = signature_failures(generate(true).execute().await);
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with("user-0: "), "{}", failures[0]);
+
+    // A configuration swapped for one owned and signed by another key is rejected, though its
+    // signature is valid for the key in the configuration
+    let other_keys_dir = generate_keys(workspace.keys_dir(), 5).await;
+    sign_with(
+        0,
+        other_keys_dir.join(PUBLIC_KEYS_FILE),
+        other_keys_dir.join(PRIVATE_KEYS_FILE),
+    )
+    .execute()
+    .await
+    .unwrap();
+    let failures = signature_failures(generate(true).execute().await);
+    assert_eq!(failures.len(), 1);
+    assert!(
+        failures[0].contains("is not the owner key") && failures[0].contains("registered"),
+        "{}",
+        failures[0]
+    );
+    sign(0, private_keys_file(0)).execute().await.unwrap();
+    generate(true).execute().await.unwrap();
+
+    // Encrypted private keys are decrypted with the passphrase to sign
+    let passphrase_file = workspace.keys_dir().join("passphrase.txt");
+    write_to_file(&passphrase_file, "passphrase", b"correct horse").unwrap();
+    let encrypted_keys_file = write_encrypted_file(
+        &private_keys_file(1),
+        PRIVATE_KEYS_FILE,
+        "correct horse",
+        &read_from_file(&private_keys_file(1)).unwrap(),
+    )
+    .unwrap();
+    let sign_encrypted = |passphrase_file| SetValidatorConfiguration {
+        passphrase_options: PassphraseOptions {
+            passphrase_file: Some(passphrase_file),
+        },
+        ..sign(1, encrypted_keys_file.clone())
+    };
+    let wrong_passphrase_file = workspace.keys_dir().join("wrong-passphrase.txt");
+    write_to_file(&wrong_passphrase_file, "passphrase", b"wrong").unwrap();
+    assert!(sign_encrypted(wrong_passphrase_file)
+        .execute()
+        .await
+        .is_err());
+    sign_encrypted(passphrase_file).execute().await.unwrap();
+    generate(true).execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_signed_genesis_manifest() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let root_key = root_private_key(2);
+    let root_key_file = dir.path().join("root.key");
+    write_to_file(
+        &root_key_file,
+        "root.key",
+        &EncodingType::Hex.encode_key("root key", &root_key).unwrap(),
+    )
+    .unwrap();
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let files = GenerateGenesis {
+        root_private_key_file: Some(root_key_file),
+        ..generate_genesis_command(git_options.clone(), output_dir.path().to_path_buf())
+    }
+    .execute()
+    .await
+    .unwrap();
+    let manifest_file = output_dir.path().join(MANIFEST_FILE);
+    assert!(files.contains(&manifest_file));
+
+    // The manifest is checked against the layout's root key
+    let verify = || VerifyManifest {
+        genesis_dir: Some(output_dir.path().to_path_buf()),
+        genesis_file_name: GENESIS_FILE.to_string(),
+        waypoint_file_name: WAYPOINT_FILE.to_string(),
+        git_options: git_options.clone(),
+    };
+    let manifest = verify().execute().await.unwrap();
+    assert_eq!(manifest.chain_id, ChainId::test());
+    let genesis_file = output_dir.path().join("genesis.blob");
+    let genesis = read_from_file(&genesis_file).unwrap();
+    assert_eq!(manifest.genesis_hash, HashValue::sha3_256_of(&genesis));
+
+    // A manifest signed by another key than the layout's, or with no key in the layout, is
+    // rejected
+    let client = git_options.clone().get_client().unwrap();
+    let mut layout: Layout = client.get(Path::new(LAYOUT_FILE)).unwrap();
+    layout.root_key = Some(Ed25519PrivateKey::generate_for_testing().public_key());
+    client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+    assert!(verify().execute().await.is_err());
+    layout.root_key = None;
+    client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+    assert!(matches!(
+        verify().execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+    layout.root_key = Some(root_key.public_key());
+    client.put(Path::new(LAYOUT_FILE), &layout).unwrap();
+
+    // Editing a field of the manifest invalidates its signature
+    let signed: SignedGenesisManifest =
+        from_yaml(&String::from_utf8(read_from_file(&manifest_file).unwrap()).unwrap()).unwrap();
+    let mut tampered = signed.clone();
+    tampered.manifest.chain_id = ChainId::new(42);
+    write_to_file(
+        &manifest_file,
+        MANIFEST_FILE,
+        serde_yaml::to_string(&tampered).unwrap().as_bytes(),
+    )
+    .unwrap();
+    assert!(verify().execute().await.is_err());
+    write_to_file(
+        &manifest_file,
+        MANIFEST_FILE,
+        serde_yaml::to_string(&signed).unwrap().as_bytes(),
+    )
+    .unwrap();
+    verify().execute().await.unwrap();
+
+    // A genesis blob that doesn't match the manifest is rejected
+    let mut modified = genesis;
+    modified.push(0);
+    write_to_file(&genesis_file, "genesis.blob", &modified).unwrap();
+    assert!(verify().execute().await.is_err());
+}
+
+#[tokio::test]
+async fn test_custom_genesis_file_names() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let root_key_file = dir.path().join("root.key");
+    write_to_file(
+        &root_key_file,
+        "root.key",
+        &EncodingType::Hex
+            .encode_key("root key", &root_private_key(2))
+            .unwrap(),
+    )
+    .unwrap();
+
+    let genesis_file_name = "devnet.blob".to_string();
+    let waypoint_file_name = "devnet.waypoint.txt".to_string();
+    let mut output_dirs = Vec::new();
+    for _ in 0..2 {
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        let output_dir = PathBuf::from(output_dir.path());
+        GenerateGenesis {
+            genesis_file_name: genesis_file_name.clone(),
+            waypoint_file_name: waypoint_file_name.clone(),
+            root_private_key_file: Some(root_key_file.clone()),
+            ..generate_genesis_command(git_options.clone(), output_dir.clone())
+        }
+        .execute()
+        .await
+        .unwrap();
+        output_dirs.push(output_dir);
+    }
+
+    // Genesis written under other names is verified, checked against its manifest and diffed
+    // under the same names
+    let verify = |genesis_file_name: &str, waypoint_file_name: &str| VerifyGenesis {
+        genesis_dir: Some(output_dirs[0].clone()),
+        genesis_file_name: genesis_file_name.to_string(),
+        waypoint_file_name: waypoint_file_name.to_string(),
+        git_options: git_options.clone(),
+    };
+    let verified = verify(&genesis_file_name, &waypoint_file_name)
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(verified.validators.len(), 2);
+
+    let verify_manifest = |genesis_file_name: &str, waypoint_file_name: &str| VerifyManifest {
+        genesis_dir: Some(output_dirs[0].clone()),
+        genesis_file_name: genesis_file_name.to_string(),
+        waypoint_file_name: waypoint_file_name.to_string(),
+        git_options: git_options.clone(),
+    };
+    let manifest = verify_manifest(&genesis_file_name, &waypoint_file_name)
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(manifest.waypoint, verified.waypoint);
+
+    let diff = |genesis_file_name: &str, waypoint_file_name: &str| DiffGenesis {
+        first_dir: output_dirs[0].clone(),
+        second_dir: output_dirs[1].clone(),
+        genesis_file_name: genesis_file_name.to_string(),
+        waypoint_file_name: waypoint_file_name.to_string(),
+        state: false,
+    };
+    let genesis_diff = diff(&genesis_file_name, &waypoint_file_name)
+        .execute()
+        .await
+        .unwrap();
+    assert!(genesis_diff.identical);
+
+    // None of the default names were written
+    assert!(verify(GENESIS_FILE, WAYPOINT_FILE).execute().await.is_err());
+    assert!(verify_manifest(GENESIS_FILE, WAYPOINT_FILE)
+        .execute()
+        .await
+        .is_err());
+    assert!(diff(GENESIS_FILE, WAYPOINT_FILE).execute().await.is_err());
+}
+
+#[tokio::test]
+async fn test_genesis_start_paused() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    GenerateGenesis {
+        start_paused: true,
+        ..generate_genesis_command(git_options.clone(), PathBuf::from(output_dir.path()))
+    }
+    .execute()
+    .await
+    .unwrap();
+
+    let genesis = read_genesis(&output_dir.path().join("genesis.blob")).unwrap();
+    let state = genesis_state(&genesis).unwrap();
+    let framework_address = AccountAddress::from_hex_literal("0x1").unwrap();
+    let chain_status = Identifier::new("chain_status").unwrap();
+
+    // Only the validator accounts, and the root account of a test chain, can submit transactions
+    let paused_tag = StructTag {
+        address: framework_address,
+        module: chain_status.clone(),
+        name: Identifier::new("PausedMarker").unwrap(),
+        type_params: vec![],
+    };
+    let paused = state
+        .get(&StateKey::AccessPath(AccessPath::new(
+            framework_address,
+            AccessPath::resource_access_vec(paused_tag),
+        )))
+        .expect("Genesis should be paused");
+    let allowed_senders: Vec<AccountAddress> = bcs::from_bytes(paused).unwrap();
+    assert!(allowed_senders.contains(&aptos_test_root_address()));
+    for user in ["user-0", "user-1"] {
+        assert!(allowed_senders.contains(&owner_address(&git_options, user)));
+    }
+
+    // Governance can lift the pause through `chain_status::activate`
+    let module_bytes = state
+        .get(&StateKey::AccessPath(AccessPath::code_access_path(
+            ModuleId::new(framework_address, chain_status),
+        )))
+        .expect("chain_status should be published");
+    let module = CompiledModule::deserialize(module_bytes).unwrap();
+    assert!(module.function_defs().iter().any(|function| {
+        let handle = module.function_handle_at(function.function);
+        module.identifier_at(handle.name).as_str() == "activate"
+            && function.visibility == Visibility::Public
+    }));
+
+    // Without the option, genesis isn't paused
+    let unpaused_dir = TempPath::new();
+    unpaused_dir.create_as_dir().unwrap();
+    generate_genesis(git_options, PathBuf::from(unpaused_dir.path()), false).await;
+    let genesis = read_genesis(&unpaused_dir.path().join("genesis.blob")).unwrap();
+    assert!(!genesis_state(&genesis)
+        .unwrap()
+        .keys()
+        .any(|key| describe_state_key(key).contains("::chain_status::PausedMarker")));
+}
+
+#[tokio::test]
+async fn test_genesis_with_scheduled_features() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(2, &dir).await;
+
+    // Schedule a feature to turn on at epoch 5
+    let layout_file = git_options
+        .local_repository_dir
+        .as_ref()
+        .unwrap()
+        .join(LAYOUT_FILE);
+    let mut layout = Layout::from_disk(&layout_file).unwrap();
+    layout.scheduled_features = vec![ScheduledFeature {
+        feature: FeatureFlag::CODE_DEPENDENCY_CHECK,
+        epoch: 5,
+    }];
+    write_to_file(
+        &layout_file,
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let output_dir = PathBuf::from(output_dir.path());
+    generate_genesis(git_options, output_dir.clone(), false).await;
+
+    // Check that the schedule made it into genesis state
+    let genesis: Transaction =
+        bcs::from_bytes(&read_from_file(&output_dir.join("genesis.blob")).unwrap()).unwrap();
+    let change_set = match genesis {
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)) => change_set,
+        _ => panic!("Invalid genesis transaction"),
+    };
+    let WriteSet::V0(write_set) = change_set.write_set();
+    let bytes = write_set
+        .get(&StateKey::AccessPath(ScheduledFeatures::access_path()))
+        .expect("Scheduled features should be written in genesis")
+        .extract_raw_bytes()
+        .unwrap();
+    let scheduled: ScheduledFeatures = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        scheduled.features,
+        vec![FeatureFlag::CODE_DEPENDENCY_CHECK as u64]
+    );
+    assert_eq!(scheduled.epochs, vec![5]);
+}
+
+#[tokio::test]
+async fn test_genesis_rejects_past_scheduled_features() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+
+    let layout_file = git_options
+        .local_repository_dir
+        .as_ref()
+        .unwrap()
+        .join(LAYOUT_FILE);
+    let mut layout = Layout::from_disk(&layout_file).unwrap();
+    layout.scheduled_features = vec![ScheduledFeature {
+        feature: FeatureFlag::CODE_DEPENDENCY_CHECK,
+        epoch: 1,
+    }];
+    write_to_file(
+        &layout_file,
+        LAYOUT_FILE,
+        serde_yaml::to_string(&layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let command = generate_genesis_command(git_options, PathBuf::from(output_dir.path()));
+    assert!(matches!(
+        command.execute().await,
+        Err(CliError::GenesisError(GenesisError::LayoutInvalid(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_genesis_with_max_transaction_size() {
     let dir = TempPath::new();
     dir.create_as_dir().unwrap();
-    let git_options = create_users(2, &dir).await;
+    let git_options = create_users(1, &dir).await;
+    let layout_file = git_options
+        .local_repository_dir
+        .as_ref()
+        .unwrap()
+        .join(LAYOUT_FILE);
+    let set_max_transaction_size = |size: u64| {
+        let mut layout = Layout::from_disk(&layout_file).unwrap();
+        layout.max_transaction_size_in_bytes = Some(size);
+        write_to_file(
+            &layout_file,
+            LAYOUT_FILE,
+            serde_yaml::to_string(&layout).unwrap().as_bytes(),
+        )
+        .unwrap();
+    };
 
-    // Now generate genesis
+    // The custom limit is written into the genesis gas schedule
+    set_max_transaction_size(1024 * 1024);
     let output_dir = TempPath::new();
     output_dir.create_as_dir().unwrap();
     let output_dir = PathBuf::from(output_dir.path());
-    generate_genesis(git_options, output_dir.clone(), false).await;
+    generate_genesis(git_options.clone(), output_dir.clone(), false).await;
+    let genesis = read_genesis(&output_dir.join("genesis.blob")).unwrap();
+    let bytes = genesis_state(&genesis)
+        .unwrap()
+        .remove(&StateKey::AccessPath(GasScheduleV2::access_path()))
+        .expect("Gas schedule should be written in genesis");
+    let gas_schedule: GasScheduleV2 = bcs::from_bytes(&bytes).unwrap();
+    assert!(gas_schedule
+        .entries
+        .contains(&("txn.max_transaction_size_in_bytes".to_string(), 1024 * 1024)));
 
-    // TODO: Verify that these are good
-    let waypoint_file = output_dir.join("waypoint.txt");
-    assert!(waypoint_file.exists());
-    let genesis_file = output_dir.join("genesis.blob");
-    assert!(genesis_file.exists());
+    // Limits outside of the protocol range are rejected
+    set_max_transaction_size(MAX_TRANSACTION_SIZE_RANGE.end() + 1);
+    let output_dir = TempPath::new();
+    output_dir.create_as_dir().unwrap();
+    let command = generate_genesis_command(git_options, PathBuf::from(output_dir.path()));
+    assert!(matches!(
+        command.execute().await,
+        Err(CliError::GenesisError(GenesisError::LayoutInvalid(_)))
+    ));
 }
 
 #[tokio::test]
-async fn test_mainnet_genesis_e2e_flow() {
+async fn test_set_validator_configurations_from_roster() {
     let dir = TempPath::new();
     dir.create_as_dir().unwrap();
-    let git_options = create_users(2, &dir).await;
+    let names: Vec<String> = (0..3).map(|i| format!("user-{}", i)).collect();
+    let mut roster =
+        String::from("username,owner_key,operator_key,voter_key,host,stake,commission\n");
+    for (i, name) in names.iter().enumerate() {
+        let keys_dir = generate_keys(dir.path(), i as u8).await;
+        roster.push_str(&format!(
+            "{},{},,,localhost:{},100000000000000,{}\n",
+            name,
+            keys_dir.join(PUBLIC_KEYS_FILE).display(),
+            6180 + i,
+            i
+        ));
+    }
+    let roster_file = dir.path().join("roster.csv");
+    write_to_file(&roster_file, "Roster", roster.as_bytes()).unwrap();
 
-    // Create initial balances and employee vesting account files.
-    let git_dir = git_options.local_repository_dir.as_ref().unwrap().as_path();
-    create_account_balances_file(
-        PathBuf::from(git_dir),
-        vec![
-            AccountAddress::from_hex_literal("0x123").unwrap(),
-            AccountAddress::from_hex_literal("0x234").unwrap(),
-        ],
-        vec![1, 2],
+    let mut key_gen = KeyGen::from_seed([4; 32]);
+    let root_private_key = key_gen.generate_ed25519_private_key();
+    let git_options = setup_git_dir(
+        git_options(),
+        &root_private_key,
+        names.clone(),
+        ChainId::test(),
     )
     .await;
-    create_employee_vesting_accounts_file(PathBuf::from(git_dir)).await;
+    let imported = SetValidatorConfigurationsFromRoster {
+        roster_file,
+        git_options: git_options.clone(),
+    }
+    .execute()
+    .await
+    .unwrap();
+    assert_eq!(imported, names);
 
-    // Now generate genesis
+    let git_dir = git_options.local_repository_dir.unwrap();
+    for name in &names {
+        assert!(git_dir.join(name).join(OWNER_FILE).exists());
+        assert!(git_dir.join(name).join(OPERATOR_FILE).exists());
+    }
+}
+
+/// The CI fixture must reproduce the exact same bytes for a given version.  If the fixture is
+/// intentionally changed, bump `LATEST_CI_FIXTURE_VERSION` and regenerate the golden hash with
+/// `UPDATE_GOLDENFILES=1`.
+#[tokio::test]
+async fn test_ci_fixture_matches_golden_hash() {
+    let first_hash = generate_ci_fixture_hash().await;
+    let second_hash = generate_ci_fixture_hash().await;
+    assert_eq!(first_hash, second_hash, "CI fixture is not deterministic");
+
+    let mut mint = Mint::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("goldens"));
+    let mut golden = mint
+        .new_goldenfile(format!("ci_fixture_v{}.hash", LATEST_CI_FIXTURE_VERSION))
+        .unwrap();
+    writeln!(golden, "{}", first_hash.to_hex()).unwrap();
+}
+
+/// Generate the CI fixture, and hash the genesis blob and waypoint
+async fn generate_ci_fixture_hash() -> HashValue {
     let output_dir = TempPath::new();
     output_dir.create_as_dir().unwrap();
     let output_dir = PathBuf::from(output_dir.path());
-    generate_genesis(git_options, output_dir.clone(), true).await;
+    GenerateCiFixture {
+        output_dir: Some(output_dir.clone()),
+        fixture_version: LATEST_CI_FIXTURE_VERSION,
+        prompt_options: PromptOptions::yes(),
+    }
+    .execute()
+    .await
+    .unwrap();
 
-    // TODO: Verify that these are good
-    let waypoint_file = output_dir.join("waypoint.txt");
-    assert!(waypoint_file.exists());
-    let genesis_file = output_dir.join("genesis.blob");
-    assert!(genesis_file.exists());
+    let mut bytes = read_from_file(&output_dir.join("genesis.blob")).unwrap();
+    bytes.extend(read_from_file(&output_dir.join("waypoint.txt")).unwrap());
+    HashValue::sha3_256_of(&bytes)
 }
 
-async fn create_users(num_users: u8, dir: &TempPath) -> GitOptions {
-    let mut users: HashMap<String, PathBuf> = HashMap::new();
-    for i in 0..num_users {
-        let name = format!("user-{}", i);
-        let output_dir = generate_keys(dir.path(), i).await;
-        users.insert(name, output_dir);
+/// Test that generating users' keys concurrently gives the same keys as one at a time
+#[tokio::test]
+async fn test_generate_users_keys_concurrently() {
+    const NUM_USERS: u8 = 100;
+    let sequential_dir = TempPath::new();
+    let concurrent_dir = TempPath::new();
+
+    let sequential_users = generate_users_keys(NUM_USERS, sequential_dir.path(), 1).await;
+    let concurrent_users =
+        generate_users_keys(NUM_USERS, concurrent_dir.path(), KEY_GENERATION_CONCURRENCY).await;
+
+    assert_eq!(sequential_users.len(), NUM_USERS as usize);
+    assert_eq!(concurrent_users.len(), NUM_USERS as usize);
+    for (name, sequential_keys_dir) in &sequential_users {
+        assert_eq!(
+            read_from_file(&sequential_keys_dir.join(PUBLIC_KEYS_FILE)).unwrap(),
+            read_from_file(&concurrent_users[name].join(PUBLIC_KEYS_FILE)).unwrap()
+        );
     }
+}
+
+/// Number of users whose keys `create_users` generates at once
+const KEY_GENERATION_CONCURRENCY: usize = 8;
+/// Stake of each validator set up by `create_users`, the default layout's minimum stake
+const DEFAULT_STAKE_AMOUNT: u64 = 100_000_000_000_000;
+
+async fn create_users(num_users: u8, dir: &TempPath) -> GitOptions {
+    create_users_on_chain(num_users, dir, ChainId::test()).await
+}
+
+/// As `create_users`, but for a layout with the given chain id
+async fn create_users_on_chain(num_users: u8, dir: &TempPath, chain_id: ChainId) -> GitOptions {
+    create_users_in(num_users, dir.path(), git_options(), chain_id).await
+}
+
+/// As `create_users_on_chain`, with the keys written to `keys_dir`, and the repository set up
+/// at `git_options`
+async fn create_users_in(
+    num_users: u8,
+    keys_dir: &Path,
+    git_options: GitOptions,
+    chain_id: ChainId,
+) -> GitOptions {
+    let stakes = vec![DEFAULT_STAKE_AMOUNT; num_users as usize];
+    create_staked_users_in(&stakes, keys_dir, git_options, chain_id).await
+}
+
+/// As `create_users_in`, with `user-{index}` staking `stakes[index]`, so that genesis starts with
+/// an uneven split of the voting power
+async fn create_staked_users_in(
+    stakes: &[u64],
+    keys_dir: &Path,
+    git_options: GitOptions,
+    chain_id: ChainId,
+) -> GitOptions {
+    let num_users = u8::try_from(stakes.len()).unwrap();
+    let users = generate_users_keys(num_users, keys_dir, KEY_GENERATION_CONCURRENCY).await;
 
     let names = users.keys().map(|key| key.to_string()).collect();
-    let mut key_gen = KeyGen::from_seed([num_users.saturating_add(1); 32]);
     // First step is setup the local git repo
-    let root_private_key = key_gen.generate_ed25519_private_key();
-    let git_options = setup_git_dir(&root_private_key, names, ChainId::test()).await;
+    let root_private_key = root_private_key(num_users);
+    let git_options = setup_git_dir(git_options, &root_private_key, names, chain_id).await;
 
     for (name, user_dir) in users.iter() {
-        set_validator_config(name.to_string(), git_options.clone(), user_dir.as_path()).await;
+        let index: usize = name.strip_prefix("user-").unwrap().parse().unwrap();
+        set_validator_config_with_stake(
+            name.to_string(),
+            git_options.clone(),
+            user_dir.as_path(),
+            stakes[index],
+        )
+        .await;
     }
     git_options
 }
 
+/// The root key of the layout created by `create_users`
+fn root_private_key(num_users: u8) -> Ed25519PrivateKey {
+    KeyGen::from_seed([num_users.saturating_add(1); 32]).generate_ed25519_private_key()
+}
+
+/// Configurations of validators whose keys are generated as by `create_users`, without writing
+/// anything to disk
+fn in_memory_validators(num_validators: u8, stake_amount: u64) -> Vec<ValidatorConfiguration> {
+    let mut validators = Vec::new();
+    for index in 0..num_validators {
+        let mut key_generator = KeyGen::from_seed([index; 32]);
+        let (_, _, private_identity, public_identity) =
+            generate_key_objects(&mut key_generator).unwrap();
+        let account_address = public_identity.account_address;
+        let account_public_key = public_identity.account_public_key.clone();
+        validators.push(ValidatorConfiguration {
+            owner_account_address: account_address,
+            owner_account_public_key: account_public_key.clone(),
+            operator_account_address: account_address,
+            operator_account_public_key: account_public_key.clone(),
+            voter_account_address: account_address,
+            voter_account_public_key: account_public_key,
+            consensus_public_key: private_identity.consensus_private_key.public_key(),
+            proof_of_possession: public_identity.consensus_proof_of_possession.unwrap(),
+            validator_network_public_key: private_identity
+                .validator_network_private_key
+                .public_key(),
+            validator_host: HostAndPort::local(6180 + index as u16).unwrap(),
+            additional_validator_hosts: vec![],
+            full_node_network_public_key: None,
+            full_node_host: None,
+            stake_amount,
+            commission_percentage: 0,
+            chain_id: None,
+        });
+    }
+    validators
+}
+
 /// Generate genesis and waypoint
-async fn generate_genesis(git_options: GitOptions, output_dir: PathBuf, mainnet: bool) {
+async fn generate_genesis(
+    git_options: GitOptions,
+    output_dir: PathBuf,
+    mainnet: bool,
+) -> GenerateGenesisOutput {
     let command = GenerateGenesis {
+        mainnet: Some(mainnet),
+        ..generate_genesis_command(git_options, output_dir)
+    };
+    command.execute().await.unwrap()
+}
+
+/// A `GenerateGenesis` from the repository at `git_options` into `output_dir`, with every other
+/// option at its default for tests to override
+fn generate_genesis_command(git_options: GitOptions, output_dir: PathBuf) -> GenerateGenesis {
+    GenerateGenesis {
         prompt_options: PromptOptions::yes(),
         git_options,
         output_dir: Some(output_dir),
-        mainnet: Some(mainnet),
-    };
-    let _ = command.execute().await.unwrap();
+        mainnet: Some(false),
+        collections_file: None,
+        coins_file: None,
+        start_paused: false,
+        incremental: false,
+        root_private_key_file: None,
+        output_format: GenesisOutputFormat::Blob,
+        expected_total_supply: None,
+        expected_framework_hash: None,
+        balances_csv: None,
+        balances_files: vec![],
+        balance_unit: BalanceUnit::Octas,
+        resume: false,
+        force: false,
+        reproducibility_check: false,
+        require_signed_configs: false,
+        strict_min_stake: false,
+        manifest: false,
+        faucet_options: FaucetOptions::default(),
+        timeout: None,
+        estimate_only: false,
+        genesis_file_name: GENESIS_FILE.to_string(),
+        waypoint_file_name: WAYPOINT_FILE.to_string(),
+        progress: ProgressReporter::default(),
+        max_buffered_genesis_size: MAX_BUFFERED_GENESIS_SIZE,
+        output_options: OutputOptions::default(),
+    }
 }
 
-/// Setup a temporary repo location and add all required pieces
+/// Setup a repo at `git_options` and add all required pieces
 async fn setup_git_dir(
+    git_options: GitOptions,
     root_private_key: &Ed25519PrivateKey,
     users: Vec<String>,
     chain_id: ChainId,
 ) -> GitOptions {
-    let git_options = git_options();
     let layout_file = TempPath::new();
     layout_file.create_as_file().unwrap();
     let layout_file = layout_file.path();
@@ -132,6 +5038,10 @@ async fn setup_git_dir(
     let setup_command = SetupGit {
         git_options: git_options.clone(),
         layout_file: PathBuf::from(layout_file),
+        schema_validate: false,
+        framework_bundle: None,
+        init_git: false,
+        progress: ProgressReporter::default(),
     };
 
     setup_command
@@ -152,6 +5062,8 @@ fn add_framework_to_dir(git_dir: &Path) {
 }
 
 /// Local git options for testing
+///
+/// The repository isn't removed after the test, for which use a `GenesisWorkspace`
 fn git_options() -> GitOptions {
     let temp_path = TempPath::new();
     let path = PathBuf::from(temp_path.path());
@@ -161,6 +5073,239 @@ fn git_options() -> GitOptions {
     }
 }
 
+/// The directories of a genesis flow: the users' keys, the git repository, and the output
+///
+/// Each directory is removed when the workspace is dropped, including when a test panics part
+/// way through a flow, and the files in them are found by the accessors, rather than by joining
+/// their names onto the directories in each test.
+struct GenesisWorkspace {
+    keys_dir: TempPath,
+    git_dir: TempPath,
+    output_dir: TempPath,
+}
+
+impl GenesisWorkspace {
+    fn new() -> Self {
+        let keys_dir = TempPath::new();
+        keys_dir.create_as_dir().unwrap();
+        let output_dir = TempPath::new();
+        output_dir.create_as_dir().unwrap();
+        // The repository is created when it's set up
+        GenesisWorkspace {
+            keys_dir,
+            git_dir: TempPath::new(),
+            output_dir,
+        }
+    }
+
+    /// A workspace whose repository has `num_users` validators set up in it, as by `create_users`
+    async fn with_users(num_users: u8) -> Self {
+        let workspace = Self::new();
+        create_users_in(
+            num_users,
+            workspace.keys_dir(),
+            workspace.git_options(),
+            ChainId::test(),
+        )
+        .await;
+        workspace
+    }
+
+    /// A workspace whose repository has a validator for each of `stakes`, as by
+    /// `create_staked_users_in`
+    async fn with_stakes(stakes: &[u64]) -> Self {
+        let workspace = Self::new();
+        create_staked_users_in(
+            stakes,
+            workspace.keys_dir(),
+            workspace.git_options(),
+            ChainId::test(),
+        )
+        .await;
+        workspace
+    }
+
+    fn git_options(&self) -> GitOptions {
+        GitOptions {
+            local_repository_dir: Some(self.git_dir().to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    fn keys_dir(&self) -> &Path {
+        self.keys_dir.path()
+    }
+
+    fn git_dir(&self) -> &Path {
+        self.git_dir.path()
+    }
+
+    fn output_dir(&self) -> &Path {
+        self.output_dir.path()
+    }
+
+    /// Public keys of the user with `index`, as generated by `create_users`
+    fn public_keys_path(&self, index: u8) -> PathBuf {
+        self.keys_dir()
+            .join(index.to_string())
+            .join(PUBLIC_KEYS_FILE)
+    }
+
+    fn layout_path(&self) -> PathBuf {
+        self.git_dir().join(LAYOUT_FILE)
+    }
+
+    fn balances_path(&self) -> PathBuf {
+        self.git_dir().join(BALANCES_FILE)
+    }
+
+    fn owner_path(&self, username: &str) -> PathBuf {
+        self.git_dir().join(username).join(OWNER_FILE)
+    }
+
+    fn operator_path(&self, username: &str) -> PathBuf {
+        self.git_dir().join(username).join(OPERATOR_FILE)
+    }
+
+    fn genesis_path(&self) -> PathBuf {
+        self.output_dir().join(GENESIS_FILE)
+    }
+
+    fn waypoint_path(&self) -> PathBuf {
+        self.output_dir().join(WAYPOINT_FILE)
+    }
+
+    /// Generates genesis into the output directory, as by `generate_genesis`
+    async fn generate_genesis(&self, mainnet: bool) -> GenerateGenesisOutput {
+        generate_genesis(self.git_options(), self.output_dir().to_path_buf(), mainnet).await
+    }
+}
+
+/// A Github repository served over a local mock of the contents API, which fails each request
+/// transiently until its failures run out
+struct MockGithub {
+    address: SocketAddr,
+    state: Arc<Mutex<MockGithubState>>,
+}
+
+#[derive(Default)]
+struct MockGithubState {
+    files: BTreeMap<String, Vec<u8>>,
+    failures: u32,
+    requests: u32,
+}
+
+impl MockGithub {
+    const CONTENTS_PATH: &'static str = "/repos/owner/repository/contents/";
+
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(MockGithubState::default()));
+        let server_state = state.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                // A connection the client dropped is only a failed request
+                let _ = Self::serve(stream.unwrap(), &server_state);
+            }
+        });
+        Self { address, state }
+    }
+
+    /// A client of the repository, which retries up to three times without backing off
+    fn client(&self) -> GithubClient {
+        GithubClient::new(
+            "owner".into(),
+            "repository".into(),
+            "main".into(),
+            "token".into(),
+        )
+        .with_url(format!("http://{}", self.address))
+        .with_max_retries(3)
+        .with_initial_backoff(Duration::ZERO)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<MockGithubState> {
+        self.state.lock().unwrap()
+    }
+
+    fn file(&self, path: &Path) -> Option<Vec<u8>> {
+        self.state().files.get(&path.display().to_string()).cloned()
+    }
+
+    /// Answers a single request on its own connection, reading or writing a file's contents
+    fn serve(mut stream: TcpStream, state: &Mutex<MockGithubState>) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        let mut request_line = request_line.split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let target = request_line.next().unwrap_or_default();
+        let path = target
+            .split('?')
+            .next()
+            .unwrap()
+            .trim_start_matches(Self::CONTENTS_PATH)
+            .to_string();
+        let sha = |contents: &[u8]| HashValue::sha3_256_of(contents).to_hex();
+
+        let mut state = state.lock().unwrap();
+        state.requests += 1;
+        let (status, response) = if state.failures > 0 {
+            state.failures -= 1;
+            ("503 Service Unavailable", String::new())
+        } else if method == "GET" {
+            match state.files.get(&path) {
+                Some(contents) => (
+                    "200 OK",
+                    serde_json::json!({
+                        "type": "file",
+                        "path": path,
+                        "sha": sha(contents),
+                        "content": base64::encode(contents),
+                    })
+                    .to_string(),
+                ),
+                None => ("404 Not Found", String::new()),
+            }
+        } else {
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let current_sha = state.files.get(&path).map(|contents| sha(contents));
+            // Files are only replaced given the sha of their current contents, as Github does
+            if body["sha"].as_str().map(str::to_string) != current_sha {
+                ("409 Conflict", String::new())
+            } else {
+                let contents = base64::decode(body["content"].as_str().unwrap()).unwrap();
+                state.files.insert(path, contents);
+                ("201 Created", "{}".to_string())
+            }
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        )
+    }
+}
+
 /// Create a layout file for the repo
 async fn create_layout_file(
     file: &Path,
@@ -170,32 +5315,24 @@ async fn create_layout_file(
 ) {
     GenerateLayoutTemplate {
         output_file: PathBuf::from(file),
+        chain_id: Some(chain_id),
+        root_key: Some(root_public_key),
+        users,
+        users_file: None,
+        is_test: Some(true),
         prompt_options: PromptOptions::yes(),
     }
     .execute()
     .await
     .expect("Expected to create layout template");
-
-    // Update layout file
-    let mut layout: Layout =
-        from_yaml(&String::from_utf8(read_from_file(file).unwrap()).unwrap()).unwrap();
-    layout.root_key = Some(root_public_key);
-    layout.users = users;
-    layout.chain_id = chain_id;
-    layout.is_test = true;
-
-    write_to_file(
-        file,
-        "Layout file",
-        serde_yaml::to_string(&layout).unwrap().as_bytes(),
-    )
-    .unwrap();
 }
 
 /// Generate keys for a "user"
 async fn generate_keys(dir: &Path, index: u8) -> PathBuf {
     let output_dir = dir.join(index.to_string());
     let command = GenerateKeys {
+        encrypt: false,
+        passphrase_options: PassphraseOptions::default(),
         pool_address_args: OptionalPoolAddressArgs { pool_address: None },
         rng_args: RngArgs::from_seed([index; 32]),
         prompt_options: PromptOptions::yes(),
@@ -206,21 +5343,122 @@ async fn generate_keys(dir: &Path, index: u8) -> PathBuf {
     output_dir
 }
 
+/// Generates keys for users `user-0` to `user-{num_users - 1}`, `concurrency` at a time
+///
+/// Key generation is CPU bound, so each user's keys are generated on a blocking thread.  Every
+/// user's keys come from the seed of its index, so they're the same however many run at once.
+async fn generate_users_keys(
+    num_users: u8,
+    dir: &Path,
+    concurrency: usize,
+) -> HashMap<String, PathBuf> {
+    stream::iter(0..num_users)
+        .map(|index| {
+            let dir = dir.to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                let output_dir = futures::executor::block_on(generate_keys(&dir, index));
+                (format!("user-{}", index), output_dir)
+            })
+        })
+        .buffer_unordered(concurrency)
+        .map(|user| user.unwrap())
+        .collect()
+        .await
+}
+
+/// Configures a validator for employees to stake with, outside of the layout's users
+async fn employee_validator_config(dir: &Path, git_options: &GitOptions) -> ValidatorConfiguration {
+    let keys_dir = generate_keys(dir, u8::MAX).await;
+    let name = "employee-validator";
+    set_validator_config(name.to_string(), git_options.clone(), &keys_dir).await;
+    get_config(&git_options.clone().get_client().unwrap(), name).unwrap()
+}
+
+/// Reads the owner account address configured for a user
+fn owner_address(git_options: &GitOptions, username: &str) -> AccountAddress {
+    let owner_file = git_options
+        .local_repository_dir
+        .as_ref()
+        .unwrap()
+        .join(username)
+        .join(OWNER_FILE);
+    let owner_config: StringOwnerConfiguration =
+        from_yaml(&String::from_utf8(read_from_file(&owner_file).unwrap()).unwrap()).unwrap();
+    AccountAddress::from_str(owner_config.owner_account_address.as_ref().unwrap()).unwrap()
+}
+
+/// Asserts that the resource `T` exists at `address` after genesis, and returns it
+fn assert_genesis_resource<T: MoveResource + DeserializeOwned>(
+    genesis: &Transaction,
+    address: AccountAddress,
+) -> T {
+    let state = genesis_state(genesis).unwrap();
+    let bytes = state
+        .get(&StateKey::AccessPath(AccessPath::new(
+            address,
+            AccessPath::resource_access_vec(T::struct_tag()),
+        )))
+        .unwrap_or_else(|| panic!("{} should exist at {}", T::struct_tag(), address));
+    bcs::from_bytes(bytes)
+        .unwrap_or_else(|err| panic!("{} at {} is malformed: {}", T::struct_tag(), address, err))
+}
+
+/// An employee vesting group with the given schedule, whose validator isn't checked before genesis
+fn employee_group(
+    accounts: Vec<AccountAddress>,
+    vesting_schedule_numerators: Vec<u64>,
+    vesting_schedule_denominator: u64,
+) -> EmployeeAccountMap {
+    EmployeeAccountMap {
+        accounts,
+        validator: ValidatorWithCommissionRate {
+            validator: Validator {
+                owner_address: AccountAddress::ONE,
+                operator_address: AccountAddress::ONE,
+                voter_address: AccountAddress::ONE,
+                stake_amount: 0,
+                consensus_pubkey: vec![],
+                proof_of_possession: vec![],
+                network_addresses: vec![],
+                full_node_network_addresses: vec![],
+            },
+            validator_commission_percentage: 0,
+        },
+        vesting_schedule_numerators,
+        vesting_schedule_denominator,
+    }
+}
+
 /// Set validator configuration for a user
 async fn set_validator_config(username: String, git_options: GitOptions, keys_dir: &Path) {
+    set_validator_config_with_stake(username, git_options, keys_dir, DEFAULT_STAKE_AMOUNT).await
+}
+
+/// As `set_validator_config`, staking `stake_amount`
+async fn set_validator_config_with_stake(
+    username: String,
+    git_options: GitOptions,
+    keys_dir: &Path,
+    stake_amount: u64,
+) {
     let command = SetValidatorConfiguration {
         username,
+        output_options: OutputOptions::default(),
         git_options,
         owner_public_identity_file: Some(PathBuf::from(keys_dir).join(PUBLIC_KEYS_FILE)),
-        validator_host: HostAndPort::from_str("localhost:6180").unwrap(),
-        stake_amount: 100_000_000_000_000,
+        validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+        stake_amount,
         full_node_host: None,
         operator_public_identity_file: None,
         voter_public_identity_file: None,
         commission_percentage: 0,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
     };
 
-    command.execute().await.unwrap()
+    command.execute().await.unwrap();
 }
 
 async fn create_account_balances_file(
@@ -245,10 +5483,23 @@ async fn create_account_balances_file(
     .unwrap();
 }
 
-// TODO: Update to generate real employee vesting account details.
-async fn create_employee_vesting_accounts_file(path: PathBuf) {
-    let employee_vesting_accounts: Vec<EmployeeAccountMap> = vec![];
+/// Encodes account balances as a CSV for `--balances-csv`
+fn balances_csv_contents(accounts: &[AccountMap]) -> String {
+    let mut contents = "account_address,balance\n".to_string();
+    for account in accounts {
+        contents.push_str(&format!(
+            "{},{}\n",
+            account.account_address.to_hex_literal(),
+            account.balance
+        ));
+    }
+    contents
+}
 
+async fn create_employee_vesting_accounts_file(
+    path: PathBuf,
+    employee_vesting_accounts: Vec<EmployeeAccountMap>,
+) {
     write_to_file(
         &path.join(EMPLOYEE_VESTING_ACCOUNTS_FILE),
         EMPLOYEE_VESTING_ACCOUNTS_FILE,
@@ -258,3 +5509,466 @@ async fn create_employee_vesting_accounts_file(path: PathBuf) {
     )
     .unwrap();
 }
+
+#[test]
+fn test_stdio_paths() {
+    assert!(is_stdio(Path::new(STDIO_PATH)));
+    assert!(!is_stdio(Path::new("./-")));
+    assert!(!is_stdio(Path::new("layout.yaml")));
+
+    // A layout piped in can be piped back out byte for byte
+    let layout = Layout {
+        root_key: Some(Ed25519PrivateKey::generate_for_testing().public_key()),
+        users: vec!["0".to_string(), "1".to_string()],
+        ..Layout::default()
+    };
+    let yaml = serde_yaml::to_string(&layout).unwrap();
+    let piped_in = read_from_reader(yaml.as_bytes(), "stdin").unwrap();
+    let piped_layout: Layout = serde_yaml::from_slice(&piped_in).unwrap();
+    assert_eq!(piped_layout.users, layout.users);
+    assert_eq!(piped_layout.root_key, layout.root_key);
+    let mut piped_out = Vec::new();
+    write_to_writer(
+        &mut piped_out,
+        "layout",
+        serde_yaml::to_string(&piped_layout).unwrap().as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(piped_out, yaml.as_bytes());
+
+    // Binary output isn't altered on the way out
+    let bytes: Vec<u8> = (0..=u8::MAX).chain(b"\r\n\n".iter().copied()).collect();
+    let mut piped_out = Vec::new();
+    write_to_writer(&mut piped_out, "genesis.blob", &bytes).unwrap();
+    assert_eq!(piped_out, bytes);
+
+    // Once a file is written to stdout, the command's result is printed to stderr instead
+    write_to_file(Path::new(STDIO_PATH), "layout", b"").unwrap();
+    assert!(wrote_to_stdout());
+}
+
+#[test]
+fn test_atomic_writes() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let genesis_file = dir.path().join(GENESIS_FILE);
+    let waypoint_file = dir.path().join(WAYPOINT_FILE);
+
+    // Files are written whole, and overwritten whole
+    write_to_file(&genesis_file, GENESIS_FILE, b"first genesis").unwrap();
+    write_to_file(&genesis_file, GENESIS_FILE, b"second").unwrap();
+    write_to_user_only_file(&waypoint_file, WAYPOINT_FILE, b"0:waypoint").unwrap();
+    assert_eq!(read_from_file(&genesis_file).unwrap(), b"second");
+    assert_eq!(read_from_file(&waypoint_file).unwrap(), b"0:waypoint");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&waypoint_file)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // No temporary files are left behind, whether writing succeeds or fails
+    let missing_dir_file = dir.path().join("missing").join(GENESIS_FILE);
+    assert!(write_to_file(&missing_dir_file, GENESIS_FILE, b"genesis").is_err());
+    let mut files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(files, vec![GENESIS_FILE, WAYPOINT_FILE]);
+    assert!(files.iter().all(|file| !file.starts_with(TEMP_FILE_PREFIX)));
+}
+
+#[test]
+fn test_streamed_genesis_matches_buffered() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let layout = Layout {
+        users: vec!["user-0".to_string(), "user-1".to_string()],
+        ..Layout::default()
+    };
+    let validators = in_memory_validators(2, layout.min_stake);
+    let framework = cached_packages::head_release_bundle().clone();
+    let (genesis, _) = GenesisBuilder::new(layout, validators, framework)
+        .build()
+        .unwrap();
+
+    // Streaming genesis into its file writes the same bytes as buffering it
+    let buffered_file = dir.path().join("buffered.blob");
+    let streamed_file = dir.path().join("streamed.blob");
+    let genesis_bytes = bcs::to_bytes(&genesis).unwrap();
+    write_to_file(&buffered_file, GENESIS_FILE, &genesis_bytes).unwrap();
+    write_bcs_to_file(&streamed_file, GENESIS_FILE, &genesis).unwrap();
+    let streamed_bytes = read_from_file(&streamed_file).unwrap();
+    assert_eq!(streamed_bytes, read_from_file(&buffered_file).unwrap());
+    assert_eq!(
+        streamed_bytes.len(),
+        bcs::serialized_size(&genesis).unwrap()
+    );
+    assert_eq!(decode_genesis(&streamed_bytes).unwrap(), genesis);
+
+    // Nor is a temporary file left behind
+    let missing_dir_file = dir.path().join("missing").join(GENESIS_FILE);
+    assert!(write_bcs_to_file(&missing_dir_file, GENESIS_FILE, &genesis).is_err());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn test_assume_no() {
+    // Overwriting is declined, rather than waiting for an answer
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let layout_file = dir.path().join(LAYOUT_FILE);
+    write_to_file(&layout_file, LAYOUT_FILE, b"existing").unwrap();
+    let result = GenerateLayoutTemplate {
+        output_file: layout_file.clone(),
+        chain_id: None,
+        root_key: None,
+        users: vec![],
+        users_file: None,
+        is_test: None,
+        prompt_options: PromptOptions::no(),
+    }
+    .execute()
+    .await;
+    assert!(matches!(result, Err(CliError::AbortedByAssumeNo)));
+    assert_eq!(read_from_file(&layout_file).unwrap(), b"existing");
+
+    // Prompts can't be assumed both yes and no
+    let options = PromptOptions::try_parse_from(["aptos", "--assume-no"]).unwrap();
+    assert!(options.assume_no && !options.assume_yes);
+    assert!(PromptOptions::try_parse_from(["aptos", "--assume-yes", "--assume-no"]).is_err());
+}
+
+#[test]
+fn test_rng_seeds() {
+    let seed = [7u8; 32];
+    let seed_hex = hex::encode(seed);
+    let parse = |args: &[&str]| RngArgs::try_parse_from(["aptos"].iter().chain(args));
+    let public_key = |rng_args: RngArgs| {
+        rng_args
+            .key_generator()
+            .unwrap()
+            .generate_ed25519_keypair()
+            .1
+    };
+
+    // The same seed generates the same keys, however it's given
+    let from_hex = public_key(parse(&["--seed-hex", &seed_hex]).unwrap());
+    assert_eq!(
+        from_hex,
+        public_key(parse(&["--seed-hex", &seed_hex]).unwrap())
+    );
+    let prefixed_hex = format!("0x{}", seed_hex);
+    assert_eq!(
+        from_hex,
+        public_key(parse(&["--random-seed", &prefixed_hex]).unwrap())
+    );
+    assert_eq!(from_hex, public_key(RngArgs::from_seed(seed)));
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let seed_file = dir.path().join("seed");
+    write_to_file(&seed_file, "seed", &seed).unwrap();
+    let seed_file = seed_file.to_str().unwrap();
+    assert_eq!(
+        from_hex,
+        public_key(parse(&["--seed-file", seed_file]).unwrap())
+    );
+    assert_ne!(from_hex, public_key(RngArgs::from_seed([8u8; 32])));
+
+    // Seeds must be exactly 32 bytes of valid hex
+    for invalid in [
+        &seed_hex[1..],
+        &seed_hex[2..],
+        "zz".repeat(32).as_str(),
+        "0x",
+    ] {
+        let rng_args = parse(&["--seed-hex", invalid]).unwrap();
+        assert!(matches!(
+            rng_args.key_generator(),
+            Err(CliError::UnableToParse("--random-seed", _))
+        ));
+    }
+    let short_file = dir.path().join("short_seed");
+    write_to_file(&short_file, "seed", &seed[1..]).unwrap();
+    let rng_args = parse(&["--seed-file", short_file.to_str().unwrap()]).unwrap();
+    assert!(matches!(
+        rng_args.key_generator(),
+        Err(CliError::UnableToParse("--seed-file", _))
+    ));
+
+    // Only one seed can be given
+    assert!(parse(&["--seed-hex", &seed_hex, "--seed-file", seed_file]).is_err());
+}
+
+#[tokio::test]
+async fn test_json_output() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let options = OutputOptions::try_parse_from(["aptos", "--output", "json"]).unwrap();
+    assert_eq!(options.output_format, OutputFormat::Json);
+    assert_eq!(
+        OutputOptions::default().output_format,
+        OutputFormat::Default
+    );
+
+    // The result of the command is the only thing printed, as one JSON object
+    let command = |output_options| SetValidatorConfiguration {
+        username: "user-0".to_string(),
+        output_options,
+        git_options: git_options.clone(),
+        owner_public_identity_file: Some(dir.path().join("0").join(PUBLIC_KEYS_FILE)),
+        validator_host: vec![HostAndPort::from_str("localhost:6180").unwrap()],
+        stake_amount: 100_000_000_000_000,
+        full_node_host: None,
+        operator_public_identity_file: None,
+        voter_public_identity_file: None,
+        commission_percentage: 10,
+        check_reachability: false,
+        commit: false,
+        owner_private_keys_file: None,
+        passphrase_options: PassphraseOptions::default(),
+    };
+    let printed = command(options)
+        .execute_serialized_without_logger()
+        .await
+        .unwrap();
+    assert_eq!(printed.lines().count(), 1);
+    let output: CommandOutput = serde_json::from_str(&printed).unwrap();
+    assert_eq!(output.command, "SetValidatorConfiguration");
+    assert!(output.success);
+    assert!(output.error.is_none());
+    let summary: ValidatorConfigurationSummary =
+        serde_json::from_value(output.result.unwrap()).unwrap();
+    assert_eq!(summary.username, "user-0");
+    assert_eq!(
+        summary.owner_account_address,
+        owner_address(&git_options, "user-0")
+    );
+    assert_eq!(summary.stake_amount, 100_000_000_000_000);
+    assert_eq!(summary.commission_percentage, 10);
+    assert_eq!(summary.validator_hosts.len(), 1);
+
+    // Failures are reported in the same object
+    let mut failing = command(options);
+    failing.owner_public_identity_file = Some(dir.path().join("missing.yaml"));
+    let printed = failing
+        .execute_serialized_without_logger()
+        .await
+        .unwrap_err();
+    let output: CommandOutput = serde_json::from_str(&printed).unwrap();
+    assert!(!output.success);
+    assert!(output.result.is_none());
+    assert!(output.error.unwrap().contains("missing.yaml"));
+
+    // Without --output json, the common output is printed as before
+    let printed = command(OutputOptions::default())
+        .execute_serialized_without_logger()
+        .await
+        .unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&printed).unwrap()["Result"].is_object());
+}
+
+#[tokio::test]
+async fn test_encrypted_keys() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let passphrase_file = dir.path().join("passphrase");
+    write_to_file(&passphrase_file, "passphrase", b"correct horse\n").unwrap();
+    let generate_keys = |output_dir: PathBuf, encrypt: bool| GenerateKeys {
+        encrypt,
+        passphrase_options: PassphraseOptions {
+            passphrase_file: Some(passphrase_file.clone()),
+        },
+        pool_address_args: OptionalPoolAddressArgs { pool_address: None },
+        rng_args: RngArgs::from_seed([3; 32]),
+        prompt_options: PromptOptions::yes(),
+        output_dir: Some(output_dir),
+    };
+    let plain_dir = dir.path().join("plain");
+    generate_keys(plain_dir.clone(), false)
+        .execute()
+        .await
+        .unwrap();
+    let encrypted_dir = dir.path().join("encrypted");
+    generate_keys(encrypted_dir.clone(), true)
+        .execute()
+        .await
+        .unwrap();
+
+    // Only the public keys are left in plaintext
+    let read = |dir: &Path, name: &str| read_from_file(&dir.join(name)).unwrap();
+    assert_eq!(
+        read(&plain_dir, PUBLIC_KEYS_FILE),
+        read(&encrypted_dir, PUBLIC_KEYS_FILE)
+    );
+    for name in PRIVATE_FILES {
+        assert!(!encrypted_dir.join(name).exists());
+        let encrypted_file = encrypted_file_path(&encrypted_dir.join(name)).unwrap();
+        assert_ne!(
+            read_from_file(&encrypted_file).unwrap(),
+            read(&plain_dir, name)
+        );
+    }
+
+    // Nothing is decrypted with the wrong passphrase
+    let wrong_passphrase_file = dir.path().join("wrong_passphrase");
+    write_to_file(&wrong_passphrase_file, "passphrase", b"wrong horse").unwrap();
+    let decrypt_keys = |passphrase_file: &Path| DecryptKeys {
+        key_dir: Some(encrypted_dir.clone()),
+        passphrase_options: PassphraseOptions {
+            passphrase_file: Some(passphrase_file.to_path_buf()),
+        },
+        prompt_options: PromptOptions::yes(),
+    };
+    let result = decrypt_keys(&wrong_passphrase_file).execute().await;
+    assert!(matches!(result, Err(CliError::UnableToDecrypt(_))));
+    for name in PRIVATE_FILES {
+        assert!(!encrypted_dir.join(name).exists());
+    }
+
+    // The right passphrase gives back the keys, as if they'd never been encrypted
+    decrypt_keys(&passphrase_file).execute().await.unwrap();
+    for name in PRIVATE_FILES {
+        assert_eq!(read(&encrypted_dir, name), read(&plain_dir, name));
+    }
+
+    // The passphrase can't be prompted for when prompts are assumed answered
+    let mut unprompted = generate_keys(dir.path().join("unprompted"), true);
+    unprompted.passphrase_options = PassphraseOptions::default();
+    assert!(matches!(
+        unprompted.execute().await,
+        Err(CliError::CommandArgumentError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_rotate_validator_keys() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let git_options = create_users(1, &dir).await;
+    let client = git_options.get_client().unwrap();
+    let original = get_config(&client, "user-0").unwrap();
+    let original_dir = dir.path().join("0");
+    let rotate = |output_dir: PathBuf| RotateValidatorKeys {
+        public_identity_file: Some(original_dir.join(PUBLIC_KEYS_FILE)),
+        output_dir: Some(output_dir),
+        validator_host: HostAndPort::from_str("validator.example.com:6180").unwrap(),
+        full_node_host: Some(HostAndPort::from_str("fullnode.example.com:6182").unwrap()),
+        encrypt: false,
+        passphrase_options: PassphraseOptions::default(),
+        pool_address_args: OptionalPoolAddressArgs { pool_address: None },
+        prompt_options: PromptOptions::yes(),
+        rng_args: RngArgs::from_seed([100; 32]),
+    };
+
+    // The original keys are needed to sign the rotation, so can't be overwritten
+    let result = rotate(original_dir.clone()).execute().await;
+    assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+    // Nor can the keys be rotated to themselves
+    let mut unrotated = rotate(dir.path().join("unrotated"));
+    unrotated.rng_args = RngArgs::from_seed([0; 32]);
+    let result = unrotated.execute().await;
+    assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+
+    let rotated_dir = dir.path().join("rotated");
+    let files = rotate(rotated_dir.clone()).execute().await.unwrap();
+    let payload_file = rotated_dir.join(ROTATION_PAYLOAD_FILE);
+    assert!(files.contains(&payload_file));
+    let network_payload_file = rotated_dir.join(NETWORK_ROTATION_PAYLOAD_FILE);
+    assert!(files.contains(&network_payload_file));
+    assert!(files.contains(&rotated_dir.join(PUBLIC_KEYS_FILE)));
+
+    // The rotated identity configures the same validator, owned and operated by the same accounts
+    set_validator_config("user-0".to_string(), git_options.clone(), &rotated_dir).await;
+    let rotated = get_config(&client, "user-0").unwrap();
+    assert_eq!(
+        rotated.owner_account_address,
+        original.owner_account_address
+    );
+    assert_eq!(
+        rotated.operator_account_address,
+        original.operator_account_address
+    );
+    assert_eq!(
+        rotated.voter_account_address,
+        original.voter_account_address
+    );
+    assert_ne!(
+        rotated.owner_account_public_key,
+        original.owner_account_public_key
+    );
+    assert_ne!(rotated.consensus_public_key, original.consensus_public_key);
+    assert_ne!(
+        rotated.validator_network_public_key,
+        original.validator_network_public_key
+    );
+
+    // The payload rotates the pool's consensus key to the one now configured
+    let payload: serde_json::Value =
+        serde_json::from_slice(&read_from_file(&payload_file).unwrap()).unwrap();
+    assert_eq!(payload["type"], "entry_function_payload");
+    assert_eq!(payload["function"], "0x1::stake::rotate_consensus_key");
+    let hex_literal = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+    assert_eq!(
+        payload["arguments"],
+        serde_json::json!([
+            original.owner_account_address.to_hex_literal(),
+            hex_literal(&rotated.consensus_public_key.to_bytes()),
+            hex_literal(&rotated.proof_of_possession.to_bytes()),
+        ])
+    );
+
+    // And the other payload moves its network addresses to the network keys now configured
+    let payload: serde_json::Value =
+        serde_json::from_slice(&read_from_file(&network_payload_file).unwrap()).unwrap();
+    assert_eq!(payload["type"], "entry_function_payload");
+    assert_eq!(
+        payload["function"],
+        "0x1::stake::update_network_and_fullnode_addresses"
+    );
+    let addresses = |host: &str, key| {
+        let address = HostAndPort::from_str(host)
+            .unwrap()
+            .as_network_address(key)
+            .unwrap();
+        hex_literal(&bcs::to_bytes(&vec![address]).unwrap())
+    };
+    assert_eq!(
+        payload["arguments"],
+        serde_json::json!([
+            original.owner_account_address.to_hex_literal(),
+            addresses(
+                "validator.example.com:6180",
+                rotated.validator_network_public_key
+            ),
+            addresses(
+                "fullnode.example.com:6182",
+                rotated.full_node_network_public_key.unwrap()
+            ),
+        ])
+    );
+
+    // Without a fullnode host, the validator is left without fullnode addresses
+    let files = RotateValidatorKeys {
+        full_node_host: None,
+        ..rotate(dir.path().join("rotated-without-fullnode"))
+    }
+    .execute()
+    .await
+    .unwrap();
+    let network_payload_file = files
+        .into_iter()
+        .find(|file| file.ends_with(NETWORK_ROTATION_PAYLOAD_FILE))
+        .unwrap();
+    let payload: serde_json::Value =
+        serde_json::from_slice(&read_from_file(&network_payload_file).unwrap()).unwrap();
+    assert_eq!(
+        payload["arguments"][2],
+        hex_literal(&bcs::to_bytes(&Vec::<NetworkAddress>::new()).unwrap())
+    );
+}
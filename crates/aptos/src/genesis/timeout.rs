@@ -0,0 +1,81 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliTypedResult},
+    genesis::{error::GenesisError, progress::ProgressReporter},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Files written by a genesis command, so that they can be removed if it times out part way
+#[derive(Clone, Default)]
+pub struct OutputTracker {
+    inner: Arc<Mutex<TrackedOutputs>>,
+}
+
+#[derive(Default)]
+struct TrackedOutputs {
+    cancelled: bool,
+    files: Vec<PathBuf>,
+}
+
+impl OutputTracker {
+    /// Writes `file` with `write`, unless the command was cancelled
+    ///
+    /// The lock is held while writing, so that a write racing the cancellation can't leave its
+    /// file behind
+    pub fn write<T>(
+        &self,
+        file: &Path,
+        write: impl FnOnce() -> CliTypedResult<T>,
+    ) -> CliTypedResult<T> {
+        let mut outputs = self.inner.lock().unwrap();
+        if outputs.cancelled {
+            return Err(CliError::AbortedError);
+        }
+        outputs.files.push(file.to_path_buf());
+        write()
+    }
+
+    /// Refuses any further writes, and removes the files already written
+    pub fn cancel(&self) {
+        let mut outputs = self.inner.lock().unwrap();
+        outputs.cancelled = true;
+        for file in outputs.files.drain(..) {
+            // A write which failed may not have created its file
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+/// Runs a blocking genesis command, failing if it takes longer than `timeout`
+///
+/// The command can't be interrupted part way, so on timeout it's left to finish on its own, with
+/// the outputs it wrote removed and any further writes refused.  The error names the phase it was
+/// in, going by the last step it reported to `progress`.
+pub async fn run_with_timeout<T: Send + 'static>(
+    timeout: Duration,
+    progress: ProgressReporter,
+    outputs: OutputTracker,
+    command: impl FnOnce() -> CliTypedResult<T> + Send + 'static,
+) -> CliTypedResult<T> {
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(command)).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => Err(CliError::UnexpectedError(format!(
+            "Genesis command failed to run: {}",
+            err
+        ))),
+        Err(_) => {
+            outputs.cancel();
+            Err(GenesisError::Timeout {
+                secs: timeout.as_secs(),
+                phase: progress.phase(),
+            }
+            .into())
+        }
+    }
+}
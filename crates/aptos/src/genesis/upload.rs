@@ -0,0 +1,190 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::read_from_file,
+    },
+    genesis::{
+        git::{from_yaml, Client, GitOptions, OPERATOR_FILE, OWNER_FILE},
+        signature::CONFIG_SIGNATURE_FILE,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::StringOwnerConfiguration;
+use aptos_github_client::{Client as GithubClient, Error as GithubError};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Number of times each request to Github is retried after a transient failure, by default
+const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Upload a validator's configuration to the Github repository of the ceremony
+///
+/// For operators who set their configuration in a local repository with
+/// `SetValidatorConfiguration`, and share it with the others through Github.  The user's files are
+/// written to `--github-repository` on `--github-branch`, each write being a commit, and every
+/// request is retried when Github fails transiently.  Files already uploaded unchanged are
+/// skipped, so an interrupted upload can be finished by running it again.  A configuration already
+/// uploaded for the user with another owner account is refused, unless given `--force`.
+#[derive(Parser)]
+pub struct UploadValidatorConfiguration {
+    /// Name of the validator whose configuration is uploaded
+    #[clap(long)]
+    pub(crate) username: String,
+
+    /// Local repository the configuration was set in
+    #[clap(long, parse(from_os_str))]
+    pub(crate) source_repository_dir: PathBuf,
+
+    /// Overwrite a configuration uploaded for the user with another owner account
+    #[clap(long)]
+    pub(crate) force: bool,
+
+    /// Number of times each request to Github is retried after a transient failure
+    #[clap(long, default_value_t = DEFAULT_MAX_RETRIES)]
+    pub(crate) max_retries: u32,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// Files of a configuration uploaded by `UploadValidatorConfiguration`
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UploadSummary {
+    /// Files written to the repository
+    pub uploaded: Vec<PathBuf>,
+    /// Files already in the repository as they are locally, which were skipped
+    pub unchanged: Vec<PathBuf>,
+}
+
+#[async_trait]
+impl CliCommand<UploadSummary> for UploadValidatorConfiguration {
+    fn command_name(&self) -> &'static str {
+        "UploadValidatorConfiguration"
+    }
+
+    async fn execute(self) -> CliTypedResult<UploadSummary> {
+        if self.git_options.github_token.is_none() && self.git_options.github_token_file.is_none() {
+            return Err(CliError::CommandArgumentError(
+                "Uploading to Github needs --github-token-file, --github-token or APTOS_GITHUB_TOKEN"
+                    .to_string(),
+            ));
+        }
+        let github = match self.git_options.get_client()? {
+            Client::Github(github, _) => github.with_max_retries(self.max_retries),
+            Client::Local(_) => {
+                return Err(CliError::CommandArgumentError(
+                    "Configurations are uploaded to a --github-repository".to_string(),
+                ))
+            }
+        };
+        tokio::task::spawn_blocking(move || {
+            upload_configuration(
+                &github,
+                &self.source_repository_dir,
+                &self.username,
+                self.force,
+            )
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to upload: {}", err)))?
+    }
+}
+
+/// A repository which configurations are uploaded to, retrying requests which fail transiently
+pub trait UploadRemote {
+    /// Contents of a file, or `None` if the repository doesn't have it
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, GithubError>;
+
+    /// Creates or replaces a file
+    fn write(&self, path: &str, contents: &[u8]) -> Result<(), GithubError>;
+}
+
+impl UploadRemote for GithubClient {
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, GithubError> {
+        match self.get_file(path) {
+            Ok(contents) => base64::decode(contents)
+                .map(Some)
+                .map_err(|err| GithubError::SerializationError(err.to_string())),
+            Err(GithubError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) -> Result<(), GithubError> {
+        self.put(path, &base64::encode(contents))
+    }
+}
+
+/// Uploads a user's configuration from a local repository to `remote`
+///
+/// The operator and owner configurations are uploaded, along with the configuration's signature
+/// if it was signed.  Files which `remote` already has unchanged aren't written again.
+pub fn upload_configuration(
+    remote: &impl UploadRemote,
+    source_repository_dir: &Path,
+    username: &str,
+    force: bool,
+) -> CliTypedResult<UploadSummary> {
+    let user_dir = PathBuf::from(username);
+    let local_dir = source_repository_dir.join(&user_dir);
+    let owner_contents = read_from_file(&local_dir.join(OWNER_FILE))?;
+    let mut files = vec![
+        (
+            OPERATOR_FILE,
+            read_from_file(&local_dir.join(OPERATOR_FILE))?,
+        ),
+        (OWNER_FILE, owner_contents.clone()),
+    ];
+    // Only the signature is optional, as configurations needn't be signed
+    let signature_path = local_dir.join(CONFIG_SIGNATURE_FILE);
+    if signature_path.exists() {
+        files.push((CONFIG_SIGNATURE_FILE, read_from_file(&signature_path)?));
+    }
+
+    // Check the configuration uploaded for the user is this owner's, before writing anything
+    let owner_path = user_dir.join(OWNER_FILE);
+    if let Some(uploaded) = remote.read(&remote_path(username, OWNER_FILE))? {
+        let owner = owner_account(&owner_contents, &owner_path)?;
+        let uploaded_owner = owner_account(&uploaded, &owner_path)?;
+        if owner != uploaded_owner && !force {
+            return Err(CliError::CommandArgumentError(format!(
+                "The repository already has a configuration for {} with owner account {}, but \
+                 this configuration's owner account is {}.  Use --force to overwrite it",
+                username, uploaded_owner, owner
+            )));
+        }
+    }
+
+    let mut summary = UploadSummary::default();
+    for (file, contents) in files {
+        let remote_path = remote_path(username, file);
+        if remote.read(&remote_path)?.as_ref() != Some(&contents) {
+            remote.write(&remote_path, &contents)?;
+            summary.uploaded.push(user_dir.join(file));
+        } else {
+            summary.unchanged.push(user_dir.join(file));
+        }
+    }
+    Ok(summary)
+}
+
+/// Path of a user's file in the remote repository, which is always separated by `/`
+fn remote_path(username: &str, file: &str) -> String {
+    format!("{}/{}", username, file)
+}
+
+/// The owner account of an owner configuration, as it's written
+fn owner_account(contents: &[u8], path: &Path) -> CliTypedResult<String> {
+    let owner_config: StringOwnerConfiguration = from_yaml(&String::from_utf8(contents.to_vec())?)?;
+    owner_config.owner_account_address.ok_or_else(|| {
+        CliError::CommandArgumentError(format!(
+            "Field owner_account_address is missing in file {}",
+            path.display()
+        ))
+    })
+}
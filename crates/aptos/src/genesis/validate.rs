@@ -0,0 +1,406 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::current_dir,
+    },
+    genesis::{
+        get_string_configs,
+        git::{GitOptions, LAYOUT_FILE, OPERATOR_FILE, OWNER_FILE},
+        keys::{read_public_identity_file, ValidatorConfigurationSummary, PUBLIC_KEYS_FILE},
+    },
+    CliCommand,
+};
+use aptos_crypto::{bls12381, ed25519::Ed25519PublicKey, x25519, ValidCryptoMaterialStringExt};
+use aptos_genesis::config::{HostAndPort, Layout};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use std::{
+    fmt::{Debug, Display},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Check a validator's configuration in the repository against the identities it was set from
+///
+/// Checks every field of the configuration parses, that the keys match the public identity
+/// files, that the consensus key's proof of possession is valid, that the hosts can be dialed,
+/// and that the stake and commission are in range.  Every problem found is reported at once, so
+/// an operator can fix their configuration before genesis is generated from it.
+#[derive(Parser)]
+pub struct ValidateValidatorConfiguration {
+    /// Name of the validator
+    #[clap(long)]
+    pub(crate) username: String,
+
+    /// Path to the owner's public identity, defaults to public-keys.yaml in the current directory
+    #[clap(long, parse(from_os_str))]
+    pub(crate) owner_public_identity_file: Option<PathBuf>,
+
+    /// Path to the operator's public identity, defaults to the owner's
+    #[clap(long, parse(from_os_str))]
+    pub(crate) operator_public_identity_file: Option<PathBuf>,
+
+    /// Path to the voter's public identity, defaults to the owner's
+    #[clap(long, parse(from_os_str))]
+    pub(crate) voter_public_identity_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<ValidatorConfigurationSummary> for ValidateValidatorConfiguration {
+    fn command_name(&self) -> &'static str {
+        "ValidateValidatorConfiguration"
+    }
+
+    async fn execute(self) -> CliTypedResult<ValidatorConfigurationSummary> {
+        let owner_identity_file = if let Some(ref file) = self.owner_public_identity_file {
+            file.clone()
+        } else {
+            current_dir()?.join(PUBLIC_KEYS_FILE)
+        };
+        let owner_identity = read_public_identity_file(&owner_identity_file)?;
+        let read_identity = |file: &Option<PathBuf>| match file {
+            Some(file) => read_public_identity_file(file),
+            None => Ok(owner_identity.clone()),
+        };
+        let operator_identity = read_identity(&self.operator_public_identity_file)?;
+        let voter_identity = read_identity(&self.voter_public_identity_file)?;
+
+        let client = self.git_options.get_client()?;
+        let (owner_config, operator_config) = get_string_configs(&client, &self.username)?;
+        // The layout is normally uploaded first by SetupGit, but may not have been yet
+        let layout: Option<Layout> = client.get(Path::new(LAYOUT_FILE)).ok();
+
+        let dir = PathBuf::from(&self.username);
+        let owner_file = dir.join(OWNER_FILE).display().to_string();
+        let operator_file = dir.join(OPERATOR_FILE).display().to_string();
+        let mut problems = Problems::default();
+        let owner_account_address = problems.parse(
+            &owner_file,
+            "owner_account_address",
+            &owner_config.owner_account_address,
+            AccountAddress::from_str,
+        );
+        let owner_account_public_key = problems.parse(
+            &owner_file,
+            "owner_account_public_key",
+            &owner_config.owner_account_public_key,
+            Ed25519PublicKey::from_encoded_string,
+        );
+        let operator_account_address = problems.parse(
+            &owner_file,
+            "operator_account_address",
+            &owner_config.operator_account_address,
+            AccountAddress::from_str,
+        );
+        let operator_account_public_key = problems.parse(
+            &owner_file,
+            "operator_account_public_key",
+            &owner_config.operator_account_public_key,
+            Ed25519PublicKey::from_encoded_string,
+        );
+        let voter_account_address = problems.parse(
+            &owner_file,
+            "voter_account_address",
+            &owner_config.voter_account_address,
+            AccountAddress::from_str,
+        );
+        let voter_account_public_key = problems.parse(
+            &owner_file,
+            "voter_account_public_key",
+            &owner_config.voter_account_public_key,
+            Ed25519PublicKey::from_encoded_string,
+        );
+        let stake_amount = problems.parse(
+            &owner_file,
+            "stake_amount",
+            &owner_config.stake_amount,
+            u64::from_str,
+        );
+        // Default to 0 for commission percentage if missing, as genesis does
+        let commission_percentage = problems
+            .parse_optional(
+                &owner_file,
+                "commission_percentage",
+                &owner_config.commission_percentage,
+                u64::from_str,
+            )
+            .unwrap_or_default();
+
+        let operator_file_address = problems.parse(
+            &operator_file,
+            "operator_account_address",
+            &operator_config.operator_account_address,
+            AccountAddress::from_str,
+        );
+        let operator_file_public_key = problems.parse(
+            &operator_file,
+            "operator_account_public_key",
+            &operator_config.operator_account_public_key,
+            Ed25519PublicKey::from_encoded_string,
+        );
+        let consensus_public_key = problems.parse(
+            &operator_file,
+            "consensus_public_key",
+            &operator_config.consensus_public_key,
+            bls12381::PublicKey::from_encoded_string,
+        );
+        let proof_of_possession = problems.parse(
+            &operator_file,
+            "consensus_proof_of_possession",
+            &operator_config.consensus_proof_of_possession,
+            bls12381::ProofOfPossession::from_encoded_string,
+        );
+        let validator_network_public_key = problems.parse(
+            &operator_file,
+            "validator_network_public_key",
+            &operator_config.validator_network_public_key,
+            x25519::PublicKey::from_encoded_string,
+        );
+        let full_node_network_public_key = problems.parse_optional(
+            &operator_file,
+            "full_node_network_public_key",
+            &operator_config.full_node_network_public_key,
+            x25519::PublicKey::from_encoded_string,
+        );
+
+        // The owner and operator files must agree on the operator
+        problems.check_match(
+            &owner_file,
+            "operator_account_address",
+            operator_account_address,
+            &operator_file,
+            operator_file_address,
+        );
+        problems.check_match(
+            &owner_file,
+            "operator_account_public_key",
+            operator_account_public_key.clone(),
+            &operator_file,
+            operator_file_public_key,
+        );
+
+        // The keys must be those of the identities
+        let owner_source = owner_identity_file.display().to_string();
+        let operator_source = identity_source(&self.operator_public_identity_file, &owner_source);
+        let voter_source = identity_source(&self.voter_public_identity_file, &owner_source);
+        for (role, address, public_key, identity, source) in [
+            (
+                "owner",
+                owner_account_address,
+                owner_account_public_key,
+                &owner_identity,
+                &owner_source,
+            ),
+            (
+                "operator",
+                operator_account_address,
+                operator_account_public_key,
+                &operator_identity,
+                &operator_source,
+            ),
+            (
+                "voter",
+                voter_account_address,
+                voter_account_public_key,
+                &voter_identity,
+                &voter_source,
+            ),
+        ] {
+            problems.check_match(
+                &owner_file,
+                &format!("{}_account_address", role),
+                address,
+                source,
+                Some(identity.account_address),
+            );
+            problems.check_match(
+                &owner_file,
+                &format!("{}_account_public_key", role),
+                public_key,
+                source,
+                Some(identity.account_public_key.clone()),
+            );
+        }
+        problems.check_match(
+            &operator_file,
+            "consensus_public_key",
+            consensus_public_key.clone(),
+            &operator_source,
+            operator_identity.consensus_public_key.clone(),
+        );
+        problems.check_match(
+            &operator_file,
+            "validator_network_public_key",
+            validator_network_public_key,
+            &operator_source,
+            operator_identity.validator_network_public_key,
+        );
+        problems.check_match(
+            &operator_file,
+            "full_node_network_public_key",
+            full_node_network_public_key,
+            &operator_source,
+            operator_identity.full_node_network_public_key,
+        );
+        if let (Some(key), Some(proof)) = (&consensus_public_key, &proof_of_possession) {
+            if let Err(err) = proof.verify(key) {
+                problems.0.push(format!(
+                    "{}: consensus_proof_of_possession isn't valid for consensus_public_key: {}",
+                    operator_file, err
+                ));
+            }
+        }
+
+        // Hosts must be dialable, with the keys they're dialed with
+        let validator_hosts = std::iter::once(&operator_config.validator_host)
+            .chain(&operator_config.additional_validator_hosts);
+        for host in validator_hosts {
+            problems.check_host(&operator_file, host, validator_network_public_key);
+        }
+        match (
+            &operator_config.full_node_host,
+            &operator_config.full_node_network_public_key,
+        ) {
+            (Some(host), Some(_)) => {
+                problems.check_host(&operator_file, host, full_node_network_public_key)
+            }
+            (None, None) => (),
+            _ => problems.0.push(format!(
+                "{}: full_node_host and full_node_network_public_key must be set together",
+                operator_file
+            )),
+        }
+
+        if commission_percentage > 100 {
+            problems.0.push(format!(
+                "{}: commission_percentage {} is greater than 100",
+                owner_file, commission_percentage
+            ));
+        }
+        if let (Some(stake_amount), Some(layout)) = (stake_amount, &layout) {
+            if stake_amount < layout.min_stake || stake_amount > layout.max_stake {
+                problems.0.push(format!(
+                    "{}: stake_amount {} is outside of the layout's min_stake {} and max_stake {}",
+                    owner_file, stake_amount, layout.min_stake, layout.max_stake
+                ));
+            }
+        }
+
+        problems.into_result(&self.username)?;
+        Ok(ValidatorConfigurationSummary {
+            username: self.username,
+            owner_account_address: owner_identity.account_address,
+            operator_account_address: operator_identity.account_address,
+            voter_account_address: voter_identity.account_address,
+            stake_amount: stake_amount.unwrap_or_default(),
+            commission_percentage,
+            validator_hosts: std::iter::once(operator_config.validator_host)
+                .chain(operator_config.additional_validator_hosts)
+                .collect(),
+            full_node_host: operator_config.full_node_host,
+        })
+    }
+}
+
+/// Names the identity a key should match, which is the owner's unless another file was given
+fn identity_source(file: &Option<PathBuf>, owner_source: &str) -> String {
+    match file {
+        Some(file) => file.display().to_string(),
+        None => owner_source.to_string(),
+    }
+}
+
+/// Every problem found with a validator's configuration
+#[derive(Default)]
+struct Problems(Vec<String>);
+
+impl Problems {
+    /// Parses a required field of a file, recording why if it's missing or invalid
+    fn parse<T, E: Display>(
+        &mut self,
+        file: &str,
+        name: &str,
+        field: &Option<String>,
+        parse: impl Fn(&str) -> Result<T, E>,
+    ) -> Option<T> {
+        if field.is_none() {
+            self.0.push(format!("{}: {} is missing", file, name));
+        }
+        self.parse_optional(file, name, field, parse)
+    }
+
+    /// Parses an optional field of a file, recording why if it's invalid
+    fn parse_optional<T, E: Display>(
+        &mut self,
+        file: &str,
+        name: &str,
+        field: &Option<String>,
+        parse: impl Fn(&str) -> Result<T, E>,
+    ) -> Option<T> {
+        match parse(field.as_deref()?) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.0
+                    .push(format!("{}: {} is invalid: {}", file, name, err));
+                None
+            }
+        }
+    }
+
+    /// Records a value which differs from the one expected, or which isn't expected at all.
+    /// Values which couldn't be read are already recorded.
+    fn check_match<T: Debug + PartialEq>(
+        &mut self,
+        file: &str,
+        name: &str,
+        value: Option<T>,
+        expected_source: &str,
+        expected: Option<T>,
+    ) {
+        match (value, expected) {
+            (Some(value), Some(expected)) if value != expected => self.0.push(format!(
+                "{}: {} {:?} doesn't match {:?} in {}",
+                file, name, value, expected, expected_source
+            )),
+            (Some(_), None) => self.0.push(format!(
+                "{}: {} is set, but missing from {}",
+                file, name, expected_source
+            )),
+            _ => (),
+        }
+    }
+
+    fn check_host(&mut self, file: &str, host: &HostAndPort, key: Option<x25519::PublicKey>) {
+        if host.port == 0 {
+            self.0
+                .push(format!("{}: host {} has port 0", file, host.host));
+        } else if let Some(key) = key {
+            if let Err(err) = host.as_network_address(key) {
+                self.0.push(format!(
+                    "{}: host {}:{} can't be dialed: {}",
+                    file, host.host, host.port, err
+                ));
+            }
+        }
+    }
+
+    fn into_result(self, username: &str) -> CliTypedResult<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(CliError::CommandArgumentError(format!(
+                "Validator configuration of {} has {} problems:\n{}",
+                username,
+                self.0.len(),
+                self.0.join("\n")
+            )))
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliError, CliResult, CliTypedResult},
+    genesis::{
+        diff::{genesis_state, read_genesis},
+        dump::{decode_resource, funded_faucet, missing_resource},
+        get_config,
+        git::{Client, GitOptions, LAYOUT_FILE},
+        GENESIS_FILE,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::Layout;
+use aptos_types::{
+    access_path::Path as AccessPathType,
+    account_address::AccountAddress,
+    account_config::{CoinStoreResource, CORE_CODE_ADDRESS},
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    stake_pool::StakePool,
+    state_store::state_key::StateKey,
+    transaction::Transaction,
+};
+use async_trait::async_trait;
+use clap::{ArgEnum, Parser};
+use move_deps::move_core_types::{
+    ident_str,
+    identifier::IdentStr,
+    move_resource::{MoveResource, MoveStructType},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+use vm_genesis::AccountMap;
+
+/// Show the validator set a genesis starts with
+///
+/// Decodes every stake pool in the genesis, and lists them in validator index order with their
+/// stake, voting power and whether they're in the validator set, followed by the pools which
+/// aren't.  Pools whose stake is below the minimum stake of the genesis are flagged.  With a
+/// repository, each validator is labelled with the user whose configuration it was created from,
+/// and the users left out of genesis for a stake below the minimum are listed last, as excluded.
+/// The faucet account, if given, is listed after them with its balance, as it isn't a validator's.
+#[derive(Parser)]
+pub struct ShowGenesisValidators {
+    /// Path to the genesis to show, defaults to genesis.blob in the current directory
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_file: Option<PathBuf>,
+
+    /// Format the validators are printed in
+    #[clap(long, arg_enum, default_value_t = ValidatorSetFormat::Json)]
+    pub(crate) format: ValidatorSetFormat,
+
+    /// Repository the genesis was generated from, to label the validators with their users
+    ///
+    /// Validators are only listed by address without either --local-repository-dir or
+    /// --github-repository
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+
+    /// Address of the faucet account the genesis was generated with `--faucet-account`
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    pub(crate) faucet_account: Option<AccountAddress>,
+}
+
+/// Formats the validator set is printed in
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValidatorSetFormat {
+    /// A table with a row per validator
+    Table,
+    /// The command's result, as JSON
+    Json,
+}
+
+/// Where a stake pool is in the validator set after genesis
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum ValidatorStatus {
+    Active,
+    PendingActive,
+    PendingInactive,
+    /// The pool isn't in the validator set
+    Inactive,
+    /// The user's stake was below the minimum, so genesis has no pool for them
+    Excluded,
+}
+
+impl Display for ValidatorStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ValidatorStatus::Active => "active",
+            ValidatorStatus::PendingActive => "pending_active",
+            ValidatorStatus::PendingInactive => "pending_inactive",
+            ValidatorStatus::Inactive => "inactive",
+            ValidatorStatus::Excluded => "excluded",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// A stake pool created by genesis, or a user excluded from it for their stake
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GenesisValidatorStake {
+    /// User whose configuration the pool was created from, if the repository was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Address of the pool, or of the owner for an excluded user, who has no pool
+    pub pool_address: AccountAddress,
+    pub operator_address: AccountAddress,
+    /// Active and pending active stake of the pool
+    pub stake: u64,
+    /// Voting power in the validator set, zero for inactive pools
+    pub voting_power: u64,
+    pub status: ValidatorStatus,
+    pub below_minimum_stake: bool,
+}
+
+/// The validator set a genesis starts with, decoded by `ShowGenesisValidators`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GenesisValidatorSet {
+    pub min_stake: u64,
+    pub max_stake: u64,
+    pub total_voting_power: u128,
+    /// Validators in validator index order, active first, followed by the inactive pools, then
+    /// the users excluded for their stake in layout order
+    pub validators: Vec<GenesisValidatorStake>,
+    /// The faucet account funded at genesis, which isn't a validator's, if it was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<AccountMap>,
+}
+
+impl Display for GenesisValidatorSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows = vec![[
+            "user".to_string(),
+            "pool_address".to_string(),
+            "operator_address".to_string(),
+            "stake".to_string(),
+            "voting_power".to_string(),
+            "status".to_string(),
+        ]];
+        for validator in &self.validators {
+            let status = if validator.below_minimum_stake {
+                format!("{} (below minimum stake)", validator.status)
+            } else {
+                validator.status.to_string()
+            };
+            rows.push([
+                validator.user.clone().unwrap_or_else(|| "-".to_string()),
+                validator.pool_address.to_hex_literal(),
+                validator.operator_address.to_hex_literal(),
+                validator.stake.to_string(),
+                validator.voting_power.to_string(),
+                status,
+            ]);
+        }
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        for row in &rows {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        if let Some(faucet) = &self.faucet {
+            writeln!(
+                f,
+                "faucet: {}, balance: {}",
+                faucet.account_address.to_hex_literal(),
+                faucet.balance
+            )?;
+        }
+        write!(
+            f,
+            "min_stake: {}, max_stake: {}, total_voting_power: {}",
+            self.min_stake, self.max_stake, self.total_voting_power
+        )
+    }
+}
+
+#[async_trait]
+impl CliCommand<GenesisValidatorSet> for ShowGenesisValidators {
+    fn command_name(&self) -> &'static str {
+        "ShowGenesisValidators"
+    }
+
+    async fn execute(self) -> CliTypedResult<GenesisValidatorSet> {
+        let genesis_file = self
+            .genesis_file
+            .unwrap_or_else(|| PathBuf::from(GENESIS_FILE));
+        let genesis = read_genesis(&genesis_file)?;
+        let git_options = self.git_options;
+        let client = if git_options.github_repository.is_none()
+            && git_options.local_repository_dir.is_none()
+        {
+            None
+        } else {
+            Some(git_options.get_client()?)
+        };
+        genesis_validator_set(&genesis, client.as_ref(), self.faucet_account)
+    }
+}
+
+impl ShowGenesisValidators {
+    /// Executes the command, printing the validator set as a table with `--format table`
+    pub async fn execute_formatted(self) -> CliResult {
+        match self.format {
+            ValidatorSetFormat::Json => self.execute_serialized().await,
+            ValidatorSetFormat::Table => self
+                .execute()
+                .await
+                .map(|validator_set| validator_set.to_string())
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// The staking config resource of the framework, which has no Rust type outside of genesis
+#[derive(Debug, Deserialize)]
+struct StakingConfig {
+    minimum_stake: u64,
+    maximum_stake: u64,
+    _recurring_lockup_duration_secs: u64,
+    _allow_validator_set_change: bool,
+    _rewards_rate: u64,
+    _rewards_rate_denominator: u64,
+    _voting_power_increase_limit: u64,
+}
+
+impl MoveStructType for StakingConfig {
+    const MODULE_NAME: &'static IdentStr = ident_str!("staking_config");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("StakingConfig");
+}
+
+impl MoveResource for StakingConfig {}
+
+/// Decodes the validator set and stake pools written by a genesis transaction
+///
+/// Validators are labelled with the user in the layout of `client` whose owner or operator
+/// address created them, and the faucet account, if given, is labelled with its balance.
+pub fn genesis_validator_set(
+    genesis: &Transaction,
+    client: Option<&Client>,
+    faucet: Option<AccountAddress>,
+) -> CliTypedResult<GenesisValidatorSet> {
+    let mut validator_set = None;
+    let mut staking_config = None;
+    let mut stake_pools: BTreeMap<_, StakePool> = BTreeMap::new();
+    let mut balances = BTreeMap::new();
+    for (key, bytes) in genesis_state(genesis)? {
+        let access_path = match key {
+            StateKey::AccessPath(access_path) => access_path,
+            _ => continue,
+        };
+        let address = access_path.address;
+        let struct_tag = match access_path.get_path() {
+            AccessPathType::Resource(struct_tag) => struct_tag,
+            AccessPathType::Code(_) => continue,
+        };
+        if struct_tag == StakePool::struct_tag() {
+            stake_pools.insert(address, decode_resource(&bytes, address)?);
+        } else if struct_tag == CoinStoreResource::struct_tag() {
+            let coin_store: CoinStoreResource = decode_resource(&bytes, address)?;
+            balances.insert(address, coin_store.coin());
+        } else if address == CORE_CODE_ADDRESS {
+            if struct_tag == ValidatorSet::struct_tag() {
+                validator_set = Some(decode_resource::<ValidatorSet>(&bytes, address)?);
+            } else if struct_tag == StakingConfig::struct_tag() {
+                staking_config = Some(decode_resource::<StakingConfig>(&bytes, address)?);
+            }
+        }
+    }
+    let validator_set = validator_set.ok_or_else(|| missing_resource("validator set"))?;
+    let staking_config = staking_config.ok_or_else(|| missing_resource("staking config"))?;
+
+    // Pools are created at their owner's address, or with a commission, at an address derived
+    // from it, so users are found by their operator if the pool isn't at their owner's address
+    let mut users_by_address = BTreeMap::new();
+    let mut configs = Vec::new();
+    if let Some(client) = client {
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        for user in &layout.users {
+            let config = get_config(client, user)?;
+            users_by_address
+                .entry(config.operator_account_address)
+                .or_insert_with(|| user.clone());
+            users_by_address.insert(config.owner_account_address, user.clone());
+            configs.push((user.clone(), config));
+        }
+    }
+
+    let in_set = [
+        (ValidatorStatus::Active, &validator_set.active_validators),
+        (
+            ValidatorStatus::PendingActive,
+            &validator_set.pending_active,
+        ),
+        (
+            ValidatorStatus::PendingInactive,
+            &validator_set.pending_inactive,
+        ),
+    ];
+    let mut members: Vec<_> = in_set
+        .iter()
+        .flat_map(|(status, validators)| {
+            validators.iter().map(move |validator| {
+                (
+                    *validator.account_address(),
+                    *status,
+                    validator.consensus_voting_power(),
+                )
+            })
+        })
+        .collect();
+    members.extend(
+        stake_pools
+            .keys()
+            .filter(|address| !members.iter().any(|(member, ..)| member == *address))
+            .map(|address| (*address, ValidatorStatus::Inactive, 0))
+            .collect::<Vec<_>>(),
+    );
+
+    let mut validators = Vec::new();
+    for (pool_address, status, voting_power) in members {
+        let pool = stake_pools.get(&pool_address).ok_or_else(|| {
+            CliError::UnexpectedError(format!(
+                "Validator {} in {} has no stake pool",
+                pool_address, GENESIS_FILE
+            ))
+        })?;
+        let stake = pool.active + pool.pending_active;
+        validators.push(GenesisValidatorStake {
+            user: users_by_address
+                .get(&pool_address)
+                .or_else(|| users_by_address.get(&pool.operator_address))
+                .cloned(),
+            pool_address,
+            operator_address: pool.operator_address,
+            stake,
+            voting_power,
+            status,
+            below_minimum_stake: stake < staking_config.minimum_stake,
+        });
+    }
+    for (user, config) in configs {
+        let in_genesis = validators
+            .iter()
+            .any(|validator| validator.user.as_ref() == Some(&user));
+        if !in_genesis && config.stake_amount < staking_config.minimum_stake {
+            validators.push(GenesisValidatorStake {
+                user: Some(user),
+                pool_address: config.owner_account_address,
+                operator_address: config.operator_account_address,
+                stake: config.stake_amount,
+                voting_power: 0,
+                status: ValidatorStatus::Excluded,
+                below_minimum_stake: true,
+            });
+        }
+    }
+
+    Ok(GenesisValidatorSet {
+        min_stake: staking_config.minimum_stake,
+        max_stake: staking_config.maximum_stake,
+        total_voting_power: validator_set.total_voting_power,
+        validators,
+        faucet: faucet
+            .map(|faucet| funded_faucet(&balances, faucet))
+            .transpose()?,
+    })
+}
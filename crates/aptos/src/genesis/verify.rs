@@ -0,0 +1,263 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::dir_default_to_current,
+    },
+    genesis::{
+        diff::read_genesis,
+        error::GenesisError,
+        get_config,
+        git::{Client, GitOptions, LAYOUT_FILE},
+        read_waypoint, GENESIS_FILE, WAYPOINT_FILE,
+    },
+    CliCommand,
+};
+use aptos_genesis::config::Layout;
+use aptos_types::waypoint::Waypoint;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Verify a generated genesis before publishing it
+///
+/// Recomputes the waypoint from the genesis.blob and checks it matches the waypoint.txt next to
+/// it, then checks that every validator in the layout has a valid configuration in the Git
+/// repository, with stake and a host that can be dialed.  Validators staking less than the
+/// layout's min_stake are reported apart, as `GenerateGenesis` leaves them out of genesis.
+#[derive(Parser)]
+pub struct VerifyGenesis {
+    /// Directory containing the genesis.blob and waypoint.txt to verify
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_dir: Option<PathBuf>,
+
+    /// Name of the genesis file in the directory
+    #[clap(long, default_value = GENESIS_FILE)]
+    pub(crate) genesis_file_name: String,
+
+    /// Name of the waypoint file in the directory
+    #[clap(long, default_value = WAYPOINT_FILE)]
+    pub(crate) waypoint_file_name: String,
+
+    #[clap(flatten)]
+    pub(crate) git_options: GitOptions,
+}
+
+/// A genesis which passed verification
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct VerifiedGenesis {
+    pub waypoint: Waypoint,
+    /// Users whose validators were checked and are in genesis, in layout order
+    pub validators: Vec<String>,
+    /// Users whose validators were checked, but left out of genesis for a stake below the
+    /// layout's min_stake, in layout order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_validators: Vec<String>,
+}
+
+/// The first problem found verifying a genesis
+#[derive(Debug, Error)]
+pub enum GenesisVerificationError {
+    #[error("waypoint.txt is {written}, but genesis.blob has waypoint {computed}")]
+    WaypointMismatch {
+        written: Waypoint,
+        computed: Waypoint,
+    },
+    #[error("Validator {user} has no valid configuration: {reason}")]
+    InvalidConfiguration { user: String, reason: String },
+    #[error("Validator {user} has no stake")]
+    ZeroStake { user: String },
+    #[error("Validator {user} has an invalid host: {reason}")]
+    InvalidHost { user: String, reason: String },
+    #[error(transparent)]
+    Cli(#[from] CliError),
+}
+
+impl From<GenesisVerificationError> for CliError {
+    fn from(err: GenesisVerificationError) -> Self {
+        match err {
+            GenesisVerificationError::Cli(err) => err,
+            GenesisVerificationError::WaypointMismatch { written, computed } => {
+                GenesisError::WaypointMismatch {
+                    expected: written,
+                    computed,
+                }
+                .into()
+            }
+            err => CliError::UnexpectedError(err.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<VerifiedGenesis> for VerifyGenesis {
+    fn command_name(&self) -> &'static str {
+        "VerifyGenesis"
+    }
+
+    async fn execute(self) -> CliTypedResult<VerifiedGenesis> {
+        let genesis_dir = dir_default_to_current(self.genesis_dir)?;
+        let client = self.git_options.get_client()?;
+        Ok(verify_genesis(
+            &genesis_dir.join(&self.genesis_file_name),
+            &genesis_dir.join(&self.waypoint_file_name),
+            &client,
+        )?)
+    }
+}
+
+/// Verifies the genesis and waypoint files against the layout and validators in the repository
+pub fn verify_genesis(
+    genesis_file: &Path,
+    waypoint_file: &Path,
+    client: &Client,
+) -> Result<VerifiedGenesis, GenesisVerificationError> {
+    let genesis = read_genesis(genesis_file)?;
+    let written = read_waypoint(waypoint_file)?;
+    let computed = aptos_genesis::generate_waypoint(&genesis).map_err(|err| {
+        CliError::UnexpectedError(format!("Failed to compute the genesis waypoint: {}", err))
+    })?;
+    if written != computed {
+        return Err(GenesisVerificationError::WaypointMismatch { written, computed });
+    }
+
+    let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+    let mut validators = Vec::new();
+    let mut excluded_validators = Vec::new();
+    for user in &layout.users {
+        let config = get_config(client, user).map_err(|err| {
+            GenesisVerificationError::InvalidConfiguration {
+                user: user.clone(),
+                reason: err.to_string(),
+            }
+        })?;
+        if config.stake_amount == 0 {
+            return Err(GenesisVerificationError::ZeroStake { user: user.clone() });
+        }
+
+        let mut hosts: Vec<_> = config
+            .validator_hosts()
+            .map(|host| (host, config.validator_network_public_key))
+            .collect();
+        if let (Some(host), Some(key)) = (
+            config.full_node_host.as_ref(),
+            config.full_node_network_public_key,
+        ) {
+            hosts.push((host, key));
+        }
+        for (host, key) in hosts {
+            if host.port == 0 {
+                return Err(GenesisVerificationError::InvalidHost {
+                    user: user.clone(),
+                    reason: format!("{} has port 0", host.host),
+                });
+            }
+            if let Err(err) = host.as_network_address(key) {
+                return Err(GenesisVerificationError::InvalidHost {
+                    user: user.clone(),
+                    reason: err.to_string(),
+                });
+            }
+        }
+
+        if config.stake_amount < layout.min_stake {
+            excluded_validators.push(user.clone());
+        } else {
+            validators.push(user.clone());
+        }
+    }
+
+    Ok(VerifiedGenesis {
+        waypoint: computed,
+        validators,
+        excluded_validators,
+    })
+}
+
+/// Verify a waypoint against the genesis it was derived from
+///
+/// Recomputes the waypoint from the genesis.blob and checks it's the waypoint given, either
+/// inline or in a waypoint.txt, so that operators joining a network can trust the waypoint they
+/// were handed.
+#[derive(Parser)]
+pub struct VerifyWaypoint {
+    /// Path to the genesis.blob the waypoint was derived from
+    #[clap(long, parse(from_os_str))]
+    pub(crate) genesis_file: PathBuf,
+
+    /// Waypoint to verify e.g. 0:<hash>
+    #[clap(long)]
+    pub(crate) waypoint: Option<Waypoint>,
+
+    /// Path to a waypoint.txt holding the waypoint to verify, instead of --waypoint
+    #[clap(long, parse(from_os_str))]
+    pub(crate) waypoint_file: Option<PathBuf>,
+}
+
+/// Why a waypoint couldn't be verified against a genesis
+#[derive(Debug, Error)]
+pub enum WaypointVerificationError {
+    #[error("Unable to compute a waypoint from genesis {path}: {reason}")]
+    UnreadableGenesis { path: String, reason: String },
+    #[error("Waypoint is {expected}, but genesis has waypoint {computed}")]
+    WaypointMismatch {
+        expected: Waypoint,
+        computed: Waypoint,
+    },
+    #[error(transparent)]
+    Cli(#[from] CliError),
+}
+
+impl From<WaypointVerificationError> for CliError {
+    fn from(err: WaypointVerificationError) -> Self {
+        match err {
+            WaypointVerificationError::Cli(err) => err,
+            WaypointVerificationError::WaypointMismatch { expected, computed } => {
+                GenesisError::WaypointMismatch { expected, computed }.into()
+            }
+            err => CliError::UnexpectedError(err.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<Waypoint> for VerifyWaypoint {
+    fn command_name(&self) -> &'static str {
+        "VerifyWaypoint"
+    }
+
+    async fn execute(self) -> CliTypedResult<Waypoint> {
+        let expected = match (self.waypoint, self.waypoint_file) {
+            (Some(waypoint), None) => waypoint,
+            (None, Some(waypoint_file)) => read_waypoint(&waypoint_file)?,
+            _ => {
+                return Err(CliError::CommandArgumentError(
+                    "Must provide exactly one of --waypoint and --waypoint-file".to_string(),
+                ))
+            }
+        };
+        Ok(verify_waypoint(&self.genesis_file, expected)?)
+    }
+}
+
+/// Verifies `expected` is the waypoint of the genesis in `genesis_file`, returning it
+pub fn verify_waypoint(
+    genesis_file: &Path,
+    expected: Waypoint,
+) -> Result<Waypoint, WaypointVerificationError> {
+    let unreadable = |reason: String| WaypointVerificationError::UnreadableGenesis {
+        path: genesis_file.display().to_string(),
+        reason,
+    };
+    let genesis = read_genesis(genesis_file).map_err(|err| unreadable(err.to_string()))?;
+    let computed =
+        aptos_genesis::generate_waypoint(&genesis).map_err(|err| unreadable(err.to_string()))?;
+    if expected != computed {
+        return Err(WaypointVerificationError::WaypointMismatch { expected, computed });
+    }
+    Ok(computed)
+}
@@ -0,0 +1,258 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{CliError, CliTypedResult},
+        utils::write_to_file,
+    },
+    genesis::git::{to_yaml, EMPLOYEE_VESTING_ACCOUNTS_FILE},
+};
+use aptos_crypto::PrivateKey;
+use aptos_genesis::{
+    config::{HostAndPort, ValidatorConfiguration},
+    keys::generate_key_objects,
+};
+use aptos_keygen::KeyGen;
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use vm_genesis::{AccountMap, EmployeeAccountMap};
+
+/// An employee vesting group, whose beneficiaries pool their balances into one vesting contract
+#[derive(Clone, Debug)]
+pub struct EmployeeVestingGroup {
+    /// Accounts whose balances vest through the contract
+    pub beneficiaries: Vec<AccountAddress>,
+    /// Number of vesting periods at the start of the schedule in which nothing vests
+    pub cliff_periods: usize,
+    /// Fraction of the balances vested in each period after the cliff, over the denominator
+    pub vesting_schedule_numerators: Vec<u64>,
+    pub vesting_schedule_denominator: u64,
+    /// Validator run with the pooled stake.  Its owner administers the vesting contract, and its
+    /// operator and voter operate and vote with the stake.
+    pub validator: ValidatorConfiguration,
+}
+
+/// Builds the employee vesting accounts of a mainnet genesis, checking each group against the
+/// balances it is funded from
+pub struct EmployeeVestingBuilder {
+    balances: BTreeMap<AccountAddress, u64>,
+    beneficiaries: BTreeSet<AccountAddress>,
+    groups: Vec<EmployeeAccountMap>,
+}
+
+impl EmployeeVestingBuilder {
+    /// Starts a builder for employees funded from `accounts`, as in the balances file
+    pub fn new(accounts: &[AccountMap]) -> EmployeeVestingBuilder {
+        EmployeeVestingBuilder {
+            balances: accounts
+                .iter()
+                .map(|account| (account.account_address, account.balance))
+                .collect(),
+            beneficiaries: BTreeSet::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Adds a group, which must vest all of its beneficiaries' balances, and whose accounts must
+    /// all have balances
+    pub fn add_group(mut self, group: EmployeeVestingGroup) -> CliTypedResult<Self> {
+        if group.vesting_schedule_denominator == 0 {
+            return Err(CliError::UnexpectedError(format!(
+                "Vesting schedule of employees {:?} has a zero denominator",
+                group.beneficiaries
+            )));
+        }
+        let vested: u128 = group
+            .vesting_schedule_numerators
+            .iter()
+            .map(|numerator| *numerator as u128)
+            .sum();
+        if vested != group.vesting_schedule_denominator as u128 {
+            return Err(CliError::UnexpectedError(format!(
+                "Vesting schedule {:?}/{} of employees {:?} vests {}/{} of their balances, \
+                 instead of all of them",
+                group.vesting_schedule_numerators,
+                group.vesting_schedule_denominator,
+                group.beneficiaries,
+                vested,
+                group.vesting_schedule_denominator
+            )));
+        }
+        if group.beneficiaries.is_empty() {
+            return Err(CliError::UnexpectedError(
+                "Employee vesting group has no beneficiaries".to_string(),
+            ));
+        }
+        for beneficiary in &group.beneficiaries {
+            if !self.beneficiaries.insert(*beneficiary) {
+                return Err(CliError::UnexpectedError(format!(
+                    "Employee {} is in more than one vesting group",
+                    beneficiary
+                )));
+            }
+        }
+
+        let validator = &group.validator;
+        for (role, account) in group
+            .beneficiaries
+            .iter()
+            .map(|beneficiary| ("Employee", beneficiary))
+            .chain([
+                ("Admin", &validator.owner_account_address),
+                ("Operator", &validator.operator_account_address),
+                ("Voter", &validator.voter_account_address),
+            ])
+        {
+            if !self.balances.contains_key(account) {
+                return Err(CliError::UnexpectedError(format!(
+                    "{} account {} is not in the balances file",
+                    role, account
+                )));
+            }
+        }
+
+        let mut vesting_schedule_numerators = vec![0; group.cliff_periods];
+        vesting_schedule_numerators.extend(group.vesting_schedule_numerators);
+        self.groups.push(EmployeeAccountMap {
+            accounts: group.beneficiaries,
+            validator: group.validator.try_into().map_err(|err: anyhow::Error| {
+                CliError::UnexpectedError(format!("Invalid employee validator: {}", err))
+            })?,
+            vesting_schedule_numerators,
+            vesting_schedule_denominator: group.vesting_schedule_denominator,
+        });
+        Ok(self)
+    }
+
+    pub fn build(self) -> Vec<EmployeeAccountMap> {
+        self.groups
+    }
+
+    /// Writes the employee vesting accounts file into `dir`, the root of the genesis repository
+    pub fn write(self, dir: &Path) -> CliTypedResult<PathBuf> {
+        let file = dir.join(EMPLOYEE_VESTING_ACCOUNTS_FILE);
+        write_to_file(
+            &file,
+            EMPLOYEE_VESTING_ACCOUNTS_FILE,
+            to_yaml(&self.build())?.as_bytes(),
+        )?;
+        Ok(file)
+    }
+}
+
+/// Shape of the vesting schedule of each group generated by `generate_employee_vesting`
+#[derive(Clone, Copy, Debug)]
+pub struct VestingScheduleShape {
+    /// Number of vesting periods at the start of the schedule in which nothing vests
+    pub cliff_periods: usize,
+    /// Number of periods after the cliff, over which the balances vest in equal parts
+    pub vesting_periods: usize,
+}
+
+/// Employee vesting groups generated by `generate_employee_vesting`, with the balances funding
+/// them
+#[derive(Clone, Debug)]
+pub struct GeneratedEmployeeVesting {
+    /// Balances of every employee, and of each group's validator account, which has nothing
+    pub accounts: Vec<AccountMap>,
+    pub groups: Vec<EmployeeVestingGroup>,
+}
+
+impl GeneratedEmployeeVesting {
+    /// A builder holding every generated group, checked against the generated balances
+    pub fn builder(&self) -> CliTypedResult<EmployeeVestingBuilder> {
+        self.groups.iter().cloned().try_fold(
+            EmployeeVestingBuilder::new(&self.accounts),
+            |builder, group| builder.add_group(group),
+        )
+    }
+}
+
+/// Generates employee vesting groups from `seed`, such as to test mainnet genesis with vesting
+/// accounts
+///
+/// `employees` employees, each funded with `employee_balance`, are split in order into groups of
+/// up to `group_size`.  Each group pools its balances for a validator run by a single account,
+/// and vests them with `schedule`.  Every address and key is derived from the seed, so the same
+/// arguments always generate the same groups.
+pub fn generate_employee_vesting(
+    seed: [u8; 32],
+    employees: usize,
+    group_size: usize,
+    employee_balance: u64,
+    schedule: VestingScheduleShape,
+) -> CliTypedResult<GeneratedEmployeeVesting> {
+    if employees == 0 || group_size == 0 || schedule.vesting_periods == 0 {
+        return Err(CliError::UnexpectedError(format!(
+            "Can't generate {} employees in groups of {}, vesting over {} periods",
+            employees, group_size, schedule.vesting_periods
+        )));
+    }
+    let mut keygen = KeyGen::from_seed(seed);
+    let mut accounts = Vec::new();
+    let mut groups = Vec::new();
+    for (index, start) in (0..employees).step_by(group_size).enumerate() {
+        let (_, _, private_identity, public_identity) = generate_key_objects(&mut keygen)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let beneficiaries: Vec<_> = (start..employees.min(start + group_size))
+            .map(|_| {
+                let (_, public_key) = keygen.generate_ed25519_keypair();
+                AuthenticationKey::ed25519(&public_key).derived_address()
+            })
+            .collect();
+        let stake_amount = employee_balance
+            .checked_mul(beneficiaries.len() as u64)
+            .ok_or_else(|| {
+                CliError::UnexpectedError(format!(
+                    "Stake of employee vesting group {} overflows",
+                    index
+                ))
+            })?;
+        accounts.extend(beneficiaries.iter().map(|beneficiary| AccountMap {
+            account_address: *beneficiary,
+            balance: employee_balance,
+        }));
+
+        let account_address = public_identity.account_address;
+        let account_public_key = public_identity.account_public_key.clone();
+        accounts.push(AccountMap {
+            account_address,
+            balance: 0,
+        });
+        let validator_host =
+            HostAndPort::from_str(&format!("employee-validator-{}.example.com:6180", index))
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        groups.push(EmployeeVestingGroup {
+            beneficiaries,
+            cliff_periods: schedule.cliff_periods,
+            vesting_schedule_numerators: vec![1; schedule.vesting_periods],
+            vesting_schedule_denominator: schedule.vesting_periods as u64,
+            validator: ValidatorConfiguration {
+                owner_account_address: account_address,
+                owner_account_public_key: account_public_key.clone(),
+                operator_account_address: account_address,
+                operator_account_public_key: account_public_key.clone(),
+                voter_account_address: account_address,
+                voter_account_public_key: account_public_key,
+                consensus_public_key: private_identity.consensus_private_key.public_key(),
+                proof_of_possession: public_identity.consensus_proof_of_possession.unwrap(),
+                validator_network_public_key: private_identity
+                    .validator_network_private_key
+                    .public_key(),
+                validator_host,
+                additional_validator_hosts: vec![],
+                full_node_network_public_key: None,
+                full_node_host: None,
+                stake_amount,
+                commission_percentage: 0,
+                chain_id: None,
+            },
+        });
+    }
+    Ok(GeneratedEmployeeVesting { accounts, groups })
+}
@@ -5,7 +5,7 @@
 
 #![forbid(unsafe_code)]
 
-use aptos::{move_tool, Tool};
+use aptos::{common::utils::wrote_to_stdout, move_tool, Tool};
 use clap::Parser;
 use std::process::exit;
 
@@ -16,11 +16,19 @@ async fn main() {
     // Run the corresponding tools
     let result = Tool::parse().execute().await;
 
-    // At this point, we'll want to print and determine whether to exit for an error code
+    // At this point, we'll want to print and determine whether to exit for an error code.  A
+    // file written to stdout is kept apart from the result, which goes to stderr instead.
+    let print = |inner: String| {
+        if wrote_to_stdout() {
+            eprintln!("{}", inner)
+        } else {
+            println!("{}", inner)
+        }
+    };
     match result {
-        Ok(inner) => println!("{}", inner),
+        Ok(inner) => print(inner),
         Err(inner) => {
-            println!("{}", inner);
+            print(inner);
             exit(1);
         }
     }
@@ -16,6 +16,8 @@ export type { AccountSignature_MultiEd25519Signature } from './models/AccountSig
 export type { Address } from './models/Address';
 export type { AptosError } from './models/AptosError';
 export { AptosErrorCode } from './models/AptosErrorCode';
+export type { BatchTableItemRequest } from './models/BatchTableItemRequest';
+export type { BatchTableItemResponse } from './models/BatchTableItemResponse';
 export type { Block } from './models/Block';
 export type { BlockMetadataTransaction } from './models/BlockMetadataTransaction';
 export type { DecodedTableData } from './models/DecodedTableData';
@@ -66,6 +68,10 @@ export type { ScriptWriteSet } from './models/ScriptWriteSet';
 export type { StateCheckpointTransaction } from './models/StateCheckpointTransaction';
 export type { SubmitTransactionRequest } from './models/SubmitTransactionRequest';
 export type { TableItemRequest } from './models/TableItemRequest';
+export type { TableItemResult } from './models/TableItemResult';
+export type { TableIterItem } from './models/TableIterItem';
+export type { TableIterRequest } from './models/TableIterRequest';
+export type { TableIterResponse } from './models/TableIterResponse';
 export type { Transaction } from './models/Transaction';
 export type { Transaction_BlockMetadataTransaction } from './models/Transaction_BlockMetadataTransaction';
 export type { Transaction_GenesisTransaction } from './models/Transaction_GenesisTransaction';
@@ -108,6 +114,8 @@ export { $AccountSignature_MultiEd25519Signature } from './schemas/$AccountSigna
 export { $Address } from './schemas/$Address';
 export { $AptosError } from './schemas/$AptosError';
 export { $AptosErrorCode } from './schemas/$AptosErrorCode';
+export { $BatchTableItemRequest } from './schemas/$BatchTableItemRequest';
+export { $BatchTableItemResponse } from './schemas/$BatchTableItemResponse';
 export { $Block } from './schemas/$Block';
 export { $BlockMetadataTransaction } from './schemas/$BlockMetadataTransaction';
 export { $DecodedTableData } from './schemas/$DecodedTableData';
@@ -158,6 +166,10 @@ export { $ScriptWriteSet } from './schemas/$ScriptWriteSet';
 export { $StateCheckpointTransaction } from './schemas/$StateCheckpointTransaction';
 export { $SubmitTransactionRequest } from './schemas/$SubmitTransactionRequest';
 export { $TableItemRequest } from './schemas/$TableItemRequest';
+export { $TableItemResult } from './schemas/$TableItemResult';
+export { $TableIterItem } from './schemas/$TableIterItem';
+export { $TableIterRequest } from './schemas/$TableIterRequest';
+export { $TableIterResponse } from './schemas/$TableIterResponse';
 export { $Transaction } from './schemas/$Transaction';
 export { $Transaction_BlockMetadataTransaction } from './schemas/$Transaction_BlockMetadataTransaction';
 export { $Transaction_GenesisTransaction } from './schemas/$Transaction_GenesisTransaction';
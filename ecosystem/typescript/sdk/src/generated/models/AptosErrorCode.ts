@@ -18,6 +18,7 @@ export enum AptosErrorCode {
     VERSION_PRUNED = 'version_pruned',
     BLOCK_PRUNED = 'block_pruned',
     INVALID_INPUT = 'invalid_input',
+    TABLE_VALUE_TYPE_MISMATCH = 'table_value_type_mismatch',
     INVALID_TRANSACTION_UPDATE = 'invalid_transaction_update',
     SEQUENCE_NUMBER_TOO_OLD = 'sequence_number_too_old',
     VM_ERROR = 'vm_error',
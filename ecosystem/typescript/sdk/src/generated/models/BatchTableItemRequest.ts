@@ -0,0 +1,16 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { TableItemRequest } from './TableItemRequest';
+
+/**
+ * Table Items request for the GetTableItems API, retrieving several items of a table at once
+ */
+export type BatchTableItemRequest = {
+    /**
+     * Requests for each of the items, answered in the same order
+     */
+    items: Array<TableItemRequest>;
+};
+
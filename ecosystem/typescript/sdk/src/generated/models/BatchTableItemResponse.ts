@@ -0,0 +1,15 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { HashValue } from './HashValue';
+import type { TableItemResult } from './TableItemResult';
+
+/**
+ * The items retrieved by a GetTableItems request, in the order they were requested
+ */
+export type BatchTableItemResponse = {
+    items: Array<TableItemResult>;
+    state_root_hash?: HashValue;
+};
+
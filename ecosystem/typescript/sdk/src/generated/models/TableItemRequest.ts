@@ -2,17 +2,21 @@
 /* tslint:disable */
 /* eslint-disable */
 
+import type { HexEncodedBytes } from './HexEncodedBytes';
 import type { MoveType } from './MoveType';
 
 /**
  * Table Item request for the GetTableItem API
+ *
+ * Exactly one of `key` and `key_bytes` must be provided
  */
 export type TableItemRequest = {
     key_type: MoveType;
     value_type: MoveType;
     /**
-     * The value of the table item's key
+     * The value of the table item's key, encoded as `key_type`
      */
-    key: any;
+    key?: any;
+    key_bytes?: HexEncodedBytes;
 };
 
@@ -0,0 +1,19 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { AptosError } from './AptosError';
+import type { HexEncodedBytes } from './HexEncodedBytes';
+import type { MoveValue } from './MoveValue';
+
+/**
+ * The result of retrieving one item of a batch
+ *
+ * Exactly one of `value` and `error` is set, so that one bad key doesn't fail the whole batch
+ */
+export type TableItemResult = {
+    value?: MoveValue;
+    error?: AptosError;
+    proof?: HexEncodedBytes;
+};
+
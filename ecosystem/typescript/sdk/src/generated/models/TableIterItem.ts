@@ -0,0 +1,14 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { HexEncodedBytes } from './HexEncodedBytes';
+
+/**
+ * An item of a table, as it's stored
+ */
+export type TableIterItem = {
+    key: HexEncodedBytes;
+    value: HexEncodedBytes;
+};
+
@@ -0,0 +1,22 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { HexEncodedBytes } from './HexEncodedBytes';
+
+/**
+ * Table Items Page request for the GetTableItemsPage API, retrieving a page of a table's items
+ *
+ * Items are ordered by their raw state keys, being the table handle followed by the BCS
+ * serialized key, rather than by the Move ordering of their keys
+ */
+export type TableIterRequest = {
+    cursor?: HexEncodedBytes;
+    /**
+     * Maximum number of items in the page, up to the node's maximum page size
+     *
+     * If not provided, it will be the default page size
+     */
+    limit?: number;
+};
+
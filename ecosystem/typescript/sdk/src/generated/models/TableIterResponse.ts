@@ -0,0 +1,20 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+
+import type { HexEncodedBytes } from './HexEncodedBytes';
+import type { TableIterItem } from './TableIterItem';
+import type { U64 } from './U64';
+
+/**
+ * A page of a table's items, retrieved by a GetTableItemsPage request
+ */
+export type TableIterResponse = {
+    /**
+     * The items of the page, in the order of their raw state keys
+     */
+    items: Array<TableIterItem>;
+    cursor?: HexEncodedBytes;
+    ledger_version: U64;
+};
+
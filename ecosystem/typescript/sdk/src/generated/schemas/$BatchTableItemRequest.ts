@@ -0,0 +1,15 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $BatchTableItemRequest = {
+    description: `Table Items request for the GetTableItems API, retrieving several items of a table at once`,
+    properties: {
+        items: {
+            type: 'array',
+            contains: {
+                type: 'TableItemRequest',
+            },
+            isRequired: true,
+        },
+    },
+} as const;
@@ -0,0 +1,18 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $BatchTableItemResponse = {
+    description: `The items retrieved by a GetTableItems request, in the order they were requested`,
+    properties: {
+        items: {
+            type: 'array',
+            contains: {
+                type: 'TableItemResult',
+            },
+            isRequired: true,
+        },
+        state_root_hash: {
+            type: 'HashValue',
+        },
+    },
+} as const;
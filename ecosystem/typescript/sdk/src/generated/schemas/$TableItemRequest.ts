@@ -2,7 +2,9 @@
 /* tslint:disable */
 /* eslint-disable */
 export const $TableItemRequest = {
-    description: `Table Item request for the GetTableItem API`,
+    description: `Table Item request for the GetTableItem API
+
+    Exactly one of \`key\` and \`key_bytes\` must be provided`,
     properties: {
         key_type: {
             type: 'MoveType',
@@ -13,10 +15,12 @@ export const $TableItemRequest = {
             isRequired: true,
         },
         key: {
-            description: `The value of the table item's key`,
+            description: `The value of the table item's key, encoded as \`key_type\``,
             properties: {
             },
-            isRequired: true,
+        },
+        key_bytes: {
+            type: 'HexEncodedBytes',
         },
     },
 } as const;
@@ -0,0 +1,19 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $TableItemResult = {
+    description: `The result of retrieving one item of a batch
+
+    Exactly one of \`value\` and \`error\` is set, so that one bad key doesn't fail the whole batch`,
+    properties: {
+        value: {
+            type: 'MoveValue',
+        },
+        error: {
+            type: 'AptosError',
+        },
+        proof: {
+            type: 'HexEncodedBytes',
+        },
+    },
+} as const;
@@ -0,0 +1,16 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $TableIterItem = {
+    description: `An item of a table, as it's stored`,
+    properties: {
+        key: {
+            type: 'HexEncodedBytes',
+            isRequired: true,
+        },
+        value: {
+            type: 'HexEncodedBytes',
+            isRequired: true,
+        },
+    },
+} as const;
@@ -0,0 +1,21 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $TableIterRequest = {
+    description: `Table Items Page request for the GetTableItemsPage API, retrieving a page of a table's items
+
+    Items are ordered by their raw state keys, being the table handle followed by the BCS
+    serialized key, rather than by the Move ordering of their keys`,
+    properties: {
+        cursor: {
+            type: 'HexEncodedBytes',
+        },
+        limit: {
+            type: 'number',
+            description: `Maximum number of items in the page, up to the node's maximum page size
+
+            If not provided, it will be the default page size`,
+            format: 'uint16',
+        },
+    },
+} as const;
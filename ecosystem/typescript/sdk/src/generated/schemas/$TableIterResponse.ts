@@ -0,0 +1,22 @@
+/* istanbul ignore file */
+/* tslint:disable */
+/* eslint-disable */
+export const $TableIterResponse = {
+    description: `A page of a table's items, retrieved by a GetTableItemsPage request`,
+    properties: {
+        items: {
+            type: 'array',
+            contains: {
+                type: 'TableIterItem',
+            },
+            isRequired: true,
+        },
+        cursor: {
+            type: 'HexEncodedBytes',
+        },
+        ledger_version: {
+            type: 'U64',
+            isRequired: true,
+        },
+    },
+} as const;
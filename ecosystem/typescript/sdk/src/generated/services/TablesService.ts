@@ -2,8 +2,12 @@
 /* tslint:disable */
 /* eslint-disable */
 import type { Address } from '../models/Address';
+import type { BatchTableItemRequest } from '../models/BatchTableItemRequest';
+import type { BatchTableItemResponse } from '../models/BatchTableItemResponse';
 import type { MoveValue } from '../models/MoveValue';
 import type { TableItemRequest } from '../models/TableItemRequest';
+import type { TableIterRequest } from '../models/TableIterRequest';
+import type { TableIterResponse } from '../models/TableIterResponse';
 import type { U64 } from '../models/U64';
 
 import type { CancelablePromise } from '../core/CancelablePromise';
@@ -18,6 +22,21 @@ export class TablesService {
      * Get a table item at a specific ledger version from the table identified by {table_handle}
      * in the path and the "key" (TableItemRequest) provided in the request body.
      *
+     * The key is either given as JSON, and encoded as its "key_type", or given already BCS
+     * serialized as "key_bytes", but not both.
+     *
+     * With BCS output (an Accept header of application/x-bcs), the value is returned as the
+     * raw BCS bytes stored in the table, without being converted to JSON by its "value_type",
+     * so that it can be decoded without losing precision.
+     *
+     * With `with_proof`, the item's sparse merkle proof, in the same format as the proofs of
+     * table items in a batch, is returned in the X-Aptos-State-Proof header, and the root hash
+     * of the state at the ledger version it's against in the X-Aptos-State-Root-Hash header.
+     * The value is returned as it is without them.  The state root hash is only known at state
+     * checkpoints, so if the ledger version isn't one, the server responds with a 400.  Only
+     * items in the table are proven, the proof that an item isn't in it is only returned by
+     * the batch endpoint.
+     *
      * This is a POST endpoint because the "key" for requesting a specific
      * table item (TableItemRequest) could be quite complex, as each of its
      * fields could themselves be composed of other structs. This makes it
@@ -30,6 +49,9 @@ export class TablesService {
      * @param ledgerVersion Ledger version to get state of account
      *
      * If not provided, it will be the latest version
+     * @param withProof If set to true, include a proof of the item against the state root hash
+     *
+     * If not provided, no proof will be retrieved
      * @returns MoveValue
      * @throws ApiError
      */
@@ -37,6 +59,7 @@ export class TablesService {
         tableHandle: Address,
         requestBody: TableItemRequest,
         ledgerVersion?: U64,
+        withProof?: boolean,
     ): CancelablePromise<MoveValue> {
         return this.httpRequest.request({
             method: 'POST',
@@ -44,6 +67,98 @@ export class TablesService {
             path: {
                 'table_handle': tableHandle,
             },
+            query: {
+                'ledger_version': ledgerVersion,
+                'with_proof': withProof,
+            },
+            body: requestBody,
+            mediaType: 'application/json',
+        });
+    }
+
+    /**
+     * Get table items
+     * Get several items at a specific ledger version from the table identified by {table_handle}
+     * in the path, with a request (TableItemRequest) for each of them in the request body.
+     *
+     * The items are returned in the order they were requested.  Each is either the item's value,
+     * or the error which retrieving it on its own would have returned, so that one bad key
+     * doesn't fail the whole batch.  Items can only be returned as JSON.
+     *
+     * If more items are requested than the node's limit, the server responds with a 400.
+     *
+     * With `with_proof`, each item also has a sparse merkle proof against the root hash of the
+     * state at the ledger version, which is returned alongside the items.  Items which aren't
+     * in the table are proven not to be.  The state root hash is only known at state
+     * checkpoints, so if the ledger version isn't one, the server responds with a 400.
+     *
+     * The Aptos nodes prune account state history, via a configurable time window.
+     * If the requested ledger version has been pruned, the server responds with a 410.
+     * @param tableHandle Table handle hex encoded 32-byte string
+     * @param requestBody
+     * @param ledgerVersion Ledger version to get state of account
+     *
+     * If not provided, it will be the latest version
+     * @param withProof If set to true, include a proof of each item against the state root hash
+     *
+     * If not provided, no proofs will be retrieved
+     * @returns BatchTableItemResponse
+     * @throws ApiError
+     */
+    public getTableItems(
+        tableHandle: Address,
+        requestBody: BatchTableItemRequest,
+        ledgerVersion?: U64,
+        withProof?: boolean,
+    ): CancelablePromise<BatchTableItemResponse> {
+        return this.httpRequest.request({
+            method: 'POST',
+            url: '/tables/{table_handle}/items',
+            path: {
+                'table_handle': tableHandle,
+            },
+            query: {
+                'ledger_version': ledgerVersion,
+                'with_proof': withProof,
+            },
+            body: requestBody,
+            mediaType: 'application/json',
+        });
+    }
+
+    /**
+     * Get table items page
+     * Get a page of the items of the table identified by {table_handle} in the path, at a
+     * specific ledger version, from the cursor (TableIterRequest) in the request body.
+     *
+     * Items are returned as their BCS serialized keys and values, ordered by their raw state
+     * keys, which isn't the Move ordering of the keys.  Every page but the last has the cursor
+     * of the next.  Retrieve each page at the ledger version returned with the first, so that
+     * together they're a consistent view of the table.  Items can only be returned as JSON.
+     *
+     * If the limit is over the node's maximum page size, the server responds with a 400.
+     *
+     * The Aptos nodes prune account state history, via a configurable time window.
+     * If the requested ledger version has been pruned, the server responds with a 410.
+     * @param tableHandle Table handle hex encoded 32-byte string
+     * @param requestBody
+     * @param ledgerVersion Ledger version to get state of account
+     *
+     * If not provided, it will be the latest version
+     * @returns TableIterResponse
+     * @throws ApiError
+     */
+    public getTableItemsPage(
+        tableHandle: Address,
+        requestBody: TableIterRequest,
+        ledgerVersion?: U64,
+    ): CancelablePromise<TableIterResponse> {
+        return this.httpRequest.request({
+            method: 'POST',
+            url: '/tables/{table_handle}/iter',
+            path: {
+                'table_handle': tableHandle,
+            },
             query: {
                 'ledger_version': ledgerVersion,
             },
@@ -6,6 +6,7 @@
 use proxy::Proxy;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::{io::Read, thread, time::Duration};
 use thiserror::Error;
 
 /// Request timeout for github operations
@@ -13,9 +14,16 @@ const ACCEPT_HEADER: &str = "Accept";
 const ACCEPT_VALUE: &str = "Accept: application/vnd.github.v3+json";
 const TIMEOUT: u64 = 10_000;
 const URL: &str = "https://api.github.com";
+const RAW_URL: &str = "https://raw.githubusercontent.com";
+/// Number of times requests are retried after transient failures by default, and the backoff
+/// before the first retry, doubling with each one
+const MAX_RETRIES: u32 = 4;
+const INITIAL_BACKOFF_MS: u64 = 500;
 
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum Error {
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
     #[error("Http error, status code: {0}, status text: {1}, body: {2}")]
     HttpError(u16, String, String),
     #[error("Internal error: {0}")]
@@ -28,6 +36,17 @@ pub enum Error {
     SerializationError(String),
 }
 
+impl Error {
+    /// Whether the request which failed with this error may succeed if retried
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::ConnectionError(_) => true,
+            Error::HttpError(status, _, _) => is_transient_status(*status),
+            _ => false,
+        }
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
         Self::SerializationError(format!("{}", error))
@@ -37,8 +56,12 @@ impl From<std::io::Error> for Error {
 impl From<ureq::Response> for Error {
     fn from(resp: ureq::Response) -> Self {
         if let Some(e) = resp.synthetic_error() {
-            // Local error
-            Error::InternalError(e.to_string())
+            if is_connection_failure(e) {
+                Error::ConnectionError(e.to_string())
+            } else {
+                // Local error
+                Error::InternalError(e.to_string())
+            }
         } else {
             // Clear the buffer
             let status = resp.status();
@@ -67,7 +90,10 @@ pub struct Client {
     branch: String,
     owner: String,
     repository: String,
-    token: String,
+    token: Option<String>,
+    url: String,
+    max_retries: u32,
+    initial_backoff: Duration,
 }
 
 impl Client {
@@ -76,10 +102,44 @@ impl Client {
             branch,
             owner,
             repository,
-            token,
+            token: Some(token),
+            url: URL.to_string(),
+            max_retries: MAX_RETRIES,
+            initial_backoff: Duration::from_millis(INITIAL_BACKOFF_MS),
         }
     }
 
+    /// Creates a client without a token, which can only read public repositories
+    pub fn unauthenticated(owner: String, repository: String, branch: String) -> Self {
+        Self {
+            branch,
+            owner,
+            repository,
+            token: None,
+            url: URL.to_string(),
+            max_retries: MAX_RETRIES,
+            initial_backoff: Duration::from_millis(INITIAL_BACKOFF_MS),
+        }
+    }
+
+    /// Sends requests to the API at `url` rather than GitHub's, such as a GitHub Enterprise server
+    pub fn with_url(mut self, url: String) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Retries each request up to `max_retries` times after transient failures
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Backs off for `initial_backoff` before the first retry of a request, doubling with each one
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
     /// Delete a file from a GitHub repository
     pub fn delete_file(&self, path: &str) -> Result<(), Error> {
         // Occasionally GitHub sends us back delayed results and the file is already deleted.
@@ -116,7 +176,10 @@ impl Client {
 
     /// Retrieve a list of branches, this is effectively a status check on the repository
     pub fn get_branches(&self) -> Result<Vec<String>, Error> {
-        let url = format!("{}/repos/{}/{}/branches", URL, self.owner, self.repository);
+        let url = format!(
+            "{}/repos/{}/{}/branches",
+            self.url, self.owner, self.repository
+        );
         let resp = self.upgrade_request(ureq::get(&url)).call();
 
         match resp.status() {
@@ -164,6 +227,25 @@ impl Client {
         }
     }
 
+    /// Retrieve the raw contents of a file through the raw content API, which unlike
+    /// [`Client::get_file`] isn't limited in size and returns the contents undecoded.
+    pub fn get_raw_file(&self, path: &str) -> Result<Vec<u8>, Error> {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            RAW_URL, self.owner, self.repository, self.branch, path
+        );
+        let resp = self.call_with_retries(|| self.upgrade_request(ureq::get(&url)).call());
+        match resp.status() {
+            200 => {
+                let mut contents = Vec::new();
+                resp.into_reader().read_to_end(&mut contents)?;
+                Ok(contents)
+            }
+            404 => Err(Error::NotFound(path.into())),
+            _ => Err(resp.into()),
+        }
+    }
+
     /// Create or update a file.
     pub fn put(&self, path: &str, content: &str) -> Result<(), Error> {
         let json = match self.get_sha(path) {
@@ -176,9 +258,12 @@ impl Client {
             Err(e) => return Err(e),
         };
 
-        let resp = self
-            .upgrade_request(ureq::put(&self.post_url(path)))
-            .send_json(json);
+        // The sha makes the update conditional on the file being unchanged, so it can be retried
+        let url = self.post_url(path);
+        let resp = self.call_with_retries(|| {
+            self.upgrade_request(ureq::put(&url))
+                .send_json(json.clone())
+        });
 
         match resp.status() {
             200 => Ok(()),
@@ -189,8 +274,10 @@ impl Client {
 
     /// Simple wrapper around requests to add default parameters to the request
     fn upgrade_request(&self, mut request: ureq::Request) -> ureq::Request {
+        if let Some(token) = &self.token {
+            request.set("Authorization", &format!("token {}", token));
+        }
         request
-            .set("Authorization", &format!("token {}", self.token))
             .set(ACCEPT_HEADER, ACCEPT_VALUE)
             .timeout_connect(TIMEOUT);
 
@@ -203,9 +290,30 @@ impl Client {
         request
     }
 
+    /// Sends a request until it doesn't fail transiently, backing off exponentially between
+    /// attempts.  Only idempotent or conditional requests may be retried.
+    fn call_with_retries(&self, call: impl Fn() -> ureq::Response) -> ureq::Response {
+        let mut backoff = self.initial_backoff;
+        let mut resp = call();
+        for _ in 0..self.max_retries {
+            let transient = match resp.synthetic_error() {
+                Some(e) => is_connection_failure(e),
+                None => is_transient_status(resp.status()),
+            };
+            if !transient {
+                break;
+            }
+            thread::sleep(backoff);
+            backoff *= 2;
+            resp = call();
+        }
+        resp
+    }
+
     /// Get can read files or directories, this makes it easier to use
     fn get_internal(&self, path: &str) -> Result<Vec<GetResponse>, Error> {
-        let resp = self.upgrade_request(ureq::get(&self.get_url(path))).call();
+        let url = self.get_url(path);
+        let resp = self.call_with_retries(|| self.upgrade_request(ureq::get(&url)).call());
         match resp.status() {
             200 => {
                 let resp = resp.into_string()?;
@@ -246,18 +354,32 @@ impl Client {
     fn post_url(&self, path: &str) -> String {
         format!(
             "{}/repos/{}/{}/contents/{}",
-            URL, self.owner, self.repository, path
+            self.url, self.owner, self.repository, path
         )
     }
 
     fn get_url(&self, path: &str) -> String {
         format!(
             "{}/repos/{}/{}/contents/{}?ref={}",
-            URL, self.owner, self.repository, path, self.branch
+            self.url, self.owner, self.repository, path, self.branch
         )
     }
 }
 
+/// Whether a request failing with `status` may succeed if retried
+fn is_transient_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+/// Whether a request failed locally because it couldn't reach GitHub, or lost its connection,
+/// rather than because the request itself is invalid
+fn is_connection_failure(error: &ureq::Error) -> bool {
+    matches!(
+        error,
+        ureq::Error::DnsFailed(_) | ureq::Error::ConnectionFailed(_) | ureq::Error::Io(_)
+    )
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq, Serialize)]
 struct Branch {
     name: String,
@@ -390,6 +512,51 @@ mod tests {
         github.get_file(file1).unwrap_err();
     }
 
+    #[test]
+    fn test_transient_statuses() {
+        for status in [429, 500, 502, 503, 504] {
+            assert!(is_transient_status(status));
+        }
+        for status in [200, 401, 403, 404, 422] {
+            assert!(!is_transient_status(status));
+        }
+        let http_error = |status| Error::HttpError(status, String::new(), String::new());
+        assert!(http_error(503).is_transient());
+        assert!(!http_error(409).is_transient());
+        assert!(Error::ConnectionError("connection refused".into()).is_transient());
+        assert!(!Error::InternalError("No content found".into()).is_transient());
+        assert!(!Error::NotFound("file".into()).is_transient());
+    }
+
+    #[test]
+    fn test_connection_failures() {
+        // Nothing listens on the port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let github = Client::unauthenticated(OWNER.into(), REPOSITORY.into(), BRANCH.into())
+            .with_url(url)
+            .with_max_retries(1)
+            .with_initial_backoff(Duration::ZERO);
+        let err = github.get_file("data.txt").unwrap_err();
+        assert!(matches!(err, Error::ConnectionError(_)), "{:?}", err);
+        assert!(err.is_transient());
+    }
+
+    #[ignore]
+    #[test]
+    fn test_raw_files() {
+        let path = "raw.txt";
+        let value = "hello";
+
+        let github = Client::new(OWNER.into(), REPOSITORY.into(), BRANCH.into(), TOKEN.into());
+        github.get_raw_file(path).unwrap_err();
+        github.put(path, &base64::encode(&value)).unwrap();
+        assert_eq!(github.get_raw_file(path).unwrap(), value.as_bytes());
+        github.delete_file(path).unwrap();
+    }
+
     #[ignore]
     #[test]
     fn test_branches() {
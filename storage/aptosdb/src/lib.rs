@@ -880,6 +880,20 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_values_by_key_prefix_paged(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        limit: usize,
+        version: Version,
+    ) -> Result<(Vec<(StateKey, StateValue)>, Option<StateKey>)> {
+        gauged_api("get_state_values_by_key_prefix_paged", || {
+            self.error_if_ledger_pruned("State", version)?;
+            self.state_store
+                .get_values_by_key_prefix_paged(key_prefix, cursor, limit, version)
+        })
+    }
+
     fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
         gauged_api("get_latest_ledger_info_option", || {
             Ok(self.ledger_store.get_latest_ledger_info_option())
@@ -473,6 +473,62 @@ impl StateStore {
         Ok(result)
     }
 
+    /// Returns a page of at most `limit` key, value pairs for a state key prefix at a desired
+    /// version, starting from `cursor` if given, along with the cursor of the next page if there
+    /// is one.  Pages are ordered by the encoded state keys, so keys which are added to the
+    /// prefix after the desired version don't change them.
+    pub fn get_values_by_key_prefix_paged(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        limit: usize,
+        desired_version: Version,
+    ) -> Result<(Vec<(StateKey, StateValue)>, Option<StateKey>)> {
+        let mut read_opts = ReadOptions::default();
+        // See `get_values_by_key_prefix`, seeks must be in total order to move between keys
+        read_opts.set_total_order_seek(true);
+        let mut iter = self.ledger_db.iter::<StateValueSchema>(read_opts)?;
+        match cursor {
+            Some(cursor) => {
+                ensure!(
+                    key_prefix.is_prefix(cursor)?,
+                    "Cursor {:?} is not in key_prefix {:?}",
+                    cursor,
+                    key_prefix
+                );
+                iter.seek(&(cursor.clone(), desired_version))?;
+            }
+            None => iter.seek(&(key_prefix))?,
+        }
+
+        let mut result = Vec::new();
+        let mut prev_key = None;
+        while let Some(((state_key, version), state_value_opt)) = iter.next().transpose()? {
+            // In case the previous seek() ends on the same key with version 0.
+            if Some(&state_key) == prev_key.as_ref() {
+                continue;
+            }
+            if !key_prefix.is_prefix(&state_key)? {
+                break;
+            }
+            if version > desired_version {
+                iter.seek(&(state_key.clone(), desired_version))?;
+                continue;
+            }
+
+            if let Some(state_value) = state_value_opt {
+                // The page is full, so this key starts the next one
+                if result.len() == limit {
+                    return Ok((result, Some(state_key)));
+                }
+                result.push((state_key.clone(), state_value));
+            }
+            prev_key = Some(state_key.clone());
+            iter.seek(&(state_key, 0))?;
+        }
+        Ok((result, None))
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_value_range_proof(
         &self,
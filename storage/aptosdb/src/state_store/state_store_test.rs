@@ -9,7 +9,9 @@ use crate::{
 use aptos_jellyfish_merkle::TreeReader;
 use aptos_temppath::TempPath;
 use aptos_types::{
-    access_path::AccessPath, account_address::AccountAddress, state_store::state_key::StateKeyTag,
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::{state_key::StateKeyTag, table::TableHandle},
 };
 use proptest::{collection::hash_map, prelude::*};
 use storage_interface::{jmt_update_refs, jmt_updates, DbReader, DbWriter, StateSnapshotReceiver};
@@ -226,6 +228,81 @@ fn test_get_values_by_key_prefix() {
     assert_eq!(*key_value_map.get(&key5).unwrap(), value5_v2);
 }
 
+#[test]
+fn test_get_values_by_key_prefix_paged() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let handle = TableHandle(AccountAddress::new([12u8; AccountAddress::LENGTH]));
+    let other_handle = TableHandle(AccountAddress::new([22u8; AccountAddress::LENGTH]));
+    let key = |handle: TableHandle, key: u8| StateKey::table_item(handle, vec![key]);
+    let value = |value: &str| StateValue::from(value.as_bytes().to_vec());
+    let table_key_prefix = StateKeyPrefix::from(handle);
+
+    put_value_set(
+        store,
+        vec![
+            (key(handle, 1), value("value1_v0")),
+            (key(handle, 2), value("value2_v0")),
+            (key(handle, 3), value("value3_v0")),
+            (key(other_handle, 1), value("other_v0")),
+        ],
+        0,
+        None,
+    );
+    put_value_set(
+        store,
+        vec![
+            (key(handle, 0), value("value0_v1")),
+            (key(handle, 2), value("value2_v1")),
+        ],
+        1,
+        Some(0),
+    );
+
+    // Pages are in key order, and continue from their cursor
+    let (page, cursor) = store
+        .get_values_by_key_prefix_paged(&table_key_prefix, None, 2, 0)
+        .unwrap();
+    assert_eq!(
+        page,
+        vec![
+            (key(handle, 1), value("value1_v0")),
+            (key(handle, 2), value("value2_v0")),
+        ]
+    );
+    assert_eq!(cursor, Some(key(handle, 3)));
+    let (page, cursor) = store
+        .get_values_by_key_prefix_paged(&table_key_prefix, cursor.as_ref(), 2, 0)
+        .unwrap();
+    assert_eq!(page, vec![(key(handle, 3), value("value3_v0"))]);
+    assert_eq!(cursor, None);
+
+    // Later versions have the items added and updated since
+    let (page, cursor) = store
+        .get_values_by_key_prefix_paged(&table_key_prefix, None, 3, 1)
+        .unwrap();
+    assert_eq!(
+        page,
+        vec![
+            (key(handle, 0), value("value0_v1")),
+            (key(handle, 1), value("value1_v0")),
+            (key(handle, 2), value("value2_v1")),
+        ]
+    );
+    assert_eq!(cursor, Some(key(handle, 3)));
+
+    // A full last page has no cursor, and cursors must be in the prefix
+    let (page, cursor) = store
+        .get_values_by_key_prefix_paged(&table_key_prefix, None, 4, 1)
+        .unwrap();
+    assert_eq!(page.len(), 4);
+    assert_eq!(cursor, None);
+    assert!(store
+        .get_values_by_key_prefix_paged(&table_key_prefix, Some(&key(other_handle, 1)), 2, 1)
+        .is_err());
+}
+
 #[test]
 pub fn test_get_state_snapshot_before() {
     let tmp_dir = TempPath::new();
@@ -268,6 +268,19 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns a page of at most `limit` key, value pairs for a state key prefix at a desired
+    /// version, ordered by the encoded state keys, starting from `cursor` if given.  Also returns
+    /// the key the next page starts from, as its cursor, unless this is the last page.
+    fn get_state_values_by_key_prefix_paged(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        limit: usize,
+        version: Version,
+    ) -> Result<(Vec<(StateKey, StateValue)>, Option<StateKey>)> {
+        unimplemented!()
+    }
+
     /// Returns the latest ledger info, if any.
     fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
         unimplemented!()
@@ -12,15 +12,28 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{env, num::NonZeroUsize, process, thread, time::Duration};
 use structopt::StructOpt;
+use testcases::api_overload_test::ApiOverloadTest;
+use testcases::backward_time_jump_test::BackwardTimeJumpTest;
+use testcases::connection_limit_test::ConnectionLimitTest;
 use testcases::consensus_reliability_tests::ChangingWorkingQuorumTest;
+use testcases::consensus_stall_test::ConsensusStallTest;
 use testcases::continuous_progress_test::ContinuousProgressTest;
+use testcases::coordinated_upgrade_test::CoordinatedUpgradeTest;
 use testcases::fullnode_reboot_stress_test::FullNodeRebootStressTest;
+use testcases::gas_limit_test::{
+    GasLimitTest, DEFAULT_OVER_LIMIT_GAS_AMOUNTS, DEFAULT_WITHIN_LIMIT_GAS_AMOUNTS,
+};
+use testcases::leader_election_tie_test::LeaderElectionTieTest;
 use testcases::load_vs_perf_benchmark::LoadVsPerfBenchmark;
 use testcases::network_bandwidth_test::NetworkBandwidthTest;
 use testcases::network_latency_test::NetworkLatencyTest;
 use testcases::network_loss_test::NetworkLossTest;
 use testcases::performance_with_fullnode_test::PerformanceBenchmarkWithFN;
+use testcases::rolling_restart_test::RollingRestartTest;
+use testcases::split_brain_merge_test::SplitBrainMergeTest;
+use testcases::stake_concentration_test::StakeConcentrationTest;
 use testcases::state_sync_performance::StateSyncValidatorPerformance;
+use testcases::validator_partition_test::ValidatorPartitionTest;
 use testcases::validator_reboot_stress_test::ValidatorRebootStressTest;
 use testcases::{
     compatibility_test::SimpleValidatorUpgrade, forge_setup_test::ForgeSetupTest, generate_traffic,
@@ -450,7 +463,65 @@ fn single_test_suite(test_name: &str) -> Result<ForgeConfig<'static>> {
             .with_genesis_helm_config_fn(Arc::new(|helm_values| {
                 helm_values["chain"]["epoch_duration_secs"] = 30.into();
             })),
+        "coordinated_upgrade" => config
+            .with_initial_validator_count(NonZeroUsize::new(5).unwrap())
+            .with_initial_fullnode_count(1)
+            .with_network_tests(vec![&CoordinatedUpgradeTest {
+                old_version: None,
+                new_version: None,
+                health_wait: HealthWaitConfig::with_timeout(Duration::from_secs(120)),
+            }])
+            .with_success_criteria(SuccessCriteria::new(
+                1000,
+                10000,
+                false,
+                Some(Duration::from_secs(240)),
+                None,
+            )),
+        "api_overload" => config
+            .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+            .with_initial_fullnode_count(1)
+            .with_network_tests(vec![&ApiOverloadTest { concurrency: 256 }]),
         "config" => config.with_network_tests(vec![&ReconfigurationTest]),
+        "gas_limit_test" => config
+            .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+            .with_network_tests(vec![&GasLimitTest {
+                over_limit_gas_amounts: DEFAULT_OVER_LIMIT_GAS_AMOUNTS,
+                within_limit_gas_amounts: DEFAULT_WITHIN_LIMIT_GAS_AMOUNTS,
+            }]),
+        "leader_election_tie" => config
+            .with_initial_validator_count(NonZeroUsize::new(7).unwrap())
+            .with_network_tests(vec![&LeaderElectionTieTest {
+                run_length_secs: 600,
+            }])
+            .with_genesis_helm_config_fn(Arc::new(|helm_values| {
+                helm_values["genesis"]["validator"]["num_validators_with_larger_stake"] = 0.into();
+            })),
+        "stake_concentration" => config
+            .with_initial_validator_count(NonZeroUsize::new(5).unwrap())
+            .with_network_tests(vec![&StakeConcentrationTest {
+                target_concentration: 0.5,
+            }]),
+        "backward_time_jump" => config
+            .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+            .with_network_tests(vec![&BackwardTimeJumpTest {
+                validator_index: 1,
+                jump_secs: 60,
+            }]),
+        "connection_limit" => config
+            .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+            .with_network_tests(vec![&ConnectionLimitTest {
+                num_connections: 150,
+            }]),
+        "split_brain_merge" => config
+            .with_initial_validator_count(NonZeroUsize::new(6).unwrap())
+            .with_network_tests(vec![&SplitBrainMergeTest {
+                partition_size: 3,
+                partition_duration_secs: 60,
+            }])
+            .with_genesis_helm_config_fn(Arc::new(|helm_values| {
+                helm_values["genesis"]["validator"]["num_validators_with_larger_stake"] = 0.into();
+            })),
         "network_partition" => config
             .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
             .with_network_tests(vec![&NetworkPartitionTest])
@@ -461,6 +532,29 @@ fn single_test_suite(test_name: &str) -> Result<ForgeConfig<'static>> {
                 Some(Duration::from_secs(240)),
                 None,
             )),
+        "validator_partition" => config
+            .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
+            .with_initial_fullnode_count(1)
+            .with_network_tests(vec![&ValidatorPartitionTest {
+                minority_size: 3,
+                partition_duration_secs: 60,
+            }])
+            .with_genesis_helm_config_fn(Arc::new(|helm_values| {
+                helm_values["genesis"]["validator"]["num_validators_with_larger_stake"] = 0.into();
+            }))
+            .with_success_criteria(SuccessCriteria::new(
+                3000,
+                10000,
+                true,
+                Some(Duration::from_secs(240)),
+                None,
+            )),
+        "consensus_stall" => config
+            .with_initial_validator_count(NonZeroUsize::new(7).unwrap())
+            .with_network_tests(vec![&ConsensusStallTest {
+                stop_count: None,
+                stall_duration_secs: 60,
+            }]),
         "network_latency" => config
             .with_network_tests(vec![&NetworkLatencyTest])
             .with_success_criteria(SuccessCriteria::new(4000, 10000, true, None, None)),
@@ -495,6 +589,20 @@ fn single_test_suite(test_name: &str) -> Result<ForgeConfig<'static>> {
             .with_genesis_helm_config_fn(Arc::new(|helm_values| {
                 helm_values["chain"]["epoch_duration_secs"] = 120.into();
             })),
+        "rolling_restart_test" => config
+            .with_initial_validator_count(NonZeroUsize::new(7).unwrap())
+            .with_initial_fullnode_count(1)
+            .with_network_tests(vec![&RollingRestartTest {
+                dwell_secs: 30,
+                node_timeout_secs: 300,
+            }])
+            .with_success_criteria(SuccessCriteria::new(
+                3000,
+                50000,
+                false,
+                Some(Duration::from_secs(600)),
+                None,
+            )),
         "fullnode_reboot_stress_test" => config
             .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
             .with_initial_fullnode_count(10)
@@ -955,15 +1063,27 @@ impl NetworkTest for RestartValidator {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
         let runtime = Runtime::new()?;
         runtime.block_on(async {
-            let node = ctx.swarm().validators_mut().next().unwrap();
-            node.health_check().await.expect("node health check failed");
-            node.stop().await.unwrap();
-            println!("Restarting node {}", node.peer_id());
-            node.start().await.unwrap();
+            let node = ctx
+                .swarm()
+                .validators_mut()
+                .next()
+                .ok_or_else(|| format_err!("Swarm has no validators to restart"))?;
+            let peer_id = node.peer_id();
+            node.health_check()
+                .await
+                .with_context(|| format!("Node {} is unhealthy before restarting", peer_id))?;
+            node.stop()
+                .await
+                .with_context(|| format!("Failed to stop node {}", peer_id))?;
+            println!("Restarting node {}", peer_id);
+            node.start()
+                .await
+                .with_context(|| format!("Failed to start node {}", peer_id))?;
             tokio::time::sleep(Duration::from_secs(1)).await;
-            node.health_check().await.expect("node health check failed");
-        });
-        Ok(())
+            node.health_check()
+                .await
+                .with_context(|| format!("Node {} is unhealthy after restarting", peer_id))
+        })
     }
 }
 
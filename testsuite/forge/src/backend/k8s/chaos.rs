@@ -7,7 +7,7 @@ use tempfile::TempDir;
 
 use crate::{
     dump_string_to_file, Result, SwarmChaos, SwarmNetworkBandwidth, SwarmNetworkDelay,
-    SwarmNetworkLoss, SwarmNetworkPartition, KUBECTL_BIN,
+    SwarmNetworkLoss, SwarmNetworkPartition, SwarmSplitBrain, SwarmTimeOffset, KUBECTL_BIN,
 };
 
 macro_rules! DELAY_NETWORK_CHAOS_TEMPLATE {
@@ -32,6 +32,18 @@ macro_rules! NETWORK_LOSS_CHAOS_TEMPLATE {
     };
 }
 
+macro_rules! TIME_OFFSET_CHAOS_TEMPLATE {
+    () => {
+        "chaos/time_offset.yaml"
+    };
+}
+
+macro_rules! SPLIT_BRAIN_CHAOS_TEMPLATE {
+    () => {
+        "chaos/split_brain.yaml"
+    };
+}
+
 /// Injects the SwarmChaos into the specified namespace
 pub fn inject_swarm_chaos(kube_namespace: &str, chaos: &SwarmChaos) -> Result<()> {
     let template = create_chaos_template(kube_namespace, chaos)?;
@@ -93,12 +105,31 @@ fn create_network_loss_template(
     )
 }
 
+fn create_time_offset_template(kube_namespace: &str, time_offset: &SwarmTimeOffset) -> String {
+    format!(
+        include_str!(TIME_OFFSET_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        validator_name = time_offset.validator_name,
+        offset_ms = time_offset.offset_ms,
+    )
+}
+
+fn create_split_brain_template(kube_namespace: &str, split_brain: &SwarmSplitBrain) -> String {
+    format!(
+        include_str!(SPLIT_BRAIN_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        validator_names = split_brain.validator_names.join(", "),
+    )
+}
+
 fn create_chaos_template(kube_namespace: &str, chaos: &SwarmChaos) -> Result<String> {
     let template = match chaos {
         SwarmChaos::Delay(c) => create_network_delay_template(kube_namespace, c),
         SwarmChaos::Partition(c) => create_network_partition_template(kube_namespace, c),
         SwarmChaos::Bandwidth(c) => create_network_bandwidth_template(kube_namespace, c),
         SwarmChaos::Loss(c) => create_network_loss_template(kube_namespace, c),
+        SwarmChaos::TimeOffset(c) => create_time_offset_template(kube_namespace, c),
+        SwarmChaos::SplitBrain(c) => create_split_brain_template(kube_namespace, c),
     };
     Ok(template)
 }
@@ -2,12 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    get_free_port, scale_stateful_set_replicas, FullNode, HealthCheckError, Node, NodeExt, Result,
-    Validator, Version, KUBECTL_BIN, LOCALHOST, NODE_METRIC_PORT, REST_API_HAPROXY_SERVICE_PORT,
-    REST_API_SERVICE_PORT,
+    get_free_port, scale_stateful_set_replicas, FullNode, HealthCheckError, Node, NodeExt,
+    NodeIdentity, Result, Validator, Version, KUBECTL_BIN, LOCALHOST, NODE_METRIC_PORT,
+    REST_API_HAPROXY_SERVICE_PORT, REST_API_SERVICE_PORT,
 };
 use anyhow::{anyhow, format_err};
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{IdentityBlob, NodeConfig};
 use aptos_logger::info;
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::types::PeerId;
@@ -25,6 +25,8 @@ use std::{
 };
 
 const APTOS_DATA_DIR: &str = "/opt/aptos/data";
+/// Where the validator's identity is mounted from its genesis secret, as in the aptos-node chart
+const VALIDATOR_IDENTITY_PATH: &str = "/opt/aptos/genesis/validator-identity.yaml";
 
 pub struct K8sNode {
     pub(crate) name: String,
@@ -248,6 +250,31 @@ impl Node for K8sNode {
             })
     }
 
+    async fn get_identity(&self) -> Result<NodeIdentity> {
+        // The node's config isn't available here, so the identity is read from the pod
+        let output = Command::new(KUBECTL_BIN)
+            .args(&[
+                "-n",
+                self.namespace(),
+                "exec",
+                &format!("sts/{}", self.stateful_set_name()),
+                "--",
+                "cat",
+                VALIDATOR_IDENTITY_PATH,
+            ])
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to read the identity of node {}:{}: {}",
+                self.name(),
+                self.peer_id(),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        let identity: IdentityBlob = serde_yaml::from_slice(&output.stdout)?;
+        Ok(NodeIdentity::from(&identity))
+    }
+
     // TODO: verify this still works
     fn inspection_service_endpoint(&self) -> Url {
         Url::parse(&format!(
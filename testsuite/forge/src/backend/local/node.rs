@@ -1,9 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{FullNode, HealthCheckError, LocalVersion, Node, NodeExt, Validator, Version};
+use crate::{
+    FullNode, HealthCheckError, LocalVersion, Node, NodeExt, NodeIdentity, StorageSnapshot,
+    Validator, Version,
+};
 use anyhow::{anyhow, ensure, Context, Result};
-use aptos_config::{config::NodeConfig, keys::ConfigKey};
+use aptos_config::{
+    config::{IdentityBlob, InitialSafetyRulesConfig, NodeConfig},
+    keys::ConfigKey,
+};
 use aptos_logger::{debug, info};
 use aptos_sdk::{
     crypto::ed25519::Ed25519PrivateKey,
@@ -14,7 +20,7 @@ use state_sync_driver::metadata_storage::STATE_SYNC_DB_NAME;
 use std::{
     env,
     fs::{self, OpenOptions},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Child, Command},
     str::FromStr,
 };
@@ -177,6 +183,20 @@ impl LocalNode {
         self.start()
     }
 
+    /// Paths of the databases, and of a validator's secure storage, which make up its storage
+    fn storage_paths(&self) -> Vec<PathBuf> {
+        let storage_dir = self.config.storage.dir();
+        let mut paths = vec![
+            storage_dir.join(LEDGER_DB_NAME),
+            storage_dir.join(STATE_MERKLE_DB_NAME),
+            storage_dir.join(STATE_SYNC_DB_NAME),
+        ];
+        if self.config.base.role.is_validator() {
+            paths.push(self.config.base.data_dir.join("secure_storage.json"));
+        }
+        paths
+    }
+
     pub fn get_log_contents(&self) -> Result<String> {
         fs::read_to_string(self.log_path()).map_err(Into::into)
     }
@@ -310,10 +330,93 @@ impl Node for LocalNode {
         Ok(())
     }
 
+    async fn snapshot_storage(&mut self) -> Result<StorageSnapshot> {
+        // The databases are only consistent on disk once the node stops writing to them
+        let was_running = self.process.is_some();
+        self.stop();
+        let directory = tempfile::tempdir()?;
+        let snapshot = StorageSnapshot {
+            node_name: self.name.clone(),
+            paths: self.storage_paths(),
+            directory,
+        };
+        for path in &snapshot.paths {
+            ensure!(
+                path.exists(),
+                "Storage {:?} of node {} doesn't exist",
+                path,
+                self.name
+            );
+            copy_recursively(path, &snapshot.copy_of(path))
+                .with_context(|| format!("Failed to snapshot {:?}", path))?;
+        }
+        debug!(
+            "Snapshotted storage of node {} into {:?}",
+            self.name, snapshot.directory
+        );
+
+        if was_running {
+            self.start()?;
+        }
+        Ok(snapshot)
+    }
+
+    async fn restore_storage(&mut self, snapshot: &StorageSnapshot) -> Result<()> {
+        ensure!(
+            snapshot.node_name == self.name,
+            "Snapshot of node {} can't restore node {}",
+            snapshot.node_name,
+            self.name
+        );
+        let was_running = self.process.is_some();
+        self.stop();
+        for path in &snapshot.paths {
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else if path.exists() {
+                fs::remove_file(path)?;
+            }
+            copy_recursively(&snapshot.copy_of(path), path)
+                .with_context(|| format!("Failed to restore {:?}", path))?;
+        }
+        debug!(
+            "Restored storage of node {} from {:?}",
+            self.name, snapshot.directory
+        );
+
+        if was_running {
+            self.start()?;
+        }
+        Ok(())
+    }
+
     async fn health_check(&mut self) -> Result<(), HealthCheckError> {
         self.health_check().await
     }
 
+    async fn get_identity(&self) -> Result<NodeIdentity> {
+        let identity_blob_path = match &self
+            .config
+            .consensus
+            .safety_rules
+            .initial_safety_rules_config
+        {
+            InitialSafetyRulesConfig::FromFile {
+                identity_blob_path, ..
+            } => identity_blob_path,
+            InitialSafetyRulesConfig::None => {
+                return Err(anyhow!(
+                    "Node {}:{} has no identity file",
+                    self.name,
+                    self.peer_id
+                ))
+            }
+        };
+        let identity: IdentityBlob =
+            serde_yaml::from_str(&fs::read_to_string(identity_blob_path)?)?;
+        Ok(NodeIdentity::from(&identity))
+    }
+
     fn counter(&self, _counter: &str, _port: u64) -> Result<f64> {
         todo!()
     }
@@ -322,7 +425,28 @@ impl Node for LocalNode {
     fn expose_metric(&self) -> Result<u64> {
         Ok(0)
     }
+
+    async fn recent_logs(&self, max_lines: usize) -> Result<String> {
+        let logs = self.get_log_contents()?;
+        let lines: Vec<&str> = logs.lines().collect();
+        let start = lines.len().saturating_sub(max_lines);
+        Ok(lines[start..].join("\n"))
+    }
 }
 
 impl Validator for LocalNode {}
 impl FullNode for LocalNode {}
+
+/// Copies a file, or a directory along with everything in it
+fn copy_recursively(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
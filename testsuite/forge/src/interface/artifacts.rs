@@ -0,0 +1,143 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{NetworkContext, NetworkTest, NodeExt, Result, ShouldFail, Test, Validator};
+use aptos_logger::{info, warn};
+use std::{
+    collections::BTreeMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Runtime;
+
+/// Lines of each validator's logs kept in the artifacts
+const MAX_LOG_LINES: usize = 2_000;
+/// Prefix of the resource metrics exported by every node
+const RESOURCE_METRICS_PREFIX: &str = "node_";
+
+/// Runs a network test, and if it fails or panics, captures the recent logs, consensus state and
+/// resource metrics of every validator into a timestamped directory under `artifacts_dir`, before
+/// propagating the failure.
+pub struct CaptureArtifactsOnFailure<T> {
+    test: T,
+    artifacts_dir: PathBuf,
+}
+
+impl<T: NetworkTest> CaptureArtifactsOnFailure<T> {
+    pub fn new(test: T, artifacts_dir: PathBuf) -> Self {
+        Self {
+            test,
+            artifacts_dir,
+        }
+    }
+}
+
+impl<T: NetworkTest> Test for CaptureArtifactsOnFailure<T> {
+    fn name(&self) -> &'static str {
+        self.test.name()
+    }
+
+    fn ignored(&self) -> bool {
+        self.test.ignored()
+    }
+
+    fn should_fail(&self) -> ShouldFail {
+        self.test.should_fail()
+    }
+}
+
+impl<T: NetworkTest> NetworkTest for CaptureArtifactsOnFailure<T> {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| self.test.run(ctx)));
+        if let Ok(Ok(())) = result {
+            return Ok(());
+        }
+
+        match capture_artifacts(ctx, self.name(), &self.artifacts_dir) {
+            Ok(dir) => {
+                let msg = format!(
+                    "{} failed, artifacts captured in {}",
+                    self.name(),
+                    dir.display()
+                );
+                info!("{}", msg);
+                ctx.report.report_text(msg);
+            }
+            Err(error) => warn!("Failed to capture artifacts of {}: {}", self.name(), error),
+        }
+        match result {
+            Ok(result) => result,
+            Err(panic) => panic::resume_unwind(panic),
+        }
+    }
+}
+
+/// Captures the recent logs, consensus state and resource metrics of every validator into a new
+/// timestamped directory under `artifacts_dir`, and returns that directory.
+///
+/// Artifacts which can't be read, e.g. because the validator is down, are replaced by the error,
+/// so that whatever is available is still captured.
+pub fn capture_artifacts(
+    ctx: &mut NetworkContext,
+    test_name: &str,
+    artifacts_dir: &Path,
+) -> Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let test_name: String = test_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let dir = artifacts_dir.join(format!("{}-{}", test_name, timestamp));
+
+    let runtime = Runtime::new()?;
+    for validator in ctx.swarm().validators() {
+        let validator_dir = dir.join(validator.name());
+        fs::create_dir_all(&validator_dir)?;
+        write_artifact(
+            &validator_dir.join("logs.txt"),
+            runtime.block_on(validator.recent_logs(MAX_LOG_LINES)),
+        )?;
+        write_artifact(
+            &validator_dir.join("consensus.txt"),
+            Ok(runtime.block_on(consensus_snapshot(validator))),
+        )?;
+        write_artifact(
+            &validator_dir.join("resource_metrics.txt"),
+            runtime.block_on(resource_metrics(validator)),
+        )?;
+    }
+    Ok(dir)
+}
+
+fn write_artifact(path: &Path, contents: Result<String>) -> Result<()> {
+    let contents = contents.unwrap_or_else(|error| format!("Failed to capture: {:?}\n", error));
+    Ok(fs::write(path, contents)?)
+}
+
+async fn consensus_snapshot(validator: &dyn Validator) -> String {
+    let safety_rules = match validator.get_safety_rules_state().await {
+        Ok(state) => format!("{:?}", state),
+        Err(error) => format!("unavailable: {}", error),
+    };
+    let ledger = match validator.rest_client().get_ledger_information().await {
+        Ok(state) => format!("{:?}", state.into_inner()),
+        Err(error) => format!("unavailable: {}", error),
+    };
+    format!("safety_rules: {}\nledger: {}\n", safety_rules, ledger)
+}
+
+async fn resource_metrics(validator: &dyn Validator) -> Result<String> {
+    let metrics: BTreeMap<_, _> = validator
+        .inspection_client()
+        .get_node_metrics()
+        .await?
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(RESOURCE_METRICS_PREFIX))
+        .collect();
+    Ok(metrics
+        .iter()
+        .map(|(name, value)| format!("{} {:?}\n", name, value))
+        .collect())
+}
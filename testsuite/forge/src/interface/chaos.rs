@@ -7,6 +7,8 @@ pub enum SwarmChaos {
     Partition(SwarmNetworkPartition),
     Bandwidth(SwarmNetworkBandwidth),
     Loss(SwarmNetworkLoss),
+    TimeOffset(SwarmTimeOffset),
+    SplitBrain(SwarmSplitBrain),
 }
 
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
@@ -39,6 +41,20 @@ pub struct SwarmNetworkLoss {
     pub correlation_percentage: u64,
 }
 
+/// Shifts the system clock of a single validator
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmTimeOffset {
+    pub validator_name: String,
+    /// Negative offsets move the clock backward
+    pub offset_ms: i64,
+}
+
+/// Cuts a group of validators off from every other validator, in both directions
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmSplitBrain {
+    pub validator_names: Vec<String>,
+}
+
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub struct NodeNetworkDelay {
     pub latency_ms: u64,
@@ -3,6 +3,8 @@
 
 mod admin;
 pub use admin::*;
+mod artifacts;
+pub use artifacts::*;
 mod aptos;
 pub use aptos::*;
 mod network;
@@ -1,12 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use super::Test;
 use crate::success_criteria::SuccessCriteria;
-use crate::{CoreContext, Result, Swarm, TestReport};
-use tokio::runtime::Runtime;
+use crate::{ConsensusHealthSeries, CoreContext, Result, Swarm, SwarmExt, TestReport};
+use aptos_sdk::types::PeerId;
+use tokio::runtime::{Handle, Runtime};
 use transaction_emitter_lib::{EmitJobRequest, TxnStats};
 
 /// The testing interface which defines a test written with full control over an existing network.
@@ -55,6 +56,34 @@ impl<'t> NetworkContext<'t> {
         &mut self.core
     }
 
+    /// Handle to the runtime the test runs on, which tests block on futures with rather than
+    /// starting runtimes of their own.  It's owned, so it can be used while the swarm is borrowed.
+    pub fn handle(&self) -> Handle {
+        self.runtime.handle().clone()
+    }
+
+    /// Waits until at least `fraction` of the validators are healthy, for tests which
+    /// intentionally keep some validators down.  Returns how many validators were healthy.
+    pub fn wait_for_quorum_healthy(&mut self, fraction: f64, timeout: Duration) -> Result<usize> {
+        let deadline = Instant::now() + timeout;
+        self.runtime
+            .block_on(self.swarm.wait_for_quorum_healthy(fraction, deadline))
+    }
+
+    /// Polls the consensus health of `validators` every `interval` for `duration`, returning
+    /// every sample so that tests can check for liveness, or for twins proposing in the same round
+    pub fn collect_consensus_health(
+        &mut self,
+        validators: &[PeerId],
+        duration: Duration,
+        interval: Duration,
+    ) -> Result<ConsensusHealthSeries> {
+        self.runtime.block_on(
+            self.swarm
+                .collect_consensus_health(validators, duration, interval),
+        )
+    }
+
     pub fn check_for_success(
         &mut self,
         stats: &TxnStats,
@@ -3,14 +3,23 @@
 
 use crate::{Result, Version};
 use anyhow::anyhow;
-use aptos_config::{config::NodeConfig, network_id::NetworkId};
+use aptos_config::{
+    config::{IdentityBlob, NodeConfig},
+    network_id::NetworkId,
+};
 use aptos_rest_client::Client as RestClient;
-use aptos_sdk::types::PeerId;
+use aptos_sdk::{
+    crypto::{bls12381, x25519, PrivateKey},
+    types::{account_address::AccountAddress, PeerId},
+};
+use futures::future::BoxFuture;
 use inspection_service::inspection_client::InspectionClient;
 use std::{
     collections::HashMap,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
+use tempfile::TempDir;
 use url::Url;
 
 #[derive(Debug)]
@@ -28,6 +37,166 @@ impl std::fmt::Display for HealthCheckError {
 
 impl std::error::Error for HealthCheckError {}
 
+/// How long a node may take to become healthy, and how often its health is checked meanwhile
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HealthWaitConfig {
+    /// How long the node has to become healthy
+    pub timeout: Duration,
+    /// How long to wait after a failed health check before checking again
+    pub poll_interval: Duration,
+    /// How many health checks may fail in a row before giving up before the timeout, or `None`
+    /// to keep checking until the timeout
+    pub max_consecutive_failures: Option<usize>,
+}
+
+impl Default for HealthWaitConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(300),
+            poll_interval: Duration::from_millis(500),
+            max_consecutive_failures: None,
+        }
+    }
+}
+
+impl HealthWaitConfig {
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            ..Self::default()
+        }
+    }
+
+    /// Polls `health_check` on `node` until it passes.  Fails straight away if the node isn't
+    /// running, and otherwise once the timeout or the maximum consecutive failures is reached,
+    /// with the last health check error.
+    pub async fn wait_until_healthy<T, F>(
+        &self,
+        name: &str,
+        node: &mut T,
+        mut health_check: F,
+    ) -> Result<()>
+    where
+        T: ?Sized + Send,
+        F: for<'a> FnMut(&'a mut T) -> BoxFuture<'a, Result<(), HealthCheckError>> + Send,
+    {
+        let deadline = Instant::now() + self.timeout;
+        let mut failures = 0;
+        let mut last_error = None;
+        while Instant::now() < deadline {
+            match health_check(node).await {
+                Ok(()) => return Ok(()),
+                Err(HealthCheckError::NotRunning(error)) => {
+                    return Err(anyhow!("Node {} not running! Error: {:?}", name, error))
+                }
+                Err(error) => {
+                    failures += 1;
+                    if Some(failures) == self.max_consecutive_failures {
+                        return Err(anyhow!(
+                            "Node {} failed {} consecutive health checks, the last with: {}",
+                            name,
+                            failures,
+                            error
+                        ));
+                    }
+                    last_error = Some(error);
+                }
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        let last_error = last_error
+            .map(|error| format!(", the last with: {}", error))
+            .unwrap_or_default();
+        Err(anyhow!(
+            "Timed out after {:?} waiting for Node {} to be healthy, {} health checks failed{}",
+            self.timeout,
+            name,
+            failures,
+            last_error
+        ))
+    }
+}
+
+/// The persisted safety-rules state of a validator, as reported through its metrics
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SafetyRulesState {
+    pub epoch: u64,
+    pub last_voted_round: u64,
+    pub preferred_round: u64,
+}
+
+impl SafetyRulesState {
+    /// Returns true if a validator in this state can take over voting from one in `previous`
+    /// without equivocating, i.e. it will never vote again in a round that was already voted in.
+    pub fn is_successor_of(&self, previous: &SafetyRulesState) -> bool {
+        self.epoch > previous.epoch
+            || (self.epoch == previous.epoch
+                && self.last_voted_round >= previous.last_voted_round
+                && self.preferred_round >= previous.preferred_round)
+    }
+}
+
+const SAFETY_RULES_STATE_METRIC: &str = "aptos_safety_rules_state";
+
+/// The progress of a validator's consensus, as reported through its metrics
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ConsensusHealth {
+    pub current_round: u64,
+    pub last_committed_version: u64,
+    /// Proposals the validator sent since it last restarted
+    pub proposals: u64,
+}
+
+/// A copy of a node's storage taken by `Node::snapshot_storage`, which the node can be put back
+/// to with `Node::restore_storage` after its storage was cleared
+#[derive(Debug)]
+pub struct StorageSnapshot {
+    /// Name of the node the snapshot was taken of, the only node it can restore
+    pub node_name: String,
+    /// The node's databases and secure storage, each of which is copied into the snapshot
+    pub paths: Vec<PathBuf>,
+    /// Directory holding the copies, which is removed when the snapshot is dropped
+    pub directory: TempDir,
+}
+
+impl StorageSnapshot {
+    /// Where the copy of one of the node's storage `paths` is kept
+    pub fn copy_of(&self, path: &Path) -> PathBuf {
+        self.directory
+            .path()
+            .join(path.file_name().unwrap_or_else(|| path.as_os_str()))
+    }
+}
+
+/// The public keys of the identity a validator runs with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeIdentity {
+    pub account_address: Option<AccountAddress>,
+    pub consensus_public_key: Option<bls12381::PublicKey>,
+    pub network_public_key: x25519::PublicKey,
+}
+
+impl From<&IdentityBlob> for NodeIdentity {
+    fn from(identity: &IdentityBlob) -> Self {
+        Self {
+            account_address: identity.account_address,
+            consensus_public_key: identity
+                .consensus_private_key
+                .as_ref()
+                .map(PrivateKey::public_key),
+            network_public_key: identity.network_private_key.public_key(),
+        }
+    }
+}
+
+const CONSENSUS_HEALTH_METRICS: [&str; 3] = [
+    "aptos_consensus_current_round",
+    "aptos_consensus_last_committed_version",
+    "aptos_consensus_proposals_count",
+];
+
 /// Trait used to represent a running Validator or FullNode
 #[async_trait::async_trait]
 pub trait Node: Send + Sync {
@@ -63,12 +232,48 @@ pub trait Node: Send + Sync {
     /// Clears this Node's Storage. This stops the node as well
     async fn clear_storage(&mut self) -> Result<()>;
 
+    /// Copies this Node's databases and secure storage, so that they can be put back with
+    /// `restore_storage` after the storage is cleared.  The node is stopped while its storage is
+    /// copied, and started again if it was running.
+    async fn snapshot_storage(&mut self) -> Result<StorageSnapshot> {
+        Err(anyhow!(
+            "Storage of node {}:{} can't be snapshotted from this backend",
+            self.name(),
+            self.peer_id()
+        ))
+    }
+
+    /// Replaces this Node's storage with a snapshot taken of it by `snapshot_storage`.  The node
+    /// is stopped while its storage is replaced, and started again if it was running.
+    ///
+    /// A validator restored to before votes it has since sent could vote again in those rounds,
+    /// so validators should stay stopped from the snapshot until they're restored.
+    async fn restore_storage(&mut self, _snapshot: &StorageSnapshot) -> Result<()> {
+        Err(anyhow!(
+            "Storage of node {}:{} can't be restored from this backend",
+            self.name(),
+            self.peer_id()
+        ))
+    }
+
     /// Performs a Health Check on the Node
     async fn health_check(&mut self) -> Result<(), HealthCheckError>;
 
+    /// Read the identity this Node's safety rules were initialized with
+    async fn get_identity(&self) -> Result<NodeIdentity>;
+
     fn counter(&self, counter: &str, port: u64) -> Result<f64>;
 
     fn expose_metric(&self) -> Result<u64>;
+
+    /// Return the last `max_lines` lines of this Node's logs, if the backend can read them
+    async fn recent_logs(&self, _max_lines: usize) -> Result<String> {
+        Err(anyhow!(
+            "Logs of node {}:{} are not available from this backend",
+            self.name(),
+            self.peer_id()
+        ))
+    }
 }
 
 /// Trait used to represent a running Validator
@@ -207,32 +412,134 @@ pub trait NodeExt: Node {
             .await
     }
 
+    /// Query the safety-rules state (epoch, last voted round, preferred round) of this Node
+    async fn get_safety_rules_state(&self) -> Result<SafetyRulesState> {
+        let mut values = [0u64; 3];
+        for (value, field) in
+            values
+                .iter_mut()
+                .zip(["epoch", "last_voted_round", "preferred_round"])
+        {
+            let mut fields = HashMap::new();
+            fields.insert("field".to_string(), field.to_string());
+            let metric = self
+                .get_metric_with_fields_i64(SAFETY_RULES_STATE_METRIC, fields)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Node {}:{} does not report safety rules field {}",
+                        self.name(),
+                        self.peer_id(),
+                        field
+                    )
+                })?;
+            *value = u64::try_from(metric)?;
+        }
+
+        Ok(SafetyRulesState {
+            epoch: values[0],
+            last_voted_round: values[1],
+            preferred_round: values[2],
+        })
+    }
+
+    /// Query the consensus round, last committed version and proposal count of this Node
+    async fn get_consensus_health(&self) -> Result<ConsensusHealth> {
+        let mut values = [0u64; 3];
+        for (value, metric_name) in values.iter_mut().zip(CONSENSUS_HEALTH_METRICS) {
+            // Metrics are only reported once they're first set, e.g. before the first proposal
+            let metric = self.get_metric_i64(metric_name).await?.unwrap_or(0);
+            *value = u64::try_from(metric)?;
+        }
+
+        Ok(ConsensusHealth {
+            current_round: values[0],
+            last_committed_version: values[1],
+            proposals: values[2],
+        })
+    }
+
     async fn liveness_check(&self, seconds: u64) -> Result<()> {
         Ok(self.rest_client().health_check(seconds).await?)
     }
 
     async fn wait_until_healthy(&mut self, deadline: Instant) -> Result<()> {
-        while Instant::now() < deadline {
-            match self.health_check().await {
-                Ok(()) => return Ok(()),
-                Err(HealthCheckError::NotRunning(error)) => {
-                    return Err(anyhow::anyhow!(
-                        "Node {}:{} not running! Error: {:?}",
-                        self.name(),
-                        self.peer_id(),
-                        error,
-                    ))
-                }
-                Err(_) => {} // For other errors we'll retry
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.wait_until_healthy_with(HealthWaitConfig::with_timeout(timeout))
+            .await
+    }
+
+    /// Waits until this Node is healthy, checking its health as often and for as long as `config`
+    async fn wait_until_healthy_with(&mut self, config: HealthWaitConfig) -> Result<()> {
+        let name = format!("{}:{}", self.name(), self.peer_id());
+        config
+            .wait_until_healthy(&name, self, |node| node.health_check())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A node which fails its first health checks
+    struct FlakyNode {
+        failures: usize,
+    }
+
+    fn health_check(node: &mut FlakyNode) -> BoxFuture<'_, Result<(), HealthCheckError>> {
+        Box::pin(async move {
+            if node.failures == 0 {
+                Ok(())
+            } else {
+                node.failures -= 1;
+                Err(HealthCheckError::Failure(anyhow!("connection refused")))
             }
+        })
+    }
 
-            tokio::time::sleep(Duration::from_millis(500)).await;
+    fn config(timeout_ms: u64, max_consecutive_failures: Option<usize>) -> HealthWaitConfig {
+        HealthWaitConfig {
+            timeout: Duration::from_millis(timeout_ms),
+            poll_interval: Duration::from_millis(10),
+            max_consecutive_failures,
         }
+    }
 
-        Err(anyhow::anyhow!(
-            "Timed out waiting for Node {}:{} to be healthy",
-            self.name(),
-            self.peer_id()
-        ))
+    #[tokio::test]
+    async fn test_wait_until_healthy() {
+        let mut node = FlakyNode { failures: 2 };
+        config(10_000, None)
+            .wait_until_healthy("flaky", &mut node, health_check)
+            .await
+            .unwrap();
+
+        // A timeout too short for the node to recover names the node and its last failure
+        let mut node = FlakyNode { failures: 1_000 };
+        let error = config(5, None)
+            .wait_until_healthy("flaky", &mut node, health_check)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("Timed out after 5ms waiting for Node flaky to be healthy"),
+            "{}",
+            error
+        );
+        assert!(error.contains("connection refused"), "{}", error);
+
+        // Consecutive failures give up before the timeout
+        let mut node = FlakyNode { failures: 1_000 };
+        let error = config(10_000, Some(3))
+            .wait_until_healthy("flaky", &mut node, health_check)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("Node flaky failed 3 consecutive health checks"),
+            "{}",
+            error
+        );
+        assert_eq!(node.failures, 997);
     }
 }
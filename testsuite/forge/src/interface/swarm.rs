@@ -3,16 +3,20 @@
 
 use crate::interface::system_metrics::SystemMetricsThreshold;
 use crate::{
-    AptosPublicInfo, ChainInfo, FullNode, NodeExt, Result, SwarmChaos, Validator, Version,
+    AptosPublicInfo, ChainInfo, ConsensusHealth, FullNode, HealthCheckError, NodeExt, NodeIdentity,
+    Result, SwarmChaos, Validator, Version,
 };
 use anyhow::{anyhow, bail};
 use aptos_config::config::NodeConfig;
 use aptos_logger::info;
-use aptos_rest_client::Client as RestClient;
-use aptos_sdk::types::PeerId;
-use futures::future::try_join_all;
+use aptos_rest_client::{aptos_api_types::HashValue, Client as RestClient};
+use aptos_sdk::{crypto::bls12381, types::PeerId};
+use futures::future::{join_all, try_join_all};
 use prometheus_http_query::response::PromqlResult;
-use std::time::{Duration, Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::{Duration, Instant},
+};
 use tokio::runtime::Runtime;
 
 /// Trait used to represent a running network comprised of Validators and FullNodes
@@ -135,6 +139,174 @@ pub trait SwarmExt: Swarm {
         Ok(())
     }
 
+    /// Waits until at least `fraction` of the validators are live, leaving the rest free to stay
+    /// down.  Returns how many validators were live.
+    async fn wait_for_quorum_healthy(&self, fraction: f64, deadline: Instant) -> Result<usize> {
+        if !(0.0..=1.0).contains(&fraction) {
+            bail!(
+                "Fraction of healthy validators must be within [0, 1]: {}",
+                fraction
+            );
+        }
+        let liveness_check_seconds = 10;
+        let validators = self.validators().collect::<Vec<_>>();
+        // Allow for floating point error, e.g. 2/3 of 3 validators must be 2 and not 3
+        let required = (validators.len() as f64 * fraction - 1e-9).ceil().max(0.0) as usize;
+
+        loop {
+            let healthy = join_all(
+                validators
+                    .iter()
+                    .map(|node| node.liveness_check(liveness_check_seconds)),
+            )
+            .await
+            .into_iter()
+            .filter(|result| result.is_ok())
+            .count();
+            if healthy >= required {
+                info!(
+                    "{} of {} validators healthy, {} required",
+                    healthy,
+                    validators.len(),
+                    required
+                );
+                return Ok(healthy);
+            }
+            if Instant::now() > deadline {
+                bail!(
+                    "Timed out waiting for {} of {} validators to be healthy, only {} are",
+                    required,
+                    validators.len(),
+                    healthy
+                );
+            }
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Waits until every one of `validators` is healthy, checking them all at once so the wait
+    /// doesn't grow with their number.  Fails as soon as one isn't running, or at `deadline`,
+    /// naming the validators which were still unhealthy.
+    async fn wait_until_all_healthy(
+        &mut self,
+        validators: &[PeerId],
+        deadline: Instant,
+    ) -> Result<()> {
+        for id in validators {
+            if self.validator(*id).is_none() {
+                bail!("Validator {} is not in the swarm", id);
+            }
+        }
+        let mut unhealthy = validators.to_vec();
+
+        loop {
+            let nodes = self
+                .validators_mut()
+                .filter(|node| unhealthy.contains(&node.peer_id()))
+                .collect::<Vec<_>>();
+            let results = join_all(nodes.into_iter().map(|node| async move {
+                let result = node.health_check().await;
+                (node.name().to_string(), node.peer_id(), result)
+            }))
+            .await;
+
+            let mut unhealthy_names = Vec::new();
+            unhealthy.clear();
+            for (name, peer_id, result) in results {
+                match result {
+                    Ok(()) => {}
+                    Err(HealthCheckError::NotRunning(error)) => {
+                        bail!("Node {}:{} not running! Error: {:?}", name, peer_id, error)
+                    }
+                    // For other errors we'll retry
+                    Err(_) => {
+                        unhealthy.push(peer_id);
+                        unhealthy_names.push(name);
+                    }
+                }
+            }
+            if unhealthy.is_empty() {
+                info!("All {} validators healthy", validators.len());
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                bail!(
+                    "Timed out waiting for validators to be healthy, {} of {} still aren't: {}",
+                    unhealthy.len(),
+                    validators.len(),
+                    unhealthy_names.join(", ")
+                );
+            }
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Polls the consensus health of `validators` every `interval` until `duration` has passed,
+    /// e.g. while load runs against them, returning every sample taken
+    async fn collect_consensus_health(
+        &self,
+        validators: &[PeerId],
+        duration: Duration,
+        interval: Duration,
+    ) -> Result<ConsensusHealthSeries> {
+        let validators = validators
+            .iter()
+            .map(|id| {
+                self.validator(*id)
+                    .ok_or_else(|| anyhow!("Validator {} is not in the swarm", id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let start = Instant::now();
+        let mut series = ConsensusHealthSeries::default();
+
+        loop {
+            let elapsed = start.elapsed();
+            let healths = try_join_all(
+                validators
+                    .iter()
+                    .map(|validator| validator.get_consensus_health()),
+            )
+            .await?;
+            for (validator, health) in validators.iter().zip(healths) {
+                series
+                    .samples
+                    .entry(validator.peer_id())
+                    .or_default()
+                    .push((elapsed, health));
+            }
+            if elapsed >= duration {
+                return Ok(series);
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Reads the identities of every validator concurrently, keyed by their peer id
+    async fn get_validator_identities(&self) -> Result<HashMap<PeerId, NodeIdentity>> {
+        let validators = self.validators().collect::<Vec<_>>();
+        let identities =
+            try_join_all(validators.iter().map(|validator| validator.get_identity())).await?;
+        Ok(validators
+            .iter()
+            .map(|validator| validator.peer_id())
+            .zip(identities)
+            .collect())
+    }
+
+    /// Fails if any two validators run with the same consensus key, as a validator and its twin
+    /// do, so that tests can check they start from a swarm without twins
+    async fn ensure_no_duplicate_identities(&self) -> Result<()> {
+        let duplicates = find_duplicate_identities(&self.get_validator_identities().await?);
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            bail!("Validators share consensus keys: {:?}", duplicates)
+        }
+    }
+
     /// Waits for the swarm to achieve connectivity
     async fn wait_for_connectivity(&self, deadline: Instant) -> Result<()> {
         let validators = self.validators().collect::<Vec<_>>();
@@ -230,6 +402,31 @@ pub trait SwarmExt: Swarm {
         Ok(())
     }
 
+    /// Reads the committed ledger of every node at `version`, keyed by node name, once they've
+    /// all caught up to it
+    async fn get_ledger_snapshots(
+        &self,
+        version: u64,
+        timeout: Duration,
+    ) -> Result<BTreeMap<String, LedgerSnapshot>> {
+        let clients = self.get_all_nodes_clients_with_names();
+        wait_for_all_nodes_to_catchup_to_version(&clients, version, timeout).await?;
+        let snapshots = try_join_all(clients.iter().map(|(name, client)| async move {
+            let block = client.get_block_by_version(version, false).await?;
+            let transaction = client.get_transaction_by_version(version).await?;
+            let snapshot = LedgerSnapshot {
+                block_hash: block.into_inner().block_hash,
+                accumulator_root_hash: transaction
+                    .into_inner()
+                    .transaction_info()?
+                    .accumulator_root_hash,
+            };
+            Ok::<_, anyhow::Error>((name.clone(), snapshot))
+        }))
+        .await?;
+        Ok(snapshots.into_iter().collect())
+    }
+
     /// Waits for all nodes to have caught up to the specified `verison`.
     async fn wait_for_all_nodes_to_catchup_to_version(
         &self,
@@ -354,3 +551,261 @@ pub async fn get_highest_synced_version(clients: &[(String, RestClient)]) -> Res
     }
     Ok(latest_version)
 }
+
+/// Validators running with the same consensus key
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DuplicateIdentity {
+    pub consensus_public_key: bls12381::PublicKey,
+    /// The validators sharing the key, in order
+    pub validators: Vec<PeerId>,
+}
+
+/// Finds the consensus keys which more than one of `identities` runs with, ordered by the
+/// validators sharing them.  Identities without a consensus key are ignored.
+pub fn find_duplicate_identities(
+    identities: &HashMap<PeerId, NodeIdentity>,
+) -> Vec<DuplicateIdentity> {
+    let mut validators_by_key: HashMap<_, Vec<_>> = HashMap::new();
+    for (validator, identity) in identities {
+        if let Some(key) = &identity.consensus_public_key {
+            validators_by_key.entry(key).or_default().push(*validator);
+        }
+    }
+    let mut duplicates: Vec<_> = validators_by_key
+        .into_iter()
+        .filter(|(_, validators)| validators.len() > 1)
+        .map(|(key, mut validators)| {
+            validators.sort();
+            DuplicateIdentity {
+                consensus_public_key: key.clone(),
+                validators,
+            }
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a.validators.cmp(&b.validators));
+    duplicates
+}
+
+/// A node's committed ledger at a version, read by `get_ledger_snapshots`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerSnapshot {
+    /// Hash of the block the version was committed in
+    pub block_hash: HashValue,
+    /// Root hash of the transaction accumulator at the version, covering the whole ledger up to it
+    pub accumulator_root_hash: HashValue,
+}
+
+/// Groups nodes by their committed ledger at a version, if they don't all agree on it
+///
+/// Each group is a branch of a fork, such as one committed by a validator and the other by its
+/// twin.  The nodes of each group are sorted, and the groups ordered by their first node.
+pub fn find_ledger_forks(snapshots: &BTreeMap<String, LedgerSnapshot>) -> Vec<Vec<String>> {
+    let mut branches: Vec<(&LedgerSnapshot, Vec<String>)> = Vec::new();
+    for (node, snapshot) in snapshots {
+        match branches.iter_mut().find(|(ledger, _)| *ledger == snapshot) {
+            Some((_, nodes)) => nodes.push(node.clone()),
+            None => branches.push((snapshot, vec![node.clone()])),
+        }
+    }
+    if branches.len() < 2 {
+        return vec![];
+    }
+    branches.into_iter().map(|(_, nodes)| nodes).collect()
+}
+
+/// Samples of the consensus health of validators, taken by `collect_consensus_health`
+#[derive(Clone, Debug, Default)]
+pub struct ConsensusHealthSeries {
+    /// The health of each validator in the order it was sampled, along with how long after
+    /// collection started it was sampled
+    pub samples: HashMap<PeerId, Vec<(Duration, ConsensusHealth)>>,
+}
+
+impl ConsensusHealthSeries {
+    /// Rounds a validator was in when it was seen to have sent a proposal since the sample before
+    pub fn proposal_rounds(&self, validator: PeerId) -> BTreeSet<u64> {
+        self.samples
+            .get(&validator)
+            .map(|samples| {
+                samples
+                    .windows(2)
+                    .filter(|pair| pair[1].1.proposals > pair[0].1.proposals)
+                    .map(|pair| pair[1].1.current_round)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Rounds in which both validators were seen to propose.  For a validator and its twin, which
+    /// share an identity, this is evidence they signed conflicting proposals.  Proposals are only
+    /// seen through periodic samples, so some can be missed.
+    pub fn common_proposal_rounds(&self, first: PeerId, second: PeerId) -> BTreeSet<u64> {
+        self.proposal_rounds(first)
+            .intersection(&self.proposal_rounds(second))
+            .copied()
+            .collect()
+    }
+
+    /// Whether every validator sampled committed new versions between its first and last sample
+    pub fn made_progress(&self) -> bool {
+        self.samples
+            .values()
+            .all(|samples| match (samples.first(), samples.last()) {
+                (Some((_, first)), Some((_, last))) => {
+                    last.last_committed_version > first.last_committed_version
+                }
+                _ => false,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::crypto::{x25519, PrivateKey, Uniform};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn health(current_round: u64, last_committed_version: u64, proposals: u64) -> ConsensusHealth {
+        ConsensusHealth {
+            current_round,
+            last_committed_version,
+            proposals,
+        }
+    }
+
+    fn samples(healths: Vec<ConsensusHealth>) -> Vec<(Duration, ConsensusHealth)> {
+        healths
+            .into_iter()
+            .enumerate()
+            .map(|(i, health)| (Duration::from_secs(i as u64), health))
+            .collect()
+    }
+
+    #[test]
+    fn test_consensus_health_series() {
+        let main = PeerId::random();
+        let twin = PeerId::random();
+        let mut series = ConsensusHealthSeries::default();
+        series.samples.insert(
+            main,
+            samples(vec![health(1, 10, 0), health(3, 20, 1), health(5, 30, 2)]),
+        );
+        // The twin restarted after its first proposal, resetting its proposal count
+        series.samples.insert(
+            twin,
+            samples(vec![health(1, 10, 1), health(3, 20, 0), health(5, 25, 1)]),
+        );
+
+        assert_eq!(series.proposal_rounds(main), BTreeSet::from([3, 5]));
+        assert_eq!(series.proposal_rounds(twin), BTreeSet::from([5]));
+        assert_eq!(
+            series.common_proposal_rounds(main, twin),
+            BTreeSet::from([5])
+        );
+        assert!(series.proposal_rounds(PeerId::random()).is_empty());
+        assert!(series.made_progress());
+
+        // A validator which didn't commit anything new stalled
+        series
+            .samples
+            .insert(twin, samples(vec![health(1, 10, 0), health(7, 10, 0)]));
+        assert!(!series.made_progress());
+    }
+
+    #[test]
+    fn test_find_ledger_forks() {
+        let snapshot = |block: u8, root: u8| LedgerSnapshot {
+            block_hash: aptos_sdk::crypto::HashValue::new([block; 32]).into(),
+            accumulator_root_hash: aptos_sdk::crypto::HashValue::new([root; 32]).into(),
+        };
+        let mut snapshots: BTreeMap<_, _> = (0..4)
+            .map(|i| (format!("validator-{}", i), snapshot(1, 1)))
+            .chain([("fullnode-0".to_string(), snapshot(1, 1))])
+            .collect();
+        assert!(find_ledger_forks(&snapshots).is_empty());
+        assert!(find_ledger_forks(&BTreeMap::new()).is_empty());
+
+        // A twin committing a different block at the version forks the ledger
+        snapshots.insert("validator-1-twin".to_string(), snapshot(2, 2));
+        snapshots.insert("validator-3".to_string(), snapshot(2, 2));
+        assert_eq!(
+            find_ledger_forks(&snapshots),
+            vec![
+                vec!["fullnode-0", "validator-0", "validator-1", "validator-2"],
+                vec!["validator-1-twin", "validator-3"],
+            ]
+        );
+
+        // As does one with the same block, but a different ledger before it
+        snapshots.insert("validator-1-twin".to_string(), snapshot(1, 3));
+        snapshots.insert("validator-3".to_string(), snapshot(1, 1));
+        assert_eq!(
+            find_ledger_forks(&snapshots),
+            vec![
+                vec![
+                    "fullnode-0",
+                    "validator-0",
+                    "validator-1",
+                    "validator-2",
+                    "validator-3"
+                ],
+                vec!["validator-1-twin"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_duplicate_identities() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let mut identity = || NodeIdentity {
+            account_address: Some(PeerId::random()),
+            consensus_public_key: Some(bls12381::PrivateKey::generate(&mut rng).public_key()),
+            network_public_key: x25519::PrivateKey::generate(&mut rng).public_key(),
+        };
+        let mut identities: HashMap<_, _> =
+            (0..4).map(|_| (PeerId::random(), identity())).collect();
+        // Validators without a consensus key don't share one
+        for _ in 0..2 {
+            identities.insert(
+                PeerId::random(),
+                NodeIdentity {
+                    consensus_public_key: None,
+                    ..identity()
+                },
+            );
+        }
+        assert!(find_duplicate_identities(&identities).is_empty());
+
+        // A twin runs with the identity of the validator it's copied from
+        let (main, main_identity) = identities
+            .iter()
+            .find(|(_, identity)| identity.consensus_public_key.is_some())
+            .map(|(peer_id, identity)| (*peer_id, identity.clone()))
+            .unwrap();
+        let twin = PeerId::random();
+        identities.insert(twin, main_identity.clone());
+        let mut twins = vec![main, twin];
+        twins.sort();
+        assert_eq!(
+            find_duplicate_identities(&identities),
+            vec![DuplicateIdentity {
+                consensus_public_key: main_identity.consensus_public_key.clone().unwrap(),
+                validators: twins,
+            }]
+        );
+
+        // Sharing only the consensus key is enough to be twins
+        let other_twin = PeerId::random();
+        identities.insert(
+            other_twin,
+            NodeIdentity {
+                account_address: Some(PeerId::random()),
+                ..main_identity
+            },
+        );
+        let duplicates = find_duplicate_identities(&identities);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].validators.len(), 3);
+        assert!(duplicates[0].validators.contains(&other_twin));
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::smoke_test_environment::SwarmBuilder;
+use anyhow::bail;
+use aptos_temppath::TempPath;
+use forge::{
+    success_criteria::SuccessCriteria, CaptureArtifactsOnFailure, CoreContext, EmitJobRequest,
+    NetworkContext, NetworkTest, Result, Swarm, Test, TestReport,
+};
+use rand::rngs::OsRng;
+use std::{fs, time::Duration};
+use tokio::runtime::Runtime;
+
+struct FailingTest;
+
+impl Test for FailingTest {
+    fn name(&self) -> &'static str {
+        "failing test"
+    }
+}
+
+impl NetworkTest for FailingTest {
+    fn run<'t>(&self, _ctx: &mut NetworkContext<'t>) -> Result<()> {
+        bail!("Forced failure")
+    }
+}
+
+#[test]
+fn test_artifacts_captured_on_failure() {
+    let runtime = Runtime::new().unwrap();
+    let mut swarm = runtime.block_on(SwarmBuilder::new_local(2).with_aptos().build());
+    let validator_names: Vec<String> = swarm.validators().map(|v| v.name().to_string()).collect();
+
+    let artifacts_dir = TempPath::new();
+    artifacts_dir.create_as_dir().unwrap();
+    let test = CaptureArtifactsOnFailure::new(FailingTest, artifacts_dir.path().to_path_buf());
+
+    let mut report = TestReport::new();
+    let mut ctx = NetworkContext::new(
+        CoreContext::from_rng(OsRng),
+        &mut swarm,
+        &mut report,
+        Duration::from_secs(1),
+        EmitJobRequest::default(),
+        SuccessCriteria::new(0, 0, false, None, None),
+    );
+    let error = test.run(&mut ctx).unwrap_err();
+    assert!(error.to_string().contains("Forced failure"));
+
+    // A single timestamped directory, with the artifacts of every validator
+    let dirs: Vec<_> = fs::read_dir(artifacts_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(dirs.len(), 1);
+    let dir_name = dirs[0].file_name().unwrap().to_str().unwrap();
+    assert!(dir_name.starts_with("failing-test-"), "{}", dir_name);
+
+    for name in validator_names {
+        for artifact in ["logs.txt", "consensus.txt", "resource_metrics.txt"] {
+            let contents = fs::read_to_string(dirs[0].join(&name).join(artifact)).unwrap();
+            assert!(!contents.is_empty(), "{} of {} is empty", artifact, name);
+            assert!(
+                !contents.starts_with("Failed to capture"),
+                "{} of {} was not captured: {}",
+                artifact,
+                name,
+                contents
+            );
+        }
+    }
+}
@@ -2,3 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod consensus_fault_tolerance;
+mod quorum_health;
+mod safety_rules;
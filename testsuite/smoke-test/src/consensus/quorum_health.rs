@@ -0,0 +1,29 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::smoke_test_environment::new_local_swarm_with_aptos;
+use forge::SwarmExt;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_wait_for_quorum_healthy_with_nodes_down() {
+    let mut swarm = new_local_swarm_with_aptos(4).await;
+    swarm.validators_mut().next().unwrap().stop();
+
+    // Exactly the requested fraction is up, so the wait returns while the last node stays down
+    let healthy = swarm
+        .wait_for_quorum_healthy(0.75, Instant::now() + Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(healthy, 3);
+
+    // Requiring every validator can't succeed while one is stopped
+    swarm
+        .wait_for_quorum_healthy(1.0, Instant::now() + Duration::from_secs(15))
+        .await
+        .unwrap_err();
+    assert!(swarm
+        .wait_for_quorum_healthy(1.5, Instant::now())
+        .await
+        .is_err());
+}
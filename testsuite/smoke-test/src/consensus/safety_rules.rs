@@ -0,0 +1,65 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::smoke_test_environment::new_local_swarm_with_aptos;
+use forge::{Node, NodeExt, SafetyRulesState, Swarm, Validator};
+use std::time::{Duration, Instant};
+
+const MAX_WAIT_SECS: u64 = 60;
+
+/// Safety rules only report their state once they persist it, so wait for a vote in a round
+/// after `round`
+async fn wait_for_vote_after(validator: &dyn Validator, round: u64) -> SafetyRulesState {
+    let deadline = Instant::now() + Duration::from_secs(MAX_WAIT_SECS);
+    loop {
+        match validator.get_safety_rules_state().await {
+            Ok(state) if state.last_voted_round > round => return state,
+            result => assert!(
+                Instant::now() < deadline,
+                "Validator {} never voted after round {}: {:?}",
+                validator.name(),
+                round,
+                result
+            ),
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_safety_rules_state_preserved_across_identity_handover() {
+    let mut swarm = new_local_swarm_with_aptos(4).await;
+    let validator_peer_id = swarm.validators().next().unwrap().peer_id();
+    let validator = swarm.validator_mut(validator_peer_id).unwrap();
+    let identity = validator.get_identity().await.unwrap();
+    let original_state = wait_for_vote_after(validator, 0).await;
+
+    // Hand the identity over to a fresh host, which keeps only the identity and the safety data
+    // of the original, and has to sync the chain from its peers
+    validator.stop().await.unwrap();
+    let snapshot = validator.snapshot_storage().await.unwrap();
+    let safety_data = snapshot
+        .paths
+        .iter()
+        .find(|path| path.ends_with("secure_storage.json"))
+        .unwrap()
+        .clone();
+    validator.clear_storage().await.unwrap();
+    std::fs::copy(snapshot.copy_of(&safety_data), &safety_data).unwrap();
+    validator.start().await.unwrap();
+    validator
+        .wait_until_healthy(Instant::now() + Duration::from_secs(MAX_WAIT_SECS))
+        .await
+        .unwrap();
+    assert_eq!(validator.get_identity().await.unwrap(), identity);
+
+    // The new host picks up from where the original left off rather than from a fresh safety
+    // state, and goes on voting in later rounds only
+    let new_state = wait_for_vote_after(validator, original_state.last_voted_round).await;
+    assert!(
+        new_state.is_successor_of(&original_state),
+        "Safety state regressed from {:?} to {:?}",
+        original_state,
+        new_state
+    );
+}
@@ -8,6 +8,8 @@ mod aptos;
 #[cfg(test)]
 mod aptos_cli;
 #[cfg(test)]
+mod artifacts;
+#[cfg(test)]
 mod client;
 #[cfg(test)]
 mod consensus;
@@ -325,3 +325,90 @@ pub(crate) fn db_restore(backup_path: &Path, db_path: &Path, trusted_waypoints:
     }
     println!("Backup restored in {} seconds.", now.elapsed().as_secs());
 }
+
+#[tokio::test]
+async fn test_storage_snapshot_restore() {
+    let mut swarm = new_local_swarm_with_aptos(4).await;
+    let validator_peer_ids = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+    let validator = validator_peer_ids[1];
+    let client = swarm.validator(validator).unwrap().rest_client();
+    let transaction_factory = swarm.chain_info().transaction_factory();
+    let mut account_0 = create_and_fund_account(&mut swarm, 1000).await;
+    let account_1 = create_and_fund_account(&mut swarm, 1000).await;
+    swarm
+        .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_WAIT_SECS))
+        .await
+        .unwrap();
+
+    // The validator stays stopped from the snapshot until it's restored, so it can't vote again
+    // in rounds it voted in before the snapshot
+    let committed_version = client
+        .get_ledger_information()
+        .await
+        .unwrap()
+        .into_inner()
+        .version;
+    let node = swarm.validator_mut(validator).unwrap();
+    node.stop().await.unwrap();
+    let snapshot = node.snapshot_storage().await.unwrap();
+    node.clear_storage().await.unwrap();
+    for path in &snapshot.paths {
+        assert!(!path.exists(), "{:?} wasn't cleared", path);
+    }
+    node.restore_storage(&snapshot).await.unwrap();
+
+    // The peers are stopped while the restored validator starts, so it can't sync from them, and
+    // the version it reports is the one in its restored databases
+    let peers = validator_peer_ids
+        .iter()
+        .filter(|peer_id| **peer_id != validator)
+        .collect::<Vec<_>>();
+    for peer_id in &peers {
+        swarm
+            .validator_mut(**peer_id)
+            .unwrap()
+            .stop()
+            .await
+            .unwrap();
+    }
+    let node = swarm.validator_mut(validator).unwrap();
+    node.start().await.unwrap();
+    node.wait_until_healthy(Instant::now() + Duration::from_secs(MAX_WAIT_SECS))
+        .await
+        .unwrap();
+
+    // The restored databases start from the version committed before the snapshot, rather than
+    // from genesis
+    let restored_version = client
+        .get_ledger_information()
+        .await
+        .unwrap()
+        .into_inner()
+        .version;
+    assert!(
+        restored_version >= committed_version,
+        "Restored at version {} before the committed version {}",
+        restored_version,
+        committed_version
+    );
+    for peer_id in &peers {
+        let peer = swarm.validator_mut(**peer_id).unwrap();
+        peer.start().await.unwrap();
+        peer.wait_until_healthy(Instant::now() + Duration::from_secs(MAX_WAIT_SECS))
+            .await
+            .unwrap();
+    }
+    transfer_coins(
+        &client,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        10,
+    )
+    .await;
+    swarm
+        .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_WAIT_SECS))
+        .await
+        .unwrap();
+    assert_balance(&client, &account_1, 1010).await;
+}
@@ -0,0 +1,199 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{LoadDestination, NetworkLoadTest};
+use anyhow::{anyhow, ensure};
+use aptos_logger::info;
+use aptos_rest_client::{error::RestError, Client as RestClient};
+use aptos_sdk::{
+    move_types::{account_address::AccountAddress, move_resource::MoveStructType},
+    types::account_config::CoinInfoResource,
+};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test};
+use futures::future::join_all;
+use reqwest::StatusCode;
+use std::time::{Duration, Instant};
+
+/// Client side timeout of a single read, a read taking longer counts as timed out
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Fraction of reads which may fail without backpressure before the API is considered broken
+const MAX_FAILED_FRACTION: f64 = 0.05;
+
+/// Saturates the REST API of a single node with reads, including `GetTableItem` calls, while the
+/// usual write load goes to the validators.
+///
+/// The API is allowed to shed load, either by rejecting requests with `429 Too Many Requests` or
+/// `503 Service Unavailable`, or by being slow to answer.  It is not allowed to answer with other
+/// server errors, drop connections, or restart, and consensus must keep committing throughout.
+pub struct ApiOverloadTest {
+    /// Number of workers concurrently issuing reads against the API
+    pub concurrency: usize,
+}
+
+impl Test for ApiOverloadTest {
+    fn name(&self) -> &'static str {
+        "api-overload-test"
+    }
+}
+
+/// Outcomes of the reads issued by one or more workers
+#[derive(Debug, Default)]
+struct ReadStats {
+    succeeded: u64,
+    throttled: u64,
+    timed_out: u64,
+    failed: u64,
+}
+
+impl ReadStats {
+    fn record<T>(&mut self, result: std::result::Result<T, RestError>) {
+        match result {
+            Ok(_) => self.succeeded += 1,
+            Err(RestError::Api(error)) if is_backpressure(error.status_code) => self.throttled += 1,
+            Err(RestError::Http(status, _)) if is_backpressure(status) => self.throttled += 1,
+            Err(RestError::Unknown(error))
+                if error
+                    .downcast_ref::<reqwest::Error>()
+                    .map_or(false, reqwest::Error::is_timeout) =>
+            {
+                self.timed_out += 1
+            }
+            Err(_) => self.failed += 1,
+        }
+    }
+
+    fn merge(mut self, other: ReadStats) -> Self {
+        self.succeeded += other.succeeded;
+        self.throttled += other.throttled;
+        self.timed_out += other.timed_out;
+        self.failed += other.failed;
+        self
+    }
+
+    fn total(&self) -> u64 {
+        self.succeeded + self.throttled + self.timed_out + self.failed
+    }
+}
+
+fn is_backpressure(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Issues reads until the deadline, alternating between ledger info and the coin supply table item
+async fn read_until(
+    client: RestClient,
+    deadline: Instant,
+    table_handle: AccountAddress,
+    table_key: AccountAddress,
+) -> ReadStats {
+    let mut stats = ReadStats::default();
+    while Instant::now() < deadline {
+        stats.record(client.get_ledger_information().await);
+        stats.record(
+            client
+                .get_table_item(table_handle, "address", "u128", table_key.to_hex_literal())
+                .await,
+        );
+    }
+    stats
+}
+
+impl NetworkLoadTest for ApiOverloadTest {
+    fn setup(&self, _ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        ensure!(
+            self.concurrency > 0,
+            "concurrency is required to be non zero"
+        );
+        // Keep the writes on the validators, so the reads are the only load on the target
+        Ok(LoadDestination::AllValidators)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let on_fullnode = swarm.full_nodes().count() > 0;
+        let (target, client) = if on_fullnode {
+            let node = swarm.full_nodes().next().unwrap();
+            (node.peer_id(), node.rest_client_with_timeout(READ_TIMEOUT))
+        } else {
+            let node = swarm.validators().next().unwrap();
+            (node.peer_id(), node.rest_client_with_timeout(READ_TIMEOUT))
+        };
+        let validator_client = swarm.validators().next().unwrap().rest_client();
+
+        // The aggregator tracking the coin supply is a table item updated by every transaction
+        let coin_info = runtime
+            .block_on(client.get_account_resource_bcs::<CoinInfoResource>(
+                AccountAddress::ONE,
+                &CoinInfoResource::struct_tag().to_string(),
+            ))?
+            .into_inner();
+        let aggregator = coin_info
+            .supply()
+            .as_ref()
+            .and_then(|supply| supply.aggregator.as_ref())
+            .ok_or_else(|| anyhow!("Coin supply is not tracked by an aggregator"))?;
+
+        let start_version = runtime
+            .block_on(validator_client.get_ledger_information())?
+            .into_inner()
+            .version;
+        info!(
+            "Overloading the API of {} with {} concurrent readers for {}s",
+            target,
+            self.concurrency,
+            duration.as_secs()
+        );
+        let deadline = Instant::now() + duration;
+        let stats = runtime
+            .block_on(join_all((0..self.concurrency).map(|_| {
+                read_until(
+                    client.clone(),
+                    deadline,
+                    aggregator.handle(),
+                    aggregator.key(),
+                )
+            })))
+            .into_iter()
+            .fold(ReadStats::default(), ReadStats::merge);
+        info!("API overload read stats: {:?}", stats);
+
+        // The API must shed load gracefully rather than break
+        ensure!(stats.succeeded > 0, "No read succeeded against {}", target);
+        let failed_fraction = stats.failed as f64 / stats.total() as f64;
+        ensure!(
+            failed_fraction <= MAX_FAILED_FRACTION,
+            "{} of {} reads against {} failed without backpressure",
+            stats.failed,
+            stats.total(),
+            target
+        );
+        runtime.block_on(async {
+            if on_fullnode {
+                swarm.ensure_no_fullnode_restart().await
+            } else {
+                swarm.ensure_no_validator_restart().await
+            }
+        })?;
+        runtime.block_on(client.health_check(10))?;
+
+        // Consensus must be unaffected by the load on the API
+        let end_version = runtime
+            .block_on(validator_client.get_ledger_information())?
+            .into_inner()
+            .version;
+        ensure!(
+            end_version > start_version,
+            "Ledger did not advance from version {} while the API was overloaded",
+            start_version
+        );
+        runtime.block_on(swarm.liveness_check(Instant::now() + Duration::from_secs(60)))?;
+        swarm.fork_check()
+    }
+}
+
+impl NetworkTest for ApiOverloadTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
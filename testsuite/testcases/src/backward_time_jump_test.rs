@@ -0,0 +1,182 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    swarm_utils::{get_block_events, latest_block_event_sequence_number},
+    LoadDestination, NetworkLoadTest,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use forge::{
+    NetworkContext, NetworkTest, NodeExt, Result, SafetyRulesState, SwarmChaos, SwarmExt,
+    SwarmTimeOffset, Test, Validator,
+};
+use std::time::{Duration, Instant};
+
+/// Interval between two reads of the skewed validator's safety rules state
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Time the skewed validator has to vote again once its clock is restored
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Moves the system clock of one validator backward under load, and checks that it never votes
+/// in a round twice, the network stays live and committed blocks stay ordered, then restores its
+/// clock and checks that it recovers.
+///
+/// Backward jumps happen in practice through NTP corrections.  The clock is moved back for the
+/// first half of the test, and restored for the second half.
+pub struct BackwardTimeJumpTest {
+    /// Index of the validator whose clock jumps backward
+    pub validator_index: usize,
+    /// How far back the clock jumps, in seconds
+    pub jump_secs: u64,
+}
+
+impl Test for BackwardTimeJumpTest {
+    fn name(&self) -> &'static str {
+        "backward-time-jump-test"
+    }
+}
+
+impl NetworkLoadTest for BackwardTimeJumpTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        ensure!(self.jump_secs > 0, "jump_secs must be positive");
+        let validators = ctx.swarm().validators().count();
+        ensure!(
+            validators > 1 && self.validator_index < validators,
+            "validator_index {} must be one of {} validators, leaving another to observe the chain",
+            self.validator_index,
+            validators
+        );
+        Ok(LoadDestination::AllNodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let skewed = swarm
+            .validators()
+            .find(|validator| validator.index() == self.validator_index)
+            .ok_or_else(|| anyhow!("No validator with index {}", self.validator_index))?;
+        let skewed_name = skewed.name().to_string();
+        let skewed_peer_id = skewed.peer_id();
+        let observer = swarm
+            .validators()
+            .find(|validator| validator.peer_id() != skewed_peer_id)
+            .unwrap()
+            .rest_client();
+        let start = runtime.block_on(latest_block_event_sequence_number(&observer))? + 1;
+        let mut state = runtime.block_on(skewed.get_safety_rules_state())?;
+
+        let chaos = SwarmChaos::TimeOffset(SwarmTimeOffset {
+            validator_name: skewed_name.clone(),
+            offset_ms: -(self.jump_secs as i64 * 1000),
+        });
+        info!(
+            "Moving the clock of {} back by {}s",
+            skewed_name, self.jump_secs
+        );
+        swarm.inject_chaos(chaos.clone())?;
+        let skewed_result = runtime.block_on(async {
+            let validator = swarm.validator(skewed_peer_id).unwrap();
+            state = monitor_safety_rules(validator, state, duration / 2).await?;
+            swarm
+                .liveness_check(Instant::now() + Duration::from_secs(60))
+                .await
+        });
+        // The clock must be restored even if the checks failed
+        swarm.remove_chaos(chaos)?;
+        skewed_result?;
+        info!("Restored the clock of {}", skewed_name);
+
+        let validator = swarm.validator(skewed_peer_id).unwrap();
+        let restored = state;
+        state = runtime.block_on(monitor_safety_rules(validator, state, duration / 2))?;
+        runtime.block_on(wait_for_vote(validator, restored, state))?;
+        runtime.block_on(swarm.wait_for_all_nodes_to_catchup(RECOVERY_TIMEOUT))?;
+
+        let end = runtime.block_on(latest_block_event_sequence_number(&observer))? + 1;
+        runtime.block_on(check_block_order(&observer, start, end))?;
+        swarm.fork_check()
+    }
+}
+
+impl NetworkTest for BackwardTimeJumpTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+/// Reads the validator's safety rules state for `duration`, failing if it ever goes back, i.e.
+/// if the validator could vote again in a round it already voted in.  Returns the last state.
+async fn monitor_safety_rules(
+    validator: &dyn Validator,
+    mut previous: SafetyRulesState,
+    duration: Duration,
+) -> Result<SafetyRulesState> {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        // The validator may be briefly unreachable, which isn't a safety violation
+        if let Ok(state) = validator.get_safety_rules_state().await {
+            ensure!(
+                state.is_successor_of(&previous),
+                "Safety rules state of {} went back from {:?} to {:?}",
+                validator.name(),
+                previous,
+                state
+            );
+            previous = state;
+        }
+    }
+    Ok(previous)
+}
+
+/// Waits for the validator to vote in a later round than when its clock was restored
+async fn wait_for_vote(
+    validator: &dyn Validator,
+    restored: SafetyRulesState,
+    mut state: SafetyRulesState,
+) -> Result<()> {
+    let deadline = Instant::now() + RECOVERY_TIMEOUT;
+    while state.epoch == restored.epoch && state.last_voted_round <= restored.last_voted_round {
+        if Instant::now() > deadline {
+            bail!(
+                "{} did not vote within {:?} of its clock being restored, still at {:?}",
+                validator.name(),
+                RECOVERY_TIMEOUT,
+                state
+            );
+        }
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        state = validator.get_safety_rules_state().await?;
+    }
+    Ok(())
+}
+
+/// Checks that committed blocks have strictly increasing rounds, so that no round was proposed
+/// twice, and timestamps which never go back
+async fn check_block_order(client: &RestClient, start: u64, end: u64) -> Result<()> {
+    let events = get_block_events(client, start, end).await?;
+    for pair in events.windows(2) {
+        let (previous, next) = (&pair[0].event, &pair[1].event);
+        ensure!(
+            (next.epoch(), next.round()) > (previous.epoch(), previous.round()),
+            "Block at epoch {} round {} was committed after epoch {} round {}",
+            next.epoch(),
+            next.round(),
+            previous.epoch(),
+            previous.round()
+        );
+        ensure!(
+            next.proposed_time() >= previous.proposed_time(),
+            "Block at epoch {} round {} has timestamp {}, before the previous block's {}",
+            next.epoch(),
+            next.round(),
+            next.proposed_time(),
+            previous.proposed_time()
+        );
+    }
+    info!("{} blocks committed in order", events.len());
+    Ok(())
+}
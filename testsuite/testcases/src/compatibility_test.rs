@@ -5,7 +5,7 @@ use crate::{batch_update, generate_traffic};
 use anyhow::bail;
 use aptos_logger::info;
 use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
-use tokio::{runtime::Runtime, time::Duration};
+use tokio::time::Duration;
 
 pub struct SimpleValidatorUpgrade;
 
@@ -17,7 +17,7 @@ impl Test for SimpleValidatorUpgrade {
 
 impl NetworkTest for SimpleValidatorUpgrade {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
-        let runtime = Runtime::new()?;
+        let runtime = ctx.handle();
 
         // Get the different versions we're testing with
         let (old_version, new_version) = {
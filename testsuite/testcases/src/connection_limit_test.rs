@@ -0,0 +1,232 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{LoadDestination, NetworkLoadTest};
+use anyhow::{anyhow, ensure};
+use aptos_config::{config::HANDSHAKE_VERSION, network_id::NetworkId};
+use aptos_logger::info;
+use aptos_network_checker::check_endpoint::open_connection;
+use aptos_sdk::{
+    crypto::{x25519, Uniform},
+    types::network_address::NetworkAddress,
+};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test, Validator};
+use futures::AsyncReadExt;
+use rand::{
+    rngs::{OsRng, StdRng},
+    Rng, SeedableRng,
+};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Time allowed for each connection attempt, including the handshake
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time the validator has to close the connections over its limit
+const REJECTION_DELAY: Duration = Duration::from_secs(5);
+/// Time to wait for a connection to be closed before considering it open
+const CLOSED_CHECK_TIMEOUT: Duration = Duration::from_millis(100);
+const CONNECTIONS_REJECTED_METRIC: &str = "aptos_connections_rejected";
+
+/// Opens connections from unknown peers to the public network of a validator, up to and beyond
+/// its inbound connection limit, and checks that the connections over the limit are closed while
+/// the validator keeps its peers and consensus stays live.
+///
+/// The connections are made directly through the network handshake, each with a new identity, and
+/// are held open for the duration of the test.
+pub struct ConnectionLimitTest {
+    /// Number of connections to open, which should exceed the validator's limit
+    pub num_connections: usize,
+}
+
+impl Test for ConnectionLimitTest {
+    fn name(&self) -> &'static str {
+        "connection-limit-test"
+    }
+}
+
+impl NetworkLoadTest for ConnectionLimitTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        let validator = ctx.swarm().validators().next().unwrap();
+        let limit = public_network_limit(validator)?;
+        ensure!(
+            self.num_connections > limit,
+            "num_connections {} must exceed the inbound connection limit {} of {}",
+            self.num_connections,
+            limit,
+            validator.name()
+        );
+        Ok(LoadDestination::AllNodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let chain_id = swarm.chain_info().chain_id;
+        let validator = swarm.validators().next().unwrap();
+        let limit = public_network_limit(validator)?;
+        let address = public_network_address(validator)?;
+
+        let (validator_peers, inbound, rejected) = runtime.block_on(async {
+            Ok::<_, anyhow::Error>((
+                validator
+                    .get_connected_peers(NetworkId::Validator, None)
+                    .await?
+                    .unwrap_or(0),
+                validator
+                    .get_connected_peers(NetworkId::Public, Some("inbound"))
+                    .await?
+                    .unwrap_or(0) as usize,
+                connections_rejected(validator).await?,
+            ))
+        })?;
+        let available = limit.saturating_sub(inbound);
+        info!(
+            "Opening {} connections to {} at {}, with {} of {} inbound connections in use",
+            self.num_connections,
+            validator.name(),
+            address,
+            inbound,
+            limit
+        );
+
+        let mut rng = StdRng::from_seed(OsRng.gen());
+        let mut connections = Vec::with_capacity(self.num_connections);
+        let mut failed = 0;
+        for _ in 0..self.num_connections {
+            let key = x25519::PrivateKey::generate(&mut rng);
+            let connection = runtime.block_on(open_connection(
+                chain_id,
+                NetworkId::Public,
+                key,
+                &address,
+                CONNECTION_TIMEOUT,
+            ));
+            match connection {
+                Ok(connection) => connections.push(connection),
+                // The validator may refuse the handshake outright, which enforces the limit too
+                Err(error) => {
+                    info!("Connection to {} failed: {:#}", validator.name(), error);
+                    failed += 1;
+                }
+            }
+        }
+        std::thread::sleep(REJECTION_DELAY);
+
+        // Connections the validator closed read as end of file, others yield nothing in time
+        let mut open = 0;
+        for connection in &mut connections {
+            let mut buf = [0; 1];
+            let read = runtime.block_on(tokio::time::timeout(
+                CLOSED_CHECK_TIMEOUT,
+                connection.socket.read(&mut buf),
+            ));
+            if !matches!(read, Ok(Ok(0)) | Ok(Err(_))) {
+                open += 1;
+            }
+        }
+        let newly_rejected = runtime.block_on(connections_rejected(validator))? - rejected;
+        info!(
+            "{} connections open, {} rejected and {} failed, with {} available",
+            open, newly_rejected, failed, available
+        );
+        ensure!(
+            open <= available,
+            "{} connections to {} are open, beyond the {} available under its limit {}",
+            open,
+            validator.name(),
+            available,
+            limit
+        );
+        ensure!(
+            open == available.min(self.num_connections),
+            "Only {} connections to {} are open, expected {} under its limit {}",
+            open,
+            validator.name(),
+            available.min(self.num_connections),
+            limit
+        );
+        ensure!(
+            newly_rejected as usize + failed >= self.num_connections - available,
+            "{} connections over the limit of {} should be rejected, but {} were",
+            self.num_connections - available,
+            validator.name(),
+            newly_rejected as usize + failed
+        );
+
+        // Hold the connections while the validator keeps running under load
+        if let Some(remaining) = duration.checked_sub(start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+        let peers_after = runtime
+            .block_on(validator.get_connected_peers(NetworkId::Validator, None))?
+            .unwrap_or(0);
+        ensure!(
+            peers_after >= validator_peers,
+            "{} lost validator peers while over its connection limit, {} before and {} after",
+            validator.name(),
+            validator_peers,
+            peers_after
+        );
+        drop(connections);
+
+        runtime.block_on(swarm.liveness_check(Instant::now() + Duration::from_secs(60)))?;
+        swarm.fork_check()
+    }
+}
+
+impl NetworkTest for ConnectionLimitTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+/// Reads the limit of inbound connections from unknown peers on the validator's public network
+fn public_network_limit(validator: &dyn Validator) -> Result<usize> {
+    validator
+        .config()
+        .full_node_networks
+        .iter()
+        .find(|network| network.network_id == NetworkId::Public)
+        .map(|network| network.max_inbound_connections)
+        .ok_or_else(|| anyhow!("{} has no public network", validator.name()))
+}
+
+/// Builds the address to dial the validator's public network at, with its noise key
+fn public_network_address(validator: &dyn Validator) -> Result<NetworkAddress> {
+    let network = validator
+        .config()
+        .full_node_networks
+        .iter()
+        .find(|network| network.network_id == NetworkId::Public)
+        .ok_or_else(|| anyhow!("{} has no public network", validator.name()))?;
+    let port = network
+        .listen_address
+        .find_port()
+        .ok_or_else(|| anyhow!("{} has no public network port", validator.name()))?;
+    // The listen address may be unspecified, so dial the host serving the REST API instead
+    let endpoint = validator.rest_api_endpoint();
+    let host = endpoint
+        .host_str()
+        .ok_or_else(|| anyhow!("{} has no host", validator.name()))?;
+    let host = match IpAddr::from_str(host) {
+        Ok(IpAddr::V4(ip)) => format!("/ip4/{}", ip),
+        Ok(IpAddr::V6(ip)) => format!("/ip6/{}", ip),
+        Err(_) => format!("/dns/{}", host),
+    };
+    Ok(NetworkAddress::from_str(&format!("{}/tcp/{}", host, port))?
+        .append_prod_protos(network.identity_key().public_key(), HANDSHAKE_VERSION))
+}
+
+async fn connections_rejected(validator: &dyn Validator) -> Result<i64> {
+    let mut fields = HashMap::new();
+    fields.insert("network_id".to_string(), NetworkId::Public.to_string());
+    Ok(validator
+        .get_metric_with_fields_i64(CONNECTIONS_REJECTED_METRIC, fields)
+        .await?
+        .unwrap_or(0))
+}
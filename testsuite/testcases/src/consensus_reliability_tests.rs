@@ -9,11 +9,10 @@ use aptos_sdk::types::account_config::CORE_CODE_ADDRESS;
 use forge::test_utils::consensus_utils::{
     test_consensus_fault_tolerance, FailPointFailureInjection, NodeState,
 };
-use forge::{NetworkContext, NetworkTest, Result, Swarm, SwarmExt, Test};
+use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
 use rand::Rng;
 use std::collections::HashSet;
 use std::time::Duration;
-use tokio::runtime::Runtime;
 
 pub struct ChangingWorkingQuorumTest {
     pub min_tps: usize,
@@ -49,8 +48,9 @@ impl NetworkLoadTest for ChangingWorkingQuorumTest {
         }
     }
 
-    fn test(&self, swarm: &mut dyn Swarm, duration: Duration) -> Result<()> {
-        let runtime = Runtime::new().unwrap();
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
 
         let validators = swarm.get_validator_clients_with_names();
 
@@ -0,0 +1,199 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::swarm_utils::{
+    get_voting_powers, has_quorum, restart_validators, stop_validators, validator_clients,
+    voting_power_of,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::types::PeerId;
+use forge::{
+    get_highest_synced_version, NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test,
+};
+use std::time::{Duration, Instant};
+
+/// Longest the running validators may keep committing for once the quorum is lost, while the
+/// blocks certified before the validators were stopped are committed
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval between checks of the committed version
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Time for the stopped validators to come back, and for the network to commit again
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Stops enough validators to lose the quorum, and checks the network stops committing, then
+/// starts them again and checks the network recovers.
+///
+/// Validators are stopped in decreasing order of voting power, so that by default the fewest
+/// validators are stopped which leave the others short of more than two thirds of the voting
+/// power.  With a `stop_count` which leaves a quorum running, the network must keep committing
+/// instead, as a stall with the quorum up is a liveness bug rather than the expected stall.
+pub struct ConsensusStallTest {
+    /// Number of validators to stop, or the fewest which lose the quorum if `None`
+    pub stop_count: Option<usize>,
+    /// How long the network must stay stalled, or keep committing, in seconds
+    pub stall_duration_secs: u64,
+}
+
+impl Test for ConsensusStallTest {
+    fn name(&self) -> &'static str {
+        "consensus-stall-test"
+    }
+}
+
+impl NetworkTest for ConsensusStallTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = ctx.handle();
+        let client = ctx.swarm().validators().next().unwrap().rest_client();
+        let voting_powers = runtime.block_on(get_voting_powers(&client))?;
+        let mut validators = Vec::new();
+        for validator in ctx.swarm().validators() {
+            let power = voting_powers.get(&validator.peer_id()).ok_or_else(|| {
+                anyhow!("Validator {} is not in the validator set", validator.name())
+            })?;
+            validators.push((validator.peer_id(), *power));
+        }
+        let total: u64 = voting_powers.values().sum();
+        let stopped = stop_set(validators.clone(), total, self.stop_count)?;
+        let running = validators
+            .iter()
+            .map(|(validator, _)| *validator)
+            .filter(|validator| !stopped.contains(validator))
+            .collect::<Vec<_>>();
+        let running_power = voting_power_of(&voting_powers, &running)?;
+        let quorum_lost = !has_quorum(running_power, total);
+        info!(
+            "Stopping {} of {} validators, leaving {} of {} voting power, {}",
+            stopped.len(),
+            validators.len(),
+            running_power,
+            total,
+            if quorum_lost {
+                "without a quorum"
+            } else {
+                "with a quorum"
+            }
+        );
+
+        let running_clients = validator_clients(ctx.swarm(), &running);
+        stop_validators(&runtime, ctx.swarm(), &stopped)?;
+        let stall_duration = Duration::from_secs(self.stall_duration_secs);
+        let stall_result = runtime.block_on(async {
+            if quorum_lost {
+                let version = wait_for_stall(&running_clients).await?;
+                info!("Committing stopped at version {} without a quorum", version);
+                tokio::time::sleep(stall_duration).await;
+                let after = get_highest_synced_version(&running_clients).await?;
+                ensure!(
+                    after == version,
+                    "Committed from version {} to {} with only {} of {} voting power running",
+                    version,
+                    after,
+                    running_power,
+                    total
+                );
+            } else {
+                let version = get_highest_synced_version(&running_clients).await?;
+                tokio::time::sleep(stall_duration).await;
+                let after = get_highest_synced_version(&running_clients).await?;
+                ensure!(
+                    after > version,
+                    "Stalled at version {} with a quorum of {} of {} voting power running, \
+                     which is a liveness bug",
+                    version,
+                    running_power,
+                    total
+                );
+            }
+            Ok::<(), anyhow::Error>(())
+        });
+
+        // The stopped validators must be started even if the checks failed
+        let deadline = Instant::now() + RECOVERY_TIMEOUT;
+        restart_validators(&runtime, ctx.swarm(), &stopped, deadline)?;
+        stall_result?;
+
+        let clients = ctx.swarm().get_validator_clients_with_names();
+        runtime.block_on(async {
+            let version = get_highest_synced_version(&clients).await?;
+            while get_highest_synced_version(&clients).await? <= version {
+                ensure!(
+                    Instant::now() < deadline,
+                    "Still stalled at version {} {}s after restarting the stopped validators",
+                    version,
+                    RECOVERY_TIMEOUT.as_secs()
+                );
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            info!("Committing resumed after version {}", version);
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(RECOVERY_TIMEOUT)
+                .await
+        })?;
+        ctx.swarm().fork_check()
+    }
+}
+
+/// The validators to stop, in decreasing order of voting power: the first `stop_count`, or the
+/// fewest which leave the others without a quorum
+fn stop_set(
+    mut validators: Vec<(PeerId, u64)>,
+    total: u64,
+    stop_count: Option<usize>,
+) -> Result<Vec<PeerId>> {
+    validators.sort_by(|(a, a_power), (b, b_power)| b_power.cmp(a_power).then(a.cmp(b)));
+    let count = match stop_count {
+        Some(count) => {
+            ensure!(
+                count > 0 && count < validators.len(),
+                "stop_count must leave validators running, but is {} of {}",
+                count,
+                validators.len()
+            );
+            count
+        }
+        None => {
+            let mut running = total;
+            let mut count = 0;
+            while has_quorum(running, total) {
+                match validators.get(count) {
+                    Some((_, power)) => running -= power,
+                    None => bail!("No validators left to stop"),
+                }
+                count += 1;
+            }
+            ensure!(
+                count < validators.len(),
+                "Losing the quorum takes stopping all {} validators",
+                validators.len()
+            );
+            count
+        }
+    };
+    Ok(validators
+        .into_iter()
+        .take(count)
+        .map(|(validator, _)| validator)
+        .collect())
+}
+
+/// Waits until the version the running validators committed stops advancing, returning it
+async fn wait_for_stall(clients: &[(String, RestClient)]) -> Result<u64> {
+    let deadline = Instant::now() + STALL_TIMEOUT;
+    let mut versions = vec![get_highest_synced_version(clients).await?];
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let after = get_highest_synced_version(clients).await?;
+        if versions.last() == Some(&after) {
+            return Ok(after);
+        }
+        versions.push(after);
+        ensure!(
+            Instant::now() < deadline,
+            "Still committing {}s after losing the quorum, at versions {:?}",
+            STALL_TIMEOUT.as_secs(),
+            versions
+        );
+    }
+}
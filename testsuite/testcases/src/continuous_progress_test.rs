@@ -4,9 +4,8 @@
 use crate::NetworkLoadTest;
 use anyhow::bail;
 use forge::test_utils::consensus_utils::{no_failure_injection, test_consensus_fault_tolerance};
-use forge::{NetworkContext, NetworkTest, Result, Swarm, Test};
+use forge::{NetworkContext, NetworkTest, Result, Test};
 use std::time::Duration;
-use tokio::runtime::Runtime;
 
 pub struct ContinuousProgressTest {
     pub target_tps: usize,
@@ -19,8 +18,9 @@ impl Test for ContinuousProgressTest {
 }
 
 impl NetworkLoadTest for ContinuousProgressTest {
-    fn test(&self, swarm: &mut dyn Swarm, duration: Duration) -> Result<()> {
-        let runtime = Runtime::new().unwrap();
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
 
         // Check that every 27s all nodes make progress,
         // without any failures.
@@ -0,0 +1,212 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{LoadDestination, NetworkLoadTest};
+use anyhow::{anyhow, bail, ensure};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::{
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{account_config::CORE_CODE_ADDRESS, on_chain_config::Version as OnChainVersion},
+};
+use forge::{
+    HealthWaitConfig, NetworkContext, NetworkTest, NodeExt, Result, Swarm, SwarmExt, Test, Version,
+};
+use std::time::{Duration, Instant};
+
+/// How long the network may take to enter the upgrade epoch once the new version is set
+const UPGRADE_EPOCH_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Upgrades every validator from `old_version` to `new_version` while the network is under load,
+/// then activates the upgrade by bumping the on-chain version through the governance-gated
+/// `version::set_version`, which triggers a reconfiguration.
+///
+/// Binaries are swapped one validator at a time so that quorum is held throughout, and the test
+/// asserts that the ledger keeps advancing after every swap, that the network enters the upgrade
+/// epoch at the new on-chain version, and that it keeps committing without forks afterwards.
+pub struct CoordinatedUpgradeTest {
+    /// Display name of the version to upgrade from, defaults to the oldest version in the swarm
+    pub old_version: Option<&'static str>,
+    /// Display name of the version to upgrade to, defaults to the newest version in the swarm
+    pub new_version: Option<&'static str>,
+    /// How long, and how often, to wait for each validator to come back healthy after its binary
+    /// is swapped
+    pub health_wait: HealthWaitConfig,
+}
+
+impl CoordinatedUpgradeTest {
+    fn find_version(
+        swarm: &dyn Swarm,
+        name: Option<&'static str>,
+        newest: bool,
+    ) -> Result<Version> {
+        let mut versions = swarm.versions().collect::<Vec<_>>();
+        versions.sort();
+        let version = match name {
+            Some(name) => versions
+                .into_iter()
+                .find(|version| version.to_string() == name),
+            None if newest => versions.pop(),
+            None => versions.into_iter().next(),
+        };
+        version.ok_or_else(|| anyhow!("Version {:?} is not available in the swarm", name))
+    }
+
+    fn versions(&self, swarm: &dyn Swarm) -> Result<(Version, Version)> {
+        let old_version = Self::find_version(swarm, self.old_version, false)?;
+        let new_version = Self::find_version(swarm, self.new_version, true)?;
+        if old_version == new_version {
+            bail!(
+                "coordinated upgrade test requires two different versions, but both are {}",
+                old_version
+            );
+        }
+        Ok((old_version, new_version))
+    }
+}
+
+impl Test for CoordinatedUpgradeTest {
+    fn name(&self) -> &'static str {
+        "compatibility::coordinated-upgrade"
+    }
+}
+
+impl NetworkLoadTest for CoordinatedUpgradeTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        let (old_version, new_version) = self.versions(ctx.swarm())?;
+        let msg = format!(
+            "Coordinated upgrade test for {} ==> {}",
+            old_version, new_version
+        );
+        info!("{}", msg);
+        ctx.report.report_text(msg);
+
+        // Every validator must start from the old version
+        let runtime = ctx.handle();
+        let outdated = ctx
+            .swarm()
+            .validators()
+            .filter(|validator| validator.version() != old_version)
+            .map(|validator| validator.peer_id())
+            .collect::<Vec<_>>();
+        for validator in outdated {
+            runtime.block_on(ctx.swarm().upgrade_validator(validator, &old_version))?;
+        }
+        runtime.block_on(ctx.swarm().health_check())?;
+
+        // Validators restart during the test, so keep the load on fullnodes when there are any
+        if ctx.swarm().full_nodes().count() > 0 {
+            Ok(LoadDestination::AllFullnodes)
+        } else {
+            Ok(LoadDestination::AllValidators)
+        }
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let (_, new_version) = self.versions(swarm)?;
+        let validators = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+        let clients = swarm
+            .validators()
+            .map(|v| v.rest_client())
+            .collect::<Vec<_>>();
+
+        let start_state = runtime.block_on(clients[0].get_ledger_information())?;
+        let start_epoch = start_state.inner().epoch;
+        let mut last_version = start_state.inner().version;
+        let start_major = runtime.block_on(get_on_chain_version(&clients[0]))?.major;
+
+        // Swap the binary of one validator at a time, so quorum is never lost
+        for (index, validator) in validators.iter().enumerate() {
+            info!(
+                "Upgrading validator {} to version {}",
+                validator, new_version
+            );
+            runtime.block_on(async {
+                swarm.upgrade_validator(*validator, &new_version).await?;
+                swarm
+                    .validator_mut(*validator)
+                    .unwrap()
+                    .wait_until_healthy_with(self.health_wait)
+                    .await
+            })?;
+
+            // Any remaining validator must observe progress, otherwise the swap caused downtime
+            let client = &clients[(index + 1) % clients.len()];
+            let state = runtime.block_on(client.wait_for_version(last_version + 1))?;
+            last_version = state.version;
+        }
+
+        // Activate the upgrade on chain, which reconfigures into the upgrade epoch
+        let target_major = start_major + 1;
+        info!("Setting on-chain version to {}", target_major);
+        let txn = {
+            let chain_info = swarm.chain_info();
+            let factory = TransactionFactory::new(chain_info.chain_id);
+            chain_info.root_account.sign_with_transaction_builder(
+                factory.payload(aptos_stdlib::version_set_version(target_major)),
+            )
+        };
+        runtime.block_on(clients[0].submit_and_wait(&txn))?;
+
+        let upgrade_state = runtime.block_on(async {
+            let deadline = Instant::now() + UPGRADE_EPOCH_TIMEOUT;
+            loop {
+                let state = clients[0].get_ledger_information().await?.into_inner();
+                if state.epoch > start_epoch {
+                    return Ok(state);
+                }
+                if Instant::now() > deadline {
+                    bail!(
+                        "Network did not leave epoch {} after the upgrade was activated",
+                        start_epoch
+                    );
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        })?;
+        info!("Upgrade activated in epoch {}", upgrade_state.epoch);
+
+        // Every validator must continue past the upgrade epoch at the new version
+        for (client, validator) in clients.iter().zip(&validators) {
+            runtime.block_on(client.wait_for_version(upgrade_state.version + 1))?;
+            let major = runtime.block_on(get_on_chain_version(client))?.major;
+            ensure!(
+                major == target_major,
+                "Validator {} reports on-chain version {} instead of {}",
+                validator,
+                major,
+                target_major
+            );
+        }
+        ensure!(
+            swarm
+                .validators()
+                .all(|validator| validator.version() == new_version),
+            "Not every validator is running version {}",
+            new_version
+        );
+        swarm.fork_check()?;
+
+        // Keep the load running for the rest of the test to observe post-upgrade behavior
+        if let Some(remaining) = duration.checked_sub(start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+        Ok(())
+    }
+}
+
+impl NetworkTest for CoordinatedUpgradeTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+async fn get_on_chain_version(client: &RestClient) -> Result<OnChainVersion> {
+    Ok(client
+        .get_account_resource_bcs::<OnChainVersion>(CORE_CODE_ADDRESS, "0x1::version::Version")
+        .await?
+        .into_inner())
+}
@@ -10,7 +10,6 @@ use rand::{
     seq::IteratorRandom,
     Rng, SeedableRng,
 };
-use tokio::runtime::Runtime;
 
 const STATE_SYNC_VERSION_COUNTER_NAME: &str = "aptos_state_sync_version";
 
@@ -25,7 +24,7 @@ impl Test for ForgeSetupTest {
 impl NetworkTest for ForgeSetupTest {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
         let mut rng = StdRng::from_seed(OsRng.gen());
-        let runtime = Runtime::new().unwrap();
+        let runtime = ctx.handle();
 
         let swarm = ctx.swarm();
 
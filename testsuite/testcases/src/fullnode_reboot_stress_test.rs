@@ -2,11 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{LoadDestination, NetworkLoadTest};
-use forge::{NetworkContext, NetworkTest, Result, Swarm, Test};
+use forge::{NetworkContext, NetworkTest, Result, Test};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::time::Duration;
-use tokio::runtime::Runtime;
 use tokio::time::Instant;
 
 pub struct FullNodeRebootStressTest;
@@ -22,9 +21,10 @@ impl NetworkLoadTest for FullNodeRebootStressTest {
         Ok(LoadDestination::AllFullnodes)
     }
 
-    fn test(&self, swarm: &mut dyn Swarm, duration: Duration) -> Result<()> {
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
         let start = Instant::now();
-        let runtime = Runtime::new().unwrap();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
 
         let all_fullnodes = swarm.full_nodes().map(|v| v.peer_id()).collect::<Vec<_>>();
 
@@ -0,0 +1,116 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure};
+use aptos_logger::info;
+use aptos_rest_client::error::RestError;
+use aptos_sdk::{
+    move_types::vm_status::StatusCode, transaction_builder::aptos_stdlib,
+    types::transaction::SignedTransaction,
+};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+
+/// Mirrors `maximum_number_of_gas_units` in the genesis gas schedule
+pub const MAX_GAS_UNITS_PER_TXN: u64 = 4_000_000;
+const GAS_UNIT_PRICE: u64 = 1;
+
+pub const DEFAULT_OVER_LIMIT_GAS_AMOUNTS: &[u64] = &[
+    MAX_GAS_UNITS_PER_TXN + 1,
+    MAX_GAS_UNITS_PER_TXN * 2,
+    u64::MAX,
+];
+pub const DEFAULT_WITHIN_LIMIT_GAS_AMOUNTS: &[u64] =
+    &[MAX_GAS_UNITS_PER_TXN, MAX_GAS_UNITS_PER_TXN - 1];
+
+/// Submits transactions declaring max gas amounts around the per-transaction bound, and checks
+/// that the ones above it are rejected at admission while the ones within it execute.
+pub struct GasLimitTest {
+    /// Declared max gas amounts above the bound, which must never commit
+    pub over_limit_gas_amounts: &'static [u64],
+    /// Declared max gas amounts at or below the bound, which must execute
+    pub within_limit_gas_amounts: &'static [u64],
+}
+
+impl Test for GasLimitTest {
+    fn name(&self) -> &'static str {
+        "gas-limit-test"
+    }
+}
+
+impl NetworkTest for GasLimitTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = ctx.handle();
+        let client = ctx.swarm().validators().next().unwrap().rest_client();
+        let mut public_info = ctx.swarm().chain_info().into_aptos_public_info();
+
+        runtime.block_on(async {
+            // Fund the sender well enough that balance is never the reason for a rejection
+            let largest_valid = self.within_limit_gas_amounts.iter().max().copied();
+            let funding = largest_valid.unwrap_or(0) * GAS_UNIT_PRICE * 10 + 1_000_000;
+            let mut sender = public_info.create_and_fund_user_account(funding).await?;
+            let receiver = public_info.random_account();
+            public_info
+                .create_user_account(receiver.public_key())
+                .await?;
+            let transaction_factory = public_info
+                .transaction_factory()
+                .with_gas_unit_price(GAS_UNIT_PRICE);
+
+            let mut rejected_txns: Vec<SignedTransaction> = vec![];
+            for max_gas_amount in self.over_limit_gas_amounts {
+                let txn = sender.sign_with_transaction_builder(
+                    transaction_factory
+                        .clone()
+                        .with_max_gas_amount(*max_gas_amount)
+                        .payload(aptos_stdlib::aptos_coin_transfer(receiver.address(), 1)),
+                );
+                match client.submit(&txn).await {
+                    Ok(_) => bail!(
+                        "Transaction with max gas amount {} above the bound {} was accepted",
+                        max_gas_amount,
+                        MAX_GAS_UNITS_PER_TXN
+                    ),
+                    Err(RestError::Api(error)) => ensure!(
+                        error.error.vm_error_code
+                            == Some(StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND as u64),
+                        "Transaction with max gas amount {} was rejected for the wrong reason: {}",
+                        max_gas_amount,
+                        error
+                    ),
+                    Err(error) => return Err(error.into()),
+                }
+                info!(
+                    "Transaction with max gas amount {} rejected as expected",
+                    max_gas_amount
+                );
+                // The rejected transaction never made it to mempool, so reuse its sequence number
+                *sender.sequence_number_mut() -= 1;
+                rejected_txns.push(txn);
+            }
+
+            for max_gas_amount in self.within_limit_gas_amounts {
+                let txn = sender.sign_with_transaction_builder(
+                    transaction_factory
+                        .clone()
+                        .with_max_gas_amount(*max_gas_amount)
+                        .payload(aptos_stdlib::aptos_coin_transfer(receiver.address(), 1)),
+                );
+                client.submit_and_wait(&txn).await?;
+                info!(
+                    "Transaction with max gas amount {} executed as expected",
+                    max_gas_amount
+                );
+            }
+
+            for txn in rejected_txns {
+                let hash = txn.committed_hash();
+                ensure!(
+                    client.get_transaction_by_hash(hash).await.is_err(),
+                    "Transaction {} above the gas bound was committed",
+                    hash
+                );
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    swarm_utils::{get_block_events, get_voting_powers, latest_block_event_sequence_number},
+    LoadDestination, NetworkLoadTest,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::move_types::account_address::AccountAddress;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Every validator must propose at least this fraction of its fair share of blocks
+const MIN_FAIR_SHARE: f64 = 0.5;
+/// No validator may propose more than this multiple of its fair share of blocks
+const MAX_FAIR_SHARE: f64 = 1.5;
+/// Blocks needed per validator for the distribution to be meaningful
+const MIN_BLOCKS_PER_VALIDATOR: usize = 10;
+
+/// Runs validators with exactly equal stake under load, and checks that the weighted leader
+/// election breaks the ties deterministically and fairly.
+///
+/// The proposer of every block committed during the test is read from the `NewBlockEvent`s.  All
+/// validators must report the same proposers, and every validator must win its fair share of
+/// rounds within a tolerance, so that no validator is starved or favored by the tie breaking.
+pub struct LeaderElectionTieTest {
+    /// How long to run under load, in seconds
+    pub run_length_secs: u64,
+}
+
+impl Test for LeaderElectionTieTest {
+    fn name(&self) -> &'static str {
+        "leader-election-tie-test"
+    }
+}
+
+impl NetworkLoadTest for LeaderElectionTieTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        // The test is only meaningful if every validator has the same voting power
+        let client = ctx.swarm().validators().next().unwrap().rest_client();
+        let voting_powers = ctx.handle().block_on(get_voting_powers(&client))?;
+        let mut powers = voting_powers.values();
+        let first = powers
+            .next()
+            .ok_or_else(|| anyhow!("Validator set is empty"))?;
+        ensure!(
+            powers.all(|power| power == first),
+            "Validators must have equal stake, but voting powers are {:?}",
+            voting_powers
+        );
+        info!(
+            "{} validators with equal voting power {}",
+            voting_powers.len(),
+            first
+        );
+        Ok(LoadDestination::AllNodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let clients = swarm
+            .validators()
+            .map(|validator| validator.rest_client())
+            .collect::<Vec<_>>();
+        let start = runtime.block_on(latest_block_event_sequence_number(&clients[0]))? + 1;
+
+        std::thread::sleep(duration);
+
+        let end = runtime.block_on(latest_block_event_sequence_number(&clients[0]))? + 1;
+        let voting_powers = runtime.block_on(get_voting_powers(&clients[0]))?;
+        let proposers = runtime.block_on(get_proposers(&clients[0], start, end))?;
+
+        // Leader election must be deterministic, so every validator agrees on the proposers
+        for client in &clients[1..] {
+            ensure!(
+                runtime.block_on(get_proposers(client, start, end))? == proposers,
+                "Validators disagree on the proposers of blocks {}..{}",
+                start,
+                end
+            );
+        }
+
+        let mut proposals: BTreeMap<AccountAddress, usize> =
+            voting_powers.keys().map(|address| (*address, 0)).collect();
+        for proposer in &proposers {
+            match proposals.get_mut(proposer) {
+                Some(count) => *count += 1,
+                None => bail!("Block proposed by {}, which is not a validator", proposer),
+            }
+        }
+        info!("Proposals per validator: {:?}", proposals);
+
+        let num_validators = proposals.len();
+        ensure!(
+            proposers.len() >= num_validators * MIN_BLOCKS_PER_VALIDATOR,
+            "Only {} blocks were proposed, at least {} are needed to judge fairness",
+            proposers.len(),
+            num_validators * MIN_BLOCKS_PER_VALIDATOR
+        );
+        let fair_share = proposers.len() as f64 / num_validators as f64;
+        for (validator, count) in proposals {
+            ensure!(
+                count as f64 >= fair_share * MIN_FAIR_SHARE,
+                "Validator {} proposed {} blocks, less than {:.0}% of its fair share {:.1}",
+                validator,
+                count,
+                MIN_FAIR_SHARE * 100.0,
+                fair_share
+            );
+            ensure!(
+                count as f64 <= fair_share * MAX_FAIR_SHARE,
+                "Validator {} proposed {} blocks, more than {:.0}% of its fair share {:.1}",
+                validator,
+                count,
+                MAX_FAIR_SHARE * 100.0,
+                fair_share
+            );
+        }
+        Ok(())
+    }
+}
+
+impl NetworkTest for LeaderElectionTieTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ctx.global_duration = Duration::from_secs(self.run_length_secs);
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+/// Reads the proposers of the blocks with event sequence numbers in `start..end`, skipping
+/// NIL blocks, which have no proposer
+async fn get_proposers(client: &RestClient, start: u64, end: u64) -> Result<Vec<AccountAddress>> {
+    Ok(get_block_events(client, start, end)
+        .await?
+        .into_iter()
+        .map(|event| event.event.proposer())
+        .filter(|proposer| *proposer != AccountAddress::ZERO)
+        .collect())
+}
@@ -1,12 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod api_overload_test;
+pub mod backward_time_jump_test;
 pub mod compatibility_test;
+pub mod connection_limit_test;
 pub mod consensus_reliability_tests;
+pub mod consensus_stall_test;
 pub mod continuous_progress_test;
+pub mod coordinated_upgrade_test;
 pub mod forge_setup_test;
 pub mod fullnode_reboot_stress_test;
+pub mod gas_limit_test;
 pub mod gas_price_test;
+pub mod leader_election_tie_test;
 pub mod load_vs_perf_benchmark;
 pub mod network_bandwidth_test;
 pub mod network_latency_test;
@@ -16,23 +23,31 @@ pub mod partial_nodes_down_test;
 pub mod performance_test;
 pub mod performance_with_fullnode_test;
 pub mod reconfiguration_test;
+pub mod rolling_restart_test;
+pub mod split_brain_merge_test;
+pub mod stake_concentration_test;
 pub mod state_sync_performance;
+pub mod validator_partition_test;
 pub mod validator_reboot_stress_test;
 
+mod swarm_utils;
+
 use anyhow::{anyhow, ensure};
 use aptos_logger::info;
 use aptos_sdk::{transaction_builder::TransactionFactory, types::PeerId};
 use forge::{
-    EmitJobRequest, NetworkContext, NetworkTest, NodeExt, Result, Swarm, SwarmExt, Test,
+    find_ledger_forks, EmitJobRequest, NetworkContext, NetworkTest, Result, Swarm, SwarmExt, Test,
     TxnEmitter, TxnStats, Version,
 };
-use futures::future::join_all;
+use futures::future::{join_all, try_join_all};
 use rand::{rngs::StdRng, SeedableRng};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::runtime::Builder;
 
 const WARMUP_DURATION_FRACTION: f32 = 0.05;
 const COOLDOWN_DURATION_FRACTION: f32 = 0.05;
+/// How long nodes are given to catch up to the version their ledgers are compared at
+const LEDGER_CATCHUP_TIMEOUT: Duration = Duration::from_secs(60);
 
 async fn batch_update(
     ctx: &mut NetworkContext<'_>,
@@ -45,15 +60,9 @@ async fn batch_update(
 
     ctx.swarm().health_check().await?;
     let deadline = Instant::now() + Duration::from_secs(60);
-    for validator in validators_to_update {
-        ctx.swarm()
-            .validator_mut(*validator)
-            .unwrap()
-            .wait_until_healthy(deadline)
-            .await?;
-    }
-
-    Ok(())
+    ctx.swarm()
+        .wait_until_all_healthy(validators_to_update, deadline)
+        .await
 }
 
 pub fn create_emitter_and_request(
@@ -104,11 +113,70 @@ pub fn generate_traffic(
     Ok(stats)
 }
 
+/// Fails if the nodes of the swarm don't all have the same committed ledger at `version`
+///
+/// Every validator and fullnode, including any twins, must catch up to the version.  Their blocks
+/// at the version, and their accumulator root hashes covering the ledger up to it, must all match,
+/// or the nodes on each branch of the fork are reported.
+pub fn assert_consistent_ledger(ctx: &mut NetworkContext, version: u64) -> Result<()> {
+    let runtime = ctx.handle();
+    let snapshots = runtime.block_on(
+        ctx.swarm()
+            .get_ledger_snapshots(version, LEDGER_CATCHUP_TIMEOUT),
+    )?;
+    let forks = find_ledger_forks(&snapshots);
+    ensure!(
+        forks.is_empty(),
+        "Ledger forked at version {}, between nodes {:?}",
+        version,
+        forks
+    );
+    info!(
+        "Ledger is consistent across {} nodes at version {}",
+        snapshots.len(),
+        version
+    );
+    Ok(())
+}
+
+/// The latest version every node of the swarm has committed
+fn lowest_committed_version(ctx: &mut NetworkContext) -> Result<u64> {
+    let runtime = ctx.handle();
+    let clients = ctx.swarm().get_all_nodes_clients_with_names();
+    let states = runtime.block_on(try_join_all(
+        clients
+            .iter()
+            .map(|(_, client)| client.get_ledger_information()),
+    ))?;
+    states
+        .into_iter()
+        .map(|state| state.into_inner().version)
+        .min()
+        .ok_or_else(|| anyhow!("Swarm has no nodes to compare the ledgers of"))
+}
+
 pub enum LoadDestination {
     AllNodes,
     AllValidators,
     AllFullnodes,
     Peers(Vec<PeerId>),
+    /// Only the given validators, each of which must be in the swarm
+    SpecificValidators(Vec<PeerId>),
+    /// Only the given fullnodes, each of which must be in the swarm
+    SpecificFullnodes(Vec<PeerId>),
+}
+
+/// Checks each of `peers` is one of the swarm's nodes of a role, to send load to only them
+fn specific_peers(peers: Vec<PeerId>, swarm_peers: &[PeerId], role: &str) -> Result<Vec<PeerId>> {
+    for peer in &peers {
+        ensure!(
+            swarm_peers.contains(peer),
+            "{} {} to send load to is not in the swarm",
+            role,
+            peer
+        );
+    }
+    Ok(peers)
 }
 
 pub trait NetworkLoadTest: Test {
@@ -118,7 +186,7 @@ pub trait NetworkLoadTest: Test {
     // Load is started before this funciton is called, and stops after this function returns.
     // Expected duration is passed into this function, expecting this function to take that much
     // time to finish. How long this function takes will dictate how long the actual test lasts.
-    fn test(&self, _swarm: &mut dyn Swarm, duration: Duration) -> Result<()> {
+    fn test(&self, _ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
         std::thread::sleep(duration);
         Ok(())
     }
@@ -126,6 +194,15 @@ pub trait NetworkLoadTest: Test {
     fn finish(&self, _swarm: &mut dyn Swarm) -> Result<()> {
         Ok(())
     }
+
+    /// Checks the nodes agree on the ledger once the test has finished, failing on any fork
+    ///
+    /// By default the ledgers of every node are compared at the latest version they've all
+    /// committed.  Tests which leave nodes unreachable once finished must override this.
+    fn verify_ledger(&self, ctx: &mut NetworkContext) -> Result<()> {
+        let version = lowest_committed_version(ctx)?;
+        assert_consistent_ledger(ctx, version)
+    }
 }
 
 impl NetworkTest for dyn NetworkLoadTest {
@@ -154,6 +231,7 @@ impl NetworkTest for dyn NetworkLoadTest {
             .as_secs();
 
         self.finish(ctx.swarm())?;
+        self.verify_ledger(ctx)?;
 
         ctx.check_for_success(
             &txn_stat,
@@ -193,6 +271,12 @@ impl dyn NetworkLoadTest {
             LoadDestination::AllValidators => all_validators,
             LoadDestination::AllFullnodes => all_fullnodes,
             LoadDestination::Peers(peers) => peers,
+            LoadDestination::SpecificValidators(validators) => {
+                specific_peers(validators, &all_validators, "Validator")?
+            }
+            LoadDestination::SpecificFullnodes(fullnodes) => {
+                specific_peers(fullnodes, &all_fullnodes, "Fullnode")?
+            }
         };
 
         // Generate some traffic
@@ -243,7 +327,7 @@ impl dyn NetworkLoadTest {
         job.start_next_phase();
 
         let test_start = Instant::now();
-        self.test(ctx.swarm(), test_duration)?;
+        self.test(ctx, test_duration)?;
         let actual_test_duration = test_start.elapsed();
         info!(
             "{}s test finished after {}s",
@@ -6,7 +6,6 @@ use aptos_logger::info;
 use forge::{EmitJobMode, NetworkContext, NetworkTest, Result, Test, TxnStats};
 use rand::SeedableRng;
 use std::time::Duration;
-use tokio::runtime::Runtime;
 
 pub struct SingleRunStats {
     tps: usize,
@@ -57,7 +56,7 @@ impl LoadVsPerfBenchmark {
 
 impl NetworkTest for LoadVsPerfBenchmark {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
-        let runtime = Runtime::new().unwrap();
+        let runtime = ctx.handle();
         let individual_with_buffer = ctx
             .global_duration
             .checked_div(self.tps.len() as u32)
@@ -4,7 +4,7 @@
 use crate::generate_traffic;
 use forge::{NetworkContext, NetworkTest, Result, Test};
 use std::thread;
-use tokio::{runtime::Runtime, time::Duration};
+use tokio::time::Duration;
 
 pub struct PartialNodesDown;
 
@@ -16,7 +16,7 @@ impl Test for PartialNodesDown {
 
 impl NetworkTest for PartialNodesDown {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
-        let runtime = Runtime::new()?;
+        let runtime = ctx.handle();
         let duration = Duration::from_secs(120);
         let all_validators = ctx
             .swarm()
@@ -0,0 +1,107 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{LoadDestination, NetworkLoadTest};
+use anyhow::{anyhow, ensure};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test};
+use std::time::{Duration, Instant};
+
+/// Restarts the validators one at a time while load runs, and checks that the rest of the network
+/// keeps committing while each is down, and again once it's back.
+///
+/// Only one validator is ever down, so the network should never lose its quorum.  Unlike the
+/// reboot stress test, which restarts random validators straight away, each validator stays down
+/// for a while and must be healthy before the next is stopped, so the graceful restart of every
+/// validator is exercised.
+pub struct RollingRestartTest {
+    /// How long each validator stays down before it's started again, in seconds
+    pub dwell_secs: u64,
+    /// How long each validator has to become healthy once it's started again, in seconds
+    pub node_timeout_secs: u64,
+}
+
+impl Test for RollingRestartTest {
+    fn name(&self) -> &'static str {
+        "rolling-restart-test"
+    }
+}
+
+impl NetworkLoadTest for RollingRestartTest {
+    fn setup(&self, _ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        // Validators are taken down, so the load goes through the fullnodes
+        Ok(LoadDestination::AllFullnodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let clients = swarm.get_validator_clients_with_names();
+        let validators = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+        let mut version = runtime.block_on(committed_version(&clients, None))?;
+
+        // Every validator is restarted at least once, and then again until the test is over
+        let mut restarts = 0;
+        while restarts < validators.len() || start.elapsed() < duration {
+            let validator = swarm
+                .validator_mut(validators[restarts % validators.len()])
+                .unwrap();
+            let name = validator.name().to_string();
+            info!("Restarting {} for {}s", name, self.dwell_secs);
+            runtime.block_on(validator.stop())?;
+            std::thread::sleep(Duration::from_secs(self.dwell_secs));
+            version = runtime.block_on(check_progress(&clients, Some(&name), version, &name))?;
+
+            runtime.block_on(validator.start())?;
+            let deadline = Instant::now() + Duration::from_secs(self.node_timeout_secs);
+            runtime.block_on(validator.wait_until_healthy(deadline))?;
+            version = runtime.block_on(check_progress(&clients, None, version, &name))?;
+            info!("Restarted {}, the network is at version {}", name, version);
+            restarts += 1;
+        }
+        swarm.fork_check()
+    }
+}
+
+impl NetworkTest for RollingRestartTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+/// Checks the network has committed past `version` since `restarted` was stopped, returning
+/// the version it's now at
+async fn check_progress(
+    clients: &[(String, RestClient)],
+    excluded: Option<&str>,
+    version: u64,
+    restarted: &str,
+) -> Result<u64> {
+    let next = committed_version(clients, excluded).await?;
+    ensure!(
+        next > version,
+        "Network stopped committing at version {} while {} was restarted",
+        version,
+        restarted
+    );
+    Ok(next)
+}
+
+/// The highest version committed by any of the validators, other than `excluded`
+async fn committed_version(
+    clients: &[(String, RestClient)],
+    excluded: Option<&str>,
+) -> Result<u64> {
+    let mut version = None;
+    for (name, client) in clients {
+        if Some(name.as_str()) == excluded {
+            continue;
+        }
+        if let Ok(state) = client.get_ledger_information().await {
+            version = version.max(Some(state.into_inner().version));
+        }
+    }
+    version.ok_or_else(|| anyhow!("No validator returned its ledger information"))
+}
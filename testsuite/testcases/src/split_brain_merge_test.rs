@@ -0,0 +1,122 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    swarm_utils::{
+        committed_versions, finish_under_load, get_voting_powers, has_quorum, voting_power_of,
+    },
+    LoadDestination, NetworkLoadTest,
+};
+use anyhow::ensure;
+use aptos_logger::info;
+use forge::{
+    NetworkContext, NetworkTest, NodeExt, Result, SwarmChaos, SwarmExt, SwarmSplitBrain, Test,
+};
+use std::time::{Duration, Instant};
+
+/// Time for blocks certified just before the split to be committed, before progress is checked
+const SPLIT_SETTLE_TIME: Duration = Duration::from_secs(10);
+/// Time both sides have to converge once the split is healed
+const MERGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Splits the validators into two sides which both hold less than the quorum of voting power,
+/// and checks that neither side commits anything while split, then heals the split and checks
+/// that both sides converge on the same chain.
+///
+/// Unlike the partition test, where a side keeps the quorum and makes progress, no progress is
+/// safe here, so any block committed during the split would be unsafe.
+pub struct SplitBrainMergeTest {
+    /// Number of validators on the first side of the split, the others forming the second side
+    pub partition_size: usize,
+    /// How long to hold the split, in seconds
+    pub partition_duration_secs: u64,
+}
+
+impl Test for SplitBrainMergeTest {
+    fn name(&self) -> &'static str {
+        "split-brain-merge-test"
+    }
+}
+
+impl NetworkLoadTest for SplitBrainMergeTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        // A quorum needs more than two thirds of the voting power
+        let client = ctx.swarm().validators().next().unwrap().rest_client();
+        let voting_powers = ctx.handle().block_on(get_voting_powers(&client))?;
+        let total: u64 = voting_powers.values().sum();
+        let first_side_validators = ctx
+            .swarm()
+            .validators()
+            .take(self.partition_size)
+            .map(|validator| validator.peer_id())
+            .collect::<Vec<_>>();
+        let first_side = voting_power_of(&voting_powers, &first_side_validators)?;
+        for side in [first_side, total - first_side] {
+            ensure!(
+                !has_quorum(side, total),
+                "Sides of the split must both be short of the quorum, but one has {} of {} \
+                 voting power, with partition_size {}",
+                side,
+                total,
+                self.partition_size
+            );
+        }
+        info!(
+            "Splitting validators into sides with {} and {} of {} voting power",
+            first_side,
+            total - first_side,
+            total
+        );
+        Ok(LoadDestination::AllNodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let clients = swarm.get_validator_clients_with_names();
+        let chaos = SwarmChaos::SplitBrain(SwarmSplitBrain {
+            validator_names: swarm
+                .validators()
+                .take(self.partition_size)
+                .map(|validator| validator.name().to_string())
+                .collect(),
+        });
+
+        info!(
+            "Splitting the validators for {}s",
+            self.partition_duration_secs
+        );
+        swarm.inject_chaos(chaos.clone())?;
+        let split_result = runtime.block_on(async {
+            tokio::time::sleep(SPLIT_SETTLE_TIME).await;
+            let versions = committed_versions(&clients).await?;
+
+            tokio::time::sleep(Duration::from_secs(self.partition_duration_secs)).await;
+            for ((name, client), version) in clients.iter().zip(versions) {
+                let after = client.get_ledger_information().await?.into_inner().version;
+                ensure!(
+                    after == version,
+                    "{} committed from version {} to {} while no side had a quorum",
+                    name,
+                    version,
+                    after
+                );
+            }
+            Ok::<(), anyhow::Error>(())
+        });
+        // The split must be healed even if the checks failed
+        swarm.remove_chaos(chaos)?;
+        split_result?;
+        info!("Healed the split, no validator made progress");
+
+        runtime.block_on(swarm.wait_for_all_nodes_to_catchup(MERGE_TIMEOUT))?;
+        finish_under_load(&runtime, swarm, start, duration)
+    }
+}
+
+impl NetworkTest for SplitBrainMergeTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    swarm_utils::{finish_under_load, get_voting_powers},
+    LoadDestination, NetworkLoadTest,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_config::config::{IdentityBlob, InitialSafetyRulesConfig};
+use aptos_logger::info;
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::{
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{account_config::CORE_CODE_ADDRESS, LocalAccount},
+};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test, Validator};
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Most epochs the stake may be moved over, each one limited by the voting power increase limit
+const MAX_CONCENTRATION_EPOCHS: usize = 10;
+/// Coins minted to the owner on top of the stake, to pay for its transactions
+const GAS_BUDGET: u64 = 1_000_000;
+
+/// Moves a large fraction of the total stake to a single validator under load, and checks that
+/// the staking limits are enforced while consensus stays safe and live.
+///
+/// Stake is added to the first validator, over as many epochs as the per epoch voting power
+/// increase limit requires, until it holds `target_concentration` of the voting power or hits the
+/// per validator maximum stake.  Stake increases beyond either limit must be rejected.
+pub struct StakeConcentrationTest {
+    /// Fraction of the total voting power the validator should end up with, within (0, 1)
+    pub target_concentration: f64,
+}
+
+impl Test for StakeConcentrationTest {
+    fn name(&self) -> &'static str {
+        "stake-concentration-test"
+    }
+}
+
+impl NetworkLoadTest for StakeConcentrationTest {
+    fn setup(&self, _ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        ensure!(
+            self.target_concentration > 0.0 && self.target_concentration < 1.0,
+            "target_concentration must be within (0, 1), but is {}",
+            self.target_concentration
+        );
+        Ok(LoadDestination::AllNodes)
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let client = swarm.validators().next().unwrap().rest_client();
+        let mut owner =
+            runtime.block_on(owner_account(swarm.validators().next().unwrap(), &client))?;
+        let pool_address = owner.address();
+        let factory = TransactionFactory::new(swarm.chain_info().chain_id).with_gas_unit_price(1);
+
+        let start_powers = runtime.block_on(get_voting_powers(&client))?;
+        ensure!(
+            start_powers.contains_key(&pool_address),
+            "Validator {} is not in the validator set",
+            pool_address
+        );
+        let (maximum_stake, increase_limit) = runtime.block_on(get_staking_limits(&client))?;
+        info!(
+            "Concentrating {:.0}% of the stake in {}, maximum stake {}, increase limit {}%",
+            self.target_concentration * 100.0,
+            pool_address,
+            maximum_stake,
+            increase_limit
+        );
+
+        // A single increase beyond the per epoch limit must be rejected
+        let allowance = |total: u64| (total as u128 * increase_limit as u128 / 100) as u64;
+        let excess = allowance(start_powers.values().sum()) + 1;
+        runtime.block_on(
+            swarm
+                .aptos_public_info()
+                .mint(pool_address, excess + GAS_BUDGET),
+        )?;
+        runtime.block_on(expect_rejected(
+            &client,
+            &factory,
+            &mut owner,
+            excess,
+            "EVOTING_POWER_INCREASE_EXCEEDS_LIMIT",
+        ))?;
+
+        // Move the stake over, as fast as the increase limit allows
+        let mut reached = false;
+        for _ in 0..MAX_CONCENTRATION_EPOCHS {
+            let powers = runtime.block_on(get_voting_powers(&client))?;
+            let power = powers[&pool_address];
+            let total: u64 = powers.values().sum();
+            let target = self.target_concentration;
+            if power as f64 >= target * total as f64 {
+                reached = true;
+                break;
+            }
+
+            let needed = ((target * total as f64 - power as f64) / (1.0 - target)).ceil() as u64;
+            let amount = needed
+                .min(allowance(total))
+                .min(maximum_stake.saturating_sub(power));
+            if amount == 0 {
+                break;
+            }
+            info!(
+                "Adding {} stake to {} with power {} of {}",
+                amount, pool_address, power, total
+            );
+            runtime.block_on(async {
+                swarm
+                    .aptos_public_info()
+                    .mint(pool_address, amount + GAS_BUDGET)
+                    .await?;
+                client
+                    .submit_and_wait(&owner.sign_with_transaction_builder(
+                        factory.payload(aptos_stdlib::stake_add_stake(amount)),
+                    ))
+                    .await?;
+                swarm.aptos_public_info().reconfig().await;
+                Ok::<(), anyhow::Error>(())
+            })?;
+
+            let new_powers = runtime.block_on(get_voting_powers(&client))?;
+            ensure!(
+                new_powers.keys().eq(start_powers.keys()),
+                "Validator set changed from {:?} to {:?} while concentrating stake",
+                start_powers.keys(),
+                new_powers.keys()
+            );
+            ensure!(
+                new_powers[&pool_address] >= power + amount,
+                "Voting power of {} is {}, expected at least {} after adding {}",
+                pool_address,
+                new_powers[&pool_address],
+                power + amount,
+                amount
+            );
+        }
+
+        // The per validator cap holds, whether or not it stopped the concentration
+        let powers = runtime.block_on(get_voting_powers(&client))?;
+        for (validator, power) in &powers {
+            ensure!(
+                *power <= maximum_stake,
+                "Validator {} has voting power {}, above the maximum stake {}",
+                validator,
+                power,
+                maximum_stake
+            );
+        }
+        if !reached {
+            ensure!(
+                powers[&pool_address] == maximum_stake,
+                "Validator {} has {} of {} voting power, short of the target {} after {} epochs",
+                pool_address,
+                powers[&pool_address],
+                powers.values().sum::<u64>(),
+                self.target_concentration,
+                MAX_CONCENTRATION_EPOCHS
+            );
+            runtime.block_on(swarm.aptos_public_info().mint(pool_address, 1 + GAS_BUDGET))?;
+            runtime.block_on(expect_rejected(
+                &client,
+                &factory,
+                &mut owner,
+                1,
+                "ESTAKE_EXCEEDS_MAX",
+            ))?;
+        }
+        info!("Final voting powers: {:?}", powers);
+
+        // Consensus must stay safe and live with the concentrated stake
+        finish_under_load(&runtime, swarm, start, duration)
+    }
+}
+
+impl NetworkTest for StakeConcentrationTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
+
+/// Loads the owner account of a validator from its identity
+async fn owner_account(validator: &dyn Validator, client: &RestClient) -> Result<LocalAccount> {
+    let identity = match &validator
+        .config()
+        .consensus
+        .safety_rules
+        .initial_safety_rules_config
+    {
+        InitialSafetyRulesConfig::FromFile {
+            identity_blob_path, ..
+        } => IdentityBlob::from_file(identity_blob_path)?,
+        InitialSafetyRulesConfig::None => {
+            bail!("Validator {} has no identity", validator.peer_id())
+        }
+    };
+    let address = identity
+        .account_address
+        .ok_or_else(|| anyhow!("Validator {} has no account address", validator.peer_id()))?;
+    let key = identity
+        .account_private_key
+        .ok_or_else(|| anyhow!("Validator {} has no account key", validator.peer_id()))?;
+    let sequence_number = client
+        .get_account(address)
+        .await?
+        .into_inner()
+        .sequence_number;
+    Ok(LocalAccount::new(address, key, sequence_number))
+}
+
+/// Reads the maximum stake and the voting power increase limit, in percent
+async fn get_staking_limits(client: &RestClient) -> Result<(u64, u64)> {
+    let staking_config = client
+        .get_account_resource(CORE_CODE_ADDRESS, "0x1::staking_config::StakingConfig")
+        .await?
+        .into_inner()
+        .ok_or_else(|| anyhow!("StakingConfig resource not found"))?;
+    let field = |name: &str| -> Result<u64> {
+        let value = staking_config.data[name]
+            .as_str()
+            .ok_or_else(|| anyhow!("StakingConfig is missing {}", name))?;
+        Ok(u64::from_str(value)?)
+    };
+    Ok((
+        field("maximum_stake")?,
+        field("voting_power_increase_limit")?,
+    ))
+}
+
+/// Adds stake, and fails unless the transaction aborts with `abort_code`
+async fn expect_rejected(
+    client: &RestClient,
+    factory: &TransactionFactory,
+    owner: &mut LocalAccount,
+    amount: u64,
+    abort_code: &str,
+) -> Result<()> {
+    let txn =
+        owner.sign_with_transaction_builder(factory.payload(aptos_stdlib::stake_add_stake(amount)));
+    match client.submit_and_wait(&txn).await {
+        Ok(_) => bail!(
+            "Adding {} stake to {} should be rejected",
+            amount,
+            owner.address()
+        ),
+        Err(error) if error.to_string().contains(abort_code) => Ok(()),
+        Err(error) => bail!(
+            "Adding {} stake to {} failed with {}, expected {}",
+            amount,
+            owner.address(),
+            error,
+            abort_code
+        ),
+    }
+}
@@ -6,7 +6,7 @@ use anyhow::bail;
 use aptos_logger::info;
 use forge::{get_highest_synced_version, NetworkContext, NetworkTest, Result, SwarmExt, Test};
 use std::time::Instant;
-use tokio::{runtime::Runtime, time::Duration};
+use tokio::time::Duration;
 
 const MAX_NODE_LAG_SECS: u64 = 10; // Max amount of lag (in seconds) that nodes should adhere to
 
@@ -59,7 +59,7 @@ impl NetworkTest for StateSyncFullnodePerformance {
         // Wait for all nodes to synchronize. We time bound this to ensure
         // fullnodes don't fall too far behind the validators.
         info!("Waiting for the validators and fullnodes to be synchronized.");
-        let runtime = Runtime::new().unwrap();
+        let runtime = ctx.handle();
         runtime.block_on(async {
             ctx.swarm()
                 .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_NODE_LAG_SECS))
@@ -133,7 +133,7 @@ impl NetworkTest for StateSyncValidatorPerformance {
 
         // Wait for all nodes to synchronize and stabilize.
         info!("Waiting for the validators to be synchronized.");
-        let runtime = Runtime::new().unwrap();
+        let runtime = ctx.handle();
         runtime.block_on(async {
             ctx.swarm()
                 .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_NODE_LAG_SECS))
@@ -170,7 +170,7 @@ fn ensure_state_sync_throughput(
     test_name: &str,
 ) -> Result<()> {
     // Get the highest synced version for the chain
-    let runtime = Runtime::new().unwrap();
+    let runtime = ctx.handle();
     let highest_synced_version = runtime.block_on(async {
         get_highest_synced_version(&ctx.swarm().get_all_nodes_clients_with_names())
             .await
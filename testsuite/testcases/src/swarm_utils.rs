@@ -0,0 +1,165 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the tests which stop validators, split the swarm or read the chain's
+//! validator set and block events while under load.
+
+use anyhow::{anyhow, ensure};
+use aptos_rest_client::{Client as RestClient, VersionedNewBlockEvent};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    types::{account_config::CORE_CODE_ADDRESS, PeerId},
+};
+use forge::{NodeExt, Result, Swarm, SwarmExt};
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Handle;
+
+/// Page size used when reading block events
+const BLOCK_EVENTS_PAGE_SIZE: u16 = 100;
+/// Time the network has to make progress in the final liveness check
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Reads the voting power of every active validator from the on-chain validator set
+pub(crate) async fn get_voting_powers(
+    client: &RestClient,
+) -> Result<BTreeMap<AccountAddress, u64>> {
+    let validator_set = client
+        .get_account_resource(CORE_CODE_ADDRESS, "0x1::stake::ValidatorSet")
+        .await?
+        .into_inner()
+        .ok_or_else(|| anyhow!("ValidatorSet resource not found"))?;
+    let validators = validator_set.data["active_validators"]
+        .as_array()
+        .ok_or_else(|| anyhow!("ValidatorSet has no active validators"))?;
+
+    let mut voting_powers = BTreeMap::new();
+    for validator in validators {
+        let field = |name: &str| {
+            validator[name]
+                .as_str()
+                .ok_or_else(|| anyhow!("Active validator is missing {}: {}", name, validator))
+        };
+        voting_powers.insert(
+            AccountAddress::from_str(field("addr")?)?,
+            u64::from_str(field("voting_power")?)?,
+        );
+    }
+    Ok(voting_powers)
+}
+
+/// Total voting power of `validators`, which must all be in the validator set
+pub(crate) fn voting_power_of<'a>(
+    voting_powers: &BTreeMap<AccountAddress, u64>,
+    validators: impl IntoIterator<Item = &'a PeerId>,
+) -> Result<u64> {
+    let mut power = 0;
+    for validator in validators {
+        power += voting_powers
+            .get(validator)
+            .ok_or_else(|| anyhow!("Validator {} is not in the validator set", validator))?;
+    }
+    Ok(power)
+}
+
+/// Whether validators with `power` of the `total` voting power form a quorum, which needs more
+/// than two thirds of it
+pub(crate) fn has_quorum(power: u64, total: u64) -> bool {
+    power as u128 * 3 > total as u128 * 2
+}
+
+/// Names and REST clients of the validators in `validators`
+pub(crate) fn validator_clients(
+    swarm: &dyn Swarm,
+    validators: &[PeerId],
+) -> Vec<(String, RestClient)> {
+    swarm
+        .validators()
+        .filter(|v| validators.contains(&v.peer_id()))
+        .map(|v| (v.name().to_string(), v.rest_client()))
+        .collect()
+}
+
+/// Reads the version each of the clients has committed
+pub(crate) async fn committed_versions(clients: &[(String, RestClient)]) -> Result<Vec<u64>> {
+    let mut versions = Vec::new();
+    for (_, client) in clients {
+        versions.push(client.get_ledger_information().await?.into_inner().version);
+    }
+    Ok(versions)
+}
+
+/// Stops the validators
+pub(crate) fn stop_validators(
+    runtime: &Handle,
+    swarm: &mut dyn Swarm,
+    validators: &[PeerId],
+) -> Result<()> {
+    for validator in validators {
+        runtime.block_on(swarm.validator_mut(*validator).unwrap().stop())?;
+    }
+    Ok(())
+}
+
+/// Starts the stopped validators again, and waits until they are healthy by `deadline`
+pub(crate) fn restart_validators(
+    runtime: &Handle,
+    swarm: &mut dyn Swarm,
+    validators: &[PeerId],
+    deadline: Instant,
+) -> Result<()> {
+    for validator in validators {
+        runtime.block_on(swarm.validator_mut(*validator).unwrap().start())?;
+    }
+    runtime.block_on(swarm.wait_until_all_healthy(validators, deadline))
+}
+
+/// Keeps the load running until `duration` has passed since `start`, then checks the network is
+/// still live and no validator forked
+pub(crate) fn finish_under_load(
+    runtime: &Handle,
+    swarm: &mut dyn Swarm,
+    start: Instant,
+    duration: Duration,
+) -> Result<()> {
+    if let Some(remaining) = duration.checked_sub(start.elapsed()) {
+        std::thread::sleep(remaining);
+    }
+    runtime.block_on(swarm.liveness_check(Instant::now() + LIVENESS_TIMEOUT))?;
+    swarm.fork_check()
+}
+
+/// Reads the sequence number of the latest block event
+pub(crate) async fn latest_block_event_sequence_number(client: &RestClient) -> Result<u64> {
+    client
+        .get_new_block_events_bcs(None, Some(1))
+        .await?
+        .into_inner()
+        .last()
+        .map(|event| event.sequence_number)
+        .ok_or_else(|| anyhow!("No block events found"))
+}
+
+/// Reads the block events with sequence numbers in `start..end`
+pub(crate) async fn get_block_events(
+    client: &RestClient,
+    start: u64,
+    end: u64,
+) -> Result<Vec<VersionedNewBlockEvent>> {
+    let mut events: Vec<VersionedNewBlockEvent> = Vec::new();
+    let mut next = start;
+    while next < end {
+        let limit = std::cmp::min(end - next, BLOCK_EVENTS_PAGE_SIZE as u64) as u16;
+        let page = client
+            .get_new_block_events_bcs(Some(next), Some(limit))
+            .await?
+            .into_inner();
+        ensure!(!page.is_empty(), "Block event {} not found", next);
+        next += page.len() as u64;
+        events.extend(page);
+    }
+    Ok(events)
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    swarm_utils::{
+        committed_versions, finish_under_load, get_voting_powers, has_quorum, restart_validators,
+        stop_validators, validator_clients, voting_power_of,
+    },
+    LoadDestination, NetworkLoadTest,
+};
+use anyhow::ensure;
+use aptos_logger::info;
+use aptos_sdk::types::PeerId;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Swarm, SwarmExt, Test};
+use std::time::{Duration, Instant};
+
+/// Time for the majority to settle once the minority is stopped, before progress is checked
+const PARTITION_SETTLE_TIME: Duration = Duration::from_secs(10);
+/// Time for the minority to catch up with the majority once the partition is healed
+const HEAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Partitions a minority of the validators off from the rest by stopping them, and checks the
+/// majority keeps committing without them, then starts them again and checks every validator
+/// converges on a single chain.
+///
+/// Unlike the partition test, which cuts the network between pods with chaos, this only stops
+/// and starts validators, so can run on any swarm.  The majority must hold more than two thirds
+/// of the voting power, which is checked before anything is stopped.
+pub struct ValidatorPartitionTest {
+    /// Number of validators in the minority, which is stopped for the partition
+    pub minority_size: usize,
+    /// How long to hold the partition, in seconds
+    pub partition_duration_secs: u64,
+}
+
+impl ValidatorPartitionTest {
+    /// The validators stopped for the partition, and the validators left running
+    fn groups(&self, swarm: &dyn Swarm) -> (Vec<PeerId>, Vec<PeerId>) {
+        let mut minority = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+        let majority = minority.split_off(self.minority_size.min(minority.len()));
+        (minority, majority)
+    }
+}
+
+impl Test for ValidatorPartitionTest {
+    fn name(&self) -> &'static str {
+        "validator-partition-test"
+    }
+}
+
+impl NetworkLoadTest for ValidatorPartitionTest {
+    fn setup(&self, ctx: &mut NetworkContext) -> Result<LoadDestination> {
+        let (minority, majority) = self.groups(ctx.swarm());
+        ensure!(
+            !minority.is_empty() && !majority.is_empty(),
+            "minority_size must leave validators on both sides of the partition, but is {} of {}",
+            self.minority_size,
+            minority.len() + majority.len()
+        );
+
+        // A quorum needs more than two thirds of the voting power
+        let client = ctx.swarm().validators().next().unwrap().rest_client();
+        let voting_powers = ctx.handle().block_on(get_voting_powers(&client))?;
+        let total: u64 = voting_powers.values().sum();
+        let majority_power = voting_power_of(&voting_powers, &majority)?;
+        ensure!(
+            has_quorum(majority_power, total),
+            "The majority must keep a quorum, but has {} of {} voting power, with minority_size {}",
+            majority_power,
+            total,
+            self.minority_size
+        );
+        info!(
+            "Partitioning {} validators off, leaving {} with {} of {} voting power",
+            minority.len(),
+            majority.len(),
+            majority_power,
+            total
+        );
+
+        // The minority is stopped, so the load goes only to the majority
+        Ok(LoadDestination::SpecificValidators(majority))
+    }
+
+    fn test(&self, ctx: &mut NetworkContext, duration: Duration) -> Result<()> {
+        let start = Instant::now();
+        let runtime = ctx.handle();
+        let swarm = ctx.swarm();
+        let (minority, majority) = self.groups(swarm);
+        let majority_clients = validator_clients(swarm, &majority);
+
+        info!(
+            "Stopping the minority for {}s",
+            self.partition_duration_secs
+        );
+        stop_validators(&runtime, swarm, &minority)?;
+        let partition_result = runtime.block_on(async {
+            tokio::time::sleep(PARTITION_SETTLE_TIME).await;
+            let versions = committed_versions(&majority_clients).await?;
+
+            tokio::time::sleep(Duration::from_secs(self.partition_duration_secs)).await;
+            for ((name, client), version) in majority_clients.iter().zip(versions) {
+                let after = client.get_ledger_information().await?.into_inner().version;
+                ensure!(
+                    after > version,
+                    "{} stalled at version {} while the majority had a quorum",
+                    name,
+                    version
+                );
+            }
+            Ok::<(), anyhow::Error>(())
+        });
+
+        // The partition must be healed even if the checks failed
+        restart_validators(&runtime, swarm, &minority, Instant::now() + HEAL_TIMEOUT)?;
+        partition_result?;
+        info!("Healed the partition, the majority made progress");
+
+        runtime.block_on(swarm.wait_for_all_nodes_to_catchup(HEAL_TIMEOUT))?;
+        finish_under_load(&runtime, swarm, start, duration)
+    }
+}
+
+impl NetworkTest for ValidatorPartitionTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        <dyn NetworkLoadTest>::run(self, ctx)
+    }
+}
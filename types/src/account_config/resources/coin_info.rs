@@ -30,6 +30,14 @@ impl Aggregator {
         Self { handle, key, limit }
     }
 
+    pub fn handle(&self) -> AccountAddress {
+        self.handle
+    }
+
+    pub fn key(&self) -> AccountAddress {
+        self.key
+    }
+
     /// Helper function to return the state key where the actual value is stored.
     pub fn state_key(&self) -> StateKey {
         let key_bytes = self.key.to_vec();
@@ -72,6 +80,10 @@ impl MoveStructType for CoinInfoResource {
 impl MoveResource for CoinInfoResource {}
 
 impl CoinInfoResource {
+    pub fn name(&self) -> Result<String, FromUtf8Error> {
+        String::from_utf8(self.name.clone())
+    }
+
     pub fn symbol(&self) -> Result<String, FromUtf8Error> {
         String::from_utf8(self.symbol.clone())
     }
@@ -5,7 +5,7 @@ use crate::on_chain_config::OnChainConfig;
 use serde::{Deserialize, Serialize};
 
 /// The feature flags define in the Move source. This must stay aligned with the constants there.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 #[allow(non_camel_case_types)]
 pub enum FeatureFlag {
     CODE_DEPENDENCY_CHECK = 1,
@@ -24,6 +24,19 @@ impl OnChainConfig for Features {
     const TYPE_IDENTIFIER: &'static str = "Features";
 }
 
+/// Feature flags scheduled to be enabled at a future epoch, as stored on chain. The i-th entry of
+/// `features` is enabled once the chain reaches the i-th entry of `epochs`.
+#[derive(Default, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ScheduledFeatures {
+    pub epochs: Vec<u64>,
+    pub features: Vec<u64>,
+}
+
+impl OnChainConfig for ScheduledFeatures {
+    const MODULE_IDENTIFIER: &'static str = "features";
+    const TYPE_IDENTIFIER: &'static str = "ScheduledFeatures";
+}
+
 impl Features {
     pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
         let val = flag as u64;
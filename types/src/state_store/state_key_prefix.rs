@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::state_store::state_key::{StateKey, StateKeyTag};
+use crate::state_store::{
+    state_key::{StateKey, StateKeyTag},
+    table::TableHandle,
+};
 use move_deps::move_core_types::account_address::AccountAddress;
 
 // Struct for defining prefix of a state key, which can be used for finding all the values with a
@@ -42,6 +45,12 @@ impl From<AccountAddress> for StateKeyPrefix {
     }
 }
 
+impl From<TableHandle> for StateKeyPrefix {
+    fn from(handle: TableHandle) -> Self {
+        Self::new(StateKeyTag::TableItem, handle.0.to_vec())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -49,6 +58,7 @@ mod tests {
         state_store::{
             state_key::{StateKey, StateKeyTag},
             state_key_prefix::StateKeyPrefix,
+            table::TableHandle,
         },
     };
     use move_deps::move_core_types::account_address::AccountAddress;
@@ -68,5 +78,15 @@ mod tests {
 
         assert!(!account1_key_prefx.is_prefix(&key2).unwrap());
         assert!(!account2_key_prefx.is_prefix(&key1).unwrap());
+
+        // A table's prefix covers its items, but not another table's or an account's
+        let table_key = StateKey::table_item(TableHandle(address1), b"key".to_vec());
+        let table_key_prefix = StateKeyPrefix::from(TableHandle(address1));
+        assert!(table_key_prefix.is_prefix(&table_key).unwrap());
+        assert!(!StateKeyPrefix::from(TableHandle(address2))
+            .is_prefix(&table_key)
+            .unwrap());
+        assert!(!table_key_prefix.is_prefix(&key1).unwrap());
+        assert!(!account1_key_prefx.is_prefix(&table_key).unwrap());
     }
 }